@@ -0,0 +1,64 @@
+use criterion::{black_box, Criterion};
+use log::error;
+use osmgraphing::{configs, helpers, io::network::graph::Parser, network::MetricIdx};
+use std::time::Duration;
+
+fn main() {
+    let mut criterion = Criterion::default()
+        .warm_up_time(Duration::from_secs(5))
+        .measurement_time(Duration::from_secs(30))
+        .configure_from_args();
+    do_benchmark(&mut criterion);
+    criterion.final_summary();
+}
+
+fn do_benchmark(criterion: &mut Criterion) {
+    helpers::init_logging("WARN", &[]).expect("No user-input, so this should be fine.");
+
+    let parsing_cfg =
+        configs::parsing::Config::from_yaml("resources/isle_of_man_2020-03-14/osm.pbf.yaml");
+    let mut graph = match Parser::parse_and_finalize(parsing_cfg) {
+        Ok(graph) => graph,
+        Err(msg) => {
+            error!("{}", msg);
+            return;
+        }
+    };
+    let metric_idx = MetricIdx(0);
+
+    // routing reads all metrics of an edge
+    criterion.bench_function("Reading edge-metrics by index (rows)", |b| {
+        let fwd_edges = graph.fwd_edges();
+        let metrics = graph.metrics();
+        b.iter(|| {
+            fwd_edges
+                .iter()
+                .map(|edge_idx| metrics[edge_idx][*metric_idx])
+                .sum::<f64>()
+        })
+    });
+    criterion.bench_function("Reading edge-metrics by gathering (columns)", |b| {
+        let fwd_edges = graph.fwd_edges();
+        let metrics = graph.metrics();
+        b.iter(|| {
+            fwd_edges
+                .iter()
+                .map(|edge_idx| metrics.get(edge_idx)[*metric_idx])
+                .sum::<f64>()
+        })
+    });
+
+    // analysts and the balancer process whole metrics
+    criterion.bench_function("Reading a metric-column", |b| {
+        b.iter(|| graph.metrics().column(metric_idx).iter().sum::<f64>())
+    });
+    let column = graph.metrics().column(metric_idx).to_vec();
+    criterion.bench_function("Writing a metric-column (including its rows)", |b| {
+        b.iter(|| {
+            graph
+                .metrics_mut()
+                .column_mut(metric_idx)
+                .copy_from_slice(black_box(&column))
+        })
+    });
+}
@@ -0,0 +1,50 @@
+use criterion::{black_box, Criterion};
+use osmgraphing::{configs, helpers, io::network::graph::Parser};
+use std::time::Duration;
+
+fn main() {
+    let mut criterion = Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(5))
+        .measurement_time(Duration::from_secs(60))
+        .configure_from_args();
+    do_benchmark(&mut criterion);
+    criterion.final_summary();
+}
+
+fn do_benchmark(criterion: &mut Criterion) {
+    helpers::init_logging("WARN", &[]).expect("No user-input, so this should be fine.");
+
+    // Decoding pbf-blocks is spread over the configured threads, while the graph is built by
+    // one thread, hence the thread-counts are compared.
+    for &num_threads in &[1, 2, 4, 8] {
+        let mut parsing_cfg =
+            configs::parsing::Config::from_yaml("resources/isle_of_man_2020-03-14/osm.pbf.yaml");
+        parsing_cfg.num_threads = num_threads;
+
+        criterion.bench_function(
+            &format!("Parsing Isle of Man (pbf) with {} threads", num_threads),
+            |b| {
+                b.iter(|| {
+                    Parser::parse_and_finalize(black_box(parsing_cfg.clone()))
+                        .expect("Parsing the isle-of-man should work.")
+                })
+            },
+        );
+    }
+
+    // The fmi-parser streams its files without buffering proto-nodes.
+    for &(name, yaml) in &[
+        ("fmi", "resources/isle_of_man_2020-03-14/fmi.yaml"),
+        ("ch.fmi", "resources/isle_of_man_2020-03-14/ch.fmi.yaml"),
+    ] {
+        let parsing_cfg = configs::parsing::Config::from_yaml(yaml);
+
+        criterion.bench_function(&format!("Parsing Isle of Man ({})", name), |b| {
+            b.iter(|| {
+                Parser::parse_and_finalize(black_box(parsing_cfg.clone()))
+                    .expect("Parsing the isle-of-man should work.")
+            })
+        });
+    }
+}
@@ -35,6 +35,8 @@ fn do_benchmark(criterion: &mut Criterion) {
     ];
 
     // create graph
+    let mut compact_cfg = parsing_cfg.clone();
+    compact_cfg.is_using_compact_indices = true;
     let graph = match Parser::parse_and_finalize(parsing_cfg) {
         Ok(graph) => graph,
         Err(msg) => {
@@ -110,6 +112,59 @@ fn do_benchmark(criterion: &mut Criterion) {
             );
         }
     }
+
+    // benchmarking the per-query reset of a reused Dijkstra, which dominates short routes on
+    // big graphs, if every node's routing-data is reset
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: Dijkstra, metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    for (prefix, suffix, routes) in labelled_routes.iter() {
+        let mut dijkstra = Dijkstra::new();
+        criterion.bench_function(
+            &format!("{}Shortest Dijkstra (bidir, 1D, reused){}", prefix, suffix),
+            |b| {
+                b.iter(|| {
+                    reused_dijkstra(
+                        black_box(&mut dijkstra),
+                        black_box(&graph),
+                        black_box(&routes),
+                        black_box(&routing_cfg),
+                    )
+                })
+            },
+        );
+    }
+
+    // benchmarking compact indices (see `configs::parsing::Config::is_using_compact_indices`)
+    let compact_graph = match Parser::parse_and_finalize(compact_cfg) {
+        Ok(graph) => graph,
+        Err(msg) => {
+            error!("{}", msg);
+            return;
+        }
+    };
+    let routing_str = "routing: { algorithm: Dijkstra, metrics: [{ id: 'kilometers' }] }";
+    for &(graph, label) in &[(&graph, "wide"), (&compact_graph, "compact")] {
+        let routing_cfg = configs::routing::Config::from_str(routing_str, graph.cfg());
+        for (prefix, suffix, routes) in labelled_routes.iter() {
+            criterion.bench_function(
+                &format!(
+                    "{}Shortest Dijkstra (bidir, 1D, {} indices){}",
+                    prefix, label, suffix
+                ),
+                |b| {
+                    b.iter(|| {
+                        bidir_shortest_dijkstra(
+                            black_box(graph),
+                            black_box(&routes),
+                            black_box(&routing_cfg),
+                        )
+                    })
+                },
+            );
+        }
+    }
 }
 
 fn bidir_shortest_dijkstra(
@@ -125,6 +180,26 @@ fn bidir_shortest_dijkstra(
             dst_idx,
             graph,
             routing_cfg: cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        });
+    }
+}
+
+fn reused_dijkstra(
+    dijkstra: &mut Dijkstra,
+    graph: &Graph,
+    routes: &Vec<(NodeIdx, NodeIdx)>,
+    cfg: &configs::routing::Config,
+) {
+    for &(src_idx, dst_idx) in routes.iter() {
+        let _option_path = dijkstra.compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg: cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
         });
     }
 }
@@ -142,6 +217,8 @@ fn bidir_fastest_dijkstra(
             dst_idx,
             graph,
             routing_cfg: cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
         });
     }
 }
@@ -61,6 +61,8 @@ fn run() -> err::Feedback {
             dst_idx: dst.idx(),
             graph: &graph,
             routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
         },
         &mut dijkstra,
     );
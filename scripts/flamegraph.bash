@@ -7,4 +7,4 @@ cur_dir="$(dirname "$(pwd)"/"${0}")"
 cargo build --release
 
 # cargo install flamegraph, see https://github.com/killercup/cargo-flamegraph
-cargo flamegraph --bin osmgraphing -- --config "${cur_dir}/../custom/config.yaml"
+cargo flamegraph --bin osmgraphing -- route --config "${cur_dir}/../custom/config.yaml"
@@ -36,7 +36,7 @@ do
             fi
 
             # create new ones
-            cargo run --bin osmgraphing -- --config "${cfg}" --writing-routes
+            cargo run --bin osmgraphing -- write-routes --config "${cfg}"
         fi
     done
 done
@@ -0,0 +1,398 @@
+use crate::{
+    approximating::Approx,
+    configs::{self, balancing::ChRebuild, routing::RoutingAlgo, SimpleId},
+    helpers::err,
+    io,
+    network::{Graph, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+use log::warn;
+use std::{
+    fmt::{self, Display},
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// Whether an iteration contracts its graph again or keeps the hierarchy of a previous one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    Rebuild,
+    Skip,
+}
+
+impl Decision {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Decision::Rebuild => "rebuild",
+            Decision::Skip => "skip",
+        }
+    }
+}
+
+impl Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Why a decision has been made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// No graph has been contracted yet.
+    NoHierarchy,
+    /// The last contraction has ignored the optimized metric, while this iteration uses it
+    /// (or vice versa).
+    ChangedMetrics,
+    /// No thresholds are configured, hence every iteration contracts.
+    NoThresholds,
+    /// The optimized metric's change exceeds a threshold.
+    AboveThreshold,
+    /// The optimized metric's change is below both thresholds.
+    BelowThreshold,
+    /// The optimized metric's change is below both thresholds, but CH-Dijkstra's costs differ
+    /// from Dijkstra's costs after patching the shortcuts, because the hierarchy misses
+    /// shortcuts needed by the changed metric.
+    StaleHierarchy,
+}
+
+impl Reason {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reason::NoHierarchy => "no-hierarchy",
+            Reason::ChangedMetrics => "changed-metrics",
+            Reason::NoThresholds => "no-thresholds",
+            Reason::AboveThreshold => "above-threshold",
+            Reason::BelowThreshold => "below-threshold",
+            Reason::StaleHierarchy => "stale-hierarchy",
+        }
+    }
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The relative change `|new - old| / |old|` of a metric over all edges, which are no shortcuts.
+/// An edge, whose old value is zero, has changed infinitely, unless it is still zero.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RelativeDelta {
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl RelativeDelta {
+    /// Compares the graph's current values with the old ones, which are indexed by the graph's
+    /// edge-indices.
+    pub fn between(
+        old_column: &[f64],
+        graph: &Graph,
+        metric_id: &SimpleId,
+    ) -> err::Result<RelativeDelta> {
+        let metric_idx = graph.cfg().edges.metrics.try_idx_of(metric_id)?;
+        let metrics = graph.metrics();
+        let new_column = metrics.column(metric_idx);
+        if old_column.len() != new_column.len() {
+            return Err(format!(
+                "The metric {} has {} old values, but the graph has {} edges.",
+                metric_id,
+                old_column.len(),
+                new_column.len()
+            )
+            .into());
+        }
+
+        let fwd_edges = graph.fwd_edges();
+        let mut max = 0.0;
+        let mut sum = 0.0;
+        let mut count = 0;
+        for edge_idx in fwd_edges.iter().filter(|&idx| !fwd_edges.is_shortcut(idx)) {
+            let (old, new) = (old_column[*edge_idx], new_column[*edge_idx]);
+            let delta = if old == 0.0 {
+                if new == 0.0 {
+                    0.0
+                } else {
+                    std::f64::INFINITY
+                }
+            } else {
+                (new - old).abs() / old.abs()
+            };
+            if delta > max {
+                max = delta;
+            }
+            sum += delta;
+            count += 1;
+        }
+
+        Ok(RelativeDelta {
+            max,
+            mean: if count > 0 { sum / count as f64 } else { 0.0 },
+        })
+    }
+
+    /// Returns true, if the max or the mean exceeds its threshold.
+    pub fn exceeds(&self, thresholds: &ChRebuild) -> bool {
+        self.max > thresholds.max_relative_delta || self.mean > thresholds.mean_relative_delta
+    }
+}
+
+/// A decision of one iteration, as it is written into the run's metadata.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Record {
+    pub iter: usize,
+    pub decision: Decision,
+    pub reason: Reason,
+    /// The iteration, whose hierarchy the decision has been compared with, if any.
+    pub hierarchy_iter: Option<usize>,
+    pub relative_delta: Option<RelativeDelta>,
+}
+
+/// The hierarchy of the last contraction
+struct Hierarchy {
+    iter: usize,
+    is_using_new_metric: bool,
+    /// the optimized metric's values, when the graph has been contracted
+    column: Vec<f64>,
+}
+
+/// Decides per iteration, whether the balancer's graph is contracted again (see
+/// `configs::balancing::ChRebuild`).
+///
+/// The optimized metric is remembered after every contraction via `on_contracted(...)`.
+/// Before the next iteration, `decide(...)` compares it with the graph's updated metric, where a
+/// skipped rebuild keeps the hierarchy and only patches the shortcuts (see
+/// `Graph::recompute_shortcuts(...)`).
+/// Since small changes can still change shortest paths, which the hierarchy has no shortcuts for,
+/// `verify(...)` compares CH-Dijkstra with Dijkstra on sample queries of the patched graph and
+/// turns a skip into a rebuild, if they differ.
+/// Every decision is recorded.
+pub struct Trigger {
+    metric_id: SimpleId,
+    thresholds: Option<ChRebuild>,
+    hierarchy: Option<Hierarchy>,
+    records: Vec<Record>,
+}
+
+impl Trigger {
+    /// Without thresholds, every iteration rebuilds.
+    pub fn new(metric_id: SimpleId, thresholds: Option<ChRebuild>) -> Trigger {
+        Trigger {
+            metric_id,
+            thresholds,
+            hierarchy: None,
+            records: Vec::new(),
+        }
+    }
+
+    /// Decides, whether the given iteration contracts its graph again, and records the decision.
+    ///
+    /// The first contraction and a change of the contracted metrics (e.g. when the optimized
+    /// metric is used for the first time) always rebuild, since the hierarchy doesn't fit
+    /// anymore.
+    pub fn decide(
+        &mut self,
+        iter: usize,
+        is_using_new_metric: bool,
+        graph: &Graph,
+    ) -> err::Result<Decision> {
+        let (decision, reason, relative_delta) = match &self.hierarchy {
+            None => (Decision::Rebuild, Reason::NoHierarchy, None),
+            Some(hierarchy) if hierarchy.is_using_new_metric != is_using_new_metric => {
+                (Decision::Rebuild, Reason::ChangedMetrics, None)
+            }
+            Some(hierarchy) => {
+                let relative_delta =
+                    RelativeDelta::between(&hierarchy.column, graph, &self.metric_id)?;
+                match &self.thresholds {
+                    None => (
+                        Decision::Rebuild,
+                        Reason::NoThresholds,
+                        Some(relative_delta),
+                    ),
+                    Some(thresholds) if relative_delta.exceeds(thresholds) => (
+                        Decision::Rebuild,
+                        Reason::AboveThreshold,
+                        Some(relative_delta),
+                    ),
+                    Some(_) => (Decision::Skip, Reason::BelowThreshold, Some(relative_delta)),
+                }
+            }
+        };
+
+        self.records.push(Record {
+            iter,
+            decision,
+            reason,
+            hierarchy_iter: self.hierarchy.as_ref().map(|hierarchy| hierarchy.iter),
+            relative_delta,
+        });
+        Ok(decision)
+    }
+
+    /// Verifies the last decision, if it has skipped the rebuild, by comparing CH-Dijkstra's costs
+    /// with Dijkstra's costs of the optimized metric on up to `query_count` route-pairs, which are
+    /// spread evenly over all pairs of nodes.
+    /// Hence the graph's shortcuts should have been patched already.
+    ///
+    /// If any costs differ, the hierarchy is stale and the recorded decision is turned into a
+    /// rebuild.
+    pub fn verify(&mut self, graph: &Graph, query_count: usize) -> err::Result<Decision> {
+        match self.records.last() {
+            Some(record) if record.decision == Decision::Skip => (),
+            Some(record) => return Ok(record.decision),
+            None => return Err("No decision has been made, which could be verified.".into()),
+        }
+
+        if let Some((src_id, dst_id)) = find_stale_query(graph, &self.metric_id, query_count)? {
+            warn!(
+                "The hierarchy is stale, since CH-Dijkstra's costs differ from Dijkstra's costs \
+                 from node {} to node {}, hence the graph is contracted again.",
+                src_id, dst_id
+            );
+            if let Some(record) = self.records.last_mut() {
+                record.decision = Decision::Rebuild;
+                record.reason = Reason::StaleHierarchy;
+            }
+            Ok(Decision::Rebuild)
+        } else {
+            Ok(Decision::Skip)
+        }
+    }
+
+    /// Remembers the optimized metric of the freshly contracted graph.
+    pub fn on_contracted(
+        &mut self,
+        iter: usize,
+        is_using_new_metric: bool,
+        ch_graph: &Graph,
+    ) -> err::Feedback {
+        let metric_idx = ch_graph.cfg().edges.metrics.try_idx_of(&self.metric_id)?;
+        self.hierarchy = Some(Hierarchy {
+            iter,
+            is_using_new_metric,
+            column: ch_graph.metrics().column(metric_idx).to_vec(),
+        });
+        Ok(())
+    }
+
+    /// The recorded decisions in the order of their iterations
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Appends the last decision to the run's metadata, where the first one starts the list
+    /// `ch-rebuilds`.
+    pub fn append_last_to<P: AsRef<Path> + ?Sized>(&self, metadata_file: &P) -> err::Feedback {
+        let record = match self.records.last() {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+        let metadata_file = metadata_file.as_ref();
+        let mut writer = BufWriter::new(
+            match OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(metadata_file)
+            {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(format!(
+                        "Couldn't open {} due to error: {}",
+                        metadata_file.display(),
+                        e
+                    )
+                    .into())
+                }
+            },
+        );
+
+        if self.records.len() == 1 {
+            writeln!(writer, "ch-rebuilds:")?;
+        }
+        write!(
+            writer,
+            "- {{ iteration: {}, decision: {}, reason: {}",
+            record.iter, record.decision, record.reason
+        )?;
+        if let Some(hierarchy_iter) = record.hierarchy_iter {
+            write!(writer, ", hierarchy-iteration: {}", hierarchy_iter)?;
+        }
+        if let Some(relative_delta) = record.relative_delta {
+            write!(
+                writer,
+                ", max-relative-delta: {}, mean-relative-delta: {}",
+                yaml_float(relative_delta.max),
+                yaml_float(relative_delta.mean)
+            )?;
+        }
+        writeln!(writer, " }}")?;
+        Ok(())
+    }
+}
+
+/// Returns the first sample query `(src-id, dst-id)`, whose costs of CH-Dijkstra and Dijkstra
+/// differ.
+///
+/// The samples are spread evenly over all `n * n` pairs of nodes, so all pairs are checked, if
+/// `query_count >= n * n`.
+fn find_stale_query(
+    graph: &Graph,
+    metric_id: &SimpleId,
+    query_count: usize,
+) -> err::Result<Option<(i64, i64)>> {
+    let metric_idx = graph.cfg().edges.metrics.try_idx_of(metric_id)?;
+    let ch_routing_cfg = configs::routing::Config::try_from_str(
+        &format!(
+            "routing: {{ algorithm: 'CHDijkstra', metrics: [{{ id: '{}' }}] }}",
+            metric_id
+        ),
+        graph.cfg(),
+    )?;
+    let mut routing_cfg = ch_routing_cfg.clone();
+    routing_cfg.routing_algo = RoutingAlgo::Dijkstra;
+
+    let nodes = graph.nodes();
+    let node_count = nodes.count();
+    // node_count² could overflow usize for large graphs
+    let pair_count = node_count as u128 * node_count as u128;
+    let query_count = (query_count as u128).min(pair_count);
+    let mut dijkstra = Dijkstra::new();
+    let mut cost_of = |src_idx: NodeIdx,
+                       dst_idx: NodeIdx,
+                       routing_cfg: &configs::routing::Config|
+     -> err::Result<Option<f64>> {
+        let best_path = dijkstra.try_compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })?;
+        Ok(match best_path {
+            Some(best_path) => Some(best_path.try_flatten(graph)?.costs()[*metric_idx]),
+            None => None,
+        })
+    };
+    for i in 0..query_count {
+        let pair_idx = i * pair_count / query_count;
+        let src_idx = NodeIdx((pair_idx / node_count as u128) as usize);
+        let dst_idx = NodeIdx((pair_idx % node_count as u128) as usize);
+        let ch_cost = cost_of(src_idx, dst_idx, &ch_routing_cfg)?;
+        let cost = cost_of(src_idx, dst_idx, &routing_cfg)?;
+        if ch_cost.map(Approx) != cost.map(Approx) {
+            return Ok(Some((nodes.id(src_idx), nodes.id(dst_idx))));
+        }
+    }
+    Ok(None)
+}
+
+fn yaml_float(value: f64) -> String {
+    if value.is_infinite() {
+        String::from(".inf")
+    } else {
+        io::fmt::float(value, None)
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{
+    approximating::Approx,
+    configs::balancing::EdgeUsersTracking,
+    helpers::err,
+    io,
+    network::{EdgeIdx, Graph},
+    routing::paths::Path as RoutingPath,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+    io::Write,
+    mem,
+    path::Path,
+};
+
+/// Min-heap-entry of an edge's user, see `EdgeUsers::from_paths(...)`
+type HeapEntry = Reverse<(Approx<f64>, Reverse<usize>, i64, i64)>;
+
+/// A route-pair using an edge, where `count` is the sum of the counts of the route-pair's chosen
+/// paths containing the edge.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EdgeUser {
+    /// The route-pair's index in the (unsampled) route-pairs
+    pub pair_idx: usize,
+    pub src_id: i64,
+    pub dst_id: i64,
+    pub count: f64,
+}
+
+/// Answers, which route-pairs load an edge, by remembering the top-k contributing route-pairs of
+/// every edge, whose workload is at least the configured minimum.
+///
+/// Edges below the minimum are not tracked at all, which bounds the memory to
+/// `top-k * tracked edges`.
+#[derive(Clone, Debug)]
+pub struct EdgeUsers {
+    users: BTreeMap<EdgeIdx, Vec<EdgeUser>>,
+}
+
+impl EdgeUsers {
+    /// The provided paths have to be flattened and their route-pairs are looked up in
+    /// `pair_indices` by `(src-id, dst-id)`.
+    ///
+    /// The paths are streamed into a bounded min-heap per edge, so besides the top-k per edge,
+    /// only the contributions of the current route-pair are buffered.
+    /// Hence the chosen paths of a route-pair should be consecutive, as the balancer collects
+    /// them.
+    /// Otherwise, the occurrences of a route-pair are merged as long as the route-pair is in an
+    /// edge's top-k, but an occurrence dropped from the top-k doesn't add to later ones.
+    pub fn from_paths(
+        chosen_paths: &[(RoutingPath, f64)],
+        abs_workloads: &[f64],
+        pair_indices: &BTreeMap<(i64, i64), usize>,
+        tracking: &EdgeUsersTracking,
+    ) -> EdgeUsers {
+        // min-heap per edge, so the smallest contribution is dropped when exceeding top-k
+        // (and of equal contributions the one of the highest pair-idx)
+        let mut heaps: BTreeMap<EdgeIdx, BinaryHeap<HeapEntry>> = BTreeMap::new();
+
+        // A route-pair could have several chosen paths (e.g. when explorating), hence the
+        // contributions are summed up per route-pair before they compete for the top-k.
+        let mut pair: Option<(i64, i64)> = None;
+        let mut pair_contributions: BTreeMap<EdgeIdx, f64> = BTreeMap::new();
+        for (path, count) in chosen_paths {
+            let path_pair = (path.src_id(), path.dst_id());
+            if pair != Some(path_pair) {
+                if let Some(pair) = pair {
+                    push_contributions(
+                        &mut heaps,
+                        pair,
+                        &mut pair_contributions,
+                        pair_indices,
+                        tracking,
+                    );
+                }
+                pair = Some(path_pair);
+            }
+            for &edge_idx in path {
+                if abs_workloads[*edge_idx] >= tracking.min_workload {
+                    *pair_contributions.entry(edge_idx).or_insert(0.0) += count;
+                }
+            }
+        }
+        if let Some(pair) = pair {
+            push_contributions(
+                &mut heaps,
+                pair,
+                &mut pair_contributions,
+                pair_indices,
+                tracking,
+            );
+        }
+
+        let users = heaps
+            .into_iter()
+            .map(|(edge_idx, heap)| {
+                // sorted by descending count (then ascending pair-idx), which equals ascending
+                // reversed entries
+                let edge_users = heap
+                    .into_sorted_vec()
+                    .into_iter()
+                    .map(
+                        |Reverse((count, Reverse(pair_idx), src_id, dst_id))| EdgeUser {
+                            pair_idx,
+                            src_id,
+                            dst_id,
+                            count: count.0,
+                        },
+                    )
+                    .collect();
+                (edge_idx, edge_users)
+            })
+            .collect();
+        EdgeUsers { users }
+    }
+
+    /// Returns the edge's users sorted by descending count, or `None` if the edge is not tracked.
+    pub fn top_users(&self, edge_idx: EdgeIdx) -> Option<&[EdgeUser]> {
+        self.users.get(&edge_idx).map(|users| users.as_slice())
+    }
+
+    pub fn tracked_edges<'a>(&'a self) -> impl Iterator<Item = EdgeIdx> + 'a {
+        self.users.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    /// Writes one line per tracked edge and user, where edges are given by their nodes' ids.
+    pub fn write_csv(&self, graph: &Graph, file: &Path) -> err::Feedback {
+        let mut writer = io::files::AtomicFile::create(file)?;
+
+        // write header
+
+        writeln!(
+            writer,
+            "edge-src-id edge-dst-id rank pair-idx src-id dst-id count"
+        )?;
+
+        // write values
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        for (edge_idx, users) in &self.users {
+            let edge_src_id = nodes.id(bwd_edges.dst_idx(*edge_idx));
+            let edge_dst_id = nodes.id(fwd_edges.dst_idx(*edge_idx));
+            for (rank, user) in users.iter().enumerate() {
+                writeln!(
+                    writer,
+                    "{} {} {} {} {} {} {}",
+                    edge_src_id,
+                    edge_dst_id,
+                    rank,
+                    user.pair_idx,
+                    user.src_id,
+                    user.dst_id,
+                    io::fmt::float(user.count, None)
+                )?;
+            }
+        }
+
+        writer.commit()
+    }
+}
+
+/// Pushes (and drains) the route-pair's contributions into the edges' heaps, keeping at most
+/// top-k entries per edge.
+fn push_contributions(
+    heaps: &mut BTreeMap<EdgeIdx, BinaryHeap<HeapEntry>>,
+    (src_id, dst_id): (i64, i64),
+    pair_contributions: &mut BTreeMap<EdgeIdx, f64>,
+    pair_indices: &BTreeMap<(i64, i64), usize>,
+    tracking: &EdgeUsersTracking,
+) {
+    let contributions = mem::replace(pair_contributions, BTreeMap::new());
+    let pair_idx = match pair_indices.get(&(src_id, dst_id)) {
+        Some(pair_idx) => *pair_idx,
+        None => return,
+    };
+    for (edge_idx, mut count) in contributions {
+        let heap = heaps.entry(edge_idx).or_insert_with(BinaryHeap::new);
+
+        // an earlier occurrence of the route-pair is merged into this one
+        if heap
+            .iter()
+            .any(|Reverse((_, Reverse(other_idx), _, _))| *other_idx == pair_idx)
+        {
+            let mut entries = mem::replace(heap, BinaryHeap::new()).into_vec();
+            entries.retain(|Reverse((other_count, Reverse(other_idx), _, _))| {
+                if *other_idx == pair_idx {
+                    count += other_count.0;
+                    false
+                } else {
+                    true
+                }
+            });
+            *heap = BinaryHeap::from(entries);
+        }
+
+        heap.push(Reverse((Approx(count), Reverse(pair_idx), src_id, dst_id)));
+        if heap.len() > tracking.top_k {
+            heap.pop();
+        }
+    }
+}
@@ -0,0 +1,10 @@
+pub mod ch_rebuild;
+#[cfg(feature = "gpl")]
+pub mod edge_users;
+#[cfg(feature = "gpl")]
+pub mod multithreading;
+#[cfg(feature = "gpl")]
+mod runner;
+#[cfg(feature = "gpl")]
+pub use runner::{vehicles_for_smarts, BalancingObserver, NoObserver, Runner};
+pub mod timing;
@@ -1,7 +1,11 @@
-use log::{debug, info, trace, warn};
-use osmgraphing::{
+use super::{
+    timing::{Phase, PhaseTimer},
+    BalancingObserver,
+};
+use crate::{
     configs::{
         self,
+        balancing::DemandSampling,
         routing::{ExploratorAlgo, RoutingAlgo},
     },
     defaults,
@@ -11,8 +15,10 @@ use osmgraphing::{
         dijkstra::{self, Dijkstra},
         explorating::ConvexHullExplorator,
         paths::Path,
+        stabilizing::{EdgeBias, RouteMemory},
     },
 };
+use log::{debug, info, trace, warn};
 use progressing::{mapping::Bar as MappingBar, Baring};
 use rand::{
     distributions::{Distribution, Uniform},
@@ -35,20 +41,32 @@ pub struct Master {
 }
 
 impl Master {
-    pub fn work_off(
+    /// The route-pairs are taken lazily in chunks of the work-size, so they could be streamed
+    /// from a file (see `io::routing::Parser::stream(...)`), where `num_of_route_pairs` is only
+    /// used for the progress.
+    ///
+    /// The observer is notified about every routed route-pair in the order, in which the workers'
+    /// outcomes are received.
+    pub fn work_off<I>(
         &mut self,
-        mut route_pairs: Vec<(RoutePair<i64>, usize)>,
+        mut route_pairs: I,
+        num_of_route_pairs: usize,
         arc_ch_graph: &Arc<Graph>,
         rng: &mut rand_pcg::Lcg64Xsh32,
         is_collecting_paths: bool,
-    ) -> err::Result<(Vec<usize>, Option<Vec<Path>>)> {
+        demand_sampling: &DemandSampling,
+        timer: &mut PhaseTimer,
+        mut route_memory: Option<&mut RouteMemory>,
+        observer: &mut dyn BalancingObserver,
+    ) -> err::Result<(Vec<f64>, Option<Vec<(Path, f64)>>)>
+    where
+        I: Iterator<Item = err::Result<(RoutePair<i64>, f64)>>,
+    {
         info!("Using {} threads working off", self.num_threads());
 
-        route_pairs.reverse();
         // not routes, because progress can be shown without it (though it is less accurate)
-        let num_of_route_pairs = route_pairs.len();
 
-        let mut abs_workloads: Vec<usize> = vec![0; arc_ch_graph.fwd_edges().count()];
+        let mut abs_workloads: Vec<f64> = vec![0.0; arc_ch_graph.fwd_edges().count()];
         let mut chosen_paths = if is_collecting_paths {
             // num_of_route_pairs is not accurate, but lower bound
             Some(Vec::with_capacity(num_of_route_pairs))
@@ -64,20 +82,37 @@ impl Master {
         loop {
             if let Ok(outcome) = self.recv() {
                 // update counts from outcome
+                // -> everything else is waiting for the workers' routing
+
+                for routed_pair in outcome.routed_pairs {
+                    timer.start(Phase::SampleAndFlatten);
+                    let found_paths: Vec<Path> = routed_pair
+                        .found_paths
+                        .into_iter()
+                        .map(|path| path.flatten(&arc_ch_graph))
+                        .collect();
 
-                for path in outcome
-                    .chosen_paths
-                    .into_iter()
-                    .map(|path| path.flatten(&arc_ch_graph))
-                {
-                    for &edge_idx in &path {
-                        abs_workloads[*edge_idx] += 1;
-                    }
+                    timer.start(Phase::WorkloadUpdate);
+                    for &(path_idx, count) in &routed_pair.chosen {
+                        let path = &found_paths[path_idx];
+                        defaults::balancing::add_workloads(&mut abs_workloads, path, count);
 
-                    if let Some(chosen_paths) = chosen_paths.as_mut() {
-                        chosen_paths.push(path);
+                        if let Some(route_memory) = route_memory.as_mut() {
+                            route_memory.remember(path, &arc_ch_graph);
+                        }
+
+                        if let Some(chosen_paths) = chosen_paths.as_mut() {
+                            chosen_paths.push((path.clone(), count));
+                        }
                     }
+
+                    observer.on_pair_routed(
+                        &routed_pair.route_pair,
+                        &found_paths,
+                        &routed_pair.chosen,
+                    );
                 }
+                timer.start(Phase::Explorate);
                 // num_of_routes is ignored here
                 progress_bar.add(outcome.num_of_route_pairs);
 
@@ -135,15 +170,15 @@ impl Master {
 
                 // send new work
 
-                if route_pairs.len() > 0 {
-                    let chunk_len = std::cmp::min(route_pairs.len(), self.work_size());
-                    let chunk: Vec<_> = route_pairs
-                        .splice((route_pairs.len() - chunk_len).., vec![])
-                        .rev()
-                        .collect();
+                let chunk = route_pairs
+                    .by_ref()
+                    .take(self.work_size())
+                    .collect::<err::Result<Vec<_>>>()?;
+                if chunk.len() > 0 {
                     self.send(Work {
                         route_pairs: chunk,
                         seed: rng.gen(),
+                        is_expecting: *demand_sampling == DemandSampling::Expected,
                     })?;
                 } else {
                     self.drop_and_join_worker()?;
@@ -198,6 +233,7 @@ impl Master {
         count: usize,
         arc_graph: &Arc<Graph>,
         arc_routing_cfg: &Arc<configs::routing::Config>,
+        stabilization: Option<(f64, Arc<RouteMemory>)>,
     ) -> err::Result<Master> {
         info!("Using routing-algo: {:?}", arc_routing_cfg.routing_algo);
         let mut worker_sockets = Vec::with_capacity(count);
@@ -213,6 +249,9 @@ impl Master {
                 idx,
                 arc_graph: Arc::clone(arc_graph),
                 arc_routing_cfg: Arc::clone(arc_routing_cfg),
+                stabilization: stabilization
+                    .as_ref()
+                    .map(|(bonus, memory)| (*bonus, Arc::clone(memory))),
                 work_rx,
                 outcome_tx: outcome_txs
                     .pop()
@@ -351,21 +390,37 @@ impl WorkerSocket {
 }
 
 pub struct Work {
-    pub route_pairs: Vec<(RoutePair<i64>, usize)>,
+    pub route_pairs: Vec<(RoutePair<i64>, f64)>,
     pub seed: u64,
+    /// If true, the count of a route-pair is split evenly between all found paths instead of choosing paths randomly.
+    pub is_expecting: bool,
 }
 
 /// Chosen paths are not necessarily the same as found paths (e.g. when using explorator), for which reason the `num_of_found_paths` is provided separatedly.
 pub struct Outcome {
-    pub chosen_paths: Vec<Path>,
+    pub routed_pairs: Vec<RoutedPair>,
     pub num_of_found_paths: Vec<usize>,
     pub num_of_route_pairs: usize,
 }
 
+/// A route-pair with its found (not flattened) paths, where route-pairs without any path have no
+/// found paths.
+///
+/// Every chosen path is given by its index in the found paths and comes with its (maybe
+/// fractional) count.
+/// A found path could be chosen several times, e.g. when sampling.
+pub struct RoutedPair {
+    pub route_pair: RoutePair<i64>,
+    pub found_paths: Vec<Path>,
+    pub chosen: Vec<(usize, f64)>,
+}
+
 struct WorkerContext {
     idx: WorkerIdx,
     arc_graph: Arc<Graph>,
     arc_routing_cfg: Arc<configs::routing::Config>,
+    // bonus and the route-pairs' edges of the previous iteration
+    stabilization: Option<(f64, Arc<RouteMemory>)>,
     work_rx: mpsc::Receiver<Work>,
     outcome_tx: mpsc::Sender<(WorkerIdx, Outcome)>,
 }
@@ -373,6 +428,7 @@ struct WorkerContext {
 struct Worker {
     dijkstra: Dijkstra,
     explorator: ConvexHullExplorator,
+    edge_bias: Option<(EdgeBias, Arc<RouteMemory>)>,
     // context
     idx: WorkerIdx,
     arc_graph: Arc<Graph>,
@@ -386,6 +442,9 @@ impl Worker {
         Worker {
             dijkstra: Dijkstra::new(),
             explorator: ConvexHullExplorator::new(),
+            edge_bias: context
+                .stabilization
+                .map(|(bonus, memory)| (EdgeBias::new(bonus, &context.arc_graph), memory)),
             idx: context.idx,
             arc_graph: context.arc_graph,
             arc_routing_cfg: context.arc_routing_cfg,
@@ -401,7 +460,7 @@ impl Worker {
             .send((
                 self.idx,
                 Outcome {
-                    chosen_paths: Vec::new(),
+                    routed_pairs: Vec::new(),
                     num_of_found_paths: Vec::new(),
                     num_of_route_pairs: 0,
                 },
@@ -421,11 +480,9 @@ impl Worker {
 
                 // do work
                 let outcome = match self.arc_routing_cfg.routing_algo {
-                    super::RoutingAlgo::Dijkstra => self.work_off_with_dijkstra(work),
-                    super::RoutingAlgo::CHDijkstra => self.work_off_with_dijkstra(work),
-                    super::RoutingAlgo::Explorator { algo } => {
-                        self.work_off_with_explorator(work, algo)
-                    }
+                    RoutingAlgo::Dijkstra => self.work_off_with_dijkstra(work),
+                    RoutingAlgo::CHDijkstra => self.work_off_with_dijkstra(work),
+                    RoutingAlgo::Explorator { algo } => self.work_off_with_explorator(work, algo),
                 };
 
                 // return outcome
@@ -440,7 +497,7 @@ impl Worker {
     }
 
     fn work_off_with_dijkstra(&mut self, work: Work) -> Outcome {
-        let mut chosen_paths = Vec::new();
+        let mut routed_pairs = Vec::with_capacity(work.route_pairs.len());
         let mut num_of_found_paths = Vec::new();
         let num_of_route_pairs = work.route_pairs.len();
 
@@ -448,12 +505,21 @@ impl Worker {
             let RoutePair { src, dst } = route_pair.into_node(&self.arc_graph);
 
             // find explorated routes
+            // -> preferring the route-pair's edges of the previous iteration, if stabilizing
 
+            let edge_bias = match &mut self.edge_bias {
+                Some((edge_bias, memory)) => {
+                    edge_bias.of(route_pair.src, route_pair.dst, memory, &self.arc_graph)
+                }
+                None => None,
+            };
             let best_path = self.dijkstra.compute_best_path(dijkstra::Query {
                 src_idx: src.idx(),
                 dst_idx: dst.idx(),
-                graph: &self.arc_graph,
+                graph: &*self.arc_graph,
                 routing_cfg: &self.arc_routing_cfg,
+                edge_bias,
+                path_hint: dijkstra::PathHint::ExpectShort,
             });
 
             // Update next workload by looping over all found routes
@@ -461,30 +527,32 @@ impl Worker {
             // -> or future shortcuts using the resulting workload
             //    will lead to wrong best-paths, because counts won't be cumulated.
 
+            let mut routed_pair = RoutedPair {
+                route_pair,
+                found_paths: Vec::with_capacity(1),
+                chosen: Vec::with_capacity(1),
+            };
             if let Some(best_path) = best_path {
                 num_of_found_paths.push(1);
-
-                for _ in 0..(route_count - 1) {
-                    chosen_paths.push(best_path.clone());
-                }
-                chosen_paths.push(best_path);
+                routed_pair.found_paths.push(best_path);
+                routed_pair.chosen.push((0, route_count));
             } else {
                 warn!("Didn't find any path when executing Dijkstra.")
             }
+            routed_pairs.push(routed_pair);
         }
 
-        chosen_paths.shrink_to_fit();
         num_of_found_paths.shrink_to_fit();
 
         Outcome {
-            chosen_paths,
+            routed_pairs,
             num_of_found_paths,
             num_of_route_pairs,
         }
     }
 
     fn work_off_with_explorator(&mut self, work: Work, explorator_algo: ExploratorAlgo) -> Outcome {
-        let mut chosen_paths = Vec::new();
+        let mut routed_pairs = Vec::with_capacity(work.route_pairs.len());
         let mut num_of_found_paths = Vec::new();
         let num_of_route_pairs = work.route_pairs.len();
         let mut rng = rand_pcg::Pcg32::seed_from_u64(work.seed);
@@ -501,8 +569,10 @@ impl Worker {
                 dijkstra::Query {
                     src_idx: src.idx(),
                     dst_idx: dst.idx(),
-                    graph: &self.arc_graph,
+                    graph: &*self.arc_graph,
                     routing_cfg: &routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::ExpectShort,
                 },
                 &mut self.dijkstra,
             );
@@ -513,23 +583,38 @@ impl Worker {
             // -> Routes have to be flattened,
             // -> or shortcuts will lead to wrong best-paths, because counts won't be cumulated.
 
+            let mut chosen = Vec::new();
             if found_paths.len() > 0 {
-                let die = Uniform::from(0..found_paths.len());
-                for _ in 0..route_count {
-                    let chosen_path = found_paths[die.sample(&mut rng)].clone();
-                    trace!("    {}", chosen_path);
-                    chosen_paths.push(chosen_path);
+                if work.is_expecting {
+                    // every path is chosen with the same probability
+                    let count = route_count / (found_paths.len() as f64);
+                    for (path_idx, chosen_path) in found_paths.iter().enumerate() {
+                        trace!("    {}", chosen_path);
+                        chosen.push((path_idx, count));
+                    }
+                } else {
+                    // sampled counts are integers
+                    let die = Uniform::from(0..found_paths.len());
+                    for _ in 0..(route_count.round() as usize) {
+                        let path_idx = die.sample(&mut rng);
+                        trace!("    {}", found_paths[path_idx]);
+                        chosen.push((path_idx, 1.0));
+                    }
                 }
             } else {
                 warn!("Didn't find any path when explorating.")
             }
+            routed_pairs.push(RoutedPair {
+                route_pair,
+                found_paths,
+                chosen,
+            });
         }
 
-        chosen_paths.shrink_to_fit();
         num_of_found_paths.shrink_to_fit();
 
         Outcome {
-            chosen_paths,
+            routed_pairs,
             num_of_found_paths,
             num_of_route_pairs,
         }
@@ -0,0 +1,1067 @@
+use super::{
+    ch_rebuild::{self, Decision},
+    multithreading,
+    timing::{Phase, PhaseTimer},
+};
+use crate::{
+    configs, defaults,
+    helpers::err,
+    io,
+    network::{Graph, RoutePair},
+    routing::paths::{similarity, Path as RoutingPath},
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use rand::SeedableRng;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+/// Polylines of chosen paths per route-pair `(src-id, dst-id)`
+type Polylines = BTreeMap<(i64, i64), Vec<Coordinate>>;
+
+/// Hooks into the balancer's iterations, e.g. for custom workload-smoothing or extra exports.
+///
+/// Every method does nothing by default, so only the needed hooks have to be implemented.
+/// The hooks of an iteration are called in the order
+///
+/// 1. `on_iteration_start(...)`
+/// 1. `on_iteration_prepared(...)`
+/// 1. `on_pair_routed(...)` for every routed route-pair
+/// 1. `on_workload_computed(...)`
+/// 1. `on_iteration_end(...)`
+pub trait BalancingObserver {
+    fn on_iteration_start(&mut self, _iter: usize) {}
+
+    /// Is called after the iteration's graph and routing-config have been written, right before
+    /// routing.
+    /// An error aborts the run, which leaves the iteration incomplete like a crash.
+    fn on_iteration_prepared(&mut self, _iter: usize) -> err::Feedback {
+        Ok(())
+    }
+
+    /// Is called for every routed route-pair with its found (flattened) paths, where route-pairs
+    /// without any path have no paths.
+    /// Every chosen path is given by its index in `paths` and comes with its (maybe fractional)
+    /// count, so a path could be chosen several times, e.g. when sampling.
+    ///
+    /// With several threads, route-pairs are not necessarily routed in the order of the
+    /// route-pairs-file.
+    fn on_pair_routed(
+        &mut self,
+        _route_pair: &RoutePair<i64>,
+        _paths: &[RoutingPath],
+        _chosen: &[(usize, f64)],
+    ) {
+    }
+
+    /// Is called with the absolute workloads of all edges after routing all route-pairs.
+    ///
+    /// The workloads can be changed, before they are exported and used for updating the
+    /// optimized metric.
+    fn on_workload_computed(&mut self, _abs_workloads: &mut [f64]) {}
+
+    /// Is called with the graph, whose optimized metric has been updated.
+    fn on_iteration_end(&mut self, _graph: &Graph) {}
+}
+
+/// Observes nothing, which is the default of the `Runner`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoObserver;
+
+impl BalancingObserver for NoObserver {}
+
+/// Runs the balancer as configured in a yaml-file, which contains the configs for parsing,
+/// balancing, routing and writing the balanced graph.
+///
+/// Every iteration writes its graph, contracts it via the multi-ch-constructor, routes all
+/// route-pairs on the contracted graph and updates the optimized metric with the resulting
+/// workloads.
+/// If configured, the contraction is skipped for small changes of the optimized metric (see
+/// `balancing::ch_rebuild`).
+/// Custom logic can be hooked into the iterations via `with_observer(...)`.
+///
+/// Every iteration is written into its temporary dir `<iter>.tmp`, which is renamed to `<iter>`
+/// only after all its files have been written and synced (see `io::files::AtomicDir`).
+/// Hence, a crashed run never leaves an incomplete `<iter>`, but its `<iter>.tmp`, which is
+/// removed with a warning by the next run.
+/// Runs, which are still active (e.g. in another process), are recognized by their lock-file
+/// and never touched.
+pub struct Runner<O: BalancingObserver> {
+    cfg: PathBuf,
+    is_contracting: bool,
+    observer: O,
+    ch_rebuilds: Vec<ch_rebuild::Record>,
+}
+
+impl Runner<NoObserver> {
+    pub fn new<P: AsRef<Path> + ?Sized>(cfg: &P) -> Runner<NoObserver> {
+        Runner {
+            cfg: cfg.as_ref().to_path_buf(),
+            is_contracting: true,
+            observer: NoObserver,
+            ch_rebuilds: Vec::new(),
+        }
+    }
+}
+
+impl<O: BalancingObserver> Runner<O> {
+    pub fn with_observer<T: BalancingObserver>(self, observer: T) -> Runner<T> {
+        Runner {
+            cfg: self.cfg,
+            is_contracting: self.is_contracting,
+            observer,
+            ch_rebuilds: self.ch_rebuilds,
+        }
+    }
+
+    /// Without contraction, every iteration routes on the graph, which is parsed as configured
+    /// in the iteration's config (e.g. the written `graph.fmi` instead of `graph.ch.fmi`).
+    /// This doesn't need the external multi-ch-constructor, but is only reasonable for small
+    /// graphs and routing without CH.
+    pub fn with_contraction(mut self, is_contracting: bool) -> Runner<O> {
+        self.is_contracting = is_contracting;
+        self
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn into_observer(self) -> O {
+        self.observer
+    }
+
+    /// The decisions of the last run, whether its iterations have contracted their graph again,
+    /// which are written into the run's metadata as well.
+    pub fn ch_rebuilds(&self) -> &[ch_rebuild::Record] {
+        &self.ch_rebuilds
+    }
+
+    /// Returns the balanced graph, which is the graph of the last iteration with the updated
+    /// metric.
+    pub fn run(&mut self) -> err::Result<Graph> {
+        // check writing-cfg
+        let writing_cfg = configs::writing::network::graph::Config::try_from_yaml(&self.cfg)?;
+        let mut balancing_cfg = configs::balancing::Config::try_from_yaml(&self.cfg)?;
+
+        info!("EXECUTE balancer");
+        info!("Using balancer-seed={}", balancing_cfg.seed);
+
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(balancing_cfg.seed);
+
+        // check route-pairs before the (expensive) graph is parsed
+        simulation_pipeline::check_route_pairs(&self.cfg)?;
+
+        // prepare simulation
+        // e.g. creating the results-folder and converting the graph into the right format
+
+        let custom_graph = simulation_pipeline::read_in_custom_graph(&self.cfg)?;
+        // check configs against the graph before balancing
+        // (the balanced graph is written into the results-dir, hence only the ids are checked)
+        writing_cfg.validate_ids(custom_graph.cfg())?;
+        balancing_cfg.validate(custom_graph.cfg())?;
+        // check routing-cfg
+        let _ = configs::routing::Config::try_from_yaml(&self.cfg, custom_graph.cfg())?;
+
+        // start balancing
+
+        // removed when returning, even on errors
+        let _run_lock = simulation_pipeline::prepare_results(&self.cfg, &mut balancing_cfg)?;
+
+        let mut graph = custom_graph;
+        simulation_pipeline::init_new_metric(&balancing_cfg, &mut graph)?;
+        let mut ch_rebuild_trigger = ch_rebuild::Trigger::new(
+            balancing_cfg.optimization.metric_id.clone(),
+            balancing_cfg.ch_rebuild,
+        );
+        let mut prev_polylines = None;
+        let mut route_memory = None;
+        let mut timer = PhaseTimer::new();
+        for iter in 0..balancing_cfg.num_iter {
+            timer.start_iteration(iter);
+            self.observer.on_iteration_start(iter);
+
+            // Iterate +1 to get analysis of new graph as well.
+            // -> store graph before creating a new one
+
+            if iter == balancing_cfg.num_iter - 1 {
+                // store balanced graph
+
+                timer.start(Phase::IoWrite);
+
+                let mut writing_cfg =
+                    configs::writing::network::graph::Config::try_from_yaml(&self.cfg)?;
+                writing_cfg.map_file =
+                    balancing_cfg
+                        .results_dir
+                        .join(writing_cfg.map_file.file_name().ok_or(err::Msg::from(
+                            "The provided route-pairs-file in the (routing-)config is not a file.",
+                        ))?);
+                write_graph(&graph, &writing_cfg)?;
+            }
+
+            // simulate and create new balanced graph
+
+            timer.start(Phase::IoWrite);
+            let iter_dir = simulation_pipeline::prepare_iteration(iter, &balancing_cfg)?;
+            simulation_pipeline::write_multi_ch_graph(&balancing_cfg, &graph, iter)?;
+            timer.start(Phase::Contraction);
+            let ch_graph = match simulation_pipeline::decide_ch_rebuild(
+                iter,
+                &balancing_cfg,
+                &mut graph,
+                &mut ch_rebuild_trigger,
+            )? {
+                Decision::Rebuild => {
+                    // free memory before the new graph is read in
+                    drop(graph);
+                    if self.is_contracting {
+                        simulation_pipeline::construct_ch_graph(&balancing_cfg, iter)?;
+                    }
+                    let ch_graph = simulation_pipeline::read_in_ch_graph(&balancing_cfg, iter)?;
+                    ch_rebuild_trigger.on_contracted(
+                        iter,
+                        defaults::balancing::is_using_new_metric(iter, &balancing_cfg),
+                        &ch_graph,
+                    )?;
+                    ch_graph
+                }
+                // the shortcuts have been patched already
+                Decision::Skip => graph,
+            };
+            self.ch_rebuilds = ch_rebuild_trigger.records().to_vec();
+            timer.start(Phase::IoWrite);
+            simulation_pipeline::write_results_format(&balancing_cfg, &ch_graph, iter)?;
+            let routing_cfg = simulation_pipeline::read_in_routing_cfg(
+                &balancing_cfg,
+                iter,
+                &self.cfg,
+                &ch_graph,
+            )?;
+            self.observer.on_iteration_prepared(iter)?;
+
+            let mut arc_ch_graph = Arc::new(ch_graph);
+            simulation_pipeline::balance(
+                iter,
+                &balancing_cfg,
+                &mut arc_ch_graph,
+                &Arc::new(routing_cfg),
+                &mut rng,
+                &mut prev_polylines,
+                &mut route_memory,
+                &mut timer,
+                &mut self.observer,
+            )?;
+            graph = Arc::try_unwrap(arc_ch_graph)
+                .map_err(|_e| "The ch-graph should be owned by only one Arc.")?;
+            timer.start(Phase::IoWrite);
+            iter_dir.commit()?;
+            self.observer.on_iteration_end(&graph);
+
+            // rewritten every iteration to keep the timing of aborted runs
+            timer.finish_iteration();
+            timer.write_csv(
+                &balancing_cfg
+                    .results_dir
+                    .join(defaults::balancing::files::TIMING),
+            )?;
+        }
+
+        info!(
+            "Execute py ./scripts/balancing/visualizer --results-dir {} to visualize.",
+            balancing_cfg.results_dir.display()
+        );
+
+        Ok(graph)
+    }
+}
+
+mod simulation_pipeline {
+    use super::{multithreading, BalancingObserver, Polylines};
+    use crate::{
+        balancing::{
+            ch_rebuild,
+            edge_users::EdgeUsers,
+            timing::{Phase, PhaseTimer},
+        },
+        configs::{self, balancing::ResultsFormat, routing::RoutingAlgo},
+        defaults,
+        helpers::err,
+        io, multi_ch_constructor,
+        network::{Graph, RoutePair},
+        routing::{paths::similarity, stabilizing::RouteMemory},
+    };
+    use fs2::FileExt;
+    use log::{info, warn};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        fs::{self, File, OpenOptions},
+        io::{BufWriter, Write},
+        path::{Path, PathBuf},
+        process,
+        sync::Arc,
+        time::Instant,
+    };
+
+    /// Route-pairs, which are parsed or streamed (see `balance(...)`)
+    type RoutePairs = Box<dyn Iterator<Item = err::Result<(RoutePair<i64>, f64)>>>;
+
+    /// The temporary dir of the iteration, which is written into until the iteration is
+    /// completed (see `prepare_iteration(...)`).
+    fn iter_dir(iter: usize, balancing_cfg: &configs::balancing::Config) -> PathBuf {
+        io::files::tmp_dir_of(&io::balancing::Reader::iter_dir(
+            iter,
+            &balancing_cfg.results_dir,
+        ))
+    }
+
+    /// Fails if the routing-config has no route-pairs-file, or if it doesn't contain any
+    /// route-pairs, since balancing needs route-pairs in every iteration.
+    /// Only the file's header is read, hence this is cheap enough to be done before parsing the
+    /// graph.
+    pub fn check_route_pairs(raw_cfg: &Path) -> err::Feedback {
+        // The graph's generated metrics are unknown yet, hence only the file is read.
+        let route_pairs_file =
+            match configs::routing::Config::try_route_pairs_file_from_yaml(raw_cfg)? {
+                Some(route_pairs_file) => route_pairs_file,
+                None => {
+                    return Err(err::Msg::from(
+                        "Balancing needs route-pairs, but the routing-config doesn't specify a \
+                         route-pairs-file.",
+                    ))
+                }
+            };
+        if io::routing::Parser::count(&route_pairs_file)? == 0 {
+            return Err(err::Msg::from(format!(
+                "The route-pairs-file {} doesn't contain any route-pairs, \
+                 hence there is nothing to balance.",
+                route_pairs_file.display()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn read_in_custom_graph(raw_parsing_cfg: &Path) -> err::Result<Graph> {
+        let parsing_cfg = configs::parsing::Config::try_from_yaml(raw_parsing_cfg)?;
+        super::parse_graph(parsing_cfg)
+    }
+
+    /// Marks a run's dir as active by holding an exclusive OS-lock on its lock-file, which is
+    /// released by the OS when the run's process ends, even if it crashes.
+    /// The lock-file is removed when the run ends regularly, even if it fails.
+    ///
+    /// The file contains the process-id, but only for humans.
+    pub struct RunLock {
+        file: PathBuf,
+        handle: Option<File>,
+    }
+
+    impl RunLock {
+        fn create(run_dir: &Path) -> err::Result<RunLock> {
+            let file = run_dir.join(defaults::balancing::files::RUN_LOCK);
+            let mut handle = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&file)?;
+            if let Err(e) = handle.try_lock_exclusive() {
+                let _ = fs::remove_file(&file);
+                return Err(err::Msg::from(format!(
+                    "Couldn't lock {} due to error: {}",
+                    file.display(),
+                    e
+                )));
+            }
+            writeln!(handle, "{}", process::id())?;
+            Ok(RunLock {
+                file,
+                handle: Some(handle),
+            })
+        }
+
+        /// Returns true, if the run's dir has a lock-file, which is locked by another process.
+        ///
+        /// Lock-files, which aren't locked, are stale, e.g. left behind by a crashed run.
+        /// If the lock can't be checked at all, the run is assumed to be active.
+        fn is_active(run_dir: &Path) -> bool {
+            let file = run_dir.join(defaults::balancing::files::RUN_LOCK);
+            let handle = match OpenOptions::new().read(true).write(true).open(&file) {
+                Ok(handle) => handle,
+                // completed or crashed before its lock-file has been created
+                Err(_) => return false,
+            };
+            match handle.try_lock_exclusive() {
+                // dropping the handle releases the lock again
+                Ok(()) => false,
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => true,
+                Err(e) => {
+                    warn!(
+                        "Couldn't check the lock {} due to error: {} \
+                         Hence its run is assumed to be active.",
+                        file.display(),
+                        e
+                    );
+                    true
+                }
+            }
+        }
+    }
+
+    impl Drop for RunLock {
+        fn drop(&mut self) {
+            // some OSs don't allow removing opened files
+            drop(self.handle.take());
+            let _ = fs::remove_file(&self.file);
+        }
+    }
+
+    /// Returns the run's lock, which has to be kept until the run has finished.
+    pub fn prepare_results<P: AsRef<Path>>(
+        raw_cfg: P,
+        balancing_cfg: &mut configs::balancing::Config,
+    ) -> err::Result<RunLock> {
+        let raw_cfg = raw_cfg.as_ref();
+
+        remove_incomplete_iterations(&balancing_cfg.results_dir)?;
+
+        // set results-directory dependent of the current date in utc
+        balancing_cfg.results_dir = balancing_cfg.results_dir.join(format!(
+            "utc_{}",
+            chrono::Utc::now().format("%Y-%m-%d_%H-%M-%S")
+        ));
+        fs::create_dir_all(&balancing_cfg.results_dir)?;
+        let run_lock = RunLock::create(&balancing_cfg.results_dir)?;
+        info!("Storing results in {}", balancing_cfg.results_dir.display());
+
+        fs::copy(
+            raw_cfg,
+            balancing_cfg.results_dir.join(
+                raw_cfg
+                    .file_name()
+                    .ok_or(err::Msg::from("The provided cfg is not a file."))?,
+            ),
+        )?;
+
+        Ok(run_lock)
+    }
+
+    /// Removes the temporary dirs of all inactive runs in the given results-dir, which are left
+    /// behind by crashed runs, since every completed iteration has been renamed.
+    /// Active runs (see `RunLock`) are skipped, since their current iteration is still written.
+    fn remove_incomplete_iterations(results_dir: &Path) -> err::Feedback {
+        if !results_dir.is_dir() {
+            return Ok(());
+        }
+
+        for entry in fs::read_dir(results_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() || RunLock::is_active(&entry.path()) {
+                continue;
+            }
+            for tmp_dir in io::files::remove_tmp_dirs(&entry.path())? {
+                warn!(
+                    "Removed the incomplete iteration {} of a crashed run.",
+                    tmp_dir.display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Initializes the new metric before iteration 0 and records this in the run's metadata.
+    pub fn init_new_metric(
+        balancing_cfg: &configs::balancing::Config,
+        graph: &mut Graph,
+    ) -> err::Feedback {
+        defaults::balancing::init_new_metric(graph, balancing_cfg)?;
+
+        let metadata_file = balancing_cfg
+            .results_dir
+            .join(defaults::balancing::files::METADATA);
+        let mut writer = BufWriter::new(
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&metadata_file)?,
+        );
+        writeln!(
+            writer,
+            "initial-workload: {}",
+            balancing_cfg.initial_workload
+        )?;
+
+        Ok(())
+    }
+
+    /// Creates the iteration's temporary dir, where every file of the iteration is written into,
+    /// until the returned dir is committed at the iteration's end.
+    pub fn prepare_iteration(
+        iter: usize,
+        balancing_cfg: &configs::balancing::Config,
+    ) -> err::Result<io::files::AtomicDir> {
+        // create directory for results
+
+        let atomic_dir = io::files::AtomicDir::create(&io::balancing::Reader::iter_dir(
+            iter,
+            &balancing_cfg.results_dir,
+        ))?;
+        let iter_dir = atomic_dir.tmp_dir();
+        fs::create_dir_all(&iter_dir.join(defaults::balancing::stats::DIR))?;
+
+        // copy all necessary configs in there
+        // -> when warm-started, iteration 0 handles the new metric like every other iteration
+
+        fs::copy(
+            if !defaults::balancing::is_using_new_metric(iter, balancing_cfg) {
+                &balancing_cfg.iter_0_cfg
+            } else {
+                &balancing_cfg.iter_i_cfg
+            },
+            iter_dir.join(defaults::balancing::files::ITERATION_CFG),
+        )?;
+
+        Ok(atomic_dir)
+    }
+
+    pub fn write_multi_ch_graph(
+        balancing_cfg: &configs::balancing::Config,
+        graph: &Graph,
+        iter: usize,
+    ) -> err::Feedback {
+        let iter_dir = iter_dir(iter, balancing_cfg);
+
+        // writing graph
+
+        let mut writing_cfg = configs::writing::network::graph::Config::try_from_yaml(
+            &iter_dir.join(defaults::balancing::files::ITERATION_CFG),
+        )?;
+        // path is relative to results-dir
+        writing_cfg.map_file = iter_dir.join(writing_cfg.map_file);
+
+        super::write_graph(graph, &writing_cfg)?;
+
+        // writing edges
+
+        let mut writing_cfg = configs::writing::network::edges::Config::try_from_yaml(
+            &iter_dir.join(defaults::balancing::files::ITERATION_CFG),
+        )?;
+        // path is relative to results-dir
+        writing_cfg.file = iter_dir.join(writing_cfg.file);
+
+        super::write_edges(graph, &writing_cfg)
+    }
+
+    /// Decides, whether the graph is contracted again, and appends the decision to the run's
+    /// metadata.
+    ///
+    /// When skipping the rebuild, the graph's shortcuts are patched and verified with sample
+    /// queries, which may still turn the decision into a rebuild.
+    pub fn decide_ch_rebuild(
+        iter: usize,
+        balancing_cfg: &configs::balancing::Config,
+        graph: &mut Graph,
+        trigger: &mut ch_rebuild::Trigger,
+    ) -> err::Result<ch_rebuild::Decision> {
+        let mut decision = trigger.decide(
+            iter,
+            defaults::balancing::is_using_new_metric(iter, balancing_cfg),
+            graph,
+        )?;
+        if decision == ch_rebuild::Decision::Skip {
+            let metric_idx = graph
+                .cfg()
+                .edges
+                .metrics
+                .try_idx_of(&balancing_cfg.optimization.metric_id)?;
+            graph.recompute_shortcuts(metric_idx);
+            decision =
+                trigger.verify(graph, defaults::balancing::ch_rebuild::VERIFIED_QUERY_COUNT)?;
+        }
+        if let Some(record) = trigger.records().last() {
+            match record.relative_delta {
+                Some(relative_delta) => info!(
+                    "Decided to {} the ch-graph ({}) with a relative delta of max {} and mean {}.",
+                    decision, record.reason, relative_delta.max, relative_delta.mean
+                ),
+                None => info!("Decided to {} the ch-graph ({}).", decision, record.reason),
+            }
+        }
+        trigger.append_last_to(
+            &balancing_cfg
+                .results_dir
+                .join(defaults::balancing::files::METADATA),
+        )?;
+        Ok(decision)
+    }
+
+    pub fn construct_ch_graph(
+        balancing_cfg: &configs::balancing::Config,
+        iter: usize,
+    ) -> err::Feedback {
+        let mut mchc_cfg = balancing_cfg.multi_ch_constructor.clone();
+
+        if !defaults::balancing::is_using_new_metric(iter, balancing_cfg) {
+            mchc_cfg.dim -= 1;
+        }
+
+        let iter_dir = iter_dir(iter, balancing_cfg);
+        mchc_cfg.fmi_graph = iter_dir.join(mchc_cfg.fmi_graph);
+        mchc_cfg.ch_fmi_graph = iter_dir.join(mchc_cfg.ch_fmi_graph);
+
+        mchc_cfg.cost_accuracy = defaults::accuracy::F64_ABS;
+
+        multi_ch_constructor::build(&mchc_cfg)?;
+        multi_ch_constructor::construct_ch_graph(&mchc_cfg)
+    }
+
+    pub fn read_in_ch_graph(
+        balancing_cfg: &configs::balancing::Config,
+        iter: usize,
+    ) -> err::Result<Graph> {
+        // map-files and edges-info.csv are stored relative to the iteration's dir
+        let parsing_cfg =
+            io::balancing::Reader::try_parsing_cfg_of(&iter_dir(iter, balancing_cfg))?;
+        super::parse_graph(parsing_cfg)
+    }
+
+    /// In delta-mode, the base (iteration 0) keeps its full graph and every other iteration
+    /// replaces its graph-files by the delta of the read-in graph's optimized metric.
+    pub fn write_results_format(
+        balancing_cfg: &configs::balancing::Config,
+        ch_graph: &Graph,
+        iter: usize,
+    ) -> err::Feedback {
+        match balancing_cfg.results_format {
+            ResultsFormat::Full => Ok(()),
+            ResultsFormat::Delta => {
+                let iter_dir = iter_dir(iter, balancing_cfg);
+                if iter == defaults::balancing::BASE_ITER {
+                    return io::balancing::Writer::write_base(ch_graph, &iter_dir);
+                }
+
+                io::balancing::Writer::write_delta(iter, ch_graph, &iter_dir, balancing_cfg)?;
+
+                let writing_cfg = configs::writing::network::graph::Config::try_from_yaml(
+                    &iter_dir.join(defaults::balancing::files::ITERATION_CFG),
+                )?;
+                for map_file in &[
+                    iter_dir.join(writing_cfg.map_file),
+                    iter_dir.join(&balancing_cfg.multi_ch_constructor.ch_fmi_graph),
+                ] {
+                    if map_file.exists() {
+                        fs::remove_file(map_file)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn read_in_routing_cfg(
+        balancing_cfg: &configs::balancing::Config,
+        iter: usize,
+        raw_routing_cfg: &Path,
+        ch_graph: &Graph,
+    ) -> err::Result<configs::routing::Config> {
+        // read in routing-cfg and
+
+        let mut routing_cfg =
+            configs::routing::Config::try_from_yaml_for_graph(raw_routing_cfg, ch_graph)?;
+        routing_cfg.validate(ch_graph)?;
+        let old_route_pairs_file = routing_cfg.route_pairs_file.ok_or(err::Msg::from(
+            "Please provide a route-pairs-file in your (routing-)config.",
+        ))?;
+        let new_route_pairs_file =
+            balancing_cfg
+                .results_dir
+                .join(old_route_pairs_file.file_name().ok_or(err::Msg::from(
+                    "The provided route-pairs-file in the (routing-)config is not a file.",
+                ))?);
+
+        // if first iteration without warm-start
+        if !defaults::balancing::is_using_new_metric(iter, balancing_cfg) {
+            // -> deactivate workload-metric
+
+            // The 'new_metric' is probably workload or something related to it.
+            let new_metric_id = ch_graph
+                .cfg()
+                .edges
+                .metrics
+                .try_idx_of(&balancing_cfg.optimization.metric_id)?;
+            routing_cfg.alphas[*new_metric_id] = 0.0;
+        }
+
+        // if first iteration
+        if iter == 0 {
+            // -> copy route-pairs-file into the results-directory
+            match fs::copy(&old_route_pairs_file, &new_route_pairs_file) {
+                Ok(_) => (),
+                Err(e) => {
+                    return Err(format!(
+                        "Couldn't copy {} due to error: {}",
+                        old_route_pairs_file.display(),
+                        e
+                    )
+                    .into())
+                }
+            };
+        }
+
+        routing_cfg.route_pairs_file = Some(new_route_pairs_file);
+
+        // remember the effective routing-config, since alphas could have been changed
+        let routing_meta_file =
+            iter_dir(iter, balancing_cfg).join(defaults::balancing::files::ROUTING_META);
+        if let Err(e) = fs::write(
+            &routing_meta_file,
+            format!("{}\n", routing_cfg.to_embedded(ch_graph.cfg())),
+        ) {
+            return Err(format!(
+                "Couldn't write {} due to error: {}",
+                routing_meta_file.display(),
+                e
+            )
+            .into());
+        }
+
+        Ok(routing_cfg)
+    }
+
+    /// Maps every wanted route-pair to the index of its first occurrence in the route-pairs.
+    /// Only the chosen paths' route-pairs are wanted, so the map is bounded by the chosen paths,
+    /// which are kept anyway, instead of growing with all (streamed) route-pairs.
+    fn pair_indices_of<I>(
+        route_pairs: I,
+        wanted_pairs: &BTreeSet<(i64, i64)>,
+    ) -> err::Result<BTreeMap<(i64, i64), usize>>
+    where
+        I: Iterator<Item = err::Result<(RoutePair<i64>, f64)>>,
+    {
+        let mut pair_indices = BTreeMap::new();
+        for (pair_idx, route_pair) in route_pairs.enumerate() {
+            let (route_pair, _count) = route_pair?;
+            let pair = (route_pair.src, route_pair.dst);
+            if wanted_pairs.contains(&pair) {
+                pair_indices.entry(pair).or_insert(pair_idx);
+            }
+        }
+        Ok(pair_indices)
+    }
+
+    pub fn balance(
+        iter: usize,
+        balancing_cfg: &configs::balancing::Config,
+        arc_ch_graph: &mut Arc<Graph>,
+        arc_routing_cfg: &Arc<configs::routing::Config>,
+        rng: &mut rand_pcg::Lcg64Xsh32,
+        prev_polylines: &mut Option<Polylines>,
+        route_memory: &mut Option<RouteMemory>,
+        timer: &mut PhaseTimer,
+        observer: &mut dyn BalancingObserver,
+    ) -> err::Feedback {
+        info!(
+            "Balance via explorating several routes for metrics {:?}x{:?}",
+            arc_ch_graph.cfg().edges.metrics.units,
+            arc_routing_cfg.alphas,
+        );
+
+        timer.start(Phase::SampleAndFlatten);
+        // Huge route-pairs-files (by their header) are streamed, hence re-read in every iteration,
+        // while small files and coordinates (which need snapping) are kept in memory.
+        // Edge-users refer to the route-pairs by their index (of the first occurrence), which is
+        // looked up after routing (see `pair_indices_of(...)`).
+        let stream = match io::routing::Parser::stream(&arc_routing_cfg) {
+            Ok(stream) if stream.total_count() > defaults::balancing::MAX_IN_MEMORY_ROUTE_PAIRS => {
+                Some(stream)
+            }
+            _ => None,
+        };
+        let (route_pairs, num_of_route_pairs, kept_route_pairs): (RoutePairs, _, _) = match stream {
+            Some(stream) => {
+                info!(
+                    "Stream {} route-pairs instead of keeping them in memory.",
+                    stream.total_count()
+                );
+                let num_of_route_pairs = stream.total_count();
+                let route_pairs = defaults::balancing::sample_route_counts_lazily(
+                    stream,
+                    iter,
+                    &balancing_cfg.demand_sampling,
+                );
+                (route_pairs, num_of_route_pairs, None)
+            }
+            None => {
+                let parsed_route_pairs =
+                    io::routing::Parser::parse_and_snap(&arc_routing_cfg, &arc_ch_graph)?;
+                let route_pairs = defaults::balancing::sample_route_counts(
+                    &parsed_route_pairs,
+                    iter,
+                    &balancing_cfg.demand_sampling,
+                );
+                let num_of_route_pairs = route_pairs.len();
+                // kept for looking up the edge-users' route-pairs
+                let kept_route_pairs = balancing_cfg
+                    .edge_users_tracking
+                    .as_ref()
+                    .map(|_| parsed_route_pairs);
+                (
+                    Box::new(route_pairs.into_iter().map(Ok)),
+                    num_of_route_pairs,
+                    kept_route_pairs,
+                )
+            }
+        };
+
+        // the route-pairs' edges of the previous iteration get a bonus,
+        // while this iteration's edges are remembered for the next one
+
+        let stabilization = match balancing_cfg.route_stabilization {
+            Some(stabilization) => match arc_routing_cfg.routing_algo {
+                RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => Some((
+                    stabilization.bonus,
+                    Arc::new(route_memory.take().unwrap_or_default()),
+                )),
+                RoutingAlgo::Explorator { .. } => {
+                    warn!(
+                        "Stabilizing routes is only supported by Dijkstra and CHDijkstra, \
+                         hence it is ignored."
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        let mut new_route_memory = stabilization.as_ref().map(|_| RouteMemory::new());
+
+        timer.start(Phase::Explorate);
+        let mut master = multithreading::Master::spawn_some(
+            balancing_cfg.num_threads,
+            &arc_ch_graph,
+            &arc_routing_cfg,
+            stabilization,
+        )?;
+        let (mut abs_workloads, chosen_paths) = master.work_off(
+            route_pairs,
+            num_of_route_pairs,
+            &arc_ch_graph,
+            rng,
+            balancing_cfg.monitoring.is_writing_for_smarts
+                || balancing_cfg.monitoring.is_writing_path_stability
+                || balancing_cfg.edge_users_tracking.is_some(),
+            &balancing_cfg.demand_sampling,
+            timer,
+            new_route_memory.as_mut(),
+            observer,
+        )?;
+        *route_memory = new_route_memory;
+        // custom changes of the workloads affect every following step
+        observer.on_workload_computed(&mut abs_workloads);
+
+        // compare chosen paths with the previous iteration's ones
+        // before the graph is updated
+
+        if balancing_cfg.monitoring.is_writing_path_stability {
+            timer.start(Phase::IoWrite);
+            let mut polylines = Polylines::new();
+            if let Some(chosen_paths) = &chosen_paths {
+                // route-pairs could be sampled several times -> take the first one
+                for (path, _count) in chosen_paths {
+                    polylines
+                        .entry((path.src_id(), path.dst_id()))
+                        .or_insert_with(|| similarity::polyline(path, &arc_ch_graph));
+                }
+            }
+
+            if let Some(prev_polylines) = prev_polylines {
+                super::write_path_stability(
+                    &polylines,
+                    prev_polylines,
+                    &iter_dir(iter, balancing_cfg).join(defaults::balancing::files::PATH_STABILITY),
+                )?;
+            }
+            *prev_polylines = Some(polylines);
+        }
+
+        // remember the top route-pairs of highly loaded edges
+
+        if let (Some(tracking), Some(chosen_paths)) =
+            (&balancing_cfg.edge_users_tracking, &chosen_paths)
+        {
+            timer.start(Phase::IoWrite);
+            let chosen_pairs: BTreeSet<_> = chosen_paths
+                .iter()
+                .map(|(path, _count)| (path.src_id(), path.dst_id()))
+                .collect();
+            let pair_indices = match &kept_route_pairs {
+                Some(kept_route_pairs) => pair_indices_of(
+                    kept_route_pairs.iter().map(|&route_pair| Ok(route_pair)),
+                    &chosen_pairs,
+                )?,
+                // streamed route-pairs are re-read
+                None => pair_indices_of(
+                    io::routing::Parser::stream(&arc_routing_cfg)?,
+                    &chosen_pairs,
+                )?,
+            };
+            let edge_users =
+                EdgeUsers::from_paths(chosen_paths, &abs_workloads, &pair_indices, tracking);
+            info!(
+                "Tracked the top-{} route-pairs of {} edges.",
+                tracking.top_k,
+                edge_users.len()
+            );
+            edge_users.write_csv(
+                &arc_ch_graph,
+                &iter_dir(iter, balancing_cfg).join(defaults::balancing::files::EDGE_USERS),
+            )?;
+        }
+
+        // update graph with new values
+        timer.start(Phase::MetricWrite);
+        defaults::balancing::update_new_metric(
+            iter,
+            &abs_workloads,
+            Arc::get_mut(arc_ch_graph).expect(
+                "Mutable access to graph should be possible, since Arc should be the only owner.",
+            ),
+            &balancing_cfg,
+        )?;
+
+        // export density and iteration-results
+
+        // measure writing-time
+        timer.start(Phase::IoWrite);
+        let now = Instant::now();
+
+        // write results from this iteration
+
+        let stats_dir = iter_dir(iter, balancing_cfg).join(defaults::balancing::stats::DIR);
+
+        let writing_cfg = configs::evaluating_balance::Config {
+            seed: balancing_cfg.seed,
+            results_dir: stats_dir.clone(),
+            monitoring: balancing_cfg.monitoring.clone(),
+            num_threads: balancing_cfg.num_threads,
+        };
+        io::evaluating_balance::Writer::write(&abs_workloads, &arc_ch_graph, &writing_cfg)?;
+        // write SMARTS-paths
+        if let Some(chosen_paths) = chosen_paths {
+            if balancing_cfg.monitoring.is_writing_for_smarts {
+                let tmp_cfg = configs::writing::smarts::Config {
+                    file: writing_cfg
+                        .results_dir
+                        .join(defaults::smarts::XML_FILE_NAME),
+                };
+                io::smarts::Writer::write(
+                    &super::vehicles_for_smarts(&chosen_paths),
+                    &arc_ch_graph,
+                    &tmp_cfg,
+                )?;
+            }
+        }
+
+        info!(
+            "FINISHED Written in {} seconds ({} µs).",
+            now.elapsed().as_secs(),
+            now.elapsed().as_micros(),
+        );
+        info!("");
+
+        Ok(())
+    }
+}
+
+// utils
+
+fn parse_graph(parsing_cfg: configs::parsing::Config) -> err::Result<Graph> {
+    Ok(io::load(configs::bundle::Config::new(parsing_cfg))?.graph)
+}
+
+fn write_graph(
+    graph: &Graph,
+    writing_cfg: &configs::writing::network::graph::Config,
+) -> err::Feedback {
+    // check if new file can be written
+
+    writing_cfg.validate(graph.cfg())?;
+
+    // writing to file
+
+    let now = Instant::now();
+
+    io::network::graph::Writer::write(&graph, &writing_cfg)?;
+    info!(
+        "Finished writing in {} seconds ({} µs).",
+        now.elapsed().as_secs(),
+        now.elapsed().as_micros(),
+    );
+    info!("");
+
+    Ok(())
+}
+
+fn write_edges(
+    graph: &Graph,
+    writing_cfg: &configs::writing::network::edges::Config,
+) -> err::Feedback {
+    // check if new file can be written
+
+    writing_cfg.validate(graph.cfg())?;
+
+    // writing to file
+
+    let now = Instant::now();
+
+    io::network::edges::Writer::write(&graph, &writing_cfg)?;
+    info!(
+        "Finished writing in {} seconds ({} µs).",
+        now.elapsed().as_secs(),
+        now.elapsed().as_micros(),
+    );
+    info!("");
+
+    Ok(())
+}
+
+/// SMARTS simulates single vehicles, hence every chosen path is repeated according to its rounded count.
+pub fn vehicles_for_smarts(chosen_paths: &[(RoutingPath, f64)]) -> Vec<RoutingPath> {
+    chosen_paths
+        .iter()
+        .flat_map(|(path, count)| std::iter::repeat(path.clone()).take(count.round() as usize))
+        .collect()
+}
+
+/// Writes the Fréchet-distance (in meters) of every route-pair, which has been chosen in both iterations.
+fn write_path_stability(
+    polylines: &Polylines,
+    prev_polylines: &Polylines,
+    file: &Path,
+) -> err::Feedback {
+    let mut writer = io::files::AtomicFile::create(file)?;
+
+    // write header
+
+    writeln!(writer, "src-id dst-id frechet_m")?;
+
+    // write values
+
+    for ((src_id, dst_id), polyline) in polylines {
+        if let Some(prev_polyline) = prev_polylines.get(&(*src_id, *dst_id)) {
+            let frechet = similarity::discrete_frechet(prev_polyline, polyline);
+            writeln!(
+                writer,
+                "{} {} {}",
+                src_id,
+                dst_id,
+                io::fmt::float(frechet, None)
+            )?;
+        }
+    }
+
+    writer.commit()
+}
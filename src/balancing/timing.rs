@@ -0,0 +1,228 @@
+use crate::{helpers::err, io};
+use log::info;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// The phases of a balancer's iteration, in which the wall-clock-time of an iteration is split.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// Building the contraction-hierarchy and reading in the contracted graph
+    Contraction,
+    /// Routing (e.g. explorating) the route-pairs
+    Explorate,
+    /// Sampling the route-counts and flattening the chosen paths
+    SampleAndFlatten,
+    /// Adding the chosen paths' counts to the workloads
+    WorkloadUpdate,
+    /// Writing the new metric into the graph
+    MetricWrite,
+    /// Writing graphs, configs and stats into the results-dir
+    IoWrite,
+}
+
+impl Phase {
+    pub fn all() -> [Phase; 6] {
+        [
+            Phase::Contraction,
+            Phase::Explorate,
+            Phase::SampleAndFlatten,
+            Phase::WorkloadUpdate,
+            Phase::MetricWrite,
+            Phase::IoWrite,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Contraction => "contraction",
+            Phase::Explorate => "explorate",
+            Phase::SampleAndFlatten => "sample-and-flatten",
+            Phase::WorkloadUpdate => "workload-update",
+            Phase::MetricWrite => "metric-write",
+            Phase::IoWrite => "io-write",
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The timing of a finished iteration.
+///
+/// The total is measured independently of the phases, hence it contains the time between phases as well.
+#[derive(Clone, Debug)]
+pub struct IterationTiming {
+    pub iter: usize,
+    pub phases: BTreeMap<Phase, Duration>,
+    pub total: Duration,
+}
+
+impl IterationTiming {
+    pub fn phase(&self, phase: Phase) -> Duration {
+        self.phases
+            .get(&phase)
+            .cloned()
+            .unwrap_or(Duration::from_secs(0))
+    }
+
+    pub fn phase_sum(&self) -> Duration {
+        self.phases.values().sum()
+    }
+}
+
+impl Display for IterationTiming {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Iteration {} took {:.3} s",
+            self.iter,
+            self.total.as_secs_f64()
+        )?;
+        for (i, phase) in Phase::all().iter().enumerate() {
+            write!(
+                f,
+                "{}{}={:.3} s",
+                if i == 0 { " (" } else { ", " },
+                phase,
+                self.phase(*phase).as_secs_f64()
+            )?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Accumulates the wall-clock-time of phases per iteration.
+///
+/// At most one phase is running at a time, so starting a phase stops the running one.
+/// Phases can be started several times per iteration, e.g. alternating when working off routes.
+///
+/// With the feature `tracing`, iterations and phases are entered as spans
+/// `balancing::iteration` and `balancing::phase` as well.
+#[derive(Debug, Default)]
+pub struct PhaseTimer {
+    iterations: Vec<IterationTiming>,
+    current_iter: Option<(usize, Instant)>,
+    current_phase: Option<(Phase, Instant)>,
+    phases: BTreeMap<Phase, Duration>,
+    #[cfg(feature = "tracing")]
+    iter_span: Option<tracing_lib::span::EnteredSpan>,
+    #[cfg(feature = "tracing")]
+    phase_span: Option<tracing_lib::span::EnteredSpan>,
+}
+
+impl PhaseTimer {
+    pub fn new() -> PhaseTimer {
+        PhaseTimer::default()
+    }
+
+    pub fn start_iteration(&mut self, iter: usize) {
+        if let Some((running_iter, _)) = self.current_iter {
+            panic!(
+                "Iteration {} has to be finished before starting iteration {}.",
+                running_iter, iter
+            );
+        }
+
+        self.phases.clear();
+        self.current_iter = Some((iter, Instant::now()));
+        #[cfg(feature = "tracing")]
+        {
+            self.iter_span = Some(tracing_lib::info_span!("balancing::iteration", iter).entered());
+        }
+    }
+
+    /// Stops the running phase (if any) and starts the given one.
+    pub fn start(&mut self, phase: Phase) {
+        let now = Instant::now();
+        self.stop_at(now);
+        self.current_phase = Some((phase, now));
+        #[cfg(feature = "tracing")]
+        {
+            self.phase_span =
+                Some(tracing_lib::info_span!("balancing::phase", phase = phase.name()).entered());
+        }
+    }
+
+    /// Stops the running phase (if any).
+    pub fn stop(&mut self) {
+        self.stop_at(Instant::now());
+    }
+
+    fn stop_at(&mut self, now: Instant) {
+        if let Some((phase, start)) = self.current_phase.take() {
+            *self.phases.entry(phase).or_insert(Duration::from_secs(0)) += now - start;
+        }
+        #[cfg(feature = "tracing")]
+        {
+            self.phase_span = None;
+        }
+    }
+
+    /// Stops the running phase and records the iteration, which is logged as one-line summary.
+    pub fn finish_iteration(&mut self) -> &IterationTiming {
+        let now = Instant::now();
+        self.stop_at(now);
+        let (iter, start) = self
+            .current_iter
+            .take()
+            .expect("An iteration has to be started before finishing it.");
+        #[cfg(feature = "tracing")]
+        {
+            self.iter_span = None;
+        }
+
+        let mut phases = BTreeMap::new();
+        std::mem::swap(&mut phases, &mut self.phases);
+        self.iterations.push(IterationTiming {
+            iter,
+            phases,
+            total: now - start,
+        });
+
+        let timing = self
+            .iterations
+            .last()
+            .expect("The finished iteration has just been pushed.");
+        info!("{}", timing);
+        timing
+    }
+
+    pub fn iterations(&self) -> &[IterationTiming] {
+        &self.iterations
+    }
+
+    /// Writes one row per phase and finished iteration, containing the iteration's total as well.
+    pub fn write_csv(&self, file: &Path) -> err::Feedback {
+        // rewritten every iteration, hence replaced atomically
+        let mut writer = io::files::AtomicFile::replace(file)?;
+
+        // write header
+
+        writeln!(writer, "iteration phase phase_us iteration_us")?;
+
+        // write values
+
+        for timing in &self.iterations {
+            for phase in Phase::all().iter() {
+                writeln!(
+                    writer,
+                    "{} {} {} {}",
+                    timing.iter,
+                    phase,
+                    timing.phase(*phase).as_micros(),
+                    timing.total.as_micros()
+                )?;
+            }
+        }
+
+        writer.commit()
+    }
+}
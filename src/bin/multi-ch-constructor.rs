@@ -1,6 +1,6 @@
 use log::{error, info};
 use osmgraphing::{
-    helpers::{err, init_logging},
+    helpers::{err, init_logging, init_tracing},
     multi_ch_constructor::{self, Config},
 };
 
@@ -11,6 +11,12 @@ fn main() {
         error!("{}{}", msg, "\n");
         panic!("{}", msg);
     }
+    if args.is_tracing {
+        if let Err(msg) = init_tracing() {
+            error!("{}{}", msg, "\n");
+            panic!("{}", msg);
+        }
+    }
     let result = run(args);
     if let Err(msg) = result {
         error!("{}{}", msg, "\n");
@@ -48,6 +54,14 @@ fn parse_cmdline<'a>() -> CmdlineArgs {
         .default_value("INFO")
         .possible_values(&vec!["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]);
 
+    let arg_is_tracing = clap::Arg::with_name(constants::ids::IS_TRACING)
+        .long("trace")
+        .help(
+            "Prints spans of the major phases with their durations to stderr, e.g. for \
+            profiling. Needs the cargo-feature 'tracing'.",
+        )
+        .takes_value(false);
+
     let arg_cfg = clap::Arg::with_name(constants::ids::CFG)
         .long("config")
         .short("c")
@@ -76,6 +90,7 @@ fn parse_cmdline<'a>() -> CmdlineArgs {
                 .as_ref(),
         )
         .arg(arg_log_level)
+        .arg(arg_is_tracing)
         .arg(arg_cfg)
         .get_matches()
         .into()
@@ -84,12 +99,14 @@ fn parse_cmdline<'a>() -> CmdlineArgs {
 mod constants {
     pub mod ids {
         pub const MAX_LOG_LEVEL: &str = "max-log-level";
+        pub const IS_TRACING: &str = "is_tracing";
         pub const CFG: &str = "cfg";
     }
 }
 
 struct CmdlineArgs {
     max_log_level: String,
+    is_tracing: bool,
     cfg: String,
 }
 
@@ -98,12 +115,14 @@ impl<'a> From<clap::ArgMatches<'a>> for CmdlineArgs {
         let max_log_level = matches
             .value_of(constants::ids::MAX_LOG_LEVEL)
             .expect(&format!("cmdline-arg: {}", constants::ids::MAX_LOG_LEVEL));
+        let is_tracing = matches.is_present(constants::ids::IS_TRACING);
         let cfg = matches
             .value_of(constants::ids::CFG)
             .expect(&format!("cmdline-arg: {}", constants::ids::CFG));
 
         CmdlineArgs {
             max_log_level: String::from(max_log_level),
+            is_tracing,
             cfg: String::from(cfg),
         }
     }
@@ -0,0 +1,79 @@
+use log::{info, warn};
+use osmgraphing::{
+    balancing::{BalancingObserver, Runner},
+    helpers::err,
+    network::{Graph, RoutePair},
+    routing::paths::Path,
+};
+use std::time::Instant;
+
+pub fn run(cfg: &str) -> err::Feedback {
+    Runner::new(cfg)
+        .with_observer(LoggingObserver::new())
+        .run()?;
+    Ok(())
+}
+
+/// Logs a summary of every iteration.
+struct LoggingObserver {
+    iter: usize,
+    start: Instant,
+    num_of_route_pairs: usize,
+    num_of_unrouted_pairs: usize,
+}
+
+impl LoggingObserver {
+    fn new() -> LoggingObserver {
+        LoggingObserver {
+            iter: 0,
+            start: Instant::now(),
+            num_of_route_pairs: 0,
+            num_of_unrouted_pairs: 0,
+        }
+    }
+}
+
+impl BalancingObserver for LoggingObserver {
+    fn on_iteration_start(&mut self, iter: usize) {
+        info!("START Balancer-iteration {}", iter);
+        self.iter = iter;
+        self.start = Instant::now();
+        self.num_of_route_pairs = 0;
+        self.num_of_unrouted_pairs = 0;
+    }
+
+    fn on_pair_routed(
+        &mut self,
+        _route_pair: &RoutePair<i64>,
+        paths: &[Path],
+        _chosen: &[(usize, f64)],
+    ) {
+        self.num_of_route_pairs += 1;
+        if paths.is_empty() {
+            self.num_of_unrouted_pairs += 1;
+        }
+    }
+
+    fn on_workload_computed(&mut self, abs_workloads: &mut [f64]) {
+        let total: f64 = abs_workloads.iter().sum();
+        let max = abs_workloads.iter().cloned().fold(0.0, f64::max);
+        info!(
+            "Routed {} route-pairs, resulting in a total workload of {} (max {} per edge).",
+            self.num_of_route_pairs, total, max
+        );
+        if self.num_of_unrouted_pairs > 0 {
+            warn!(
+                "{} route-pairs don't have any path.",
+                self.num_of_unrouted_pairs
+            );
+        }
+    }
+
+    fn on_iteration_end(&mut self, _graph: &Graph) {
+        info!(
+            "FINISHED Balancer-iteration {} in {} seconds.",
+            self.iter,
+            self.start.elapsed().as_secs()
+        );
+    }
+}
@@ -1,18 +1,31 @@
 use log::{debug, error, info, warn};
 #[cfg(feature = "gpl")]
 mod balancing;
+#[cfg(feature = "gpl")]
+use osmgraphing::{
+    approximating::Approx,
+    balancing::{multithreading, timing::PhaseTimer, vehicles_for_smarts, NoObserver},
+    helpers,
+    routing::{explorating::ConvexHullExplorator, paths},
+};
 use osmgraphing::{
+    cli::{self, CmdlineArgs, Command},
     configs::{self, routing::RoutingAlgo},
-    helpers::{err, init_logging},
+    defaults,
+    helpers::{err, init_logging, init_tracing},
     io,
     network::{Graph, RoutePair},
-    routing::dijkstra::{self, Dijkstra},
+    routing::{
+        dijkstra::{self, Dijkstra},
+        verification,
+    },
 };
 #[cfg(feature = "gpl")]
-use osmgraphing::{defaults, routing::explorating::ConvexHullExplorator};
-#[cfg(feature = "gpl")]
 use rand::SeedableRng;
-use std::{convert::TryFrom, path::PathBuf, time::Instant};
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
 #[cfg(feature = "gpl")]
 use std::{fs, sync::Arc};
 
@@ -37,7 +50,7 @@ use std::{fs, sync::Arc};
 //------------------------------------------------------------------------------------------------//
 
 fn main() {
-    let args = match parse_cmdline() {
+    let args = match CmdlineArgs::try_from(cli::app().get_matches()) {
         Ok(args) => args,
         Err(msg) => {
             println!("ERROR: {}", msg);
@@ -50,6 +63,12 @@ fn main() {
         error!("{}{}", msg, "\n");
         panic!("{}", msg);
     }
+    if args.is_tracing {
+        if let Err(msg) = init_tracing() {
+            error!("{}{}", msg, "\n");
+            panic!("{}", msg);
+        }
+    }
     let result = run(args);
     if let Err(msg) = result {
         error!("{}{}", msg, "\n");
@@ -60,157 +79,283 @@ fn main() {
 fn run(args: CmdlineArgs) -> err::Feedback {
     info!("EXECUTE {}", env!("CARGO_PKG_NAME"));
 
-    // parse graph
+    // the balancer parses its graphs on its own
 
-    let graph = {
-        // get config by provided user-input
-
-        let parsing_cfg = {
-            let raw_parsing_cfg = PathBuf::from(args.cfg.clone());
-            configs::parsing::Config::try_from_yaml(&raw_parsing_cfg)?
-        };
+    if let Command::Balance = args.command {
+        #[cfg(feature = "gpl")]
+        balancing::run(&args.cfg)?;
+        return Ok(());
+    }
 
-        // parse and create graph
+    // print the parsing-config as it would be parsed, e.g. with expanded profile
 
-        // measure parsing-time
-        let now = Instant::now();
-        let graph = io::network::graph::Parser::parse_and_finalize(parsing_cfg)?;
-        debug!(
-            "Finished parsing in {} seconds ({} µs).",
-            now.elapsed().as_secs(),
-            now.elapsed().as_micros(),
+    if args.is_printing_effective_cfg {
+        print!(
+            "{}",
+            configs::parsing::profiles::try_expand_yaml(&args.cfg)?
         );
-        debug!("");
-        debug!("{}", graph);
-        debug!("");
+        return Ok(());
+    }
 
-        graph
-    };
+    // validate writing-configs before parsing, since parsing is expensive
+    // (ids are validated against the parsed graph)
+
+    match &args.command {
+        Command::WriteGraph => {
+            configs::writing::network::graph::Config::try_from_yaml(&args.cfg)?.validate_files()?
+        }
+        Command::WriteEdges => {
+            configs::writing::network::edges::Config::try_from_yaml(&args.cfg)?.validate_files()?
+        }
+        Command::WriteSimpleFmi => {
+            configs::writing::network::simple_fmi::Config::try_from_yaml(&args.cfg)?
+                .validate_files()?
+        }
+        Command::WriteGeojson => {
+            configs::writing::network::geojson::Config::try_from_yaml(&args.cfg)?
+                .validate_files()?
+        }
+        Command::WriteRoutes { profile: _ } => {
+            configs::writing::routing::Config::try_from_yaml(&args.cfg)?.validate()?
+        }
+        _ => (),
+    }
+
+    // parse graph and, when routing, the route-pairs
 
-    // writing built graph
+    let mut bundle_cfg = configs::bundle::Config::try_from_yaml(&args.cfg)?;
+    if args.is_ignoring_embedded_cfg {
+        bundle_cfg.parsing.is_ignoring_embedded_cfg = true;
+    }
+    if let Command::Route {
+        profile,
+        timeout: _,
+    } = &args.command
+    {
+        bundle_cfg = bundle_cfg.with_routing(&args.cfg, profile.clone());
+    }
+    let io::Artifacts {
+        graph,
+        routing_cfg,
+        route_pairs,
+        spatial_index: _,
+    } = io::load(bundle_cfg)?;
+
+    match &args.command {
+        Command::Parse => cli::log_stats(&graph),
+        Command::WriteGraph => write_graph(&args, &graph)?,
+        Command::WriteEdges => write_edges(&args, &graph)?,
+        Command::WriteSimpleFmi => write_simple_fmi(&args, &graph)?,
+        Command::WriteGeojson => write_geojson(&args, &graph)?,
+        Command::WriteRoutes { profile } => write_route_pairs(&args, profile, &graph)?,
+        Command::Route {
+            profile: _,
+            timeout,
+        } => {
+            let routing_cfg = routing_cfg.expect("Routing should have been loaded.");
+            let route_pairs = route_pairs.expect("Route-pairs should have been loaded.");
+            let timed_out_count = do_simply_routing(&routing_cfg, &route_pairs, timeout, &graph)?;
+            if timed_out_count > 0 {
+                error!("{} queries have timed out.", timed_out_count);
+                std::process::exit(cli::constants::exit_codes::TIMEOUT);
+            }
+        }
+        Command::Verify {
+            profile,
+            reference_file,
+        } => verify(&args, profile, reference_file, &graph)?,
+        Command::Balance => (),
+        #[cfg(feature = "gpl")]
+        Command::EvaluateBalance { profile } => {
+            do_evaluating_routing(&args, profile, &Arc::new(graph))?
+        }
+        #[cfg(not(feature = "gpl"))]
+        Command::EvaluateBalance { profile: _ } => (),
+    }
 
-    if args.is_writing_graph {
-        // get config by provided user-input
+    Ok(())
+}
 
-        let writing_cfg = configs::writing::network::graph::Config::try_from_yaml(&args.cfg)?;
+fn write_graph(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
+    // get config by provided user-input
 
-        // check if new file does already exist
+    let writing_cfg = configs::writing::network::graph::Config::try_from_yaml(&args.cfg)?;
 
-        if writing_cfg.map_file.exists() {
-            return Err(err::Msg::from(format!(
-                "New map-file {} does already exist. Please remove it.",
-                writing_cfg.map_file.display()
-            )));
-        }
+    // check if new file can be written
 
-        // writing to file
-        io::network::graph::Writer::write(&graph, &writing_cfg)?;
-    }
+    writing_cfg.validate(graph.cfg())?;
 
-    // writing edges to file
+    // writing to file
+    io::network::graph::Writer::write(graph, &writing_cfg)
+}
 
-    if args.is_writing_edges {
-        // get config by provided user-input
+fn write_simple_fmi(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
+    // get config by provided user-input
 
-        let writing_cfg = configs::writing::network::edges::Config::try_from_yaml(&args.cfg)?;
+    let writing_cfg = configs::writing::network::simple_fmi::Config::try_from_yaml(&args.cfg)?;
 
-        // check if new file does already exist
+    // check if new file can be written and if the graph has the weight
 
-        if writing_cfg.file.exists() {
-            return Err(err::Msg::from(format!(
-                "New file {} does already exist. Please remove it.",
-                writing_cfg.file.display()
-            )));
-        }
+    writing_cfg.validate(graph.cfg())?;
 
-        // writing to file
+    // writing to file
+    io::network::simple_fmi::Writer::write(graph, &writing_cfg)
+}
 
-        // measure writing-time
-        let now = Instant::now();
-        io::network::edges::Writer::write(&graph, &writing_cfg)?;
-        debug!(
-            "Finished writing in {} seconds ({} µs).",
-            now.elapsed().as_secs(),
-            now.elapsed().as_micros(),
-        );
-        debug!("");
-    }
+fn write_geojson(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
+    // get config by provided user-input
 
-    // writing routes to file
+    let writing_cfg = configs::writing::network::geojson::Config::try_from_yaml(&args.cfg)?;
 
-    if args.is_writing_route_pairs {
-        // get config by provided user-input
+    // check if new file can be written
 
-        let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
-        let writing_cfg = configs::writing::routing::Config::try_from_yaml(&args.cfg)?;
+    writing_cfg.validate_files()?;
 
-        // check if new file does already exist
+    // writing to file
+    io::network::geojson::Writer::write(graph, &writing_cfg)
+}
 
-        if writing_cfg.file.exists() {
-            return Err(err::Msg::from(format!(
-                "New routes-file {} does already exist. Please remove it.",
-                writing_cfg.file.display()
-            )));
-        }
+fn write_edges(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
+    // get config by provided user-input
 
-        // writing to file
+    let writing_cfg = configs::writing::network::edges::Config::try_from_yaml(&args.cfg)?;
 
-        // measure writing-time
-        let now = Instant::now();
-        io::routing::Writer::write(&graph, &routing_cfg, &writing_cfg)?;
-        debug!(
-            "Finished writing in {} seconds ({} µs).",
-            now.elapsed().as_secs(),
-            now.elapsed().as_micros(),
-        );
-        debug!("");
-    }
+    // check if new file can be written
 
-    // routing-example
+    writing_cfg.validate(graph.cfg())?;
 
-    if args.is_routing || args.is_evaluating_balance {
-        if !args.is_evaluating_balance {
-            do_simply_routing(&args, &graph)?;
-        } else {
-            #[cfg(feature = "gpl")]
-            do_evaluating_routing(&args, &Arc::new(graph))?;
-        }
-    }
+    // writing to file
 
-    #[cfg(feature = "gpl")]
-    if args.is_balancing {
-        balancing::run(balancing::CmdlineArgs {
-            max_log_level: args.max_log_level.clone(),
-            cfg: args.cfg.clone(),
-        })?;
-    }
+    // measure writing-time
+    let now = Instant::now();
+    io::network::edges::Writer::write(graph, &writing_cfg)?;
+    debug!(
+        "Finished writing in {} seconds ({} µs).",
+        now.elapsed().as_secs(),
+        now.elapsed().as_micros(),
+    );
+    debug!("");
 
     Ok(())
 }
 
-fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
+fn write_route_pairs(args: &CmdlineArgs, profile: &Option<String>, graph: &Graph) -> err::Feedback {
     // get config by provided user-input
-    let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
+
+    let routing_cfg = routing_cfg_from(args, profile, graph)?;
+    let writing_cfg = configs::writing::routing::Config::try_from_yaml(&args.cfg)?;
+
+    // check if new file can be written
+
+    writing_cfg.validate()?;
+
+    // writing to file
+
+    // measure writing-time
+    let now = Instant::now();
+    io::routing::Writer::write(graph, &routing_cfg, &writing_cfg)?;
+    debug!(
+        "Finished writing in {} seconds ({} µs).",
+        now.elapsed().as_secs(),
+        now.elapsed().as_micros(),
+    );
+    debug!("");
+
+    Ok(())
+}
+
+/// Verifies routes against a reference.
+fn verify(
+    args: &CmdlineArgs,
+    profile: &Option<String>,
+    reference_file: &str,
+    graph: &Graph,
+) -> err::Feedback {
+    let routing_cfg = routing_cfg_from(args, profile, graph)?;
+    let report = verification::verify(
+        reference_file,
+        graph,
+        &routing_cfg,
+        defaults::routing::verification::TOLERANCE,
+    )?;
+    info!("{}", report);
+    if report.failed_count() > 0 {
+        warn!(
+            "{} of {} verified route(s) deviate from the reference.",
+            report.failed_count(),
+            report.pairs.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Takes the routing-profile chosen by the user, or the default-profile otherwise, and validates
+/// it against the graph.
+fn routing_cfg_from(
+    args: &CmdlineArgs,
+    profile: &Option<String>,
+    graph: &Graph,
+) -> err::Result<configs::routing::Config> {
+    let profiles = configs::routing::Profiles::try_from_yaml_for_graph(&args.cfg, graph)?;
+    let mut routing_cfg = match profile {
+        Some(name) => profiles.try_get(name)?,
+        None => profiles.default_profile(),
+    }
+    .clone();
+    routing_cfg.validate(graph)?;
+    Ok(routing_cfg)
+}
+
+/// Returns the number of queries, which have timed out.
+fn do_simply_routing(
+    routing_cfg: &configs::routing::Config,
+    route_pairs: &[(RoutePair<i64>, f64)],
+    timeout: &Option<Duration>,
+    graph: &Graph,
+) -> err::Result<usize> {
     info!("EXECUTE Do routing with alphas: {:?}", routing_cfg.alphas);
 
-    // get routing-pairs
-    let routing_pairs = io::routing::Parser::parse(&routing_cfg)?;
-    let iter_route_pairs = routing_pairs
+    let iter_route_pairs = route_pairs
         .iter()
         .map(|(route_pair, route_count)| (route_pair.into_node(&graph), *route_count));
 
     match routing_cfg.routing_algo {
         RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => {
             let mut dijkstra = Dijkstra::new();
+            let mut timed_out_count = 0;
 
             for (RoutePair { src, dst }, _route_count) in iter_route_pairs {
                 let now = Instant::now();
-                let best_path = dijkstra.compute_best_path(dijkstra::Query {
+                let query = dijkstra::Query {
                     src_idx: src.idx(),
                     dst_idx: dst.idx(),
-                    graph: &graph,
-                    routing_cfg: &routing_cfg,
-                });
+                    graph,
+                    routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                };
+                let best_path = match timeout {
+                    Some(timeout) => {
+                        match dijkstra.try_compute_best_path_within(query, *timeout)? {
+                            dijkstra::Outcome::Finished(best_path) => best_path,
+                            dijkstra::Outcome::TimedOut => {
+                                warn!(
+                                    "Query from ({}) to ({}) has timed out after {} ms.",
+                                    src,
+                                    dst,
+                                    timeout.as_millis()
+                                );
+                                timed_out_count += 1;
+                                continue;
+                            }
+                            dijkstra::Outcome::Cancelled => {
+                                unreachable!("Query has been cancelled without any token.")
+                            }
+                        }
+                    }
+                    None => dijkstra.try_compute_best_path(query)?,
+                };
                 info!("");
                 info!(
                     "Ran Dijkstra-query in {} ms",
@@ -230,6 +375,8 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                     warn!("No path from ({}) to ({}).", src, dst);
                 }
             }
+
+            Ok(timed_out_count)
         }
         #[cfg(feature = "gpl")]
         RoutingAlgo::Explorator { algo } => {
@@ -237,6 +384,9 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
             let mut explorator = ConvexHullExplorator::new();
             let mut routing_cfg = routing_cfg.clone();
             routing_cfg.routing_algo = RoutingAlgo::from(algo);
+            if timeout.is_some() {
+                warn!("The explorator doesn't support timeouts, hence the timeout is ignored.");
+            }
 
             for (RoutePair { src, dst }, _route_count) in iter_route_pairs {
                 let now = Instant::now();
@@ -244,8 +394,10 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                     dijkstra::Query {
                         src_idx: src.idx(),
                         dst_idx: dst.idx(),
-                        graph: &graph,
+                        graph,
                         routing_cfg: &routing_cfg,
+                        edge_bias: None,
+                        path_hint: dijkstra::PathHint::Auto,
                     },
                     &mut dijkstra,
                 );
@@ -259,7 +411,21 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                 if !found_paths.is_empty() {
                     if !found_paths.is_empty() {
                         info!("Found {} path(s):", found_paths.len());
-                        found_paths.iter().for_each(|path| info!("  {}", path))
+
+                        // compare alternatives with the best path wrt the configured alphas
+                        let optimal_path = found_paths
+                            .iter()
+                            .min_by_key(|path| {
+                                Approx(helpers::dot_product(&routing_cfg.alphas, path.costs()))
+                            })
+                            .expect("Found paths should not be empty.");
+                        for path in &found_paths {
+                            info!("  {}", path);
+                            info!(
+                                "    compared to best: {}",
+                                paths::compare(optimal_path, path, &graph)
+                            );
+                        }
                     } else {
                         info!("No path found from ({}) to ({}).", src, dst);
                     }
@@ -267,16 +433,20 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                     warn!("No path found from ({}) to ({}).", src, dst);
                 }
             }
+
+            Ok(0)
         }
     }
-
-    Ok(())
 }
 
 #[cfg(feature = "gpl")]
-fn do_evaluating_routing(args: &CmdlineArgs, arc_graph: &Arc<Graph>) -> err::Feedback {
+fn do_evaluating_routing(
+    args: &CmdlineArgs,
+    profile: &Option<String>,
+    arc_graph: &Arc<Graph>,
+) -> err::Feedback {
     // get config by provided user-input
-    let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, arc_graph.cfg())?;
+    let routing_cfg = routing_cfg_from(args, profile, arc_graph)?;
     let evaluating_balance_cfg = configs::evaluating_balance::Config::try_from_yaml(&args.cfg)?;
 
     // check if files exist
@@ -287,21 +457,32 @@ fn do_evaluating_routing(args: &CmdlineArgs, arc_graph: &Arc<Graph>) -> err::Fee
     info!("EXECUTE Do routing with alphas: {:?}", routing_cfg.alphas);
 
     // get routing-pairs
-    let route_pairs = io::routing::Parser::parse(&routing_cfg)?;
+    let route_pairs = defaults::balancing::sample_route_counts(
+        &io::routing::Parser::parse_and_snap(&routing_cfg, &arc_graph)?,
+        0,
+        &defaults::balancing::DEMAND_SAMPLING,
+    );
 
     // work-off multithreaded
 
     let arc_routing_cfg = Arc::new(routing_cfg);
-    let mut master = balancing::multithreading::Master::spawn_some(
+    let mut master = multithreading::Master::spawn_some(
         evaluating_balance_cfg.num_threads,
         &arc_graph,
         &arc_routing_cfg,
+        None,
     )?;
+    let num_of_route_pairs = route_pairs.len();
     let (abs_workloads, chosen_paths) = master.work_off(
-        route_pairs,
+        route_pairs.into_iter().map(Ok),
+        num_of_route_pairs,
         &arc_graph,
         &mut rng,
         evaluating_balance_cfg.monitoring.is_writing_for_smarts,
+        &defaults::balancing::DEMAND_SAMPLING,
+        &mut PhaseTimer::new(),
+        None,
+        &mut NoObserver,
     )?;
 
     // write results from (optional) evaluation
@@ -315,215 +496,7 @@ fn do_evaluating_routing(args: &CmdlineArgs, arc_graph: &Arc<Graph>) -> err::Fee
                 .results_dir
                 .join(defaults::smarts::XML_FILE_NAME),
         };
-        io::smarts::Writer::write(&chosen_paths, &arc_graph, &tmp_cfg)?;
-    }
-
-    Ok(())
-}
-
-fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
-    let args = clap::App::new(env!("CARGO_PKG_NAME"))
-        .version(env!("CARGO_PKG_VERSION"))
-        .author(env!("CARGO_PKG_AUTHORS"))
-        .about(env!("CARGO_PKG_DESCRIPTION"))
-        .long_about(
-            "\n\
-            This tool takes a config-file, parses the chosen graph with specified settings, and \
-            can execute specified tasks. Such tasks may be exporting the graph as fmi-map-file or \
-            doing some routing-queries (if provided in config-file).\n\
-            \n\
-            NOTE\n\
-            Some cmdline-arguments can only be used with the cargo-feature 'gpl' and hence are \
-            hidden without it.",
-        );
-
-    let args = {
-        let arg_log_level = clap::Arg::with_name(constants::ids::MAX_LOG_LEVEL)
-            .long("log")
-            .short("l")
-            .value_name("FILTER-LEVEL")
-            .help(
-                "Sets the logging-level according to the env-variable 'RUST_LOG'. The env-variable \
-                'RUST_LOG' has precedence. It takes values of modules, e.g. export RUST_LOG='warn,\
-                osmgraphing=info' for getting warn's by default, but 'info' about the others",
-            )
-            .takes_value(true)
-            .required(false)
-            .case_insensitive(true)
-            .default_value("INFO")
-            .possible_values(&vec!["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]);
-        args.arg(arg_log_level)
-    };
-
-    let args = {
-        let arg_parser_cfg = clap::Arg::with_name(constants::ids::CFG)
-            .long("config")
-            .short("c")
-            .alias("parsing")
-            .value_name("PATH")
-            .help("Sets the parser and other configurations according to this config.")
-            .takes_value(true)
-            .required(true);
-        args.arg(arg_parser_cfg)
-    };
-
-    let args = {
-        let arg_is_writing_graph = clap::Arg::with_name(constants::ids::IS_WRITING_GRAPH)
-            .long("writing_graph")
-            .help(
-                "The generated graph will be exported \
-               as described in the provided config.",
-            )
-            .takes_value(false)
-            .requires(constants::ids::CFG);
-        args.arg(arg_is_writing_graph)
-    };
-
-    let args = {
-        let arg_is_writing_edges = clap::Arg::with_name(constants::ids::IS_WRITING_EDGES)
-            .long("writing_edges")
-            .help(
-                "The generated graph's edges will be exported \
-               as described in the provided config.",
-            )
-            .takes_value(false)
-            .requires(constants::ids::CFG);
-        args.arg(arg_is_writing_edges)
-    };
-
-    let args = {
-        let arg_is_writing_route_pairs =
-            clap::Arg::with_name(constants::ids::IS_WRITING_ROUTE_PAIRS)
-                .long("writing_route-pairs")
-                .help(
-                    "The generated graph will be used to \
-               generate and export valid routes \
-               as described in the provided config.",
-                )
-                .takes_value(false)
-                .requires(constants::ids::CFG);
-        args.arg(arg_is_writing_route_pairs)
-    };
-
-    let args = {
-        let arg_is_routing = clap::Arg::with_name(constants::ids::IS_ROUTING)
-            .long("routing")
-            .help("Does routing as specified in the provided config.")
-            .takes_value(false)
-            .requires(constants::ids::CFG);
-        args.arg(arg_is_routing)
-    };
-
-    let args = {
-        let arg_is_balancing = clap::Arg::with_name(constants::ids::IS_BALANCING)
-            .long("balancing")
-            .help(
-                "This balancer takes a config-file, parses the chosen graph with specified \
-                settings, and optimizes found routes with the provided balancing- and routing- \
-                config before writing the balanced graph into a fmi-file. Optimizing means \
-                generating a new metric.\n\
-                \n\
-                Hence a correct config-file contains following:\n\
-                - A parsing-config reading graph being balanced.\n\
-                - A balancing-config defining the settings for the balancer.\n\
-                - A routing-config specifying the routing-settings, which are used for calculating \
-                the new metric.\n\
-                - A writing-config for exporting the balanced graph.\n\
-                \n\
-                You can visualize the results with the python-module\n\
-                py ./scripts/balancing/visualizer --results-dir <RESULTS_DIR/DATE>\n",
-            )
-            .takes_value(false)
-            .hidden(!cfg!(feature = "gpl"))
-            .requires(constants::ids::CFG);
-        args.arg(arg_is_balancing)
-    };
-
-    let args = {
-        let arg_is_evaluating_balance = clap::Arg::with_name(constants::ids::IS_EVALUATING_BALANCE)
-            .long("evaluating_balance")
-            .help(
-                "With this flag, the provided graph is executed with the defined \
-                routing-algorithm. In opposite to simply executing the routing-queries, the \
-                workload is counted per edge and being written to a specified file.\n\
-                \n\
-                You can visualize the results with the python-module\n\
-                py ./scripts/balancing/visualizer --results-dir <RESULTS_DIR/DATE>\n",
-            )
-            .takes_value(false)
-            .hidden(!cfg!(feature = "gpl"))
-            .requires(constants::ids::CFG);
-        args.arg(arg_is_evaluating_balance)
-    };
-
-    CmdlineArgs::try_from(args.get_matches())
-}
-
-mod constants {
-    pub mod ids {
-        pub const MAX_LOG_LEVEL: &str = "max-log-level";
-        pub const CFG: &str = "cfg";
-        pub const IS_WRITING_GRAPH: &str = "is_writing_graph";
-        pub const IS_WRITING_EDGES: &str = "is_writing_edges";
-        pub const IS_WRITING_ROUTE_PAIRS: &str = "is_writing_route_pairs";
-        pub const IS_ROUTING: &str = "is_routing";
-        pub const IS_EXPLORATING: &str = "is_explorating";
-        pub const IS_BALANCING: &str = "is_balancing";
-        pub const IS_EVALUATING_BALANCE: &str = "is_evaluating_balance";
-    }
-}
-
-struct CmdlineArgs {
-    max_log_level: String,
-    cfg: String,
-    is_writing_graph: bool,
-    is_writing_edges: bool,
-    is_writing_route_pairs: bool,
-    is_routing: bool,
-    #[cfg(feature = "gpl")]
-    is_balancing: bool,
-    is_evaluating_balance: bool,
-}
-
-impl<'a> TryFrom<clap::ArgMatches<'a>> for CmdlineArgs {
-    type Error = err::Msg;
-
-    fn try_from(matches: clap::ArgMatches<'a>) -> err::Result<CmdlineArgs> {
-        let max_log_level = matches
-            .value_of(constants::ids::MAX_LOG_LEVEL)
-            .expect(&format!("cmdline-arg: {}", constants::ids::MAX_LOG_LEVEL));
-        let cfg = matches
-            .value_of(constants::ids::CFG)
-            .expect(&format!("cmdline-arg: {}", constants::ids::CFG));
-        let is_writing_graph = matches.is_present(constants::ids::IS_WRITING_GRAPH);
-        let is_writing_edges = matches.is_present(constants::ids::IS_WRITING_EDGES);
-        let is_writing_route_pairs = matches.is_present(constants::ids::IS_WRITING_ROUTE_PAIRS);
-        let is_routing = matches.is_present(constants::ids::IS_ROUTING);
-        let is_explorating = matches.is_present(constants::ids::IS_EXPLORATING);
-        let is_balancing = matches.is_present(constants::ids::IS_BALANCING);
-        let is_evaluating_balance = matches.is_present(constants::ids::IS_EVALUATING_BALANCE);
-
-        if is_explorating || is_balancing || is_evaluating_balance {
-            check_for_activated_feature()?;
-        }
-
-        Ok(CmdlineArgs {
-            max_log_level: String::from(max_log_level),
-            cfg: String::from(cfg),
-            is_writing_graph,
-            is_writing_edges,
-            is_writing_route_pairs,
-            is_routing,
-            #[cfg(feature = "gpl")]
-            is_balancing,
-            is_evaluating_balance,
-        })
-    }
-}
-
-fn check_for_activated_feature() -> err::Feedback {
-    if !cfg!(feature = "gpl") {
-        return Err(err::Msg::from("Please activate cargo-feature gpl."));
+        io::smarts::Writer::write(&vehicles_for_smarts(&chosen_paths), &arc_graph, &tmp_cfg)?;
     }
 
     Ok(())
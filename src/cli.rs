@@ -0,0 +1,469 @@
+use crate::{helpers::err, network::Graph};
+use log::info;
+use std::{convert::TryFrom, ffi::OsString, time::Duration};
+
+/// Logs the graph's stats, e.g. after the subcommand `parse`.
+pub fn log_stats(graph: &Graph) {
+    info!(
+        "Graph has {} nodes and {} edges with metrics {:?}.",
+        graph.nodes().count(),
+        graph.fwd_edges().count(),
+        graph
+            .cfg()
+            .edges
+            .metrics
+            .ids
+            .iter()
+            .map(|id| &id.0)
+            .collect::<Vec<_>>()
+    );
+}
+
+/// The subcommands of the binary `osmgraphing`.
+///
+/// There is no subcommand `serve`, since the library doesn't depend on a web-server
+/// (which only exists as dev-dependency for `examples/playground/actix.rs`).
+/// Serving routes is left to async applications, e.g. using `routing::blocking` (cargo-feature
+/// `tokio`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    Parse,
+    WriteGraph,
+    WriteEdges,
+    WriteSimpleFmi,
+    WriteGeojson,
+    WriteRoutes {
+        profile: Option<String>,
+    },
+    Route {
+        profile: Option<String>,
+        /// Queries taking longer are interrupted.
+        timeout: Option<Duration>,
+    },
+    Verify {
+        profile: Option<String>,
+        reference_file: String,
+    },
+    /// Needs the cargo-feature `gpl`.
+    Balance,
+    /// Needs the cargo-feature `gpl`.
+    EvaluateBalance {
+        profile: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct CmdlineArgs {
+    pub max_log_level: String,
+    /// Prints spans of the major phases with their durations (needs the cargo-feature `tracing`).
+    pub is_tracing: bool,
+    pub cfg: String,
+    pub is_ignoring_embedded_cfg: bool,
+    /// Prints the parsing-config with expanded profile instead of executing the command.
+    pub is_printing_effective_cfg: bool,
+    pub command: Command,
+}
+
+impl CmdlineArgs {
+    /// Parses the provided args, where the first arg is the binary's name, like `std::env::args_os()`.
+    ///
+    /// Errors (and requests for help or version) are returned as message.
+    pub fn try_from_iter<I, T>(args: I) -> err::Result<CmdlineArgs>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        match app().get_matches_from_safe(args) {
+            Ok(matches) => CmdlineArgs::try_from(matches),
+            Err(e) => Err(err::Msg::from(e.message)),
+        }
+    }
+}
+
+impl<'a> TryFrom<clap::ArgMatches<'a>> for CmdlineArgs {
+    type Error = err::Msg;
+
+    fn try_from(matches: clap::ArgMatches<'a>) -> err::Result<CmdlineArgs> {
+        let (name, sub_matches) = match matches.subcommand() {
+            (name, Some(sub_matches)) => (name, sub_matches),
+            (_, None) => return Err(err::Msg::from("Please provide a subcommand.")),
+        };
+
+        let max_log_level = sub_matches
+            .value_of(constants::ids::MAX_LOG_LEVEL)
+            .expect(&format!("cmdline-arg: {}", constants::ids::MAX_LOG_LEVEL));
+        let is_tracing = sub_matches.is_present(constants::ids::IS_TRACING);
+        let cfg = sub_matches
+            .value_of(constants::ids::CFG)
+            .expect(&format!("cmdline-arg: {}", constants::ids::CFG));
+        let is_ignoring_embedded_cfg =
+            sub_matches.is_present(constants::ids::IS_IGNORING_EMBEDDED_CFG);
+        let is_printing_effective_cfg =
+            sub_matches.is_present(constants::ids::IS_PRINTING_EFFECTIVE_CFG);
+        let profile = sub_matches
+            .value_of(constants::ids::PROFILE)
+            .map(String::from);
+
+        let command = match name {
+            constants::subcommands::PARSE => Command::Parse,
+            constants::subcommands::WRITE_GRAPH => Command::WriteGraph,
+            constants::subcommands::WRITE_EDGES => Command::WriteEdges,
+            constants::subcommands::WRITE_SIMPLE_FMI => Command::WriteSimpleFmi,
+            constants::subcommands::WRITE_GEOJSON => Command::WriteGeojson,
+            constants::subcommands::WRITE_ROUTES => Command::WriteRoutes { profile },
+            constants::subcommands::ROUTE => Command::Route {
+                profile,
+                timeout: match sub_matches.value_of(constants::ids::TIMEOUT) {
+                    Some(millis) => match millis.parse::<u64>() {
+                        Ok(millis) => Some(Duration::from_millis(millis)),
+                        Err(e) => {
+                            return Err(format!(
+                                "The timeout {} should be a number of milliseconds, \
+                                 but parsing failed due to error: {}",
+                                millis, e
+                            )
+                            .into())
+                        }
+                    },
+                    None => None,
+                },
+            },
+            constants::subcommands::VERIFY => Command::Verify {
+                profile,
+                reference_file: String::from(
+                    sub_matches
+                        .value_of(constants::ids::REFERENCE_FILE)
+                        .expect(&format!("cmdline-arg: {}", constants::ids::REFERENCE_FILE)),
+                ),
+            },
+            constants::subcommands::BALANCE => {
+                check_for_activated_feature()?;
+                Command::Balance
+            }
+            constants::subcommands::EVALUATE_BALANCE => {
+                check_for_activated_feature()?;
+                Command::EvaluateBalance { profile }
+            }
+            _ => return Err(format!("Unknown subcommand {}", name).into()),
+        };
+
+        Ok(CmdlineArgs {
+            max_log_level: String::from(max_log_level),
+            is_tracing,
+            cfg: String::from(cfg),
+            is_ignoring_embedded_cfg,
+            is_printing_effective_cfg,
+            command,
+        })
+    }
+}
+
+fn check_for_activated_feature() -> err::Feedback {
+    if !cfg!(feature = "gpl") {
+        return Err(err::Msg::from("Please activate cargo-feature gpl."));
+    }
+
+    Ok(())
+}
+
+/// The cmdline-parser of the binary `osmgraphing`.
+///
+/// Every task is its own subcommand with its own arguments, so flags of different tasks can't be combined.
+pub fn app<'a, 'b>() -> clap::App<'a, 'b> {
+    clap::App::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .long_about(
+            "\n\
+            This tool takes a config-file, parses the chosen graph with specified settings, and \
+            executes the task of the chosen subcommand. Such tasks may be exporting the graph as \
+            fmi-map-file or doing some routing-queries (if provided in config-file).\n\
+            \n\
+            NOTE\n\
+            Some subcommands can only be used with the cargo-feature 'gpl' and hence are hidden \
+            without it.",
+        )
+        .setting(clap::AppSettings::SubcommandRequiredElseHelp)
+        .setting(clap::AppSettings::VersionlessSubcommands)
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::PARSE)
+                .about("Parses the graph and logs its stats.")
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg()),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::WRITE_GRAPH)
+                .about(
+                    "The generated graph will be exported \
+                    as described in the provided config.",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg()),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::WRITE_EDGES)
+                .about(
+                    "The generated graph's edges will be exported \
+                    as described in the provided config.",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg()),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::WRITE_SIMPLE_FMI)
+                .about(
+                    "The generated graph will be exported as simple fmi-file \
+                    (nodes and edges with one weight) as described in the provided config.",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg()),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::WRITE_GEOJSON)
+                .about(
+                    "The generated graph will be exported as GeoJSON-file \
+                    (nodes as points and edges with their metrics as lines), \
+                    e.g. for QGIS or Leaflet, as described in the provided config.",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg()),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::WRITE_ROUTES)
+                .about(
+                    "The generated graph will be used to \
+                    generate and export valid routes \
+                    as described in the provided config.",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg())
+                .arg(arg_profile()),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::ROUTE)
+                .about("Does routing as specified in the provided config.")
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg())
+                .arg(arg_profile())
+                .arg(
+                    clap::Arg::with_name(constants::ids::TIMEOUT)
+                        .long("timeout")
+                        .value_name("MILLISECONDS")
+                        .help(
+                            "Interrupts every query taking longer than this. \
+                            If any query has timed out, the process exits with a distinct \
+                            exit-code.",
+                        )
+                        .takes_value(true)
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(constants::subcommands::VERIFY)
+                .about(
+                    "Routes every pair of the reference-file with the provided routing-config and \
+                    compares the found costs with the expected costs, e.g. from an OSRM- or \
+                    Valhalla-dump.",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg())
+                .arg(arg_profile())
+                .arg(
+                    clap::Arg::with_name(constants::ids::REFERENCE_FILE)
+                        .long("reference")
+                        .value_name("FILE")
+                        .help(
+                            "The json-file is an array of entries like \
+                            { \"src_id\": 1, \"dst_id\": 2, \"expected\": \
+                            { \"kilometers\": 3.4, \"hours\": 0.1 } }.",
+                        )
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(hidden_without_gpl(
+            clap::SubCommand::with_name(constants::subcommands::BALANCE)
+                .about(
+                    "This balancer takes a config-file, parses the chosen graph with specified \
+                    settings, and optimizes found routes with the provided balancing- and routing- \
+                    config before writing the balanced graph into a fmi-file. Optimizing means \
+                    generating a new metric.",
+                )
+                .long_about(
+                    "This balancer takes a config-file, parses the chosen graph with specified \
+                    settings, and optimizes found routes with the provided balancing- and routing- \
+                    config before writing the balanced graph into a fmi-file. Optimizing means \
+                    generating a new metric.\n\
+                    \n\
+                    Hence a correct config-file contains following:\n\
+                    - A parsing-config reading graph being balanced.\n\
+                    - A balancing-config defining the settings for the balancer.\n\
+                    - A routing-config specifying the routing-settings, which are used for \
+                    calculating the new metric.\n\
+                    - A writing-config for exporting the balanced graph.\n\
+                    \n\
+                    You can visualize the results with the python-module\n\
+                    py ./scripts/balancing/visualizer --results-dir <RESULTS_DIR/DATE>\n",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg()),
+        ))
+        .subcommand(hidden_without_gpl(
+            clap::SubCommand::with_name(constants::subcommands::EVALUATE_BALANCE)
+                .about(
+                    "The provided graph is executed with the defined routing-algorithm. In \
+                    opposite to simply executing the routing-queries, the workload is counted per \
+                    edge and being written to a specified file.",
+                )
+                .long_about(
+                    "The provided graph is executed with the defined routing-algorithm. In \
+                    opposite to simply executing the routing-queries, the workload is counted per \
+                    edge and being written to a specified file.\n\
+                    \n\
+                    You can visualize the results with the python-module\n\
+                    py ./scripts/balancing/visualizer --results-dir <RESULTS_DIR/DATE>\n",
+                )
+                .arg(arg_log_level())
+                .arg(arg_is_tracing())
+                .arg(arg_cfg())
+                .arg(arg_is_ignoring_embedded_cfg())
+                .arg(arg_is_printing_effective_cfg())
+                .arg(arg_profile()),
+        ))
+}
+
+fn hidden_without_gpl<'a, 'b>(subcommand: clap::App<'a, 'b>) -> clap::App<'a, 'b> {
+    if cfg!(feature = "gpl") {
+        subcommand
+    } else {
+        subcommand.setting(clap::AppSettings::Hidden)
+    }
+}
+
+fn arg_log_level<'a, 'b>() -> clap::Arg<'a, 'b> {
+    clap::Arg::with_name(constants::ids::MAX_LOG_LEVEL)
+        .long("log")
+        .short("l")
+        .value_name("FILTER-LEVEL")
+        .help(
+            "Sets the logging-level according to the env-variable 'RUST_LOG'. The env-variable \
+            'RUST_LOG' has precedence. It takes values of modules, e.g. export RUST_LOG='warn,\
+            osmgraphing=info' for getting warn's by default, but 'info' about the others",
+        )
+        .takes_value(true)
+        .required(false)
+        .case_insensitive(true)
+        .default_value("INFO")
+        .possible_values(&["TRACE", "DEBUG", "INFO", "WARN", "ERROR"])
+}
+
+fn arg_is_tracing<'a, 'b>() -> clap::Arg<'a, 'b> {
+    clap::Arg::with_name(constants::ids::IS_TRACING)
+        .long("trace")
+        .help(
+            "Prints spans of the major phases (e.g. parsing or routing) with their durations \
+            to stderr, e.g. for profiling. Needs the cargo-feature 'tracing'.",
+        )
+        .takes_value(false)
+}
+
+fn arg_cfg<'a, 'b>() -> clap::Arg<'a, 'b> {
+    clap::Arg::with_name(constants::ids::CFG)
+        .long("config")
+        .short("c")
+        .alias("parsing")
+        .value_name("PATH")
+        .help("Sets the parser and other configurations according to this config.")
+        .takes_value(true)
+        .required(true)
+}
+
+fn arg_is_ignoring_embedded_cfg<'a, 'b>() -> clap::Arg<'a, 'b> {
+    clap::Arg::with_name(constants::ids::IS_IGNORING_EMBEDDED_CFG)
+        .long("ignore-embedded-config")
+        .help(
+            "Fmi-files written by osmgraphing describe their columns in a header-line, \
+            which is checked against the provided parsing-config. \
+            With this flag, this check is skipped.",
+        )
+        .takes_value(false)
+}
+
+fn arg_is_printing_effective_cfg<'a, 'b>() -> clap::Arg<'a, 'b> {
+    clap::Arg::with_name(constants::ids::IS_PRINTING_EFFECTIVE_CFG)
+        .long("print-effective-config")
+        .help(
+            "Prints the parsing-config with its profile (e.g. 'car-default') expanded, \
+            as it would be parsed, and exits without executing the subcommand.",
+        )
+        .takes_value(false)
+}
+
+fn arg_profile<'a, 'b>() -> clap::Arg<'a, 'b> {
+    clap::Arg::with_name(constants::ids::PROFILE)
+        .long("profile")
+        .value_name("NAME")
+        .help(
+            "Chooses the routing-profile of the provided config. \
+            Without this flag, the config's default-profile is used.",
+        )
+        .takes_value(true)
+        .required(false)
+}
+
+pub mod constants {
+    pub mod subcommands {
+        pub const PARSE: &str = "parse";
+        pub const WRITE_GRAPH: &str = "write-graph";
+        pub const WRITE_EDGES: &str = "write-edges";
+        pub const WRITE_SIMPLE_FMI: &str = "write-simple-fmi";
+        pub const WRITE_GEOJSON: &str = "write-geojson";
+        pub const WRITE_ROUTES: &str = "write-routes";
+        pub const ROUTE: &str = "route";
+        pub const VERIFY: &str = "verify";
+        pub const BALANCE: &str = "balance";
+        pub const EVALUATE_BALANCE: &str = "evaluate-balance";
+    }
+
+    pub mod ids {
+        pub const MAX_LOG_LEVEL: &str = "max-log-level";
+        pub const IS_TRACING: &str = "is_tracing";
+        pub const CFG: &str = "cfg";
+        pub const PROFILE: &str = "profile";
+        pub const IS_IGNORING_EMBEDDED_CFG: &str = "is_ignoring_embedded_cfg";
+        pub const IS_PRINTING_EFFECTIVE_CFG: &str = "is_printing_effective_cfg";
+        pub const REFERENCE_FILE: &str = "reference_file";
+        pub const TIMEOUT: &str = "timeout";
+    }
+
+    pub mod exit_codes {
+        /// Some route-queries have been interrupted by the provided timeout.
+        pub const TIMEOUT: i32 = 3;
+    }
+}
@@ -2,11 +2,12 @@ use crate::{
     configs::{self, SimpleId},
     defaults,
     helpers::err,
-    io::SupportingFileExts,
+    io::{self, SupportingFileExts},
     multi_ch_constructor,
 };
 use serde::Deserialize;
 use std::{
+    fmt::{self, Display},
     fs::OpenOptions,
     path::{Path, PathBuf},
 };
@@ -24,6 +25,19 @@ pub struct Config {
     pub seed: u64,
     pub min_new_metric: Option<f64>,
     pub is_err_when_metric_is_zero: bool,
+    pub demand_sampling: DemandSampling,
+    pub results_format: ResultsFormat,
+    pub initial_workload: InitialWorkload,
+    /// If set, every route-pair's edges of the previous iteration get a bonus to prevent routes
+    /// from flapping between near-equal paths.
+    pub route_stabilization: Option<RouteStabilization>,
+    /// If set, the top contributing route-pairs of every highly loaded edge are written per
+    /// iteration.
+    pub edge_users_tracking: Option<EdgeUsersTracking>,
+    /// If set, the graph is only contracted again, if the optimized metric has changed enough
+    /// since the last contraction.
+    /// Otherwise, the graph is contracted in every iteration.
+    pub ch_rebuild: Option<ChRebuild>,
 }
 
 impl SupportingFileExts for Config {
@@ -90,6 +104,55 @@ impl Config {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    /// Checks, that the optimized metric is part of the graph, that the monitored edges-info can
+    /// be written, that tracked edge-users are at least one, that thresholds of ch-rebuilds are
+    /// non-negative and that the results-directory is writable.
+    ///
+    /// The edges-info's ids are not checked, because they are written from the ch-graphs, whose
+    /// configs are created while balancing.
+    pub fn validate(&self, graph_cfg: &configs::parsing::Config) -> err::Feedback {
+        if let Err(msg) = graph_cfg
+            .edges
+            .metrics
+            .try_idx_of(&self.optimization.metric_id)
+        {
+            return Err(err::Msg::from(format!(
+                "The optimized metric is not part of the graph: {}",
+                msg
+            )));
+        }
+
+        let edges_info_file = &self.monitoring.edges_info.file;
+        if !io::osm::Writer::is_file_supported(edges_info_file)
+            && !io::network::edges::Writer::is_file_supported(edges_info_file)
+        {
+            return Err(err::Msg::from(format!(
+                "No support for writing the edges-info {}.",
+                edges_info_file.display()
+            )));
+        }
+
+        if let Some(tracking) = &self.edge_users_tracking {
+            if tracking.top_k == 0 {
+                return Err(err::Msg::from(
+                    "Tracking edge-users needs a top-k of at least 1.",
+                ));
+            }
+        }
+
+        if let Some(ch_rebuild) = &self.ch_rebuild {
+            if !(ch_rebuild.max_relative_delta >= 0.0 && ch_rebuild.mean_relative_delta >= 0.0) {
+                return Err(err::Msg::from(format!(
+                    "The thresholds of ch-rebuilds should be non-negative, but they are \
+                     max-relative-delta {} and mean-relative-delta {}.",
+                    ch_rebuild.max_relative_delta, ch_rebuild.mean_relative_delta
+                )));
+            }
+        }
+
+        io::files::check_writable_dir(&self.results_dir)
+    }
 }
 
 impl From<ProtoConfig> for Config {
@@ -111,6 +174,185 @@ impl From<ProtoConfig> for Config {
             is_err_when_metric_is_zero: proto_cfg
                 .is_err_when_metric_is_zero
                 .unwrap_or(defaults::balancing::IS_ERR_WHEN_METRIC_IS_ZERO),
+            demand_sampling: proto_cfg
+                .demand_sampling
+                .map(DemandSampling::from)
+                .unwrap_or(defaults::balancing::DEMAND_SAMPLING),
+            results_format: proto_cfg
+                .results_format
+                .map(ResultsFormat::from)
+                .unwrap_or(defaults::balancing::RESULTS_FORMAT),
+            initial_workload: proto_cfg
+                .initial_workload
+                .map(InitialWorkload::from)
+                .unwrap_or(defaults::balancing::INITIAL_WORKLOAD),
+            route_stabilization: proto_cfg.route_stabilization.map(RouteStabilization::from),
+            edge_users_tracking: proto_cfg.edge_users_tracking.map(EdgeUsersTracking::from),
+            ch_rebuild: proto_cfg.ch_rebuild.map(ChRebuild::from),
+        }
+    }
+}
+
+/// Defines the optimized metric's values, with which iteration 0 starts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitialWorkload {
+    /// The parsed values are kept, but iteration 0 ignores the optimized metric (alpha 0).
+    Zero,
+    /// Every edge starts with the given value, normalized by its mean (see
+    /// `defaults::balancing::init_new_metric(...)`).
+    Uniform(f64),
+    /// Every edge starts with `lane-count * distance`, normalized by its mean, as proxy for its
+    /// capacity (see `defaults::balancing::init_new_metric(...)`).
+    Freeflow {
+        lane_count_id: SimpleId,
+        distance_id: SimpleId,
+    },
+}
+
+impl InitialWorkload {
+    /// True, if iteration 0 routes with the optimized metric already.
+    pub fn is_warm(&self) -> bool {
+        self != &InitialWorkload::Zero
+    }
+}
+
+/// Formatted like the yaml-value of `initial-workload`.
+impl Display for InitialWorkload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InitialWorkload::Zero => write!(f, "zero"),
+            InitialWorkload::Uniform(value) => write!(f, "{{ uniform: {} }}", value),
+            InitialWorkload::Freeflow {
+                lane_count_id,
+                distance_id,
+            } => write!(
+                f,
+                "{{ freeflow: {{ lane-count-id: '{}', distance-id: '{}' }} }}",
+                lane_count_id, distance_id
+            ),
+        }
+    }
+}
+
+impl From<ProtoInitialWorkload> for InitialWorkload {
+    fn from(proto_workload: ProtoInitialWorkload) -> InitialWorkload {
+        match proto_workload {
+            ProtoInitialWorkload::Zero => InitialWorkload::Zero,
+            ProtoInitialWorkload::Uniform(value) => InitialWorkload::Uniform(value),
+            ProtoInitialWorkload::Freeflow {
+                lane_count_id,
+                distance_id,
+            } => InitialWorkload::Freeflow {
+                lane_count_id,
+                distance_id,
+            },
+        }
+    }
+}
+
+/// Stabilizes routes across iterations (see `routing::stabilizing`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RouteStabilization {
+    /// Subtracted from the cost of every edge, which the route-pair has used in the previous
+    /// iteration.
+    /// It should be lower than the edges' costs, since biased costs are clamped at 0.
+    pub bonus: f64,
+}
+
+impl From<ProtoRouteStabilization> for RouteStabilization {
+    fn from(proto_stabilization: ProtoRouteStabilization) -> RouteStabilization {
+        RouteStabilization {
+            bonus: proto_stabilization.bonus,
+        }
+    }
+}
+
+/// Thresholds of the optimized metric's relative change since the last contraction (see
+/// `balancing::ch_rebuild`).
+/// Exceeding one of them contracts the graph again, while below both of them, the shortcuts are
+/// patched in place.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChRebuild {
+    /// Threshold of the highest relative change of a single edge
+    pub max_relative_delta: f64,
+    /// Threshold of the mean relative change over all edges
+    pub mean_relative_delta: f64,
+}
+
+impl From<ProtoChRebuild> for ChRebuild {
+    fn from(proto_ch_rebuild: ProtoChRebuild) -> ChRebuild {
+        ChRebuild {
+            max_relative_delta: proto_ch_rebuild
+                .max_relative_delta
+                .unwrap_or(defaults::balancing::ch_rebuild::MAX_RELATIVE_DELTA),
+            mean_relative_delta: proto_ch_rebuild
+                .mean_relative_delta
+                .unwrap_or(defaults::balancing::ch_rebuild::MEAN_RELATIVE_DELTA),
+        }
+    }
+}
+
+/// Tracks the route-pairs loading an edge (see `balancing::edge_users`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EdgeUsersTracking {
+    /// Number of remembered route-pairs per edge
+    pub top_k: usize,
+    /// Edges of lower workload are not tracked, which bounds the memory.
+    pub min_workload: f64,
+}
+
+impl From<ProtoEdgeUsersTracking> for EdgeUsersTracking {
+    fn from(proto_tracking: ProtoEdgeUsersTracking) -> EdgeUsersTracking {
+        EdgeUsersTracking {
+            top_k: proto_tracking
+                .top_k
+                .unwrap_or(defaults::balancing::edge_users::TOP_K),
+            min_workload: proto_tracking
+                .min_workload
+                .unwrap_or(defaults::balancing::edge_users::MIN_WORKLOAD),
+        }
+    }
+}
+
+/// Defines how the graphs of the balancer's iterations are stored in the results-dir.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultsFormat {
+    /// Every iteration keeps its full graph.
+    Full,
+    /// Only iteration 0 keeps its full graph (the base), while every other iteration keeps only
+    /// the optimized metric's column (see `io::balancing::Reader::reconstruct(...)`).
+    Delta,
+}
+
+impl From<ProtoResultsFormat> for ResultsFormat {
+    fn from(proto_format: ProtoResultsFormat) -> ResultsFormat {
+        match proto_format {
+            ProtoResultsFormat::Full => ResultsFormat::Full,
+            ProtoResultsFormat::Delta => ResultsFormat::Delta,
+        }
+    }
+}
+
+/// Defines how the (maybe fractional) counts of route-pairs are turned into workloads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DemandSampling {
+    /// The count is rounded to an integer number of trips, and every trip chooses one of the
+    /// found paths randomly.
+    /// For integer counts, this is how route-pairs have been worked off before fractional
+    /// counts have been supported.
+    Rounded,
+    /// The count is used as workload-weight directly, without any randomness.
+    Expected,
+    /// In every iteration, an integer count is sampled from `Poisson(count)`.
+    Poisson { seed: u64 },
+}
+
+impl From<ProtoDemandSampling> for DemandSampling {
+    fn from(proto_sampling: ProtoDemandSampling) -> DemandSampling {
+        match proto_sampling {
+            ProtoDemandSampling::Rounded => DemandSampling::Rounded,
+            ProtoDemandSampling::Expected => DemandSampling::Expected,
+            ProtoDemandSampling::Poisson { seed } => DemandSampling::Poisson { seed },
         }
     }
 }
@@ -151,6 +393,8 @@ impl From<ProtoOptimizationMethod> for OptimizationMethod {
 pub struct MonitoringConfig {
     pub edges_info: configs::writing::network::edges::Config,
     pub is_writing_for_smarts: bool,
+    /// If true, the Fréchet-distance between every route-pair's chosen paths of consecutive iterations is written.
+    pub is_writing_path_stability: bool,
 }
 
 impl From<ProtoMonitoringConfig> for MonitoringConfig {
@@ -160,6 +404,9 @@ impl From<ProtoMonitoringConfig> for MonitoringConfig {
             is_writing_for_smarts: proto_cfg
                 .is_writing_for_smarts
                 .unwrap_or(defaults::smarts::IS_WRITING),
+            is_writing_path_stability: proto_cfg
+                .is_writing_path_stability
+                .unwrap_or(defaults::balancing::IS_WRITING_PATH_STABILITY),
         }
     }
 }
@@ -179,6 +426,12 @@ pub struct ProtoConfig {
     pub num_threads: Option<usize>,
     pub min_new_metric: Option<f64>,
     pub is_err_when_metric_is_zero: Option<bool>,
+    pub demand_sampling: Option<ProtoDemandSampling>,
+    pub results_format: Option<ProtoResultsFormat>,
+    pub initial_workload: Option<ProtoInitialWorkload>,
+    pub route_stabilization: Option<ProtoRouteStabilization>,
+    pub edge_users_tracking: Option<ProtoEdgeUsersTracking>,
+    pub ch_rebuild: Option<ProtoChRebuild>,
 }
 
 impl From<RawConfig> for ProtoConfig {
@@ -197,6 +450,117 @@ impl From<RawConfig> for ProtoConfig {
             num_threads: raw_cfg.num_threads,
             min_new_metric: raw_cfg.min_new_metric,
             is_err_when_metric_is_zero: raw_cfg.is_err_when_metric_is_zero,
+            demand_sampling: raw_cfg.demand_sampling.map(ProtoDemandSampling::from),
+            results_format: raw_cfg.results_format.map(ProtoResultsFormat::from),
+            initial_workload: raw_cfg.initial_workload.map(ProtoInitialWorkload::from),
+            route_stabilization: raw_cfg
+                .route_stabilization
+                .map(ProtoRouteStabilization::from),
+            edge_users_tracking: raw_cfg
+                .edge_users_tracking
+                .map(ProtoEdgeUsersTracking::from),
+            ch_rebuild: raw_cfg.ch_rebuild.map(ProtoChRebuild::from),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ProtoRouteStabilization {
+    pub bonus: f64,
+}
+
+impl From<RawRouteStabilization> for ProtoRouteStabilization {
+    fn from(raw_stabilization: RawRouteStabilization) -> ProtoRouteStabilization {
+        ProtoRouteStabilization {
+            bonus: raw_stabilization.bonus,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ProtoChRebuild {
+    pub max_relative_delta: Option<f64>,
+    pub mean_relative_delta: Option<f64>,
+}
+
+impl From<RawChRebuild> for ProtoChRebuild {
+    fn from(raw_ch_rebuild: RawChRebuild) -> ProtoChRebuild {
+        ProtoChRebuild {
+            max_relative_delta: raw_ch_rebuild.max_relative_delta,
+            mean_relative_delta: raw_ch_rebuild.mean_relative_delta,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ProtoEdgeUsersTracking {
+    pub top_k: Option<usize>,
+    pub min_workload: Option<f64>,
+}
+
+impl From<RawEdgeUsersTracking> for ProtoEdgeUsersTracking {
+    fn from(raw_tracking: RawEdgeUsersTracking) -> ProtoEdgeUsersTracking {
+        ProtoEdgeUsersTracking {
+            top_k: raw_tracking.top_k,
+            min_workload: raw_tracking.min_workload,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ProtoInitialWorkload {
+    Zero,
+    Uniform(f64),
+    Freeflow {
+        lane_count_id: SimpleId,
+        distance_id: SimpleId,
+    },
+}
+
+impl From<RawInitialWorkload> for ProtoInitialWorkload {
+    fn from(raw_workload: RawInitialWorkload) -> ProtoInitialWorkload {
+        match raw_workload {
+            RawInitialWorkload::Zero => ProtoInitialWorkload::Zero,
+            RawInitialWorkload::Uniform(value) => ProtoInitialWorkload::Uniform(value),
+            RawInitialWorkload::Freeflow {
+                lane_count_id,
+                distance_id,
+            } => ProtoInitialWorkload::Freeflow {
+                lane_count_id,
+                distance_id,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ProtoResultsFormat {
+    Full,
+    Delta,
+}
+
+impl From<RawResultsFormat> for ProtoResultsFormat {
+    fn from(raw_format: RawResultsFormat) -> ProtoResultsFormat {
+        match raw_format {
+            RawResultsFormat::Full => ProtoResultsFormat::Full,
+            RawResultsFormat::Delta => ProtoResultsFormat::Delta,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ProtoDemandSampling {
+    Rounded,
+    Expected,
+    Poisson { seed: u64 },
+}
+
+impl From<RawDemandSampling> for ProtoDemandSampling {
+    fn from(raw_sampling: RawDemandSampling) -> ProtoDemandSampling {
+        match raw_sampling {
+            RawDemandSampling::Rounded => ProtoDemandSampling::Rounded,
+            RawDemandSampling::Expected => ProtoDemandSampling::Expected,
+            RawDemandSampling::Poisson { seed } => ProtoDemandSampling::Poisson { seed },
         }
     }
 }
@@ -205,6 +569,7 @@ impl From<RawConfig> for ProtoConfig {
 pub struct ProtoMonitoringConfig {
     pub edges_info: configs::writing::network::edges::Config,
     pub is_writing_for_smarts: Option<bool>,
+    pub is_writing_path_stability: Option<bool>,
 }
 
 impl From<RawMonitoringConfig> for ProtoMonitoringConfig {
@@ -212,6 +577,7 @@ impl From<RawMonitoringConfig> for ProtoMonitoringConfig {
         ProtoMonitoringConfig {
             edges_info: configs::writing::network::edges::Config::from(raw_cfg.edges_info),
             is_writing_for_smarts: raw_cfg.is_writing_for_smarts,
+            is_writing_path_stability: raw_cfg.is_writing_path_stability,
         }
     }
 }
@@ -276,6 +642,78 @@ pub struct RawContent {
     pub min_new_metric: Option<f64>,
     #[serde(rename = "throw_err_when_new_metric_is_zero")]
     pub is_err_when_metric_is_zero: Option<bool>,
+    #[serde(rename = "demand-sampling")]
+    pub demand_sampling: Option<RawDemandSampling>,
+    #[serde(rename = "results-format")]
+    pub results_format: Option<RawResultsFormat>,
+    #[serde(rename = "initial-workload")]
+    pub initial_workload: Option<RawInitialWorkload>,
+    #[serde(rename = "stabilize-routes")]
+    pub route_stabilization: Option<RawRouteStabilization>,
+    #[serde(rename = "track-edge-users")]
+    pub edge_users_tracking: Option<RawEdgeUsersTracking>,
+    #[serde(rename = "ch-rebuild")]
+    pub ch_rebuild: Option<RawChRebuild>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawRouteStabilization {
+    pub bonus: f64,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawChRebuild {
+    #[serde(rename = "max-relative-delta")]
+    pub max_relative_delta: Option<f64>,
+    #[serde(rename = "mean-relative-delta")]
+    pub mean_relative_delta: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawEdgeUsersTracking {
+    #[serde(rename = "top-k")]
+    pub top_k: Option<usize>,
+    #[serde(rename = "min-workload")]
+    pub min_workload: Option<f64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawInitialWorkload {
+    #[serde(rename = "zero")]
+    Zero,
+    #[serde(rename = "uniform")]
+    Uniform(f64),
+    #[serde(rename = "freeflow")]
+    Freeflow {
+        #[serde(rename = "lane-count-id")]
+        lane_count_id: SimpleId,
+        #[serde(rename = "distance-id")]
+        distance_id: SimpleId,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawResultsFormat {
+    #[serde(rename = "full")]
+    Full,
+    #[serde(rename = "delta")]
+    Delta,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawDemandSampling {
+    #[serde(rename = "rounded")]
+    Rounded,
+    #[serde(rename = "expected")]
+    Expected,
+    #[serde(rename = "poisson")]
+    Poisson { seed: u64 },
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -285,6 +723,8 @@ pub struct RawMonitoringConfig {
     edges_info: configs::writing::network::edges::ProtoConfig,
     #[serde(rename = "export_vehicles_for_SMARTS")]
     is_writing_for_smarts: Option<bool>,
+    #[serde(rename = "export_path_stability")]
+    is_writing_path_stability: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
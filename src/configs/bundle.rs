@@ -0,0 +1,65 @@
+use crate::{configs, helpers::err};
+use std::path::{Path, PathBuf};
+
+/// Names everything `io::load(...)` should load, so binaries and library-users load their
+/// artifacts in the same order.
+///
+/// The parsing-config is given as config (and not as file), since the balancer adjusts it
+/// between its iterations.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub parsing: configs::parsing::Config,
+    /// Route-pairs are loaded, if given.
+    pub routing: Option<Routing>,
+    /// The edge-grid for bounding-box-queries is built eagerly, if true.
+    pub is_building_spatial_index: bool,
+}
+
+/// The yaml-file of the routing-profiles and the chosen profile (the default-profile otherwise).
+#[derive(Clone, Debug)]
+pub struct Routing {
+    pub file: PathBuf,
+    pub profile: Option<String>,
+}
+
+impl Config {
+    /// Only the graph would be loaded.
+    pub fn new(parsing_cfg: configs::parsing::Config) -> Config {
+        Config {
+            parsing: parsing_cfg,
+            routing: None,
+            is_building_spatial_index: false,
+        }
+    }
+
+    /// Reads the parsing-config of the given yaml-file.
+    /// Route-pairs and spatial index can be added afterwards.
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        Ok(Config::new(configs::parsing::Config::try_from_yaml(path)?))
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Loads the route-pairs of the given profile of the given yaml-file as well.
+    pub fn with_routing<P: AsRef<Path> + ?Sized>(
+        mut self,
+        file: &P,
+        profile: Option<String>,
+    ) -> Config {
+        self.routing = Some(Routing {
+            file: file.as_ref().to_path_buf(),
+            profile,
+        });
+        self
+    }
+
+    pub fn with_spatial_index(mut self) -> Config {
+        self.is_building_spatial_index = true;
+        self
+    }
+}
@@ -3,6 +3,7 @@ use std::{fmt, fmt::Display};
 
 #[cfg(feature = "gpl")]
 pub mod balancing;
+pub mod bundle;
 #[cfg(feature = "gpl")]
 pub mod evaluating_balance;
 pub mod parsing;
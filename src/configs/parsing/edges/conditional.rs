@@ -0,0 +1,51 @@
+use crate::defaults::parsing::conditional as defaults;
+use serde::Deserialize;
+
+/// Describes, how conditional access-restrictions of pbf-ways (e.g.
+/// `motor_vehicle:conditional = no @ (Mo-Fr 07:00-09:00)`) are handled.
+///
+/// Their presence is parsed into metrics of unit `ConditionalRestriction`.
+/// Conditions aren't evaluated, but may be kept for later processing.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub is_keeping_conditions: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            is_keeping_conditions: defaults::IS_KEEPING_CONDITIONS,
+        }
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            is_keeping_conditions: proto_cfg
+                .is_keeping_conditions
+                .unwrap_or(defaults::IS_KEEPING_CONDITIONS),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct ProtoConfig {
+    pub is_keeping_conditions: Option<bool>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            is_keeping_conditions: raw_cfg.is_keeping_conditions,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    #[serde(rename = "keep-conditions")]
+    pub is_keeping_conditions: Option<bool>,
+}
@@ -0,0 +1,118 @@
+use crate::{defaults::parsing::cycleway as defaults, helpers::err};
+use serde::Deserialize;
+use std::{collections::BTreeMap, convert::TryFrom};
+
+/// Maps the values of the osm-tags `cycleway` and `cycleway:left/right/both` to comfort-costs
+/// for cyclists.
+///
+/// Like every metric, lower costs are better, hence separated tracks have `0.0` and streets
+/// without cycleway have `1.0` by default.
+/// Optionally, bicycle-speeds on ways with separated tracks are multiplied by a factor.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub comfort: BTreeMap<String, f64>,
+    pub track_speed_factor: Option<f64>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            comfort: to_map(defaults::COMFORT),
+            track_speed_factor: defaults::TRACK_SPEED_FACTOR,
+        }
+    }
+}
+
+impl Config {
+    /// Takes the best (lowest) comfort-costs of all given tag-values, since a cycleway on one
+    /// side is enough.
+    ///
+    /// Returns None, if no tag-value is known, so the default for missing cycleways can be used.
+    pub fn comfort<'a, I>(&self, values: I) -> Option<f64>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        values
+            .into_iter()
+            .filter_map(|value| {
+                self.comfort
+                    .get(&value.trim().to_ascii_lowercase())
+                    .copied()
+            })
+            .fold(None, |best, comfort| match best {
+                Some(best) if best <= comfort => Some(best),
+                _ => Some(comfort),
+            })
+    }
+}
+
+fn to_map(table: &[(&str, f64)]) -> BTreeMap<String, f64> {
+    table
+        .iter()
+        .map(|(value, comfort)| (String::from(*value), *comfort))
+        .collect()
+}
+
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let cfg = Config {
+            comfort: proto_cfg
+                .comfort
+                .unwrap_or_else(|| to_map(defaults::COMFORT)),
+            track_speed_factor: proto_cfg
+                .track_speed_factor
+                .or(defaults::TRACK_SPEED_FACTOR),
+        };
+
+        for (value, comfort) in cfg.comfort.iter() {
+            if !(comfort.is_finite() && *comfort >= 0.0) {
+                return Err(format!(
+                    "The comfort-costs of cycleway={} have to be non-negative, but are {}.",
+                    value, comfort
+                )
+                .into());
+            }
+        }
+        if let Some(factor) = cfg.track_speed_factor {
+            if !(factor.is_finite() && factor > 0.0) {
+                return Err(format!(
+                    "The speed-factor of cycle-tracks has to be positive, but is {}.",
+                    factor
+                )
+                .into());
+            }
+        }
+
+        Ok(cfg)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct ProtoConfig {
+    pub comfort: Option<BTreeMap<String, f64>>,
+    pub track_speed_factor: Option<f64>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            comfort: raw_cfg.comfort.map(|map| {
+                map.into_iter()
+                    .map(|(value, comfort)| (value.trim().to_ascii_lowercase(), comfort))
+                    .collect()
+            }),
+            track_speed_factor: raw_cfg.track_speed_factor,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    pub comfort: Option<BTreeMap<String, f64>>,
+    #[serde(rename = "track-speed-factor")]
+    pub track_speed_factor: Option<f64>,
+}
@@ -1,3 +1,4 @@
+use super::EdgeCategory;
 use crate::{
     configs::{parsing::generating::edges::metrics as gen, SimpleId},
     defaults::capacity::DimVec,
@@ -9,34 +10,35 @@ use kissunits::{
     time::{Hours, Minutes, Seconds},
 };
 use serde::Deserialize;
+use std::convert::TryFrom;
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub are_normalized: bool,
+    /// If true, the graph remembers for every metric-value, whether it has been measured,
+    /// defaulted or calculated (see `network::Provenance`).
+    pub is_tracking_provenance: bool,
     pub units: DimVec<UnitInfo>,
     pub ids: DimVec<SimpleId>,
 }
 
 impl Config {
+    /// Errs, naming the available ids, if the id doesn't exist.
     pub fn try_idx_of<S>(&self, id: S) -> err::Result<MetricIdx>
     where
         S: AsRef<str>,
     {
-        Ok(MetricIdx(
-            match self.ids.iter().position(|self_id| self_id.0 == id.as_ref()) {
-                Some(idx) => idx,
-                None => {
-                    return Err(format!(
-                        "Metric-id {} should be existent in graph, but isn't.",
-                        id.as_ref()
-                    )
-                    .into())
-                }
-            },
-        ))
+        match self.ids.iter().position(|self_id| self_id.0 == id.as_ref()) {
+            Some(idx) => Ok(MetricIdx(idx)),
+            None => Err(err::Msg::from(format!(
+                "Metric-id {} should be existent in graph, but isn't. Available metric-ids are {:?}",
+                id.as_ref(),
+                self.ids.iter().map(|id| &id.0).collect::<Vec<_>>()
+            ))),
+        }
     }
 
-    /// Panics if id doesn't exist
+    /// Panics if id doesn't exist (see `try_idx_of(...)`).
     pub fn idx_of<S>(&self, id: S) -> MetricIdx
     where
         S: AsRef<str>,
@@ -46,6 +48,46 @@ impl Config {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    /// Errs, naming the number of metrics, if the idx doesn't exist.
+    pub fn try_id_of(&self, idx: MetricIdx) -> err::Result<&SimpleId> {
+        match self.ids.get(*idx) {
+            Some(id) => Ok(id),
+            None => Err(err::Msg::from(format!(
+                "Metric-idx {} should be existent in graph, but only {} metrics exist.",
+                *idx,
+                self.ids.len()
+            ))),
+        }
+    }
+
+    /// Panics if idx doesn't exist (see `try_id_of(...)`).
+    pub fn id_of(&self, idx: MetricIdx) -> &SimpleId {
+        match self.try_id_of(idx) {
+            Ok(id) => id,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Errs, naming the number of metrics, if the idx doesn't exist.
+    pub fn try_unit_of(&self, idx: MetricIdx) -> err::Result<&UnitInfo> {
+        match self.units.get(*idx) {
+            Some(unit) => Ok(unit),
+            None => Err(err::Msg::from(format!(
+                "Metric-idx {} should be existent in graph, but only {} metrics exist.",
+                *idx,
+                self.units.len()
+            ))),
+        }
+    }
+
+    /// Panics if idx doesn't exist (see `try_unit_of(...)`).
+    pub fn unit_of(&self, idx: MetricIdx) -> &UnitInfo {
+        match self.try_unit_of(idx) {
+            Ok(unit) => unit,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
@@ -57,6 +99,14 @@ pub enum UnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    /// Factor of a street's surface, where `1.0` is smooth asphalt and higher is rougher.
+    Roughness,
+    /// Flag (`0.0` or `1.0`), whether a street's access is restricted under some conditions,
+    /// e.g. at certain times.
+    ConditionalRestriction,
+    /// Comfort-costs of a street for cyclists, where `0.0` is a separated cycle-track and higher
+    /// is less comfortable.
+    CyclewayComfort,
     F64,
 }
 
@@ -70,6 +120,9 @@ impl From<ProtoUnitInfo> for UnitInfo {
             ProtoUnitInfo::Hours => UnitInfo::Hours,
             ProtoUnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
             ProtoUnitInfo::LaneCount => UnitInfo::LaneCount,
+            ProtoUnitInfo::Roughness => UnitInfo::Roughness,
+            ProtoUnitInfo::ConditionalRestriction => UnitInfo::ConditionalRestriction,
+            ProtoUnitInfo::CyclewayComfort => UnitInfo::CyclewayComfort,
             ProtoUnitInfo::F64 => UnitInfo::F64,
         }
     }
@@ -85,6 +138,7 @@ impl From<gen::UnitInfo> for UnitInfo {
             gen::UnitInfo::Hours => UnitInfo::Hours,
             gen::UnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
             gen::UnitInfo::LaneCount => UnitInfo::LaneCount,
+            gen::UnitInfo::Roughness => UnitInfo::Roughness,
             gen::UnitInfo::F64 => UnitInfo::F64,
         }
     }
@@ -100,7 +154,10 @@ impl UnitInfo {
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
             },
             UnitInfo::Kilometers => match to {
                 UnitInfo::Meters => Some(*Meters::from(Kilometers(raw_value))),
@@ -109,7 +166,10 @@ impl UnitInfo {
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
             },
             UnitInfo::Seconds => match to {
                 UnitInfo::Seconds | UnitInfo::F64 => Some(raw_value),
@@ -118,7 +178,10 @@ impl UnitInfo {
                 UnitInfo::Meters
                 | UnitInfo::Kilometers
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
             },
             UnitInfo::Minutes => match to {
                 UnitInfo::Minutes | UnitInfo::F64 => Some(raw_value),
@@ -127,7 +190,10 @@ impl UnitInfo {
                 UnitInfo::Meters
                 | UnitInfo::Kilometers
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
             },
             UnitInfo::Hours => match to {
                 UnitInfo::Hours | UnitInfo::F64 => Some(raw_value),
@@ -136,7 +202,10 @@ impl UnitInfo {
                 UnitInfo::Meters
                 | UnitInfo::Kilometers
                 | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
             },
             UnitInfo::KilometersPerHour => match to {
                 UnitInfo::KilometersPerHour | UnitInfo::F64 => Some(raw_value),
@@ -145,7 +214,10 @@ impl UnitInfo {
                 | UnitInfo::Seconds
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
-                | UnitInfo::LaneCount => None,
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
             },
             UnitInfo::LaneCount => match to {
                 UnitInfo::LaneCount | UnitInfo::F64 => Some(raw_value),
@@ -154,7 +226,46 @@ impl UnitInfo {
                 | UnitInfo::Seconds
                 | UnitInfo::Minutes
                 | UnitInfo::Hours
-                | UnitInfo::KilometersPerHour => None,
+                | UnitInfo::KilometersPerHour
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
+            },
+            UnitInfo::Roughness => match to {
+                UnitInfo::Roughness | UnitInfo::F64 => Some(raw_value),
+                UnitInfo::Meters
+                | UnitInfo::Kilometers
+                | UnitInfo::Seconds
+                | UnitInfo::Minutes
+                | UnitInfo::Hours
+                | UnitInfo::KilometersPerHour
+                | UnitInfo::LaneCount
+                | UnitInfo::ConditionalRestriction
+                | UnitInfo::CyclewayComfort => None,
+            },
+            UnitInfo::ConditionalRestriction => match to {
+                UnitInfo::ConditionalRestriction | UnitInfo::F64 => Some(raw_value),
+                UnitInfo::Meters
+                | UnitInfo::Kilometers
+                | UnitInfo::Seconds
+                | UnitInfo::Minutes
+                | UnitInfo::Hours
+                | UnitInfo::KilometersPerHour
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::CyclewayComfort => None,
+            },
+            UnitInfo::CyclewayComfort => match to {
+                UnitInfo::CyclewayComfort | UnitInfo::F64 => Some(raw_value),
+                UnitInfo::Meters
+                | UnitInfo::Kilometers
+                | UnitInfo::Seconds
+                | UnitInfo::Minutes
+                | UnitInfo::Hours
+                | UnitInfo::KilometersPerHour
+                | UnitInfo::LaneCount
+                | UnitInfo::Roughness
+                | UnitInfo::ConditionalRestriction => None,
             },
             UnitInfo::F64 => Some(raw_value),
         };
@@ -172,6 +283,23 @@ impl UnitInfo {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    /// Returns the unit's symbol for displaying values, which is empty for unitless metrics.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            UnitInfo::Meters => "m",
+            UnitInfo::Kilometers => "km",
+            UnitInfo::Seconds => "s",
+            UnitInfo::Minutes => "min",
+            UnitInfo::Hours => "h",
+            UnitInfo::KilometersPerHour => "km/h",
+            UnitInfo::LaneCount => "lanes",
+            UnitInfo::Roughness
+            | UnitInfo::ConditionalRestriction
+            | UnitInfo::CyclewayComfort
+            | UnitInfo::F64 => "",
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
@@ -183,6 +311,9 @@ pub enum ProtoUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Roughness,
+    ConditionalRestriction,
+    CyclewayComfort,
     F64,
 }
 
@@ -196,12 +327,17 @@ impl From<RawUnitInfo> for ProtoUnitInfo {
             RawUnitInfo::Hours => ProtoUnitInfo::Hours,
             RawUnitInfo::KilometersPerHour => ProtoUnitInfo::KilometersPerHour,
             RawUnitInfo::LaneCount => ProtoUnitInfo::LaneCount,
+            RawUnitInfo::Roughness => ProtoUnitInfo::Roughness,
+            RawUnitInfo::ConditionalRestriction => ProtoUnitInfo::ConditionalRestriction,
+            RawUnitInfo::CyclewayComfort => ProtoUnitInfo::CyclewayComfort,
             RawUnitInfo::F64 => ProtoUnitInfo::F64,
         }
     }
 }
 
+/// Deserialized by the names of `EdgeCategory`, so the yaml accepts exactly these names.
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
 pub enum RawUnitInfo {
     Meters,
     Kilometers,
@@ -210,5 +346,32 @@ pub enum RawUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Roughness,
+    ConditionalRestriction,
+    CyclewayComfort,
     F64,
 }
+
+impl TryFrom<String> for RawUnitInfo {
+    type Error = err::Msg;
+
+    fn try_from(name: String) -> err::Result<RawUnitInfo> {
+        match name.parse::<EdgeCategory>()? {
+            EdgeCategory::Meters => Ok(RawUnitInfo::Meters),
+            EdgeCategory::Kilometers => Ok(RawUnitInfo::Kilometers),
+            EdgeCategory::Seconds => Ok(RawUnitInfo::Seconds),
+            EdgeCategory::Minutes => Ok(RawUnitInfo::Minutes),
+            EdgeCategory::Hours => Ok(RawUnitInfo::Hours),
+            EdgeCategory::KilometersPerHour => Ok(RawUnitInfo::KilometersPerHour),
+            EdgeCategory::LaneCount => Ok(RawUnitInfo::LaneCount),
+            EdgeCategory::Roughness => Ok(RawUnitInfo::Roughness),
+            EdgeCategory::ConditionalRestriction => Ok(RawUnitInfo::ConditionalRestriction),
+            EdgeCategory::CyclewayComfort => Ok(RawUnitInfo::CyclewayComfort),
+            EdgeCategory::F64 => Ok(RawUnitInfo::F64),
+            category => Err(err::Msg::from(format!(
+                "The edge-category {} is no metric-unit.",
+                category
+            ))),
+        }
+    }
+}
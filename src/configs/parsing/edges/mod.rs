@@ -4,8 +4,15 @@ use crate::{
     helpers::err,
 };
 use serde::Deserialize;
+pub mod conditional;
+pub mod cycleway;
 pub mod metrics;
-use std::convert::TryFrom;
+pub mod roughness;
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    str::FromStr,
+};
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -14,6 +21,30 @@ pub struct Config {
 
     // store only metrics for quick access
     pub metrics: metrics::Config,
+
+    pub roughness: roughness::Config,
+
+    pub conditional: conditional::Config,
+
+    pub cycleway: cycleway::Config,
+
+    /// If true, `*_link`-ways without maxspeed get a fraction of their parent street-category's
+    /// maxspeed (see `link_speed_fraction()`).
+    pub is_inferring_link_speeds: bool,
+
+    pub link_speed_fraction: f64,
+}
+
+impl Config {
+    /// Returns the fraction of the parent street-category's maxspeed, which `*_link`-ways without
+    /// maxspeed get, or None, if link-speeds aren't inferred.
+    pub fn link_speed_fraction(&self) -> Option<f64> {
+        if self.is_inferring_link_speeds {
+            Some(self.link_speed_fraction)
+        } else {
+            None
+        }
+    }
 }
 
 impl TryFrom<ProtoConfig> for Config {
@@ -77,15 +108,45 @@ impl TryFrom<ProtoConfig> for Config {
             }
         }
 
+        let link_speed_fraction = proto_cfg
+            .link_speed_fraction
+            .unwrap_or(defaults::parsing::link_speeds::FRACTION);
+        if !(link_speed_fraction.is_finite() && link_speed_fraction > 0.0) {
+            return Err(format!(
+                "The link-speed-fraction has to be positive, but is {}.",
+                link_speed_fraction
+            )
+            .into());
+        }
+
         Ok(Config {
             categories,
             metrics: metrics::Config {
                 are_normalized: proto_cfg
                     .are_metrics_normalized
                     .unwrap_or(defaults::parsing::WILL_NORMALIZE_METRICS_BY_MEAN),
+                is_tracking_provenance: proto_cfg
+                    .is_tracking_provenance
+                    .unwrap_or(defaults::parsing::IS_TRACKING_PROVENANCE),
                 units: metric_units,
                 ids: metric_ids,
             },
+            roughness: match proto_cfg.roughness {
+                Some(proto_roughness) => roughness::Config::try_from(proto_roughness)?,
+                None => roughness::Config::default(),
+            },
+            conditional: proto_cfg
+                .conditional
+                .map(conditional::Config::from)
+                .unwrap_or_default(),
+            cycleway: match proto_cfg.cycleway {
+                Some(proto_cycleway) => cycleway::Config::try_from(proto_cycleway)?,
+                None => cycleway::Config::default(),
+            },
+            is_inferring_link_speeds: proto_cfg
+                .is_inferring_link_speeds
+                .unwrap_or(defaults::parsing::link_speeds::IS_INFERRING),
+            link_speed_fraction,
         })
     }
 }
@@ -117,6 +178,13 @@ impl Category {
             Category::Ignored => true,
         }
     }
+
+    pub fn id(&self) -> Option<&SimpleId> {
+        match self {
+            Category::Meta { info: _, id } | Category::Metric { unit: _, id } => Some(id),
+            Category::Ignored => None,
+        }
+    }
 }
 
 impl From<ProtoCategory> for Category {
@@ -135,6 +203,185 @@ impl From<ProtoCategory> for Category {
     }
 }
 
+/// The categories of edge-data, which can be configured in the parsing-config's `data`, either
+/// as `meta`-info (structural), as `metric`-unit or as `ignored`.
+///
+/// Their names (see `Display` and `FromStr`) are the names accepted in the yaml, e.g. for tools
+/// generating parsing-configs.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EdgeCategory {
+    EdgeId,
+    SrcId,
+    DstId,
+    ShortcutIdx0,
+    ShortcutIdx1,
+    Bidirectional,
+    Meters,
+    Kilometers,
+    Seconds,
+    Minutes,
+    Hours,
+    KilometersPerHour,
+    LaneCount,
+    Roughness,
+    ConditionalRestriction,
+    CyclewayComfort,
+    F64,
+    Ignored,
+}
+
+impl EdgeCategory {
+    pub fn all() -> &'static [EdgeCategory] {
+        &[
+            EdgeCategory::EdgeId,
+            EdgeCategory::SrcId,
+            EdgeCategory::DstId,
+            EdgeCategory::ShortcutIdx0,
+            EdgeCategory::ShortcutIdx1,
+            EdgeCategory::Bidirectional,
+            EdgeCategory::Meters,
+            EdgeCategory::Kilometers,
+            EdgeCategory::Seconds,
+            EdgeCategory::Minutes,
+            EdgeCategory::Hours,
+            EdgeCategory::KilometersPerHour,
+            EdgeCategory::LaneCount,
+            EdgeCategory::Roughness,
+            EdgeCategory::ConditionalRestriction,
+            EdgeCategory::CyclewayComfort,
+            EdgeCategory::F64,
+            EdgeCategory::Ignored,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeCategory::EdgeId => "EdgeId",
+            EdgeCategory::SrcId => "SrcId",
+            EdgeCategory::DstId => "DstId",
+            EdgeCategory::ShortcutIdx0 => "ShortcutIdx0",
+            EdgeCategory::ShortcutIdx1 => "ShortcutIdx1",
+            EdgeCategory::Bidirectional => "Bidirectional",
+            EdgeCategory::Meters => "Meters",
+            EdgeCategory::Kilometers => "Kilometers",
+            EdgeCategory::Seconds => "Seconds",
+            EdgeCategory::Minutes => "Minutes",
+            EdgeCategory::Hours => "Hours",
+            EdgeCategory::KilometersPerHour => "KilometersPerHour",
+            EdgeCategory::LaneCount => "LaneCount",
+            EdgeCategory::Roughness => "Roughness",
+            EdgeCategory::ConditionalRestriction => "ConditionalRestriction",
+            EdgeCategory::CyclewayComfort => "CyclewayComfort",
+            EdgeCategory::F64 => "F64",
+            EdgeCategory::Ignored => "ignored",
+        }
+    }
+
+    /// True for `metric`-units
+    pub fn is_metric(&self) -> bool {
+        !self.is_structural() && !self.is_ignored()
+    }
+
+    pub fn is_ignored(&self) -> bool {
+        *self == EdgeCategory::Ignored
+    }
+
+    /// True for `meta`-infos, which describe the graph's structure, e.g. ids
+    pub fn is_structural(&self) -> bool {
+        match self {
+            EdgeCategory::EdgeId
+            | EdgeCategory::SrcId
+            | EdgeCategory::DstId
+            | EdgeCategory::ShortcutIdx0
+            | EdgeCategory::ShortcutIdx1
+            | EdgeCategory::Bidirectional => true,
+            EdgeCategory::Meters
+            | EdgeCategory::Kilometers
+            | EdgeCategory::Seconds
+            | EdgeCategory::Minutes
+            | EdgeCategory::Hours
+            | EdgeCategory::KilometersPerHour
+            | EdgeCategory::LaneCount
+            | EdgeCategory::Roughness
+            | EdgeCategory::ConditionalRestriction
+            | EdgeCategory::CyclewayComfort
+            | EdgeCategory::F64
+            | EdgeCategory::Ignored => false,
+        }
+    }
+}
+
+impl Display for EdgeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for EdgeCategory {
+    type Err = err::Msg;
+
+    fn from_str(name: &str) -> err::Result<EdgeCategory> {
+        match EdgeCategory::all()
+            .iter()
+            .find(|category| category.name() == name)
+        {
+            Some(category) => Ok(*category),
+            None => Err(err::Msg::from(format!(
+                "Unknown edge-category `{}`. Valid edge-categories are {:?}",
+                name,
+                EdgeCategory::all()
+                    .iter()
+                    .map(|category| category.name())
+                    .collect::<Vec<_>>()
+            ))),
+        }
+    }
+}
+
+/// Fails for meta-infos, which can only be generated (e.g. `SrcIdx`).
+impl TryFrom<&Category> for EdgeCategory {
+    type Error = err::Msg;
+
+    fn try_from(category: &Category) -> err::Result<EdgeCategory> {
+        Ok(match category {
+            Category::Meta { info, id } => match info {
+                MetaInfo::EdgeId => EdgeCategory::EdgeId,
+                MetaInfo::SrcId => EdgeCategory::SrcId,
+                MetaInfo::DstId => EdgeCategory::DstId,
+                MetaInfo::ShortcutIdx0 => EdgeCategory::ShortcutIdx0,
+                MetaInfo::ShortcutIdx1 => EdgeCategory::ShortcutIdx1,
+                MetaInfo::Bidirectional => EdgeCategory::Bidirectional,
+                MetaInfo::SrcIdx
+                | MetaInfo::SrcLat
+                | MetaInfo::SrcLon
+                | MetaInfo::DstIdx
+                | MetaInfo::DstLat
+                | MetaInfo::DstLon
+                | MetaInfo::StableId => {
+                    return Err(err::Msg::from(format!(
+                        "The edge-meta-info {:?} of {} can only be generated.",
+                        info, id
+                    )))
+                }
+            },
+            Category::Metric { unit, id: _ } => match unit {
+                metrics::UnitInfo::Meters => EdgeCategory::Meters,
+                metrics::UnitInfo::Kilometers => EdgeCategory::Kilometers,
+                metrics::UnitInfo::Seconds => EdgeCategory::Seconds,
+                metrics::UnitInfo::Minutes => EdgeCategory::Minutes,
+                metrics::UnitInfo::Hours => EdgeCategory::Hours,
+                metrics::UnitInfo::KilometersPerHour => EdgeCategory::KilometersPerHour,
+                metrics::UnitInfo::LaneCount => EdgeCategory::LaneCount,
+                metrics::UnitInfo::Roughness => EdgeCategory::Roughness,
+                metrics::UnitInfo::ConditionalRestriction => EdgeCategory::ConditionalRestriction,
+                metrics::UnitInfo::CyclewayComfort => EdgeCategory::CyclewayComfort,
+                metrics::UnitInfo::F64 => EdgeCategory::F64,
+            },
+            Category::Ignored => EdgeCategory::Ignored,
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
 pub enum MetaInfo {
     EdgeId,
@@ -146,8 +393,12 @@ pub enum MetaInfo {
     DstIdx,
     DstLat,
     DstLon,
+    /// Hash of src-id, dst-id and length (see `Graph::stable_ids()`)
+    StableId,
     ShortcutIdx0,
     ShortcutIdx1,
+    /// Flag (0 or 1) of fmi-rows, which describe both directions of an edge
+    Bidirectional,
 }
 
 impl From<ProtoMetaInfo> for MetaInfo {
@@ -158,6 +409,7 @@ impl From<ProtoMetaInfo> for MetaInfo {
             ProtoMetaInfo::DstId => MetaInfo::DstId,
             ProtoMetaInfo::ShortcutIdx0 => MetaInfo::ShortcutIdx0,
             ProtoMetaInfo::ShortcutIdx1 => MetaInfo::ShortcutIdx1,
+            ProtoMetaInfo::Bidirectional => MetaInfo::Bidirectional,
         }
     }
 }
@@ -172,6 +424,7 @@ impl From<generating::edges::MetaInfo> for MetaInfo {
             generating::edges::MetaInfo::DstIdx => MetaInfo::DstIdx,
             generating::edges::MetaInfo::DstLat => MetaInfo::DstLat,
             generating::edges::MetaInfo::DstLon => MetaInfo::DstLon,
+            generating::edges::MetaInfo::StableId => MetaInfo::StableId,
             generating::edges::MetaInfo::ShortcutIdx0 => MetaInfo::ShortcutIdx0,
             generating::edges::MetaInfo::ShortcutIdx1 => MetaInfo::ShortcutIdx1,
         }
@@ -182,14 +435,26 @@ impl From<generating::edges::MetaInfo> for MetaInfo {
 #[serde(from = "RawConfig", deny_unknown_fields)]
 pub struct ProtoConfig {
     pub are_metrics_normalized: Option<bool>,
+    pub is_tracking_provenance: Option<bool>,
     pub categories: Vec<ProtoCategory>,
+    pub roughness: Option<roughness::ProtoConfig>,
+    pub conditional: Option<conditional::ProtoConfig>,
+    pub cycleway: Option<cycleway::ProtoConfig>,
+    pub is_inferring_link_speeds: Option<bool>,
+    pub link_speed_fraction: Option<f64>,
 }
 
 impl From<RawConfig> for ProtoConfig {
     fn from(raw_cfg: RawConfig) -> ProtoConfig {
         ProtoConfig {
             are_metrics_normalized: raw_cfg.are_metrics_normalized,
+            is_tracking_provenance: raw_cfg.is_tracking_provenance,
             categories: raw_cfg.data.into_iter().map(ProtoCategory::from).collect(),
+            roughness: raw_cfg.roughness.map(roughness::ProtoConfig::from),
+            conditional: raw_cfg.conditional.map(conditional::ProtoConfig::from),
+            cycleway: raw_cfg.cycleway.map(cycleway::ProtoConfig::from),
+            is_inferring_link_speeds: raw_cfg.is_inferring_link_speeds,
+            link_speed_fraction: raw_cfg.link_speed_fraction,
         }
     }
 }
@@ -231,6 +496,7 @@ pub enum ProtoMetaInfo {
     DstId,
     ShortcutIdx0,
     ShortcutIdx1,
+    Bidirectional,
 }
 
 impl From<RawMetaInfo> for ProtoMetaInfo {
@@ -241,6 +507,7 @@ impl From<RawMetaInfo> for ProtoMetaInfo {
             RawMetaInfo::DstId => ProtoMetaInfo::DstId,
             RawMetaInfo::ShortcutIdx0 => ProtoMetaInfo::ShortcutIdx0,
             RawMetaInfo::ShortcutIdx1 => ProtoMetaInfo::ShortcutIdx1,
+            RawMetaInfo::Bidirectional => ProtoMetaInfo::Bidirectional,
         }
     }
 }
@@ -250,7 +517,17 @@ impl From<RawMetaInfo> for ProtoMetaInfo {
 pub struct RawConfig {
     #[serde(rename = "will_normalize_metrics_by_mean")]
     are_metrics_normalized: Option<bool>,
+    #[serde(rename = "track-provenance")]
+    is_tracking_provenance: Option<bool>,
     data: Vec<RawCategory>,
+    roughness: Option<roughness::RawConfig>,
+    #[serde(rename = "conditional-restrictions")]
+    conditional: Option<conditional::RawConfig>,
+    cycleway: Option<cycleway::RawConfig>,
+    #[serde(rename = "infer-link-speeds")]
+    is_inferring_link_speeds: Option<bool>,
+    #[serde(rename = "link-speed-fraction")]
+    link_speed_fraction: Option<f64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -267,11 +544,33 @@ pub enum RawCategory {
     Ignored,
 }
 
+/// Deserialized by the names of `EdgeCategory`, so the yaml accepts exactly these names.
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
 pub enum RawMetaInfo {
     EdgeId,
     SrcId,
     DstId,
     ShortcutIdx0,
     ShortcutIdx1,
+    Bidirectional,
+}
+
+impl TryFrom<String> for RawMetaInfo {
+    type Error = err::Msg;
+
+    fn try_from(name: String) -> err::Result<RawMetaInfo> {
+        match name.parse::<EdgeCategory>()? {
+            EdgeCategory::EdgeId => Ok(RawMetaInfo::EdgeId),
+            EdgeCategory::SrcId => Ok(RawMetaInfo::SrcId),
+            EdgeCategory::DstId => Ok(RawMetaInfo::DstId),
+            EdgeCategory::ShortcutIdx0 => Ok(RawMetaInfo::ShortcutIdx0),
+            EdgeCategory::ShortcutIdx1 => Ok(RawMetaInfo::ShortcutIdx1),
+            EdgeCategory::Bidirectional => Ok(RawMetaInfo::Bidirectional),
+            category => Err(err::Msg::from(format!(
+                "The edge-category {} is no meta-info.",
+                category
+            ))),
+        }
+    }
 }
@@ -0,0 +1,108 @@
+use crate::{defaults::parsing::roughness as defaults, helpers::err};
+use serde::Deserialize;
+use std::{collections::BTreeMap, convert::TryFrom};
+
+/// Maps the values of the osm-tags `surface` and `tracktype` to a roughness-factor.
+///
+/// A factor of `1.0` describes smooth asphalt, higher factors describe rougher surfaces.
+/// When applied to durations, the speed is divided by this factor.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub is_applied_to_duration: bool,
+    pub surface: BTreeMap<String, f64>,
+    pub tracktype: BTreeMap<String, f64>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            is_applied_to_duration: defaults::IS_APPLIED_TO_DURATION,
+            surface: to_map(defaults::SURFACE),
+            tracktype: to_map(defaults::TRACKTYPE),
+        }
+    }
+}
+
+impl Config {
+    /// The tag `surface` is more specific than `tracktype`, hence it has precedence.
+    ///
+    /// Returns None, if no tag-value is known, so the street-category's default can be used.
+    pub fn factor(&self, surface: Option<&str>, tracktype: Option<&str>) -> Option<f64> {
+        let lookup = |map: &BTreeMap<String, f64>, value: Option<&str>| {
+            value.and_then(|value| map.get(&value.trim().to_ascii_lowercase()).copied())
+        };
+        lookup(&self.surface, surface).or_else(|| lookup(&self.tracktype, tracktype))
+    }
+}
+
+fn to_map(table: &[(&str, f64)]) -> BTreeMap<String, f64> {
+    table
+        .iter()
+        .map(|(value, factor)| (String::from(*value), *factor))
+        .collect()
+}
+
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let cfg = Config {
+            is_applied_to_duration: proto_cfg
+                .is_applied_to_duration
+                .unwrap_or(defaults::IS_APPLIED_TO_DURATION),
+            surface: proto_cfg
+                .surface
+                .unwrap_or_else(|| to_map(defaults::SURFACE)),
+            tracktype: proto_cfg
+                .tracktype
+                .unwrap_or_else(|| to_map(defaults::TRACKTYPE)),
+        };
+
+        for (tag, map) in &[("surface", &cfg.surface), ("tracktype", &cfg.tracktype)] {
+            for (value, factor) in map.iter() {
+                if !(factor.is_finite() && *factor > 0.0) {
+                    return Err(format!(
+                        "The roughness-factor of {}={} has to be positive, but is {}.",
+                        tag, value, factor
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(cfg)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "RawConfig")]
+pub struct ProtoConfig {
+    pub is_applied_to_duration: Option<bool>,
+    pub surface: Option<BTreeMap<String, f64>>,
+    pub tracktype: Option<BTreeMap<String, f64>>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let lowercase = |map: BTreeMap<String, f64>| {
+            map.into_iter()
+                .map(|(value, factor)| (value.trim().to_ascii_lowercase(), factor))
+                .collect()
+        };
+
+        ProtoConfig {
+            is_applied_to_duration: raw_cfg.is_applied_to_duration,
+            surface: raw_cfg.surface.map(lowercase),
+            tracktype: raw_cfg.tracktype.map(lowercase),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    #[serde(rename = "apply-to-duration")]
+    pub is_applied_to_duration: Option<bool>,
+    pub surface: Option<BTreeMap<String, f64>>,
+    pub tracktype: Option<BTreeMap<String, f64>>,
+}
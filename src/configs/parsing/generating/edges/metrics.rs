@@ -36,6 +36,7 @@ pub enum UnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Roughness,
     F64,
 }
 
@@ -49,6 +50,7 @@ impl From<ProtoUnitInfo> for UnitInfo {
             ProtoUnitInfo::Hours => UnitInfo::Hours,
             ProtoUnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
             ProtoUnitInfo::LaneCount => UnitInfo::LaneCount,
+            ProtoUnitInfo::Roughness => UnitInfo::Roughness,
             ProtoUnitInfo::F64 => UnitInfo::F64,
         }
     }
@@ -65,6 +67,7 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Roughness => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Kilometers => match to {
@@ -75,6 +78,7 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Roughness => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Seconds => match to {
@@ -85,6 +89,7 @@ impl UnitInfo {
                 UnitInfo::Hours => Some(*Hours::from(Seconds(raw_value))),
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Roughness => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Minutes => match to {
@@ -95,6 +100,7 @@ impl UnitInfo {
                 UnitInfo::Hours => Some(*Hours::from(Minutes(raw_value))),
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Roughness => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::Hours => match to {
@@ -105,6 +111,7 @@ impl UnitInfo {
                 UnitInfo::Hours => Some(raw_value),
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => None,
+                UnitInfo::Roughness => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::KilometersPerHour => match to {
@@ -115,6 +122,7 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => Some(raw_value),
                 UnitInfo::LaneCount => None,
+                UnitInfo::Roughness => None,
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::LaneCount => match to {
@@ -125,6 +133,18 @@ impl UnitInfo {
                 UnitInfo::Hours => None,
                 UnitInfo::KilometersPerHour => None,
                 UnitInfo::LaneCount => Some(raw_value),
+                UnitInfo::Roughness => None,
+                UnitInfo::F64 => Some(raw_value),
+            },
+            UnitInfo::Roughness => match to {
+                UnitInfo::Meters => None,
+                UnitInfo::Kilometers => None,
+                UnitInfo::Seconds => None,
+                UnitInfo::Minutes => None,
+                UnitInfo::Hours => None,
+                UnitInfo::KilometersPerHour => None,
+                UnitInfo::LaneCount => None,
+                UnitInfo::Roughness => Some(raw_value),
                 UnitInfo::F64 => Some(raw_value),
             },
             UnitInfo::F64 => Some(raw_value),
@@ -156,6 +176,7 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -165,6 +186,7 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => true,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*Seconds::from(Kilometers(raw_a) / KilometersPerHour(raw_b)))
@@ -181,6 +203,7 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -190,6 +213,7 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => true,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*Minutes::from(Kilometers(raw_a) / KilometersPerHour(raw_b)))
@@ -206,6 +230,7 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -215,6 +240,7 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => true,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*Hours::from(Kilometers(raw_a) / KilometersPerHour(raw_b)))
@@ -231,6 +257,7 @@ impl UnitInfo {
                     UnitInfo::Hours => false,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } && match unit_b {
                     UnitInfo::Meters => false,
@@ -240,6 +267,7 @@ impl UnitInfo {
                     UnitInfo::Hours => true,
                     UnitInfo::KilometersPerHour => false,
                     UnitInfo::LaneCount => false,
+                    UnitInfo::Roughness => false,
                     UnitInfo::F64 => false,
                 } {
                     Some(*KilometersPerHour::from(Kilometers(raw_a) / Hours(raw_b)))
@@ -248,6 +276,7 @@ impl UnitInfo {
                 }
             }
             UnitInfo::LaneCount => None,
+            UnitInfo::Roughness => None,
             UnitInfo::F64 => None,
         };
 
@@ -287,6 +316,7 @@ pub enum ProtoUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Roughness,
     F64,
 }
 
@@ -300,6 +330,7 @@ impl From<RawUnitInfo> for ProtoUnitInfo {
             RawUnitInfo::Hours => ProtoUnitInfo::Hours,
             RawUnitInfo::KilometersPerHour => ProtoUnitInfo::KilometersPerHour,
             RawUnitInfo::LaneCount => ProtoUnitInfo::LaneCount,
+            RawUnitInfo::Roughness => ProtoUnitInfo::Roughness,
             RawUnitInfo::F64 => ProtoUnitInfo::F64,
         }
     }
@@ -320,5 +351,6 @@ pub enum RawUnitInfo {
     Hours,
     KilometersPerHour,
     LaneCount,
+    Roughness,
     F64,
 }
@@ -41,6 +41,10 @@ pub enum Category {
         result: metrics::Category,
         a: metrics::Category,
         b: metrics::Category,
+        /// If true, the result depends on the edge's direction, e.g. a bicycle's duration uphill.
+        /// Then, a gradient is needed.
+        is_directional: bool,
+        gradient: Option<Gradient>,
     },
     // in-place
     Merge {
@@ -75,10 +79,18 @@ impl From<ProtoCategory> for Category {
                 from: from.into(),
                 to: to.into(),
             },
-            ProtoCategory::Calc { result, a, b } => Category::Calc {
+            ProtoCategory::Calc {
+                result,
+                a,
+                b,
+                is_directional,
+                gradient,
+            } => Category::Calc {
                 result: result.into(),
                 a: a.into(),
                 b: b.into(),
+                is_directional,
+                gradient: gradient.map(Gradient::from),
             },
             ProtoCategory::Merge {
                 from,
@@ -105,6 +117,8 @@ pub enum MetaInfo {
     DstIdx,
     DstLat,
     DstLon,
+    /// Hash of src-id, dst-id and length (see `Graph::stable_ids()`)
+    StableId,
     ShortcutIdx0,
     ShortcutIdx1,
 }
@@ -119,12 +133,47 @@ impl From<ProtoMetaInfo> for MetaInfo {
             ProtoMetaInfo::DstIdx => MetaInfo::DstIdx,
             ProtoMetaInfo::DstLat => MetaInfo::DstLat,
             ProtoMetaInfo::DstLon => MetaInfo::DstLon,
+            ProtoMetaInfo::StableId => MetaInfo::StableId,
             ProtoMetaInfo::ShortcutIdx0 => MetaInfo::ShortcutIdx0,
             ProtoMetaInfo::ShortcutIdx1 => MetaInfo::ShortcutIdx1,
         }
     }
 }
 
+/// Scales the speed of an edge by its gradient, which is computed from the elevations of its src and dst.
+///
+/// Uphill, the speed is scaled by `1 / (1 + k * grade)`.
+/// Downhill, the same formula speeds up, but at most by `max_downhill_factor`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Gradient {
+    /// Id of the nodes' custom metric (unit `F64`), which holds the elevation in meters.
+    pub elevation_id: SimpleId,
+    pub k: f64,
+    pub max_downhill_factor: f64,
+}
+
+impl From<ProtoGradient> for Gradient {
+    fn from(proto_gradient: ProtoGradient) -> Gradient {
+        Gradient {
+            elevation_id: proto_gradient.elevation_id,
+            k: proto_gradient.k,
+            max_downhill_factor: proto_gradient.max_downhill_factor,
+        }
+    }
+}
+
+impl Gradient {
+    /// The grade is the elevation-difference (dst - src) divided by the edge's length, e.g. `0.05` for 5 % uphill.
+    pub fn speed_factor(&self, grade: f64) -> f64 {
+        let divisor = 1.0 + self.k * grade;
+        if divisor <= 0.0 {
+            self.max_downhill_factor
+        } else {
+            (1.0 / divisor).min(self.max_downhill_factor)
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields, from = "RawConfig")]
 pub struct ProtoConfig(pub Vec<ProtoCategory>);
@@ -163,6 +212,8 @@ pub enum ProtoCategory {
         result: metrics::ProtoCategory,
         a: metrics::ProtoCategory,
         b: metrics::ProtoCategory,
+        is_directional: bool,
+        gradient: Option<ProtoGradient>,
     },
     Merge {
         from: PathBuf,
@@ -196,10 +247,19 @@ impl From<RawCategory> for ProtoCategory {
                 from: metrics::ProtoCategory::from(from),
                 to: metrics::ProtoCategory::from(to),
             },
-            RawCategory::Calc { result, a, b } => ProtoCategory::Calc {
+            RawCategory::Calc {
+                result,
+                a,
+                b,
+                is_directional,
+                gradient,
+            } => ProtoCategory::Calc {
                 result: metrics::ProtoCategory::from(result),
                 a: metrics::ProtoCategory::from(a),
                 b: metrics::ProtoCategory::from(b),
+                is_directional: is_directional
+                    .unwrap_or(defaults::parsing::generating::IS_DIRECTIONAL),
+                gradient: gradient.map(ProtoGradient::from),
             },
             RawCategory::Merge {
                 from,
@@ -219,6 +279,27 @@ impl From<RawCategory> for ProtoCategory {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProtoGradient {
+    pub elevation_id: SimpleId,
+    pub k: f64,
+    pub max_downhill_factor: f64,
+}
+
+impl From<RawGradient> for ProtoGradient {
+    fn from(raw_gradient: RawGradient) -> ProtoGradient {
+        ProtoGradient {
+            elevation_id: raw_gradient.elevation_id,
+            k: raw_gradient
+                .k
+                .unwrap_or(defaults::parsing::generating::GRADIENT_K),
+            max_downhill_factor: raw_gradient
+                .max_downhill_factor
+                .unwrap_or(defaults::parsing::generating::MAX_DOWNHILL_FACTOR),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
 pub enum ProtoMetaInfo {
     EdgeId,
@@ -228,6 +309,8 @@ pub enum ProtoMetaInfo {
     DstIdx,
     DstLat,
     DstLon,
+    /// Hash of src-id, dst-id and length (see `Graph::stable_ids()`)
+    StableId,
     ShortcutIdx0,
     ShortcutIdx1,
 }
@@ -242,6 +325,7 @@ impl From<RawMetaInfo> for ProtoMetaInfo {
             RawMetaInfo::DstIdx => ProtoMetaInfo::DstIdx,
             RawMetaInfo::DstLat => ProtoMetaInfo::DstLat,
             RawMetaInfo::DstLon => ProtoMetaInfo::DstLon,
+            RawMetaInfo::StableId => ProtoMetaInfo::StableId,
         }
     }
 }
@@ -278,6 +362,9 @@ pub enum RawCategory {
         result: metrics::RawCategory,
         a: metrics::RawCategory,
         b: metrics::RawCategory,
+        #[serde(rename = "directional")]
+        is_directional: Option<bool>,
+        gradient: Option<RawGradient>,
     },
     Merge {
         from: PathBuf,
@@ -290,6 +377,16 @@ pub enum RawCategory {
     },
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawGradient {
+    #[serde(rename = "elevation-id")]
+    pub elevation_id: SimpleId,
+    pub k: Option<f64>,
+    #[serde(rename = "max-downhill-factor")]
+    pub max_downhill_factor: Option<f64>,
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
 pub enum RawMetaInfo {
     EdgeId,
@@ -299,4 +396,5 @@ pub enum RawMetaInfo {
     DstIdx,
     DstLat,
     DstLon,
+    StableId,
 }
@@ -1,4 +1,5 @@
 use crate::{
+    defaults,
     helpers::err,
     io::{network::graph::Parser, SupportingFileExts},
 };
@@ -12,6 +13,7 @@ use std::{
 pub mod edges;
 pub mod generating;
 pub mod nodes;
+pub mod profiles;
 pub mod vehicles;
 
 /// # Set config-values with yaml-file (TODO update this text)
@@ -28,9 +30,25 @@ pub mod vehicles;
 #[derive(Clone, Debug, Deserialize)]
 #[serde(try_from = "ProtoConfig")]
 pub struct Config {
-    pub map_file: PathBuf,
+    /// All map-files are parsed into one graph, hence they need the same file-extension.
+    pub map_files: Vec<PathBuf>,
     pub vehicles: vehicles::Config,
     pub nodes: nodes::Config,
+    pub on_node_conflict: nodes::ConflictPolicy,
+    pub merging_stacked_nodes: Option<nodes::StackedNodes>,
+    /// Only supported by pbf-files, since fmi-files are extracts already.
+    pub bbox: Option<nodes::BoundingBox>,
+    pub elevation: Option<nodes::Elevation>,
+    pub is_ignoring_embedded_cfg: bool,
+    /// Replaces invalid UTF-8 in fmi-files by replacement-characters (`U+FFFD`) instead of
+    /// rejecting the file, e.g. for comments with umlauts in latin-1.
+    pub is_lossy_utf8: bool,
+    /// Stores the graph's offsets and edge-index-maps as `u32` instead of `usize`, which halves
+    /// their memory on 64-bit systems, but allows at most `u32::MAX` edges.
+    pub is_using_compact_indices: bool,
+    /// Number of threads decoding blocks of pbf-files, where 1 decodes sequentially.
+    /// Other map-files are always parsed sequentially.
+    pub num_threads: usize,
     pub edges: edges::Config,
     pub generating: Option<generating::Config>,
 }
@@ -69,10 +87,12 @@ impl Config {
             }
         };
 
-        match Parser::find_supported_ext(&cfg.map_file) {
-            Ok(_) => Ok(cfg),
-            Err(msg) => Err(err::Msg::from(format!("Wrong parser-map-file: {}", msg))),
+        for map_file in cfg.map_files.iter() {
+            if let Err(msg) = Parser::find_supported_ext(map_file) {
+                return Err(err::Msg::from(format!("Wrong parser-map-file: {}", msg)));
+            }
         }
+        Ok(cfg)
     }
 
     pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
@@ -81,19 +101,102 @@ impl Config {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    pub fn try_from_str(yaml_str: &str) -> err::Result<Config> {
+        let cfg: Config = match serde_yaml::from_str(yaml_str) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't parse yaml-str due to error: {}",
+                    e
+                )))
+            }
+        };
+
+        for map_file in cfg.map_files.iter() {
+            if let Err(msg) = Parser::find_supported_ext(map_file) {
+                return Err(err::Msg::from(format!("Wrong parser-map-file: {}", msg)));
+            }
+        }
+        Ok(cfg)
+    }
+
+    pub fn from_str(yaml_str: &str) -> Config {
+        match Config::try_from_str(yaml_str) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
 }
 
 impl TryFrom<ProtoConfig> for Config {
     type Error = err::Msg;
 
     fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let nodes = nodes::Config::from(proto_cfg.nodes);
         Ok(Config {
-            map_file: proto_cfg.map_file,
+            map_files: proto_cfg.map_files,
             vehicles: match proto_cfg.vehicles {
-                Some(proto_vehicles) => vehicles::Config::from(proto_vehicles),
+                Some(proto_vehicles) => vehicles::Config::try_from(proto_vehicles)?,
                 None => vehicles::Config::default(),
             },
-            nodes: nodes::Config::from(proto_cfg.nodes),
+            on_node_conflict: proto_cfg
+                .on_node_conflict
+                .unwrap_or(defaults::parsing::nodes::ON_CONFLICT),
+            merging_stacked_nodes: match proto_cfg.merging_stacked_nodes {
+                Some(stacked_nodes) => {
+                    if !(*stacked_nodes.max_distance >= 0.0) {
+                        return Err(err::Msg::from(format!(
+                            "The max-distance of stacked nodes should be non-negative, \
+                             but is {} m.",
+                            *stacked_nodes.max_distance
+                        )));
+                    }
+                    Some(stacked_nodes)
+                }
+                None => None,
+            },
+            bbox: match proto_cfg.bbox {
+                Some(raw_bbox) => Some(nodes::BoundingBox::try_from(raw_bbox)?),
+                None => None,
+            },
+            elevation: match proto_cfg.elevation {
+                Some(elevation) => {
+                    if !elevation.default.is_finite() {
+                        return Err(err::Msg::from(format!(
+                            "The default elevation should be finite, but is {}.",
+                            elevation.default
+                        )));
+                    }
+                    if !nodes.custom_ids().contains(&&elevation.id) {
+                        return Err(err::Msg::from(format!(
+                            "The elevation-id {} should be a custom node-value (unit F64).",
+                            elevation.id
+                        )));
+                    }
+                    Some(elevation)
+                }
+                None => None,
+            },
+            is_ignoring_embedded_cfg: proto_cfg
+                .is_ignoring_embedded_cfg
+                .unwrap_or(defaults::parsing::IS_IGNORING_EMBEDDED_CFG),
+            is_lossy_utf8: proto_cfg
+                .is_lossy_utf8
+                .unwrap_or(defaults::parsing::IS_LOSSY_UTF8),
+            is_using_compact_indices: proto_cfg
+                .is_using_compact_indices
+                .unwrap_or(defaults::parsing::IS_USING_COMPACT_INDICES),
+            num_threads: match proto_cfg.num_threads {
+                Some(0) => {
+                    return Err(err::Msg::from(
+                        "The parsing-config's number of threads should be positive.",
+                    ))
+                }
+                Some(num_threads) => num_threads,
+                None => defaults::parsing::NUM_THREADS,
+            },
+            nodes,
             edges: edges::Config::try_from(proto_cfg.edges)?,
             generating: proto_cfg.generating.map(generating::Config::from),
         })
@@ -103,40 +206,116 @@ impl TryFrom<ProtoConfig> for Config {
 #[derive(Clone, Debug, Deserialize)]
 #[serde(try_from = "RawConfig")]
 pub struct ProtoConfig {
-    pub map_file: PathBuf,
+    pub map_files: Vec<PathBuf>,
     pub vehicles: Option<vehicles::ProtoConfig>,
     pub nodes: nodes::ProtoConfig,
+    pub on_node_conflict: Option<nodes::ConflictPolicy>,
+    pub merging_stacked_nodes: Option<nodes::StackedNodes>,
+    pub bbox: Option<nodes::RawBoundingBox>,
+    pub elevation: Option<nodes::Elevation>,
+    pub is_ignoring_embedded_cfg: Option<bool>,
+    pub is_lossy_utf8: Option<bool>,
+    pub is_using_compact_indices: Option<bool>,
+    pub num_threads: Option<usize>,
     pub edges: edges::ProtoConfig,
     pub generating: Option<generating::ProtoConfig>,
 }
 
-impl From<RawConfig> for ProtoConfig {
-    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+impl TryFrom<RawConfig> for ProtoConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: RawConfig) -> err::Result<ProtoConfig> {
         let raw_cfg = raw_cfg.parsing;
 
-        ProtoConfig {
-            map_file: raw_cfg.map_file,
+        let map_files = match (raw_cfg.map_file, raw_cfg.map_files) {
+            (Some(map_file), None) => vec![map_file],
+            (None, Some(map_files)) => {
+                if map_files.is_empty() {
+                    return Err(err::Msg::from("The parsing-config's map-files are empty."));
+                }
+                map_files
+            }
+            (Some(_), Some(_)) => {
+                return Err(err::Msg::from(
+                    "The parsing-config has a map-file and map-files, but only one is allowed.",
+                ))
+            }
+            (None, None) => {
+                return Err(err::Msg::from(
+                    "The parsing-config needs a map-file or map-files.",
+                ))
+            }
+        };
+
+        Ok(ProtoConfig {
+            map_files,
             vehicles: raw_cfg.vehicles.map(vehicles::ProtoConfig::from),
             nodes: nodes::ProtoConfig::from(raw_cfg.nodes),
+            on_node_conflict: raw_cfg.on_node_conflict.map(nodes::ConflictPolicy::from),
+            merging_stacked_nodes: raw_cfg.merge_stacked_nodes.map(nodes::StackedNodes::from),
+            bbox: raw_cfg.bbox,
+            elevation: raw_cfg.elevation.map(nodes::Elevation::from),
+            is_ignoring_embedded_cfg: raw_cfg.is_ignoring_embedded_cfg,
+            is_lossy_utf8: raw_cfg.is_lossy_utf8,
+            is_using_compact_indices: raw_cfg.is_using_compact_indices,
+            num_threads: raw_cfg.num_threads,
             edges: edges::ProtoConfig::from(raw_cfg.edges),
             generating: raw_cfg.generating.map(generating::ProtoConfig::from),
-        }
+        })
     }
 }
 
 /// Don't deny unknown fields to allow multiple configs in one yaml-file.
 #[derive(Debug, Deserialize)]
+#[serde(try_from = "RawProfiledConfig")]
 pub struct RawConfig {
     pub parsing: RawContent,
 }
 
+impl TryFrom<RawProfiledConfig> for RawConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: RawProfiledConfig) -> err::Result<RawConfig> {
+        let content = profiles::expand(raw_cfg.parsing)?;
+        match serde_yaml::from_value(content) {
+            Ok(content) => Ok(RawConfig { parsing: content }),
+            Err(e) => Err(err::Msg::from(format!(
+                "Serde couldn't read the parsing-config due to error: {}",
+                e
+            ))),
+        }
+    }
+}
+
+/// The parsing-config before its profile is expanded (see `profiles::Profile`)
+#[derive(Debug, Deserialize)]
+pub struct RawProfiledConfig {
+    pub parsing: serde_yaml::Value,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct RawContent {
     #[serde(rename = "map-file")]
-    pub map_file: PathBuf,
+    pub map_file: Option<PathBuf>,
+    #[serde(rename = "map-files")]
+    pub map_files: Option<Vec<PathBuf>>,
     pub vehicles: Option<vehicles::RawConfig>,
     pub nodes: nodes::RawConfig,
+    #[serde(rename = "on-node-conflict")]
+    pub on_node_conflict: Option<nodes::RawConflictPolicy>,
+    #[serde(rename = "merge-stacked-nodes")]
+    pub merge_stacked_nodes: Option<nodes::RawStackedNodes>,
+    pub bbox: Option<nodes::RawBoundingBox>,
+    pub elevation: Option<nodes::RawElevation>,
+    #[serde(rename = "ignore-embedded-config")]
+    pub is_ignoring_embedded_cfg: Option<bool>,
+    #[serde(rename = "lossy-utf8")]
+    pub is_lossy_utf8: Option<bool>,
+    #[serde(rename = "compact-indices")]
+    pub is_using_compact_indices: Option<bool>,
+    #[serde(rename = "number-of-threads")]
+    pub num_threads: Option<usize>,
     pub edges: edges::RawConfig,
     pub generating: Option<generating::RawConfig>,
 }
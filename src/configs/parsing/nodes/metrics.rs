@@ -1,11 +1,15 @@
-use crate::configs::parsing::generating::nodes::metrics as gen;
+use super::NodeCategory;
+use crate::{configs::parsing::generating::nodes::metrics as gen, helpers::err};
 use serde::Deserialize;
+use std::convert::TryFrom;
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
 pub enum UnitInfo {
     Latitude,
     Longitude,
     Height,
+    /// Custom values (like zone-ids) are stored as f64 and passed through, but ignored by routing.
+    F64,
 }
 
 impl From<gen::UnitInfo> for UnitInfo {
@@ -24,6 +28,7 @@ impl From<ProtoUnitInfo> for UnitInfo {
             ProtoUnitInfo::Latitude => UnitInfo::Latitude,
             ProtoUnitInfo::Longitude => UnitInfo::Longitude,
             ProtoUnitInfo::Height => UnitInfo::Height,
+            ProtoUnitInfo::F64 => UnitInfo::F64,
         }
     }
 }
@@ -33,6 +38,7 @@ pub enum ProtoUnitInfo {
     Latitude,
     Longitude,
     Height,
+    F64,
 }
 
 impl From<RawUnitInfo> for ProtoUnitInfo {
@@ -40,12 +46,32 @@ impl From<RawUnitInfo> for ProtoUnitInfo {
         match raw_unit {
             RawUnitInfo::Latitude => ProtoUnitInfo::Latitude,
             RawUnitInfo::Longitude => ProtoUnitInfo::Longitude,
+            RawUnitInfo::F64 => ProtoUnitInfo::F64,
         }
     }
 }
 
+/// Deserialized by the names of `NodeCategory`, so the yaml accepts exactly these names.
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
 pub enum RawUnitInfo {
     Latitude,
     Longitude,
+    F64,
+}
+
+impl TryFrom<String> for RawUnitInfo {
+    type Error = err::Msg;
+
+    fn try_from(name: String) -> err::Result<RawUnitInfo> {
+        match name.parse::<NodeCategory>()? {
+            NodeCategory::Latitude => Ok(RawUnitInfo::Latitude),
+            NodeCategory::Longitude => Ok(RawUnitInfo::Longitude),
+            NodeCategory::F64 => Ok(RawUnitInfo::F64),
+            category => Err(err::Msg::from(format!(
+                "The node-category {} is no metric-unit.",
+                category
+            ))),
+        }
+    }
 }
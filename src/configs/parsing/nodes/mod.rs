@@ -1,5 +1,16 @@
-use crate::configs::{parsing::generating::nodes as gen, SimpleId};
+use crate::{
+    configs::{parsing::generating::nodes as gen, SimpleId},
+    defaults,
+    helpers::err,
+};
+use kissunits::{distance::Meters, geo::Coordinate};
 use serde::Deserialize;
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    path::PathBuf,
+    str::FromStr,
+};
 pub mod metrics;
 
 #[derive(Clone, Debug)]
@@ -7,6 +18,68 @@ pub struct Config {
     pub categories: Vec<Category>,
 }
 
+impl Config {
+    /// Ch-levels are only stored (and need memory), if they are configured.
+    pub fn has_ch_levels(&self) -> bool {
+        self.categories.iter().any(|category| match category {
+            Category::Meta {
+                info: MetaInfo::CHLevel,
+                id: _,
+            } => true,
+            Category::Meta { info: _, id: _ }
+            | Category::Metric { unit: _, id: _ }
+            | Category::Ignored => false,
+        })
+    }
+
+    /// Returns the ids of all custom node-values (unit `F64`) in the order of the categories.
+    /// A node's custom values are stored in this order.
+    pub fn custom_ids(&self) -> Vec<&SimpleId> {
+        self.categories
+            .iter()
+            .filter_map(|category| match category {
+                Category::Metric {
+                    unit: metrics::UnitInfo::F64,
+                    id,
+                } => Some(id),
+                Category::Meta { info: _, id: _ }
+                | Category::Metric { unit: _, id: _ }
+                | Category::Ignored => None,
+            })
+            .collect()
+    }
+
+    pub fn try_custom_idx_of<S>(&self, id: S) -> err::Result<usize>
+    where
+        S: AsRef<str>,
+    {
+        let id = id.as_ref();
+        match self
+            .custom_ids()
+            .iter()
+            .position(|custom_id| custom_id.0 == id)
+        {
+            Some(idx) => Ok(idx),
+            None => Err(format!(
+                "Custom node-value-id {} should be existent in graph, but isn't.",
+                id
+            )
+            .into()),
+        }
+    }
+
+    /// Panics if id doesn't exist
+    pub fn custom_idx_of<S>(&self, id: S) -> usize
+    where
+        S: AsRef<str>,
+    {
+        match self.try_custom_idx_of(id) {
+            Ok(idx) => idx,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
 impl From<ProtoConfig> for Config {
     fn from(proto_cfg: ProtoConfig) -> Config {
         let mut categories: Vec<Category> = Vec::new();
@@ -32,6 +105,15 @@ pub enum Category {
     Ignored,
 }
 
+impl Category {
+    pub fn id(&self) -> Option<&SimpleId> {
+        match self {
+            Category::Meta { info: _, id } | Category::Metric { unit: _, id } => Some(id),
+            Category::Ignored => None,
+        }
+    }
+}
+
 impl From<ProtoCategory> for Category {
     fn from(proto_category: ProtoCategory) -> Category {
         match proto_category {
@@ -59,6 +141,124 @@ impl From<gen::Category> for Category {
     }
 }
 
+/// The categories of node-data, which can be configured in the parsing-config's `nodes`, either
+/// as `meta`-info (structural), as `metric`-unit or as `ignored`.
+///
+/// Their names (see `Display` and `FromStr`) are the names accepted in the yaml, e.g. for tools
+/// generating parsing-configs.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NodeCategory {
+    NodeId,
+    CHLevel,
+    Latitude,
+    Longitude,
+    F64,
+    Ignored,
+}
+
+impl NodeCategory {
+    pub fn all() -> &'static [NodeCategory] {
+        &[
+            NodeCategory::NodeId,
+            NodeCategory::CHLevel,
+            NodeCategory::Latitude,
+            NodeCategory::Longitude,
+            NodeCategory::F64,
+            NodeCategory::Ignored,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeCategory::NodeId => "NodeId",
+            NodeCategory::CHLevel => "CHLevel",
+            NodeCategory::Latitude => "Latitude",
+            NodeCategory::Longitude => "Longitude",
+            NodeCategory::F64 => "F64",
+            NodeCategory::Ignored => "ignored",
+        }
+    }
+
+    /// True for `metric`-units
+    pub fn is_metric(&self) -> bool {
+        !self.is_structural() && !self.is_ignored()
+    }
+
+    pub fn is_ignored(&self) -> bool {
+        *self == NodeCategory::Ignored
+    }
+
+    /// True for `meta`-infos, which describe the graph's structure, e.g. ids
+    pub fn is_structural(&self) -> bool {
+        match self {
+            NodeCategory::NodeId | NodeCategory::CHLevel => true,
+            NodeCategory::Latitude
+            | NodeCategory::Longitude
+            | NodeCategory::F64
+            | NodeCategory::Ignored => false,
+        }
+    }
+}
+
+impl Display for NodeCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for NodeCategory {
+    type Err = err::Msg;
+
+    fn from_str(name: &str) -> err::Result<NodeCategory> {
+        match NodeCategory::all()
+            .iter()
+            .find(|category| category.name() == name)
+        {
+            Some(category) => Ok(*category),
+            None => Err(err::Msg::from(format!(
+                "Unknown node-category `{}`. Valid node-categories are {:?}",
+                name,
+                NodeCategory::all()
+                    .iter()
+                    .map(|category| category.name())
+                    .collect::<Vec<_>>()
+            ))),
+        }
+    }
+}
+
+/// Fails for categories, which can only be generated (e.g. `NodeIdx`).
+impl TryFrom<&Category> for NodeCategory {
+    type Error = err::Msg;
+
+    fn try_from(category: &Category) -> err::Result<NodeCategory> {
+        Ok(match category {
+            Category::Meta { info, id } => match info {
+                MetaInfo::NodeId => NodeCategory::NodeId,
+                MetaInfo::CHLevel => NodeCategory::CHLevel,
+                MetaInfo::NodeIdx => {
+                    return Err(err::Msg::from(format!(
+                        "The node-meta-info {:?} of {} can only be generated.",
+                        info, id
+                    )))
+                }
+            },
+            Category::Metric { unit, id } => match unit {
+                metrics::UnitInfo::Latitude => NodeCategory::Latitude,
+                metrics::UnitInfo::Longitude => NodeCategory::Longitude,
+                metrics::UnitInfo::F64 => NodeCategory::F64,
+                metrics::UnitInfo::Height => {
+                    return Err(err::Msg::from(format!(
+                        "The node-unit {:?} of {} can only be generated.",
+                        unit, id
+                    )))
+                }
+            },
+            Category::Ignored => NodeCategory::Ignored,
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MetaInfo {
     NodeId,
@@ -156,8 +356,184 @@ pub enum RawCategory {
     Ignored,
 }
 
+/// Deserialized by the names of `NodeCategory`, so the yaml accepts exactly these names.
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
 pub enum RawMetaInfo {
     NodeId,
     CHLevel,
 }
+
+impl TryFrom<String> for RawMetaInfo {
+    type Error = err::Msg;
+
+    fn try_from(name: String) -> err::Result<RawMetaInfo> {
+        match name.parse::<NodeCategory>()? {
+            NodeCategory::NodeId => Ok(RawMetaInfo::NodeId),
+            NodeCategory::CHLevel => Ok(RawMetaInfo::CHLevel),
+            category => Err(err::Msg::from(format!(
+                "The node-category {} is no meta-info.",
+                category
+            ))),
+        }
+    }
+}
+
+/// Decides what happens when a node-id is inserted multiple times with differing coordinates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConflictPolicy {
+    Error,
+    KeepFirst,
+    KeepLast,
+    Average,
+}
+
+impl From<RawConflictPolicy> for ConflictPolicy {
+    fn from(raw_policy: RawConflictPolicy) -> ConflictPolicy {
+        match raw_policy {
+            RawConflictPolicy::Error => ConflictPolicy::Error,
+            RawConflictPolicy::KeepFirst => ConflictPolicy::KeepFirst,
+            RawConflictPolicy::KeepLast => ConflictPolicy::KeepLast,
+            RawConflictPolicy::Average => ConflictPolicy::Average,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RawConflictPolicy {
+    Error,
+    KeepFirst,
+    KeepLast,
+    Average,
+}
+
+/// Nodes with different ids at (nearly) the same coordinate, e.g. due to mapping-errors, are
+/// merged into the node with the smallest id, so routes can cross them.
+/// Nodes connected by an edge are never merged.
+#[derive(Copy, Clone, Debug)]
+pub struct StackedNodes {
+    pub max_distance: Meters,
+}
+
+impl From<RawStackedNodes> for StackedNodes {
+    fn from(raw_stacked_nodes: RawStackedNodes) -> StackedNodes {
+        StackedNodes {
+            max_distance: Meters(
+                raw_stacked_nodes
+                    .max_distance_m
+                    .unwrap_or(defaults::parsing::nodes::STACKED_MAX_DISTANCE_M),
+            ),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawStackedNodes {
+    #[serde(rename = "max-distance-m")]
+    pub max_distance_m: Option<f64>,
+}
+
+/// Only nodes within this bounding-box (borders included) are parsed from pbf-files, and ways
+/// are clipped to these nodes, e.g. to extract a city from a large map.
+#[derive(Copy, Clone, Debug)]
+pub struct BoundingBox {
+    pub min: Coordinate,
+    pub max: Coordinate,
+}
+
+impl BoundingBox {
+    pub fn contains(&self, coord: &Coordinate) -> bool {
+        self.min.lat <= coord.lat
+            && coord.lat <= self.max.lat
+            && self.min.lon <= coord.lon
+            && coord.lon <= self.max.lon
+    }
+}
+
+impl TryFrom<RawBoundingBox> for BoundingBox {
+    type Error = err::Msg;
+
+    fn try_from(raw_bbox: RawBoundingBox) -> err::Result<BoundingBox> {
+        let bbox = BoundingBox {
+            min: Coordinate {
+                lat: raw_bbox.min_lat,
+                lon: raw_bbox.min_lon,
+            },
+            max: Coordinate {
+                lat: raw_bbox.max_lat,
+                lon: raw_bbox.max_lon,
+            },
+        };
+        // like `Graph::try_subgraph_within_bbox(...)`, borders may coincide
+        if !(bbox.min.lat <= bbox.max.lat && bbox.min.lon <= bbox.max.lon) {
+            return Err(err::Msg::from(format!(
+                "The bounding-box's min ({}) should be south-west of its max ({}).",
+                bbox.min, bbox.max
+            )));
+        }
+        if !(-90.0 <= bbox.min.lat && bbox.max.lat <= 90.0)
+            || !(-180.0 <= bbox.min.lon && bbox.max.lon <= 180.0)
+        {
+            return Err(err::Msg::from(format!(
+                "The bounding-box from {} to {} should be within valid latitudes and longitudes.",
+                bbox.min, bbox.max
+            )));
+        }
+        Ok(bbox)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawBoundingBox {
+    #[serde(rename = "min-lat")]
+    pub min_lat: f64,
+    #[serde(rename = "min-lon")]
+    pub min_lon: f64,
+    #[serde(rename = "max-lat")]
+    pub max_lat: f64,
+    #[serde(rename = "max-lon")]
+    pub max_lon: f64,
+}
+
+/// Elevations of nodes in pbf-files, which are stored as the custom node-value `id` (unit `F64`).
+///
+/// A node's elevation is taken from its `ele`-tag, if present.
+/// Otherwise, it is interpolated bilinearly from the SRTM-tiles in `srtm_dir` (see
+/// `io::elevation::SrtmReader`), or set to `default`, if no tile covers the node.
+/// With `is_requiring_elevation`, nodes without any elevation are an error instead.
+#[derive(Clone, Debug)]
+pub struct Elevation {
+    pub id: SimpleId,
+    pub srtm_dir: Option<PathBuf>,
+    pub default: f64,
+    pub is_requiring_elevation: bool,
+}
+
+impl From<RawElevation> for Elevation {
+    fn from(raw_elevation: RawElevation) -> Elevation {
+        Elevation {
+            id: raw_elevation.id,
+            srtm_dir: raw_elevation.srtm_dir,
+            default: raw_elevation
+                .default
+                .unwrap_or(defaults::parsing::nodes::ELEVATION_M),
+            is_requiring_elevation: raw_elevation
+                .is_requiring_elevation
+                .unwrap_or(defaults::parsing::nodes::IS_REQUIRING_ELEVATION),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawElevation {
+    pub id: SimpleId,
+    #[serde(rename = "srtm-dir")]
+    pub srtm_dir: Option<PathBuf>,
+    pub default: Option<f64>,
+    #[serde(rename = "require-elevation")]
+    pub is_requiring_elevation: Option<bool>,
+}
@@ -0,0 +1,333 @@
+use crate::{defaults, helpers::err};
+use serde_yaml::{Mapping, Value};
+use std::{
+    fmt::{self, Display},
+    fs::OpenOptions,
+    path::Path,
+};
+
+/// Presets of vehicles and metrics for common use-cases, which are chosen in the parsing-config
+/// via `profile: <name>` instead of listing everything by hand.
+///
+/// A preset is expanded into the parsing-config before it is validated, so the resulting config
+/// is the same as a hand-written one.
+/// Explicitly listed entries override the preset's entries of the same id (e.g. a generated
+/// metric with another unit), other entries are appended.
+///
+/// All presets except `SimpleFmi` describe pbf-files and name their metrics equally:
+///
+/// - `kmph`: the street's (capped) maxspeed
+/// - `kilometers`: the haversine-distance
+/// - `duration`: in hours, calculated from `kilometers` and `kmph`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// Cars with lane-counts as `lane-count`
+    CarDefault,
+    /// Bicycles with their comfort as `cycleway-comfort`
+    BicycleDefault,
+    /// Pedestrians, whose speeds are capped by their vehicle-category
+    PedestrianDefault,
+    /// Like `CarDefault`, but with normalized metrics, generated edge-ids as `edge-id` and the
+    /// balancer's `workload`
+    BalancingDefault,
+    /// Fmi-files written as simple fmi (see `configs::writing::network::simple_fmi`), whose only
+    /// metric is `weight`
+    SimpleFmi,
+}
+
+impl Profile {
+    pub fn all() -> [Profile; 5] {
+        [
+            Profile::CarDefault,
+            Profile::BicycleDefault,
+            Profile::PedestrianDefault,
+            Profile::BalancingDefault,
+            Profile::SimpleFmi,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::CarDefault => "car-default",
+            Profile::BicycleDefault => "bicycle-default",
+            Profile::PedestrianDefault => "pedestrian-default",
+            Profile::BalancingDefault => "balancing-default",
+            Profile::SimpleFmi => "simple-fmi",
+        }
+    }
+
+    pub fn try_from_name(name: &str) -> err::Result<Profile> {
+        match Profile::all().iter().find(|profile| profile.name() == name) {
+            Some(profile) => Ok(*profile),
+            None => Err(err::Msg::from(format!(
+                "Unknown profile `{}` in the parsing-config. Valid profiles are {:?}",
+                name,
+                Profile::all()
+                    .iter()
+                    .map(|profile| profile.name())
+                    .collect::<Vec<_>>()
+            ))),
+        }
+    }
+
+    /// The preset as content of a parsing-config (without `parsing:` and map-files)
+    pub fn preset(&self) -> Value {
+        match self {
+            Profile::CarDefault => from_str(presets::CAR_DEFAULT),
+            Profile::BicycleDefault => from_str(presets::BICYCLE_DEFAULT),
+            Profile::PedestrianDefault => from_str(presets::PEDESTRIAN_DEFAULT),
+            Profile::BalancingDefault => merge(
+                from_str(presets::CAR_DEFAULT),
+                from_str(presets::BALANCING_EXTENSION),
+            ),
+            Profile::SimpleFmi => from_str(presets::SIMPLE_FMI),
+        }
+    }
+}
+
+fn from_str(preset: &str) -> Value {
+    match serde_yaml::from_str(preset) {
+        Ok(preset) => preset,
+        Err(e) => panic!(
+            "Presets should be valid yaml, but parsing failed due to error: {}",
+            e
+        ),
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Expands the profile of the provided parsing-config-content (everything below `parsing:`).
+/// Without profile, the content is returned unchanged.
+pub fn expand(content: Value) -> err::Result<Value> {
+    let mut content = match content {
+        Value::Mapping(content) => content,
+        _ => return Err(err::Msg::from("The parsing-config should be a mapping.")),
+    };
+
+    let profile = match content.remove(&Value::from(defaults::parsing::PROFILE_KEY)) {
+        Some(Value::String(name)) => Profile::try_from_name(&name)?,
+        Some(_) => {
+            return Err(err::Msg::from(format!(
+                "The parsing-config's {} should be a name.",
+                defaults::parsing::PROFILE_KEY
+            )))
+        }
+        None => return Ok(Value::Mapping(content)),
+    };
+
+    Ok(merge(profile.preset(), Value::Mapping(content)))
+}
+
+/// Reads the provided yaml-file and returns its parsing-config with expanded profile as
+/// yaml-string, which could be used as hand-written parsing-config.
+pub fn try_expand_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<String> {
+    let path = path.as_ref();
+    let file = match OpenOptions::new().read(true).open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(err::Msg::from(format!(
+                "Couldn't open {} due to error: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    let mut cfg: Mapping = match serde_yaml::from_reader(file) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            return Err(err::Msg::from(format!(
+                "Serde couldn't read {} due to error: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    let content = match cfg.remove(&Value::from("parsing")) {
+        Some(content) => expand(content)?,
+        None => {
+            return Err(err::Msg::from(format!(
+                "The config {} has no parsing-config.",
+                path.display()
+            )))
+        }
+    };
+
+    let mut effective_cfg = Mapping::new();
+    effective_cfg.insert(Value::from("parsing"), content);
+    match serde_yaml::to_string(&effective_cfg) {
+        Ok(yaml_str) => Ok(yaml_str),
+        Err(e) => Err(err::Msg::from(format!(
+            "Serde couldn't write the effective config due to error: {}",
+            e
+        ))),
+    }
+}
+
+/// Mappings are merged key by key, lists entry by entry (see `id_of(...)`) and everything else
+/// is overridden by `explicit`.
+fn merge(preset: Value, explicit: Value) -> Value {
+    match (preset, explicit) {
+        (Value::Mapping(mut preset), Value::Mapping(explicit)) => {
+            for (key, value) in explicit {
+                let value = match preset.remove(&key) {
+                    Some(preset_value) => merge(preset_value, value),
+                    None => value,
+                };
+                preset.insert(key, value);
+            }
+            Value::Mapping(preset)
+        }
+        (Value::Sequence(mut preset), Value::Sequence(explicit)) => {
+            for entry in explicit {
+                let idx = id_of(&entry).and_then(|id| {
+                    preset
+                        .iter()
+                        .position(|preset_entry| id_of(preset_entry) == Some(id))
+                });
+                match idx {
+                    Some(idx) => preset[idx] = entry,
+                    None => preset.push(entry),
+                }
+            }
+            Value::Sequence(preset)
+        }
+        (_, explicit) => explicit,
+    }
+}
+
+/// Entries of a list are mappings with one key (e.g. `metric` or `calc`) and their id is the one
+/// of the created metric, e.g. `metric: { id }`, `calc: { result: { id } }` or
+/// `convert: { to: { id } }`.
+fn id_of(entry: &Value) -> Option<&str> {
+    let inner = match entry {
+        Value::Mapping(entry) if entry.len() == 1 => entry.iter().next().map(|(_, inner)| inner)?,
+        _ => return None,
+    };
+
+    if let Some(id) = inner.get("id") {
+        return id.as_str();
+    }
+    ["result", "to"]
+        .iter()
+        .find_map(|key| inner.get(*key)?.get("id")?.as_str())
+}
+
+mod presets {
+    pub const CAR_DEFAULT: &str = "
+vehicles:
+  category: 'Car'
+  are_drivers_picky: false
+nodes:
+- meta: { info: 'NodeId', id: 'node-id' }
+- metric: { unit: 'Latitude', id: 'latitude' }
+- metric: { unit: 'Longitude', id: 'longitude' }
+edges:
+  data:
+  - meta: { info: 'SrcId', id: 'src-id' }
+  - meta: { info: 'DstId', id: 'dst-id' }
+  - metric: { unit: 'KilometersPerHour', id: 'kmph' }
+  - metric: { unit: 'LaneCount', id: 'lane-count' }
+generating:
+  nodes:
+  - meta: { info: 'NodeIdx', id: 'node-idx' }
+  edges:
+  - meta: { info: 'SrcIdx', id: 'src-idx' }
+  - meta: { info: 'DstIdx', id: 'dst-idx' }
+  - haversine: { unit: 'Kilometers', id: 'kilometers' }
+  - calc:
+      result: { unit: 'Hours', id: 'duration' }
+      a: { unit: 'Kilometers', id: 'kilometers' }
+      b: { unit: 'KilometersPerHour', id: 'kmph' }
+";
+
+    pub const BICYCLE_DEFAULT: &str = "
+vehicles:
+  category: 'Bicycle'
+  are_drivers_picky: false
+nodes:
+- meta: { info: 'NodeId', id: 'node-id' }
+- metric: { unit: 'Latitude', id: 'latitude' }
+- metric: { unit: 'Longitude', id: 'longitude' }
+edges:
+  data:
+  - meta: { info: 'SrcId', id: 'src-id' }
+  - meta: { info: 'DstId', id: 'dst-id' }
+  - metric: { unit: 'KilometersPerHour', id: 'kmph' }
+  - metric: { unit: 'CyclewayComfort', id: 'cycleway-comfort' }
+generating:
+  nodes:
+  - meta: { info: 'NodeIdx', id: 'node-idx' }
+  edges:
+  - meta: { info: 'SrcIdx', id: 'src-idx' }
+  - meta: { info: 'DstIdx', id: 'dst-idx' }
+  - haversine: { unit: 'Kilometers', id: 'kilometers' }
+  - calc:
+      result: { unit: 'Hours', id: 'duration' }
+      a: { unit: 'Kilometers', id: 'kilometers' }
+      b: { unit: 'KilometersPerHour', id: 'kmph' }
+";
+
+    pub const PEDESTRIAN_DEFAULT: &str = "
+vehicles:
+  category: 'Pedestrian'
+  are_drivers_picky: false
+nodes:
+- meta: { info: 'NodeId', id: 'node-id' }
+- metric: { unit: 'Latitude', id: 'latitude' }
+- metric: { unit: 'Longitude', id: 'longitude' }
+edges:
+  data:
+  - meta: { info: 'SrcId', id: 'src-id' }
+  - meta: { info: 'DstId', id: 'dst-id' }
+  - metric: { unit: 'KilometersPerHour', id: 'kmph' }
+generating:
+  nodes:
+  - meta: { info: 'NodeIdx', id: 'node-idx' }
+  edges:
+  - meta: { info: 'SrcIdx', id: 'src-idx' }
+  - meta: { info: 'DstIdx', id: 'dst-idx' }
+  - haversine: { unit: 'Kilometers', id: 'kilometers' }
+  - calc:
+      result: { unit: 'Hours', id: 'duration' }
+      a: { unit: 'Kilometers', id: 'kilometers' }
+      b: { unit: 'KilometersPerHour', id: 'kmph' }
+";
+
+    pub const SIMPLE_FMI: &str = "
+vehicles:
+  category: 'Car'
+  are_drivers_picky: false
+nodes:
+- meta: { info: 'NodeId', id: 'node-id' }
+- metric: { unit: 'Latitude', id: 'latitude' }
+- metric: { unit: 'Longitude', id: 'longitude' }
+edges:
+  data:
+  - meta: { info: 'SrcId', id: 'src-id' }
+  - meta: { info: 'DstId', id: 'dst-id' }
+  - metric: { unit: 'F64', id: 'weight' }
+generating:
+  nodes: []
+  edges: []
+";
+
+    /// Merged into `CAR_DEFAULT`, hence lists are appended.
+    pub const BALANCING_EXTENSION: &str = "
+edges:
+  will_normalize_metrics_by_mean: true
+generating:
+  edges:
+  - meta: { info: 'EdgeId', id: 'edge-id' }
+  - custom:
+      id: 'workload'
+      unit: 'F64'
+      # 0.0 would lead to errors due to mean-normalization
+      default: 1.0
+";
+}
@@ -1,10 +1,26 @@
-use crate::{defaults, network::vehicles::Category as VehicleCategory};
+use crate::{defaults, helpers::err, network::vehicles::Category as VehicleCategory};
 use serde::Deserialize;
+use std::convert::TryFrom;
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub category: VehicleCategory,
     pub are_drivers_picky: bool,
+    /// in km/h, caps speeds of generated metrics (see `cap_speed(...)`)
+    pub max_speed: Option<f64>,
+}
+
+impl Config {
+    /// Returns the given speed (in km/h), but at most the configured max-speed.
+    ///
+    /// Speeds of vehicle-categories, e.g. of bicycles, are capped as well,
+    /// so the minimum of both wins.
+    pub fn cap_speed(&self, kmph: f64) -> f64 {
+        match self.max_speed {
+            Some(max_speed) => kmph.min(max_speed),
+            None => kmph,
+        }
+    }
 }
 
 impl Default for Config {
@@ -12,16 +28,30 @@ impl Default for Config {
         Config {
             category: defaults::parsing::vehicles::CATEGORY,
             are_drivers_picky: defaults::parsing::vehicles::ARE_DRIVERS_PICKY,
+            max_speed: defaults::parsing::vehicles::MAX_SPEED,
         }
     }
 }
 
-impl From<ProtoConfig> for Config {
-    fn from(proto_cfg: ProtoConfig) -> Self {
-        Config {
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        if let Some(max_speed) = proto_cfg.max_speed {
+            if !(max_speed > 0.0 && max_speed.is_finite()) {
+                return Err(format!(
+                    "The vehicles' max-speed has to be positive, but is {} km/h.",
+                    max_speed
+                )
+                .into());
+            }
+        }
+
+        Ok(Config {
             category: proto_cfg.category,
             are_drivers_picky: proto_cfg.are_drivers_picky,
-        }
+            max_speed: proto_cfg.max_speed,
+        })
     }
 }
 
@@ -29,6 +59,7 @@ impl From<ProtoConfig> for Config {
 pub struct ProtoConfig {
     pub category: VehicleCategory,
     pub are_drivers_picky: bool,
+    pub max_speed: Option<f64>,
 }
 
 impl From<RawConfig> for ProtoConfig {
@@ -36,6 +67,7 @@ impl From<RawConfig> for ProtoConfig {
         ProtoConfig {
             category: raw_cfg.category,
             are_drivers_picky: raw_cfg.are_drivers_picky,
+            max_speed: raw_cfg.max_speed,
         }
     }
 }
@@ -45,4 +77,6 @@ impl From<RawConfig> for ProtoConfig {
 pub struct RawConfig {
     pub category: VehicleCategory,
     pub are_drivers_picky: bool,
+    #[serde(rename = "max-speed")]
+    pub max_speed: Option<f64>,
 }
@@ -2,13 +2,19 @@ use crate::{
     configs::{self, SimpleId},
     defaults::{self, capacity::DimVec},
     helpers::err,
-    io::SupportingFileExts,
+    io::{self, SupportingFileExts},
+    network::{Graph, MetricIdx},
 };
+use kissunits::distance::Kilometers;
+use log::{info, warn};
 use serde::Deserialize;
+use serde_json::{json, Value};
 use smallvec::smallvec;
 use std::{
+    collections::BTreeMap,
     convert::TryFrom,
     fs::OpenOptions,
+    io::{BufRead, BufReader},
     path::{Path, PathBuf},
 };
 
@@ -24,6 +30,22 @@ pub struct Config {
     pub routing_algo: RoutingAlgo,
     pub alphas: DimVec<f64>,
     pub tolerated_scales: DimVec<f64>,
+    /// The parameter `p` of metrics with `tolerated-scale: auto(p)`, whose tolerated scales are
+    /// infinite until they are derived from the graph (see `expand_auto_tolerated_scales(...)`).
+    /// Configs built for a graph (e.g. `try_from_yaml_for_graph(...)`) have derived them already,
+    /// others derive them in `validate(...)`.
+    pub auto_tolerated_scales: DimVec<Option<f64>>,
+    /// If true, Dijkstra returns an error when relaxing an edge with negative costs.
+    pub is_checking_non_negative: bool,
+    /// If false, the explorator doesn't run Dijkstra with alphas having negative components,
+    /// which would maximize the respective metric.
+    pub is_allowing_negative_alphas: bool,
+    /// Route-pairs given by coordinates are snapped to their nearest node,
+    /// but only if it is at most this far away.
+    pub max_snap_distance: Kilometers,
+    /// If true, `validate(...)` replaces a CHDijkstra by a Dijkstra (with a warning), if the graph
+    /// is not contracted, instead of returning an error.
+    pub is_allowing_ch_fallback: bool,
 }
 
 impl SupportingFileExts for Config {
@@ -58,9 +80,39 @@ impl Config {
         }
     }
 
+    /// Like `try_from_str(...)`, but the tolerated scales of metrics with
+    /// `tolerated-scale: auto(p)` are derived from the given graph right away
+    /// (see `expand_auto_tolerated_scales(...)`).
+    pub fn try_from_str_for_graph(yaml_str: &str, graph: &Graph) -> err::Result<Config> {
+        let mut cfg = Config::try_from_str(yaml_str, graph.cfg())?;
+        cfg.expand_auto_tolerated_scales(graph)?;
+        Ok(cfg)
+    }
+
+    pub fn from_str_for_graph(yaml_str: &str, graph: &Graph) -> Config {
+        match Config::try_from_str_for_graph(yaml_str, graph) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Every profile is validated, but only the default profile is returned.
     fn try_from_proto(
         proto_cfg: ProtoConfig,
         parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Config> {
+        Ok(Profiles::try_from_proto(proto_cfg, parsing_cfg)?.into_default_profile())
+    }
+
+    fn try_from_entries(
+        route_pairs_file: Option<PathBuf>,
+        routing_algo: RoutingAlgo,
+        is_checking_non_negative: bool,
+        is_allowing_negative_alphas: bool,
+        max_snap_distance: Kilometers,
+        is_allowing_ch_fallback: bool,
+        entries: DimVec<ProtoEntry>,
+        parsing_cfg: &configs::parsing::Config,
     ) -> err::Result<Config> {
         let dim = parsing_cfg.edges.metrics.units.len();
 
@@ -68,18 +120,29 @@ impl Config {
         let mut alphas = smallvec![0.0; dim];
         // Same argument holds for the toleration.
         let mut tolerated_scales = smallvec![defaults::routing::TOLERATED_SCALE_INF; dim];
+        let mut auto_tolerated_scales = smallvec![None; dim];
 
-        for entry in proto_cfg.metrics.into_iter() {
+        for entry in entries.into_iter() {
             let metric_idx = parsing_cfg.edges.metrics.try_idx_of(&entry.id)?;
             alphas[*metric_idx] = entry.alpha;
-            tolerated_scales[*metric_idx] = entry.tolerated_scale;
+            match entry.tolerated_scale {
+                ProtoToleratedScale::Fixed(tolerated_scale) => {
+                    tolerated_scales[*metric_idx] = tolerated_scale
+                }
+                ProtoToleratedScale::Auto { p } => auto_tolerated_scales[*metric_idx] = Some(p),
+            }
         }
 
         Ok(Config {
-            route_pairs_file: proto_cfg.route_pairs_file,
-            routing_algo: RoutingAlgo::from(proto_cfg.routing_algo),
+            route_pairs_file,
+            routing_algo,
             alphas,
             tolerated_scales,
+            auto_tolerated_scales,
+            is_checking_non_negative,
+            is_allowing_negative_alphas,
+            max_snap_distance,
+            is_allowing_ch_fallback,
         })
     }
 
@@ -90,10 +153,322 @@ impl Config {
         }
     }
 
+    /// Checks this config against the graph it is used for, and derives the tolerated scales of
+    /// metrics with `tolerated-scale: auto(p)` (see `expand_auto_tolerated_scales(...)`).
+    ///
+    /// A CHDijkstra on a graph, whose levels are uniform (e.g. a plain fmi-file parsed with a
+    /// ch-config) or which has no shortcuts, would run a plain bidirectional Dijkstra without any
+    /// ch-speedup.
+    /// This is an error, unless the fallback to Dijkstra is allowed.
+    pub fn validate(&mut self, graph: &Graph) -> err::Feedback {
+        self.expand_auto_tolerated_scales(graph)?;
+        self.validate_routing_algo(graph)
+    }
+
+    /// Replaces every `tolerated-scale: auto(p)` by a concrete, finite tolerated scale, which
+    /// grows with the metric's spread over the graph's edges, since alternative routes of such
+    /// metrics differ more in their costs.
+    ///
+    /// The spread is the metric's coefficient of variation, estimated robustly from quantiles as
+    /// `cv = (Q(0.75) - Q(0.25)) / (1.349 * Q(0.5))`, where `1.349` is the interquartile range of a
+    /// standard normal distribution.
+    /// With `cv = 0` for metrics, whose median isn't positive, the tolerated scale is
+    /// `1 + p * (1 + cv)`, so the explorator's cap of this metric is the single-metric optimum
+    /// times this scale.
+    pub fn expand_auto_tolerated_scales(&mut self, graph: &Graph) -> err::Feedback {
+        for metric_idx in 0..self.auto_tolerated_scales.len() {
+            let p = match self.auto_tolerated_scales[metric_idx] {
+                Some(p) => p,
+                None => continue,
+            };
+            let quantiles =
+                graph.try_metric_quantiles(MetricIdx(metric_idx), &[0.25, 0.5, 0.75])?;
+            let (q1, median, q3) = (quantiles[0], quantiles[1], quantiles[2]);
+            let cv = if median > 0.0 {
+                (q3 - q1) / (defaults::routing::NORMAL_IQR * median)
+            } else {
+                0.0
+            };
+            let tolerated_scale = 1.0 + p * (1.0 + cv);
+            if !tolerated_scale.is_finite() {
+                return Err(format!(
+                    "The auto-tolerated-scale of metric {} isn't finite, \
+                     since the metric's quantiles are {:?}.",
+                    graph.cfg().edges.metrics.ids[metric_idx].0,
+                    quantiles
+                )
+                .into());
+            }
+            info!(
+                "Derived tolerated-scale {} of metric {} from auto({}) with cv {}.",
+                tolerated_scale,
+                graph.cfg().edges.metrics.ids[metric_idx].0,
+                p,
+                cv
+            );
+            self.tolerated_scales[metric_idx] = tolerated_scale;
+            self.auto_tolerated_scales[metric_idx] = None;
+        }
+        Ok(())
+    }
+
+    fn validate_routing_algo(&mut self, graph: &Graph) -> err::Feedback {
+        let fallback_algo = match self.routing_algo {
+            RoutingAlgo::Dijkstra => return Ok(()),
+            RoutingAlgo::CHDijkstra => RoutingAlgo::Dijkstra,
+            #[cfg(feature = "gpl")]
+            RoutingAlgo::Explorator { algo } => match algo {
+                ExploratorAlgo::Dijkstra => return Ok(()),
+                ExploratorAlgo::CHDijkstra => RoutingAlgo::Explorator {
+                    algo: ExploratorAlgo::Dijkstra,
+                },
+            },
+        };
+
+        let reason = if !graph.has_nonuniform_levels() {
+            "the nodes' ch-levels are missing or uniform"
+        } else if graph.shortcut_count() == 0 {
+            "the graph has no shortcuts"
+        } else {
+            return Ok(());
+        };
+
+        if self.is_allowing_ch_fallback {
+            warn!(
+                "The routing-algorithm {} needs a contracted graph, but {}. \
+                 Falling back to {}, which is correct, but without any ch-speedup.",
+                self.routing_algo.name(),
+                reason,
+                fallback_algo.name()
+            );
+            self.routing_algo = fallback_algo;
+            Ok(())
+        } else {
+            Err(err::Msg::from(format!(
+                "The routing-algorithm {} needs a contracted graph, but {}. \
+                 Probably, a plain map-file has been parsed with a ch-config. \
+                 Use a contracted map-file, the routing-algorithm {} \
+                 or set allow-ch-fallback in the routing-config.",
+                self.routing_algo.name(),
+                reason,
+                fallback_algo.name()
+            )))
+        }
+    }
+
     pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(
         path: &P,
         parsing_cfg: &configs::parsing::Config,
     ) -> err::Result<Config> {
+        Config::try_from_proto(Config::try_proto_from_yaml(path)?, parsing_cfg)
+    }
+
+    /// Like `try_from_yaml(...)`, but the tolerated scales of metrics with
+    /// `tolerated-scale: auto(p)` are derived from the given graph right away
+    /// (see `expand_auto_tolerated_scales(...)`).
+    pub fn try_from_yaml_for_graph<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        graph: &Graph,
+    ) -> err::Result<Config> {
+        let mut cfg = Config::try_from_yaml(path, graph.cfg())?;
+        cfg.expand_auto_tolerated_scales(graph)?;
+        Ok(cfg)
+    }
+
+    /// Reads only the route-pairs-file, which doesn't need the graph's metrics, e.g. for checking
+    /// the route-pairs before parsing the graph.
+    pub fn try_route_pairs_file_from_yaml<P: AsRef<Path> + ?Sized>(
+        path: &P,
+    ) -> err::Result<Option<PathBuf>> {
+        Ok(Config::try_proto_from_yaml(path)?.route_pairs_file)
+    }
+
+    fn try_proto_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<ProtoConfig> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => Ok(proto_cfg),
+            Err(e) => Err(err::Msg::from(format!(
+                "Serde couldn't read {} due to error: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> Config {
+        match Config::try_from_yaml(path, parsing_cfg) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Returns this config as routing-section in one line of yaml, which is embedded into
+    /// routing-results (see `try_from_embedded(...)`), so they can be reproduced.
+    ///
+    /// Metrics are referenced by their ids instead of their indices, hence the embedded config
+    /// is still valid for a re-parsed graph.
+    /// Every metric of the graph is listed, so alphas and tolerated scales are recovered exactly.
+    /// The route-pairs-file is not embedded.
+    pub fn to_embedded(&self, parsing_cfg: &configs::parsing::Config) -> String {
+        let algo = match self.routing_algo {
+            RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => json!(self.routing_algo.name()),
+            #[cfg(feature = "gpl")]
+            RoutingAlgo::Explorator { algo } => {
+                json!({ "Explorator": { "algo": format!("{:?}", algo) } })
+            }
+        };
+        let metrics: Vec<Value> = parsing_cfg
+            .edges
+            .metrics
+            .ids
+            .iter()
+            .zip(self.alphas.iter().zip(self.tolerated_scales.iter()))
+            .zip(self.auto_tolerated_scales.iter())
+            .map(|((id, (&alpha, &tolerated_scale)), auto_tolerated_scale)| {
+                // not yet derived from a graph
+                let tolerated_scale = match auto_tolerated_scale {
+                    Some(p) => format!("auto({})", io::fmt::float(*p, None)),
+                    None => io::fmt::float(tolerated_scale, None),
+                };
+                json!({ "id": id.0, "alpha": alpha, "tolerated-scale": tolerated_scale })
+            })
+            .collect();
+
+        io::fmt::json_line(&json!({
+            "routing": {
+                "algorithm": algo,
+                "check-non-negative": self.is_checking_non_negative,
+                "allow-negative-alphas": self.is_allowing_negative_alphas,
+                "max-snap-distance": self.max_snap_distance.0,
+                "metrics": metrics,
+            }
+        }))
+    }
+
+    /// Recovers the config, which has been embedded into the given file by a writer of
+    /// routing-results (see `to_embedded(...)`), e.g. into route-pairs-files or golden-files.
+    ///
+    /// Yaml-files without embedded config, like the balancer's sidecar-files, are read as
+    /// routing-config directly.
+    pub fn try_from_embedded<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = match OpenOptions::new().read(true).open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Couldn't open {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't read {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            };
+            if let Some(embedded_cfg) = line
+                .trim()
+                .strip_prefix(defaults::routing::EMBEDDED_CFG_PREFIX)
+            {
+                return Config::try_from_str(embedded_cfg, parsing_cfg).map_err(|msg| {
+                    err::Msg::from(format!(
+                        "The embedded routing-config of {} is invalid: {}",
+                        path.display(),
+                        msg
+                    ))
+                });
+            }
+        }
+
+        if Config::find_supported_ext(path).is_ok() {
+            Config::try_from_yaml(path, parsing_cfg)
+        } else {
+            Err(err::Msg::from(format!(
+                "{} has no embedded routing-config.",
+                path.display()
+            )))
+        }
+    }
+
+    pub fn from_embedded<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> Config {
+        match Config::try_from_embedded(path, parsing_cfg) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+/// Named routing-configs, which share the route-pairs-file and the routing-algorithm,
+/// but differ in their metrics (alphas and tolerated scales).
+///
+/// Metrics, which are defined directly in the routing-section, form the profile `default`.
+#[derive(Clone, Debug)]
+pub struct Profiles {
+    default_name: String,
+    profiles: BTreeMap<String, Config>,
+}
+
+impl Profiles {
+    pub fn try_from_str(
+        yaml_str: &str,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Profiles> {
+        let proto_cfg = {
+            match serde_yaml::from_str(yaml_str) {
+                Ok(proto_cfg) => proto_cfg,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Serde couldn't parse yaml-str due to error: {}",
+                        e
+                    )))
+                }
+            }
+        };
+        Profiles::try_from_proto(proto_cfg, parsing_cfg)
+    }
+
+    pub fn from_str(yaml_str: &str, parsing_cfg: &configs::parsing::Config) -> Profiles {
+        match Profiles::try_from_str(yaml_str, parsing_cfg) {
+            Ok(profiles) => profiles,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Profiles> {
         let path = path.as_ref();
         let file = {
             Config::find_supported_ext(path)?;
@@ -119,14 +494,122 @@ impl Config {
                 )))
             }
         };
-        Config::try_from_proto(proto_cfg, parsing_cfg)
+        Profiles::try_from_proto(proto_cfg, parsing_cfg)
     }
 
     pub fn from_yaml<P: AsRef<Path> + ?Sized>(
         path: &P,
         parsing_cfg: &configs::parsing::Config,
-    ) -> Config {
-        match Config::try_from_yaml(path, parsing_cfg) {
+    ) -> Profiles {
+        match Profiles::try_from_yaml(path, parsing_cfg) {
+            Ok(profiles) => profiles,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Like `try_from_yaml(...)`, but the tolerated scales of metrics with
+    /// `tolerated-scale: auto(p)` are derived from the given graph right away for every profile
+    /// (see `Config::expand_auto_tolerated_scales(...)`).
+    pub fn try_from_yaml_for_graph<P: AsRef<Path> + ?Sized>(
+        path: &P,
+        graph: &Graph,
+    ) -> err::Result<Profiles> {
+        let mut profiles = Profiles::try_from_yaml(path, graph.cfg())?;
+        for (name, cfg) in profiles.profiles.iter_mut() {
+            cfg.expand_auto_tolerated_scales(graph).map_err(|msg| {
+                err::Msg::from(format!("Routing-profile {} is invalid: {}", name, msg))
+            })?;
+        }
+        Ok(profiles)
+    }
+
+    fn try_from_proto(
+        proto_cfg: ProtoConfig,
+        parsing_cfg: &configs::parsing::Config,
+    ) -> err::Result<Profiles> {
+        let routing_algo = RoutingAlgo::from(proto_cfg.routing_algo);
+        let is_checking_non_negative = proto_cfg
+            .is_checking_non_negative
+            .unwrap_or(defaults::routing::IS_CHECKING_NON_NEGATIVE);
+        let is_allowing_negative_alphas = proto_cfg
+            .is_allowing_negative_alphas
+            .unwrap_or(defaults::routing::IS_ALLOWING_NEGATIVE_ALPHAS);
+        let max_snap_distance = Kilometers(
+            proto_cfg
+                .max_snap_distance
+                .unwrap_or(defaults::routing::MAX_SNAP_DISTANCE_KM),
+        );
+        let is_allowing_ch_fallback = proto_cfg
+            .is_allowing_ch_fallback
+            .unwrap_or(defaults::routing::IS_ALLOWING_CH_FALLBACK);
+
+        let mut profiles = BTreeMap::new();
+        for (name, entries) in proto_cfg.profiles {
+            let cfg = match Config::try_from_entries(
+                proto_cfg.route_pairs_file.clone(),
+                routing_algo,
+                is_checking_non_negative,
+                is_allowing_negative_alphas,
+                max_snap_distance,
+                is_allowing_ch_fallback,
+                entries,
+                parsing_cfg,
+            ) {
+                Ok(cfg) => cfg,
+                Err(msg) => {
+                    return Err(err::Msg::from(format!(
+                        "Routing-profile {} is invalid: {}",
+                        name, msg
+                    )))
+                }
+            };
+            // Dijkstra doesn't find any path with negative alphas.
+            if cfg.alphas.iter().any(|alpha| alpha < &0.0) {
+                warn!(
+                    "Routing-profile {} has negative alphas {:?}, hence no path will be found.",
+                    name, cfg.alphas
+                );
+            }
+            profiles.insert(name, cfg);
+        }
+
+        Ok(Profiles {
+            default_name: proto_cfg.default_profile,
+            profiles,
+        })
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.profiles.keys()
+    }
+
+    pub fn default_name(&self) -> &str {
+        &self.default_name
+    }
+
+    pub fn default_profile(&self) -> &Config {
+        self.get(&self.default_name)
+    }
+
+    fn into_default_profile(mut self) -> Config {
+        self.profiles
+            .remove(&self.default_name)
+            .expect("Default-profile should exist, which is checked when deserializing.")
+    }
+
+    pub fn try_get(&self, name: &str) -> err::Result<&Config> {
+        match self.profiles.get(name) {
+            Some(cfg) => Ok(cfg),
+            None => Err(err::Msg::from(format!(
+                "Routing-profile {} doesn't exist. Available profiles are {:?}.",
+                name,
+                self.names().collect::<Vec<_>>()
+            ))),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> &Config {
+        match self.try_get(name) {
             Ok(cfg) => cfg,
             Err(msg) => panic!("{}", msg),
         }
@@ -195,7 +678,12 @@ impl From<ProtoExploratorAlgo> for ExploratorAlgo {
 pub struct ProtoConfig {
     pub route_pairs_file: Option<PathBuf>,
     pub routing_algo: ProtoRoutingAlgo,
-    pub metrics: DimVec<ProtoEntry>,
+    pub is_checking_non_negative: Option<bool>,
+    pub is_allowing_negative_alphas: Option<bool>,
+    pub max_snap_distance: Option<f64>,
+    pub is_allowing_ch_fallback: Option<bool>,
+    pub profiles: BTreeMap<String, DimVec<ProtoEntry>>,
+    pub default_profile: String,
 }
 
 impl TryFrom<RawConfig> for ProtoConfig {
@@ -204,16 +692,74 @@ impl TryFrom<RawConfig> for ProtoConfig {
     fn try_from(raw_cfg: RawConfig) -> Result<ProtoConfig, String> {
         let raw_cfg = raw_cfg.routing;
 
-        let mut metrics = DimVec::with_capacity(raw_cfg.metrics.len());
+        // collect profiles
+
+        let mut profiles = BTreeMap::new();
+        let has_default_metrics = raw_cfg.metrics.is_some();
+        if let Some(raw_entries) = raw_cfg.metrics {
+            profiles.insert(
+                String::from(defaults::routing::DEFAULT_PROFILE),
+                proto_entries_from(raw_entries)?,
+            );
+        }
+        for (name, raw_profile) in raw_cfg.profiles.unwrap_or_default() {
+            if profiles.contains_key(&name) {
+                return Err(format!(
+                    "Routing-profile {} is defined by the routing-section's metrics already.",
+                    name
+                ));
+            }
+            let entries = proto_entries_from(raw_profile.metrics)
+                .map_err(|msg: String| format!("Routing-profile {} is invalid: {}", name, msg))?;
+            profiles.insert(name, entries);
+        }
+
+        // choose default-profile
+
+        let default_profile = match raw_cfg.default_profile {
+            Some(name) => name,
+            None => {
+                if has_default_metrics {
+                    String::from(defaults::routing::DEFAULT_PROFILE)
+                } else if profiles.len() == 1 {
+                    profiles
+                        .keys()
+                        .next()
+                        .expect("Exactly one profile should exist.")
+                        .clone()
+                } else {
+                    return Err(String::from(
+                        "The routing-section needs metrics, or profiles with a default-profile.",
+                    ));
+                }
+            }
+        };
+        if !profiles.contains_key(&default_profile) {
+            return Err(format!(
+                "Default routing-profile {} doesn't exist. Available profiles are {:?}.",
+                default_profile,
+                profiles.keys().collect::<Vec<_>>()
+            ));
+        }
 
-        for raw_entry in raw_cfg.metrics {
-            metrics.push(ProtoEntry::try_from(raw_entry)?);
+        if let Some(max_snap_distance) = raw_cfg.max_snap_distance {
+            if !(max_snap_distance >= 0.0) {
+                return Err(format!(
+                    "The max-snap-distance {} should be non-negative.",
+                    max_snap_distance
+                ));
+            }
         }
 
         Ok(ProtoConfig {
             route_pairs_file: raw_cfg.route_pairs_file,
             routing_algo: ProtoRoutingAlgo::from(raw_cfg.routing_algo),
-            metrics,
+            is_checking_non_negative: raw_cfg.is_checking_non_negative,
+            is_allowing_negative_alphas: raw_cfg.is_allowing_negative_alphas,
+            max_snap_distance: raw_cfg.max_snap_distance,
+            is_allowing_ch_fallback: raw_cfg.is_allowing_ch_fallback,
+            profiles,
+            default_profile,
         })
     }
 }
@@ -263,7 +809,50 @@ impl From<RawExploratorAlgo> for ProtoExploratorAlgo {
 pub struct ProtoEntry {
     pub id: SimpleId,
     pub alpha: f64,
-    pub tolerated_scale: f64,
+    pub tolerated_scale: ProtoToleratedScale,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProtoToleratedScale {
+    Fixed(f64),
+    /// Derived from the graph's metric (see `Config::expand_auto_tolerated_scales(...)`)
+    Auto {
+        p: f64,
+    },
+}
+
+impl ProtoToleratedScale {
+    fn try_from_str(snippet: &str) -> Result<ProtoToleratedScale, String> {
+        let snippet = snippet.trim().to_ascii_lowercase();
+        match snippet.as_ref() {
+            "inf" | "infinity" => Ok(ProtoToleratedScale::Fixed(
+                defaults::routing::TOLERATED_SCALE_INF,
+            )),
+            snippet => {
+                if let Some(p) = snippet
+                    .strip_prefix("auto(")
+                    .and_then(|snippet| snippet.strip_suffix(")"))
+                {
+                    let p = p
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|_| format!("Couln't parse f64-value {} of {}", p, snippet))?;
+                    if !(p.is_finite() && p >= 0.0) {
+                        return Err(format!(
+                            "The parameter of {} should be finite and non-negative.",
+                            snippet
+                        ));
+                    }
+                    Ok(ProtoToleratedScale::Auto { p })
+                } else {
+                    snippet
+                        .parse::<f64>()
+                        .map(ProtoToleratedScale::Fixed)
+                        .map_err(|_| format!("Couln't parse f64-value {}", snippet))
+                }
+            }
+        }
+    }
 }
 
 impl TryFrom<RawEntry> for ProtoEntry {
@@ -271,14 +860,9 @@ impl TryFrom<RawEntry> for ProtoEntry {
 
     fn try_from(raw_entry: RawEntry) -> Result<ProtoEntry, String> {
         let tolerated_scale = match &raw_entry.tolerated_scale {
-            Some(snippet) => match snippet.to_ascii_lowercase().as_ref() {
-                "inf" | "infinity" => Ok(defaults::routing::TOLERATED_SCALE_INF),
-                snippet => snippet
-                    .parse::<f64>()
-                    .map_err(|_| format!("Couln't parse f64-value {}", snippet)),
-            },
-            None => Ok(defaults::routing::TOLERATED_SCALE),
-        }?;
+            Some(snippet) => ProtoToleratedScale::try_from_str(snippet)?,
+            None => ProtoToleratedScale::Fixed(defaults::routing::TOLERATED_SCALE),
+        };
 
         Ok(ProtoEntry {
             id: raw_entry.id,
@@ -301,6 +885,24 @@ pub struct RawContent {
     pub route_pairs_file: Option<PathBuf>,
     #[serde(rename = "algorithm")]
     pub routing_algo: RawRoutingAlgo,
+    #[serde(rename = "check-non-negative")]
+    pub is_checking_non_negative: Option<bool>,
+    #[serde(rename = "allow-negative-alphas")]
+    pub is_allowing_negative_alphas: Option<bool>,
+    /// in kilometers
+    #[serde(rename = "max-snap-distance")]
+    pub max_snap_distance: Option<f64>,
+    #[serde(rename = "allow-ch-fallback")]
+    pub is_allowing_ch_fallback: Option<bool>,
+    pub metrics: Option<Vec<RawEntry>>,
+    pub profiles: Option<BTreeMap<String, RawProfile>>,
+    #[serde(rename = "default-profile")]
+    pub default_profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawProfile {
     pub metrics: Vec<RawEntry>,
 }
 
@@ -331,3 +933,11 @@ pub struct RawEntry {
     #[serde(rename = "tolerated-scale")]
     pub tolerated_scale: Option<String>,
 }
+
+fn proto_entries_from(raw_entries: Vec<RawEntry>) -> Result<DimVec<ProtoEntry>, String> {
+    let mut entries = DimVec::with_capacity(raw_entries.len());
+    for raw_entry in raw_entries {
+        entries.push(ProtoEntry::try_from(raw_entry)?);
+    }
+    Ok(entries)
+}
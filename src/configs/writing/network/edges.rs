@@ -1,8 +1,12 @@
 use crate::{
-    configs::{writing::network::graph, SimpleId},
+    configs::{
+        self,
+        writing::network::{graph, Precision},
+        SimpleId,
+    },
     defaults,
     helpers::err,
-    io::{network::edges::Writer, SupportingFileExts},
+    io::{self, network::edges::Writer, SupportingFileExts},
 };
 use serde::Deserialize;
 use std::{
@@ -17,7 +21,11 @@ pub struct Config {
     pub is_writing_shortcuts: bool,
     pub is_writing_header: bool,
     pub is_denormalizing: bool,
+    /// If true, every metric is followed by a column with its values' provenances, which have
+    /// to be tracked when parsing (see `track-provenance`).
+    pub is_writing_provenance: bool,
     pub ids: Vec<Option<SimpleId>>,
+    pub precision: Precision,
 }
 
 impl SupportingFileExts for Config {
@@ -43,7 +51,11 @@ impl From<ProtoConfig> for Config {
             is_denormalizing: proto_cfg
                 .is_denormalizing
                 .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+            is_writing_provenance: proto_cfg
+                .is_writing_provenance
+                .unwrap_or(defaults::writing::IS_WRITING_PROVENANCE),
             ids: proto_cfg.ids,
+            precision: proto_cfg.precision.unwrap_or_default(),
         }
     }
 }
@@ -56,7 +68,9 @@ impl From<graph::Config> for Config {
             is_writing_shortcuts: graph_cfg.edges.is_writing_shortcuts,
             is_writing_header: false,
             is_denormalizing: graph_cfg.edges.is_denormalizing,
+            is_writing_provenance: graph_cfg.edges.is_writing_provenance,
             ids: graph_cfg.edges.ids,
+            precision: graph_cfg.edges.precision,
         }
     }
 }
@@ -101,6 +115,49 @@ impl Config {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    /// Checks everything, which doesn't need the graph, hence can be checked before parsing:
+    /// the file's extension, that the file doesn't exist yet and that its directory is writable.
+    pub fn validate_files(&self) -> err::Feedback {
+        match Writer::find_supported_ext(&self.file) {
+            Ok(_) => (),
+            Err(msg) => return Err(err::Msg::from(format!("Wrong writer-map-file: {}", msg))),
+        }
+        io::files::check_new_file(&self.file)
+    }
+
+    /// Checks, that every id is part of the graph's edge-data and that provenances are tracked,
+    /// if they should be written.
+    pub fn validate_ids(&self, graph_cfg: &configs::parsing::Config) -> err::Feedback {
+        for id in self.ids.iter().filter_map(|id| id.as_ref()) {
+            if !graph_cfg
+                .edges
+                .categories
+                .iter()
+                .any(|category| category.id() == Some(id))
+            {
+                return Err(err::Msg::from(format!(
+                    "Writing-config has id {} which is not part of graph's edge-data.",
+                    id
+                )));
+            }
+        }
+
+        if self.is_writing_provenance && !graph_cfg.edges.metrics.is_tracking_provenance {
+            return Err(err::Msg::from(
+                "Provenances should be written, but won't be tracked when parsing. \
+                 Set `track-provenance` to track them.",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Should be called right after parsing the graph, before anything expensive is done.
+    pub fn validate(&self, graph_cfg: &configs::parsing::Config) -> err::Feedback {
+        self.validate_files()?;
+        self.validate_ids(graph_cfg)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -123,7 +180,9 @@ pub struct ProtoConfig {
     pub file: PathBuf,
     pub is_writing_shortcuts: Option<bool>,
     pub is_denormalizing: Option<bool>,
+    pub is_writing_provenance: Option<bool>,
     pub ids: Vec<Option<SimpleId>>,
+    pub precision: Option<Precision>,
 }
 
 impl From<RawConfig> for ProtoConfig {
@@ -134,6 +193,7 @@ impl From<RawConfig> for ProtoConfig {
             file: raw_cfg.file,
             is_writing_shortcuts: raw_cfg.is_writing_shortcuts,
             is_denormalizing: raw_cfg.is_denormalizing,
+            is_writing_provenance: raw_cfg.is_writing_provenance,
             ids: raw_cfg
                 .ids
                 .into_iter()
@@ -142,6 +202,7 @@ impl From<RawConfig> for ProtoConfig {
                     RawCategory::Ignored => None,
                 })
                 .collect(),
+            precision: raw_cfg.precision,
         }
     }
 }
@@ -168,7 +229,10 @@ pub struct RawContent {
     pub is_writing_shortcuts: Option<bool>,
     #[serde(rename = "will_denormalize_metrics_by_mean")]
     pub is_denormalizing: Option<bool>,
+    #[serde(rename = "with_provenance")]
+    pub is_writing_provenance: Option<bool>,
     pub ids: Vec<RawCategory>,
+    pub precision: Option<Precision>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,150 @@
+use crate::{
+    defaults,
+    helpers::err,
+    io::{self, network::geojson::Writer, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Exports the graph as GeoJSON-FeatureCollection, e.g. for inspecting it in QGIS or Leaflet.
+/// Nodes become Points and edges become LineStrings carrying their metrics (see
+/// `io::network::geojson::Writer`).
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "WrappedProtoConfig")]
+pub struct Config {
+    pub file: PathBuf,
+    pub is_writing_shortcuts: bool,
+    pub is_denormalizing: bool,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<WrappedProtoConfig> for Config {
+    fn from(proto_cfg: WrappedProtoConfig) -> Config {
+        let proto_cfg = proto_cfg.writing;
+        Config {
+            file: proto_cfg.file,
+            is_writing_shortcuts: proto_cfg
+                .is_writing_shortcuts
+                .unwrap_or(defaults::parsing::IS_USING_SHORTCUTS),
+            is_denormalizing: proto_cfg
+                .is_denormalizing
+                .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+        }
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let cfg: Config = match serde_yaml::from_reader(file) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        match Writer::find_supported_ext(&cfg.file) {
+            Ok(_) => Ok(cfg),
+            Err(msg) => Err(err::Msg::from(format!(
+                "Wrong writer-geojson-file: {}",
+                msg
+            ))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Checks the file's extension, that the file doesn't exist yet and that its directory is
+    /// writable.
+    pub fn validate_files(&self) -> err::Feedback {
+        match Writer::find_supported_ext(&self.file) {
+            Ok(_) => (),
+            Err(msg) => {
+                return Err(err::Msg::from(format!(
+                    "Wrong writer-geojson-file: {}",
+                    msg
+                )))
+            }
+        }
+        io::files::check_new_file(&self.file)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        let raw_cfg = raw_cfg.writing.geojson;
+        WrappedProtoConfig {
+            writing: ProtoConfig {
+                file: raw_cfg.file,
+                is_writing_shortcuts: raw_cfg.is_writing_shortcuts,
+                is_denormalizing: raw_cfg.is_denormalizing,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProtoConfig {
+    pub file: PathBuf,
+    pub is_writing_shortcuts: Option<bool>,
+    pub is_denormalizing: Option<bool>,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub geojson: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub file: PathBuf,
+    #[serde(rename = "with_shortcuts")]
+    pub is_writing_shortcuts: Option<bool>,
+    #[serde(rename = "will_denormalize_metrics_by_mean")]
+    pub is_denormalizing: Option<bool>,
+}
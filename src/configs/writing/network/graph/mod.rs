@@ -1,8 +1,8 @@
-use super::edges;
+use super::{edges, Precision};
 use crate::{
-    defaults,
+    configs, defaults,
     helpers::err,
-    io::{network::graph::Writer, SupportingFileExts},
+    io::{self, network::graph::Writer, MapFileExt, SupportingFileExts, SupportingMapFileExts},
 };
 use serde::Deserialize;
 use std::{
@@ -17,6 +17,11 @@ pub struct Config {
     pub map_file: PathBuf,
     pub nodes: nodes::Config,
     pub edges: edges::Config,
+    /// If true, fmi-files get one flagged row for both directions of an edge, if they have
+    /// equal metrics.
+    pub is_merging_bidirectional: bool,
+    /// Only used for fmi-files, where these columns are appended to every node-row.
+    pub extra_node_columns: Vec<nodes::ExtraColumn>,
 }
 
 impl SupportingFileExts for Config {
@@ -65,6 +70,48 @@ impl Config {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    /// Checks everything, which doesn't need the graph, hence can be checked before parsing:
+    /// the map-file's extension, that the map-file doesn't exist yet and that its directory is
+    /// writable.
+    pub fn validate_files(&self) -> err::Feedback {
+        match Writer::find_supported_ext(&self.map_file) {
+            Ok(_) => (),
+            Err(msg) => return Err(err::Msg::from(format!("Wrong writer-map-file: {}", msg))),
+        }
+        io::files::check_new_file(&self.map_file)
+    }
+
+    /// Checks, that every node- and edge-id is part of the graph's data.
+    /// Pbf-files ignore these ids (see `io::network::graph::pbf::Writer`), hence they are not
+    /// checked for them.
+    pub fn validate_ids(&self, graph_cfg: &configs::parsing::Config) -> err::Feedback {
+        if Writer::from_path(&self.map_file)? == MapFileExt::PBF {
+            return Ok(());
+        }
+
+        for id in self.nodes.ids.iter().filter_map(|id| id.as_ref()) {
+            if !graph_cfg
+                .nodes
+                .categories
+                .iter()
+                .any(|category| category.id() == Some(id))
+            {
+                return Err(err::Msg::from(format!(
+                    "Writing-config has id {} which is not part of graph's node-data.",
+                    id
+                )));
+            }
+        }
+
+        self.edges.validate_ids(graph_cfg)
+    }
+
+    /// Should be called right after parsing the graph, before anything expensive is done.
+    pub fn validate(&self, graph_cfg: &configs::parsing::Config) -> err::Feedback {
+        self.validate_files()?;
+        self.validate_ids(graph_cfg)
+    }
 }
 
 impl From<WrappedProtoConfig> for Config {
@@ -83,8 +130,15 @@ impl From<WrappedProtoConfig> for Config {
                     .edges
                     .is_denormalizing
                     .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+                // graph-files are parsed again, hence they don't have provenance-columns
+                is_writing_provenance: false,
                 ids: proto_cfg.edges.ids,
+                precision: proto_cfg.edges.precision.unwrap_or_default(),
             },
+            is_merging_bidirectional: proto_cfg
+                .is_merging_bidirectional
+                .unwrap_or(defaults::writing::IS_MERGING_BIDIRECTIONAL),
+            extra_node_columns: proto_cfg.extra_node_columns.unwrap_or_default(),
         }
     }
 }
@@ -95,6 +149,8 @@ pub struct WrappedProtoConfig {
     pub map_file: PathBuf,
     pub nodes: nodes::ProtoConfig,
     pub edges: edges::ProtoConfig,
+    pub is_merging_bidirectional: Option<bool>,
+    pub extra_node_columns: Option<Vec<nodes::ExtraColumn>>,
 }
 
 impl From<WrappedRawConfig> for WrappedProtoConfig {
@@ -110,8 +166,11 @@ impl From<WrappedRawConfig> for WrappedProtoConfig {
                     is_writing_shortcuts: raw_cfg.edges.is_writing_shortcuts,
                     is_denormalizing: raw_cfg.edges.is_denormalizing,
                     ids: raw_cfg.edges.ids,
+                    precision: raw_cfg.precision,
                 },
             }),
+            is_merging_bidirectional: raw_cfg.is_merging_bidirectional,
+            extra_node_columns: raw_cfg.extra_node_columns,
         }
     }
 }
@@ -135,6 +194,11 @@ pub struct RawContent {
     map_file: PathBuf,
     nodes: nodes::RawConfig,
     edges: raw_edges::Config,
+    #[serde(rename = "merge-bidirectional")]
+    is_merging_bidirectional: Option<bool>,
+    #[serde(rename = "extra-node-columns")]
+    extra_node_columns: Option<Vec<nodes::ExtraColumn>>,
+    precision: Option<Precision>,
 }
 
 // TODO module raw exporting stuff for pub(crate) etc.
@@ -32,6 +32,31 @@ impl From<RawConfig> for ProtoConfig {
     }
 }
 
+/// Node-columns, which are computed from the graph's structure instead of its node-data, and
+/// appended to every node-row.
+/// Parsers can skip them with ignored node-categories.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExtraColumn {
+    /// Number of leaving fwd-edges
+    OutDegree,
+    /// Number of entering fwd-edges
+    InDegree,
+    /// Id of the node's strongly connected component (see `network::Graph::components()`)
+    Component,
+}
+
+impl ExtraColumn {
+    /// Used in the written header and the embedded config
+    pub fn id(&self) -> &'static str {
+        match self {
+            ExtraColumn::OutDegree => "out-degree",
+            ExtraColumn::InDegree => "in-degree",
+            ExtraColumn::Component => "component",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RawConfig(pub Vec<RawCategory>);
 
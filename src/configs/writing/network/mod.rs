@@ -1,2 +1,44 @@
+use crate::defaults;
+use serde::Deserialize;
 pub mod edges;
+pub mod geojson;
 pub mod graph;
+pub mod simple_fmi;
+
+/// Digits after the decimal separator of written floats, where None means as many as needed to
+/// parse back the exact value.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(from = "RawPrecision")]
+pub struct Precision {
+    pub coordinates: Option<usize>,
+    pub metrics: Option<usize>,
+}
+
+impl Default for Precision {
+    fn default() -> Precision {
+        Precision {
+            coordinates: defaults::writing::precision::COORDINATES,
+            metrics: defaults::writing::precision::METRICS,
+        }
+    }
+}
+
+impl From<RawPrecision> for Precision {
+    fn from(raw_precision: RawPrecision) -> Precision {
+        Precision {
+            coordinates: raw_precision
+                .coordinates
+                .or(defaults::writing::precision::COORDINATES),
+            metrics: raw_precision
+                .metrics
+                .or(defaults::writing::precision::METRICS),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawPrecision {
+    pub coordinates: Option<usize>,
+    pub metrics: Option<usize>,
+}
@@ -0,0 +1,163 @@
+use crate::{
+    configs::{self, SimpleId},
+    defaults,
+    helpers::err,
+    io::{self, network::simple_fmi::Writer, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Collapses the graph into a node-list `id lat lon` and an edge-list `src-id dst-id weight`,
+/// preceded only by the node- and edge-count, e.g. for teaching.
+/// Node-ids are the nodes' indices, so they are `0..node-count`.
+///
+/// Such files can be parsed again with the parsing-profile `simple-fmi`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "WrappedProtoConfig")]
+pub struct Config {
+    pub map_file: PathBuf,
+    /// The edges' weight
+    pub metric_id: SimpleId,
+    /// Digits after the decimal separator of the weights
+    pub precision: usize,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<WrappedProtoConfig> for Config {
+    fn from(proto_cfg: WrappedProtoConfig) -> Config {
+        let proto_cfg = proto_cfg.writing;
+        Config {
+            map_file: proto_cfg.map_file,
+            metric_id: proto_cfg.metric_id,
+            precision: proto_cfg
+                .precision
+                .unwrap_or(defaults::writing::simple_fmi::PRECISION),
+        }
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let cfg: Config = match serde_yaml::from_reader(file) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        match Writer::find_supported_ext(&cfg.map_file) {
+            Ok(_) => Ok(cfg),
+            Err(msg) => Err(err::Msg::from(format!("Wrong writer-map-file: {}", msg))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Checks the map-file's extension, that the map-file doesn't exist yet and that its
+    /// directory is writable.
+    pub fn validate_files(&self) -> err::Feedback {
+        match Writer::find_supported_ext(&self.map_file) {
+            Ok(_) => (),
+            Err(msg) => return Err(err::Msg::from(format!("Wrong writer-map-file: {}", msg))),
+        }
+        io::files::check_new_file(&self.map_file)
+    }
+
+    /// Checks, that the weight's metric is part of the graph.
+    pub fn validate_ids(&self, graph_cfg: &configs::parsing::Config) -> err::Feedback {
+        match graph_cfg.edges.metrics.try_idx_of(&self.metric_id) {
+            Ok(_) => Ok(()),
+            Err(msg) => Err(err::Msg::from(format!(
+                "The simple fmi-file's weight is not part of the graph: {}",
+                msg
+            ))),
+        }
+    }
+
+    /// Should be called right after parsing the graph, before anything expensive is done.
+    pub fn validate(&self, graph_cfg: &configs::parsing::Config) -> err::Feedback {
+        self.validate_files()?;
+        self.validate_ids(graph_cfg)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        let raw_cfg = raw_cfg.writing.simple_fmi;
+        WrappedProtoConfig {
+            writing: ProtoConfig {
+                map_file: raw_cfg.map_file,
+                metric_id: raw_cfg.metric_id,
+                precision: raw_cfg.precision,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProtoConfig {
+    pub map_file: PathBuf,
+    pub metric_id: SimpleId,
+    pub precision: Option<usize>,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    #[serde(rename = "simple-fmi")]
+    pub simple_fmi: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    #[serde(rename = "map-file")]
+    pub map_file: PathBuf,
+    #[serde(rename = "metric")]
+    pub metric_id: SimpleId,
+    pub precision: Option<usize>,
+}
@@ -1,10 +1,12 @@
 use crate::{
     defaults,
     helpers::err,
-    io::{routing::Writer, SupportingFileExts},
+    io::{self, routing::Writer, SupportingFileExts},
 };
+use kissunits::distance::Kilometers;
 use serde::Deserialize;
 use std::{
+    convert::TryFrom,
     fs::OpenOptions,
     path::{Path, PathBuf},
 };
@@ -71,11 +73,32 @@ impl Config {
             Err(msg) => panic!("{}", msg),
         }
     }
+
+    /// Checks the routes-file's extension, that it doesn't exist yet and that its directory is
+    /// writable.
+    /// Route-pairs don't refer to the graph's data, so this can be checked before parsing.
+    pub fn validate(&self) -> err::Feedback {
+        match Writer::find_supported_ext(&self.file) {
+            Ok(_) => (),
+            Err(msg) => return Err(err::Msg::from(format!("Wrong writer-routes-file: {}", msg))),
+        }
+        io::files::check_new_file(&self.file)
+    }
 }
 
 #[derive(Debug)]
 pub enum Category {
-    RandomOrAll { seed: u64, max_count: usize },
+    RandomOrAll {
+        seed: u64,
+        max_count: usize,
+    },
+    /// Every band `(max_km, count)` asks for `count` route-pairs, whose haversine-distance is at
+    /// least the previous band's `max_km` (or 0) and less than its own `max_km`.
+    /// Bands are sorted ascendingly by their `max_km`.
+    StratifiedByDistance {
+        seed: u64,
+        bands: Vec<(Kilometers, usize)>,
+    },
 }
 
 impl From<ProtoCategory> for Category {
@@ -84,41 +107,82 @@ impl From<ProtoCategory> for Category {
             ProtoCategory::RandomOrAll { seed, max_count } => {
                 Category::RandomOrAll { seed, max_count }
             }
+            ProtoCategory::StratifiedByDistance { seed, bands } => {
+                Category::StratifiedByDistance { seed, bands }
+            }
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
-#[serde(from = "WrappedRawConfig", deny_unknown_fields)]
+#[serde(try_from = "WrappedRawConfig", deny_unknown_fields)]
 pub struct WrappedProtoConfig {
     pub file: PathBuf,
     #[serde(flatten)]
     pub category: ProtoCategory,
 }
 
-impl From<WrappedRawConfig> for WrappedProtoConfig {
-    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+impl TryFrom<WrappedRawConfig> for WrappedProtoConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: WrappedRawConfig) -> err::Result<WrappedProtoConfig> {
         let raw_cfg = raw_cfg.writing.route_pairs;
 
-        WrappedProtoConfig {
+        Ok(WrappedProtoConfig {
             file: raw_cfg.file,
-            category: ProtoCategory::from(raw_cfg.category),
-        }
+            category: ProtoCategory::try_from(raw_cfg.category)?,
+        })
     }
 }
 
 #[derive(Debug)]
 pub enum ProtoCategory {
-    RandomOrAll { seed: u64, max_count: usize },
+    RandomOrAll {
+        seed: u64,
+        max_count: usize,
+    },
+    StratifiedByDistance {
+        seed: u64,
+        bands: Vec<(Kilometers, usize)>,
+    },
 }
 
-impl From<RawCategory> for ProtoCategory {
-    fn from(raw_category: RawCategory) -> ProtoCategory {
+impl TryFrom<RawCategory> for ProtoCategory {
+    type Error = err::Msg;
+
+    fn try_from(raw_category: RawCategory) -> err::Result<ProtoCategory> {
         match raw_category {
-            RawCategory::RandomOrAll { seed, max_count } => ProtoCategory::RandomOrAll {
+            RawCategory::RandomOrAll { seed, max_count } => Ok(ProtoCategory::RandomOrAll {
                 seed: seed.unwrap_or(defaults::SEED),
                 max_count,
-            },
+            }),
+            RawCategory::StratifiedByDistance { seed, bands } => {
+                if bands.is_empty() {
+                    return Err(err::Msg::from(
+                        "The route-pairs' distance-bands shouldn't be empty.",
+                    ));
+                }
+
+                let mut prev_max_km = 0.0;
+                for band in bands.iter() {
+                    if !(band.max_km > prev_max_km) {
+                        return Err(err::Msg::from(format!(
+                            "The route-pairs' distance-bands should have positive max-km \
+                             in ascending order, but {} follows {}.",
+                            band.max_km, prev_max_km
+                        )));
+                    }
+                    prev_max_km = band.max_km;
+                }
+
+                Ok(ProtoCategory::StratifiedByDistance {
+                    seed: seed.unwrap_or(defaults::SEED),
+                    bands: bands
+                        .into_iter()
+                        .map(|band| (Kilometers(band.max_km), band.count))
+                        .collect(),
+                })
+            }
         }
     }
 }
@@ -147,4 +211,17 @@ pub struct RawContent {
 pub enum RawCategory {
     #[serde(rename = "random_or_all")]
     RandomOrAll { seed: Option<u64>, max_count: usize },
+    #[serde(rename = "stratified_by_distance")]
+    StratifiedByDistance {
+        seed: Option<u64>,
+        bands: Vec<RawDistanceBand>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawDistanceBand {
+    #[serde(rename = "max-km")]
+    pub max_km: f64,
+    pub count: usize,
 }
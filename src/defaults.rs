@@ -33,14 +33,140 @@ pub mod capacity {
     pub const SMALL_VEC_INLINE_SIZE: usize = compiler::GRAPH_DIM;
     pub type DimVec<T> = smallvec::SmallVec<[T; SMALL_VEC_INLINE_SIZE]>;
     pub const MAX_BYTE_PER_CHUNK: usize = 200 * 1_000_000;
+
+    /// Compact indices and offsets are stored as `u32`.
+    pub const MAX_COMPACT_EDGE_COUNT: usize = u32::MAX as usize;
+
+    /// Offsets are edge-counts, so the edge-count is limited by the biggest offset.
+    pub fn max_edge_count(is_using_compact_indices: bool) -> usize {
+        if is_using_compact_indices {
+            MAX_COMPACT_EDGE_COUNT
+        } else {
+            usize::MAX
+        }
+    }
 }
 
 pub mod parsing {
     // provided by multi-ch-constructor
     pub const NO_SHORTCUT_IDX: &str = "-1";
     pub const IS_USING_SHORTCUTS: bool = false;
+    /// Written fmi-files describe their columns in a comment-line with this prefix.
+    pub const EMBEDDED_CFG_PREFIX: &str = "# osmgraphing-config:";
+    pub const IS_IGNORING_EMBEDDED_CFG: bool = false;
+    pub const IS_LOSSY_UTF8: bool = false;
+    pub const IS_USING_COMPACT_INDICES: bool = false;
+    /// Threads decoding pbf-blocks, see `configs::parsing::Config::num_threads`
+    pub const NUM_THREADS: usize = 4;
+    /// Key of the preset in the parsing-config (see `configs::parsing::profiles::Profile`)
+    pub const PROFILE_KEY: &str = "profile";
 
     pub const WILL_NORMALIZE_METRICS_BY_MEAN: bool = false;
+    pub const IS_TRACKING_PROVENANCE: bool = false;
+
+    /// Maxspeeds of `*_link`-ways without maxspeed (see
+    /// `configs::parsing::edges::Config::link_speed_fraction()`)
+    pub mod link_speeds {
+        pub const IS_INFERRING: bool = false;
+        /// Fraction of the parent street-category's maxspeed, e.g. 91 km/h for motorway-links
+        pub const FRACTION: f64 = 0.7;
+    }
+
+    pub mod roughness {
+        pub const IS_APPLIED_TO_DURATION: bool = false;
+        /// Roughness-factors of values of the osm-tag `surface`
+        pub const SURFACE: &[(&str, f64)] = &[
+            ("asphalt", 1.0),
+            ("concrete", 1.0),
+            ("paved", 1.0),
+            ("paving_stones", 1.1),
+            ("compacted", 1.2),
+            ("sett", 1.3),
+            ("fine_gravel", 1.3),
+            ("cobblestone", 1.5),
+            ("gravel", 1.5),
+            ("unpaved", 1.5),
+            ("pebblestone", 1.6),
+            ("dirt", 1.6),
+            ("ground", 1.6),
+            ("grass", 1.8),
+            ("mud", 2.0),
+            ("sand", 2.0),
+        ];
+        /// Roughness-factors of values of the osm-tag `tracktype`
+        pub const TRACKTYPE: &[(&str, f64)] = &[
+            ("grade1", 1.0),
+            ("grade2", 1.2),
+            ("grade3", 1.5),
+            ("grade4", 1.8),
+            ("grade5", 2.0),
+        ];
+    }
+
+    pub mod conditional {
+        pub const IS_KEEPING_CONDITIONS: bool = false;
+        /// Values of conditional access-tags, which permit access instead of restricting it.
+        pub const PERMITTING_VALUES: &[&str] = &["yes", "permissive", "designated"];
+    }
+
+    pub mod cycleway {
+        /// Tags of osm-ways, which describe cycleways along the street
+        pub const TAGS: &[&str] = &[
+            "cycleway",
+            "cycleway:both",
+            "cycleway:left",
+            "cycleway:right",
+        ];
+        /// Comfort-costs of values of the cycleway-tags
+        pub const COMFORT: &[(&str, f64)] = &[
+            ("track", 0.0),
+            ("opposite_track", 0.0),
+            ("lane", 0.3),
+            ("opposite_lane", 0.3),
+            ("share_busway", 0.5),
+            ("shared_lane", 0.7),
+            ("opposite", 0.8),
+            ("no", 1.0),
+        ];
+        /// Comfort-costs of ways without (known) cycleway-tags
+        pub const MISSING_COMFORT: f64 = 1.0;
+        /// Values of cycleway-tags, which describe tracks separated from the street
+        pub const SEPARATED_VALUES: &[&str] = &["track", "opposite_track"];
+        pub const TRACK_SPEED_FACTOR: Option<f64> = None;
+    }
+
+    pub mod scanning {
+        /// Tags of osm-ways, whose values are collected when scanning ways.
+        pub const TAGS: &[&str] = &["highway", "maxspeed", "oneway", "lanes", "surface"];
+        /// Ways without (known) street-category are collected under this name.
+        pub const UNKNOWN_CATEGORY: &str = "none";
+        /// Number of most frequent values per tag, which are printed.
+        pub const DISPLAYED_VALUES: usize = 5;
+    }
+
+    pub mod generating {
+        /// Calculated metrics don't depend on the edge's direction, e.g. a car's duration.
+        pub const IS_DIRECTIONAL: bool = false;
+        /// Uphill with a grade of 5 %, a speed is reduced to 2/3.
+        pub const GRADIENT_K: f64 = 10.0;
+        pub const MAX_DOWNHILL_FACTOR: f64 = 1.5;
+    }
+
+    // nodes
+
+    pub mod nodes {
+        use crate::configs::parsing::nodes::ConflictPolicy;
+
+        pub const ON_CONFLICT: ConflictPolicy = ConflictPolicy::Error;
+        /// Stacked nodes are merged only if they (nearly) coincide.
+        pub const STACKED_MAX_DISTANCE_M: f64 = 0.1;
+        /// Elevation of nodes without `ele`-tag, which aren't covered by any SRTM-tile.
+        pub const ELEVATION_M: f64 = 0.0;
+        pub const IS_REQUIRING_ELEVATION: bool = false;
+        /// SRTM-tiles are kept in memory, since nodes of a pbf-file are (roughly) sorted by id
+        /// rather than by location.
+        pub const SRTM_OPEN_TILES: usize = 4;
+    }
 
     // vehicles
 
@@ -49,15 +175,62 @@ pub mod parsing {
 
         pub const CATEGORY: VehicleCategory = VehicleCategory::Car;
         pub const ARE_DRIVERS_PICKY: bool = true;
+        pub const MAX_SPEED: Option<f64> = None;
     }
 }
 
 pub mod writing {
-    pub use super::parsing::NO_SHORTCUT_IDX;
+    pub use super::parsing::{EMBEDDED_CFG_PREFIX, NO_SHORTCUT_IDX};
     pub const IGNORE_STR: &str = "_";
 
     pub const IS_WRITING_WITH_HEADER: bool = true;
     pub const WILL_DENORMALIZE_METRICS_BY_MEAN: bool = false;
+    pub const IS_MERGING_BIDIRECTIONAL: bool = false;
+    pub const IS_WRITING_PROVENANCE: bool = false;
+    /// Appended to a metric's id for the header of its provenance-column in edges-info-files.
+    pub const PROVENANCE_SUFFIX: &str = "-provenance";
+    /// Id of the flag-column, which is appended to fmi-edges when merging bidirectional edges.
+    pub const BIDIRECTIONAL_ID: &str = "bidirectional";
+    /// Floats of at least this absolute value are written in scientific notation.
+    pub const SCI_NOTATION_THRESHOLD: f64 = 1e15;
+    /// Stratified route-pairs give up a distance-band, if it hasn't got a route-pair within this
+    /// many attempts.
+    pub const MAX_ATTEMPTS_PER_BAND: usize = 100_000;
+
+    /// Digits after the decimal separator of written floats, where None means as many as needed
+    /// to parse back the exact value.
+    pub mod precision {
+        pub const COORDINATES: Option<usize> = None;
+        pub const METRICS: Option<usize> = None;
+    }
+
+    pub mod simple_fmi {
+        /// Digits after the decimal separator of the edges' weights
+        pub const PRECISION: usize = 6;
+    }
+
+    /// Written files are written into a temporary file first, which is renamed on success.
+    pub mod files {
+        /// Appended to a new file's name for its temporary file
+        pub const TMP_SUFFIX: &str = "tmp";
+        /// Appended to a new file's name for the file probing the directory's writability
+        pub const PROBE_SUFFIX: &str = "probe";
+        /// Probes directories, which should be written into
+        pub const PROBE_FILE_NAME: &str = ".osmgraphing.probe";
+    }
+}
+
+/// Overlay of edge-values for osm-editors (see `io::osm::Writer`)
+pub mod osm_overlay {
+    pub const VERSION: &str = "0.6";
+    pub const GENERATOR: &str = "osmgraphing";
+
+    pub mod tags {
+        pub const WORKLOAD: &str = "osmgraphing:workload";
+        pub const LANE_COUNT: &str = "osmgraphing:lane_count";
+        /// in kilometers
+        pub const DISTANCE: &str = "osmgraphing:distance";
+    }
 }
 
 pub mod smarts {
@@ -77,22 +250,86 @@ pub mod routing {
     pub const ALPHA: f64 = 1.0;
     pub const TOLERATED_SCALE_INF: f64 = std::f64::INFINITY;
     pub const TOLERATED_SCALE: f64 = std::f64::INFINITY;
+    /// Interquartile range of the standard normal distribution, for estimating a metric's
+    /// coefficient of variation from its quantiles (see `tolerated-scale: auto(p)`)
+    pub const NORMAL_IQR: f64 = 1.349;
+    /// Name of the profile, which is formed by the routing-section's metrics.
+    pub const DEFAULT_PROFILE: &str = "default";
     /// If true, the edges are sorted by their dsts' ch-level to speedup routing.
     /// This sort isn't stable in combination with a ch-construction and varying metrics, because a ch-constructor sets the ch-levels dependent on the metrics.
     /// In result, edges can't be identified in balancer.
+    /// Dijkstra stops early at lower levels only, if the graph has verified this order when finalizing.
     pub const IS_USING_CH_LEVEL_SPEEDUP: bool = true;
+    /// Checking every relaxed edge for negative costs costs runtime, hence it's optional in release-builds.
+    pub const IS_CHECKING_NON_NEGATIVE: bool = false;
+    /// Alphas with negative components maximize the respective metric, hence the explorator
+    /// doesn't use them by default.
+    pub const IS_ALLOWING_NEGATIVE_ALPHAS: bool = false;
+    /// Initial capacity of paths of queries with `dijkstra::PathHint::ExpectShort`
+    pub const SHORT_PATH_CAPACITY: usize = 256;
+    /// Coordinates of route-pairs farther away from their nearest node are rejected.
+    pub const MAX_SNAP_DISTANCE_KM: f64 = 0.5;
+    pub const IS_ALLOWING_CH_FALLBACK: bool = false;
+    /// Route-pairs-files declare their format in a comment-line with this prefix.
+    pub const ROUTE_PAIRS_FORMAT_PREFIX: &str = "# route-pairs-format:";
+    /// Routing-results embed their routing-config in a comment-line with this prefix.
+    pub const EMBEDDED_CFG_PREFIX: &str = "# osmgraphing-routing-config:";
+    /// Queries with timeout check the clock only every this many dequeued nodes, since reading
+    /// the clock costs runtime as well.
+    pub const TIMEOUT_CHECK_INTERVAL: usize = 256;
+    /// Displayed costs of paths (see `routing::paths::Costs`) are rounded to this many digits,
+    /// unless the formatter specifies a precision.
+    pub const COSTS_DISPLAY_PRECISION: usize = 2;
+
+    /// Bearing-changes (in degrees) at junctions, from which on a turn is instructed.
+    pub mod instructions {
+        pub const SLIGHT_TURN_ANGLE: f64 = 20.0;
+        pub const TURN_ANGLE: f64 = 45.0;
+        pub const SHARP_TURN_ANGLE: f64 = 135.0;
+        pub const U_TURN_ANGLE: f64 = 170.0;
+    }
+
+    pub mod verification {
+        /// Relative deviation of a route's costs from the reference's costs, which is still passing.
+        pub const TOLERANCE: f64 = 0.01;
+    }
+
+    /// GeoJSON-export of shortest-path-trees (see `routing::sp_tree::SpTree`)
+    pub mod sp_tree {
+        /// Stroke-colors of the tree's edges from cheap to expensive, where every color
+        /// represents an equally sized quantile of the reached nodes' costs.
+        pub const COLORS: [&str; 5] = ["#1a9850", "#91cf60", "#fee08b", "#fc8d59", "#d73027"];
+    }
+
+    /// Landmarks of ALT (see `routing::alt::LandmarkPreprocessor`)
+    pub mod alt {
+        /// Landmarks are stored next to their map-file, e.g. `graph.fmi.landmarks`.
+        pub const FILE_SUFFIX: &str = "landmarks";
+    }
+
+    pub mod golden {
+        /// Decimal places of costs in dumped routing-results.
+        pub const PRECISION: usize = 6;
+        /// If set, expected golden-files are overwritten with the actual routing-results.
+        pub const UPDATE_ENV_VAR: &str = "OSMGRAPHING_UPDATE_GOLDEN";
+    }
 }
 
 #[cfg(feature = "gpl")]
 pub mod balancing {
     use crate::{
         approximating::Approx,
-        configs,
-        helpers::err,
-        network::{EdgeIdx, Graph},
+        configs::{
+            self,
+            balancing::{DemandSampling, InitialWorkload, ResultsFormat},
+        },
+        helpers::{self, err},
+        network::{Graph, RoutePair},
+        routing::paths::Path,
     };
     use kissunits::distance::Kilometers;
     use log::{info, warn};
+    use rand::SeedableRng;
     use std::cmp::max;
 
     // A high work-size could be less productive since less dynamic:
@@ -103,7 +340,17 @@ pub mod balancing {
     pub const WORK_SIZE_PLUS: usize = 30;
     pub const WORK_SIZE_MINUS: usize = 10;
     pub const NUM_THREADS: usize = 4;
+    /// Route-pairs-files with more route-pairs (by their header) are streamed in every iteration
+    /// instead of being kept in memory (see `io::routing::Parser::stream(...)`).
+    pub const MAX_IN_MEMORY_ROUTE_PAIRS: usize = 1_000_000;
     pub const IS_ERR_WHEN_METRIC_IS_ZERO: bool = true;
+    pub const IS_WRITING_PATH_STABILITY: bool = false;
+    pub const DEMAND_SAMPLING: DemandSampling = DemandSampling::Rounded;
+    pub const RESULTS_FORMAT: ResultsFormat = ResultsFormat::Full;
+    /// Map-files are hashed in chunks of this size (see `io::balancing::checksum(...)`), since
+    /// base-graphs could be too large for memory.
+    pub const CHECKSUM_CHUNK_SIZE: usize = 1 << 20;
+    pub const INITIAL_WORKLOAD: InitialWorkload = InitialWorkload::Zero;
 
     pub mod stats {
         pub const DIR: &str = "stats";
@@ -119,8 +366,41 @@ pub mod balancing {
 
     pub mod files {
         pub const ITERATION_CFG: &str = "iteration.yaml";
+        pub const PATH_STABILITY: &str = "path_stability.csv";
+        pub const BASE_MANIFEST: &str = "base.yaml";
+        pub const DELTA_MANIFEST: &str = "manifest.yaml";
+        pub const DELTA: &str = "delta.csv";
+        /// Describes the run as a whole, e.g. how the optimized metric has been initialized.
+        pub const METADATA: &str = "metadata.yaml";
+        /// Contains the process-id of an active run and is removed, when the run has finished.
+        pub const RUN_LOCK: &str = "run.lock";
+        /// The effective routing-config of an iteration (see `configs::routing::Config::to_embedded(...)`)
+        pub const ROUTING_META: &str = "routing.meta.yaml";
+        /// Wall-clock-time per phase and iteration (see `balancing::timing::PhaseTimer`)
+        pub const TIMING: &str = "timing.csv";
+        /// Top contributing route-pairs per edge (see `balancing::edge_users::EdgeUsers`)
+        pub const EDGE_USERS: &str = "edge_users.csv";
+    }
+
+    pub mod ch_rebuild {
+        /// A single edge's optimized metric may change by 50 % without contracting again.
+        pub const MAX_RELATIVE_DELTA: f64 = 0.5;
+        /// The optimized metric may change by 5 % on average without contracting again.
+        pub const MEAN_RELATIVE_DELTA: f64 = 0.05;
+        /// Skipping a rebuild is verified by comparing CH-Dijkstra with Dijkstra on this many
+        /// sample queries.
+        pub const VERIFIED_QUERY_COUNT: usize = 100;
     }
 
+    pub mod edge_users {
+        pub const TOP_K: usize = 20;
+        /// Edges of lower workload are not tracked.
+        pub const MIN_WORKLOAD: f64 = 1.0;
+    }
+
+    /// The base-graph of delta-results is stored in this iteration's directory.
+    pub const BASE_ITER: usize = 0;
+
     /// Nagel-Schreckenberg-Model -> `7.5 m` space for every vehicle
     ///
     /// Returns at least 1
@@ -128,10 +408,164 @@ pub mod balancing {
         max(1, (km / Kilometers(0.0075)) as u64)
     }
 
+    /// Returns the route-pairs with the counts, which should be routed in the given iteration.
+    ///
+    /// When sampling from Poisson, the rng is seeded by the seed and the iteration, so every iteration gets its own, but reproducible sample.
+    /// Rounded counts are rounded to the nearest integer.
+    /// Route-pairs with a sampled or rounded count of 0 are removed.
+    pub fn sample_route_counts(
+        route_pairs: &[(RoutePair<i64>, f64)],
+        iteration: usize,
+        demand_sampling: &DemandSampling,
+    ) -> Vec<(RoutePair<i64>, f64)> {
+        sample_route_counts_lazily(
+            route_pairs.iter().map(|&route_pair| Ok(route_pair)),
+            iteration,
+            demand_sampling,
+        )
+        .collect::<err::Result<_>>()
+        .expect("Sampling route-pairs in memory can't fail.")
+    }
+
+    /// Like `sample_route_counts(...)`, but for streamed route-pairs (see
+    /// `io::routing::Parser::stream(...)`), which results in the same samples for the same
+    /// route-pairs.
+    /// Errors of the stream are passed through.
+    pub fn sample_route_counts_lazily<'a, I>(
+        route_pairs: I,
+        iteration: usize,
+        demand_sampling: &DemandSampling,
+    ) -> Box<dyn Iterator<Item = err::Result<(RoutePair<i64>, f64)>> + 'a>
+    where
+        I: Iterator<Item = err::Result<(RoutePair<i64>, f64)>> + 'a,
+    {
+        match demand_sampling {
+            DemandSampling::Rounded => {
+                Box::new(route_pairs.filter_map(|route_pair| match route_pair {
+                    Ok((route_pair, count)) => {
+                        let count = count.round();
+                        if count > 0.0 {
+                            Some(Ok((route_pair, count)))
+                        } else {
+                            None
+                        }
+                    }
+                    Err(msg) => Some(Err(msg)),
+                }))
+            }
+            DemandSampling::Expected => Box::new(route_pairs),
+            DemandSampling::Poisson { seed } => {
+                let mut rng = rand_pcg::Pcg32::seed_from_u64(seed.wrapping_add(iteration as u64));
+                Box::new(route_pairs.filter_map(move |route_pair| match route_pair {
+                    Ok((route_pair, count)) => {
+                        let count = helpers::sample_poisson(count, &mut rng) as f64;
+                        if count > 0.0 {
+                            Some(Ok((route_pair, count)))
+                        } else {
+                            None
+                        }
+                    }
+                    Err(msg) => Some(Err(msg)),
+                }))
+            }
+        }
+    }
+
+    /// Adds the given (maybe fractional) count to the workload of every edge of the provided path, which has to be flattened.
+    pub fn add_workloads(abs_workloads: &mut [f64], path: &Path, count: f64) {
+        for &edge_idx in path {
+            abs_workloads[*edge_idx] += count;
+        }
+    }
+
+    /// True, if the optimized metric is used for routing and contraction in the given iteration.
+    /// Without warm-start, iteration 0 ignores it (see `configs::balancing::InitialWorkload`).
+    pub fn is_using_new_metric(
+        iteration: usize,
+        balancing_cfg: &configs::balancing::Config,
+    ) -> bool {
+        iteration > 0 || balancing_cfg.initial_workload.is_warm()
+    }
+
+    /// Initializes the optimized metric as configured, which is done once after parsing and
+    /// before iteration 0.
+    ///
+    /// For `freeflow`, the product `lane-count * distance` is normalized by its mean, so the
+    /// initial metric has mean 1, like the metric after every update.
+    /// Hence, it doesn't matter, whether lane-count and distance are normalized themselves.
+    /// For `uniform(value)`, the normalized metric is 1 for every edge.
+    /// In both cases, the actual mean is stored, if the graph's metrics are normalized, so the
+    /// metric is denormalized to the configured values.
+    pub fn init_new_metric(
+        graph: &mut Graph,
+        balancing_cfg: &configs::balancing::Config,
+    ) -> err::Feedback {
+        let new_metric_idx = graph
+            .cfg()
+            .edges
+            .metrics
+            .try_idx_of(&balancing_cfg.optimization.metric_id)?;
+
+        // the mean is stored for denormalization
+        let (new_metrics, mean) = match &balancing_cfg.initial_workload {
+            InitialWorkload::Zero => return Ok(()),
+            InitialWorkload::Uniform(value) => {
+                if Approx(*value) <= Approx(0.0) {
+                    return Err(err::Msg::from(format!(
+                        "The uniform initial workload {} should be positive, \
+                         since the metric is normalized by its mean.",
+                        value
+                    )));
+                }
+                // normalized by its mean, which is the value itself
+                (vec![1.0; graph.fwd_edges().count()], *value)
+            }
+            InitialWorkload::Freeflow {
+                lane_count_id,
+                distance_id,
+            } => {
+                let lane_count_idx = graph.cfg().edges.metrics.try_idx_of(lane_count_id)?;
+                let distance_idx = graph.cfg().edges.metrics.try_idx_of(distance_id)?;
+                let metrics = graph.metrics();
+                let mut new_metrics: Vec<f64> = metrics
+                    .column(lane_count_idx)
+                    .iter()
+                    .zip(metrics.column(distance_idx).iter())
+                    .map(|(lane_count, distance)| lane_count * distance)
+                    .collect();
+
+                let mean: f64 = new_metrics.iter().sum::<f64>() / (new_metrics.len() as f64);
+                if Approx(mean) <= Approx(0.0) {
+                    return Err(err::Msg::from(
+                        "The freeflow-workload's mean is zero, hence no normalization can be done.",
+                    ));
+                }
+                for new_metric in &mut new_metrics {
+                    *new_metric /= mean;
+                }
+                (new_metrics, mean)
+            }
+        };
+
+        info!(
+            "Initialized workload-metric {} as {}",
+            balancing_cfg.optimization.metric_id, balancing_cfg.initial_workload
+        );
+        let mut metrics = graph.metrics_mut();
+        if let Some(means) = metrics.means() {
+            means[*new_metric_idx] = mean;
+        }
+        metrics
+            .column_mut(new_metric_idx)
+            .copy_from_slice(&new_metrics);
+
+        Ok(())
+    }
+
     /// This is only called once per balancer-iteration or undefined behaviour occurs!
     pub fn update_new_metric(
         iteration: usize,
-        abs_workloads: &Vec<usize>,
+        abs_workloads: &[f64],
         graph: &mut Graph,
         balancing_cfg: &configs::balancing::Config,
     ) -> err::Feedback {
@@ -145,9 +579,9 @@ pub mod balancing {
             .cfg()
             .edges
             .metrics
-            .idx_of(&balancing_cfg.optimization.metric_id);
+            .try_idx_of(&balancing_cfg.optimization.metric_id)?;
 
-        let mut new_metrics: Vec<_> = abs_workloads.iter().map(|&w| w as f64).collect();
+        let mut new_metrics = abs_workloads.to_vec();
         let mut metrics = graph.metrics_mut();
 
         // normalize new workloads
@@ -155,26 +589,30 @@ pub mod balancing {
         // compute new mean
 
         let mean: f64 = new_metrics.iter().sum::<f64>() / (new_metrics.len() as f64);
-        if Approx(mean) == Approx(0.0) {
+        if Approx(mean) < Approx(0.0) {
             return Err(err::Msg::from(
-                "The new workload-metric's mean is zero, hence no normalization can be done.",
+                "The new workload-metric's mean is negative, hence no normalization can be done.",
             ));
         }
 
         // normalize abs-workloads with new computed mean
+        // -> zero workloads (e.g. nothing has been routed) are kept and checked below
 
-        for new_metric in &mut new_metrics {
-            *new_metric /= mean;
+        if Approx(mean) > Approx(0.0) {
+            for new_metric in &mut new_metrics {
+                *new_metric /= mean;
+            }
         }
 
         // now: new_metrics has all new metrics, normalized by its own workloads' mean
 
         // update
 
-        for (edge_idx, new_metric) in new_metrics.iter_mut().enumerate() {
+        for (new_metric, &old_metric) in new_metrics
+            .iter_mut()
+            .zip(metrics.column(old_metric_idx).iter())
+        {
             *new_metric = {
-                let old_metric = metrics[EdgeIdx(edge_idx)][*old_metric_idx];
-
                 match balancing_cfg.optimization.method {
                     configs::balancing::OptimizationMethod::ExplicitEuler { correction } => {
                         old_metric + (*new_metric - old_metric) * correction
@@ -230,17 +668,25 @@ pub mod balancing {
         // compute new mean
 
         let mean: f64 = new_metrics.iter().sum::<f64>() / (new_metrics.len() as f64);
-        if Approx(mean) <= Approx(0.0) {
+        if Approx(mean) < Approx(0.0) {
             return Err(err::Msg::from(
-                "The new workload-metric's mean is zero, hence no normalization can be done.",
+                "The new workload-metric's mean is negative, hence no normalization can be done.",
             ));
         }
 
-        // normalize abs-workloads with new computed mean
+        // A zero mean implies a zero metric, which is only accepted without error (see above)
+        // and hence kept as it is, so its mean for denormalization is 1.
 
-        for new_metric in &mut new_metrics {
-            *new_metric /= mean;
-        }
+        let mean = if Approx(mean) > Approx(0.0) {
+            // normalize abs-workloads with new computed mean
+
+            for new_metric in &mut new_metrics {
+                *new_metric /= mean;
+            }
+            mean
+        } else {
+            1.0
+        };
 
         // update graph's metric's mean
 
@@ -251,9 +697,9 @@ pub mod balancing {
 
         // update graph's metric
 
-        for (edge_idx, new_metric) in new_metrics.into_iter().enumerate() {
-            metrics[EdgeIdx(edge_idx)][*old_metric_idx] = new_metric;
-        }
+        metrics
+            .column_mut(old_metric_idx)
+            .copy_from_slice(&new_metrics);
 
         Ok(())
     }
@@ -272,9 +718,21 @@ pub mod explorating {
 pub mod network {
     pub mod nodes {
         pub const LEVEL: usize = 0;
+        /// The earth's polar radius is its smallest one, hence radius-queries based on it don't
+        /// miss any node, whatever radius the haversine-distance uses.
+        pub const MIN_EARTH_RADIUS_KM: f64 = 6356.752;
+    }
+
+    pub mod edges {
+        /// The edge-grid for bounding-box-queries has about this many edges per cell on average.
+        pub const PER_GRID_CELL: usize = 4;
+        /// Edge-lengths are rounded to buckets of this many meters for stable edge-ids, so
+        /// slightly moved coordinates don't change the id.
+        pub const STABLE_ID_LENGTH_BUCKET: f64 = 10.0;
     }
 
     use crate::{
+        configs::parsing::edges::{cycleway, roughness},
         defaults,
         network::{vehicles::Category as VehicleCategory, StreetCategory},
     };
@@ -308,7 +766,33 @@ pub mod network {
             }
         }
 
-        fn maxspeed(&self) -> KilometersPerHour {
+        /// Used if a way has no known `surface` or `tracktype`.
+        fn roughness(&self) -> f64 {
+            match self {
+                StreetCategory::Motorway
+                | StreetCategory::MotorwayLink
+                | StreetCategory::Trunk
+                | StreetCategory::TrunkLink
+                | StreetCategory::Primary
+                | StreetCategory::PrimaryLink
+                | StreetCategory::Secondary
+                | StreetCategory::SecondaryLink
+                | StreetCategory::Tertiary
+                | StreetCategory::TertiaryLink
+                | StreetCategory::Unclassified
+                | StreetCategory::Residential
+                | StreetCategory::LivingStreet
+                | StreetCategory::Service
+                | StreetCategory::Road
+                | StreetCategory::Cycleway
+                | StreetCategory::Pedestrian => 1.0,
+                StreetCategory::Path => 1.3,
+                StreetCategory::Track => 1.5,
+            }
+        }
+
+        /// The street-category's maxspeed, which is taken if a way doesn't provide a usable one.
+        pub fn maxspeed(&self) -> KilometersPerHour {
             KilometersPerHour(match self {
                 StreetCategory::Motorway => 130,
                 StreetCategory::MotorwayLink => 50,
@@ -436,18 +920,128 @@ pub mod network {
             self.lane_count()
         }
 
-        pub fn parse_maxspeed(&self, way: &Way) -> KilometersPerHour {
+        pub fn parse_roughness(&self, way: &Way, cfg: &roughness::Config) -> f64 {
+            let surface = way.tags.get("surface").map(|value| &**value);
+            let tracktype = way.tags.get("tracktype").map(|value| &**value);
+            cfg.factor(surface, tracktype)
+                .unwrap_or_else(|| self.roughness())
+        }
+
+        /// Looks at the tags `cycleway` and `cycleway:left/right/both` (see
+        /// `configs::parsing::edges::cycleway::Config::comfort(...)`).
+        pub fn parse_cycleway_comfort(&self, way: &Way, cfg: &cycleway::Config) -> f64 {
+            cfg.comfort(cycleway_values(way))
+                .unwrap_or(defaults::parsing::cycleway::MISSING_COMFORT)
+        }
+
+        /// Returns true, if any cycleway-tag describes a track, which is separated from the street.
+        pub fn has_cycle_track(&self, way: &Way) -> bool {
+            cycleway_values(way).any(|value| {
+                defaults::parsing::cycleway::SEPARATED_VALUES
+                    .contains(&value.trim().to_ascii_lowercase().as_ref())
+            })
+        }
+
+        /// Returns the conditional access-tags (as `key=value`, separated by ` | `), which restrict
+        /// the given vehicle-category under some conditions, e.g.
+        /// `motor_vehicle:conditional=no @ (Mo-Fr 07:00-09:00)`.
+        /// Conditions aren't evaluated, and tags permitting access (e.g. `yes @ ...`) are ignored.
+        pub fn parse_conditional_restriction(
+            &self,
+            way: &Way,
+            vehicle_category: &VehicleCategory,
+        ) -> Option<String> {
+            let keys: &[&str] = match vehicle_category {
+                VehicleCategory::Car => &[
+                    "access:conditional",
+                    "vehicle:conditional",
+                    "motor_vehicle:conditional",
+                    "motorcar:conditional",
+                ],
+                VehicleCategory::Bicycle => &[
+                    "access:conditional",
+                    "vehicle:conditional",
+                    "bicycle:conditional",
+                ],
+                VehicleCategory::Pedestrian => &["access:conditional", "foot:conditional"],
+            };
+
+            let restrictions: Vec<String> = keys
+                .iter()
+                .filter_map(|&key| way.tags.get(key).map(|value| (key, value)))
+                .filter(|(_key, value)| is_restricting_condition(value))
+                .map(|(key, value)| format!("{}={}", key, value.trim()))
+                .collect();
+            if restrictions.is_empty() {
+                None
+            } else {
+                Some(restrictions.join(" | "))
+            }
+        }
+
+        /// Returns the street-category, which a `*_link`-way connects to, e.g. `Motorway` for
+        /// `MotorwayLink`, or None for other street-categories.
+        pub fn parent(&self) -> Option<StreetCategory> {
+            match self {
+                StreetCategory::MotorwayLink => Some(StreetCategory::Motorway),
+                StreetCategory::TrunkLink => Some(StreetCategory::Trunk),
+                StreetCategory::PrimaryLink => Some(StreetCategory::Primary),
+                StreetCategory::SecondaryLink => Some(StreetCategory::Secondary),
+                StreetCategory::TertiaryLink => Some(StreetCategory::Tertiary),
+                StreetCategory::Motorway
+                | StreetCategory::Trunk
+                | StreetCategory::Primary
+                | StreetCategory::Secondary
+                | StreetCategory::Tertiary
+                | StreetCategory::Unclassified
+                | StreetCategory::Residential
+                | StreetCategory::LivingStreet
+                | StreetCategory::Service
+                | StreetCategory::Track
+                | StreetCategory::Road
+                | StreetCategory::Cycleway
+                | StreetCategory::Pedestrian
+                | StreetCategory::Path => None,
+            }
+        }
+
+        /// Like `parse_given_maxspeed(...)`, but falls back to `default_maxspeed(...)`.
+        pub fn parse_maxspeed(
+            &self,
+            way: &Way,
+            link_speed_fraction: Option<f64>,
+        ) -> KilometersPerHour {
+            self.parse_given_maxspeed(way)
+                .unwrap_or_else(|| self.default_maxspeed(link_speed_fraction))
+        }
+
+        /// The maxspeed of ways without usable maxspeed.
+        ///
+        /// With a link-speed-fraction (see
+        /// `configs::parsing::edges::Config::link_speed_fraction()`), `*_link`-ways get this
+        /// fraction of their parent's maxspeed instead of their own (rather low) maxspeed.
+        pub fn default_maxspeed(&self, link_speed_fraction: Option<f64>) -> KilometersPerHour {
+            match (self.parent(), link_speed_fraction) {
+                (Some(parent), Some(fraction)) => KilometersPerHour(*parent.maxspeed() * fraction),
+                _ => self.maxspeed(),
+            }
+        }
+
+        /// Returns None, if the way has no maxspeed or a maxspeed, which can't be used,
+        /// hence the street-category's default would be taken.
+        pub fn parse_given_maxspeed(&self, way: &Way) -> Option<KilometersPerHour> {
             let snippet = match way.tags.get("maxspeed") {
                 Some(snippet) => snippet,
-                None => return self.maxspeed(),
+                None => return None,
             };
 
             // parse given maxspeed and return
             match snippet.parse::<u16>() {
-                Ok(maxspeed) => {
-                    KilometersPerHour(max(defaults::speed::MIN_KMH.into(), maxspeed) as f64)
-                }
-                Err(_) => match snippet.trim().to_ascii_lowercase().as_ref() {
+                Ok(maxspeed) => Some(KilometersPerHour(max(
+                    defaults::speed::MIN_KMH.into(),
+                    maxspeed,
+                ) as f64)),
+                Err(_) => Some(match snippet.trim().to_ascii_lowercase().as_ref() {
                     // motorway
                     "de:motorway"
                     => StreetCategory::Motorway.maxspeed(),
@@ -544,7 +1138,7 @@ pub mod network {
                     | "signal" // way-id: 189189059
                     | "signals" // way-id: 3996833
                     | "variable" // way-id: 461169632
-                    => self.maxspeed(),
+                    => return None,
                     // unknown
                     _ => {
                         warn!(
@@ -554,9 +1148,9 @@ pub mod network {
                             self,
                             self.maxspeed()
                         );
-                        self.maxspeed()
+                        return None;
                     }
-                },
+                }),
             }
         }
 
@@ -610,6 +1204,45 @@ pub mod network {
         }
     }
 
+    fn cycleway_values<'a>(way: &'a Way) -> impl Iterator<Item = &'a str> + 'a {
+        defaults::parsing::cycleway::TAGS
+            .iter()
+            .filter_map(move |&key| way.tags.get(key).map(|value| &**value))
+    }
+
+    /// A conditional value consists of `;`-separated restrictions `value @ condition`, where
+    /// conditions may contain `;` in parentheses.
+    fn is_restricting_condition(tag_value: &str) -> bool {
+        let mut depth: usize = 0;
+        let mut start = 0;
+        let mut restrictions = Vec::new();
+        for (i, c) in tag_value.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ';' if depth == 0 => {
+                    restrictions.push(&tag_value[start..i]);
+                    start = i + 1;
+                }
+                _ => (),
+            }
+        }
+        restrictions.push(&tag_value[start..]);
+
+        restrictions.into_iter().any(|restriction| {
+            // malformed restrictions without condition are ignored
+            match restriction.find('@') {
+                Some(at_pos) => {
+                    let value = restriction[..at_pos].trim().to_ascii_lowercase();
+                    !value.is_empty()
+                        && !defaults::parsing::conditional::PERMITTING_VALUES
+                            .contains(&value.as_ref())
+                }
+                None => false,
+            }
+        })
+    }
+
     impl FromStr for StreetCategory {
         type Err = bool;
 
@@ -0,0 +1,29 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Cancels long-running queries cooperatively, e.g. when a user isn't interested in the result
+/// anymore.
+///
+/// Clones share their state, so a query can be cancelled from another thread, while the query
+/// checks the token regularly (see `Dijkstra::compute_best_path_cancellable(...)`).
+/// A cancelled token stays cancelled, hence new queries need a new token.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -1,13 +1,60 @@
 use crate::defaults::capacity::DimVec;
+use rand::Rng;
 use std::str::FromStr;
 
 pub mod algebra;
+pub mod cancel;
 pub mod err;
 
-pub fn is_line_functional(line: &String) -> bool {
+/// Enters a `tracing`-span at level info, which is exited at the end of the current scope, e.g.
+/// `trace_scope!("dijkstra::query", src_idx = *src_idx)`.
+///
+/// Without the feature `tracing`, nothing is compiled, so the span's fields aren't evaluated.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trace_scope {
+    ($($args:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _entered_span = ::tracing_lib::info_span!($($args)+).entered();
+    };
+}
+
+pub fn is_line_functional<S: AsRef<str> + ?Sized>(line: &S) -> bool {
+    let line = line.as_ref();
     line.len() > 0 && line.chars().next() != Some('#')
 }
 
+/// FNV-1a (64 bit), which is, in contrast to Rust's default hasher, guaranteed to be the same for
+/// every build and platform, e.g. for checksums or stable ids written into files.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv1a {
+    hash: u64,
+}
+
+impl Fnv1a {
+    pub fn new() -> Fnv1a {
+        Fnv1a {
+            hash: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash ^= byte as u64;
+            self.hash = self.hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Integers are hashed in little-endian, independent of the platform.
+    pub fn write_i64(&mut self, value: i64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
 pub fn add(a: &DimVec<f64>, b: &DimVec<f64>) -> DimVec<f64> {
     a.iter().zip(b).map(|(aa, bb)| aa + bb).collect()
 }
@@ -32,6 +79,28 @@ pub fn le(a: &[f64], b: &[f64]) -> bool {
         .fold(true, |start, (aa, bb)| start && aa.le(bb))
 }
 
+/// Samples an integer from `Poisson(lambda)` using Knuth's multiplication of uniform numbers.
+///
+/// Since `exp(-lambda)` underflows for big lambdas, lambda is split into chunks, whose samples are added up (sums of independent poisson-variables are poisson-distributed).
+pub fn sample_poisson<R: Rng + ?Sized>(lambda: f64, rng: &mut R) -> u64 {
+    const MAX_CHUNK: f64 = 30.0;
+
+    let mut sample = 0;
+    let mut remaining = lambda.max(0.0);
+    while remaining > 0.0 {
+        let chunk = remaining.min(MAX_CHUNK);
+        remaining -= chunk;
+
+        let limit = (-chunk).exp();
+        let mut product: f64 = rng.gen();
+        while product > limit {
+            sample += 1;
+            product *= rng.gen::<f64>();
+        }
+    }
+    sample
+}
+
 /// For example:
 /// Work off proto-edges in chunks to keep memory-usage lower.
 /// To keep additional memory-needs below 1 MB, the the maximum amount of four f64-values per
@@ -80,3 +149,31 @@ pub fn init_logging(max_log_level: &str, modules: &[&str]) -> err::Feedback {
     // return
     Ok(())
 }
+
+/// Prints the `tracing`-spans of the major phases (e.g. parsing, building or routing) with their
+/// durations to stderr, whenever a span is closed.
+///
+/// Spans are only emitted, if this crate is compiled with the feature `tracing`, otherwise an
+/// error is returned.
+pub fn init_tracing() -> err::Feedback {
+    #[cfg(feature = "tracing")]
+    {
+        let subscriber = tracing_subscriber::fmt()
+            .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .finish();
+        tracing_lib::subscriber::set_global_default(subscriber).map_err(|e| {
+            err::Msg::from(format!(
+                "Couldn't set the tracing-subscriber due to error: {}",
+                e
+            ))
+        })
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        Err(err::Msg::from(
+            "Tracing needs osmgraphing to be compiled with the feature `tracing`.",
+        ))
+    }
+}
@@ -0,0 +1,93 @@
+use crate::defaults::writing::SCI_NOTATION_THRESHOLD;
+use serde::Serialize;
+use std::io;
+
+/// Formats floats for all writers, independent of the platform and its locale.
+///
+/// - The decimal separator is always `.`.
+/// - With precision, at most this many digits follow the decimal separator, since trailing zeros
+///   are trimmed.
+///   Without precision, the shortest representation, which is parsed back to the same value,
+///   is written.
+/// - Absolute values of at least `defaults::writing::SCI_NOTATION_THRESHOLD` are written in
+///   scientific notation (e.g. `1.5e20`), smaller values never.
+/// - `-0` is written as `0`, also if a negative value is rounded to zero.
+pub fn float(value: f64, precision: Option<usize>) -> String {
+    if !value.is_finite() {
+        return format!("{}", value);
+    }
+
+    let formatted = if value.abs() >= SCI_NOTATION_THRESHOLD {
+        let formatted = match precision {
+            Some(precision) => format!("{:.*e}", precision, value),
+            None => format!("{:e}", value),
+        };
+        match formatted.find('e') {
+            Some(e_pos) => format!("{}{}", trim_zeros(&formatted[..e_pos]), &formatted[e_pos..]),
+            None => formatted,
+        }
+    } else {
+        match precision {
+            Some(precision) => String::from(trim_zeros(&format!("{:.*}", precision, value))),
+            None => format!("{}", value),
+        }
+    };
+
+    if formatted == "-0" {
+        String::from("0")
+    } else {
+        formatted
+    }
+}
+
+fn trim_zeros(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
+
+/// Serializes the given json-value into one line, which is valid yaml as well.
+///
+/// Compact json (`{"a":1}`) is no valid flow-mapping for every yaml-parser, hence separators are
+/// followed by a space (`{"a": 1}`), as usual in yaml.
+pub fn json_line(value: &serde_json::Value) -> String {
+    let mut ser = serde_json::Serializer::with_formatter(Vec::new(), YamlFlowFormatter);
+    value
+        .serialize(&mut ser)
+        .expect("Serializing a json-value into memory shouldn't fail.");
+    String::from_utf8(ser.into_inner()).expect("Serde-json should write valid utf8.")
+}
+
+struct YamlFlowFormatter;
+
+impl serde_json::ser::Formatter for YamlFlowFormatter {
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    configs,
+    helpers::err,
+    io,
+    network::{EdgeGrid, Graph, RoutePair},
+};
+use log::{debug, info, warn};
+use std::{sync::Arc, time::Instant};
+
+/// Everything loaded by `load(...)`.
+pub struct Artifacts {
+    pub graph: Graph,
+    /// The chosen routing-profile, if routing has been configured.
+    pub routing_cfg: Option<configs::routing::Config>,
+    /// The (snapped) route-pairs with their route-counts, if routing has been configured.
+    pub route_pairs: Option<Vec<(RoutePair<i64>, f64)>>,
+    /// The graph's edge-grid, which is cached in the graph as well.
+    pub spatial_index: Option<Arc<EdgeGrid>>,
+}
+
+/// Parses the graph, then its route-pairs (which need the graph for validating the
+/// routing-config and for snapping) and builds the spatial index at last.
+pub fn load(bundle_cfg: configs::bundle::Config) -> err::Result<Artifacts> {
+    info!("START Load artifacts.");
+    let configs::bundle::Config {
+        parsing: parsing_cfg,
+        routing: routing_bundle,
+        is_building_spatial_index,
+    } = bundle_cfg;
+
+    // graph
+
+    let now = Instant::now();
+    let graph = io::network::graph::Parser::parse_and_finalize(parsing_cfg)?;
+    info!(
+        "Parsed graph in {} seconds ({} µs).",
+        now.elapsed().as_secs(),
+        now.elapsed().as_micros(),
+    );
+    debug!("");
+    debug!("{}", graph);
+    debug!("");
+
+    // route-pairs
+
+    let (routing_cfg, route_pairs) = match routing_bundle {
+        Some(routing_bundle) => {
+            let now = Instant::now();
+            let profiles =
+                configs::routing::Profiles::try_from_yaml_for_graph(&routing_bundle.file, &graph)?;
+            let mut routing_cfg = match &routing_bundle.profile {
+                Some(name) => profiles.try_get(name)?,
+                None => profiles.default_profile(),
+            }
+            .clone();
+            routing_cfg.validate(&graph)?;
+            let route_pairs = io::routing::Parser::parse_and_snap(&routing_cfg, &graph)?;
+            info!(
+                "Loaded {} route-pairs in {} seconds ({} µs).",
+                route_pairs.len(),
+                now.elapsed().as_secs(),
+                now.elapsed().as_micros(),
+            );
+            (Some(routing_cfg), Some(route_pairs))
+        }
+        None => (None, None),
+    };
+
+    // spatial index
+
+    let spatial_index = if !is_building_spatial_index {
+        None
+    } else if graph.nodes().count() == 0 {
+        warn!("The graph has no nodes, hence no spatial index is built.");
+        None
+    } else {
+        let now = Instant::now();
+        let spatial_index = graph.edge_grid();
+        info!(
+            "Built spatial index in {} seconds ({} µs).",
+            now.elapsed().as_secs(),
+            now.elapsed().as_micros(),
+        );
+        Some(spatial_index)
+    };
+
+    info!("FINISHED");
+    Ok(Artifacts {
+        graph,
+        routing_cfg,
+        route_pairs,
+        spatial_index,
+    })
+}
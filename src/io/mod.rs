@@ -1,25 +1,53 @@
 use crate::helpers::err;
 use std::path::Path;
 
+pub mod fmt;
+mod loading;
+pub use loading::{load, Artifacts};
 mod parsing;
 mod writing;
 
+#[cfg(feature = "gpl")]
+pub mod balancing {
+    pub use crate::io::parsing::balancing::{BaseManifest, DeltaKey, DeltaManifest, Reader};
+    pub use crate::io::writing::balancing::Writer;
+}
+pub mod elevation {
+    pub use crate::io::parsing::elevation::SrtmReader;
+}
+pub mod files {
+    pub use crate::io::writing::files::{
+        check_new_file, check_writable_dir, remove_tmp_dirs, tmp_dir_of, AtomicDir, AtomicFile,
+    };
+}
+pub mod osm {
+    pub use crate::io::writing::osm::Writer;
+}
 pub mod smarts {
     pub use crate::io::writing::smarts::Writer;
 }
 pub mod network {
     pub mod graph {
-        pub use crate::io::parsing::network::graph::Parser;
+        pub use crate::io::parsing::network::graph::{ParseStats, Parser, WayCounts, WayScan};
         pub use crate::io::writing::network::graph::Writer;
     }
     pub mod edges {
         pub use crate::io::parsing::network::edges::Parser;
         pub use crate::io::writing::network::edges::Writer;
     }
+    pub mod simple_fmi {
+        pub use crate::io::writing::network::simple_fmi::Writer;
+    }
+    pub mod geojson {
+        pub use crate::io::writing::network::geojson::Writer;
+    }
 }
 pub mod routing {
-    pub use crate::io::parsing::routing::Parser;
+    pub use crate::io::parsing::routing::{Error, Parser, Stream};
     pub use crate::io::writing::routing::Writer;
+    pub mod cost_profile {
+        pub use crate::io::writing::routing::cost_profile::Writer;
+    }
 }
 #[cfg(feature = "gpl")]
 pub mod evaluating_balance {
@@ -85,15 +113,17 @@ pub trait SupportingFileExts {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MapFileExt {
     PBF,
     FMI,
+    OSM,
 }
 
 impl SupportingMapFileExts for MapFileExt {}
 impl SupportingFileExts for MapFileExt {
     fn supported_exts<'a>() -> &'a [&'a str] {
-        &["osm.pbf", "pbf", "fmi"]
+        &["osm.pbf", "pbf", "fmi", "osm"]
     }
 }
 
@@ -102,6 +132,7 @@ pub trait SupportingMapFileExts: SupportingFileExts {
         match Self::find_supported_ext(path)? {
             "osm.pbf" | "pbf" => Ok(MapFileExt::PBF),
             "fmi" => Ok(MapFileExt::FMI),
+            "osm" => Ok(MapFileExt::OSM),
             _ => Err(err::Msg::from(
                 "Should not happen, since 'find_supported_ext(...)' should cover this.",
             )),
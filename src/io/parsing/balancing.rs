@@ -0,0 +1,371 @@
+use crate::{
+    configs::{self, SimpleId},
+    defaults::balancing::{files, BASE_ITER, CHECKSUM_CHUNK_SIZE},
+    helpers::{self, err},
+    io,
+    network::Graph,
+};
+use log::info;
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+/// Describes the base-graph of delta-results, which is written in iteration 0.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaseManifest {
+    /// checksum of the base-graph's map-files (see `checksum(...)`)
+    pub checksum: String,
+    #[serde(rename = "edge-count")]
+    pub edge_count: usize,
+}
+
+/// Describes an iteration's delta, which is written instead of its full graph.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeltaManifest {
+    /// relative to the results-dir
+    #[serde(rename = "base-dir")]
+    pub base_dir: PathBuf,
+    pub base: BaseManifest,
+    #[serde(rename = "metric-id")]
+    pub metric_id: SimpleId,
+    /// relative to the iteration's dir
+    #[serde(rename = "delta-file")]
+    pub delta_file: PathBuf,
+}
+
+/// Delta-values are keyed by edge-ids, if all edges (except shortcuts) have ids, and by stable
+/// ids (see `Graph::stable_ids()`) otherwise.
+/// Both are independent of edge-indices, hence the base's shortcuts and edge-order may differ
+/// from the iteration's ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeltaKey {
+    EdgeId,
+    StableId,
+}
+
+impl DeltaKey {
+    pub fn of(graph: &Graph) -> DeltaKey {
+        let fwd_edges = graph.fwd_edges();
+        let is_having_ids = fwd_edges
+            .iter()
+            .filter(|&idx| !fwd_edges.is_shortcut(idx))
+            .all(|idx| fwd_edges.try_id(idx).is_some());
+        if fwd_edges.count() > 0 && is_having_ids {
+            DeltaKey::EdgeId
+        } else {
+            DeltaKey::StableId
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeltaKey::EdgeId => "edge-id",
+            DeltaKey::StableId => "stable-id",
+        }
+    }
+
+    fn try_from_name(name: &str) -> err::Result<DeltaKey> {
+        match name {
+            "edge-id" => Ok(DeltaKey::EdgeId),
+            "stable-id" => Ok(DeltaKey::StableId),
+            _ => Err(format!(
+                "The delta's key {} is unknown, expected 'edge-id' or 'stable-id'.",
+                name
+            )
+            .into()),
+        }
+    }
+}
+
+pub struct Reader;
+
+impl Reader {
+    pub fn iter_dir<P: AsRef<Path> + ?Sized>(iter: usize, results_dir: &P) -> PathBuf {
+        results_dir.as_ref().join(format!("{}", iter))
+    }
+
+    /// Reads the parsing-config of the iteration's config, whose files are stored relative to the
+    /// iteration's dir.
+    pub fn try_parsing_cfg_of<P: AsRef<Path> + ?Sized>(
+        iter_dir: &P,
+    ) -> err::Result<configs::parsing::Config> {
+        let iter_dir = iter_dir.as_ref();
+        let mut parsing_cfg =
+            configs::parsing::Config::try_from_yaml(&iter_dir.join(files::ITERATION_CFG))?;
+
+        // map-files are stored relative to results-dir
+        parsing_cfg.map_files = parsing_cfg
+            .map_files
+            .iter()
+            .map(|map_file| iter_dir.join(map_file))
+            .collect();
+
+        // same holds for edges-info.csv
+        // -> update all paths to important map- or data-files
+
+        if let Some(gen_cfg) = parsing_cfg.generating.as_mut() {
+            for category in gen_cfg.edges.categories.iter_mut() {
+                match category {
+                    configs::parsing::generating::edges::Category::Merge {
+                        from,
+                        is_file_with_header: _,
+                        edge_id: _,
+                        edges_info: _,
+                    } => *from = iter_dir.join(&from),
+                    configs::parsing::generating::edges::Category::Meta { info: _, id: _ }
+                    | configs::parsing::generating::edges::Category::Custom {
+                        unit: _,
+                        id: _,
+                        default: _,
+                    }
+                    | configs::parsing::generating::edges::Category::Haversine { unit: _, id: _ }
+                    | configs::parsing::generating::edges::Category::Copy { from: _, to: _ }
+                    | configs::parsing::generating::edges::Category::Convert { from: _, to: _ }
+                    | configs::parsing::generating::edges::Category::Calc {
+                        result: _,
+                        a: _,
+                        b: _,
+                        is_directional: _,
+                        gradient: _,
+                    } => {
+                        // no file to update
+                    }
+                }
+            }
+        }
+
+        Ok(parsing_cfg)
+    }
+
+    /// Reconstructs the graph of the given iteration from the results-dir.
+    ///
+    /// With full results, the iteration's graph is parsed directly.
+    /// With delta-results, the base-graph of iteration 0 is parsed and checked against the
+    /// delta's manifest (edge-count and checksum).
+    /// Then, the optimized metric is replaced by the delta's values.
+    /// Shortcuts are the base's, hence their values are recomputed as the sum of their
+    /// sc-edges' values.
+    pub fn try_reconstruct<P: AsRef<Path> + ?Sized>(
+        iter: usize,
+        results_dir: &P,
+    ) -> err::Result<Graph> {
+        let results_dir = results_dir.as_ref();
+        let iter_dir = Reader::iter_dir(iter, results_dir);
+        let manifest_file = iter_dir.join(files::DELTA_MANIFEST);
+        if iter == BASE_ITER || !manifest_file.exists() {
+            let parsing_cfg = Reader::try_parsing_cfg_of(&iter_dir)?;
+            return io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+        }
+
+        info!("START Reconstruct graph of iteration {}.", iter);
+        let manifest: DeltaManifest = read_yaml(&manifest_file)?;
+
+        // parse and check base
+
+        let base_dir = results_dir.join(&manifest.base_dir);
+        let parsing_cfg = Reader::try_parsing_cfg_of(&base_dir)?;
+        let base_checksum = checksum(&parsing_cfg.map_files)?;
+        if base_checksum != manifest.base.checksum {
+            return Err(format!(
+                "The base-graph in {} has checksum {}, but the delta of iteration {} expects {}.",
+                base_dir.display(),
+                base_checksum,
+                iter,
+                manifest.base.checksum
+            )
+            .into());
+        }
+        let mut graph = io::network::graph::Parser::parse_and_finalize(parsing_cfg)?;
+        if graph.fwd_edges().count() != manifest.base.edge_count {
+            return Err(format!(
+                "The base-graph in {} has {} edges, but the delta of iteration {} expects {}.",
+                base_dir.display(),
+                graph.fwd_edges().count(),
+                iter,
+                manifest.base.edge_count
+            )
+            .into());
+        }
+
+        // replay delta
+
+        let metric_idx = graph.cfg().edges.metrics.try_idx_of(&manifest.metric_id)?;
+        let mut column = graph.metrics().column(metric_idx).to_vec();
+        let mut is_set = vec![false; column.len()];
+        let delta_file = iter_dir.join(&manifest.delta_file);
+        let key = {
+            let fwd_edges = graph.fwd_edges();
+            let mut stable_id_index = None;
+            let mut key = None;
+            for line in functional_lines(&delta_file)? {
+                let line = line?;
+                let params: Vec<&str> = line.split_whitespace().collect();
+                if params.len() != 2 {
+                    return Err(format!(
+                        "A line of {} is expected to consist of (key, value), \
+                         but {} values are provided.",
+                        delta_file.display(),
+                        params.len()
+                    )
+                    .into());
+                }
+
+                // header
+                let key = match key {
+                    Some(key) => key,
+                    None => {
+                        if params[1] != manifest.metric_id.0 {
+                            return Err(format!(
+                                "The delta {} contains metric {}, but the manifest expects {}.",
+                                delta_file.display(),
+                                params[1],
+                                manifest.metric_id
+                            )
+                            .into());
+                        }
+                        key = Some(DeltaKey::try_from_name(params[0])?);
+                        continue;
+                    }
+                };
+
+                let edge_idx = match key {
+                    DeltaKey::EdgeId => fwd_edges.try_idx_from(parse_param(params[0])?)?,
+                    DeltaKey::StableId => {
+                        let stable_id: u64 = parse_param(params[0])?;
+                        let stable_id_index =
+                            stable_id_index.get_or_insert_with(|| graph.stable_id_index());
+                        match stable_id_index.get(&stable_id) {
+                            Some(&edge_idx) => edge_idx,
+                            None => {
+                                return Err(format!(
+                                    "The delta's stable id {} is not in the base-graph.",
+                                    stable_id
+                                )
+                                .into())
+                            }
+                        }
+                    }
+                };
+                column[*edge_idx] = parse_param(params[1])?;
+                is_set[*edge_idx] = true;
+            }
+            key.ok_or(err::Msg::from(format!(
+                "The delta {} has no header.",
+                delta_file.display()
+            )))?
+        };
+
+        // every edge, which isn't a shortcut, should be given
+        {
+            let fwd_edges = graph.fwd_edges();
+            if let Some(edge_idx) = fwd_edges
+                .iter()
+                .find(|&idx| !is_set[*idx] && !fwd_edges.is_shortcut(idx))
+            {
+                return Err(format!(
+                    "The delta {} (keyed by {}) misses the edge of idx {}.",
+                    delta_file.display(),
+                    key.name(),
+                    edge_idx
+                )
+                .into());
+            }
+        }
+        graph
+            .metrics_mut()
+            .column_mut(metric_idx)
+            .copy_from_slice(&column);
+        graph.recompute_shortcuts(metric_idx);
+
+        info!("FINISHED");
+        Ok(graph)
+    }
+
+    pub fn reconstruct<P: AsRef<Path> + ?Sized>(iter: usize, results_dir: &P) -> Graph {
+        match Reader::try_reconstruct(iter, results_dir) {
+            Ok(graph) => graph,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+/// FNV-1a (64 bit) over the bytes of all given files, which is enough to detect a changed or
+/// replaced base-graph (but not meant to be cryptographically secure).
+///
+/// The files are streamed in chunks, so large map-files aren't loaded into memory.
+pub fn checksum<P: AsRef<Path>>(files: &[P]) -> err::Result<String> {
+    let mut hasher = helpers::Fnv1a::new();
+    for file in files {
+        let file = file.as_ref();
+        let mut reader = match OpenOptions::new().read(true).open(file) {
+            Ok(reader) => BufReader::with_capacity(CHECKSUM_CHUNK_SIZE, reader),
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", file.display(), e).into())
+            }
+        };
+        loop {
+            let chunk_len = match reader.fill_buf() {
+                Ok(chunk) if chunk.is_empty() => break,
+                Ok(chunk) => {
+                    hasher.write(chunk);
+                    chunk.len()
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    return Err(
+                        format!("Couldn't read {} due to error: {}", file.display(), e).into(),
+                    )
+                }
+            };
+            reader.consume(chunk_len);
+        }
+    }
+    Ok(format!("fnv1a64:{:016x}", hasher.finish()))
+}
+
+pub fn read_yaml<T, P>(file: &P) -> err::Result<T>
+where
+    T: serde::de::DeserializeOwned,
+    P: AsRef<Path> + ?Sized,
+{
+    let file = file.as_ref();
+    let reader = match OpenOptions::new().read(true).open(file) {
+        Ok(reader) => reader,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", file.display(), e).into())
+        }
+    };
+    match serde_yaml::from_reader(reader) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(format!("Serde couldn't read {} due to error: {}", file.display(), e).into()),
+    }
+}
+
+/// Streams the functional lines of the given file (see `helpers::is_line_functional(...)`).
+fn functional_lines(file: &Path) -> err::Result<impl Iterator<Item = err::Result<String>>> {
+    let reader = match OpenOptions::new().read(true).open(file) {
+        Ok(reader) => reader,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", file.display(), e).into())
+        }
+    };
+    Ok(BufReader::new(reader)
+        .lines()
+        .map(|line| line.map_err(err::Msg::from))
+        .filter(|line| match line {
+            Ok(line) => helpers::is_line_functional(line),
+            Err(_) => true,
+        }))
+}
+
+fn parse_param<T: std::str::FromStr>(param: &str) -> err::Result<T> {
+    param
+        .parse::<T>()
+        .ok()
+        .ok_or(format!("Could not parse delta-value {}", param).into())
+}
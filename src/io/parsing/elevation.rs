@@ -0,0 +1,213 @@
+use crate::{defaults, helpers::err};
+use kissunits::geo::Coordinate;
+use std::{
+    collections::VecDeque,
+    fs::OpenOptions,
+    io::{ErrorKind, Read},
+    path::{Path, PathBuf},
+};
+
+/// Samples of SRTM-tiles with this value are voids, which don't have any elevation.
+const VOID: i16 = -32768;
+/// Coordinates closer to a tile-border (in degrees) are considered on the border, which is far
+/// below the samples' spacing.
+const BORDER_TOLERANCE: f64 = 1e-9;
+
+/// Reads elevations from SRTM-tiles (`*.hgt`) in a directory.
+///
+/// A tile covers one degree of latitude and longitude and is named by its south-west corner,
+/// e.g. `N48E009.hgt` covers latitudes `[48, 49]` and longitudes `[9, 10]`.
+/// It consists of `n x n` big-endian `i16`-samples in meters (usually `n = 1201` or `n = 3601`),
+/// ordered row by row from north to south, where neighbouring tiles share their border-samples.
+///
+/// Elevations are interpolated bilinearly between the four surrounding samples.
+/// Coordinates on a tile-border are covered by both tiles, hence they are read from the
+/// neighbouring tile, if the first one is missing.
+///
+/// The most recently used tiles are kept in memory (see `with_capacity(...)`), including the
+/// knowledge of missing tiles.
+pub struct SrtmReader {
+    dir: PathBuf,
+    capacity: usize,
+    /// most recently used tiles first, where `None` marks a missing tile
+    tiles: VecDeque<((i32, i32), Option<Tile>)>,
+}
+
+impl SrtmReader {
+    pub fn new<P: AsRef<Path> + ?Sized>(dir: &P) -> SrtmReader {
+        SrtmReader::with_capacity(dir, defaults::parsing::nodes::SRTM_OPEN_TILES)
+    }
+
+    /// Keeps at most `capacity` (but at least one) tiles in memory.
+    pub fn with_capacity<P: AsRef<Path> + ?Sized>(dir: &P, capacity: usize) -> SrtmReader {
+        let capacity = capacity.max(1);
+        SrtmReader {
+            dir: dir.as_ref().to_path_buf(),
+            capacity,
+            tiles: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the file-name of the tile, whose south-west corner is at the given degrees,
+    /// e.g. `S05W001.hgt` for `(-5, -1)`.
+    pub fn tile_name(lat: i32, lon: i32) -> String {
+        format!(
+            "{}{:02}{}{:03}.hgt",
+            if lat < 0 { 'S' } else { 'N' },
+            lat.abs(),
+            if lon < 0 { 'W' } else { 'E' },
+            lon.abs()
+        )
+    }
+
+    /// Returns the tile-names covering the given coordinate, where the first one is preferred.
+    /// Coordinates on tile-borders are covered by multiple tiles.
+    pub fn tile_names_of(coord: &Coordinate) -> Vec<String> {
+        SrtmReader::tile_keys_of(coord)
+            .into_iter()
+            .map(|(lat, lon)| SrtmReader::tile_name(lat, lon))
+            .collect()
+    }
+
+    /// Returns the interpolated elevation in meters, or `None`, if no tile covers the coordinate
+    /// or if the surrounding samples are voids.
+    ///
+    /// Tiles, which exist but can't be read, are an error.
+    pub fn elevation(&mut self, coord: &Coordinate) -> err::Result<Option<f64>> {
+        for (lat, lon) in SrtmReader::tile_keys_of(coord) {
+            if let Some(tile) = self.tile((lat, lon))? {
+                // relative to the tile's north-west corner, in samples
+                let row = ((lat + 1) as f64 - coord.lat) * (tile.size - 1) as f64;
+                let col = (coord.lon - lon as f64) * (tile.size - 1) as f64;
+                return Ok(tile.interpolate(row, col));
+            }
+        }
+        Ok(None)
+    }
+
+    fn tile_keys_of(coord: &Coordinate) -> Vec<(i32, i32)> {
+        let lats = SrtmReader::degrees_of(coord.lat);
+        let lons = SrtmReader::degrees_of(coord.lon);
+        lats.iter()
+            .flat_map(|&lat| lons.iter().map(move |&lon| (lat, lon)))
+            .collect()
+    }
+
+    /// Returns the degrees of the tiles' south-west corners covering the given value.
+    ///
+    /// Values, which are nearly integral, are on a tile-border, since coordinates of pbf-files
+    /// are converted from integers and hence may be slightly off.
+    fn degrees_of(value: f64) -> Vec<i32> {
+        let rounded = value.round();
+        if (value - rounded).abs() < BORDER_TOLERANCE {
+            vec![rounded as i32, rounded as i32 - 1]
+        } else {
+            vec![value.floor() as i32]
+        }
+    }
+
+    fn tile(&mut self, key: (i32, i32)) -> err::Result<Option<&Tile>> {
+        match self.tiles.iter().position(|(tile_key, _)| tile_key == &key) {
+            Some(idx) => {
+                let entry = self
+                    .tiles
+                    .remove(idx)
+                    .expect("Index of cached tile should be valid.");
+                self.tiles.push_front(entry);
+            }
+            None => {
+                let path = self.dir.join(SrtmReader::tile_name(key.0, key.1));
+                let tile = Tile::try_from_file(&path)?;
+                if self.tiles.len() >= self.capacity {
+                    self.tiles.pop_back();
+                }
+                self.tiles.push_front((key, tile));
+            }
+        }
+        Ok(self.tiles.front().and_then(|(_, tile)| tile.as_ref()))
+    }
+}
+
+struct Tile {
+    /// number of samples per row and column
+    size: usize,
+    samples: Vec<i16>,
+}
+
+impl Tile {
+    /// Returns `None`, if the file doesn't exist.
+    fn try_from_file(path: &Path) -> err::Result<Option<Tile>> {
+        let mut file = match OpenOptions::new().read(true).open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+            }
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|e| format!("Couldn't read {} due to error: {}", path.display(), e))?;
+
+        let size = ((bytes.len() / 2) as f64).sqrt().round() as usize;
+        if size < 2 || 2 * size * size != bytes.len() {
+            return Err(format!(
+                "The SRTM-tile {} should consist of n x n samples (n >= 2) of 2 bytes, \
+                 but has {} bytes.",
+                path.display(),
+                bytes.len()
+            )
+            .into());
+        }
+        let samples = bytes
+            .chunks_exact(2)
+            .map(|sample| i16::from_be_bytes([sample[0], sample[1]]))
+            .collect();
+
+        Ok(Some(Tile { size, samples }))
+    }
+
+    fn sample(&self, row: usize, col: usize) -> Option<f64> {
+        match self.samples[row * self.size + col] {
+            VOID => None,
+            sample => Some(sample as f64),
+        }
+    }
+
+    /// Interpolates bilinearly between the samples around `(row, col)`, where voids are skipped
+    /// by re-weighting the remaining samples.
+    fn interpolate(&self, row: f64, col: f64) -> Option<f64> {
+        let max_idx = self.size - 1;
+        let row = row.max(0.0).min(max_idx as f64);
+        let col = col.max(0.0).min(max_idx as f64);
+        let (row_0, col_0) = (row.floor() as usize, col.floor() as usize);
+        let (row_1, col_1) = ((row_0 + 1).min(max_idx), (col_0 + 1).min(max_idx));
+        let (row_frac, col_frac) = (row - row_0 as f64, col - col_0 as f64);
+
+        let mut weighted_sum = 0.0;
+        let mut weight_sum = 0.0;
+        for &(row, col, weight) in &[
+            (row_0, col_0, (1.0 - row_frac) * (1.0 - col_frac)),
+            (row_0, col_1, (1.0 - row_frac) * col_frac),
+            (row_1, col_0, row_frac * (1.0 - col_frac)),
+            (row_1, col_1, row_frac * col_frac),
+        ] {
+            if weight <= 0.0 {
+                continue;
+            }
+            if let Some(sample) = self.sample(row, col) {
+                weighted_sum += weight * sample;
+                weight_sum += weight;
+            }
+        }
+
+        if weight_sum > 0.0 {
+            Some(weighted_sum / weight_sum)
+        } else {
+            None
+        }
+    }
+}
@@ -1,2 +1,5 @@
+#[cfg(feature = "gpl")]
+pub mod balancing;
+pub mod elevation;
 pub mod network;
 pub mod routing;
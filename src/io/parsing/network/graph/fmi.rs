@@ -8,16 +8,29 @@ use crate::{
     network::{EdgeBuilder, EdgeIdx, NodeBuilder, ProtoEdge, ProtoNode, ProtoShortcut},
 };
 use kissunits::geo;
-use log::info;
+use log::{info, warn};
+use serde::Deserialize;
 use std::{
-    fs::OpenOptions,
-    io::{BufRead, BufReader},
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Seek, SeekFrom},
     ops::Range,
+    path::{Path, PathBuf},
+    str,
 };
 
+/// Excel and other editors prefix UTF-8-files with this byte-order-mark.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// The fmi-file is streamed without remembering proto-nodes.
+/// Since nodes precede edges in fmi-files, but the graph-builder expects edges first,
+/// the byte-offset of every file's node-section is remembered when preprocessing.
+/// Parsing edges skips the node-lines unparsed, and parsing nodes seeks to the node-section
+/// and stops at its end, hence every line is parsed only once.
 pub struct Parser {
     node_lines: Range<usize>,
     edge_lines: Range<usize>,
+    node_sections: BTreeMap<PathBuf, NodeSection>,
 }
 
 impl Parser {
@@ -25,30 +38,58 @@ impl Parser {
         Parser {
             node_lines: 1..0,
             edge_lines: 1..0,
+            node_sections: BTreeMap::new(),
         }
     }
 }
 
+/// Where a file's node-lines start, to seek there instead of reading the header again.
+struct NodeSection {
+    byte_offset: usize,
+    /// of the first node-line, starting with 1
+    line_number: usize,
+    count: usize,
+}
+
 impl super::Parsing for Parser {
     /// Remembers range of edge-lines and node-lines
-    fn preprocess(&mut self, cfg: &parsing::Config) -> err::Feedback {
+    fn preprocess(&mut self, cfg: &parsing::Config, map_file: &Path) -> err::Feedback {
         info!("START Start preprocessing fmi-parser.");
         super::check_config(cfg)?;
+        if cfg.bbox.is_some() {
+            return Err(err::Msg::from(
+                "A bounding-box is only supported for pbf- and osm-files, \
+                 since fmi-files are extracts already.",
+            ));
+        }
+        if let Some(elevation) = &cfg.elevation {
+            warn!(
+                "Elevations are read from the fmi-file's column {}, \
+                 hence ele-tags and srtm-tiles are ignored.",
+                elevation.id
+            );
+        }
 
         // only functional-lines are counted
-        let mut line_number = 0;
+        let mut functional_line_number = 0;
         let mut is_taking_counts = false;
         // counts are only metric-count, node-count, edge-count (in this order)
         let mut counts = vec![];
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&cfg.map_file)
-            .expect(&format!("Couldn't open {}", cfg.map_file.display()));
-        for line in BufReader::new(file)
-            .lines()
-            .map(Result::unwrap)
-            .filter(helpers::is_line_functional)
-        {
+        // config, which has been embedded by osmgraphing's fmi-writer
+        let mut embedded_cfg = None;
+        // (byte-offset, line-number) of the first node-line
+        let mut node_section_start = None;
+        let mut reader = LineReader::open(map_file, cfg.is_lossy_utf8)?;
+        while let Some((line_number, line)) = reader.next_line()? {
+            if line.starts_with(defaults::parsing::EMBEDDED_CFG_PREFIX) {
+                embedded_cfg =
+                    Some(line[defaults::parsing::EMBEDDED_CFG_PREFIX.len()..].to_owned());
+                continue;
+            }
+            if !helpers::is_line_functional(line) {
+                continue;
+            }
+
             // If there is a count, remember it.
             // The first occuring count let `is_taking_counts` getting true.
             // If all counts have been processed, `is_taking_counts` would change to false,
@@ -61,10 +102,23 @@ impl super::Parsing for Parser {
                     counts.push(count);
                 }
             } else if is_taking_counts {
+                node_section_start = Some((reader.byte_offset, line_number));
                 break;
             }
 
-            line_number += 1;
+            functional_line_number += 1;
+        }
+        // without any node- or edge-line, the sections start at the file's end
+        let (byte_offset, first_line_number) =
+            node_section_start.unwrap_or((reader.next_byte_offset, reader.line_number + 1));
+
+        // check embedded config
+        if let Some(embedded_cfg) = embedded_cfg {
+            if cfg.is_ignoring_embedded_cfg {
+                warn!("The fmi-map-file's embedded config is ignored as requested.");
+            } else {
+                check_embedded_cfg(&embedded_cfg, cfg)?;
+            }
         }
 
         // add counts
@@ -81,9 +135,17 @@ impl super::Parsing for Parser {
         let node_count = counts.pop().expect("Expect counts.len() >= 2.");
 
         // nodes
-        let start = line_number;
+        let start = functional_line_number;
         let end = start + node_count;
         self.node_lines = start..end;
+        self.node_sections.insert(
+            map_file.to_path_buf(),
+            NodeSection {
+                byte_offset,
+                line_number: first_line_number,
+                count: node_count,
+            },
+        );
 
         // edges
         let start = end;
@@ -94,62 +156,162 @@ impl super::Parsing for Parser {
         Ok(())
     }
 
-    fn parse_ways(&self, builder: &mut EdgeBuilder) -> err::Feedback {
-        info!("START Create edges from input-file.");
-        let mut line_number = 0;
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&builder.cfg().map_file)
-            .expect(&format!(
-                "Couldn't open {}",
-                builder.cfg().map_file.display()
-            ));
-        for line in BufReader::new(file)
-            .lines()
-            .map(Result::unwrap)
-            .filter(helpers::is_line_functional)
-        {
-            // check if line contains edge
-            if !self.edge_lines.contains(&line_number) {
-                line_number += 1;
+    /// Parses edges, beginning at the node-section, whose lines are only counted, not parsed.
+    fn parse_ways(&mut self, map_file: &Path, builder: &mut EdgeBuilder) -> err::Feedback {
+        info!("START Create edges from input-file {}.", map_file.display());
+        crate::trace_scope!(
+            "fmi::ways",
+            map_file = %map_file.display(),
+            node_lines = self.node_lines.len()
+        );
+        let node_section = self.node_section(map_file)?;
+
+        // only functional-lines are counted, and the header has been counted when preprocessing
+        let mut functional_line_number = self.node_lines.start;
+        let mut last_line_number = 0;
+        // consumed lines are compared to the declared counts in the end
+        let mut node_count = 0;
+        let mut edge_count = 0;
+        // (line-number of first surplus line, count of surplus lines)
+        let mut surplus_lines: Option<(usize, usize)> = None;
+        let mut reader = LineReader::open_at(
+            map_file,
+            builder.cfg().is_lossy_utf8,
+            node_section.byte_offset,
+            node_section.line_number,
+        )?;
+        while let Some((line_number, line)) = reader.next_line()? {
+            if !helpers::is_line_functional(line) {
                 continue;
             }
-            line_number += 1;
+            last_line_number = line_number;
+
+            if self.node_lines.contains(&functional_line_number) {
+                // parsed in parse_nodes(...)
+                node_count += 1;
+            } else if self.edge_lines.contains(&functional_line_number) {
+                let (proto_edge, is_bidirectional) =
+                    ProtoShortcut::try_from_str(line, &builder.cfg().edges.categories).map_err(
+                        |msg| {
+                            // a node-line at the edges' beginning hints at a too low node-count
+                            if functional_line_number == self.edge_lines.start {
+                                format!(
+                                    "Line {}: {} This is the first edge-line after the {} \
+                                     declared nodes, hence the node-count could be too low.",
+                                    line_number,
+                                    msg,
+                                    self.node_lines.len()
+                                )
+                            } else {
+                                format!("Line {}: {}", line_number, msg)
+                            }
+                        },
+                    )?;
+                let reversed_edge = if is_bidirectional {
+                    Some(proto_edge.reversed())
+                } else {
+                    None
+                };
+                builder.insert(proto_edge)?;
+                if let Some(reversed_edge) = reversed_edge {
+                    builder.insert(reversed_edge)?;
+                }
+                edge_count += 1;
+            } else if functional_line_number >= self.edge_lines.end && !line.trim().is_empty() {
+                // counted to report all of them, but trailing whitespace is fine
+                match &mut surplus_lines {
+                    Some((_, count)) => *count += 1,
+                    None => surplus_lines = Some((line_number, 1)),
+                }
+            }
 
-            // create edge and add it
-            let proto_edge = ProtoShortcut::try_from_str(&line, &builder.cfg().edges.categories)?;
-            builder.insert(proto_edge)?;
+            functional_line_number += 1;
+        }
+
+        // The declared ranges would shift silently, if a count doesn't match the file.
+        if node_count < self.node_lines.len() {
+            return Err(format!(
+                "The fmi-map-file {} declares {} nodes, but has only {} node-lines, \
+                 ending with line {}.",
+                map_file.display(),
+                self.node_lines.len(),
+                node_count,
+                last_line_number
+            )
+            .into());
+        }
+        if edge_count < self.edge_lines.len() {
+            // Edge-lines, which have been parsed as nodes, are missing as well.
+            return Err(format!(
+                "The fmi-map-file {} declares {} edges, but has only {} edge-lines, \
+                 ending with line {}. \
+                 If all edges are there, the node-count {} is too high.",
+                map_file.display(),
+                self.edge_lines.len(),
+                edge_count,
+                last_line_number,
+                self.node_lines.len()
+            )
+            .into());
+        }
+        if let Some((line_number, count)) = surplus_lines {
+            return Err(format!(
+                "The fmi-map-file {} declares {} nodes and {} edges, \
+                 but has {} more functional lines, beginning with line {}.",
+                map_file.display(),
+                self.node_lines.len(),
+                self.edge_lines.len(),
+                count,
+                line_number
+            )
+            .into());
         }
         info!("FINISHED");
 
         Ok(())
     }
 
-    fn parse_nodes(&self, builder: &mut NodeBuilder) -> err::Feedback {
-        info!("START Create nodes from input-file.");
-        let mut line_number = 0;
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&builder.cfg().map_file)
-            .expect(&format!(
-                "Couldn't open {}",
-                builder.cfg().map_file.display()
-            ));
-        for line in BufReader::new(file)
-            .lines()
-            .map(Result::unwrap)
-            .filter(helpers::is_line_functional)
-        {
-            // check if line contains edge
-            if !self.node_lines.contains(&line_number) {
-                line_number += 1;
+    /// Seeks to the node-section of the map-file and stops at its end, so edges aren't read again.
+    fn parse_nodes(&mut self, map_file: &Path, builder: &mut NodeBuilder) -> err::Feedback {
+        info!("START Create nodes from input-file {}.", map_file.display());
+        let node_section = self.node_section(map_file)?;
+        crate::trace_scope!("fmi::nodes", nodes = node_section.count);
+
+        // the node-count is checked when parsing edges, but the file could have changed since then
+        let mut node_count = 0;
+        let mut last_line_number = node_section.line_number;
+        let mut reader = LineReader::open_at(
+            map_file,
+            builder.cfg().is_lossy_utf8,
+            node_section.byte_offset,
+            node_section.line_number,
+        )?;
+        while node_count < node_section.count {
+            let (line_number, line) = match reader.next_line()? {
+                Some(numbered_line) => numbered_line,
+                None => break,
+            };
+            if !helpers::is_line_functional(line) {
                 continue;
             }
-            line_number += 1;
 
-            // create node and add it
-            let proto_node = ProtoNode::from_str(&line, &builder.cfg().nodes)?;
+            last_line_number = line_number;
+
+            let proto_node = ProtoNode::from_str(line, &builder.cfg().nodes)
+                .map_err(|msg| format!("Line {}: {}", line_number, msg))?;
             builder.insert(proto_node);
+            node_count += 1;
+        }
+        if node_count < node_section.count {
+            return Err(format!(
+                "The fmi-map-file {} declares {} nodes, but has only {} node-lines, \
+                 ending with line {}.",
+                map_file.display(),
+                node_section.count,
+                node_count,
+                last_line_number
+            )
+            .into());
         }
         info!("FINISHED");
 
@@ -157,20 +319,263 @@ impl super::Parsing for Parser {
     }
 }
 
+impl Parser {
+    fn node_section(&self, map_file: &Path) -> err::Result<&NodeSection> {
+        self.node_sections.get(map_file).ok_or_else(|| {
+            format!(
+                "The fmi-map-file {} has to be preprocessed before parsing it.",
+                map_file.display()
+            )
+            .into()
+        })
+    }
+}
+
+/// Reads lines into one reused buffer instead of allocating a new `String` per line.
+///
+/// A leading UTF-8 byte-order-mark (e.g. from Excel) is skipped.
+/// Invalid UTF-8 is an error, or replaced by replacement-characters (`U+FFFD`), if lossy.
+struct LineReader {
+    path: PathBuf,
+    reader: BufReader<File>,
+    buf: Vec<u8>,
+    /// only used for lossy lines, which have been repaired
+    lossy_buf: String,
+    is_lossy: bool,
+    line_number: usize,
+    /// of the current line's first byte in the file
+    byte_offset: usize,
+    /// of the next line's first byte in the file
+    next_byte_offset: usize,
+}
+
+impl LineReader {
+    fn open(path: &Path, is_lossy: bool) -> err::Result<LineReader> {
+        LineReader::open_at(path, is_lossy, 0, 1)
+    }
+
+    /// Seeks to the given byte-offset, which has to be the beginning of the given line.
+    fn open_at(
+        path: &Path,
+        is_lossy: bool,
+        byte_offset: usize,
+        line_number: usize,
+    ) -> err::Result<LineReader> {
+        let mut file = match OpenOptions::new().read(true).open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+            }
+        };
+        if byte_offset > 0 {
+            file.seek(SeekFrom::Start(byte_offset as u64))?;
+        }
+        Ok(LineReader {
+            path: path.to_path_buf(),
+            reader: BufReader::new(file),
+            buf: Vec::new(),
+            lossy_buf: String::new(),
+            is_lossy,
+            line_number: line_number - 1,
+            byte_offset,
+            next_byte_offset: byte_offset,
+        })
+    }
+
+    /// Returns the next line (without line-break) and its number, starting with 1.
+    /// Returns `None` if the file has been read completely.
+    fn next_line(&mut self) -> err::Result<Option<(usize, &str)>> {
+        self.buf.clear();
+        let num_of_bytes = self.reader.read_until(b'\n', &mut self.buf)?;
+        if num_of_bytes == 0 {
+            return Ok(None);
+        }
+        self.line_number += 1;
+        self.byte_offset = self.next_byte_offset;
+        self.next_byte_offset += num_of_bytes;
+
+        while self.buf.last() == Some(&b'\n') || self.buf.last() == Some(&b'\r') {
+            self.buf.pop();
+        }
+
+        let bom_len = if self.line_number == 1 && self.buf.starts_with(UTF8_BOM) {
+            UTF8_BOM.len()
+        } else {
+            0
+        };
+        let bytes = &self.buf[bom_len..];
+
+        match str::from_utf8(bytes) {
+            Ok(line) => Ok(Some((self.line_number, line))),
+            Err(_) if self.is_lossy => {
+                self.lossy_buf = String::from_utf8_lossy(bytes).into_owned();
+                Ok(Some((self.line_number, &self.lossy_buf)))
+            }
+            Err(e) => Err(format!(
+                "Line {} of the fmi-map-file {} is not valid UTF-8, \
+                 beginning with the invalid byte 0x{:02x} at byte-offset {}. \
+                 The file is probably encoded differently (e.g. in latin-1), \
+                 hence please convert it into UTF-8, or set lossy-utf8 in the parsing-config \
+                 to replace invalid bytes.",
+                self.line_number,
+                self.path.display(),
+                bytes[e.valid_up_to()],
+                self.byte_offset + bom_len + e.valid_up_to()
+            )
+            .into()),
+        }
+    }
+}
+
+/// Columns of a written fmi-file, as described by osmgraphing's fmi-writer in json.
+#[derive(Debug, Deserialize)]
+struct EmbeddedConfig {
+    nodes: Vec<Option<EmbeddedColumn>>,
+    edges: Vec<Option<EmbeddedColumn>>,
+}
+
+/// `kind` is the column's meta-info or unit.
+#[derive(Debug, Deserialize, PartialEq)]
+struct EmbeddedColumn {
+    id: String,
+    kind: String,
+}
+
+impl EmbeddedColumn {
+    fn from_node_category(category: &nodes::Category) -> Option<EmbeddedColumn> {
+        match category {
+            nodes::Category::Meta { info, id } => Some(EmbeddedColumn {
+                id: id.0.clone(),
+                kind: format!("{:?}", info),
+            }),
+            nodes::Category::Metric { unit, id } => Some(EmbeddedColumn {
+                id: id.0.clone(),
+                kind: format!("{:?}", unit),
+            }),
+            nodes::Category::Ignored => None,
+        }
+    }
+
+    fn from_edge_category(category: &edges::Category) -> Option<EmbeddedColumn> {
+        match category {
+            edges::Category::Meta { info, id } => Some(EmbeddedColumn {
+                id: id.0.clone(),
+                kind: format!("{:?}", info),
+            }),
+            edges::Category::Metric { unit, id } => Some(EmbeddedColumn {
+                id: id.0.clone(),
+                kind: format!("{:?}", unit),
+            }),
+            edges::Category::Ignored => None,
+        }
+    }
+}
+
+/// Checks the parsing-config against the config, which has been embedded into the fmi-file when writing.
+fn check_embedded_cfg(embedded_cfg: &str, cfg: &parsing::Config) -> err::Feedback {
+    // written as json by the fmi-writer
+    let embedded_cfg: EmbeddedConfig = match serde_json::from_str(embedded_cfg) {
+        Ok(embedded_cfg) => embedded_cfg,
+        Err(e) => {
+            return Err(format!(
+                "The fmi-map-file's embedded config couldn't be read due to error: {}",
+                e
+            )
+            .into())
+        }
+    };
+
+    check_embedded_columns(
+        "node",
+        &embedded_cfg.nodes,
+        &cfg.nodes
+            .categories
+            .iter()
+            .map(EmbeddedColumn::from_node_category)
+            .collect::<Vec<_>>(),
+    )?;
+    let expected_edge_columns = cfg
+        .edges
+        .categories
+        .iter()
+        .map(EmbeddedColumn::from_edge_category)
+        .collect::<Vec<_>>();
+    check_embedded_columns("edge", &embedded_cfg.edges, &expected_edge_columns)?;
+
+    // Without parsing the bidirectional-flag, half of the merged edges would be missing.
+    let flag_kind = format!("{:?}", edges::MetaInfo::Bidirectional);
+    for (i, embedded_column) in embedded_cfg.edges.iter().enumerate() {
+        if let Some(embedded_column) = embedded_column {
+            if embedded_column.kind == flag_kind
+                && expected_edge_columns.get(i).and_then(Option::as_ref) != Some(embedded_column)
+            {
+                return Err(format!(
+                    "The fmi-map-file has been written with merged bidirectional edges, \
+                     hence the parsing-config needs the edge-column {} ({}) at position {}.",
+                    embedded_column.id, embedded_column.kind, i
+                )
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Columns, which are ignored by the parsing-config, are not checked.
+fn check_embedded_columns(
+    name: &str,
+    embedded_columns: &[Option<EmbeddedColumn>],
+    expected_columns: &[Option<EmbeddedColumn>],
+) -> err::Feedback {
+    let is_matching = expected_columns
+        .iter()
+        .enumerate()
+        .all(|(i, expected)| match expected {
+            Some(expected) => embedded_columns.get(i).and_then(Option::as_ref) == Some(expected),
+            None => true,
+        });
+
+    if is_matching {
+        Ok(())
+    } else {
+        let describe = |columns: &[Option<EmbeddedColumn>]| {
+            columns
+                .iter()
+                .map(|column| match column {
+                    Some(column) => format!("{} ({})", column.id, column.kind),
+                    None => String::from(defaults::writing::IGNORE_STR),
+                })
+                .collect::<Vec<_>>()
+        };
+        Err(format!(
+            "The fmi-map-file has been written with {}-columns {:?}, \
+             but the parsing-config expects {:?}. \
+             Set ignore-embedded-config to skip this check.",
+            name,
+            describe(embedded_columns),
+            describe(expected_columns)
+        )
+        .into())
+    }
+}
+
 impl ProtoShortcut {
     /// Parse a line of metrics into an edge.
     ///
     /// - When NodeIds are parsed, the first one is interpreted as src-id and the second one as dst-id.
+    /// - The returned flag is true, if the line describes the reversed edge as well.
     pub fn try_from_str(
         line: &str,
         categories: &Vec<parsing::edges::Category>,
-    ) -> Result<ProtoShortcut, String> {
+    ) -> Result<(ProtoShortcut, bool), String> {
         let mut metric_values = DimVec::new();
         let mut edge_id = None;
         let mut src_id = None;
         let mut dst_id = None;
         let mut sc_edge_0 = None;
         let mut sc_edge_1 = None;
+        let mut is_bidirectional = false;
 
         // Loop over edge-categories and parse params accordingly.
         let params: Vec<&str> = line.split_whitespace().collect();
@@ -265,12 +670,26 @@ impl ProtoShortcut {
                             }
                         }
                     }
+                    edges::MetaInfo::Bidirectional => {
+                        is_bidirectional = match param {
+                            "0" => false,
+                            "1" => true,
+                            _ => {
+                                return Err(format!(
+                                    "Parsing {:?} '{}' of edge-param #{} didn't work, \
+                                     expected 0 or 1.",
+                                    category, param, param_idx
+                                ))
+                            }
+                        };
+                    }
                     edges::MetaInfo::SrcIdx
                     | edges::MetaInfo::SrcLat
                     | edges::MetaInfo::SrcLon
                     | edges::MetaInfo::DstIdx
                     | edges::MetaInfo::DstLat
-                    | edges::MetaInfo::DstLon => {
+                    | edges::MetaInfo::DstLon
+                    | edges::MetaInfo::StableId => {
                         return Err(format!("Unsupported category {:?}", category))
                     }
                 },
@@ -296,15 +715,34 @@ impl ProtoShortcut {
             }
         };
 
-        Ok(ProtoShortcut {
+        Ok((
+            ProtoShortcut {
+                proto_edge: ProtoEdge {
+                    id: edge_id,
+                    src_id: src_id
+                        .ok_or("Proto-edge should have a src-id, but doesn't.".to_owned())?,
+                    dst_id: dst_id
+                        .ok_or("Proto-edge should have a dst-id, but doesn't.".to_owned())?,
+                    metrics: metric_values,
+                },
+                sc_edges,
+            },
+            is_bidirectional,
+        ))
+    }
+
+    /// The reversed edge of a bidirectional row, which has the same metrics.
+    /// Edge-ids and shortcut-indices are not allowed in bidirectional rows (see `check_config`).
+    fn reversed(&self) -> ProtoShortcut {
+        ProtoShortcut {
             proto_edge: ProtoEdge {
-                id: edge_id,
-                src_id: src_id.ok_or("Proto-edge should have a src-id, but doesn't.".to_owned())?,
-                dst_id: dst_id.ok_or("Proto-edge should have a dst-id, but doesn't.".to_owned())?,
-                metrics: metric_values,
+                id: None,
+                src_id: self.proto_edge.dst_id,
+                dst_id: self.proto_edge.src_id,
+                metrics: self.proto_edge.metrics.clone(),
             },
-            sc_edges,
-        })
+            sc_edges: None,
+        }
     }
 }
 
@@ -314,6 +752,7 @@ impl ProtoNode {
         let mut lat = None;
         let mut lon = None;
         let mut ch_level = None;
+        let mut customs = DimVec::new();
 
         // Loop over node-categories and parse params accordingly.
         let params: Vec<&str> = line.split_whitespace().collect();
@@ -378,6 +817,16 @@ impl ProtoNode {
                     nodes::metrics::UnitInfo::Height => {
                         return Err(format!("Unsupported category {:?}", category))
                     }
+                    nodes::metrics::UnitInfo::F64 => {
+                        if let Ok(value) = param.parse::<f64>() {
+                            customs.push(value);
+                        } else {
+                            return Err(format!(
+                                "Parsing {:?} '{}' of node-param #{} didn't work.",
+                                category, param, param_idx
+                            ));
+                        }
+                    }
                 },
                 nodes::Category::Ignored => (),
             }
@@ -390,6 +839,7 @@ impl ProtoNode {
             id: node_id,
             coord: geo::Coordinate { lat, lon },
             ch_level,
+            customs,
         })
     }
 }
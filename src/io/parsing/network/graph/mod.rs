@@ -1,5 +1,10 @@
 pub mod fmi;
+pub mod osm_xml;
 pub mod pbf;
+mod scanning;
+pub use scanning::{WayCounts, WayScan};
+mod stats;
+pub use stats::ParseStats;
 
 use crate::{
     configs::parsing::{self, generating},
@@ -9,9 +14,9 @@ use crate::{
     network::{EdgeBuilder, Graph, GraphBuilder, NodeBuilder},
 };
 use log::{info, warn};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// The parser parsing `*.osm.pbf`- and `*.fmi`-files into a graphbuilder or a graph.
+/// The parser parsing `*.osm.pbf`-, `*.osm`- and `*.fmi`-files into a graphbuilder or a graph.
 ///
 ///
 /// ## The filter-pipeline
@@ -39,60 +44,139 @@ use std::path::Path;
 ///
 /// This `pbf`-parser uses [osmpbfreader-rs](https://crates.io/crates/osmpbfreader).
 /// An own implementation would need [the pbf-impl of rust](https://github.com/stepancheg/rust-protobuf), but the previously mentioned osmpbfreader works well.
-/// `*.osm`-xml-files are not supported, but could be read with [quick-xml](https://github.com/tafia/quick-xml).
+/// `*.osm`-xml-files are read with [quick-xml](https://github.com/tafia/quick-xml) and processed by the same way- and node-logic as `pbf`-files.
 ///
 /// Other libraries processing openstreetmap-data can be found [in the osm-wiki](https://wiki.openstreetmap.org/wiki/Frameworks#Data_Processing_or_Parsing_Libraries).
 pub struct Parser;
 
 impl Parser {
     pub fn parse(cfg: parsing::Config) -> err::Result<GraphBuilder> {
-        match Parser::from_path(&cfg.map_file)? {
+        match Parser::from_paths(&cfg.map_files)? {
             MapFileExt::PBF => pbf::Parser::new().parse(cfg),
             MapFileExt::FMI => fmi::Parser::new().parse(cfg),
+            MapFileExt::OSM => osm_xml::Parser::new().parse(cfg),
         }
     }
 
     pub fn parse_and_finalize(cfg: parsing::Config) -> err::Result<Graph> {
-        match Parser::from_path(&cfg.map_file)? {
+        match Parser::from_paths(&cfg.map_files)? {
             MapFileExt::PBF => pbf::Parser::new().parse_and_finalize(cfg),
             MapFileExt::FMI => fmi::Parser::new().parse_and_finalize(cfg),
+            MapFileExt::OSM => osm_xml::Parser::new().parse_and_finalize(cfg),
         }
     }
+
+    /// Like `parse_and_finalize(...)`, but returns the parser's stats as well, e.g. to compare
+    /// graphs parsed with different vehicle-configs.
+    pub fn parse_and_finalize_with_stats(cfg: parsing::Config) -> err::Result<(Graph, ParseStats)> {
+        match Parser::from_paths(&cfg.map_files)? {
+            MapFileExt::PBF => pbf::Parser::new().parse_and_finalize_with_stats(cfg),
+            MapFileExt::FMI => fmi::Parser::new().parse_and_finalize_with_stats(cfg),
+            MapFileExt::OSM => osm_xml::Parser::new().parse_and_finalize_with_stats(cfg),
+        }
+    }
+
+    /// Runs only the way-pass of the pbf- or osm-xml-parser (no node-pass, no graph) and collects the values of some tags, e.g. to audit a map before parsing it completely.
+    pub fn scan_ways(cfg: &parsing::Config) -> err::Result<WayScan> {
+        match Parser::from_paths(&cfg.map_files)? {
+            MapFileExt::PBF => {
+                info!("START Scan ways of given files");
+                let mut way_scan = WayScan::new();
+                for map_file in cfg.map_files.iter() {
+                    pbf::Parser::new().scan_ways(map_file, cfg, &mut way_scan)?;
+                }
+                info!("FINISHED");
+                Ok(way_scan)
+            }
+            MapFileExt::OSM => {
+                info!("START Scan ways of given files");
+                let mut way_scan = WayScan::new();
+                for map_file in cfg.map_files.iter() {
+                    osm_xml::Parser::new().scan_ways(map_file, cfg, &mut way_scan)?;
+                }
+                info!("FINISHED");
+                Ok(way_scan)
+            }
+            MapFileExt::FMI => {
+                Err("Scanning ways is only supported for pbf- and osm-files.".into())
+            }
+        }
+    }
+
+    /// All map-files are parsed by the same parser, so mixed extensions are rejected.
+    fn from_paths(map_files: &[PathBuf]) -> err::Result<MapFileExt> {
+        let mut map_file_ext = None;
+        for map_file in map_files {
+            let ext = Parser::from_path(map_file)?;
+            match &map_file_ext {
+                None => map_file_ext = Some(ext),
+                Some(first_ext) => {
+                    if first_ext != &ext {
+                        return Err(format!(
+                            "The map-files {} and {} have different extensions, \
+                             but can only be merged with the same extension.",
+                            map_files[0].display(),
+                            map_file.display()
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+        map_file_ext.ok_or(err::Msg::from("No map-file is given."))
+    }
 }
 
 impl SupportingMapFileExts for Parser {}
 impl SupportingFileExts for Parser {
     fn supported_exts<'a>() -> &'a [&'a str] {
-        &["pbf", "fmi"]
+        &["pbf", "fmi", "osm"]
     }
 }
 
 trait Parsing {
-    fn preprocess(&mut self, cfg: &parsing::Config) -> err::Feedback {
+    fn preprocess(&mut self, cfg: &parsing::Config, _map_file: &Path) -> err::Feedback {
         check_config(cfg)
     }
 
+    /// Every map-file is parsed into the same builder, which merges duplicate nodes and edges.
     fn parse(&mut self, cfg: parsing::Config) -> err::Result<GraphBuilder> {
+        let map_files = cfg.map_files.clone();
         let mut builder = GraphBuilder::new(cfg);
+        crate::trace_scope!("parsing", map_files = map_files.len());
 
-        info!("START Process given file");
-        self.preprocess(builder.cfg())?;
-        self.parse_ways(&mut builder)?;
+        info!("START Process given files");
+        for map_file in map_files.iter() {
+            self.preprocess(builder.cfg(), map_file)?;
+            self.parse_ways(map_file, &mut builder)?;
+        }
         let mut builder = builder.next();
-        self.parse_nodes(&mut builder)?;
+        for map_file in map_files.iter() {
+            self.parse_nodes(map_file, &mut builder)?;
+        }
         let builder = builder.next();
         info!("FINISHED");
 
         builder
     }
 
-    fn parse_ways(&self, builder: &mut EdgeBuilder) -> err::Feedback;
+    fn parse_ways(&mut self, map_file: &Path, builder: &mut EdgeBuilder) -> err::Feedback;
+
+    fn parse_nodes(&mut self, map_file: &Path, builder: &mut NodeBuilder) -> err::Feedback;
 
-    fn parse_nodes(&self, builder: &mut NodeBuilder) -> err::Feedback;
+    /// Stats of the parsed ways, which are empty for parsers not filtering their ways.
+    fn way_stats(&self) -> ParseStats {
+        ParseStats::new()
+    }
 
     fn parse_and_finalize(&mut self, cfg: parsing::Config) -> err::Result<Graph> {
-        let path = Path::new(&cfg.map_file);
-        info!("START Parse from given path {}", path.display());
+        info!(
+            "START Parse from given paths {:?}",
+            cfg.map_files
+                .iter()
+                .map(|map_file| map_file.display().to_string())
+                .collect::<Vec<_>>()
+        );
 
         // TODO parse "cycleway" and other tags
         // see https://wiki.openstreetmap.org/wiki/Key:highway
@@ -101,6 +185,27 @@ trait Parsing {
         info!("FINISHED");
         result
     }
+
+    /// Like `parse_and_finalize(...)`, but counts the node-conflicts in the way-stats.
+    fn parse_and_finalize_with_stats(
+        &mut self,
+        cfg: parsing::Config,
+    ) -> err::Result<(Graph, ParseStats)> {
+        info!(
+            "START Parse (with stats) from given paths {:?}",
+            cfg.map_files
+                .iter()
+                .map(|map_file| map_file.display().to_string())
+                .collect::<Vec<_>>()
+        );
+
+        let builder = self.parse(cfg)?;
+        let mut stats = self.way_stats();
+        stats.num_of_coord_conflicts = builder.num_of_coord_conflicts();
+        let result = builder.finalize().map(|graph| (graph, stats));
+        info!("FINISHED");
+        result
+    }
 }
 
 /// check if yaml-config is correct
@@ -156,6 +261,8 @@ fn check_config(cfg: &parsing::Config) -> err::Feedback {
                         result: _,
                         a: _,
                         b: _,
+                        is_directional: _,
+                        gradient: _,
                     } => 1,
                     generating::edges::Category::Copy { from: _, to: _ } => 1,
                     generating::edges::Category::Haversine { unit: _, id: _ } => 1,
@@ -221,7 +328,9 @@ fn check_config(cfg: &parsing::Config) -> err::Feedback {
                     | parsing::edges::MetaInfo::DstId
                     | parsing::edges::MetaInfo::DstIdx
                     | parsing::edges::MetaInfo::DstLat
-                    | parsing::edges::MetaInfo::DstLon => false,
+                    | parsing::edges::MetaInfo::DstLon
+                    | parsing::edges::MetaInfo::StableId
+                    | parsing::edges::MetaInfo::Bidirectional => false,
                 },
                 parsing::edges::Category::Metric { unit: _, id: _ }
                 | parsing::edges::Category::Ignored => false,
@@ -235,5 +344,55 @@ fn check_config(cfg: &parsing::Config) -> err::Feedback {
         .into());
     }
 
+    // shortcut-indices refer to edges of their own map-file
+    if count > 0 && cfg.map_files.len() > 1 {
+        return Err("Shortcut-indices can't be merged from multiple map-files.".into());
+    }
+
+    // Both directions of a bidirectional row would share the row's edge-id or shortcut-indices.
+
+    let is_bidirectional =
+        |info: &parsing::edges::MetaInfo| info == &parsing::edges::MetaInfo::Bidirectional;
+    let is_row_specific = |info: &parsing::edges::MetaInfo| match info {
+        parsing::edges::MetaInfo::EdgeId
+        | parsing::edges::MetaInfo::StableId
+        | parsing::edges::MetaInfo::ShortcutIdx0
+        | parsing::edges::MetaInfo::ShortcutIdx1 => true,
+        parsing::edges::MetaInfo::SrcId
+        | parsing::edges::MetaInfo::SrcIdx
+        | parsing::edges::MetaInfo::SrcLat
+        | parsing::edges::MetaInfo::SrcLon
+        | parsing::edges::MetaInfo::DstId
+        | parsing::edges::MetaInfo::DstIdx
+        | parsing::edges::MetaInfo::DstLat
+        | parsing::edges::MetaInfo::DstLon
+        | parsing::edges::MetaInfo::Bidirectional => false,
+    };
+    let metas: Vec<&parsing::edges::MetaInfo> = cfg
+        .edges
+        .categories
+        .iter()
+        .filter_map(|category| match category {
+            parsing::edges::Category::Meta { info, id: _ } => Some(info),
+            parsing::edges::Category::Metric { unit: _, id: _ }
+            | parsing::edges::Category::Ignored => None,
+        })
+        .collect();
+    let bidirectional_count = metas.iter().filter(|info| is_bidirectional(info)).count();
+    if bidirectional_count > 1 {
+        return Err(format!(
+            "The config-file has {} bidirectional-flags, but should have at most 1.",
+            bidirectional_count
+        )
+        .into());
+    }
+    if bidirectional_count > 0 && metas.iter().any(|info| is_row_specific(info)) {
+        return Err(
+            "Bidirectional-flags can't be combined with edge-ids or shortcut-indices, \
+             because both directions of a row would share them."
+                .into(),
+        );
+    }
+
     Ok(())
 }
@@ -0,0 +1,370 @@
+use super::pbf;
+use crate::{
+    configs::parsing,
+    defaults::capacity::DimVec,
+    helpers::err,
+    network::{EdgeBuilder, NodeBuilder, ProtoNode},
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use osmpbfreader::{NodeId, Tags, Way, WayId};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// Parses `*.osm`-xml-files with the same two passes (ways, then nodes) as the pbf-parser.
+///
+/// Every way is converted into an `osmpbfreader::Way`, hence tags are handled by exactly the
+/// same logic (see `network::StreetCategory`).
+/// The file is streamed in both passes, so it is never held in memory completely.
+pub struct Parser {
+    stats: super::ParseStats,
+    /// sorted ids of the map-file's nodes within the configured bounding-box, if any
+    bbox_node_ids: Option<Vec<i64>>,
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser {
+            stats: super::ParseStats::new(),
+            bbox_node_ids: None,
+        }
+    }
+
+    /// Returns the stats of all parsed ways, e.g. how many ways of a street-category have been
+    /// rejected by the configured vehicle.
+    pub fn stats(&self) -> &super::ParseStats {
+        &self.stats
+    }
+
+    /// Like the way-pass of the parser, but only collects tag-values and counts instead of creating edges.
+    pub fn scan_ways(
+        &mut self,
+        map_file: &Path,
+        cfg: &parsing::Config,
+        way_scan: &mut super::WayScan,
+    ) -> err::Feedback {
+        info!("START Scan ways from input-file {}.", map_file.display());
+        let mut reader = ObjReader::open(map_file)?;
+        while let Some(obj) = reader.next_obj(ObjKind::Way)? {
+            pbf::scan_way(&obj.into_way(), cfg, way_scan);
+        }
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl super::Parsing for Parser {
+    fn preprocess(&mut self, cfg: &parsing::Config, map_file: &Path) -> err::Feedback {
+        info!("START Start preprocessing osm-xml-parser.");
+        super::check_config(cfg)?;
+        pbf::check_osm_config(cfg, "osm-files")?;
+
+        // Nodes are parsed after ways, hence the nodes within the bounding-box are collected
+        // beforehand to clip the ways.
+        self.bbox_node_ids = match cfg.bbox {
+            Some(bbox) => {
+                info!(
+                    "DO Collect nodes within the bounding-box from {} to {}.",
+                    bbox.min, bbox.max
+                );
+                let mut node_ids = Vec::new();
+                let mut reader = ObjReader::open(map_file)?;
+                while let Some(obj) = reader.next_obj(ObjKind::Node)? {
+                    if obj.coord.map_or(false, |coord| bbox.contains(&coord)) {
+                        node_ids.push(obj.id);
+                    }
+                }
+                let node_ids = pbf::sorted_bbox_node_ids(node_ids);
+                info!("Found {} nodes within the bounding-box.", node_ids.len());
+                Some(node_ids)
+            }
+            None => None,
+        };
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    fn parse_ways(&mut self, map_file: &Path, builder: &mut EdgeBuilder) -> err::Feedback {
+        info!("START Create edges from input-file {}.", map_file.display());
+        crate::trace_scope!("osm_xml::ways", map_file = %map_file.display());
+        let mut reader = ObjReader::open(map_file)?;
+        while let Some(obj) = reader.next_obj(ObjKind::Way)? {
+            pbf::insert_way(
+                &obj.into_way(),
+                builder,
+                self.bbox_node_ids.as_ref().map(Vec::as_slice),
+                &mut self.stats,
+            )?;
+        }
+        info!("Ways per street-category (so far):");
+        for line in self.stats.to_string().lines() {
+            info!("    {}", line);
+        }
+        info!("FINISHED");
+        Ok(())
+    }
+
+    fn parse_nodes(&mut self, map_file: &Path, builder: &mut NodeBuilder) -> err::Feedback {
+        info!("START Create nodes from input-file {}.", map_file.display());
+        crate::trace_scope!(
+            "osm_xml::nodes",
+            map_file = %map_file.display(),
+            nodes = builder.node_count()
+        );
+        let is_parsing_ele_tags = builder.cfg().elevation.is_some();
+        let mut proto_nodes = Vec::new();
+        let mut reader = ObjReader::open(map_file)?;
+        while let Some(obj) = reader.next_obj(ObjKind::Node)? {
+            // Only nodes of edges are remembered.
+            if !builder.contains(obj.id) {
+                continue;
+            }
+
+            let ele_tag = if is_parsing_ele_tags {
+                obj.tags.get("ele").and_then(|ele| pbf::parse_ele_tag(ele))
+            } else {
+                None
+            };
+            let coord = match obj.coord {
+                Some(coord) => coord,
+                None => {
+                    return Err(format!(
+                        "Node {} in {} has no coordinate (attributes lat and lon).",
+                        obj.id,
+                        map_file.display()
+                    )
+                    .into())
+                }
+            };
+            let proto_node = ProtoNode {
+                id: obj.id,
+                coord,
+                ch_level: None,
+                customs: DimVec::new(),
+            };
+            proto_nodes.push((proto_node, ele_tag));
+        }
+        pbf::insert_nodes(proto_nodes.into_iter().map(Ok), builder)?;
+        info!("FINISHED");
+        Ok(())
+    }
+
+    fn way_stats(&self) -> super::ParseStats {
+        self.stats.clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ObjKind {
+    Node,
+    Way,
+}
+
+impl ObjKind {
+    fn tag_name(&self) -> &'static [u8] {
+        match self {
+            ObjKind::Node => b"node",
+            ObjKind::Way => b"way",
+        }
+    }
+}
+
+/// A node or a way of an osm-xml-file with the data needed for parsing.
+struct Obj {
+    id: i64,
+    /// Only nodes have a coordinate.
+    coord: Option<Coordinate>,
+    /// Only ways have nodes (`<nd ref="..."/>`).
+    node_ids: Vec<i64>,
+    tags: Tags,
+}
+
+impl Obj {
+    fn into_way(self) -> Way {
+        Way {
+            id: WayId(self.id),
+            tags: self.tags,
+            nodes: self.node_ids.into_iter().map(NodeId).collect(),
+        }
+    }
+}
+
+/// Streams the objects of one kind from an osm-xml-file, skipping all others (including
+/// relations).
+struct ObjReader {
+    map_file: PathBuf,
+    reader: Reader<BufReader<File>>,
+    buf: Vec<u8>,
+}
+
+impl ObjReader {
+    fn open(map_file: &Path) -> err::Result<ObjReader> {
+        let mut reader = Reader::from_file(map_file)
+            .map_err(|e| format!("Couldn't open {} due to error: {}", map_file.display(), e))?;
+        reader.trim_text(true);
+        Ok(ObjReader {
+            map_file: map_file.to_path_buf(),
+            reader,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Returns the next object of the given kind, or `None` at the end of the file.
+    fn next_obj(&mut self, kind: ObjKind) -> err::Result<Option<Obj>> {
+        let ObjReader {
+            map_file,
+            reader,
+            buf,
+        } = self;
+        let map_file = map_file.as_path();
+        let mut current: Option<Obj> = None;
+        loop {
+            buf.clear();
+            let event = reader
+                .read_event(buf)
+                .map_err(|e| Location::new(map_file, reader).error_msg(e))?;
+            let location = Location::new(map_file, reader);
+
+            let (e, is_empty) = match &event {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(e) => {
+                    if e.name() == kind.tag_name() {
+                        if let Some(obj) = current.take() {
+                            return Ok(Some(obj));
+                        }
+                    }
+                    continue;
+                }
+                Event::Eof => {
+                    return match current {
+                        None => Ok(None),
+                        Some(obj) => Err(format!(
+                            "The {} of id {} in {} isn't closed.",
+                            String::from_utf8_lossy(kind.tag_name()),
+                            obj.id,
+                            map_file.display()
+                        )
+                        .into()),
+                    }
+                }
+                _ => continue,
+            };
+
+            match &mut current {
+                None if e.name() == kind.tag_name() => {
+                    let obj = location.new_obj(e, kind)?;
+                    if is_empty {
+                        return Ok(Some(obj));
+                    }
+                    current = Some(obj);
+                }
+                None => (),
+                Some(obj) => match e.name() {
+                    b"nd" if kind == ObjKind::Way => {
+                        let node_id = location.attr(e, b"ref")?;
+                        obj.node_ids.push(location.parse_id(&node_id)?);
+                    }
+                    b"tag" => {
+                        let key = location.attr(e, b"k")?;
+                        let value = location.attr(e, b"v")?;
+                        obj.tags.insert(key, value);
+                    }
+                    _ => (),
+                },
+            }
+        }
+    }
+}
+
+/// The reader's current position in its file for error-messages
+struct Location<'a> {
+    map_file: &'a Path,
+    reader: &'a Reader<BufReader<File>>,
+}
+
+impl<'a> Location<'a> {
+    fn new(map_file: &'a Path, reader: &'a Reader<BufReader<File>>) -> Location<'a> {
+        Location { map_file, reader }
+    }
+
+    fn new_obj(&self, e: &BytesStart, kind: ObjKind) -> err::Result<Obj> {
+        let id = self.parse_id(&self.attr(e, b"id")?)?;
+        let coord = match kind {
+            ObjKind::Node => Some(Coordinate {
+                lat: self.parse_float(&self.attr(e, b"lat")?)?,
+                lon: self.parse_float(&self.attr(e, b"lon")?)?,
+            }),
+            ObjKind::Way => None,
+        };
+        Ok(Obj {
+            id,
+            coord,
+            node_ids: Vec::new(),
+            tags: Tags::new(),
+        })
+    }
+
+    /// Returns the unescaped value of the element's attribute.
+    fn attr(&self, e: &BytesStart, key: &[u8]) -> err::Result<String> {
+        for attr in e.attributes() {
+            let attr = attr.map_err(|e| self.error_msg(e))?;
+            if attr.key == key {
+                return attr
+                    .unescape_and_decode_value(self.reader)
+                    .map_err(|e| self.error_msg(e));
+            }
+        }
+        Err(format!(
+            "The element {} in {} at byte-offset {} has no attribute {}.",
+            String::from_utf8_lossy(e.name()),
+            self.map_file.display(),
+            self.reader.buffer_position(),
+            String::from_utf8_lossy(key)
+        )
+        .into())
+    }
+
+    fn parse_id(&self, value: &str) -> err::Result<i64> {
+        value.parse::<i64>().map_err(|_| {
+            format!(
+                "The id {} in {} at byte-offset {} isn't an integer.",
+                value,
+                self.map_file.display(),
+                self.reader.buffer_position()
+            )
+            .into()
+        })
+    }
+
+    fn parse_float(&self, value: &str) -> err::Result<f64> {
+        match value.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(format!(
+                "The coordinate {} in {} at byte-offset {} isn't a number.",
+                value,
+                self.map_file.display(),
+                self.reader.buffer_position()
+            )
+            .into()),
+        }
+    }
+
+    fn error_msg(&self, e: quick_xml::Error) -> err::Msg {
+        format!(
+            "Couldn't read {} at byte-offset {} due to error: {}",
+            self.map_file.display(),
+            self.reader.buffer_position(),
+            e
+        )
+        .into()
+    }
+}
@@ -1,200 +1,989 @@
 use crate::{
     configs::parsing::{self, edges},
-    defaults::capacity::DimVec,
+    defaults::{self, capacity::DimVec},
     helpers::err,
-    network::{EdgeBuilder, NodeBuilder, ProtoEdge, ProtoNode, StreetCategory},
+    io::elevation::SrtmReader,
+    network::{
+        vehicles::Category as VehicleCategory, EdgeBuilder, MetricIdx, NodeBuilder, ProtoEdge,
+        ProtoNode, StreetCategory, TurnRestriction, TurnRestrictionKind,
+    },
 };
 use kissunits::geo::Coordinate;
 use log::info;
-use osmpbfreader::{reader::OsmPbfReader, OsmObj};
+use osmpbfreader::{
+    blocks,
+    osmformat::PrimitiveBlock,
+    reader::{primitive_block_from_blob, OsmPbfReader},
+    OsmId, OsmObj, Relation, Way,
+};
 use smallvec::smallvec;
-use std::fs::OpenOptions;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    path::Path,
+    sync::{mpsc, Arc, Condvar, Mutex},
+    thread, vec,
+};
 
-pub struct Parser;
+pub struct Parser {
+    stats: super::ParseStats,
+    /// sorted ids of the map-file's nodes within the configured bounding-box, if any
+    bbox_node_ids: Option<Vec<i64>>,
+}
 
 impl Parser {
     pub fn new() -> Parser {
-        Parser {}
+        Parser {
+            stats: super::ParseStats::new(),
+            bbox_node_ids: None,
+        }
+    }
+
+    /// Returns the stats of all parsed ways, e.g. how many ways of a street-category have been
+    /// rejected by the configured vehicle.
+    pub fn stats(&self) -> &super::ParseStats {
+        &self.stats
+    }
+
+    /// Like the way-pass of the parser, but only collects tag-values and counts instead of creating edges.
+    pub fn scan_ways(
+        &mut self,
+        map_file: &Path,
+        cfg: &parsing::Config,
+        way_scan: &mut super::WayScan,
+    ) -> err::Feedback {
+        info!("START Scan ways from input-file {}.", map_file.display());
+        let file = OpenOptions::new()
+            .read(true)
+            .open(map_file)
+            .map_err(|e| format!("Couldn't open {} due to error: {}", map_file.display(), e))?;
+
+        for way in OsmPbfReader::new(file)
+            .par_iter()
+            .filter_map(Result::ok)
+            .filter_map(|obj| match obj {
+                OsmObj::Way(way) => Some(way),
+                _ => None,
+            })
+        {
+            scan_way(&way, cfg, way_scan);
+        }
+        info!("FINISHED");
+        Ok(())
     }
 }
 
 impl super::Parsing for Parser {
-    fn preprocess(&mut self, cfg: &parsing::Config) -> err::Feedback {
+    fn preprocess(&mut self, cfg: &parsing::Config, map_file: &Path) -> err::Feedback {
         info!("START Start preprocessing pbf-parser.");
         super::check_config(cfg)?;
+        check_osm_config(cfg, "pbf-files")?;
 
-        for category in cfg.edges.categories.iter() {
-            match category {
-                edges::Category::Meta { info, id: _ } => match info {
-                    edges::MetaInfo::SrcId | edges::MetaInfo::DstId => {
-                        // already checked in check_config(...)
+        // Nodes are parsed after ways, hence the nodes within the bounding-box are collected
+        // beforehand to clip the ways.
+        self.bbox_node_ids = match cfg.bbox {
+            Some(bbox) => {
+                info!(
+                    "DO Collect nodes within the bounding-box from {} to {}.",
+                    bbox.min, bbox.max
+                );
+                let file = match OpenOptions::new().read(true).open(map_file) {
+                    Ok(file) => file,
+                    Err(e) => {
+                        return Err(err::Msg::from(format!(
+                            "Couldn't open {} due to error: {}",
+                            map_file.display(),
+                            e
+                        )))
                     }
-                    edges::MetaInfo::EdgeId
-                    | edges::MetaInfo::SrcIdx
-                    | edges::MetaInfo::SrcLat
-                    | edges::MetaInfo::SrcLon
-                    | edges::MetaInfo::DstIdx
-                    | edges::MetaInfo::DstLat
-                    | edges::MetaInfo::DstLon
-                    | edges::MetaInfo::ShortcutIdx0
-                    | edges::MetaInfo::ShortcutIdx1 => {
-                        return Err(format!("{:?} are not supported in pbf-files.", category).into())
-                    }
-                },
-                edges::Category::Metric { unit, id: _ } => match unit {
-                    edges::metrics::UnitInfo::Meters
-                    | edges::metrics::UnitInfo::Kilometers
-                    | edges::metrics::UnitInfo::Seconds
-                    | edges::metrics::UnitInfo::Minutes
-                    | edges::metrics::UnitInfo::Hours
-                    | edges::metrics::UnitInfo::F64 => {
-                        return Err(format!(
-                            "The {:?} of an edge in a pbf-file has to be calculated, \
-                             but is expected to be provided.",
-                            category
-                        )
-                        .into());
-                    }
-                    edges::metrics::UnitInfo::KilometersPerHour
-                    | edges::metrics::UnitInfo::LaneCount => {
-                        // irrelevant
-                    }
-                },
-                edges::Category::Ignored => (),
+                };
+                let node_ids: Vec<i64> = decode_blocks(file, cfg.num_threads, move |block| {
+                    blocks::nodes(block)
+                        .filter(|node| {
+                            bbox.contains(&Coordinate::from_decimicro(
+                                node.decimicro_lat,
+                                node.decimicro_lon,
+                            ))
+                        })
+                        .map(|node| node.id.0)
+                        .collect()
+                })
+                .collect::<err::Result<_>>()?;
+                let node_ids = sorted_bbox_node_ids(node_ids);
+                info!("Found {} nodes within the bounding-box.", node_ids.len());
+                Some(node_ids)
             }
-        }
+            None => None,
+        };
 
         info!("FINISHED");
         Ok(())
     }
 
-    fn parse_ways(&self, builder: &mut EdgeBuilder) -> err::Feedback {
-        info!("START Create edges from input-file.");
+    fn parse_ways(&mut self, map_file: &Path, builder: &mut EdgeBuilder) -> err::Feedback {
+        info!("START Create edges from input-file {}.", map_file.display());
+        crate::trace_scope!("pbf::ways", map_file = %map_file.display());
         let file = OpenOptions::new()
             .read(true)
-            .open(&builder.cfg().map_file)
-            .expect(&format!(
-                "Couldn't open {}",
-                builder.cfg().map_file.display()
-            ));
+            .open(map_file)
+            .expect(&format!("Couldn't open {}", map_file.display()));
 
-        for mut way in OsmPbfReader::new(file)
-            .par_iter()
-            .filter_map(Result::ok)
-            .filter_map(|obj| match obj {
-                OsmObj::Way(way) => Some(way),
-                _ => None,
-            })
-        {
-            if way.nodes.len() < 2 {
-                continue;
+        let num_threads = builder.cfg().num_threads;
+        let mut turn_restrictions = TurnRestrictions::new(builder.cfg().vehicles.category);
+        for obj in decode_blocks(file, num_threads, |block| {
+            blocks::ways(block)
+                .map(OsmObj::Way)
+                .chain(blocks::relations(block).map(OsmObj::Relation))
+                .collect()
+        }) {
+            match obj? {
+                OsmObj::Way(way) => {
+                    turn_restrictions.add_way(&way);
+                    insert_way(
+                        &way,
+                        builder,
+                        self.bbox_node_ids.as_ref().map(Vec::as_slice),
+                        &mut self.stats,
+                    )?;
+                }
+                OsmObj::Relation(relation) => turn_restrictions.add_relation(&relation),
+                OsmObj::Node(_) => (),
             }
+        }
+        turn_restrictions.insert_into(builder, self.bbox_node_ids.as_ref().map(Vec::as_slice));
+        info!("Ways per street-category (so far):");
+        for line in self.stats.to_string().lines() {
+            info!("    {}", line);
+        }
+        info!("FINISHED");
+        Ok(())
+    }
 
-            // collect relevant data from file, if way-type is as expected by user
-            let highway_tag = match StreetCategory::from(&way) {
-                Some(highway_tag) => highway_tag,
-                None => continue,
-            };
-            if !highway_tag.is_for(
-                &builder.cfg().vehicles.category,
-                builder.cfg().vehicles.are_drivers_picky,
-            ) {
-                continue;
-            }
-
-            // get nodes of way to create proto-edges later
-            let (is_oneway, is_reverse) = highway_tag.parse_oneway(&way);
-            if is_reverse {
-                way.nodes.reverse();
-            }
-            let iter_range = if is_oneway {
-                0..0
-            } else {
-                // if not oneway
-                // -> add node-IDs reversed to generate edges forwards and backwards
-                // -> don't use last one, to not use it twice (a->b->c->c->b->a)
-                0..(way.nodes.len() - 1)
-            };
-            let nodes: Vec<i64> = way
-                .nodes
-                .iter()
-                .chain(way.nodes[iter_range].iter().rev())
-                .map(|id| id.0)
-                .collect();
-
-            // Collect metrics as expected by user-config
-            // ATTENTION: A way contains multiple edges, thus be careful when adding new metrics.
-
-            let mut metrics: DimVec<_> = smallvec![];
-
-            for category in builder.cfg().edges.categories.iter() {
-                match category {
-                    edges::Category::Meta { info: _, id: _ } => {
-                        // already checked in preprocessing
-                    }
-                    edges::Category::Metric { unit, id: _ } => match unit {
-                        edges::metrics::UnitInfo::KilometersPerHour => {
-                            let maxspeed = highway_tag.parse_maxspeed(&way);
-                            metrics.push(*maxspeed);
-                        }
-                        edges::metrics::UnitInfo::LaneCount => {
-                            let lane_count = highway_tag.parse_lane_count(&way);
-                            metrics.push(lane_count as f64);
-                        }
-                        edges::metrics::UnitInfo::Meters
-                        | edges::metrics::UnitInfo::Kilometers
-                        | edges::metrics::UnitInfo::Seconds
-                        | edges::metrics::UnitInfo::Minutes
-                        | edges::metrics::UnitInfo::Hours
-                        | edges::metrics::UnitInfo::F64 => {
-                            // already checked in preprocessing
+    fn parse_nodes(&mut self, map_file: &Path, builder: &mut NodeBuilder) -> err::Feedback {
+        info!("START Create nodes from input-file {}.", map_file.display());
+        crate::trace_scope!(
+            "pbf::nodes",
+            map_file = %map_file.display(),
+            nodes = builder.node_count()
+        );
+        let file = OpenOptions::new()
+            .read(true)
+            .open(map_file)
+            .expect(&format!("Couldn't open {}", map_file.display()));
+
+        let num_threads = builder.cfg().num_threads;
+        let is_parsing_ele_tags = builder.cfg().elevation.is_some();
+        let proto_nodes = decode_blocks(file, num_threads, move |block| {
+            blocks::nodes(block)
+                .map(|node| {
+                    let ele_tag = if is_parsing_ele_tags {
+                        node.tags.get("ele").and_then(|ele| parse_ele_tag(ele))
+                    } else {
+                        None
+                    };
+                    let proto_node = ProtoNode {
+                        id: node.id.0,
+                        coord: Coordinate::from_decimicro(node.decimicro_lat, node.decimicro_lon),
+                        ch_level: None,
+                        customs: DimVec::new(),
+                    };
+                    (proto_node, ele_tag)
+                })
+                .collect()
+        });
+
+        insert_nodes(proto_nodes, builder)?;
+        info!("FINISHED");
+        Ok(())
+    }
+
+    fn way_stats(&self) -> super::ParseStats {
+        self.stats.clone()
+    }
+}
+
+/// Checks the config's categories, which are supported by osm-files (pbf or xml), where `files`
+/// names them in error-messages, e.g. `pbf-files`.
+pub(super) fn check_osm_config(cfg: &parsing::Config, files: &str) -> err::Feedback {
+    // only the elevation is supported as custom node-value
+    let custom_ids = cfg.nodes.custom_ids();
+    match &cfg.elevation {
+        Some(elevation) if custom_ids == vec![&elevation.id] => (),
+        Some(elevation) => {
+            return Err(format!(
+                "Custom node-values except for the elevation {} are not supported in {}.",
+                elevation.id, files
+            )
+            .into())
+        }
+        None if custom_ids.is_empty() => (),
+        None => {
+            return Err(format!(
+                "Custom node-values are not supported in {}, except for an elevation \
+                 (see the parsing-config's elevation).",
+                files
+            )
+            .into())
+        }
+    }
+
+    for category in cfg.edges.categories.iter() {
+        match category {
+            edges::Category::Meta { info, id: _ } => match info {
+                edges::MetaInfo::SrcId | edges::MetaInfo::DstId => {
+                    // already checked in check_config(...)
+                }
+                edges::MetaInfo::EdgeId
+                | edges::MetaInfo::SrcIdx
+                | edges::MetaInfo::SrcLat
+                | edges::MetaInfo::SrcLon
+                | edges::MetaInfo::DstIdx
+                | edges::MetaInfo::DstLat
+                | edges::MetaInfo::DstLon
+                | edges::MetaInfo::StableId
+                | edges::MetaInfo::ShortcutIdx0
+                | edges::MetaInfo::ShortcutIdx1
+                | edges::MetaInfo::Bidirectional => {
+                    return Err(format!("{:?} are not supported in {}.", category, files).into())
+                }
+            },
+            edges::Category::Metric { unit, id: _ } => match unit {
+                edges::metrics::UnitInfo::Meters
+                | edges::metrics::UnitInfo::Kilometers
+                | edges::metrics::UnitInfo::Seconds
+                | edges::metrics::UnitInfo::Minutes
+                | edges::metrics::UnitInfo::Hours
+                | edges::metrics::UnitInfo::F64 => {
+                    return Err(format!(
+                        "The {:?} of an edge in {} has to be calculated, \
+                         but is expected to be provided.",
+                        category, files
+                    )
+                    .into());
+                }
+                edges::metrics::UnitInfo::KilometersPerHour
+                | edges::metrics::UnitInfo::LaneCount
+                | edges::metrics::UnitInfo::Roughness
+                | edges::metrics::UnitInfo::ConditionalRestriction
+                | edges::metrics::UnitInfo::CyclewayComfort => {
+                    // irrelevant
+                }
+            },
+            edges::Category::Ignored => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the way's tag-values, as if it was parsed (see `super::WayScan`).
+pub(super) fn scan_way(way: &Way, cfg: &parsing::Config, way_scan: &mut super::WayScan) {
+    for tag in defaults::parsing::scanning::TAGS {
+        if let Some(value) = way.tags.get(*tag) {
+            way_scan.add_tag(tag, value);
+        }
+    }
+
+    // same filters as when parsing ways
+    let highway_tag = StreetCategory::from(way);
+    let is_accepted = way.nodes.len() >= 2
+        && highway_tag.as_ref().map_or(false, |highway_tag| {
+            highway_tag.is_for(&cfg.vehicles.category, cfg.vehicles.are_drivers_picky)
+        });
+    way_scan.add_way(
+        highway_tag.map(|highway_tag| highway_tag.to_string()),
+        is_accepted,
+    );
+}
+
+/// Sorts and dedups the ids of nodes within the bounding-box, e.g. for binary-searching them in
+/// `insert_way(...)`.
+pub(super) fn sorted_bbox_node_ids<I>(node_ids: I) -> Vec<i64>
+where
+    I: IntoIterator<Item = i64>,
+{
+    let mut node_ids: Vec<i64> = node_ids.into_iter().collect();
+    node_ids.sort_unstable();
+    node_ids.dedup();
+    node_ids.shrink_to_fit();
+    node_ids
+}
+
+/// Inserts the edges of the way, if its street-category is accepted by the configured vehicle,
+/// and counts it in the stats.
+///
+/// With `bbox_node_ids` (sorted), the way is clipped to these nodes, so edges with an endpoint
+/// outside the bounding-box are dropped.
+/// Ways without any edge within the bounding-box are skipped, as if they weren't in the map-file.
+pub(super) fn insert_way(
+    way: &Way,
+    builder: &mut EdgeBuilder,
+    bbox_node_ids: Option<&[i64]>,
+    stats: &mut super::ParseStats,
+) -> err::Feedback {
+    if way.nodes.len() < 2 {
+        return Ok(());
+    }
+
+    let is_in_bbox =
+        |id: i64| bbox_node_ids.map_or(true, |node_ids| node_ids.binary_search(&id).is_ok());
+    if bbox_node_ids.is_some()
+        && !way
+            .nodes
+            .windows(2)
+            .any(|ids| is_in_bbox(ids[0].0) && is_in_bbox(ids[1].0))
+    {
+        return Ok(());
+    }
+
+    // collect relevant data from file, if way-type is as expected by user
+    let highway_tag = match StreetCategory::from(way) {
+        Some(highway_tag) => highway_tag,
+        None => return Ok(()),
+    };
+    let is_accepted = highway_tag.is_for(
+        &builder.cfg().vehicles.category,
+        builder.cfg().vehicles.are_drivers_picky,
+    );
+    stats.add_way(&highway_tag, is_accepted);
+    if !is_accepted {
+        return Ok(());
+    }
+
+    // get nodes of way to create proto-edges later
+    let (is_oneway, is_reverse) = highway_tag.parse_oneway(way);
+    let node_ids: Vec<i64> = way.nodes.iter().map(|id| id.0).collect();
+    let mut segments = directed_segments(&node_ids, is_oneway, is_reverse);
+    debug_assert!(
+        !is_oneway || segments.len() == node_ids.len() - 1,
+        "Oneway-way {} should emit exactly one edge per segment.",
+        way.id.0
+    );
+    debug_assert!(
+        !(is_oneway && is_reverse)
+            || segments.iter().all(|&(src_id, dst_id)| {
+                // a way may visit a segment in both directions
+                !node_ids.windows(2).any(|w| w == [src_id, dst_id])
+                    || node_ids.windows(2).any(|w| w == [dst_id, src_id])
+            }),
+        "Reverse-oneway-way {} shouldn't emit edges in its original orientation.",
+        way.id.0
+    );
+    if bbox_node_ids.is_some() {
+        segments.retain(|&(src_id, dst_id)| is_in_bbox(src_id) && is_in_bbox(dst_id));
+    }
+
+    // Conditions are parsed once per way, and only if needed.
+    let restriction = {
+        let cfg = builder.cfg();
+        let is_needed = cfg.edges.conditional.is_keeping_conditions
+            || cfg
+                .edges
+                .metrics
+                .units
+                .contains(&edges::metrics::UnitInfo::ConditionalRestriction);
+        if is_needed {
+            highway_tag.parse_conditional_restriction(way, &cfg.vehicles.category)
+        } else {
+            None
+        }
+    };
+
+    // Cyclists are faster on separated tracks, if configured.
+    let speed_factor = {
+        let cfg = builder.cfg();
+        match (
+            &cfg.vehicles.category,
+            cfg.edges.cycleway.track_speed_factor,
+        ) {
+            (VehicleCategory::Bicycle, Some(factor)) if highway_tag.has_cycle_track(way) => factor,
+            _ => 1.0,
+        }
+    };
+
+    // Collect metrics as expected by user-config
+    // ATTENTION: A way contains multiple edges, thus be careful when adding new metrics.
+
+    let mut metrics: DimVec<_> = smallvec![];
+    // metric-indices of values, which are defaulted, because the way doesn't provide them
+    let mut defaulted_metrics: DimVec<MetricIdx> = smallvec![];
+
+    for category in builder.cfg().edges.categories.iter() {
+        match category {
+            edges::Category::Meta { info: _, id: _ } => {
+                // already checked in preprocessing
+            }
+            edges::Category::Metric { unit, id: _ } => match unit {
+                edges::metrics::UnitInfo::KilometersPerHour => {
+                    let maxspeed = match highway_tag.parse_given_maxspeed(way) {
+                        Some(maxspeed) => maxspeed,
+                        None => {
+                            defaulted_metrics.push(MetricIdx(metrics.len()));
+                            highway_tag.default_maxspeed(builder.cfg().edges.link_speed_fraction())
                         }
-                    },
-                    edges::Category::Ignored => {
-                        // already checked in preprocessing
+                    };
+                    metrics.push(*maxspeed * speed_factor);
+                }
+                edges::metrics::UnitInfo::LaneCount => {
+                    let lane_count = highway_tag.parse_lane_count(way);
+                    metrics.push(lane_count as f64);
+                }
+                edges::metrics::UnitInfo::Roughness => {
+                    let roughness =
+                        highway_tag.parse_roughness(way, &builder.cfg().edges.roughness);
+                    metrics.push(roughness);
+                }
+                edges::metrics::UnitInfo::ConditionalRestriction => {
+                    metrics.push(if restriction.is_some() { 1.0 } else { 0.0 });
+                }
+                edges::metrics::UnitInfo::CyclewayComfort => {
+                    let comfort =
+                        highway_tag.parse_cycleway_comfort(way, &builder.cfg().edges.cycleway);
+                    metrics.push(comfort);
+                }
+                edges::metrics::UnitInfo::Meters
+                | edges::metrics::UnitInfo::Kilometers
+                | edges::metrics::UnitInfo::Seconds
+                | edges::metrics::UnitInfo::Minutes
+                | edges::metrics::UnitInfo::Hours
+                | edges::metrics::UnitInfo::F64 => {
+                    // already checked in preprocessing
+                }
+            },
+            edges::Category::Ignored => {
+                // already checked in preprocessing
+            }
+        }
+    }
+
+    for (src_id, dst_id) in segments {
+        // add proto-edge to graph
+        builder.insert(ProtoEdge {
+            id: None,
+            src_id,
+            dst_id,
+            metrics: metrics.clone(),
+        })?;
+        for &metric_idx in defaulted_metrics.iter() {
+            builder.mark_defaulted(metric_idx);
+        }
+
+        if let Some(condition) = &restriction {
+            if builder.cfg().edges.conditional.is_keeping_conditions {
+                builder.insert_condition(condition.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the turn-restrictions of the configured vehicle from relations of type `restriction`
+/// while parsing ways.
+///
+/// Relations follow the ways in osm-files, hence the first two and the last two node-ids of every
+/// street are remembered until all restrictions are resolved into the node-ids of their turns.
+/// Like in the osm-wiki, the from-way and the to-way have to start or end at the via-node, and
+/// restrictions via ways are not supported.
+pub(super) struct TurnRestrictions {
+    vehicle_category: VehicleCategory,
+    way_ends: HashMap<i64, [i64; 4]>,
+    restrictions: Vec<TurnRestriction>,
+}
+
+impl TurnRestrictions {
+    pub(super) fn new(vehicle_category: VehicleCategory) -> TurnRestrictions {
+        TurnRestrictions {
+            vehicle_category,
+            way_ends: HashMap::new(),
+            restrictions: Vec::new(),
+        }
+    }
+
+    /// Pedestrians ignore turn-restrictions.
+    fn is_needed(&self) -> bool {
+        match self.vehicle_category {
+            VehicleCategory::Car | VehicleCategory::Bicycle => true,
+            VehicleCategory::Pedestrian => false,
+        }
+    }
+
+    pub(super) fn add_way(&mut self, way: &Way) {
+        if !self.is_needed() || way.nodes.len() < 2 || StreetCategory::from(way).is_none() {
+            return;
+        }
+
+        let n = way.nodes.len();
+        self.way_ends.insert(
+            way.id.0,
+            [
+                way.nodes[0].0,
+                way.nodes[1].0,
+                way.nodes[n - 2].0,
+                way.nodes[n - 1].0,
+            ],
+        );
+    }
+
+    /// A relation with multiple from-ways (e.g. `no_entry`) or to-ways (e.g. `no_exit`) restricts
+    /// every combination of them.
+    pub(super) fn add_relation(&mut self, relation: &Relation) {
+        if !self.is_needed() || relation.tags.get("type").map(String::as_str) != Some("restriction")
+        {
+            return;
+        }
+
+        // a vehicle-specific restriction has precedence
+        let (keys, exception): (&[&str], &str) = match self.vehicle_category {
+            VehicleCategory::Car => (&["restriction:motorcar", "restriction"], "motorcar"),
+            VehicleCategory::Bicycle => (&["restriction:bicycle", "restriction"], "bicycle"),
+            VehicleCategory::Pedestrian => return,
+        };
+        let restriction = match keys
+            .iter()
+            .find_map(|&key| relation.tags.get(key))
+            .and_then(|value| TurnRestrictionKind::from_tag(value))
+        {
+            Some(restriction) => restriction,
+            None => return,
+        };
+        if let Some(except) = relation.tags.get("except") {
+            if except.split(';').any(|value| value.trim() == exception) {
+                return;
+            }
+        }
+
+        let mut from_ways = Vec::new();
+        let mut via_nodes = Vec::new();
+        let mut to_ways = Vec::new();
+        for member in relation.refs.iter() {
+            match (member.role.as_str(), member.member) {
+                ("from", OsmId::Way(way_id)) => from_ways.push(way_id.0),
+                ("via", OsmId::Node(node_id)) => via_nodes.push(node_id.0),
+                ("to", OsmId::Way(way_id)) => to_ways.push(way_id.0),
+                // restrictions via ways are not supported
+                ("via", _) => return,
+                _ => (),
+            }
+        }
+        if via_nodes.len() != 1 {
+            return;
+        }
+
+        for &from_way in from_ways.iter() {
+            for &to_way in to_ways.iter() {
+                self.restrictions.push(TurnRestriction {
+                    from_way,
+                    via_node: via_nodes[0],
+                    to_way,
+                    restriction,
+                });
+            }
+        }
+    }
+
+    /// Returns the node-ids `(from-src, via, to-dst)` of the restricted turn, if both ways start
+    /// or end at the via-node.
+    fn resolve(&self, turn_restriction: &TurnRestriction) -> Option<(i64, i64, i64)> {
+        let via_id = turn_restriction.via_node;
+        let from_ends = self.way_ends.get(&turn_restriction.from_way)?;
+        let to_ends = self.way_ends.get(&turn_restriction.to_way)?;
+
+        let from_src_id = if from_ends[3] == via_id {
+            from_ends[2]
+        } else if from_ends[0] == via_id {
+            from_ends[1]
+        } else {
+            return None;
+        };
+        let to_dst_id = if to_ends[0] == via_id {
+            to_ends[1]
+        } else if to_ends[3] == via_id {
+            to_ends[2]
+        } else {
+            return None;
+        };
+        Some((from_src_id, via_id, to_dst_id))
+    }
+
+    /// Inserts all resolvable restrictions into the builder.
+    ///
+    /// With `bbox_node_ids` (sorted), restrictions of turns leaving the bounding-box are skipped.
+    pub(super) fn insert_into(self, builder: &mut EdgeBuilder, bbox_node_ids: Option<&[i64]>) {
+        let mut resolved_count = 0;
+        for turn_restriction in self.restrictions.iter() {
+            if let Some((from_src_id, via_id, to_dst_id)) = self.resolve(turn_restriction) {
+                if let Some(node_ids) = bbox_node_ids {
+                    if [from_src_id, via_id, to_dst_id]
+                        .iter()
+                        .any(|id| node_ids.binary_search(id).is_err())
+                    {
+                        continue;
                     }
                 }
+                builder.insert_turn_restriction(from_src_id, via_id, to_dst_id, *turn_restriction);
+                resolved_count += 1;
             }
+        }
+        info!(
+            "Resolved {} of {} turn-restrictions.",
+            resolved_count,
+            self.restrictions.len()
+        );
+    }
+}
 
-            // for n nodes in a way, you can create (n-1) edges
-            for node_idx in 0..(nodes.len() - 1) {
-                // add proto-edge to graph
-                builder.insert(ProtoEdge {
-                    id: None,
-                    src_id: nodes[node_idx],
-                    dst_id: nodes[node_idx + 1],
-                    metrics: metrics.clone(),
-                })?;
+/// Inserts all nodes, which are part of an edge, where every node is given with the value of
+/// its `ele`-tag, if any.
+/// If an elevation is configured, it is taken from the ele-tag, an srtm-tile or the default.
+pub(super) fn insert_nodes<I>(proto_nodes: I, builder: &mut NodeBuilder) -> err::Feedback
+where
+    I: IntoIterator<Item = err::Result<(ProtoNode, Option<f64>)>>,
+{
+    let elevation_cfg = builder.cfg().elevation.clone();
+    match elevation_cfg {
+        Some(elevation_cfg) => {
+            let mut srtm_reader = elevation_cfg.srtm_dir.as_ref().map(SrtmReader::new);
+            // nodes with elevation from (ele-tag, srtm-tile, default)
+            let mut counts = (0, 0, 0);
+            for proto_node in proto_nodes {
+                let (mut proto_node, ele_tag) = proto_node?;
+                // Only nodes of edges are looked up in tiles.
+                if !builder.contains(proto_node.id) {
+                    continue;
+                }
+
+                let elevation = match ele_tag {
+                    Some(elevation) => {
+                        counts.0 += 1;
+                        elevation
+                    }
+                    None => {
+                        let srtm_elevation = match &mut srtm_reader {
+                            Some(srtm_reader) => srtm_reader.elevation(&proto_node.coord)?,
+                            None => None,
+                        };
+                        match srtm_elevation {
+                            Some(elevation) => {
+                                counts.1 += 1;
+                                elevation
+                            }
+                            None if elevation_cfg.is_requiring_elevation => {
+                                return Err(missing_elevation_msg(
+                                    &proto_node,
+                                    srtm_reader.as_ref(),
+                                ))
+                            }
+                            None => {
+                                counts.2 += 1;
+                                elevation_cfg.default
+                            }
+                        }
+                    }
+                };
+                proto_node.customs.push(elevation);
+                builder.insert(proto_node);
+            }
+            info!(
+                "Took elevations of {} nodes from ele-tags, of {} nodes from srtm-tiles \
+                 and defaulted {} nodes.",
+                counts.0, counts.1, counts.2
+            );
+        }
+        None => {
+            for proto_node in proto_nodes {
+                let (proto_node, _ele_tag) = proto_node?;
+                // add node to graph if it's part of an edge
+                builder.insert(proto_node);
             }
         }
-        info!("FINISHED");
-        Ok(())
     }
 
-    fn parse_nodes(&self, builder: &mut NodeBuilder) -> err::Feedback {
-        info!("START Create nodes from input-file.");
-        let cfg = builder.cfg();
+    Ok(())
+}
 
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&cfg.map_file)
-            .expect(&format!("Couldn't open {}", cfg.map_file.display()));
-        for node in OsmPbfReader::new(file)
-            .par_iter()
-            .filter_map(Result::ok)
-            .filter_map(|obj| match obj {
-                OsmObj::Node(node) => Some(node),
-                _ => None,
+/// Parses the value of an `ele`-tag in meters, which may have the unit `m`, e.g. `432.5 m`.
+pub(super) fn parse_ele_tag(ele: &str) -> Option<f64> {
+    let ele = ele.trim();
+    let ele = ele.strip_suffix('m').unwrap_or(ele).trim();
+    match ele.parse::<f64>() {
+        Ok(elevation) if elevation.is_finite() => Some(elevation),
+        _ => None,
+    }
+}
+
+fn missing_elevation_msg(proto_node: &ProtoNode, srtm_reader: Option<&SrtmReader>) -> err::Msg {
+    let reason = match srtm_reader {
+        Some(srtm_reader) => format!(
+            "no SRTM-tile of {:?} in {} covers it (or its samples are voids)",
+            SrtmReader::tile_names_of(&proto_node.coord),
+            srtm_reader.dir().display()
+        ),
+        None => String::from("no srtm-dir is configured"),
+    };
+    format!(
+        "Node {} at ({}, {}) has no ele-tag and {}, but elevations are required.",
+        proto_node.id, proto_node.coord.lat, proto_node.coord.lon, reason
+    )
+    .into()
+}
+
+/// Returns the directed (src-id, dst-id) of every edge, a way with the given node-ids consists of.
+///
+/// For n nodes, a oneway-way has (n-1) edges, either in the way's orientation or, if reversed,
+/// against it.
+/// Other ways have (n-1) edges in each direction, with backward-edges following forward-edges
+/// (a->b->c->b->a), since the edge-builder expects consecutive edges to share nodes.
+fn directed_segments(node_ids: &[i64], is_oneway: bool, is_reverse: bool) -> Vec<(i64, i64)> {
+    let fwd_segments = node_ids.windows(2).map(|w| (w[0], w[1]));
+    let bwd_segments = node_ids.windows(2).rev().map(|w| (w[1], w[0]));
+
+    match (is_oneway, is_reverse) {
+        (true, false) => fwd_segments.collect(),
+        (true, true) => bwd_segments.collect(),
+        (false, _) => fwd_segments.chain(bwd_segments).collect(),
+    }
+}
+
+/// Decodes the blocks of the given pbf-file with `num_threads` threads and returns the decoded
+/// objects in file-order, independent of which thread has finished first.
+///
+/// Blobs are read by an own thread and distributed to the decoding threads, which tag their
+/// results with the blob's sequence-index.
+/// Since the graph-builder expects consecutive edges of a way (and deterministic edge-indices),
+/// the returned iterator buffers early blocks until their predecessors have been received.
+///
+/// Blobs or blocks, which can't be read, and panicking threads are returned as error in place of
+/// the missing objects, so a corrupt file doesn't lead to a silently truncated graph.
+/// After an error, the iterator is finished.
+fn decode_blocks<T, F>(file: File, num_threads: usize, decode: F) -> DecodedBlocks<T>
+where
+    T: Send + 'static,
+    F: Fn(&PrimitiveBlock) -> Vec<T> + Send + Sync + 'static,
+{
+    let num_threads = num_threads.max(1);
+    let decode = Arc::new(decode);
+    // Both channels are bounded to not read the whole file into memory when decoding is slower
+    // than reading, or building is slower than decoding.
+    let (blob_tx, blob_rx) = mpsc::sync_channel(2 * num_threads);
+    let blob_rx = Arc::new(Mutex::new(blob_rx));
+    let (decoded_tx, decoded_rx) = mpsc::sync_channel(2 * num_threads);
+    // Blocks, which are decoded before their (slow) predecessors, wait for reordering.
+    // Hence the reader doesn't read blobs beyond this window, so they don't pile up in memory.
+    let window = Arc::new(ReorderWindow::new(2 * num_threads));
+
+    // returns the number of read blobs
+    let reader_window = Arc::clone(&window);
+    let reader_handle = thread::spawn(move || -> Result<usize, String> {
+        let mut reader = OsmPbfReader::new(file);
+        let mut blob_count = 0;
+        for blob in reader.blobs() {
+            let blob = blob.map_err(|e| {
+                format!(
+                    "Couldn't read blob {} of the pbf-file due to error: {}",
+                    blob_count, e
+                )
+            })?;
+            // the consumer is gone if it has stopped early
+            if !reader_window.wait_for(blob_count) || blob_tx.send((blob_count, blob)).is_err() {
+                break;
+            }
+            blob_count += 1;
+        }
+        Ok(blob_count)
+    });
+
+    let decoder_handles = (0..num_threads)
+        .map(|_| {
+            let blob_rx = Arc::clone(&blob_rx);
+            let decoded_tx = decoded_tx.clone();
+            let decode = Arc::clone(&decode);
+            thread::spawn(move || loop {
+                let job = match blob_rx.lock() {
+                    Ok(blob_rx) => blob_rx.recv(),
+                    // another decoding-thread has panicked, which is reported when joining it
+                    Err(_) => break,
+                };
+                let (seq_idx, blob) = match job {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+                let objs = primitive_block_from_blob(&blob)
+                    .map(|block| decode(&block))
+                    .map_err(|e| {
+                        format!(
+                            "Couldn't decode block {} of the pbf-file due to error: {}",
+                            seq_idx, e
+                        )
+                    });
+                if decoded_tx.send((seq_idx, objs)).is_err() {
+                    break;
+                }
             })
-        {
-            // add node to graph if it's part of an edge
-            builder.insert(ProtoNode {
-                id: node.id.0,
-                coord: Coordinate::from_decimicro(node.decimicro_lat, node.decimicro_lon),
-                ch_level: None,
-            });
+        })
+        .collect();
+
+    DecodedBlocks {
+        decoded_rx,
+        window,
+        reader_handle: Some(reader_handle),
+        decoder_handles,
+        pending: BTreeMap::new(),
+        next_seq_idx: 0,
+        current: Vec::new().into_iter(),
+        is_done: false,
+    }
+}
+
+/// Limits the blocks, which are read, but not consumed yet, to a fixed number of sequence-indices
+/// after the next consumed block (see `decode_blocks(...)`).
+struct ReorderWindow {
+    size: usize,
+    /// (next consumed sequence-index, is consumer gone)
+    state: Mutex<(usize, bool)>,
+    changed: Condvar,
+}
+
+impl ReorderWindow {
+    fn new(size: usize) -> ReorderWindow {
+        ReorderWindow {
+            size,
+            state: Mutex::new((0, false)),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Blocks until the given sequence-index is within the window and returns false, if the
+    /// consumer is gone.
+    fn wait_for(&self, seq_idx: usize) -> bool {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return false,
+        };
+        loop {
+            let (next_seq_idx, is_closed) = *state;
+            if is_closed {
+                return false;
+            }
+            if seq_idx < next_seq_idx + self.size {
+                return true;
+            }
+            state = match self.changed.wait(state) {
+                Ok(state) => state,
+                Err(_) => return false,
+            };
+        }
+    }
+
+    fn advance_to(&self, next_seq_idx: usize) {
+        if let Ok(mut state) = self.state.lock() {
+            state.0 = next_seq_idx;
+        }
+        self.changed.notify_all();
+    }
+
+    fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.1 = true;
+        }
+        self.changed.notify_all();
+    }
+}
+
+/// See `decode_blocks(...)`
+struct DecodedBlocks<T> {
+    decoded_rx: mpsc::Receiver<(usize, Result<Vec<T>, String>)>,
+    window: Arc<ReorderWindow>,
+    reader_handle: Option<thread::JoinHandle<Result<usize, String>>>,
+    decoder_handles: Vec<thread::JoinHandle<()>>,
+    /// decoded blocks, which have been received before their predecessors
+    pending: BTreeMap<usize, Result<Vec<T>, String>>,
+    next_seq_idx: usize,
+    current: vec::IntoIter<T>,
+    is_done: bool,
+}
+
+impl<T> DecodedBlocks<T> {
+    /// Joins all threads after every decoded block has been received and returns an error, if a
+    /// thread has failed or a block is missing.
+    fn finish(&mut self) -> err::Feedback {
+        let mut has_panicked = false;
+        for handle in self.decoder_handles.drain(..) {
+            has_panicked |= handle.join().is_err();
+        }
+        if has_panicked {
+            return Err(format!(
+                "A pbf-decoding-thread has panicked before block {} has been decoded.",
+                self.next_seq_idx
+            )
+            .into());
+        }
+
+        let blob_count = match self.reader_handle.take().map(thread::JoinHandle::join) {
+            Some(Ok(Ok(blob_count))) => blob_count,
+            Some(Ok(Err(msg))) => return Err(msg.into()),
+            Some(Err(_)) => return Err("The pbf-reading-thread has panicked.".into()),
+            None => return Ok(()),
+        };
+        if self.next_seq_idx != blob_count {
+            return Err(format!(
+                "Only {} of {} blocks of the pbf-file have been decoded.",
+                self.next_seq_idx, blob_count
+            )
+            .into());
         }
-        info!("FINISHED");
         Ok(())
     }
 }
+
+impl<T> Drop for DecodedBlocks<T> {
+    fn drop(&mut self) {
+        // a reader waiting for the window would never stop otherwise
+        self.window.close();
+    }
+}
+
+impl<T> Iterator for DecodedBlocks<T> {
+    type Item = err::Result<T>;
+
+    fn next(&mut self) -> Option<err::Result<T>> {
+        loop {
+            if let Some(obj) = self.current.next() {
+                return Some(Ok(obj));
+            }
+            if self.is_done {
+                return None;
+            }
+
+            let objs = loop {
+                if let Some(objs) = self.pending.remove(&self.next_seq_idx) {
+                    break objs;
+                }
+                match self.decoded_rx.recv() {
+                    Ok((seq_idx, objs)) => {
+                        self.pending.insert(seq_idx, objs);
+                    }
+                    // All threads are done, so every block should have been returned.
+                    Err(_) => {
+                        self.is_done = true;
+                        return match self.finish() {
+                            Ok(()) => None,
+                            Err(msg) => Some(Err(msg)),
+                        };
+                    }
+                }
+            };
+            match objs {
+                Ok(objs) => {
+                    self.next_seq_idx += 1;
+                    self.window.advance_to(self.next_seq_idx);
+                    self.current = objs.into_iter();
+                }
+                Err(msg) => {
+                    self.is_done = true;
+                    return Some(Err(msg.into()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{defaults::parsing::scanning as defaults, helpers::err};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+/// Counts of ways, which are accepted or rejected for the configured vehicle-category.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct WayCounts {
+    pub accepted: usize,
+    pub rejected: usize,
+}
+
+impl WayCounts {
+    pub fn total(&self) -> usize {
+        self.accepted + self.rejected
+    }
+}
+
+/// Result of scanning the ways of a pbf-file without building a graph.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct WayScan {
+    pub num_of_ways: usize,
+    /// `tag -> (value -> count)` for every tag in `defaults::parsing::scanning::TAGS`
+    pub histograms: BTreeMap<String, BTreeMap<String, usize>>,
+    /// `street-category -> counts`, where unknown categories are collected under `defaults::parsing::scanning::UNKNOWN_CATEGORY`
+    pub categories: BTreeMap<String, WayCounts>,
+}
+
+impl WayScan {
+    pub fn new() -> WayScan {
+        WayScan {
+            num_of_ways: 0,
+            histograms: defaults::TAGS
+                .iter()
+                .map(|tag| (String::from(*tag), BTreeMap::new()))
+                .collect(),
+            categories: BTreeMap::new(),
+        }
+    }
+
+    pub fn histogram(&self, tag: &str) -> Option<&BTreeMap<String, usize>> {
+        self.histograms.get(tag)
+    }
+
+    pub fn num_of_accepted(&self) -> usize {
+        self.categories.values().map(|counts| counts.accepted).sum()
+    }
+
+    pub fn num_of_rejected(&self) -> usize {
+        self.categories.values().map(|counts| counts.rejected).sum()
+    }
+
+    pub fn try_to_json(&self) -> err::Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            err::Msg::from(format!(
+                "Serde couldn't serialize way-scan due to error: {}",
+                e
+            ))
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        match self.try_to_json() {
+            Ok(json) => json,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    pub(super) fn add_tag(&mut self, tag: &str, value: &str) {
+        if let Some(histogram) = self.histograms.get_mut(tag) {
+            *histogram.entry(String::from(value)).or_insert(0) += 1;
+        }
+    }
+
+    pub(super) fn add_way(&mut self, category: Option<String>, is_accepted: bool) {
+        self.num_of_ways += 1;
+
+        let counts = self
+            .categories
+            .entry(category.unwrap_or(String::from(defaults::UNKNOWN_CATEGORY)))
+            .or_insert_with(WayCounts::default);
+        if is_accepted {
+            counts.accepted += 1;
+        } else {
+            counts.rejected += 1;
+        }
+    }
+}
+
+impl Display for WayScan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "ways: {} (accepted: {}, rejected: {})",
+            self.num_of_ways,
+            self.num_of_accepted(),
+            self.num_of_rejected()
+        )?;
+
+        // most frequent values first
+        for (tag, histogram) in &self.histograms {
+            let mut values: Vec<_> = histogram.iter().collect();
+            values.sort_by(|(_, a), (_, b)| b.cmp(a));
+            write!(f, "{}: {} values", tag, values.len())?;
+            for (value, count) in values.iter().take(defaults::DISPLAYED_VALUES) {
+                write!(f, ", {}={}", value, count)?;
+            }
+            if values.len() > defaults::DISPLAYED_VALUES {
+                write!(f, ", ...")?;
+            }
+            writeln!(f, "")?;
+        }
+
+        write!(f, "categories (accepted/rejected):")?;
+        for (category, counts) in &self.categories {
+            write!(f, " {}={}/{}", category, counts.accepted, counts.rejected)?;
+        }
+        Ok(())
+    }
+}
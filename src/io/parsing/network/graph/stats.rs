@@ -0,0 +1,85 @@
+use super::WayCounts;
+use crate::network::StreetCategory;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+/// Statistics of parsing a graph, e.g. to compare graphs parsed with different vehicle-configs
+/// (see `Parser::parse_and_finalize_with_stats(...)`).
+///
+/// Only pbf- and osm-files filter their ways by the configured vehicle, hence the way-counts of
+/// fmi-files are empty.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ParseStats {
+    /// `street-category -> counts` of ways, which have been accepted or rejected by the
+    /// configured vehicle (see `vehicles.category` and `vehicles.are_drivers_picky`)
+    pub categories: BTreeMap<String, WayCounts>,
+    /// number of node-ids, which have been parsed with differing coordinates and resolved when
+    /// finalizing (see `on-node-conflict`)
+    pub num_of_coord_conflicts: usize,
+}
+
+impl ParseStats {
+    pub fn new() -> ParseStats {
+        ParseStats::default()
+    }
+
+    /// Returns zero counts, if no way of this category has been seen.
+    pub fn counts(&self, category: &StreetCategory) -> WayCounts {
+        self.categories
+            .get(&category.to_string())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn num_of_accepted(&self) -> usize {
+        self.categories.values().map(|counts| counts.accepted).sum()
+    }
+
+    pub fn num_of_rejected(&self) -> usize {
+        self.categories.values().map(|counts| counts.rejected).sum()
+    }
+
+    pub(super) fn add_way(&mut self, category: &StreetCategory, is_accepted: bool) {
+        let counts = self
+            .categories
+            .entry(category.to_string())
+            .or_insert_with(WayCounts::default);
+        if is_accepted {
+            counts.accepted += 1;
+        } else {
+            counts.rejected += 1;
+        }
+    }
+}
+
+impl Display for ParseStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>16} {:>10} {:>10}",
+            "street-category", "accepted", "rejected"
+        )?;
+        for (category, counts) in &self.categories {
+            writeln!(
+                f,
+                "{:>16} {:>10} {:>10}",
+                category, counts.accepted, counts.rejected
+            )?;
+        }
+        writeln!(
+            f,
+            "{:>16} {:>10} {:>10}",
+            "total",
+            self.num_of_accepted(),
+            self.num_of_rejected()
+        )?;
+        write!(
+            f,
+            "{:>16} {:>10}",
+            "coord-conflicts", self.num_of_coord_conflicts
+        )
+    }
+}
@@ -1,22 +1,222 @@
-use crate::{configs, helpers::err, io::SupportingFileExts, network::RoutePair};
-use log::info;
+use crate::{
+    configs,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{Graph, RoutePair},
+};
+use kissunits::{distance::Kilometers, geo::Coordinate};
+use log::{info, warn};
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
 
 mod routes;
+pub use routes::Stream;
+
+/// Route-pairs-files contain node-ids by default.
+/// With the header-line `# route-pairs-format: coordinates`, they contain coordinates, which are
+/// snapped to their nearest node (see `Parser::parse_and_snap(...)`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    NodeIds,
+    Coordinates,
+}
+
+impl Format {
+    fn try_from_str(s: &str) -> err::Result<Format> {
+        match s.trim() {
+            "node-ids" => Ok(Format::NodeIds),
+            "coordinates" => Ok(Format::Coordinates),
+            s => Err(format!(
+                "Unknown route-pairs-format {}, expected 'node-ids' or 'coordinates'.",
+                s
+            )
+            .into()),
+        }
+    }
+}
+
+/// Errors of parsing route-pairs, where a missing route-pairs-file is distinguished from other
+/// errors, since it is a matter of the routing-config rather than of the file.
+#[derive(Debug)]
+pub enum Error {
+    /// The routing-config has no `route-pairs-file`.
+    NoRoutePairsConfigured,
+    Msg(err::Msg),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoRoutePairsConfigured => {
+                write!(f, "The routing-config doesn't specify a route-pairs-file.")
+            }
+            Error::Msg(msg) => msg.fmt(f),
+        }
+    }
+}
+
+impl From<err::Msg> for Error {
+    fn from(msg: err::Msg) -> Error {
+        Error::Msg(msg)
+    }
+}
+
+impl From<Error> for err::Msg {
+    fn from(e: Error) -> err::Msg {
+        match e {
+            Error::Msg(msg) => msg,
+            e => err::Msg::from(format!("{}", e)),
+        }
+    }
+}
+
+/// Returns the config's route-pairs-file, or `Error::NoRoutePairsConfigured`.
+fn route_pairs_file(cfg: &configs::routing::Config) -> Result<&PathBuf, Error> {
+    cfg.route_pairs_file
+        .as_ref()
+        .ok_or(Error::NoRoutePairsConfigured)
+}
+
+enum ProtoRoutePairs {
+    NodeIds(Vec<(RoutePair<i64>, f64)>),
+    Coordinates(Vec<(RoutePair<Coordinate>, f64)>),
+}
 
 pub struct Parser;
 
 impl Parser {
-    pub fn parse(cfg: &configs::routing::Config) -> err::Result<Vec<(RoutePair<i64>, usize)>> {
-        let route_pairs_file = cfg
-            .route_pairs_file
-            .as_ref()
-            .expect("No routes-file specified.");
+    /// Route-pairs given by coordinates need a graph, hence use `parse_and_snap(...)` for them.
+    ///
+    /// Without route-pairs-file in the config, `Error::NoRoutePairsConfigured` is returned.
+    /// Empty route-pairs-files (or files containing only whitespace) result in no route-pairs
+    /// and a warning.
+    pub fn parse(cfg: &configs::routing::Config) -> Result<Vec<(RoutePair<i64>, f64)>, Error> {
+        match Parser::parse_proto(cfg)? {
+            ProtoRoutePairs::NodeIds(route_pairs) => Ok(route_pairs),
+            ProtoRoutePairs::Coordinates(_) => Err(Error::Msg(err::Msg::from(
+                "The route-pairs are given by coordinates, which need a graph to be snapped to.",
+            ))),
+        }
+    }
+
+    /// Returns the routes-count of the route-pairs-file's header without parsing the route-pairs,
+    /// e.g. for checking a route-pairs-file before parsing its graph.
+    pub fn count<P: AsRef<Path> + ?Sized>(route_pairs_file: &P) -> err::Result<usize> {
+        let route_pairs_file = route_pairs_file.as_ref();
+        match Parser::find_supported_ext(route_pairs_file) {
+            Ok(_) => routes::Parser::new().count(route_pairs_file),
+            Err(msg) => Err(format!("Wrong parser-routes-file: {}", msg).into()),
+        }
+    }
+
+    /// Reads route-pairs given by node-ids lazily, so even huge route-pairs-files (e.g. national
+    /// demand-matrices) don't have to fit into memory.
+    /// The stream's total count is taken from the file's header.
+    ///
+    /// Route-pairs given by coordinates can't be streamed, since snapping needs a graph.
+    pub fn stream(cfg: &configs::routing::Config) -> err::Result<Stream> {
+        let route_pairs_file = route_pairs_file(cfg)?;
 
         match Parser::find_supported_ext(route_pairs_file) {
-            Ok(_) => routes::Parser::new().parse(cfg),
+            Ok(_) => routes::Parser::new().stream(cfg),
             Err(msg) => Err(format!("Wrong parser-routes-file: {}", msg).into()),
         }
     }
+
+    /// Parses route-pairs of any format.
+    /// Coordinates are snapped to their nearest node, but pairs with a coordinate farther away
+    /// than the config's max-snap-distance are rejected with a warning.
+    pub fn parse_and_snap(
+        cfg: &configs::routing::Config,
+        graph: &Graph,
+    ) -> err::Result<Vec<(RoutePair<i64>, f64)>> {
+        match Parser::parse_proto(cfg)? {
+            ProtoRoutePairs::NodeIds(route_pairs) => Ok(route_pairs),
+            ProtoRoutePairs::Coordinates(route_pairs) => {
+                Ok(snap(route_pairs, graph, cfg.max_snap_distance))
+            }
+        }
+    }
+
+    fn parse_proto(cfg: &configs::routing::Config) -> Result<ProtoRoutePairs, Error> {
+        let route_pairs_file = route_pairs_file(cfg)?;
+
+        match Parser::find_supported_ext(route_pairs_file) {
+            Ok(_) => Ok(routes::Parser::new().parse(cfg)?),
+            Err(msg) => Err(Error::Msg(
+                format!("Wrong parser-routes-file: {}", msg).into(),
+            )),
+        }
+    }
+}
+
+fn snap(
+    route_pairs: Vec<(RoutePair<Coordinate>, f64)>,
+    graph: &Graph,
+    max_snap_distance: Kilometers,
+) -> Vec<(RoutePair<i64>, f64)> {
+    info!(
+        "START Snap coordinates of {} route-pairs to their nearest nodes.",
+        route_pairs.len()
+    );
+    let nodes = graph.nodes();
+
+    let mut snapped_route_pairs = Vec::with_capacity(route_pairs.len());
+    let mut max_distance = 0.0;
+    let mut distance_sum = 0.0;
+    for (RoutePair { src, dst }, count) in route_pairs {
+        let snapped_src = graph.nearest_node_idx(&src);
+        let snapped_dst = graph.nearest_node_idx(&dst);
+        let ((src_idx, src_distance), (dst_idx, dst_distance)) = match (snapped_src, snapped_dst) {
+            (Some(snapped_src), Some(snapped_dst)) => (snapped_src, snapped_dst),
+            _ => {
+                warn!("The graph has no nodes to snap route-pairs to.");
+                break;
+            }
+        };
+
+        if *src_distance > *max_snap_distance || *dst_distance > *max_snap_distance {
+            warn!(
+                "Route-pair from ({}, {}) to ({}, {}) is rejected, since it's snapped by \
+                 {} km and {} km, but at most {} km are allowed.",
+                src.lat,
+                src.lon,
+                dst.lat,
+                dst.lon,
+                *src_distance,
+                *dst_distance,
+                *max_snap_distance
+            );
+            continue;
+        }
+
+        for distance in &[*src_distance, *dst_distance] {
+            distance_sum += distance;
+            if *distance > max_distance {
+                max_distance = *distance;
+            }
+        }
+        snapped_route_pairs.push((
+            RoutePair {
+                src: nodes.id(src_idx),
+                dst: nodes.id(dst_idx),
+            },
+            count,
+        ));
+    }
+
+    if snapped_route_pairs.len() > 0 {
+        info!(
+            "Snapped {} route-pairs by {} km on average and by {} km at most.",
+            snapped_route_pairs.len(),
+            distance_sum / (2 * snapped_route_pairs.len()) as f64,
+            max_distance
+        );
+    }
+    info!("FINISHED");
+    snapped_route_pairs
 }
 
 impl SupportingFileExts for Parser {
@@ -27,10 +227,7 @@ impl SupportingFileExts for Parser {
 
 trait Parsing {
     fn preprocess(&mut self, cfg: &configs::routing::Config) -> err::Feedback {
-        let route_pairs_file = cfg
-            .route_pairs_file
-            .as_ref()
-            .expect("No routes-file specified.");
+        let route_pairs_file = route_pairs_file(cfg)?;
 
         match Parser::find_supported_ext(route_pairs_file) {
             Ok(_) => (),
@@ -40,15 +237,9 @@ trait Parsing {
         Ok(())
     }
 
-    fn parse_route_pairs(
-        &self,
-        cfg: &configs::routing::Config,
-    ) -> Result<Vec<(RoutePair<i64>, usize)>, String>;
+    fn parse_route_pairs(&self, cfg: &configs::routing::Config) -> err::Result<ProtoRoutePairs>;
 
-    fn parse(
-        &mut self,
-        cfg: &configs::routing::Config,
-    ) -> err::Result<Vec<(RoutePair<i64>, usize)>> {
+    fn parse(&mut self, cfg: &configs::routing::Config) -> err::Result<ProtoRoutePairs> {
         info!("DO Parse route-pairs");
         self.preprocess(cfg)?;
         let routes = self.parse_route_pairs(cfg)?;
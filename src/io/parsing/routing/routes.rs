@@ -1,45 +1,245 @@
+use super::{Format, Parsing, ProtoRoutePairs};
 use crate::{
-    configs,
+    configs, defaults,
     helpers::{self, err},
     network::RoutePair,
 };
+use kissunits::geo::Coordinate;
+use log::warn;
 use std::{
-    fs::OpenOptions,
-    io::{BufRead, BufReader},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Lines},
     ops::Range,
+    path::Path,
 };
 
 pub struct Parser {
     route_lines: Range<usize>,
+    format: Format,
 }
 
 impl Parser {
     pub fn new() -> Parser {
-        Parser { route_lines: 1..0 }
+        Parser {
+            route_lines: 1..0,
+            format: Format::NodeIds,
+        }
+    }
+
+    /// Reads only the header and returns its routes-count.
+    pub fn count(mut self, route_pairs_file: &Path) -> err::Result<usize> {
+        self.read_header(route_pairs_file)?;
+        Ok(self.route_lines.len())
+    }
+
+    /// Reads the header (format and routes-count) and returns the route-lines as lazy stream.
+    pub fn stream(mut self, cfg: &configs::routing::Config) -> err::Result<Stream> {
+        self.preprocess(cfg)?;
+        if self.format != Format::NodeIds {
+            return Err(err::Msg::from(
+                "Only route-pairs given by node-ids can be streamed, \
+                 since coordinates need a graph to be snapped to.",
+            ));
+        }
+
+        let route_pairs_file = super::route_pairs_file(cfg)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .open(route_pairs_file)
+            .map_err(|e| {
+                format!(
+                    "Couldn't open {} due to error: {}",
+                    route_pairs_file.display(),
+                    e
+                )
+            })?;
+
+        Ok(Stream {
+            lines: BufReader::new(file).lines(),
+            route_lines: self.route_lines,
+            line_number: 0,
+        })
+    }
+}
+
+/// Route-pairs, which are read line by line, so only one route-line is in memory at once.
+///
+/// The file has been scanned for its header before, hence the total count is known upfront.
+pub struct Stream {
+    lines: Lines<BufReader<File>>,
+    route_lines: Range<usize>,
+    /// number of functional lines read so far
+    line_number: usize,
+}
+
+impl Stream {
+    /// Returns the routes-count given in the file's header.
+    pub fn total_count(&self) -> usize {
+        self.route_lines.len()
+    }
+}
+
+impl Iterator for Stream {
+    type Item = err::Result<(RoutePair<i64>, f64)>;
+
+    fn next(&mut self) -> Option<err::Result<(RoutePair<i64>, f64)>> {
+        while self.line_number < self.route_lines.end {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => {
+                    // stop streaming after an error
+                    self.line_number = self.route_lines.end;
+                    return Some(Err(err::Msg::from(format!(
+                        "Couldn't read route-line due to error: {}",
+                        e
+                    ))));
+                }
+            };
+            if !helpers::is_line_functional(&line) {
+                continue;
+            }
+
+            // check if line contains route
+            let line_number = self.line_number;
+            self.line_number += 1;
+            if !self.route_lines.contains(&line_number) {
+                continue;
+            }
+
+            let params: Vec<&str> = line.split_whitespace().collect();
+            return Some(parse_id_params(&params).map_err(err::Msg::from));
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // the file could end early
+        let remaining = self.route_lines.end - self.line_number.max(self.route_lines.start);
+        (0, Some(remaining))
     }
 }
 
 impl super::Parsing for Parser {
     fn preprocess(&mut self, cfg: &configs::routing::Config) -> err::Feedback {
-        let route_pairs_file = cfg
-            .route_pairs_file
-            .as_ref()
-            .expect("No routes-file specified.");
+        self.read_header(super::route_pairs_file(cfg)?)
+    }
 
+    fn parse_route_pairs(&self, cfg: &configs::routing::Config) -> err::Result<ProtoRoutePairs> {
+        let mut id_route_pairs = Vec::new();
+        let mut coord_route_pairs = Vec::new();
+        match self.format {
+            Format::NodeIds => id_route_pairs.reserve(self.route_lines.len()),
+            Format::Coordinates => coord_route_pairs.reserve(self.route_lines.len()),
+        }
+        let route_pairs_file = super::route_pairs_file(cfg)?;
+
+        let mut line_number = 0;
+        let file = OpenOptions::new()
+            .read(true)
+            .open(route_pairs_file)
+            .map_err(|e| {
+                format!(
+                    "Couldn't open {} due to error: {}",
+                    route_pairs_file.display(),
+                    e
+                )
+            })?;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| {
+                format!(
+                    "Couldn't read {} due to error: {}",
+                    route_pairs_file.display(),
+                    e
+                )
+            })?;
+            if !helpers::is_line_functional(&line) {
+                continue;
+            }
+
+            // check if line contains route
+            if !self.route_lines.contains(&line_number) {
+                line_number += 1;
+                continue;
+            }
+            line_number += 1;
+
+            // create route
+
+            let params: Vec<&str> = line.split_whitespace().collect();
+            match self.format {
+                Format::NodeIds => id_route_pairs.push(parse_id_params(&params)?),
+                Format::Coordinates => {
+                    if params.len() != 5 {
+                        return Err(format!(
+                            "A route-line is expected to consist of \
+                             (src-lat, src-lon, dst-lat, dst-lon, count), \
+                             but {} values are provided.",
+                            params.len()
+                        )
+                        .into());
+                    }
+
+                    let src = parse_coord(params[0], params[1], "src")?;
+                    let dst = parse_coord(params[2], params[3], "dst")?;
+                    let n = parse_count(params[4])?;
+
+                    coord_route_pairs.push((RoutePair { src, dst }, n));
+                }
+            }
+        }
+
+        Ok(match self.format {
+            Format::NodeIds => ProtoRoutePairs::NodeIds(id_route_pairs),
+            Format::Coordinates => ProtoRoutePairs::Coordinates(coord_route_pairs),
+        })
+    }
+}
+
+impl Parser {
+    /// Reads the format and the routes-count, which determines the route-lines.
+    fn read_header(&mut self, route_pairs_file: &Path) -> err::Feedback {
         // only functional-lines are counted
         let mut line_number = 0;
         let mut is_taking_counts = false;
         // counts are only metric-count, node-count, edge-count (in this order)
         let mut counts = vec![];
+        // empty files (or files of whitespace only) are accepted without any route-pairs
+        let mut is_empty = true;
         let file = OpenOptions::new()
             .read(true)
             .open(route_pairs_file)
-            .expect(&format!("Couldn't open {}", route_pairs_file.display()));
-        for line in BufReader::new(file)
-            .lines()
-            .map(Result::unwrap)
-            .filter(helpers::is_line_functional)
-        {
+            .map_err(|e| {
+                format!(
+                    "Couldn't open {} due to error: {}",
+                    route_pairs_file.display(),
+                    e
+                )
+            })?;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| {
+                format!(
+                    "Couldn't read {} due to error: {}",
+                    route_pairs_file.display(),
+                    e
+                )
+            })?;
+            if !line.trim().is_empty() {
+                is_empty = false;
+            }
+
+            // The format is declared in a header-comment, which has to be read before
+            // comments are filtered out.
+            if let Some(format) = line
+                .trim()
+                .strip_prefix(defaults::routing::ROUTE_PAIRS_FORMAT_PREFIX)
+            {
+                self.format = Format::try_from_str(format)?;
+                continue;
+            }
+            if !helpers::is_line_functional(&line) {
+                continue;
+            }
+
             // If there is a count, remember it.
             // The first occuring count let `is_taking_counts` getting true.
             // If all counts have been processed, `is_taking_counts` would change to false,
@@ -58,6 +258,15 @@ impl super::Parsing for Parser {
             line_number += 1;
         }
 
+        if is_empty {
+            warn!(
+                "The routes-file {} is empty, hence it doesn't contain any route-pairs.",
+                route_pairs_file.display()
+            );
+            self.route_lines = 0..0;
+            return Ok(());
+        }
+
         // add counts
         if counts.len() < 1 {
             return Err("The provided routes-file doesn't have the routes-count.".into());
@@ -72,70 +281,71 @@ impl super::Parsing for Parser {
 
         Ok(())
     }
+}
 
-    fn parse_route_pairs(
-        &self,
-        cfg: &configs::routing::Config,
-    ) -> Result<Vec<(RoutePair<i64>, usize)>, String> {
-        let mut route_pairs = Vec::with_capacity(self.route_lines.len());
-        let route_pairs_file = cfg
-            .route_pairs_file
-            .as_ref()
-            .expect("No routes-file specified.");
-
-        let mut line_number = 0;
-        let file = OpenOptions::new()
-            .read(true)
-            .open(route_pairs_file)
-            .expect(&format!("Couldn't open {}", route_pairs_file.display()));
-        for line in BufReader::new(file)
-            .lines()
-            .map(Result::unwrap)
-            .filter(helpers::is_line_functional)
-        {
-            // check if line contains route
-            if !self.route_lines.contains(&line_number) {
-                line_number += 1;
-                continue;
-            }
-            line_number += 1;
+/// Parses a route-line of the format (src-id, dst-id, count).
+fn parse_id_params(params: &[&str]) -> Result<(RoutePair<i64>, f64), String> {
+    if params.len() != 3 {
+        return Err(format!(
+            "A route-line is expected to consist of (src-id, dst-id, count), \
+             but {} values are provided.",
+            params.len()
+        ));
+    }
 
-            // create route
+    let param = params[0];
+    let src_id = param
+        .parse::<i64>()
+        .ok()
+        .ok_or(format!("Could not parse route's src-id {}", param))?;
+    let param = params[1];
+    let dst_id = param
+        .parse::<i64>()
+        .ok()
+        .ok_or(format!("Could not parse route's dst-id {}", param))?;
+    let n = parse_count(params[2])?;
 
-            let params: Vec<&str> = line.split_whitespace().collect();
-            if params.len() != 3 {
-                return Err(format!(
-                    "A route-line is expected to consist of (src-id, dst-id, count), \
-                     but {} values are provided.",
-                    params.len()
-                ));
-            }
+    Ok((
+        RoutePair {
+            src: src_id,
+            dst: dst_id,
+        },
+        n,
+    ))
+}
 
-            let param = params[0];
-            let src_id = param
-                .parse::<i64>()
-                .ok()
-                .ok_or(format!("Could not parse route's src-id {}", param))?;
-            let param = params[1];
-            let dst_id = param
-                .parse::<i64>()
-                .ok()
-                .ok_or(format!("Could not parse route's dst-id {}", param))?;
-            let param = params[2];
-            let n = param
-                .parse::<usize>()
-                .ok()
-                .ok_or(format!("Could not parse route's count {}", param))?;
-
-            route_pairs.push((
-                RoutePair {
-                    src: src_id,
-                    dst: dst_id,
-                },
-                n,
-            ));
-        }
+fn parse_count(param: &str) -> Result<f64, String> {
+    let n = param
+        .parse::<f64>()
+        .ok()
+        .ok_or(format!("Could not parse route's count {}", param))?;
+    // counts are expected trips, hence fractional, but never negative
+    if !n.is_finite() || n < 0.0 {
+        return Err(format!(
+            "Route's count {} should be a finite, non-negative number.",
+            param
+        ));
+    }
+    Ok(n)
+}
 
-        Ok(route_pairs)
+fn parse_coord(lat: &str, lon: &str, name: &str) -> Result<Coordinate, String> {
+    let lat_value = lat
+        .parse::<f64>()
+        .ok()
+        .ok_or(format!("Could not parse route's {}-lat {}", name, lat))?;
+    let lon_value = lon
+        .parse::<f64>()
+        .ok()
+        .ok_or(format!("Could not parse route's {}-lon {}", name, lon))?;
+    if !(-90.0..=90.0).contains(&lat_value) || !(-180.0..=180.0).contains(&lon_value) {
+        return Err(format!(
+            "Route's {}-coordinate ({}, {}) is out of range.",
+            name, lat, lon
+        ));
     }
+    Ok(Coordinate {
+        lat: lat_value,
+        lon: lon_value,
+    })
 }
@@ -0,0 +1,91 @@
+use crate::{
+    configs::balancing::Config as BalancingConfig,
+    defaults::balancing::{files, BASE_ITER},
+    helpers::err,
+    io::{
+        self,
+        parsing::balancing::{checksum, read_yaml, BaseManifest, DeltaKey, Reader},
+    },
+    network::Graph,
+};
+use log::info;
+use std::{io::Write, path::Path};
+
+pub struct Writer;
+
+impl Writer {
+    /// Writes the manifest of the base-graph into the given dir, which has to contain the base's
+    /// config and map-files already.
+    /// The dir is the base's iteration-dir or its temporary dir, which is renamed to the
+    /// iteration-dir afterwards (see `io::files::AtomicDir`).
+    /// The given graph is the base-graph, which has been parsed from there.
+    pub fn write_base<P: AsRef<Path> + ?Sized>(graph: &Graph, base_dir: &P) -> err::Feedback {
+        let base_dir = base_dir.as_ref();
+        let parsing_cfg = Reader::try_parsing_cfg_of(base_dir)?;
+
+        let mut writer = io::files::AtomicFile::create(&base_dir.join(files::BASE_MANIFEST))?;
+        writeln!(writer, "checksum: '{}'", checksum(&parsing_cfg.map_files)?)?;
+        writeln!(writer, "edge-count: {}", graph.fwd_edges().count())?;
+        writer.commit()
+    }
+
+    /// Writes the optimized metric of the given graph as delta to the base-graph (see
+    /// `write_base(...)`) into the given dir, together with a manifest referencing the base.
+    /// The dir is the iteration's dir or its temporary dir, while the base is read from the
+    /// base's completed iteration-dir.
+    ///
+    /// The values are keyed by edge-ids, if the graph has them, and by stable ids otherwise (see
+    /// `DeltaKey`).
+    /// Shortcuts are skipped, since their values are recomputed from the base's shortcuts.
+    pub fn write_delta<P: AsRef<Path> + ?Sized>(
+        iter: usize,
+        graph: &Graph,
+        iter_dir: &P,
+        balancing_cfg: &BalancingConfig,
+    ) -> err::Feedback {
+        info!("START Write delta of iteration {}.", iter);
+        let iter_dir = iter_dir.as_ref();
+        let base_dir = Reader::iter_dir(BASE_ITER, &balancing_cfg.results_dir);
+        let base: BaseManifest = read_yaml(&base_dir.join(files::BASE_MANIFEST))?;
+
+        let fwd_edges = graph.fwd_edges();
+        let key = DeltaKey::of(graph);
+
+        // write delta
+
+        let metric_id = &balancing_cfg.optimization.metric_id;
+        let metric_idx = graph.cfg().edges.metrics.try_idx_of(metric_id)?;
+        let column = graph.metrics().column(metric_idx);
+        let stable_ids = match key {
+            DeltaKey::EdgeId => None,
+            DeltaKey::StableId => Some(graph.stable_ids()),
+        };
+        let mut writer = io::files::AtomicFile::create(&iter_dir.join(files::DELTA))?;
+        writeln!(writer, "{} {}", key.name(), metric_id)?;
+        for edge_idx in fwd_edges
+            .iter()
+            .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+        {
+            let value = io::fmt::float(column[*edge_idx], None);
+            match &stable_ids {
+                None => writeln!(writer, "{} {}", fwd_edges.id(edge_idx), value)?,
+                Some(stable_ids) => writeln!(writer, "{} {}", stable_ids[*edge_idx], value)?,
+            }
+        }
+        writer.commit()?;
+
+        // write manifest
+
+        let mut writer = io::files::AtomicFile::create(&iter_dir.join(files::DELTA_MANIFEST))?;
+        writeln!(writer, "base-dir: '{}'", BASE_ITER)?;
+        writeln!(writer, "base:")?;
+        writeln!(writer, "  checksum: '{}'", base.checksum)?;
+        writeln!(writer, "  edge-count: {}", base.edge_count)?;
+        writeln!(writer, "metric-id: '{}'", metric_id)?;
+        writeln!(writer, "delta-file: '{}'", files::DELTA)?;
+        writer.commit()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
@@ -5,7 +5,6 @@ use crate::{
     io::{self, SupportingFileExts},
     network::Graph,
 };
-use std::fmt::Display;
 
 pub struct Writer;
 
@@ -32,16 +31,18 @@ impl Writer {
         Ok(())
     }
 
-    pub fn write<T>(values: &[T], graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback
-    where
-        T: Display,
-    {
+    pub fn write(values: &[f64], graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
         // write edges-info
+        // -> as overlay for osm-editors (e.g. JOSM), if the file is an osm-file
 
         let mut tmp_cfg = writing_cfg.monitoring.edges_info.clone();
         // path is relative to results-dir
         tmp_cfg.file = writing_cfg.results_dir.join(tmp_cfg.file);
-        io::network::edges::Writer::write(&graph, &tmp_cfg)?;
+        if io::osm::Writer::is_file_supported(&tmp_cfg.file) {
+            io::osm::Writer::write(values, &graph, &tmp_cfg)?;
+        } else {
+            io::network::edges::Writer::write(&graph, &tmp_cfg)?;
+        }
 
         // write absolute workloads
 
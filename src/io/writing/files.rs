@@ -0,0 +1,382 @@
+use crate::{defaults::writing::files as defaults, helpers::err};
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Returns the path of `file` with the given suffix appended to its file-name, e.g.
+/// `graph.fmi` becomes `graph.fmi.tmp`.
+fn with_suffix(file: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = file
+        .file_name()
+        .map(OsString::from)
+        .unwrap_or_else(OsString::new);
+    file_name.push(".");
+    file_name.push(suffix);
+    file.with_file_name(file_name)
+}
+
+/// Errs if `file` does already exist or if its directory is not writable.
+///
+/// The directory is probed by creating and deleting a small file next to `file`.
+pub fn check_new_file<P: AsRef<Path> + ?Sized>(file: &P) -> err::Feedback {
+    let file = file.as_ref();
+
+    if file.exists() {
+        return Err(err::Msg::from(format!(
+            "New file {} does already exist. Please remove it.",
+            file.display()
+        )));
+    }
+
+    let dir = match file.parent() {
+        Some(dir) if dir.as_os_str().is_empty() => Path::new("."),
+        Some(dir) => dir,
+        None => {
+            return Err(err::Msg::from(format!(
+                "The new file {} has no parent-directory.",
+                file.display()
+            )))
+        }
+    };
+    if !dir.is_dir() {
+        return Err(err::Msg::from(format!(
+            "The directory {} of the new file {} doesn't exist.",
+            dir.display(),
+            file.display()
+        )));
+    }
+
+    probe(&with_suffix(file, defaults::PROBE_SUFFIX))
+}
+
+/// Errs if `dir` is not writable.
+/// If `dir` doesn't exist yet, its closest existing ancestor is probed, since `dir` would be
+/// created in there.
+pub fn check_writable_dir<P: AsRef<Path> + ?Sized>(dir: &P) -> err::Feedback {
+    let dir = dir.as_ref();
+
+    let mut existing_dir = dir;
+    while !existing_dir.exists() {
+        existing_dir = match existing_dir.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+            Some(parent) => parent,
+            None => {
+                return Err(err::Msg::from(format!(
+                    "No ancestor of directory {} does exist.",
+                    dir.display()
+                )))
+            }
+        };
+    }
+    if !existing_dir.is_dir() {
+        return Err(err::Msg::from(format!(
+            "{} should be a directory, but is a file.",
+            existing_dir.display()
+        )));
+    }
+
+    probe(&existing_dir.join(defaults::PROBE_FILE_NAME))
+}
+
+fn probe(probe_file: &Path) -> err::Feedback {
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(probe_file)
+    {
+        Ok(_) => Ok(fs::remove_file(probe_file)?),
+        Err(e) => Err(err::Msg::from(format!(
+            "Couldn't write into the directory of {} due to error: {}",
+            probe_file.display(),
+            e
+        ))),
+    }
+}
+
+/// A new file, whose content is written into a temporary file next to it.
+/// Only `commit()` renames the temporary file to the actual file, so failing writers never leave
+/// partially written files behind.
+/// Dropping an uncommitted `AtomicFile` removes its temporary file.
+pub struct AtomicFile {
+    file: PathBuf,
+    tmp_file: PathBuf,
+    writer: Option<BufWriter<File>>,
+    is_replacing: bool,
+}
+
+impl AtomicFile {
+    pub fn create<P: AsRef<Path> + ?Sized>(file: &P) -> err::Result<AtomicFile> {
+        let file = file.as_ref();
+
+        if file.exists() {
+            return Err(err::Msg::from(format!(
+                "New file {} does already exist. Please remove it.",
+                file.display()
+            )));
+        }
+
+        AtomicFile::open(file, false)
+    }
+
+    /// Like `create(...)`, but `commit()` replaces the file, if it does already exist, e.g. for
+    /// files, which are rewritten repeatedly.
+    /// Readers see either the old or the new content, but never a partially written file.
+    pub fn replace<P: AsRef<Path> + ?Sized>(file: &P) -> err::Result<AtomicFile> {
+        AtomicFile::open(file.as_ref(), true)
+    }
+
+    fn open(file: &Path, is_replacing: bool) -> err::Result<AtomicFile> {
+        let tmp_file = with_suffix(file, defaults::TMP_SUFFIX);
+        let writer = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_file)
+        {
+            Ok(output_file) => BufWriter::new(output_file),
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Couldn't open {} due to error: {}",
+                    tmp_file.display(),
+                    e
+                )))
+            }
+        };
+
+        Ok(AtomicFile {
+            file: file.to_path_buf(),
+            tmp_file,
+            writer: Some(writer),
+            is_replacing,
+        })
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn tmp_file(&self) -> &Path {
+        &self.tmp_file
+    }
+
+    /// Flushes everything written and renames the temporary file to the actual file.
+    pub fn commit(mut self) -> err::Feedback {
+        let result = self.try_commit();
+        if result.is_err() {
+            let _ = fs::remove_file(&self.tmp_file);
+        }
+        result
+    }
+
+    fn try_commit(&mut self) -> err::Feedback {
+        let writer = match self.writer.take() {
+            Some(writer) => writer,
+            None => return Err(err::Msg::from("The file has already been committed.")),
+        };
+        let output_file = match writer.into_inner() {
+            Ok(output_file) => output_file,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Couldn't flush {} due to error: {}",
+                    self.tmp_file.display(),
+                    e.error()
+                )))
+            }
+        };
+        output_file.sync_all()?;
+        drop(output_file);
+
+        // rename would overwrite silently
+        if !self.is_replacing && self.file.exists() {
+            return Err(err::Msg::from(format!(
+                "New file {} has been created while writing. Please remove it.",
+                self.file.display()
+            )));
+        }
+        match fs::rename(&self.tmp_file, &self.file) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(err::Msg::from(format!(
+                "Couldn't rename {} to {} due to error: {}",
+                self.tmp_file.display(),
+                self.file.display(),
+                e
+            ))),
+        }
+    }
+
+    fn writer(&mut self) -> io::Result<&mut BufWriter<File>> {
+        match self.writer.as_mut() {
+            Some(writer) => Ok(writer),
+            None => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "The file has already been committed.",
+            )),
+        }
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer()?.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // not committed -> discard what has been written
+        if let Some(writer) = self.writer.take() {
+            drop(writer);
+            let _ = fs::remove_file(&self.tmp_file);
+        }
+    }
+}
+
+/// Returns the temporary directory of `dir`, which is used by `AtomicDir`, e.g. `0.tmp` for `0`.
+pub fn tmp_dir_of<P: AsRef<Path> + ?Sized>(dir: &P) -> PathBuf {
+    with_suffix(dir.as_ref(), defaults::TMP_SUFFIX)
+}
+
+/// Removes all temporary directories (see `tmp_dir_of(...)`) directly in `dir`, e.g. left behind
+/// by crashed writers, and returns the removed ones.
+pub fn remove_tmp_dirs<P: AsRef<Path> + ?Sized>(dir: &P) -> err::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let suffix = format!(".{}", defaults::TMP_SUFFIX);
+
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let is_tmp_dir = entry.file_type()?.is_dir()
+            && entry
+                .file_name()
+                .to_str()
+                .map_or(false, |name| name.ends_with(&suffix));
+        if is_tmp_dir {
+            let tmp_dir = entry.path();
+            if let Err(e) = fs::remove_dir_all(&tmp_dir) {
+                return Err(err::Msg::from(format!(
+                    "Couldn't remove {} due to error: {}",
+                    tmp_dir.display(),
+                    e
+                )));
+            }
+            removed.push(tmp_dir);
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+/// A new directory, whose files are written into a temporary directory next to it (see
+/// `tmp_dir_of(...)`).
+/// Only `commit()` renames the temporary directory to the actual directory, after all its files
+/// have been synced to disk, so crashes never leave partially written directories behind.
+///
+/// In contrast to `AtomicFile`, dropping an uncommitted `AtomicDir` keeps its temporary
+/// directory, which can be removed via `remove_tmp_dirs(...)`.
+pub struct AtomicDir {
+    dir: PathBuf,
+    tmp_dir: PathBuf,
+}
+
+impl AtomicDir {
+    /// Errs if the directory or its temporary directory does already exist.
+    pub fn create<P: AsRef<Path> + ?Sized>(dir: &P) -> err::Result<AtomicDir> {
+        let dir = dir.as_ref();
+
+        if dir.exists() {
+            return Err(err::Msg::from(format!(
+                "New directory {} does already exist. Please remove it.",
+                dir.display()
+            )));
+        }
+
+        let tmp_dir = tmp_dir_of(dir);
+        if let Err(e) = fs::create_dir(&tmp_dir) {
+            return Err(err::Msg::from(format!(
+                "Couldn't create {} due to error: {}",
+                tmp_dir.display(),
+                e
+            )));
+        }
+
+        Ok(AtomicDir {
+            dir: dir.to_path_buf(),
+            tmp_dir,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The directory, where the files should be written into until `commit()`
+    pub fn tmp_dir(&self) -> &Path {
+        &self.tmp_dir
+    }
+
+    /// Syncs all files of the temporary directory and renames it to the actual directory.
+    pub fn commit(self) -> err::Feedback {
+        sync_dir_all(&self.tmp_dir)?;
+
+        // rename would replace empty directories silently
+        if self.dir.exists() {
+            return Err(err::Msg::from(format!(
+                "New directory {} has been created while writing. Please remove it.",
+                self.dir.display()
+            )));
+        }
+        if let Err(e) = fs::rename(&self.tmp_dir, &self.dir) {
+            return Err(err::Msg::from(format!(
+                "Couldn't rename {} to {} due to error: {}",
+                self.tmp_dir.display(),
+                self.dir.display(),
+                e
+            )));
+        }
+
+        // persist the rename itself
+        match self.dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => sync_dir(parent),
+            _ => sync_dir(Path::new(".")),
+        }
+    }
+}
+
+/// Syncs all files of the directory and its sub-directories recursively.
+fn sync_dir_all(dir: &Path) -> err::Feedback {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            sync_dir_all(&path)?;
+        } else {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            };
+            file.sync_all()?;
+        }
+    }
+    sync_dir(dir)
+}
+
+/// Syncs the directory's entries, which is only possible, where directories can be opened like
+/// files (e.g. not on Windows).
+fn sync_dir(dir: &Path) -> err::Feedback {
+    if let Ok(dir) = File::open(dir) {
+        dir.sync_all()?;
+    }
+    Ok(())
+}
@@ -1,5 +1,9 @@
 #[cfg(feature = "gpl")]
+pub mod balancing;
+#[cfg(feature = "gpl")]
 pub mod evaluating_balance;
+pub mod files;
 pub mod network;
+pub mod osm;
 pub mod routing;
 pub mod smarts;
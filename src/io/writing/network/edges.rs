@@ -2,15 +2,11 @@ use crate::{
     configs::writing::network::edges::Config as WritingConfig,
     defaults,
     helpers::err,
-    io::{writing::network::write_edges_to_file, SupportingFileExts, SupportingMapFileExts},
+    io::{self, writing::network::write_edges_to_file, SupportingFileExts, SupportingMapFileExts},
     network::Graph,
 };
 use log::info;
-use std::{
-    fmt::Display,
-    fs::OpenOptions,
-    io::{BufWriter, Write},
-};
+use std::io::Write;
 
 pub struct Writer;
 
@@ -37,31 +33,18 @@ impl Writer {
 
         // prepare
 
-        let output_file = match OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&writing_cfg.file)
-        {
-            Ok(file) => file,
-            Err(e) => {
-                return Err(err::Msg::from(format!(
-                    "Couldn't open {} due to error: {}",
-                    writing_cfg.file.display(),
-                    e
-                )))
-            }
-        };
-        let mut writer = BufWriter::new(output_file);
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.file)?;
 
-        write_edges_to_file(&mut writer, graph, writing_cfg)?;
+        write_edges_to_file(&mut writer, graph, writing_cfg, None)?;
+        writer.commit()?;
 
         info!("FINISHED");
         Ok(())
     }
 
     /// Ignores `writing_cfg.is_denormalizing`, because no mean is provided.
-    pub fn write_external_values<T: Display>(
-        values: &[T],
+    pub fn write_external_values(
+        values: &[f64],
         graph: &Graph,
         writing_cfg: &WritingConfig,
     ) -> err::Feedback {
@@ -72,23 +55,7 @@ impl Writer {
             )));
         }
 
-        let mut writer = {
-            let output_file = match OpenOptions::new()
-                .write(true)
-                .create_new(true)
-                .open(&writing_cfg.file)
-            {
-                Ok(f) => f,
-                Err(e) => {
-                    return Err(err::Msg::from(format!(
-                        "Couldn't open {} due to error: {}",
-                        writing_cfg.file.display(),
-                        e
-                    )))
-                }
-            };
-            BufWriter::new(output_file)
-        };
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.file)?;
 
         // write header
 
@@ -116,10 +83,15 @@ impl Writer {
         for edge_idx in fwd_edges.iter().filter(|&edge_idx| {
             writing_cfg.is_writing_shortcuts || !fwd_edges.is_shortcut(edge_idx)
         }) {
-            writeln!(writer, "{} {}", fwd_edges.id(edge_idx), values[*edge_idx])?;
+            writeln!(
+                writer,
+                "{} {}",
+                fwd_edges.id(edge_idx),
+                io::fmt::float(values[*edge_idx], writing_cfg.precision.metrics)
+            )?;
         }
 
-        Ok(())
+        writer.commit()
     }
 }
 
@@ -0,0 +1,131 @@
+use crate::{
+    configs::writing::network::geojson::Config as WritingConfig,
+    helpers::err,
+    io::{self, SupportingFileExts},
+    network::{Graph, MetricIdx},
+};
+use log::info;
+use serde_json::{json, Map, Value};
+use std::io::Write;
+
+/// Writes the graph as GeoJSON-FeatureCollection, which can be loaded into QGIS or Leaflet.
+///
+/// Every node becomes a Point with the properties `osm_id` and `node_idx`, and every edge
+/// becomes a LineString from its src to its dst, carrying all metrics as properties keyed by
+/// their ids (see `configs::parsing::edges::metrics::Config::ids`).
+/// Metrics are written as stored in the graph (or denormalized, if configured), where
+/// non-finite values become `null`, since JSON doesn't know them.
+///
+/// Features are written one by one, so the whole collection is never held in memory.
+pub struct Writer;
+
+impl Writer {
+    pub fn write(graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write the graph as GeoJSON {}",
+            writing_cfg.file.display()
+        );
+
+        if !Self::is_file_supported(&writing_cfg.file) {
+            return Err(format!("No support for writing {}.", writing_cfg.file.display()).into());
+        }
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let metrics = graph.metrics();
+        let metric_ids = &graph.cfg().edges.metrics.ids;
+        let factors: Vec<f64> = (0..metric_ids.len())
+            .map(MetricIdx)
+            .map(|metric_idx| match metrics.mean(metric_idx) {
+                Some(mean) if writing_cfg.is_denormalizing => mean,
+                _ => 1.0,
+            })
+            .collect();
+
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.file)?;
+        writeln!(writer, "{{")?;
+        writeln!(writer, "\"type\": \"FeatureCollection\",")?;
+        writeln!(writer, "\"features\": [")?;
+
+        let mut is_first = true;
+        let mut write_feature =
+            |writer: &mut io::files::AtomicFile, feature: Value| -> err::Feedback {
+                let feature = serde_json::to_string(&feature).map_err(|e| {
+                    err::Msg::from(format!(
+                        "Serde couldn't serialize a GeoJSON-feature due to error: {}",
+                        e
+                    ))
+                })?;
+                if is_first {
+                    is_first = false;
+                } else {
+                    writeln!(writer, ",")?;
+                }
+                write!(writer, "{}", feature)?;
+                Ok(())
+            };
+
+        // write nodes
+
+        for node_idx in &nodes {
+            let coord = nodes.coord(node_idx);
+            write_feature(
+                &mut writer,
+                json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [coord.lon, coord.lat]
+                    },
+                    "properties": {
+                        "osm_id": nodes.id(node_idx),
+                        "node_idx": *node_idx
+                    }
+                }),
+            )?;
+        }
+
+        // write edges
+
+        for edge_idx in fwd_edges.iter().filter(|&edge_idx| {
+            writing_cfg.is_writing_shortcuts || !fwd_edges.is_shortcut(edge_idx)
+        }) {
+            let src_coord = nodes.coord(bwd_edges.dst_idx(edge_idx));
+            let dst_coord = nodes.coord(fwd_edges.dst_idx(edge_idx));
+            let mut properties = Map::new();
+            for (metric_idx, metric_id) in metric_ids.iter().enumerate() {
+                let value = metrics.column(MetricIdx(metric_idx))[*edge_idx] * factors[metric_idx];
+                properties.insert(metric_id.0.clone(), json!(value));
+            }
+            write_feature(
+                &mut writer,
+                json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "LineString",
+                        "coordinates": [
+                            [src_coord.lon, src_coord.lat],
+                            [dst_coord.lon, dst_coord.lat]
+                        ]
+                    },
+                    "properties": properties
+                }),
+            )?;
+        }
+
+        writeln!(writer)?;
+        writeln!(writer, "]")?;
+        writeln!(writer, "}}")?;
+        writer.commit()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["geojson", "json"]
+    }
+}
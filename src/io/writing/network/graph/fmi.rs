@@ -1,16 +1,21 @@
 use crate::{
-    configs::{parsing::nodes, writing},
+    approximating::Approx,
+    configs::{
+        parsing::{edges, nodes},
+        writing::{self, network::graph::nodes::ExtraColumn},
+    },
     defaults,
     helpers::err,
-    io::writing::network::write_edges_to_file,
-    network::Graph,
+    io::{
+        self,
+        writing::network::{has_bidirectional_column, write_edges_to_file},
+    },
+    network::{Graph, MetricIdx},
 };
 use log::info;
 use progressing::{self, bernoulli::Bar as BernoulliBar, Baring};
-use std::{
-    fs::OpenOptions,
-    io::{BufWriter, Write},
-};
+use serde_json::{json, Value};
+use std::io::Write;
 
 pub struct Writer;
 
@@ -28,14 +33,28 @@ impl Writer {
     ) -> err::Feedback {
         // prepare
 
-        let output_file = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&writing_cfg.map_file)?;
-        let mut writer = BufWriter::new(output_file);
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.map_file)?;
 
         let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
         let nodes = graph.nodes();
+        // computed on demand, if not cached yet
+        let components = if writing_cfg
+            .extra_node_columns
+            .contains(&ExtraColumn::Component)
+        {
+            Some(graph.components())
+        } else {
+            None
+        };
+
+        let bidirectional_flags = if writing_cfg.is_merging_bidirectional {
+            Some(merge_bidirectional(graph, writing_cfg)?)
+        } else {
+            None
+        };
+        let is_appending_flag = bidirectional_flags.is_some()
+            && !has_bidirectional_column(graph, &writing_cfg.edges.ids);
 
         // write header
 
@@ -53,6 +72,12 @@ impl Writer {
                     Some(id) => format!("{}", id.0),
                     None => format!("{}", defaults::writing::IGNORE_STR),
                 })
+                .chain(
+                    writing_cfg
+                        .extra_node_columns
+                        .iter()
+                        .map(|column| String::from(column.id()))
+                )
                 .collect::<Vec<_>>()
         )?;
         writeln!(
@@ -66,6 +91,11 @@ impl Writer {
                     Some(id) => format!("{}", id.0),
                     None => format!("{}", defaults::writing::IGNORE_STR),
                 })
+                .chain(if is_appending_flag {
+                    Some(String::from(defaults::writing::BIDIRECTIONAL_ID))
+                } else {
+                    None
+                })
                 .collect::<Vec<_>>()
         )?;
         writeln!(
@@ -77,6 +107,12 @@ impl Writer {
                 "normalized"
             }
         )?;
+        writeln!(
+            writer,
+            "{} {}",
+            defaults::writing::EMBEDDED_CFG_PREFIX,
+            embedded_cfg(graph, writing_cfg, is_appending_flag)
+        )?;
 
         writeln!(writer, "")?;
 
@@ -92,6 +128,7 @@ impl Writer {
         writeln!(writer, "{}", dim)?;
         writeln!(writer, "{}", nodes.count())?;
         // only write non-shortcuts
+        // and no edges, which are part of their reversed edge's row
         writeln!(
             writer,
             "{}",
@@ -99,11 +136,26 @@ impl Writer {
                 .iter()
                 .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx)
                     || writing_cfg.edges.is_writing_shortcuts)
+                .filter(|&edge_idx| match &bidirectional_flags {
+                    Some(flags) => flags[*edge_idx].is_some(),
+                    None => true,
+                })
                 .count()
         )?;
 
         // write nodes
 
+        // resolve the columns' custom node-value-indices once instead of per node
+        let custom_indices: Vec<Option<usize>> = writing_cfg
+            .nodes
+            .ids
+            .iter()
+            .map(|id| {
+                id.as_ref()
+                    .and_then(|id| graph.cfg().nodes.try_custom_idx_of(id).ok())
+            })
+            .collect();
+
         let mut progress_bar = BernoulliBar::with_goal(nodes.count()).timed();
         info!("{}", progress_bar);
 
@@ -133,9 +185,17 @@ impl Writer {
                                 match info {
                                     nodes::MetaInfo::NodeId => write!(writer, "{}", node.id())?,
                                     nodes::MetaInfo::NodeIdx => write!(writer, "{}", node.idx())?,
-                                    nodes::MetaInfo::CHLevel => {
-                                        write!(writer, "{}", node.ch_level())?
-                                    }
+                                    nodes::MetaInfo::CHLevel => match node.ch_level() {
+                                        Some(ch_level) => write!(writer, "{}", ch_level)?,
+                                        None => {
+                                            return Err(format!(
+                                                "Node-meta-info {:?} (id: {}) should be written, \
+                                                 but the graph has no ch-levels.",
+                                                info, id
+                                            )
+                                            .into())
+                                        }
+                                    },
                                 }
                             }
                             nodes::Category::Metric { unit, id } => {
@@ -145,15 +205,38 @@ impl Writer {
 
                                 let node = graph.nodes().create(node_idx);
                                 match unit {
-                                    nodes::metrics::UnitInfo::Latitude => {
-                                        write!(writer, "{}", node.coord().lat)?
-                                    }
-                                    nodes::metrics::UnitInfo::Longitude => {
-                                        write!(writer, "{}", node.coord().lon)?
-                                    }
+                                    nodes::metrics::UnitInfo::Latitude => write!(
+                                        writer,
+                                        "{}",
+                                        io::fmt::float(
+                                            node.coord().lat,
+                                            writing_cfg.edges.precision.coordinates
+                                        )
+                                    )?,
+                                    nodes::metrics::UnitInfo::Longitude => write!(
+                                        writer,
+                                        "{}",
+                                        io::fmt::float(
+                                            node.coord().lon,
+                                            writing_cfg.edges.precision.coordinates
+                                        )
+                                    )?,
                                     nodes::metrics::UnitInfo::Height => {
                                         unimplemented!("Nodes' height is not supported yet.")
                                     }
+                                    nodes::metrics::UnitInfo::F64 => write!(
+                                        writer,
+                                        "{}",
+                                        io::fmt::float(
+                                            nodes.custom(
+                                                node_idx,
+                                                custom_indices[i].expect(
+                                                    "Custom node-values should have an index."
+                                                )
+                                            ),
+                                            writing_cfg.edges.precision.metrics
+                                        )
+                                    )?,
                                 }
                             }
                             nodes::Category::Ignored => continue, // covered in else-case
@@ -187,6 +270,23 @@ impl Writer {
                 }
             }
 
+            // append extra columns
+            for (i, column) in writing_cfg.extra_node_columns.iter().enumerate() {
+                if i > 0 || !writing_cfg.nodes.ids.is_empty() {
+                    write!(writer, " ")?;
+                }
+                match column {
+                    ExtraColumn::OutDegree => write!(writer, "{}", fwd_edges.degree(node_idx))?,
+                    ExtraColumn::InDegree => write!(writer, "{}", bwd_edges.degree(node_idx))?,
+                    ExtraColumn::Component => match &components {
+                        Some(components) => {
+                            write!(writer, "{}", components.component_of(node_idx))?
+                        }
+                        None => unreachable!("Components are computed if needed."),
+                    },
+                }
+            }
+
             // write end of line
             writeln!(writer, "")?;
 
@@ -203,9 +303,175 @@ impl Writer {
             &mut writer,
             &graph,
             &writing::network::edges::Config::from(writing_cfg.clone()),
+            bidirectional_flags.as_ref().map(|flags| flags.as_slice()),
         )?;
+        writer.commit()?;
 
         info!("FINISHED");
         Ok(())
     }
 }
+
+/// Pairs every edge with an edge of reversed direction and approximately equal metrics,
+/// so both can be written as one bidirectional row.
+/// Asymmetric pairs and shortcuts are never merged.
+///
+/// The returned flags are indexed by edge-indices.
+/// Every edge has the flag of its row, or None, if it's written as part of its partner's row.
+fn merge_bidirectional(
+    graph: &Graph,
+    writing_cfg: &writing::network::graph::Config,
+) -> err::Result<Vec<Option<bool>>> {
+    // edge-ids, stable ids and shortcut-indices are specific for one direction
+    for category in graph.cfg().edges.categories.iter() {
+        if let edges::Category::Meta { info, id } = category {
+            match info {
+                edges::MetaInfo::EdgeId
+                | edges::MetaInfo::StableId
+                | edges::MetaInfo::ShortcutIdx0
+                | edges::MetaInfo::ShortcutIdx1 => {
+                    if writing_cfg.edges.ids.contains(&Some(id.clone())) {
+                        return Err(format!(
+                            "Edge-meta-info {:?} (id: {}) can't be written \
+                             when merging bidirectional edges.",
+                            info, id
+                        )
+                        .into());
+                    }
+                }
+                edges::MetaInfo::SrcId
+                | edges::MetaInfo::SrcIdx
+                | edges::MetaInfo::SrcLat
+                | edges::MetaInfo::SrcLon
+                | edges::MetaInfo::DstId
+                | edges::MetaInfo::DstIdx
+                | edges::MetaInfo::DstLat
+                | edges::MetaInfo::DstLon
+                | edges::MetaInfo::Bidirectional => (),
+            }
+        }
+    }
+
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let metrics = graph.metrics();
+    let dim = graph.cfg().edges.metrics.units.len();
+
+    let mut flags = vec![Some(false); fwd_edges.count()];
+    for edge_idx in fwd_edges.iter() {
+        if flags[*edge_idx] != Some(false) || fwd_edges.is_shortcut(edge_idx) {
+            continue;
+        }
+
+        // Smaller edge-indices have been looked at already, and would have taken this edge
+        // as partner, if possible.
+        let src_idx = bwd_edges.dst_idx(edge_idx);
+        let dst_idx = fwd_edges.dst_idx(edge_idx);
+        let partner = fwd_edges.starting_from(dst_idx).find(|reversed_edge| {
+            let reversed_idx = reversed_edge.idx();
+            *reversed_idx > *edge_idx
+                && reversed_edge.dst_idx() == src_idx
+                && !reversed_edge.is_shortcut()
+                && flags[*reversed_idx] == Some(false)
+                && (0..dim).map(MetricIdx).all(|metric_idx| {
+                    let column = metrics.column(metric_idx);
+                    Approx(column[*edge_idx]) == Approx(column[*reversed_idx])
+                })
+        });
+        if let Some(reversed_edge) = partner {
+            flags[*edge_idx] = Some(true);
+            flags[*reversed_edge.idx()] = None;
+        }
+    }
+
+    let merged_count = flags.iter().filter(|flag| flag.is_none()).count();
+    info!(
+        "Merge {} of {} edges into bidirectional rows.",
+        2 * merged_count,
+        fwd_edges.count()
+    );
+    Ok(flags)
+}
+
+/// Describes the written columns (ids and meta-infos or units) as json,
+/// so parsers can check their config against it.
+/// Ignored columns are described as `null`.
+/// Appended extra node-columns and an appended bidirectional-flag are described as well.
+fn embedded_cfg(
+    graph: &Graph,
+    writing_cfg: &writing::network::graph::Config,
+    is_appending_flag: bool,
+) -> String {
+    // serialized by serde, so ids are escaped correctly
+    let column = |id: &str, kind: Option<String>| match kind {
+        Some(kind) => json!({ "id": id, "kind": kind }),
+        None => Value::Null,
+    };
+
+    let mut node_columns: Vec<Value> = writing_cfg
+        .nodes
+        .ids
+        .iter()
+        .map(|next_id| match next_id {
+            Some(next_id) => column(
+                &next_id.0,
+                graph
+                    .cfg()
+                    .nodes
+                    .categories
+                    .iter()
+                    .find_map(|category| match category {
+                        nodes::Category::Meta { info, id } if id == next_id => {
+                            Some(format!("{:?}", info))
+                        }
+                        nodes::Category::Metric { unit, id } if id == next_id => {
+                            Some(format!("{:?}", unit))
+                        }
+                        _ => None,
+                    }),
+            ),
+            None => Value::Null,
+        })
+        .collect();
+    node_columns.extend(
+        writing_cfg
+            .extra_node_columns
+            .iter()
+            .map(|extra_column| column(extra_column.id(), Some(format!("{:?}", extra_column)))),
+    );
+
+    let mut edge_columns: Vec<Value> = writing_cfg
+        .edges
+        .ids
+        .iter()
+        .map(|next_id| match next_id {
+            Some(next_id) => column(
+                &next_id.0,
+                graph
+                    .cfg()
+                    .edges
+                    .categories
+                    .iter()
+                    .find_map(|category| match category {
+                        edges::Category::Meta { info, id } if id == next_id => {
+                            Some(format!("{:?}", info))
+                        }
+                        edges::Category::Metric { unit, id } if id == next_id => {
+                            Some(format!("{:?}", unit))
+                        }
+                        _ => None,
+                    }),
+            ),
+            None => Value::Null,
+        })
+        .collect();
+
+    if is_appending_flag {
+        edge_columns.push(column(
+            defaults::writing::BIDIRECTIONAL_ID,
+            Some(format!("{:?}", edges::MetaInfo::Bidirectional)),
+        ));
+    }
+
+    json!({ "nodes": node_columns, "edges": edge_columns }).to_string()
+}
@@ -7,6 +7,7 @@ use crate::{
 use log::info;
 
 pub mod fmi;
+pub mod pbf;
 
 pub struct Writer;
 
@@ -19,8 +20,11 @@ impl Writer {
 
         match Writer::from_path(&writing_cfg.map_file)? {
             MapFileExt::FMI => fmi::Writer::new().write(graph, writing_cfg)?,
-            MapFileExt::PBF => {
-                return Err(format!("No support for writing pbf-files.").into());
+            MapFileExt::PBF => pbf::Writer::new().write(graph, writing_cfg)?,
+            MapFileExt::OSM => {
+                return Err(err::Msg::from(
+                    "Graphs can't be written as osm-file, but their edges via io::osm::Writer.",
+                ))
             }
         }
 
@@ -32,6 +36,6 @@ impl Writer {
 impl SupportingMapFileExts for Writer {}
 impl SupportingFileExts for Writer {
     fn supported_exts<'a>() -> &'a [&'a str] {
-        &["fmi"]
+        &["osm.pbf", "pbf", "fmi"]
     }
 }
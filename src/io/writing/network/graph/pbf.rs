@@ -0,0 +1,337 @@
+use crate::{
+    configs::{parsing::edges::metrics::UnitInfo, writing},
+    helpers::err,
+    io,
+    network::{EdgeIdx, Graph, MetricIdx, NodeIdx},
+};
+use log::{info, warn};
+use progressing::{self, bernoulli::Bar as BernoulliBar, Baring};
+use std::{collections::HashMap, io::Write};
+
+/// Writes a graph as `*.osm.pbf`-file, which can be read by other osm-tools.
+///
+/// Since a graph doesn't remember the osm-ways it has been created from, limitations apply.
+///
+/// - Every node of the graph is written with its id and coordinate, but without tags.
+/// - Every (non-shortcut) forward-edge is written as its own way of two nodes, tagged as oneway.
+///   Bidirectional streets are hence written as two ways.
+/// - The graph doesn't store street-categories, so every way is tagged as `highway=unclassified`.
+/// - `maxspeed` is taken from a metric of unit `KilometersPerHour`, or, if not existent,
+///   calculated from metrics of units `Kilometers` and `Hours`.
+///   Otherwise, `maxspeed` is not tagged and parsers will fall back to the street-default.
+/// - `lanes` is taken from a metric of unit `LaneCount`, if existent.
+/// - Ways are written with their edge's id.
+///   Edges without id (e.g. reversed edges of bidirectional fmi-edges) get unique ids above all
+///   edge-ids.
+/// - Blobs are written uncompressed, so resulting files are bigger than usual.
+///
+/// The writing-config's node- and edge-ids are ignored, since the format is fixed.
+pub struct Writer;
+
+impl Writer {
+    pub fn new() -> Writer {
+        Writer {}
+    }
+}
+
+impl Writer {
+    pub fn write(
+        &self,
+        graph: &Graph,
+        writing_cfg: &writing::network::graph::Config,
+    ) -> err::Feedback {
+        // prepare
+
+        if writing_cfg.is_merging_bidirectional {
+            warn!("Merging bidirectional edges is only supported for fmi-files, hence ignored.");
+        }
+
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.map_file)?;
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+
+        // write header
+
+        let mut header_block = Vec::new();
+        protobuf::write_bytes(&mut header_block, 4, b"OsmSchema-V0.6");
+        protobuf::write_bytes(&mut header_block, 4, b"DenseNodes");
+        protobuf::write_bytes(&mut header_block, 16, b"osmgraphing");
+        write_blob(&mut writer, "OSMHeader", &header_block)?;
+
+        // write nodes
+
+        info!("START Write nodes.");
+        let mut progress_bar = BernoulliBar::with_goal(nodes.count()).timed();
+        info!("{}", progress_bar);
+
+        let node_indices: Vec<NodeIdx> = nodes.iter().collect();
+        for chunk in node_indices.chunks(MAX_ENTITIES_PER_BLOCK) {
+            let mut dense_nodes = Vec::new();
+            protobuf::write_packed_sint(
+                &mut dense_nodes,
+                1,
+                deltas(chunk.iter().map(|&idx| nodes.id(idx))),
+            );
+            protobuf::write_packed_sint(
+                &mut dense_nodes,
+                8,
+                deltas(chunk.iter().map(|&idx| to_decimicro(nodes.coord(idx).lat))),
+            );
+            protobuf::write_packed_sint(
+                &mut dense_nodes,
+                9,
+                deltas(chunk.iter().map(|&idx| to_decimicro(nodes.coord(idx).lon))),
+            );
+            // no tags, hence every node is only terminated
+            protobuf::write_packed_uint(&mut dense_nodes, 10, chunk.iter().map(|_| 0));
+
+            let mut primitive_group = Vec::new();
+            protobuf::write_bytes(&mut primitive_group, 2, &dense_nodes);
+
+            let mut string_table = StringTable::new();
+            write_blob(
+                &mut writer,
+                "OSMData",
+                &string_table.primitive_block(&primitive_group),
+            )?;
+
+            for _ in chunk {
+                progress_bar.add(true);
+            }
+            if progress_bar.has_progressed_significantly() {
+                progress_bar.remember_significant_progress();
+                info!("{}", progress_bar);
+            }
+        }
+        info!("FINISHED");
+
+        // write edges as ways
+
+        info!("START Write edges as ways.");
+        let metrics = graph.metrics();
+        let units = &graph.cfg().edges.metrics.units;
+        let metric_idx_of = |unit: UnitInfo| {
+            units
+                .iter()
+                .position(|other| other == &unit)
+                .map(|idx| MetricIdx(idx))
+        };
+        let kmph_idx = metric_idx_of(UnitInfo::KilometersPerHour);
+        let km_hours_indices = match (
+            metric_idx_of(UnitInfo::Kilometers),
+            metric_idx_of(UnitInfo::Hours),
+        ) {
+            (Some(km_idx), Some(hours_idx)) => Some((km_idx, hours_idx)),
+            _ => None,
+        };
+        let lane_count_idx = metric_idx_of(UnitInfo::LaneCount);
+        // denormalize, since parsers expect real values
+        let metric = |edge_idx: EdgeIdx, metric_idx: MetricIdx| {
+            metrics.column(metric_idx)[*edge_idx] * metrics.mean(metric_idx).unwrap_or(1.0)
+        };
+
+        let edge_indices: Vec<EdgeIdx> = fwd_edges
+            .iter()
+            .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+            .collect();
+        // way-ids of edges without id must not collide with other edges' ids
+        let fallback_way_id = edge_indices
+            .iter()
+            .filter_map(|&edge_idx| fwd_edges.try_id(edge_idx))
+            .max()
+            .map_or(0, |max_way_id| max_way_id + 1);
+
+        let mut progress_bar = BernoulliBar::with_goal(edge_indices.len()).timed();
+        info!("{}", progress_bar);
+        for chunk in edge_indices.chunks(MAX_ENTITIES_PER_BLOCK) {
+            let mut string_table = StringTable::new();
+            let mut primitive_group = Vec::new();
+
+            for &edge_idx in chunk {
+                let mut tags = vec![
+                    ("highway", String::from("unclassified")),
+                    ("oneway", String::from("yes")),
+                ];
+                let maxspeed = if let Some(kmph_idx) = kmph_idx {
+                    Some(metric(edge_idx, kmph_idx))
+                } else if let Some((km_idx, hours_idx)) = km_hours_indices {
+                    Some(metric(edge_idx, km_idx) / metric(edge_idx, hours_idx))
+                } else {
+                    None
+                };
+                if let Some(maxspeed) = maxspeed {
+                    tags.push(("maxspeed", format!("{}", maxspeed.round() as u16)));
+                }
+                if let Some(lane_count_idx) = lane_count_idx {
+                    tags.push((
+                        "lanes",
+                        format!("{}", metric(edge_idx, lane_count_idx).round() as u8),
+                    ));
+                }
+
+                let mut way = Vec::new();
+                let way_id = match fwd_edges.try_id(edge_idx) {
+                    Some(id) => id,
+                    None => fallback_way_id + *edge_idx,
+                };
+                protobuf::write_uint(&mut way, 1, way_id as u64);
+                protobuf::write_packed_uint(
+                    &mut way,
+                    2,
+                    tags.iter()
+                        .map(|(key, _value)| string_table.idx_of(key) as u64)
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                );
+                protobuf::write_packed_uint(
+                    &mut way,
+                    3,
+                    tags.iter()
+                        .map(|(_key, value)| string_table.idx_of(value) as u64)
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                );
+                let src_id = nodes.id(bwd_edges.dst_idx(edge_idx));
+                let dst_id = nodes.id(fwd_edges.dst_idx(edge_idx));
+                protobuf::write_packed_sint(&mut way, 8, deltas(vec![src_id, dst_id].into_iter()));
+
+                protobuf::write_bytes(&mut primitive_group, 3, &way);
+            }
+
+            write_blob(
+                &mut writer,
+                "OSMData",
+                &string_table.primitive_block(&primitive_group),
+            )?;
+
+            for _ in chunk {
+                progress_bar.add(true);
+            }
+            if progress_bar.has_progressed_significantly() {
+                progress_bar.remember_significant_progress();
+                info!("{}", progress_bar);
+            }
+        }
+        writer.commit()?;
+        info!("FINISHED");
+
+        Ok(())
+    }
+}
+
+/// The osm-wiki recommends at most 8000 entities per block.
+const MAX_ENTITIES_PER_BLOCK: usize = 8_000;
+
+/// Default-granularity of 100 nanodegrees
+fn to_decimicro(degrees: f64) -> i64 {
+    (degrees * 1e7).round() as i64
+}
+
+fn deltas<I: Iterator<Item = i64>>(values: I) -> impl Iterator<Item = i64> {
+    let mut prev = 0;
+    values.map(move |value| {
+        let delta = value - prev;
+        prev = value;
+        delta
+    })
+}
+
+/// Writes a fileblock, consisting of the blob-header's length, the blob-header and the raw blob.
+fn write_blob<W: Write>(writer: &mut W, blob_type: &str, block: &[u8]) -> err::Feedback {
+    let mut blob = Vec::new();
+    protobuf::write_bytes(&mut blob, 1, block);
+    protobuf::write_uint(&mut blob, 2, block.len() as u64);
+
+    let mut blob_header = Vec::new();
+    protobuf::write_bytes(&mut blob_header, 1, blob_type.as_bytes());
+    protobuf::write_uint(&mut blob_header, 3, blob.len() as u64);
+
+    writer.write_all(&(blob_header.len() as u32).to_be_bytes())?;
+    writer.write_all(&blob_header)?;
+    writer.write_all(&blob)?;
+    Ok(())
+}
+
+/// Every primitive-block has its own string-table, where index 0 is reserved.
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl StringTable {
+    fn new() -> StringTable {
+        StringTable {
+            strings: vec![String::new()],
+            indices: HashMap::new(),
+        }
+    }
+
+    fn idx_of(&mut self, s: &str) -> usize {
+        if let Some(&idx) = self.indices.get(s) {
+            return idx;
+        }
+        let idx = self.strings.len();
+        self.strings.push(String::from(s));
+        self.indices.insert(String::from(s), idx);
+        idx
+    }
+
+    fn primitive_block(&mut self, primitive_group: &[u8]) -> Vec<u8> {
+        let mut string_table = Vec::new();
+        for s in &self.strings {
+            protobuf::write_bytes(&mut string_table, 1, s.as_bytes());
+        }
+
+        let mut primitive_block = Vec::new();
+        protobuf::write_bytes(&mut primitive_block, 1, &string_table);
+        protobuf::write_bytes(&mut primitive_block, 2, primitive_group);
+        primitive_block
+    }
+}
+
+/// Minimal protobuf-encoding, covering what the osm-pbf-format needs.
+mod protobuf {
+    const VARINT: u64 = 0;
+    const LENGTH_DELIMITED: u64 = 2;
+
+    fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        buf.push(value as u8);
+    }
+
+    fn zigzag(value: i64) -> u64 {
+        ((value << 1) ^ (value >> 63)) as u64
+    }
+
+    fn write_key(buf: &mut Vec<u8>, field: u64, wire_type: u64) {
+        write_varint(buf, (field << 3) | wire_type);
+    }
+
+    pub fn write_uint(buf: &mut Vec<u8>, field: u64, value: u64) {
+        write_key(buf, field, VARINT);
+        write_varint(buf, value);
+    }
+
+    pub fn write_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        write_key(buf, field, LENGTH_DELIMITED);
+        write_varint(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+
+    pub fn write_packed_uint<I: Iterator<Item = u64>>(buf: &mut Vec<u8>, field: u64, values: I) {
+        let mut packed = Vec::new();
+        for value in values {
+            write_varint(&mut packed, value);
+        }
+        write_bytes(buf, field, &packed);
+    }
+
+    pub fn write_packed_sint<I: Iterator<Item = i64>>(buf: &mut Vec<u8>, field: u64, values: I) {
+        write_packed_uint(buf, field, values.map(zigzag));
+    }
+}
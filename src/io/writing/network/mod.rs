@@ -1,31 +1,52 @@
 pub mod edges;
+pub mod geojson;
 pub mod graph;
+pub mod simple_fmi;
 
 use crate::{
-    configs, defaults,
+    configs::{self, SimpleId},
+    defaults,
     helpers::err,
+    io,
     network::{Graph, MetricIdx},
 };
 use log::info;
 use progressing::{bernoulli::Bar as BernoulliBar, Baring};
 use std::io::Write;
 
+/// If `bidirectional_flags` is given, every edge has its row's flag, or None, if the edge is
+/// written as part of its reversed edge's row (see `graph::fmi`).
+/// Then, the flag is appended to every row, if the writing-config doesn't contain it already.
 fn write_edges_to_file<W: Write>(
     writer: &mut W,
     graph: &Graph,
     writing_cfg: &crate::configs::writing::network::edges::Config,
+    bidirectional_flags: Option<&[Option<bool>]>,
 ) -> err::Feedback {
     let fwd_edges = graph.fwd_edges();
     let bwd_edges = graph.bwd_edges();
     let nodes = graph.nodes();
 
+    // provenances are only tracked if configured when parsing
+
+    if writing_cfg.is_writing_provenance && !graph.metrics().has_provenances() {
+        return Err(err::Msg::from(
+            "Provenances should be written, but haven't been tracked when parsing. \
+             Set `track-provenance` to track them.",
+        ));
+    }
+
     // write header
 
     if writing_cfg.is_writing_header {
+        let metric_ids = &graph.cfg().edges.metrics.ids;
         for (i, edge_info) in writing_cfg
             .ids
             .iter()
             .map(|id| match id {
+                Some(id) if writing_cfg.is_writing_provenance && metric_ids.contains(id) => {
+                    format!("{} {}{}", id.0, id.0, defaults::writing::PROVENANCE_SUFFIX)
+                }
                 Some(id) => format!("{}", id.0),
                 None => format!("{}", defaults::writing::IGNORE_STR),
             })
@@ -46,9 +67,15 @@ fn write_edges_to_file<W: Write>(
 
     // write edges to file
 
+    let is_appending_flag =
+        bidirectional_flags.is_some() && !has_bidirectional_column(graph, &writing_cfg.ids);
+
     let mut progress_bar = BernoulliBar::with_goal(fwd_edges.count()).timed();
     info!("{}", progress_bar);
 
+    // computed on demand
+    let mut stable_ids = None;
+
     // for every edge
     for edge_idx in fwd_edges.iter() {
         // if shortcut, check if shortcuts are expected to be written
@@ -64,6 +91,20 @@ fn write_edges_to_file<W: Write>(
             continue;
         }
 
+        // skip edges, which are part of their reversed edge's row
+        let is_bidirectional = match bidirectional_flags.map(|flags| flags[*edge_idx]) {
+            Some(Some(is_bidirectional)) => is_bidirectional,
+            Some(None) => {
+                progress_bar.add(true);
+                if progress_bar.has_progressed_significantly() {
+                    progress_bar.remember_significant_progress();
+                    info!("{}", progress_bar);
+                }
+                continue;
+            }
+            None => false,
+        };
+
         // loop over graphs config
         // and print respective data
         // if id fits
@@ -101,12 +142,20 @@ fn write_edges_to_file<W: Write>(
                                 configs::parsing::edges::MetaInfo::SrcLat => {
                                     let src_idx = bwd_edges.dst_idx(edge_idx);
                                     let src_lat = nodes.coord(src_idx).lat;
-                                    write!(writer, "{}", src_lat)?;
+                                    write!(
+                                        writer,
+                                        "{}",
+                                        io::fmt::float(src_lat, writing_cfg.precision.coordinates)
+                                    )?;
                                 }
                                 configs::parsing::edges::MetaInfo::SrcLon => {
                                     let src_idx = bwd_edges.dst_idx(edge_idx);
                                     let src_lon = nodes.coord(src_idx).lon;
-                                    write!(writer, "{}", src_lon)?;
+                                    write!(
+                                        writer,
+                                        "{}",
+                                        io::fmt::float(src_lon, writing_cfg.precision.coordinates)
+                                    )?;
                                 }
                                 configs::parsing::edges::MetaInfo::DstId => {
                                     let dst_idx = fwd_edges.dst_idx(edge_idx);
@@ -120,12 +169,25 @@ fn write_edges_to_file<W: Write>(
                                 configs::parsing::edges::MetaInfo::DstLat => {
                                     let dst_idx = bwd_edges.dst_idx(edge_idx);
                                     let dst_lat = nodes.coord(dst_idx).lat;
-                                    write!(writer, "{}", dst_lat)?;
+                                    write!(
+                                        writer,
+                                        "{}",
+                                        io::fmt::float(dst_lat, writing_cfg.precision.coordinates)
+                                    )?;
                                 }
                                 configs::parsing::edges::MetaInfo::DstLon => {
                                     let dst_idx = bwd_edges.dst_idx(edge_idx);
                                     let dst_lon = nodes.coord(dst_idx).lon;
-                                    write!(writer, "{}", dst_lon)?;
+                                    write!(
+                                        writer,
+                                        "{}",
+                                        io::fmt::float(dst_lon, writing_cfg.precision.coordinates)
+                                    )?;
+                                }
+                                configs::parsing::edges::MetaInfo::StableId => {
+                                    let stable_ids =
+                                        stable_ids.get_or_insert_with(|| graph.stable_ids());
+                                    write!(writer, "{}", stable_ids[*edge_idx])?;
                                 }
                                 configs::parsing::edges::MetaInfo::ShortcutIdx0 => {
                                     match fwd_edges.sc_edges(edge_idx) {
@@ -147,6 +209,9 @@ fn write_edges_to_file<W: Write>(
                                         )?,
                                     }
                                 }
+                                configs::parsing::edges::MetaInfo::Bidirectional => {
+                                    write!(writer, "{}", is_bidirectional as u8)?;
+                                }
                             }
                         }
                         configs::parsing::edges::Category::Metric {
@@ -174,7 +239,7 @@ fn write_edges_to_file<W: Write>(
 
                             // denormalize metric if wished
 
-                            let mut metric_value = graph.metrics()[edge_idx][*metric_idx];
+                            let mut metric_value = graph.metrics().column(metric_idx)[*edge_idx];
                             if writing_cfg.is_denormalizing {
                                 // check if graph is normalized
                                 if let Some(mean) = graph.metrics().mean(metric_idx) {
@@ -182,7 +247,19 @@ fn write_edges_to_file<W: Write>(
                                 }
                             };
 
-                            write!(writer, "{}", metric_value)?;
+                            write!(
+                                writer,
+                                "{}",
+                                io::fmt::float(metric_value, writing_cfg.precision.metrics)
+                            )?;
+
+                            if writing_cfg.is_writing_provenance {
+                                write!(
+                                    writer,
+                                    " {}",
+                                    graph.metrics().provenance(edge_idx, metric_idx)
+                                )?;
+                            }
                         }
                         configs::parsing::edges::Category::Ignored => continue, // covered in else-case
                     }
@@ -215,6 +292,10 @@ fn write_edges_to_file<W: Write>(
             }
         }
 
+        if is_appending_flag {
+            write!(writer, " {}", is_bidirectional as u8)?;
+        }
+
         // write end of line
         writeln!(writer, "")?;
 
@@ -228,3 +309,20 @@ fn write_edges_to_file<W: Write>(
 
     Ok(())
 }
+
+/// Returns true, if one of the given ids refers to the graph's bidirectional-flag.
+fn has_bidirectional_column(graph: &Graph, ids: &[Option<SimpleId>]) -> bool {
+    graph
+        .cfg()
+        .edges
+        .categories
+        .iter()
+        .any(|category| match category {
+            configs::parsing::edges::Category::Meta { info, id } => {
+                info == &configs::parsing::edges::MetaInfo::Bidirectional
+                    && ids.contains(&Some(id.clone()))
+            }
+            configs::parsing::edges::Category::Metric { unit: _, id: _ }
+            | configs::parsing::edges::Category::Ignored => false,
+        })
+}
@@ -0,0 +1,87 @@
+use crate::{
+    configs::writing::network::simple_fmi::Config as WritingConfig,
+    defaults,
+    helpers::err,
+    io::{self, SupportingFileExts},
+    network::Graph,
+};
+use log::info;
+use std::io::Write;
+
+/// Writes the graph as described in `configs::writing::network::simple_fmi::Config`.
+/// Shortcuts are skipped and weights are written as stored in the graph, hence normalized, if
+/// the graph's metrics are normalized.
+pub struct Writer;
+
+impl Writer {
+    pub fn write(graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write the simple fmi-graph {} with weight {}",
+            writing_cfg.map_file.display(),
+            writing_cfg.metric_id
+        );
+
+        let metric_idx = graph
+            .cfg()
+            .edges
+            .metrics
+            .try_idx_of(&writing_cfg.metric_id)?;
+        let weights = graph.metrics().column(metric_idx);
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.map_file)?;
+
+        // write counts
+
+        writeln!(writer, "{}", nodes.count())?;
+        writeln!(
+            writer,
+            "{}",
+            fwd_edges
+                .iter()
+                .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+                .count()
+        )?;
+
+        // write nodes
+
+        for node_idx in &nodes {
+            let coord = nodes.coord(node_idx);
+            writeln!(
+                writer,
+                "{} {} {}",
+                node_idx,
+                io::fmt::float(coord.lat, defaults::writing::precision::COORDINATES),
+                io::fmt::float(coord.lon, defaults::writing::precision::COORDINATES)
+            )?;
+        }
+
+        // write edges
+
+        for edge_idx in fwd_edges
+            .iter()
+            .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+        {
+            writeln!(
+                writer,
+                "{} {} {}",
+                bwd_edges.dst_idx(edge_idx),
+                fwd_edges.dst_idx(edge_idx),
+                io::fmt::float(weights[*edge_idx], Some(writing_cfg.precision))
+            )?;
+        }
+
+        writer.commit()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["fmi"]
+    }
+}
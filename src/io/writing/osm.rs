@@ -0,0 +1,169 @@
+use crate::{
+    configs::{
+        parsing::edges::metrics::UnitInfo, writing::network::edges::Config as WritingConfig,
+    },
+    defaults::osm_overlay as defaults,
+    helpers::err,
+    io::{self, SupportingFileExts},
+    network::{Graph, MetricIdx},
+};
+use log::info;
+use std::{collections::BTreeSet, io::Write};
+
+/// Writes the graph's edges as OSM-xml, which can be opened as layer in editors like JOSM or iD
+/// to inspect edge-values (e.g. balanced workloads) on the map.
+///
+/// This is an overlay, not valid upload-data:
+/// nodes keep their original ids and coordinates, but every edge becomes its own way with a
+/// negative (synthetic) id and only `osmgraphing:*`-tags.
+/// Hence the file is marked with `upload="never"`.
+pub struct Writer;
+
+impl Writer {
+    /// The given values are written as `osmgraphing:workload` per edge.
+    /// Lane-counts and distances (in kilometers) are written, if the graph has a metric of unit
+    /// `LaneCount` resp. `Kilometers` or `Meters`.
+    pub fn write(values: &[f64], graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write the graph's edges as osm-overlay {}",
+            writing_cfg.file.display()
+        );
+
+        if !Self::is_file_supported(&writing_cfg.file) {
+            return Err(format!("No support for writing {}.", writing_cfg.file.display()).into());
+        }
+
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.file)?;
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let edge_idxs: Vec<_> = fwd_edges
+            .iter()
+            .filter(|&edge_idx| {
+                writing_cfg.is_writing_shortcuts || !fwd_edges.is_shortcut(edge_idx)
+            })
+            .collect();
+
+        // find metrics, which are denormalized if wished
+
+        let metric_of = |unit: UnitInfo| -> Option<(MetricIdx, f64)> {
+            let metric_idx = graph
+                .cfg()
+                .edges
+                .metrics
+                .units
+                .iter()
+                .position(|&u| u == unit)
+                .map(MetricIdx)?;
+            let factor = match graph.metrics().mean(metric_idx) {
+                Some(mean) if writing_cfg.is_denormalizing => mean,
+                _ => 1.0,
+            };
+            Some((metric_idx, factor))
+        };
+        let lane_count = metric_of(UnitInfo::LaneCount);
+        let distance = metric_of(UnitInfo::Kilometers).or_else(|| {
+            metric_of(UnitInfo::Meters).map(|(metric_idx, factor)| (metric_idx, factor / 1_000.0))
+        });
+
+        // write header
+
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            writer,
+            "<!-- Overlay of osmgraphing's edges for inspection, not valid upload-data. -->"
+        )?;
+        writeln!(
+            writer,
+            "<osm version=\"{}\" generator=\"{}\" upload=\"never\">",
+            defaults::VERSION,
+            defaults::GENERATOR
+        )?;
+
+        // write nodes of written edges, sorted by id
+
+        let node_idxs: BTreeSet<_> = edge_idxs
+            .iter()
+            .flat_map(|&edge_idx| vec![bwd_edges.dst_idx(edge_idx), fwd_edges.dst_idx(edge_idx)])
+            .collect();
+        for node_idx in node_idxs {
+            let coord = nodes.coord(node_idx);
+            writeln!(
+                writer,
+                "  <node id=\"{}\" lat=\"{}\" lon=\"{}\"/>",
+                nodes.id(node_idx),
+                io::fmt::float(coord.lat, None),
+                io::fmt::float(coord.lon, None)
+            )?;
+        }
+
+        // write one way per edge
+
+        for (i, &edge_idx) in edge_idxs.iter().enumerate() {
+            writeln!(writer, "  <way id=\"{}\">", -(i as i64) - 1)?;
+            writeln!(
+                writer,
+                "    <nd ref=\"{}\"/>",
+                nodes.id(bwd_edges.dst_idx(edge_idx))
+            )?;
+            writeln!(
+                writer,
+                "    <nd ref=\"{}\"/>",
+                nodes.id(fwd_edges.dst_idx(edge_idx))
+            )?;
+
+            let mut tags = vec![(
+                defaults::tags::WORKLOAD,
+                io::fmt::float(values[*edge_idx], writing_cfg.precision.metrics),
+            )];
+            for &(key, metric) in &[
+                (defaults::tags::LANE_COUNT, lane_count),
+                (defaults::tags::DISTANCE, distance),
+            ] {
+                if let Some((metric_idx, factor)) = metric {
+                    let value = graph.metrics().column(metric_idx)[*edge_idx] * factor;
+                    tags.push((key, io::fmt::float(value, writing_cfg.precision.metrics)));
+                }
+            }
+            for (key, value) in tags {
+                writeln!(
+                    writer,
+                    "    <tag k=\"{}\" v=\"{}\"/>",
+                    escape(key),
+                    escape(&value)
+                )?;
+            }
+
+            writeln!(writer, "  </way>")?;
+        }
+
+        writeln!(writer, "</osm>")?;
+        writer.commit()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["osm"]
+    }
+}
+
+/// Escapes the given text to be used as xml-attribute-value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,64 @@
+use crate::{
+    helpers::err,
+    io::{self, SupportingFileExts},
+    network::{Graph, MetricIdx},
+    routing::paths::Path,
+};
+use std::{io::Write, path};
+
+pub struct Writer;
+
+impl Writer {
+    /// Writes the path's cost-profile (see `Path::cost_profile(...)`) into a csv-file,
+    /// starting with a header-line of both metric-ids.
+    ///
+    /// Values are denormalized, if the graph's metrics are normalized, hence they are written in
+    /// the metrics' units.
+    pub fn write<P: AsRef<path::Path> + ?Sized>(
+        path: &Path,
+        metric_x: MetricIdx,
+        metric_y: MetricIdx,
+        graph: &Graph,
+        file: &P,
+    ) -> err::Feedback {
+        let file = file.as_ref();
+        if !Self::is_file_supported(file) {
+            return Err(err::Msg::from(format!(
+                "No support for writing {}.",
+                file.display()
+            )));
+        }
+
+        let profile = path.try_cost_profile(metric_x, metric_y, graph)?;
+        // metrics could be normalized
+        let metrics = graph.metrics();
+        let mean_x = metrics.mean(metric_x).unwrap_or(1.0);
+        let mean_y = metrics.mean(metric_y).unwrap_or(1.0);
+
+        let mut writer = io::files::AtomicFile::create(file)?;
+
+        // write header
+
+        let ids = &graph.cfg().edges.metrics.ids;
+        writeln!(writer, "{} {}", ids[*metric_x], ids[*metric_y])?;
+
+        // write values
+
+        for (x, y) in profile {
+            writeln!(
+                writer,
+                "{} {}",
+                io::fmt::float(x * mean_x, None),
+                io::fmt::float(y * mean_y, None)
+            )?;
+        }
+
+        writer.commit()
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["csv"]
+    }
+}
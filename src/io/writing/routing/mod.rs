@@ -1,7 +1,9 @@
 use crate::{configs, helpers::err, io::SupportingFileExts, network::Graph};
 use log::info;
 
+pub mod cost_profile;
 mod random_or_all;
+mod stratified;
 
 pub struct Writer;
 
@@ -16,9 +18,12 @@ impl Writer {
             writing_cfg.file.display(),
             writing_cfg.category
         );
-        let result = match writing_cfg.category {
+        let result = match &writing_cfg.category {
             configs::writing::routing::Category::RandomOrAll { seed, max_count } => {
-                random_or_all::Writer::new(seed, max_count).write(graph, routing_cfg, writing_cfg)
+                random_or_all::Writer::new(*seed, *max_count).write(graph, routing_cfg, writing_cfg)
+            }
+            configs::writing::routing::Category::StratifiedByDistance { seed, bands } => {
+                stratified::Writer::new(*seed, bands.clone()).write(graph, routing_cfg, writing_cfg)
             }
         };
         info!("FINISHED");
@@ -1,6 +1,7 @@
 use crate::{
-    configs,
+    configs, defaults,
     helpers::err,
+    io,
     network::{Graph, NodeIdx},
     routing::{dijkstra, dijkstra::Dijkstra},
 };
@@ -10,12 +11,7 @@ use rand::{
     distributions::{Distribution, Uniform},
     SeedableRng,
 };
-use std::{
-    cmp::min,
-    collections::HashSet,
-    fs::OpenOptions,
-    io::{BufWriter, Write},
-};
+use std::{cmp::min, collections::HashSet, io::Write};
 
 pub struct Writer {
     seed: u64,
@@ -37,11 +33,7 @@ impl Writer {
     ) -> err::Feedback {
         // prepare
 
-        let output_file = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&writing_cfg.file)?;
-        let mut writer = BufWriter::new(output_file);
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.file)?;
 
         let nodes = graph.nodes();
         let fwd_edges = graph.fwd_edges();
@@ -107,8 +99,10 @@ impl Writer {
                         .compute_best_path(dijkstra::Query {
                             src_idx,
                             dst_idx,
-                            graph: &graph,
+                            graph,
                             routing_cfg: &routing_cfg,
+                            edge_bias: None,
+                            path_hint: dijkstra::PathHint::Auto,
                         })
                         .is_some()
                 {
@@ -132,9 +126,18 @@ impl Writer {
 
         // write header
 
-        writeln!(writer, "# graph-file: {}", graph.cfg().map_file.display())?;
+        for map_file in graph.cfg().map_files.iter() {
+            writeln!(writer, "# graph-file: {}", map_file.display())?;
+        }
         writeln!(writer, "# node-count: {}", nodes.count(),)?;
         writeln!(writer, "# edge-count: {}", fwd_edges.count(),)?;
+        // route-pairs are filtered by the routing-config
+        writeln!(
+            writer,
+            "{} {}",
+            defaults::routing::EMBEDDED_CFG_PREFIX,
+            routing_cfg.to_embedded(graph.cfg())
+        )?;
         writeln!(writer, "")?;
 
         // write route-count
@@ -147,13 +150,13 @@ impl Writer {
 
         writeln!(
             writer,
-            "# random routes: (src-id dst-id count) as (i64, i64, usize)"
+            "# random routes: (src-id dst-id count) as (i64, i64, f64)"
         )?;
         writeln!(writer, "# seed: {}", self.seed)?;
         for (src_id, dst_id) in found_route_pairs {
             writeln!(writer, "{} {} {}", src_id, dst_id, 1)?;
         }
 
-        Ok(())
+        writer.commit()
     }
 }
@@ -0,0 +1,217 @@
+use crate::{
+    configs, defaults,
+    helpers::err,
+    io,
+    network::{Graph, NodeIdx},
+    routing::{dijkstra, dijkstra::Dijkstra},
+};
+use kissunits::{distance::Kilometers, geo};
+use log::{info, warn};
+use progressing::{bernoulli::Bar as BernoulliBar, Baring};
+use rand::{
+    distributions::{Distribution, Uniform},
+    SeedableRng,
+};
+use std::{collections::HashSet, io::Write};
+
+pub struct Writer {
+    seed: u64,
+    bands: Vec<(Kilometers, usize)>,
+}
+
+impl Writer {
+    pub fn new(seed: u64, bands: Vec<(Kilometers, usize)>) -> Writer {
+        Writer { seed, bands }
+    }
+
+    /// Returns the idx of the band, whose range contains the given distance.
+    fn band_idx(&self, distance: Kilometers) -> Option<usize> {
+        // bands are sorted by their max-distance
+        self.bands
+            .iter()
+            .position(|(max_distance, _count)| *distance < **max_distance)
+    }
+}
+
+impl Writer {
+    /// Samples random route-pairs and sorts them into their band by their haversine-distance,
+    /// which is way quicker than routing every candidate.
+    /// Like with `random_or_all`, only route-pairs with an existing path are accepted.
+    ///
+    /// A band, which hasn't got a route-pair within `MAX_ATTEMPTS_PER_BAND` attempts, is given up
+    /// with a warning, hence it may contain less route-pairs than requested.
+    /// A graph without nodes is an error, since no route-pair can be sampled.
+    pub fn write(
+        &self,
+        graph: &Graph,
+        routing_cfg: &configs::routing::Config,
+        writing_cfg: &configs::writing::routing::Config,
+    ) -> err::Feedback {
+        // prepare
+
+        if graph.nodes().count() == 0 {
+            return Err(err::Msg::from(
+                "Route-pairs can't be sampled, because the graph has no nodes.",
+            ));
+        }
+
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.file)?;
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+
+        // create routes
+
+        let total_count: usize = self.bands.iter().map(|(_max_distance, count)| count).sum();
+        let mut found_route_pairs: Vec<Vec<(i64, i64)>> = self
+            .bands
+            .iter()
+            .map(|(_max_distance, count)| Vec::with_capacity(*count))
+            .collect();
+
+        {
+            let mut processed_indices = HashSet::new();
+
+            let mut rng = rand_pcg::Pcg32::seed_from_u64(self.seed);
+            let die = Uniform::from(0..nodes.count());
+
+            let mut dijkstra = Dijkstra::new();
+
+            // attempts since a band's last found route-pair
+            let mut attempts = vec![0; self.bands.len()];
+            let is_open = |band_idx: usize,
+                           found_route_pairs: &Vec<Vec<(i64, i64)>>,
+                           attempts: &Vec<usize>| {
+                found_route_pairs[band_idx].len() < self.bands[band_idx].1
+                    && attempts[band_idx] < defaults::writing::MAX_ATTEMPTS_PER_BAND
+            };
+
+            let mut progress_bar = BernoulliBar::with_goal(total_count).timed();
+            info!("{}", progress_bar);
+
+            // Stop when every band is full or has been given up.
+            while (0..self.bands.len()).any(|i| is_open(i, &found_route_pairs, &attempts)) {
+                if progress_bar.has_progressed_significantly() {
+                    progress_bar.remember_significant_progress();
+                    info!("{}", progress_bar);
+                }
+
+                for band_idx in 0..self.bands.len() {
+                    attempts[band_idx] += 1;
+                }
+
+                let src_idx = NodeIdx(die.sample(&mut rng));
+                let dst_idx = NodeIdx(die.sample(&mut rng));
+                if src_idx == dst_idx || !processed_indices.insert((src_idx, dst_idx)) {
+                    progress_bar.add(false);
+                    continue;
+                }
+
+                let distance = geo::haversine_distance_km(
+                    &nodes.create(src_idx).coord(),
+                    &nodes.create(dst_idx).coord(),
+                );
+                let band_idx = match self.band_idx(distance) {
+                    Some(band_idx) if is_open(band_idx, &found_route_pairs, &attempts) => band_idx,
+                    _ => {
+                        progress_bar.add(false);
+                        continue;
+                    }
+                };
+
+                if dijkstra
+                    .compute_best_path(dijkstra::Query {
+                        src_idx,
+                        dst_idx,
+                        graph,
+                        routing_cfg: &routing_cfg,
+                        edge_bias: None,
+                        path_hint: dijkstra::PathHint::Auto,
+                    })
+                    .is_some()
+                {
+                    found_route_pairs[band_idx]
+                        .push((nodes.create(src_idx).id(), nodes.create(dst_idx).id()));
+                    attempts[band_idx] = 0;
+                    progress_bar.add(true);
+                } else {
+                    progress_bar.add(false);
+                }
+            }
+
+            if progress_bar.has_progressed_significantly() {
+                progress_bar.remember_significant_progress();
+                info!("{}", progress_bar);
+            }
+        }
+
+        let mut min_km = 0.0;
+        for ((max_distance, count), route_pairs) in self.bands.iter().zip(&mut found_route_pairs) {
+            if route_pairs.len() < *count {
+                warn!(
+                    "Gave up the band [{}, {}) km after {} attempts with only {} of {} route-pairs.",
+                    min_km,
+                    **max_distance,
+                    defaults::writing::MAX_ATTEMPTS_PER_BAND,
+                    route_pairs.len(),
+                    count
+                );
+            }
+            route_pairs.sort();
+            min_km = **max_distance;
+        }
+
+        // write header
+
+        for map_file in graph.cfg().map_files.iter() {
+            writeln!(writer, "# graph-file: {}", map_file.display())?;
+        }
+        writeln!(writer, "# node-count: {}", nodes.count(),)?;
+        writeln!(writer, "# edge-count: {}", fwd_edges.count(),)?;
+        // route-pairs are filtered by the routing-config
+        writeln!(
+            writer,
+            "{} {}",
+            defaults::routing::EMBEDDED_CFG_PREFIX,
+            routing_cfg.to_embedded(graph.cfg())
+        )?;
+        writeln!(writer, "")?;
+
+        // write route-count
+
+        writeln!(writer, "# route-count")?;
+        writeln!(
+            writer,
+            "{}",
+            found_route_pairs
+                .iter()
+                .map(|route_pairs| route_pairs.len())
+                .sum::<usize>()
+        )?;
+        writeln!(writer, "")?;
+
+        // write routes
+
+        writeln!(
+            writer,
+            "# stratified routes: (src-id dst-id count) as (i64, i64, f64)"
+        )?;
+        writeln!(writer, "# seed: {}", self.seed)?;
+        let mut min_km = 0.0;
+        for ((max_distance, _count), route_pairs) in self.bands.iter().zip(found_route_pairs) {
+            writeln!(
+                writer,
+                "# band: [{}, {}) km with {} route-pairs",
+                min_km,
+                **max_distance,
+                route_pairs.len()
+            )?;
+            for (src_id, dst_id) in route_pairs {
+                writeln!(writer, "{} {} {}", src_id, dst_id, 1)?;
+            }
+            min_km = **max_distance;
+        }
+
+        writer.commit()
+    }
+}
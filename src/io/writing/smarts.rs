@@ -1,11 +1,12 @@
 use crate::{
-    configs::writing::smarts::Config as WritingConfig, defaults, helpers::err,
-    io::SupportingFileExts, network::Graph, routing::paths::Path,
-};
-use std::{
-    fs::OpenOptions,
-    io::{BufWriter, Write},
+    configs::writing::smarts::Config as WritingConfig,
+    defaults,
+    helpers::err,
+    io::{self, SupportingFileExts},
+    network::Graph,
+    routing::paths::Path,
 };
+use std::io::Write;
 
 pub struct Writer;
 
@@ -18,11 +19,7 @@ impl Writer {
     ) -> err::Feedback {
         // prepare
 
-        let output_file = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&writing_cfg.file)?;
-        let mut writer = BufWriter::new(output_file);
+        let mut writer = io::files::AtomicFile::create(&writing_cfg.file)?;
 
         let fwd_edges = graph.fwd_edges();
         let bwd_edges = graph.bwd_edges();
@@ -68,7 +65,7 @@ impl Writer {
 
         writeln!(writer, "</data>")?;
 
-        Ok(())
+        writer.commit()
     }
 }
 
@@ -1,4 +1,6 @@
 pub mod approximating;
+pub mod balancing;
+pub mod cli;
 pub mod configs;
 pub mod defaults;
 pub mod helpers;
@@ -0,0 +1,310 @@
+use super::{EdgeIdx, Graph, GraphView, LeavingEdge, LeavingEdges, NodeIdx};
+use crate::{configs::parsing::Config, defaults::capacity::DimVec, helpers::err};
+use log::info;
+use smallvec::smallvec;
+use std::cmp::Ordering;
+
+/// Combines multiple graphs, e.g. of neighbouring regions, for routing across their borders
+/// without building one big graph.
+///
+/// All nodes and edges are accessible in one index-space, where the indices of each graph are
+/// shifted by the counts of all previous graphs.
+/// Graphs are joined at pairs of node-ids, which become zero-cost connector-edges in both
+/// directions.
+/// Connector-edges are indexed after all edges of the graphs and don't have ids.
+///
+/// Since ch-levels of different graphs are not comparable, the composite graph has no levels.
+/// Hence only a (non-ch) Dijkstra can route on it (see `GraphView`).
+/// Paths found on it refer to the composite indices, so their costs are calculated with
+/// `calc_costs(...)` instead of `Path::calc_costs(...)`.
+#[derive(Debug)]
+pub struct CompositeGraph {
+    graphs: Vec<Graph>,
+    // (len + 1) with node-/edge-counts in total at the end
+    node_offsets: Vec<usize>,
+    edge_offsets: Vec<usize>,
+    // connectors sorted by src-idx, e.g. fwd_connectors[i] is leaving fwd_connector_srcs[i]
+    fwd_connector_srcs: Vec<NodeIdx>,
+    fwd_connectors: Vec<LeavingEdge>,
+    bwd_connector_srcs: Vec<NodeIdx>,
+    bwd_connectors: Vec<LeavingEdge>,
+    // (src-idx, dst-idx) of connector-edges, indexed by (edge-idx - total edge-count)
+    connector_nodes: Vec<(NodeIdx, NodeIdx)>,
+}
+
+impl CompositeGraph {
+    /// Every join `(id_a, id_b)` connects every node of id `id_a` with every node of id `id_b`
+    /// in all other graphs, e.g. `(id, id)` joins graphs sharing a boundary-node.
+    pub fn try_new(graphs: Vec<Graph>, joins: Vec<(i64, i64)>) -> err::Result<CompositeGraph> {
+        info!(
+            "START Combine {} graphs at {} joins.",
+            graphs.len(),
+            joins.len()
+        );
+
+        let first_graph = graphs.first().ok_or(err::Msg::from(
+            "A composite graph needs at least one graph.",
+        ))?;
+        // metrics are added up, so they have to be equal
+        let metric_ids = &first_graph.cfg().edges.metrics.ids;
+        for graph in &graphs[1..] {
+            if &graph.cfg().edges.metrics.ids != metric_ids {
+                return Err(format!(
+                    "All graphs of a composite graph need the same metrics, \
+                     but {:?} and {:?} differ.",
+                    metric_ids,
+                    graph.cfg().edges.metrics.ids
+                )
+                .into());
+            }
+        }
+
+        let mut node_offsets = vec![0];
+        let mut edge_offsets = vec![0];
+        for graph in &graphs {
+            node_offsets.push(node_offsets[node_offsets.len() - 1] + graph.nodes().count());
+            edge_offsets.push(edge_offsets[edge_offsets.len() - 1] + graph.fwd_edges().count());
+        }
+        let edge_count = edge_offsets[graphs.len()];
+
+        // create connector-edges in both directions
+
+        let mut connector_nodes = Vec::new();
+        for &(id_a, id_b) in &joins {
+            let mut is_joined = false;
+            for (graph_a, graph) in graphs.iter().enumerate() {
+                let idx_a = match graph.nodes().idx_from(id_a) {
+                    Ok(idx) => NodeIdx(*idx + node_offsets[graph_a]),
+                    Err(_) => continue,
+                };
+                for (graph_b, graph) in graphs.iter().enumerate() {
+                    if graph_a == graph_b {
+                        continue;
+                    }
+                    if let Ok(idx) = graph.nodes().idx_from(id_b) {
+                        let idx_b = NodeIdx(*idx + node_offsets[graph_b]);
+                        // (a, a) would be created twice
+                        if id_a != id_b || graph_a < graph_b {
+                            connector_nodes.push((idx_a, idx_b));
+                            connector_nodes.push((idx_b, idx_a));
+                        }
+                        is_joined = true;
+                    }
+                }
+            }
+            if !is_joined {
+                return Err(format!(
+                    "The join of node-ids ({}, {}) doesn't connect nodes of different graphs.",
+                    id_a, id_b
+                )
+                .into());
+            }
+        }
+
+        let mut fwd_connectors: Vec<_> = connector_nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &(src_idx, dst_idx))| {
+                (
+                    src_idx,
+                    LeavingEdge {
+                        idx: EdgeIdx(edge_count + i),
+                        dst_idx,
+                    },
+                )
+            })
+            .collect();
+        fwd_connectors.sort_by_key(|(src_idx, edge)| (*src_idx, edge.idx));
+        let mut bwd_connectors: Vec<_> = connector_nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &(src_idx, dst_idx))| {
+                (
+                    dst_idx,
+                    LeavingEdge {
+                        idx: EdgeIdx(edge_count + i),
+                        dst_idx: src_idx,
+                    },
+                )
+            })
+            .collect();
+        bwd_connectors.sort_by_key(|(src_idx, edge)| (*src_idx, edge.idx));
+
+        info!("FINISHED");
+        Ok(CompositeGraph {
+            graphs,
+            node_offsets,
+            edge_offsets,
+            fwd_connector_srcs: fwd_connectors.iter().map(|(idx, _)| *idx).collect(),
+            fwd_connectors: fwd_connectors.into_iter().map(|(_, edge)| edge).collect(),
+            bwd_connector_srcs: bwd_connectors.iter().map(|(idx, _)| *idx).collect(),
+            bwd_connectors: bwd_connectors.into_iter().map(|(_, edge)| edge).collect(),
+            connector_nodes,
+        })
+    }
+
+    pub fn new(graphs: Vec<Graph>, joins: Vec<(i64, i64)>) -> CompositeGraph {
+        match CompositeGraph::try_new(graphs, joins) {
+            Ok(composite) => composite,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    pub fn graphs(&self) -> &[Graph] {
+        &self.graphs
+    }
+
+    /// Returns the composite node-idx of the given graph's node-id.
+    pub fn idx_from(&self, graph_idx: usize, id: i64) -> Option<NodeIdx> {
+        let idx = self.graphs.get(graph_idx)?.nodes().idx_from(id).ok()?;
+        Some(NodeIdx(*idx + self.node_offsets[graph_idx]))
+    }
+
+    /// Returns the graph's index and the node-idx inside this graph.
+    pub fn locate_node(&self, idx: NodeIdx) -> (usize, NodeIdx) {
+        let graph_idx = CompositeGraph::locate(&self.node_offsets, *idx);
+        (graph_idx, NodeIdx(*idx - self.node_offsets[graph_idx]))
+    }
+
+    /// Returns the graph's index and the edge-idx inside this graph,
+    /// or None for connector-edges.
+    pub fn locate_edge(&self, idx: EdgeIdx) -> Option<(usize, EdgeIdx)> {
+        if *idx >= self.edge_offsets[self.graphs.len()] {
+            return None;
+        }
+        let graph_idx = CompositeGraph::locate(&self.edge_offsets, *idx);
+        Some((graph_idx, EdgeIdx(*idx - self.edge_offsets[graph_idx])))
+    }
+
+    /// Sums up the metrics of the given composite edges, e.g. of a found path.
+    pub fn calc_costs<'a, I>(&self, edges: I) -> DimVec<f64>
+    where
+        I: IntoIterator<Item = &'a EdgeIdx>,
+    {
+        let mut costs = smallvec![0.0; self.cfg().edges.metrics.units.len()];
+        for &edge_idx in edges {
+            for (cost, value) in costs.iter_mut().zip(self.edge_metrics(edge_idx)) {
+                *cost += value;
+            }
+        }
+        costs
+    }
+
+    /// Returns the index of the last offset, which is less or equal to the given value.
+    /// Offsets of empty graphs are equal to their successor's offsets, hence they are skipped.
+    fn locate(offsets: &[usize], value: usize) -> usize {
+        // offsets[0] == 0 <= value
+        offsets[..offsets.len() - 1]
+            .iter()
+            .rposition(|&offset| offset <= value)
+            .expect("The first offset should be 0.")
+    }
+
+    fn connectors<'a>(
+        srcs: &[NodeIdx],
+        connectors: &'a [LeavingEdge],
+        idx: NodeIdx,
+    ) -> &'a [LeavingEdge] {
+        let start = srcs
+            .binary_search_by(|src_idx| src_idx.cmp(&idx).then(Ordering::Greater))
+            .unwrap_err();
+        let end = srcs
+            .binary_search_by(|src_idx| src_idx.cmp(&idx).then(Ordering::Less))
+            .unwrap_err();
+        &connectors[start..end]
+    }
+}
+
+impl GraphView for CompositeGraph {
+    /// Returns the config of the first graph, whose metrics are equal to all other graphs.
+    fn cfg(&self) -> &Config {
+        self.graphs[0].cfg()
+    }
+
+    fn node_count(&self) -> usize {
+        self.node_offsets[self.graphs.len()]
+    }
+
+    fn node_id(&self, idx: NodeIdx) -> i64 {
+        let (graph_idx, idx) = self.locate_node(idx);
+        self.graphs[graph_idx].nodes().id(idx)
+    }
+
+    fn has_levels(&self) -> bool {
+        false
+    }
+
+    fn node_level(&self, _idx: NodeIdx) -> Option<usize> {
+        None
+    }
+
+    fn are_leaving_edges_level_sorted(&self) -> bool {
+        false
+    }
+
+    fn edge_id(&self, idx: EdgeIdx) -> Option<usize> {
+        let (graph_idx, idx) = self.locate_edge(idx)?;
+        self.graphs[graph_idx].fwd_edges().try_id(idx)
+    }
+
+    fn fwd_leaving_edges(&self, idx: NodeIdx) -> LeavingEdges {
+        let (graph_idx, inner_idx) = self.locate_node(idx);
+        self.graphs[graph_idx]
+            .fwd_leaving_edges(inner_idx)
+            .with_offsets(self.node_offsets[graph_idx], self.edge_offsets[graph_idx])
+            .with_additional_edges(CompositeGraph::connectors(
+                &self.fwd_connector_srcs,
+                &self.fwd_connectors,
+                idx,
+            ))
+    }
+
+    fn bwd_leaving_edges(&self, idx: NodeIdx) -> LeavingEdges {
+        let (graph_idx, inner_idx) = self.locate_node(idx);
+        self.graphs[graph_idx]
+            .bwd_leaving_edges(inner_idx)
+            .with_offsets(self.node_offsets[graph_idx], self.edge_offsets[graph_idx])
+            .with_additional_edges(CompositeGraph::connectors(
+                &self.bwd_connector_srcs,
+                &self.bwd_connectors,
+                idx,
+            ))
+    }
+
+    fn fwd_dst_idx(&self, idx: EdgeIdx) -> NodeIdx {
+        match self.locate_edge(idx) {
+            Some((graph_idx, idx)) => {
+                NodeIdx(*self.graphs[graph_idx].fwd_dst_idx(idx) + self.node_offsets[graph_idx])
+            }
+            None => self.connector_nodes[*idx - self.edge_offsets[self.graphs.len()]].1,
+        }
+    }
+
+    fn bwd_dst_idx(&self, idx: EdgeIdx) -> NodeIdx {
+        match self.locate_edge(idx) {
+            Some((graph_idx, idx)) => {
+                NodeIdx(*self.graphs[graph_idx].bwd_dst_idx(idx) + self.node_offsets[graph_idx])
+            }
+            None => self.connector_nodes[*idx - self.edge_offsets[self.graphs.len()]].0,
+        }
+    }
+
+    fn edge_metrics(&self, idx: EdgeIdx) -> DimVec<f64> {
+        match self.locate_edge(idx) {
+            Some((graph_idx, idx)) => self.graphs[graph_idx].metrics()[idx].clone(),
+            None => smallvec![0.0; self.cfg().edges.metrics.units.len()],
+        }
+    }
+
+    /// Turns between graphs (via connectors) are never restricted.
+    fn is_turn_forbidden(&self, from_idx: EdgeIdx, to_idx: EdgeIdx) -> bool {
+        match (self.locate_edge(from_idx), self.locate_edge(to_idx)) {
+            (Some((from_graph_idx, from_idx)), Some((to_graph_idx, to_idx)))
+                if from_graph_idx == to_graph_idx =>
+            {
+                self.graphs[from_graph_idx].is_turn_forbidden(from_idx, to_idx)
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use super::{EdgeIdx, Graph, IndexMapping, NodeIdx};
+use crate::{defaults, helpers::err};
+use kissunits::geo::Coordinate;
+use log::info;
+use std::sync::Arc;
+
+/// Edges found by `Graph::edges_in_bbox(...)`, sorted by their indices.
+#[derive(Clone, Debug)]
+pub struct BboxEdges {
+    edges: Vec<EdgeIdx>,
+    is_truncated: bool,
+}
+
+impl BboxEdges {
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = EdgeIdx> + 'a {
+        self.edges.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// True, if more edges than the requested maximum are in the bounding-box.
+    pub fn is_truncated(&self) -> bool {
+        self.is_truncated
+    }
+}
+
+impl IntoIterator for BboxEdges {
+    type Item = EdgeIdx;
+    type IntoIter = std::vec::IntoIter<EdgeIdx>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.edges.into_iter()
+    }
+}
+
+/// Uniform grid over the graph's bounding-box, where every cell stores the edges, whose
+/// bounding-box overlaps the cell.
+///
+/// The graph builds its grid once on demand (see `Graph::edge_grid()`).
+#[derive(Debug)]
+pub struct EdgeGrid {
+    min: Coordinate,
+    cell_lat: f64,
+    cell_lon: f64,
+    rows: usize,
+    cols: usize,
+    cells: Vec<Vec<EdgeIdx>>,
+}
+
+impl EdgeGrid {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the cells' range (rows, cols) covering the given box, clamped to the grid.
+    fn cell_range(
+        &self,
+        min: &Coordinate,
+        max: &Coordinate,
+    ) -> (
+        std::ops::RangeInclusive<usize>,
+        std::ops::RangeInclusive<usize>,
+    ) {
+        let row_of = |lat: f64| {
+            (((lat - self.min.lat) / self.cell_lat).max(0.0) as usize).min(self.rows - 1)
+        };
+        let col_of = |lon: f64| {
+            (((lon - self.min.lon) / self.cell_lon).max(0.0) as usize).min(self.cols - 1)
+        };
+        (
+            row_of(min.lat)..=row_of(max.lat),
+            col_of(min.lon)..=col_of(max.lon),
+        )
+    }
+}
+
+impl Graph {
+    /// Returns all edges, which have at least one node inside the given box or cross it.
+    /// Edges are treated as straight lines between their nodes' coordinates.
+    /// Shortcuts are skipped, since they are no streets.
+    ///
+    /// With `max_results`, only the edges of smallest indices are returned and the result is
+    /// marked as truncated, if more edges have been found.
+    ///
+    /// The underlying edge-grid is built once on demand, so only edges near the box are tested.
+    pub fn edges_in_bbox(
+        &self,
+        min: Coordinate,
+        max: Coordinate,
+        max_results: Option<usize>,
+    ) -> BboxEdges {
+        let mut edges = Vec::new();
+        if self.node_coords.is_empty() || min.lat > max.lat || min.lon > max.lon {
+            return BboxEdges {
+                edges,
+                is_truncated: false,
+            };
+        }
+
+        let grid = self.edge_grid();
+        let (rows, cols) = grid.cell_range(&min, &max);
+        for row in rows {
+            for col in cols.clone() {
+                edges.extend(&grid.cells[row * grid.cols + col]);
+            }
+        }
+        // edges can overlap multiple cells
+        edges.sort_unstable();
+        edges.dedup();
+
+        let bwd_edges = self.bwd_edges();
+        let fwd_edges = self.fwd_edges();
+        edges.retain(|&edge_idx| {
+            let src = self.node_coords[*bwd_edges.dst_idx(edge_idx)];
+            let dst = self.node_coords[*fwd_edges.dst_idx(edge_idx)];
+            intersects(&src, &dst, &min, &max)
+        });
+
+        let is_truncated = max_results.map_or(false, |max_results| edges.len() > max_results);
+        if let Some(max_results) = max_results {
+            edges.truncate(max_results);
+        }
+        BboxEdges {
+            edges,
+            is_truncated,
+        }
+    }
+
+    /// Extracts the subgraph of all nodes inside the given box (borders included) and all edges
+    /// between them (see `Graph::subgraph(...)`), e.g. for experiments on a part of a big graph
+    /// without parsing its map-file again.
+    ///
+    /// Node-ids are kept, so results can be compared to the original graph, while node-indices
+    /// and edge-indices are remapped.
+    /// Contracted graphs lose their shortcuts, which could bypass nodes outside the box.
+    pub fn try_subgraph_within_bbox(
+        &self,
+        min: Coordinate,
+        max: Coordinate,
+    ) -> err::Result<(Graph, IndexMapping)> {
+        if !(min.lat <= max.lat && min.lon <= max.lon) {
+            return Err(format!(
+                "The bbox's min ({}) should be south-west of its max ({}).",
+                min, max
+            )
+            .into());
+        }
+
+        let nodes: Vec<NodeIdx> = (0..self.node_coords.len())
+            .map(NodeIdx)
+            .filter(|&node_idx| {
+                let coord = &self.node_coords[*node_idx];
+                min.lat <= coord.lat
+                    && coord.lat <= max.lat
+                    && min.lon <= coord.lon
+                    && coord.lon <= max.lon
+            })
+            .collect();
+        self.try_subgraph(&nodes)
+    }
+
+    pub fn subgraph_within_bbox(&self, min: Coordinate, max: Coordinate) -> (Graph, IndexMapping) {
+        match self.try_subgraph_within_bbox(min, max) {
+            Ok(subgraph) => subgraph,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Returns the cached edge-grid and builds it, if not done yet.
+    /// The graph should have nodes.
+    pub fn edge_grid(&self) -> Arc<EdgeGrid> {
+        let mut cache = self
+            .edge_grid
+            .lock()
+            .expect("The edge-grid's cache shouldn't be poisoned.");
+        if let Some(grid) = cache.as_ref() {
+            return Arc::clone(grid);
+        }
+
+        let grid = Arc::new(self.compute_edge_grid());
+        *cache = Some(Arc::clone(&grid));
+        grid
+    }
+
+    fn compute_edge_grid(&self) -> EdgeGrid {
+        info!("START Build edge-grid for bounding-box-queries.");
+
+        let mut min = self.node_coords[0];
+        let mut max = self.node_coords[0];
+        for coord in &self.node_coords {
+            min.lat = min.lat.min(coord.lat);
+            min.lon = min.lon.min(coord.lon);
+            max.lat = max.lat.max(coord.lat);
+            max.lon = max.lon.max(coord.lon);
+        }
+
+        // about square in cell-count
+        let fwd_edges = self.fwd_edges();
+        let cell_count = (fwd_edges.count() / defaults::network::edges::PER_GRID_CELL).max(1);
+        let side = (cell_count as f64).sqrt().ceil() as usize;
+        let mut grid = EdgeGrid {
+            min,
+            // avoid division by zero for graphs without extent
+            cell_lat: ((max.lat - min.lat) / side as f64).max(std::f64::EPSILON),
+            cell_lon: ((max.lon - min.lon) / side as f64).max(std::f64::EPSILON),
+            rows: side,
+            cols: side,
+            cells: vec![Vec::new(); side * side],
+        };
+
+        for src_idx in (0..self.node_ids.len()).map(NodeIdx) {
+            let src = self.node_coords[*src_idx];
+            for offset in self.fwd_offsets.get(*src_idx)..self.fwd_offsets.get(*src_idx + 1) {
+                let edge_idx = self.fwd_to_fwd_map.get(offset);
+                if fwd_edges.is_shortcut(edge_idx) {
+                    continue;
+                }
+
+                let dst = self.node_coords[*self.fwd_dsts[*edge_idx]];
+                let (rows, cols) = grid.cell_range(
+                    &Coordinate {
+                        lat: src.lat.min(dst.lat),
+                        lon: src.lon.min(dst.lon),
+                    },
+                    &Coordinate {
+                        lat: src.lat.max(dst.lat),
+                        lon: src.lon.max(dst.lon),
+                    },
+                );
+                for row in rows {
+                    for col in cols.clone() {
+                        grid.cells[row * grid.cols + col].push(edge_idx);
+                    }
+                }
+            }
+        }
+
+        info!(
+            "FINISHED Built edge-grid of {}x{} cells.",
+            grid.rows, grid.cols
+        );
+        grid
+    }
+}
+
+/// Clips the line from `src` to `dst` to the box (Liang-Barsky), treating coordinates as planar.
+/// The line intersects the box, if anything remains.
+fn intersects(src: &Coordinate, dst: &Coordinate, min: &Coordinate, max: &Coordinate) -> bool {
+    let d_lat = dst.lat - src.lat;
+    let d_lon = dst.lon - src.lon;
+    let mut t_start: f64 = 0.0;
+    let mut t_end: f64 = 1.0;
+
+    for &(p, q) in &[
+        (-d_lon, src.lon - min.lon),
+        (d_lon, max.lon - src.lon),
+        (-d_lat, src.lat - min.lat),
+        (d_lat, max.lat - src.lat),
+    ] {
+        if p == 0.0 {
+            // parallel to this border, and outside
+            if q < 0.0 {
+                return false;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t_start = t_start.max(t);
+            } else {
+                t_end = t_end.min(t);
+            }
+            if t_start > t_end {
+                return false;
+            }
+        }
+    }
+
+    true
+}
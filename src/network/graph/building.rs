@@ -1,4 +1,7 @@
-use super::{EdgeIdx, Graph, NodeIdx};
+use super::{
+    EdgeIdx, EdgeIdxVec, Graph, MetricIdx, NodeIdx, NodeIndex, OffsetVec, Provenance,
+    ProvenanceColumn, TurnRestriction,
+};
 use crate::{
     approximating::Approx,
     configs::parsing::{self, generating},
@@ -7,64 +10,219 @@ use crate::{
         capacity::{self, DimVec},
         routing::IS_USING_CH_LEVEL_SPEEDUP,
     },
-    helpers::{self, err, MemSize},
+    helpers::{err, MemSize},
     io,
 };
-use kissunits::geo::Coordinate;
-use log::{debug, info, trace};
+use kissunits::{
+    distance::Meters,
+    geo::{haversine_distance_km, Coordinate},
+};
+use log::{debug, info, trace, warn};
 use progressing::{mapping::Bar as MappingBar, Baring};
-use smallvec::smallvec;
 use std::{
-    cmp::{min, Reverse},
+    cmp::{max, min, Ordering, Reverse},
+    collections::BTreeMap,
     mem,
+    sync::Mutex,
 };
 
 /// private stuff for graph-building
 impl Graph {
     fn new(cfg: parsing::Config) -> Graph {
+        let is_compact = cfg.is_using_compact_indices;
         Graph {
             cfg,
             // nodes
             node_ids: Vec::new(),
             // node-metrics
             node_coords: Vec::new(),
-            node_ch_levels: Vec::new(),
+            node_ch_levels: None,
+            node_customs: Vec::new(),
+            node_custom_dim: 0,
             // edges
             fwd_dsts: Vec::new(),
-            fwd_offsets: Vec::new(),
-            fwd_to_fwd_map: Vec::new(),
+            fwd_offsets: OffsetVec::new(is_compact),
+            fwd_to_fwd_map: EdgeIdxVec::new(is_compact),
             bwd_dsts: Vec::new(),
-            bwd_offsets: Vec::new(),
-            bwd_to_fwd_map: Vec::new(),
+            bwd_offsets: OffsetVec::new(is_compact),
+            bwd_to_fwd_map: EdgeIdxVec::new(is_compact),
             // edge-metrics
             metrics: Vec::new(),
+            metric_rows: Vec::new(),
             means: None,
             // edge-ids
             edge_ids: Vec::new(),
             edge_ids_to_idx_map: Vec::new(),
+            stable_ids: Mutex::new(None),
+            provenances: None,
             // shortcuts (contraction-hierarchies)
             sc_offsets: Vec::new(),
             sc_edges: Vec::new(),
+            are_leaving_edges_level_sorted: false,
+            restriction_conditions: BTreeMap::new(),
+            turn_restrictions: BTreeMap::new(),
+            components: Mutex::new(None),
+            edge_grid: Mutex::new(None),
+            node_index: NodeIndex::new(&[]),
+            metric_ranks: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Checks for every node, if its leaving edges are sorted descendingly by their dst's level.
+    /// Without levels, the edges can't be sorted by them.
+    ///
+    /// The edges are sorted in place when building (by the proto-edges' sort-key forwards and
+    /// per node's slice backwards), hence this only verifies that order without allocating.
+    fn check_level_sorting(&self) -> bool {
+        let nodes = self.nodes();
+        if !nodes.has_levels() {
+            return false;
         }
+        [self.fwd_edges(), self.bwd_edges()]
+            .iter()
+            .all(|xwd_edges| {
+                (0..nodes.count()).map(NodeIdx).all(|node_idx| {
+                    let mut levels = xwd_edges
+                        .starting_from(node_idx)
+                        .map(|leaving_edge| nodes.level(leaving_edge.dst_idx()));
+                    let mut prev_level = match levels.next() {
+                        Some(level) => level,
+                        None => return true,
+                    };
+                    levels.all(|level| {
+                        let is_sorted = prev_level >= level;
+                        prev_level = level;
+                        is_sorted
+                    })
+                })
+            })
+    }
+
+    /// Builds the backward-offset-array and the backward-mapping from the finished forward-edges,
+    /// so `fwd_dsts` and `bwd_dsts` (src per forward-edge) have to be complete.
+    ///
+    /// Backward-edges have to be grouped by their dst-idx, which is known for every
+    /// forward-edge already. Hence, instead of sorting all proto-edges a second time, the
+    /// backward-edges are counting-sorted into their final positions in O(m).
+    ///
+    /// Memory-comparison (m edges, n nodes, one usize = 8 bytes):
+    /// Sorting proto-edges backwards kept all proto-edges (40 bytes each, including their
+    /// remembered fwd-idx) alive next to the finished forward-arrays, and the stable sort
+    /// allocated another m/2 proto-edges temporarily (20 bytes per edge).
+    /// Counting-sort doesn't need the proto-edges, so they can be dropped after the forward-pass,
+    /// and only the final arrays `bwd_to_fwd_map` (8 bytes per edge) and `bwd_offsets`
+    /// (8 bytes per node) are allocated.
+    /// With compact indices, both arrays are converted to 4 bytes per entry afterwards.
+    pub(super) fn build_bwd_edges(&mut self) -> err::Result<()> {
+        info!("START Create the backward-offset-array and the backward-mapping.");
+        let node_count = self.nodes().count();
+        let edge_count = self.fwd_dsts.len();
+        crate::trace_scope!(
+            "building::bwd_offsets",
+            nodes = node_count,
+            edges = edge_count
+        );
+        let mut progress_bar = MappingBar::with_range(0, edge_count).timed();
+
+        // count backward-edges per node (their src is the forward-edge's dst)
+        // and prefix-sum them, so offsets[idx] is the first position of node idx
+        let mut bwd_offsets = vec![0; node_count + 1];
+        for &dst_idx in self.fwd_dsts.iter() {
+            bwd_offsets[*dst_idx + 1] += 1;
+        }
+        for node_idx in 1..=node_count {
+            bwd_offsets[node_idx] += bwd_offsets[node_idx - 1];
+        }
+        debug_assert_eq!(
+            bwd_offsets[node_count], edge_count,
+            "Last offset-value should be as big as the number of edges."
+        );
+
+        // Scatter forward-indices into their final positions.
+        // Forward-edges are sorted by src-idx (and id), so iterating them in order keeps every
+        // node's backward-edges sorted by their dst-idx (and id), which equals a stable sort.
+        // Every node's offset is moved forward while scattering, so it ends up being the next
+        // node's offset, which is shifted back afterwards.
+        let mut bwd_to_fwd_map = vec![EdgeIdx(0); edge_count];
+        for (edge_idx, &dst_idx) in self.fwd_dsts.iter().enumerate() {
+            let offset = &mut bwd_offsets[*dst_idx];
+            bwd_to_fwd_map[*offset] = EdgeIdx(edge_idx);
+            *offset += 1;
+
+            // print progress
+            progress_bar.set(edge_idx);
+            if progress_bar.has_progressed_significantly() {
+                progress_bar.remember_significant_progress();
+                info!("{}", progress_bar);
+            }
+        }
+        for node_idx in (1..=node_count).rev() {
+            bwd_offsets[node_idx] = bwd_offsets[node_idx - 1];
+        }
+        bwd_offsets[0] = 0;
+
+        // - sort by dst-idx, then level of src, then src-idx
+        //   -> branch prediction in dijkstra when breaking after level is reached
+        // Only the levels are missing, and stable sorting keeps the order of equal levels.
+        // Every node's backward-edges are sorted separately, which needs at most temporary
+        // memory of the node's degree.
+        let nodes = self.nodes();
+        if IS_USING_CH_LEVEL_SPEEDUP && nodes.has_levels() {
+            for node_idx in 0..node_count {
+                bwd_to_fwd_map[bwd_offsets[node_idx]..bwd_offsets[node_idx + 1]]
+                    .sort_by_key(|&fwd_idx| Reverse(nodes.level(self.bwd_dsts[*fwd_idx])));
+            }
+        }
+
+        let is_compact = self.cfg.is_using_compact_indices;
+        self.bwd_offsets = OffsetVec::try_from_vec(bwd_offsets, is_compact)?;
+        self.bwd_to_fwd_map = EdgeIdxVec::try_from_vec(bwd_to_fwd_map, is_compact)?;
+        progress_bar.set(edge_count);
+        if progress_bar.has_progressed_significantly() {
+            progress_bar.remember_significant_progress();
+            info!("{}", progress_bar);
+        }
+        // reduce and optimize memory-usage
+        self.shrink_to_fit();
+        Ok(())
     }
 
     /// Optimizes capacity of used data-structures.
     fn shrink_to_fit(&mut self) {
         self.node_ids.shrink_to_fit();
         self.node_coords.shrink_to_fit();
+        if let Some(node_ch_levels) = &mut self.node_ch_levels {
+            node_ch_levels.shrink_to_fit();
+        }
+        self.node_customs.shrink_to_fit();
         self.fwd_dsts.shrink_to_fit();
         self.fwd_offsets.shrink_to_fit();
         self.fwd_to_fwd_map.shrink_to_fit();
         self.bwd_dsts.shrink_to_fit();
         self.bwd_offsets.shrink_to_fit();
         self.bwd_to_fwd_map.shrink_to_fit();
-        self.metrics.shrink_to_fit();
+        self.metrics
+            .iter_mut()
+            .for_each(|column| column.shrink_to_fit());
         self.edge_ids.shrink_to_fit();
         self.edge_ids_to_idx_map.shrink_to_fit();
+        if let Some(provenances) = &mut self.provenances {
+            provenances
+                .iter_mut()
+                .for_each(|column| column.shrink_to_fit());
+        }
         self.sc_offsets.shrink_to_fit();
         self.sc_edges.shrink_to_fit();
     }
 
+    /// Adds the provenances of a new metric-column, which have the same value for every edge.
+    fn push_provenances(&mut self, provenance: Provenance) {
+        let edge_count = self.fwd_dsts.len();
+        if let Some(provenances) = &mut self.provenances {
+            provenances.push(ProvenanceColumn::with_value(provenance, edge_count));
+        }
+    }
+
     /// The provided edge is interpreted as forward-edge.
     fn add_metrics(&mut self, proto_edge: &mut ProtoEdgeB) -> err::Feedback {
         let cfg = &self.cfg;
@@ -82,7 +240,9 @@ impl Graph {
             }
         }
 
-        self.metrics.push(proto_edge.metrics.clone());
+        for (column, &metric) in self.metrics.iter_mut().zip(proto_edge.metrics.iter()) {
+            column.push(metric);
+        }
 
         Ok(())
     }
@@ -93,6 +253,8 @@ pub struct ProtoNode {
     pub id: i64,
     pub coord: Coordinate,
     pub ch_level: Option<usize>,
+    /// custom values in the order of the config's `custom_ids()`
+    pub customs: DimVec<f64>,
 }
 
 pub struct ProtoShortcut {
@@ -179,12 +341,11 @@ impl MemSize for ProtoEdgeB {
     }
 }
 
-/// handy for remembering indices after sorting backwards
+/// slim edge for sorting forward-edges and building the forward-offset-array
 #[derive(Debug)]
 struct ProtoEdgeC {
     src_idx: NodeIdx,
     dst_idx: NodeIdx,
-    idx: usize,
     id: Option<usize>,
 }
 
@@ -193,6 +354,12 @@ pub struct EdgeBuilder {
     node_ids: Vec<i64>,
     proto_edges: Vec<ProtoEdgeA>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    // conditions of restricted proto-edges by proto-edge-idx, if configured
+    restriction_conditions: BTreeMap<usize, String>,
+    // turn-restrictions by the node-ids (from-src, via, to-dst) of their turns
+    turn_restrictions: BTreeMap<(i64, i64, i64), TurnRestriction>,
+    // provenances of proto-edges' metrics by proto-edge-idx, if configured
+    provenances: Option<Vec<ProvenanceColumn>>,
 }
 
 impl EdgeBuilder {
@@ -263,9 +430,53 @@ impl EdgeBuilder {
             });
         }
 
+        // parsed values are measured, unless marked as defaulted
+        if let Some(provenances) = &mut self.provenances {
+            for column in provenances.iter_mut() {
+                column.push(Provenance::Measured);
+            }
+        }
+
         Ok(())
     }
 
+    /// Marks the given metric of the most recently inserted proto-edge as defaulted, e.g. if a
+    /// street's default maxspeed has been used, because the map-file doesn't provide one.
+    ///
+    /// Does nothing, if provenances aren't tracked (see `track-provenance`).
+    pub fn mark_defaulted(&mut self, metric_idx: MetricIdx) {
+        if let Some(provenances) = &mut self.provenances {
+            let column = &mut provenances[*metric_idx];
+            if column.len() > 0 {
+                let idx = column.len() - 1;
+                column.set(idx, Provenance::Defaulted);
+            }
+        }
+    }
+
+    /// Keeps the raw condition of the most recently inserted proto-edge, which is conditionally
+    /// restricted (see `configs::parsing::edges::conditional`).
+    pub fn insert_condition(&mut self, condition: String) {
+        if self.proto_edges.len() > 0 {
+            let idx = self.proto_edges.len() - 1;
+            self.restriction_conditions.insert(idx, condition);
+        }
+    }
+
+    /// Keeps a turn-restriction, which has been resolved into the node-ids of its turn.
+    /// Only the first restriction of a turn is kept.
+    pub fn insert_turn_restriction(
+        &mut self,
+        from_src_id: i64,
+        via_id: i64,
+        to_dst_id: i64,
+        turn_restriction: TurnRestriction,
+    ) {
+        self.turn_restrictions
+            .entry((from_src_id, via_id, to_dst_id))
+            .or_insert(turn_restriction);
+    }
+
     pub fn next(mut self) -> NodeBuilder {
         self.proto_edges.shrink_to_fit();
         self.proto_shortcuts.shrink_to_fit();
@@ -277,15 +488,31 @@ impl EdgeBuilder {
 
         let mut node_coords = vec![None; self.node_ids.len()];
         node_coords.shrink_to_fit();
-        let mut node_ch_levels = vec![defaults::network::nodes::LEVEL; self.node_ids.len()];
-        node_ch_levels.shrink_to_fit();
+        // only configured levels need memory
+        let node_ch_levels = if self.cfg.nodes.has_ch_levels() {
+            let mut node_ch_levels = vec![defaults::network::nodes::LEVEL; self.node_ids.len()];
+            node_ch_levels.shrink_to_fit();
+            Some(node_ch_levels)
+        } else {
+            None
+        };
+        // only configured custom values need memory
+        let node_custom_dim = self.cfg.nodes.custom_ids().len();
+        let mut node_customs = vec![0.0; node_custom_dim * self.node_ids.len()];
+        node_customs.shrink_to_fit();
         NodeBuilder {
             cfg: self.cfg,
             node_ids: self.node_ids,
             node_coords,
+            coord_repeats: Vec::new(),
             node_ch_levels,
+            node_customs,
+            node_custom_dim,
             proto_edges: self.proto_edges,
             proto_shortcuts: self.proto_shortcuts,
+            restriction_conditions: self.restriction_conditions,
+            turn_restrictions: self.turn_restrictions,
+            provenances: self.provenances,
         }
     }
 }
@@ -294,9 +521,20 @@ pub struct NodeBuilder {
     cfg: parsing::Config,
     node_ids: Vec<i64>,
     node_coords: Vec<Option<Coordinate>>,
-    node_ch_levels: Vec<usize>,
+    /// node-indices and coordinates of every repeated insertion, in insertion-order
+    coord_repeats: Vec<(usize, Coordinate)>,
+    node_ch_levels: Option<Vec<usize>>,
+    // custom values of node i are stored at i * node_custom_dim
+    node_customs: Vec<f64>,
+    node_custom_dim: usize,
     proto_edges: Vec<ProtoEdgeA>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    // conditions of restricted proto-edges by proto-edge-idx, if configured
+    restriction_conditions: BTreeMap<usize, String>,
+    // turn-restrictions by the node-ids (from-src, via, to-dst) of their turns
+    turn_restrictions: BTreeMap<(i64, i64, i64), TurnRestriction>,
+    // provenances of proto-edges' metrics by proto-edge-idx, if configured
+    provenances: Option<Vec<ProvenanceColumn>>,
 }
 
 impl NodeBuilder {
@@ -304,12 +542,43 @@ impl NodeBuilder {
         &self.cfg
     }
 
+    /// Returns the number of nodes, which are part of an edge and hence expected to be inserted.
+    pub fn node_count(&self) -> usize {
+        self.node_ids.len()
+    }
+
+    /// Returns true if node is part of an edge, hence it would be added when inserted.
+    pub fn contains(&self, id: i64) -> bool {
+        self.node_ids.binary_search(&id).is_ok()
+    }
+
     /// Returns true if node is part of edge and hence has been added.
+    ///
+    /// If a node-id is inserted again, the first coordinate is kept and the repeated coordinate
+    /// is remembered, even if it is equal, since it counts when resolving conflicts.
+    /// Conflicts are resolved when finalizing, according to the config's `on-node-conflict`.
+    /// Custom values are always taken from the first insertion.
     pub fn insert(&mut self, proto_node: ProtoNode) -> bool {
         if let Ok(idx) = self.node_ids.binary_search(&proto_node.id) {
-            self.node_coords[idx] = Some(proto_node.coord);
-            if let Some(ch_level) = proto_node.ch_level {
-                self.node_ch_levels[idx] = ch_level;
+            match self.node_coords[idx] {
+                Some(_coord) => self.coord_repeats.push((idx, proto_node.coord)),
+                None => {
+                    self.node_coords[idx] = Some(proto_node.coord);
+                    let start = idx * self.node_custom_dim;
+                    for (i, &value) in proto_node
+                        .customs
+                        .iter()
+                        .take(self.node_custom_dim)
+                        .enumerate()
+                    {
+                        self.node_customs[start + i] = value;
+                    }
+                }
+            }
+            if let (Some(node_ch_levels), Some(ch_level)) =
+                (&mut self.node_ch_levels, proto_node.ch_level)
+            {
+                node_ch_levels[idx] = ch_level;
             }
             true
         } else {
@@ -322,9 +591,15 @@ impl NodeBuilder {
             cfg: self.cfg,
             node_ids: self.node_ids,
             node_coords: self.node_coords,
+            coord_repeats: self.coord_repeats,
             node_ch_levels: self.node_ch_levels,
+            node_customs: self.node_customs,
+            node_custom_dim: self.node_custom_dim,
             proto_edges: self.proto_edges,
             proto_shortcuts: self.proto_shortcuts,
+            restriction_conditions: self.restriction_conditions,
+            turn_restrictions: self.turn_restrictions,
+            provenances: self.provenances,
         })
     }
 }
@@ -333,19 +608,296 @@ pub struct GraphBuilder {
     cfg: parsing::Config,
     node_ids: Vec<i64>,
     node_coords: Vec<Option<Coordinate>>,
-    node_ch_levels: Vec<usize>,
+    coord_repeats: Vec<(usize, Coordinate)>,
+    node_ch_levels: Option<Vec<usize>>,
+    // custom values of node i are stored at i * node_custom_dim
+    node_customs: Vec<f64>,
+    node_custom_dim: usize,
     proto_edges: Vec<ProtoEdgeA>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    // conditions of restricted proto-edges by proto-edge-idx, if configured
+    restriction_conditions: BTreeMap<usize, String>,
+    // turn-restrictions by the node-ids (from-src, via, to-dst) of their turns
+    turn_restrictions: BTreeMap<(i64, i64, i64), TurnRestriction>,
+    // provenances of proto-edges' metrics by proto-edge-idx, if configured
+    provenances: Option<Vec<ProvenanceColumn>>,
 }
 
 impl GraphBuilder {
     pub fn new(cfg: parsing::Config) -> EdgeBuilder {
+        let provenances = if cfg.edges.metrics.is_tracking_provenance {
+            Some(vec![ProvenanceColumn::new(); cfg.edges.metrics.units.len()])
+        } else {
+            None
+        };
         EdgeBuilder {
             cfg,
             node_ids: Vec::new(),
             proto_edges: Vec::new(),
             proto_shortcuts: Vec::new(),
+            restriction_conditions: BTreeMap::new(),
+            turn_restrictions: BTreeMap::new(),
+            provenances,
+        }
+    }
+
+    /// Returns the number of nodes, whose repeated insertions have a coordinate differing from
+    /// the first one (see `on-node-conflict` in the parsing-config).
+    pub fn num_of_coord_conflicts(&self) -> usize {
+        GraphBuilder::coord_conflicts(&self.node_coords, self.coord_repeats.clone()).len()
+    }
+
+    /// Returns the nodes, whose repeated insertions have a coordinate differing from the first
+    /// one, with all their inserted coordinates, starting with the first one.
+    fn coord_conflicts(
+        node_coords: &[Option<Coordinate>],
+        mut coord_repeats: Vec<(usize, Coordinate)>,
+    ) -> Vec<(usize, Vec<Coordinate>)> {
+        // stable sort keeps insertion-order per node
+        coord_repeats.sort_by_key(|&(idx, _coord)| idx);
+
+        // collect all insertions of conflicting nodes, starting with the first coordinate
+        let mut conflicts: Vec<(usize, Vec<Coordinate>)> = Vec::new();
+        let mut i = 0;
+        while i < coord_repeats.len() {
+            let idx = coord_repeats[i].0;
+            let start = i;
+            while i < coord_repeats.len() && coord_repeats[i].0 == idx {
+                i += 1;
+            }
+            let repeats = &coord_repeats[start..i];
+
+            let first_coord =
+                node_coords[idx].expect("Repeatedly inserted nodes should have a coordinate.");
+            if repeats
+                .iter()
+                .any(|&(_idx, coord)| Approx(coord) != Approx(first_coord))
+            {
+                let mut coords = vec![first_coord];
+                coords.extend(repeats.iter().map(|&(_idx, coord)| coord));
+                conflicts.push((idx, coords));
+            }
         }
+        conflicts
+    }
+
+    /// Resolves nodes, whose repeated insertions have a coordinate differing from the first one.
+    /// Repeated insertions with equal coordinates count as well, e.g. the last of A, B, A is A.
+    fn resolve_coord_conflicts(
+        policy: parsing::nodes::ConflictPolicy,
+        node_ids: &[i64],
+        node_coords: &mut [Option<Coordinate>],
+        coord_repeats: Vec<(usize, Coordinate)>,
+    ) -> err::Feedback {
+        let conflicts = GraphBuilder::coord_conflicts(node_coords, coord_repeats);
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "DO Resolve {} conflicting node-coordinates with policy {:?}.",
+            conflicts.len(),
+            policy
+        );
+
+        match policy {
+            parsing::nodes::ConflictPolicy::Error => {
+                let (idx, coords) = &conflicts[0];
+                let coord = coords
+                    .iter()
+                    .find(|&&coord| Approx(coord) != Approx(coords[0]))
+                    .expect("Conflicting nodes should have a differing coordinate.");
+                Err(format!(
+                    "{}{}{}{}{}{}{}{}{}",
+                    "Node-id ",
+                    node_ids[*idx],
+                    " has conflicting coordinates ",
+                    coords[0],
+                    " and ",
+                    coord,
+                    " (",
+                    conflicts.len(),
+                    " conflicting node-ids in total). Set `on-node-conflict` to resolve them.",
+                )
+                .into())
+            }
+            // first coordinates are stored already
+            parsing::nodes::ConflictPolicy::KeepFirst => Ok(()),
+            parsing::nodes::ConflictPolicy::KeepLast => {
+                for (idx, coords) in conflicts {
+                    node_coords[idx] = coords.last().copied();
+                }
+                Ok(())
+            }
+            parsing::nodes::ConflictPolicy::Average => {
+                for (idx, coords) in conflicts {
+                    let count = coords.len() as f64;
+                    node_coords[idx] = Some(Coordinate {
+                        lat: coords.iter().map(|coord| coord.lat).sum::<f64>() / count,
+                        lon: coords.iter().map(|coord| coord.lon).sum::<f64>() / count,
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Merges nodes with different ids, but coordinates within the given distance, into the node
+    /// with the smallest id by rewriting the edges' node-ids, and returns the number of merged
+    /// (removed) nodes.
+    ///
+    /// Only dangling ends of streets, hence nodes with at most one neighbour, are merged, because
+    /// stacked nodes inside streets are crossings of different levels, e.g. a bridge over a road.
+    /// Every merged node is within the given distance of the node it is merged into, and nodes,
+    /// which are connected by an edge, are never merged into the same node, because they are
+    /// neighbours on a street, rather than the stacked ends of two streets.
+    fn merge_stacked_nodes(&mut self, max_distance: Meters) -> usize {
+        // One degree of latitude is at least 110.5 km long (at the equator), so latitudes of nodes
+        // within max-distance differ by at most this many degrees.
+        const MIN_KM_PER_LAT_DEGREE: f64 = 110.5;
+        let max_distance_km = *max_distance / 1_000.0;
+        let max_lat_diff = max_distance_km / MIN_KM_PER_LAT_DEGREE;
+
+        // A node is dangling, if all its edges lead to the same neighbour.
+        let node_ids = &self.node_ids;
+        let idx_of = |id: i64| {
+            node_ids
+                .binary_search(&id)
+                .expect("Every edge's node should be a proto-node.")
+        };
+        let mut neighbours: Vec<Option<usize>> = vec![None; self.node_ids.len()];
+        let mut is_dangling = vec![true; self.node_ids.len()];
+        for proto_edge in self.proto_edges.iter() {
+            let idx_a = idx_of(proto_edge.src_id);
+            let idx_b = idx_of(proto_edge.dst_id);
+            if idx_a == idx_b {
+                continue;
+            }
+            for &(idx, neighbour) in &[(idx_a, idx_b), (idx_b, idx_a)] {
+                match neighbours[idx] {
+                    None => neighbours[idx] = Some(neighbour),
+                    Some(other) => {
+                        if other != neighbour {
+                            is_dangling[idx] = false;
+                        }
+                    }
+                }
+            }
+        }
+
+        // sort dangling nodes by latitude and sweep over them to find pairs within max-distance
+        let node_coords = &self.node_coords;
+        let mut sorted_indices: Vec<usize> = (0..self.node_ids.len())
+            .filter(|&idx| is_dangling[idx] && node_coords[idx].is_some())
+            .collect();
+        sorted_indices.sort_by(|&idx_a, &idx_b| {
+            let lat_a = node_coords[idx_a]
+                .expect("Coordinate has been checked.")
+                .lat;
+            let lat_b = node_coords[idx_b]
+                .expect("Coordinate has been checked.")
+                .lat;
+            lat_a.partial_cmp(&lat_b).unwrap_or(Ordering::Equal)
+        });
+        // as (bigger idx, smaller idx)
+        let mut stacked_pairs = Vec::new();
+        for (i, &idx_a) in sorted_indices.iter().enumerate() {
+            let coord_a = node_coords[idx_a].expect("Coordinate has been checked.");
+            for &idx_b in sorted_indices[(i + 1)..].iter() {
+                let coord_b = node_coords[idx_b].expect("Coordinate has been checked.");
+                if coord_b.lat - coord_a.lat > max_lat_diff {
+                    break;
+                }
+                // nodes connected by an edge aren't stacked
+                if neighbours[idx_a] == Some(idx_b) {
+                    continue;
+                }
+                if *haversine_distance_km(&coord_a, &coord_b) <= max_distance_km {
+                    stacked_pairs.push((max(idx_a, idx_b), min(idx_a, idx_b)));
+                }
+            }
+        }
+        if stacked_pairs.is_empty() {
+            return 0;
+        }
+
+        // Every node is merged into the node with the smallest idx (and id) within max-distance,
+        // which hasn't been merged itself.
+        // Hence distances are checked against this representative, and stacked nodes don't chain
+        // beyond max-distance.
+        // A node's neighbour mustn't be merged into the same representative, which would result
+        // in a self-loop.
+        stacked_pairs.sort_unstable();
+        let mut roots: Vec<usize> = (0..self.node_ids.len()).collect();
+        for (idx, root) in stacked_pairs {
+            let is_merged = roots[idx] != idx;
+            let is_root_merged = roots[root] != root;
+            let is_neighbour_merged =
+                neighbours[idx].map_or(false, |neighbour| roots[neighbour] == root);
+            if !is_merged && !is_root_merged && !is_neighbour_merged {
+                roots[idx] = root;
+            }
+        }
+        // sorted by merged id, since node-ids are sorted
+        let mut merged_ids = Vec::new();
+        for (idx, &root) in roots.iter().enumerate() {
+            if root != idx {
+                merged_ids.push((self.node_ids[idx], self.node_ids[root]));
+            }
+        }
+        if merged_ids.is_empty() {
+            return 0;
+        }
+
+        // rewrite edges and turn-restrictions
+
+        let canonical_id = |id: i64| match merged_ids.binary_search_by_key(&id, |&(id, _)| id) {
+            Ok(i) => merged_ids[i].1,
+            Err(_) => id,
+        };
+        for proto_edge in self.proto_edges.iter_mut() {
+            proto_edge.src_id = canonical_id(proto_edge.src_id);
+            proto_edge.dst_id = canonical_id(proto_edge.dst_id);
+        }
+        self.turn_restrictions = mem::replace(&mut self.turn_restrictions, BTreeMap::new())
+            .into_iter()
+            .map(|((from_src_id, via_id, to_dst_id), turn_restriction)| {
+                (
+                    (
+                        canonical_id(from_src_id),
+                        canonical_id(via_id),
+                        canonical_id(to_dst_id),
+                    ),
+                    turn_restriction,
+                )
+            })
+            .collect();
+
+        // remove merged nodes, which don't belong to any edge anymore
+
+        let dim = self.node_custom_dim;
+        let mut new_idx = 0;
+        for idx in 0..self.node_ids.len() {
+            if roots[idx] != idx {
+                continue;
+            }
+            self.node_ids[new_idx] = self.node_ids[idx];
+            self.node_coords[new_idx] = self.node_coords[idx];
+            if let Some(node_ch_levels) = &mut self.node_ch_levels {
+                node_ch_levels[new_idx] = node_ch_levels[idx];
+            }
+            for i in 0..dim {
+                self.node_customs[new_idx * dim + i] = self.node_customs[idx * dim + i];
+            }
+            new_idx += 1;
+        }
+        self.node_ids.truncate(new_idx);
+        self.node_coords.truncate(new_idx);
+        if let Some(node_ch_levels) = &mut self.node_ch_levels {
+            node_ch_levels.truncate(new_idx);
+        }
+        self.node_customs.truncate(new_idx * dim);
+
+        merged_ids.len()
     }
 
     pub fn finalize(mut self) -> err::Result<Graph> {
@@ -357,8 +909,37 @@ impl GraphBuilder {
             self.node_ids.len(),
             self.proto_edges.len()
         );
+        crate::trace_scope!(
+            "building",
+            proto_nodes = self.node_ids.len(),
+            proto_edges = self.proto_edges.len()
+        );
         let mut graph = Graph::new(self.cfg);
 
+        //----------------------------------------------------------------------------------------//
+        // resolve nodes, which have been inserted multiple times with different coordinates
+
+        if !self.coord_repeats.is_empty() {
+            GraphBuilder::resolve_coord_conflicts(
+                graph.cfg.on_node_conflict,
+                &self.node_ids,
+                &mut self.node_coords,
+                mem::replace(&mut self.coord_repeats, Vec::new()),
+            )?;
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // merge stacked nodes, if configured
+
+        if let Some(stacked_nodes) = graph.cfg.merging_stacked_nodes {
+            info!(
+                "DO Merge stacked nodes within {} m.",
+                *stacked_nodes.max_distance
+            );
+            let merged_count = self.merge_stacked_nodes(stacked_nodes.max_distance);
+            info!("Merged {} stacked nodes.", merged_count);
+        }
+
         //----------------------------------------------------------------------------------------//
         // add nodes to graph which belong to edges (sorted by asc id)
 
@@ -377,7 +958,12 @@ impl GraphBuilder {
             }
             graph.node_ids = self.node_ids;
             graph.node_coords = self.node_coords.into_iter().map(Option::unwrap).collect();
+            info!("DO Build node-index for coordinate-based lookups.");
+            graph.node_index = NodeIndex::new(&graph.node_coords);
             graph.node_ch_levels = self.node_ch_levels;
+            graph.node_customs = self.node_customs;
+            graph.node_custom_dim = self.node_custom_dim;
+            graph.turn_restrictions = self.turn_restrictions;
             graph.shrink_to_fit();
         }
 
@@ -539,8 +1125,14 @@ impl GraphBuilder {
                 // -> inc r
                 // -> remember index for updating shortcuts
                 if is_duplicate {
-                    // replace r by w-1
+                    // replace r by w-1, keeping a condition of r
                     removed_indices.push(r);
+                    if let Some(condition) = self.restriction_conditions.remove(&proto_edges[r].idx)
+                    {
+                        self.restriction_conditions
+                            .entry(proto_edges[w - 1].idx)
+                            .or_insert(condition);
+                    }
                 }
                 // if not a duplicate
                 // -> swap edge[w] and edge[r]
@@ -572,6 +1164,18 @@ impl GraphBuilder {
             info!("Removed {} duplicates.", removed_indices.len());
         }
 
+        // Offsets are edge-counts, so every offset and edge-idx fits, if the edge-count fits.
+        let max_edge_count = capacity::max_edge_count(graph.cfg.is_using_compact_indices);
+        if proto_edges.len() > max_edge_count {
+            return Err(err::Msg::from(format!(
+                "The graph has {} edges, but at most {} edges are supported (compact indices: {}).",
+                proto_edges.len(),
+                max_edge_count,
+                graph.cfg.is_using_compact_indices
+            )));
+        }
+        let node_count = graph.node_ids.len();
+
         //----------------------------------------------------------------------------------------//
         // build metrics
         // If metrics are built before indices and offsets are built, the total need of memory while
@@ -579,7 +1183,14 @@ impl GraphBuilder {
 
         info!("START Store metrics.");
         let mut new_sc_edges = Vec::with_capacity(sc_count);
+        // conditions are taken from the proto-edges, which are sorted differently
+        let mut edge_conditions = Vec::with_capacity(self.restriction_conditions.len());
         let mut proto_edges = {
+            crate::trace_scope!(
+                "building::metrics",
+                edges = proto_edges.len(),
+                metrics = graph.cfg.edges.metrics.units.len()
+            );
             let mut new_proto_edges = vec![];
 
             let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
@@ -588,11 +1199,19 @@ impl GraphBuilder {
             // Work off proto-edges in chunks to keep memory-usage lower.
             let max_chunk_size = capacity::MAX_BYTE_PER_CHUNK / ProtoShortcut::mem_size_b();
             debug!("max-chunk-size: {}", max_chunk_size);
-            // init metrics
-            graph.metrics = Vec::new();
+            // init metrics, one column per metric
+            graph.metrics = vec![Vec::new(); graph.cfg.edges.metrics.units.len()];
+            // provenances are taken from the proto-edges, which are sorted differently
+            let proto_provenances = self.provenances.take();
+            if proto_provenances.is_some() {
+                graph.provenances = Some(vec![
+                    ProvenanceColumn::new();
+                    graph.cfg.edges.metrics.units.len()
+                ]);
+            }
             debug!(
                 "initial graph-metric-capacity: {}",
-                graph.metrics.capacity()
+                graph.metrics.iter().map(Vec::capacity).sum::<usize>()
             );
 
             // sort reversed to make splice efficient
@@ -610,19 +1229,36 @@ impl GraphBuilder {
 
                 // allocate new memory-needs
                 proto_edges.shrink_to_fit();
-                graph.metrics.reserve_exact(chunk.len());
+                graph
+                    .metrics
+                    .iter_mut()
+                    .for_each(|column| column.reserve_exact(chunk.len()));
                 new_proto_edges.reserve_exact(chunk.len());
                 debug!("chunk-len: {}", chunk.len());
-                debug!("graph-metric-capacity: {}", graph.metrics.capacity());
+                debug!(
+                    "graph-metric-capacity: {}",
+                    graph.metrics.iter().map(Vec::capacity).sum::<usize>()
+                );
 
                 for mut edge in chunk.into_iter() {
                     // add to graph and remember ids
                     // -> nodes are needed to be finished here to map NodeId -> NodeIdx
                     graph.add_metrics(&mut edge)?;
+                    if let (Some(proto_provenances), Some(provenances)) =
+                        (&proto_provenances, &mut graph.provenances)
+                    {
+                        for (column, proto_column) in
+                            provenances.iter_mut().zip(proto_provenances.iter())
+                        {
+                            column.push(proto_column.get(edge.idx));
+                        }
+                    }
+                    if let Some(condition) = self.restriction_conditions.remove(&edge.idx) {
+                        edge_conditions.push((EdgeIdx(edge_idx), condition));
+                    }
                     new_proto_edges.push(ProtoEdgeC {
                         src_idx: edge.src_idx,
                         dst_idx: edge.dst_idx,
-                        idx: 0, // used later for offset-arrays
                         id: edge.id,
                     });
 
@@ -655,8 +1291,8 @@ impl GraphBuilder {
             new_proto_edges
         };
 
-        for metrics in &graph.metrics {
-            for metric in metrics {
+        for column in &graph.metrics {
+            for metric in column {
                 if metric < &defaults::accuracy::F64_ABS {
                     return Err(err::Msg::from(
                         "A metric is smaller than accuracy allows it.",
@@ -711,6 +1347,11 @@ impl GraphBuilder {
         // logging
         info!("START Create the forward-offset-array and the forward-mapping.");
         {
+            crate::trace_scope!(
+                "building::offsets",
+                nodes = node_count,
+                edges = proto_edges.len()
+            );
             let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
             // start looping
             let mut src_idx = NodeIdx(0);
@@ -720,11 +1361,7 @@ impl GraphBuilder {
             // count offset for each proto_edge (sorted)
             // and apply offset as far as src doesn't change
             let mut edge_idx = 0;
-            for proto_edge in proto_edges.iter_mut() {
-                // Add edge-idx here to remember it for indirect mapping bwd->fwd.
-                // Update it at the end of the loop.
-                proto_edge.idx = edge_idx;
-
+            for proto_edge in proto_edges.into_iter() {
                 // do not swap src and dst since this is a forward-edge
                 let edge_src_idx = proto_edge.src_idx;
                 let edge_dst_idx = proto_edge.dst_idx;
@@ -734,18 +1371,27 @@ impl GraphBuilder {
                 // Nodes of id y with no leaving edge must have the same offset as the node of id (y+1)
                 // to remember it.
                 while src_idx != edge_src_idx.into() {
-                    *src_idx += 1;
+                    src_idx = NodeIdx::try_new(*src_idx + 1, node_count)?;
                     graph.fwd_offsets.push(offset);
                 }
-                offset += 1;
+                offset = match offset.checked_add(1) {
+                    Some(offset) if offset <= max_edge_count => offset,
+                    _ => {
+                        return Err(err::Msg::from(format!(
+                            "The forward-offset after {} edges exceeds the limit of {} edges.",
+                            offset, max_edge_count
+                        )))
+                    }
+                };
+                let fwd_idx = EdgeIdx::try_new(edge_idx, max_edge_count)?;
                 graph.bwd_dsts.push(edge_src_idx);
                 graph.fwd_dsts.push(edge_dst_idx);
                 // mapping fwd to fwd is just the identity
-                graph.fwd_to_fwd_map.push(EdgeIdx(edge_idx));
+                graph.fwd_to_fwd_map.push(fwd_idx);
                 // edge-ids
                 graph.edge_ids.push(proto_edge.id);
                 if let Some(id) = proto_edge.id {
-                    graph.edge_ids_to_idx_map.push((id, EdgeIdx(edge_idx)));
+                    graph.edge_ids_to_idx_map.push((id, fwd_idx));
                 }
 
                 // print progress
@@ -808,79 +1454,26 @@ impl GraphBuilder {
         }
 
         //----------------------------------------------------------------------------------------//
-        // sort backward-edges by ascending dst-id, then by ascending src-id -> offset-array
+        // build backward-offset-array and backward-mapping
 
-        info!("DO Sort proto-backward-edges by their dst/src-IDs.");
-        {
-            if !IS_USING_CH_LEVEL_SPEEDUP {
-                proto_edges.sort_by_key(|edge| (edge.dst_idx, edge.src_idx));
-            } else {
-                let nodes = graph.nodes();
-                proto_edges.sort_by_key(|edge| {
-                    (
-                        edge.dst_idx,
-                        Reverse(nodes.level(edge.src_idx)),
-                        edge.src_idx,
-                        edge.id,
-                    )
-                });
-            }
-        }
+        graph.build_bwd_edges()?;
 
         //----------------------------------------------------------------------------------------//
-        // build backward-offset-array
+        // check if leaving edges are sorted by level
 
-        info!("START Create the backward-offset-array.");
+        info!("DO Check if leaving edges are sorted by their dst's level.");
         {
-            let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
-            // start looping
-            let mut src_idx = NodeIdx(0);
-            let mut offset = 0;
-            graph.bwd_offsets.push(offset);
-            // high-level-idea
-            // count offset for each proto_edge (sorted) and apply offset as far as src doesn't change
-            for edge_idx in 0..proto_edges.len() {
-                let proto_edge = &mut proto_edges[edge_idx];
-
-                // swap src and dst since this is the backward-edge
-                let edge_src_idx = proto_edge.dst_idx;
-
-                // If coming edges have new src, then update offset of new src.
-                // Loop because of nodes with no leaving edges.
-                // Nodes of id y with no leaving edge must have the same offset as the node of id (y+1)
-                // to remember it.
-                while src_idx != edge_src_idx {
-                    *src_idx += 1;
-                    graph.bwd_offsets.push(offset);
-                }
-                offset += 1;
-                // For the backward-mapping, bwd-indices have been remembered above,
-                // but applied to forward-sorted-edges.
-                // Now, that's used to generate the mapping from backward to forward,
-                // which is needed for the offset-arrays.
-                graph.bwd_to_fwd_map.push(EdgeIdx(proto_edge.idx));
-
-                // print progress
-                progress_bar.set(edge_idx);
-                if progress_bar.has_progressed_significantly() {
-                    progress_bar.remember_significant_progress();
-                    info!("{}", progress_bar);
-                }
-            }
-            // last node needs an upper bound as well for `leaving_edges(...)`
-            debug_assert_eq!(
-                offset,
-                proto_edges.len(),
-                "Last offset-value should be as big as the number of proto-edges."
-            );
-            graph.bwd_offsets.push(offset);
-            progress_bar.set(graph.fwd_dsts.len());
-            if progress_bar.has_progressed_significantly() {
-                progress_bar.remember_significant_progress();
-                info!("{}", progress_bar);
+            graph.are_leaving_edges_level_sorted =
+                IS_USING_CH_LEVEL_SPEEDUP && graph.check_level_sorting();
+            if IS_USING_CH_LEVEL_SPEEDUP
+                && graph.nodes().has_levels()
+                && !graph.are_leaving_edges_level_sorted
+            {
+                warn!(
+                    "Leaving edges are not sorted by their dst's level, \
+                     so ch-dijkstra can't stop early at lower levels."
+                );
             }
-            // reduce and optimize memory-usage
-            graph.shrink_to_fit();
         }
 
         //----------------------------------------------------------------------------------------//
@@ -953,6 +1546,8 @@ impl GraphBuilder {
                     | generating::edges::Category::Calc {
                         a: _,
                         b: _,
+                        is_directional: _,
+                        gradient: _,
                         result:
                             generating::edges::metrics::Category {
                                 unit: _,
@@ -1002,6 +1597,16 @@ impl GraphBuilder {
 
             // add new data
 
+            // Speeds are capped per vehicle, but parsed metrics stay untouched.
+            let vehicles_cfg = graph.cfg.vehicles.clone();
+            let cap_speed = |unit: &generating::edges::metrics::UnitInfo, raw_value: f64| {
+                if unit == &generating::edges::metrics::UnitInfo::KilometersPerHour {
+                    vehicles_cfg.cap_speed(raw_value)
+                } else {
+                    raw_value
+                }
+            };
+
             for category in generating_cfg.edges.categories.iter() {
                 match category {
                     generating::edges::Category::Meta { info, id: new_id } => {
@@ -1117,6 +1722,21 @@ impl GraphBuilder {
                                         id: new_id.clone(),
                                     });
                             }
+                            generating::edges::MetaInfo::StableId => {
+                                // update graph
+                                //
+                                // -> done after generating
+
+                                // update config
+                                graph
+                                    .cfg
+                                    .edges
+                                    .categories
+                                    .push(parsing::edges::Category::Meta {
+                                        info: parsing::edges::MetaInfo::StableId,
+                                        id: new_id.clone(),
+                                    });
+                            }
                             generating::edges::MetaInfo::ShortcutIdx0
                             | generating::edges::MetaInfo::ShortcutIdx1 => {
                                 return Err(err::Msg::from(format!(
@@ -1129,10 +1749,9 @@ impl GraphBuilder {
                     }
                     generating::edges::Category::Custom { unit, id, default } => {
                         // update graph
-                        graph
-                            .metrics
-                            .iter_mut()
-                            .for_each(|metric| metric.push(*default));
+                        let edge_count = graph.fwd_edges().count();
+                        graph.metrics.push(vec![*default; edge_count]);
+                        graph.push_provenances(Provenance::Defaulted);
 
                         // update config
 
@@ -1158,6 +1777,7 @@ impl GraphBuilder {
                             generating::edges::metrics::UnitInfo::Hours => false,
                             generating::edges::metrics::UnitInfo::KilometersPerHour => false,
                             generating::edges::metrics::UnitInfo::LaneCount => false,
+                            generating::edges::metrics::UnitInfo::Roughness => false,
                             generating::edges::metrics::UnitInfo::F64 => false,
                         } {
                             return Err(format!(
@@ -1170,7 +1790,8 @@ impl GraphBuilder {
 
                         // calculate haversine-distance and update graph and config
 
-                        for edge_idx in (0..graph.metrics.len()).map(EdgeIdx) {
+                        let mut column = Vec::with_capacity(graph.fwd_edges().count());
+                        for edge_idx in (0..graph.fwd_edges().count()).map(EdgeIdx) {
                             // get positions
 
                             let (src_coord, dst_coord) = {
@@ -1188,11 +1809,14 @@ impl GraphBuilder {
                                     .try_convert(unit, *km)?
                             };
 
-                            // update graph
-
-                            graph.metrics[*edge_idx].push(distance);
+                            column.push(distance);
                         }
 
+                        // update graph
+
+                        graph.metrics.push(column);
+                        graph.push_provenances(Provenance::Calculated);
+
                         // update config
 
                         graph
@@ -1210,19 +1834,23 @@ impl GraphBuilder {
                         // loop over all edges
                         // and add to their metrics
 
-                        let metric_idx = graph.cfg.edges.metrics.idx_of(&from.id);
-                        for edge_idx in 0..graph.metrics.len() {
-                            // get old value
-                            // and generate new value
-
-                            let new_raw_value = {
-                                let old_raw_value = graph.metrics[edge_idx][*metric_idx];
-                                from.unit.try_convert(&to.unit, old_raw_value)?
-                            };
+                        let metric_idx = graph.cfg.edges.metrics.try_idx_of(&from.id)?;
+                        let column = graph.metrics[*metric_idx]
+                            .iter()
+                            .map(|&old_raw_value| {
+                                from.unit
+                                    .try_convert(&to.unit, old_raw_value)
+                                    .map(|raw_value| cap_speed(&to.unit, raw_value))
+                            })
+                            .collect::<err::Result<Vec<_>>>()?;
 
-                            // update graph
+                        // update graph
+                        // -> copied values keep their provenance
 
-                            graph.metrics[edge_idx].push(new_raw_value);
+                        graph.metrics.push(column);
+                        if let Some(provenances) = &mut graph.provenances {
+                            let copied = provenances[*metric_idx].clone();
+                            provenances.push(copied);
                         }
 
                         // update config
@@ -1242,19 +1870,12 @@ impl GraphBuilder {
                         // loop over all edges
                         // and replace their existing metrics
 
-                        let metric_idx = graph.cfg.edges.metrics.idx_of(&from.id);
-                        for edge_idx in 0..graph.metrics.len() {
-                            // get old value
-                            // and generate new value
-
-                            let new_raw_value = {
-                                let old_raw_value = graph.metrics[edge_idx][*metric_idx];
-                                from.unit.try_convert(&to.unit, old_raw_value)?
-                            };
-
+                        let metric_idx = graph.cfg.edges.metrics.try_idx_of(&from.id)?;
+                        for raw_value in graph.metrics[*metric_idx].iter_mut() {
                             // update graph
 
-                            graph.metrics[edge_idx][*metric_idx] = new_raw_value;
+                            *raw_value =
+                                cap_speed(&to.unit, from.unit.try_convert(&to.unit, *raw_value)?);
                         }
 
                         // update config
@@ -1280,29 +1901,150 @@ impl GraphBuilder {
                         graph.cfg.edges.metrics.units[*metric_idx] = to.unit.into();
                         graph.cfg.edges.metrics.ids[*metric_idx] = to.id.clone();
                     }
-                    generating::edges::Category::Calc { result, a, b } => {
+                    generating::edges::Category::Calc {
+                        result,
+                        a,
+                        b,
+                        is_directional,
+                        gradient,
+                    } => {
                         // loop over all edges
                         // and replace their existing metrics
 
-                        let metric_idx_a = graph.cfg.edges.metrics.idx_of(&a.id);
-                        let metric_idx_b = graph.cfg.edges.metrics.idx_of(&b.id);
-                        for edge_idx in 0..graph.metrics.len() {
-                            // get old value
-                            // and generate new value
-
-                            let new_raw_value = {
-                                let old_raw_a = graph.metrics[edge_idx][*metric_idx_a];
-                                let old_raw_b = graph.metrics[edge_idx][*metric_idx_b];
+                        let metric_idx_a = graph.cfg.edges.metrics.try_idx_of(&a.id)?;
+                        let metric_idx_b = graph.cfg.edges.metrics.try_idx_of(&b.id)?;
+                        let mut column = graph.metrics[*metric_idx_a]
+                            .iter()
+                            .zip(graph.metrics[*metric_idx_b].iter())
+                            .map(|(&old_raw_a, &old_raw_b)| {
                                 result
                                     .unit
-                                    .try_calc(&a.unit, old_raw_a, &b.unit, old_raw_b)?
-                            };
+                                    .try_calc(
+                                        &a.unit,
+                                        cap_speed(&a.unit, old_raw_a),
+                                        &b.unit,
+                                        cap_speed(&b.unit, old_raw_b),
+                                    )
+                                    .map(|raw_value| cap_speed(&result.unit, raw_value))
+                            })
+                            .collect::<err::Result<Vec<_>>>()?;
+
+                        // Both directions of a street are different edges,
+                        // hence a directional value can be scaled by the edge's gradient.
+
+                        if *is_directional {
+                            let gradient = gradient.as_ref().ok_or_else(|| {
+                                err::Msg::from(format!(
+                                    "The calculated metric {} is directional, \
+                                     but has no gradient with the nodes' elevation.",
+                                    result.id
+                                ))
+                            })?;
+
+                            // scaling the speed is only supported for durations of distances
+
+                            if !match result.unit {
+                                generating::edges::metrics::UnitInfo::Seconds
+                                | generating::edges::metrics::UnitInfo::Minutes
+                                | generating::edges::metrics::UnitInfo::Hours => true,
+                                generating::edges::metrics::UnitInfo::Meters
+                                | generating::edges::metrics::UnitInfo::Kilometers
+                                | generating::edges::metrics::UnitInfo::KilometersPerHour
+                                | generating::edges::metrics::UnitInfo::LaneCount
+                                | generating::edges::metrics::UnitInfo::Roughness
+                                | generating::edges::metrics::UnitInfo::F64 => false,
+                            } || a.unit != generating::edges::metrics::UnitInfo::Kilometers
+                            {
+                                return Err(format!(
+                                    "The directional metric {} has to be a duration, \
+                                     calculated from {:?}, but is {:?} from {:?}.",
+                                    result.id,
+                                    generating::edges::metrics::UnitInfo::Kilometers,
+                                    result.unit,
+                                    a.unit
+                                )
+                                .into());
+                            }
 
-                            // update graph
+                            let elevation_idx = graph
+                                .cfg
+                                .nodes
+                                .try_custom_idx_of(&gradient.elevation_id.0)?;
+                            let nodes = graph.nodes();
+                            let fwd_edges = graph.fwd_edges();
+                            let bwd_edges = graph.bwd_edges();
+                            for (edge_idx, value) in column
+                                .iter_mut()
+                                .enumerate()
+                                .map(|(edge_idx, value)| (EdgeIdx(edge_idx), value))
+                            {
+                                let meters = graph.metrics[*metric_idx_a][*edge_idx] * 1_000.0;
+                                if meters <= 0.0 {
+                                    continue;
+                                }
+                                let src_elevation =
+                                    nodes.custom(bwd_edges.dst_idx(edge_idx), elevation_idx);
+                                let dst_elevation =
+                                    nodes.custom(fwd_edges.dst_idx(edge_idx), elevation_idx);
+                                let grade = (dst_elevation - src_elevation) / meters;
+
+                                // duration is inverse to speed
+                                *value /= gradient.speed_factor(grade);
+                            }
+                        } else if gradient.is_some() {
+                            warn!(
+                                "The calculated metric {} has a gradient, \
+                                 which is ignored, because the metric is not directional.",
+                                result.id
+                            );
+                        }
 
-                            graph.metrics[edge_idx].push(new_raw_value);
+                        // Rough surfaces slow down, hence durations are scaled by the roughness.
+
+                        let is_duration = match result.unit {
+                            generating::edges::metrics::UnitInfo::Seconds
+                            | generating::edges::metrics::UnitInfo::Minutes
+                            | generating::edges::metrics::UnitInfo::Hours => true,
+                            generating::edges::metrics::UnitInfo::Meters
+                            | generating::edges::metrics::UnitInfo::Kilometers
+                            | generating::edges::metrics::UnitInfo::KilometersPerHour
+                            | generating::edges::metrics::UnitInfo::LaneCount
+                            | generating::edges::metrics::UnitInfo::Roughness
+                            | generating::edges::metrics::UnitInfo::F64 => false,
+                        };
+                        if is_duration && graph.cfg.edges.roughness.is_applied_to_duration {
+                            let roughness_idx = graph
+                                .cfg
+                                .edges
+                                .metrics
+                                .units
+                                .iter()
+                                .position(|unit| {
+                                    unit == &parsing::edges::metrics::UnitInfo::Roughness
+                                })
+                                .map(MetricIdx)
+                                .ok_or_else(|| {
+                                    err::Msg::from(format!(
+                                        "The duration {} should be scaled by the roughness, \
+                                         but no metric has unit {:?}.",
+                                        result.id,
+                                        parsing::edges::metrics::UnitInfo::Roughness
+                                    ))
+                                })?;
+
+                            // duration is inverse to speed, which is divided by the roughness
+                            for (value, roughness) in
+                                column.iter_mut().zip(graph.metrics[*roughness_idx].iter())
+                            {
+                                *value *= roughness;
+                            }
                         }
 
+                        // update graph
+
+                        graph.metrics.push(column);
+                        graph.push_provenances(Provenance::Calculated);
+
                         // update config
 
                         graph
@@ -1370,11 +2112,15 @@ impl GraphBuilder {
                                             continue;
                                         }
 
-                                        let metric_idx = graph.cfg.edges.metrics.idx_of(id);
+                                        let metric_idx = graph.cfg.edges.metrics.try_idx_of(id)?;
 
                                         let param = params[col_idx];
                                         if let Ok(raw_value) = param.parse::<f64>() {
-                                            graph.metrics[*edge_idx][*metric_idx] = raw_value;
+                                            graph.metrics[*metric_idx][*edge_idx] = raw_value;
+                                            if let Some(provenances) = &mut graph.provenances {
+                                                provenances[*metric_idx]
+                                                    .set(*edge_idx, Provenance::Measured);
+                                            }
                                         } else {
                                             return Err(err::Msg::from(format!(
                                                 "Parsing '{}' didn't work.",
@@ -1394,6 +2140,17 @@ impl GraphBuilder {
             }
         }
 
+        if let Some(provenances) = &graph.provenances {
+            info!("Shares of defaulted metric-values:");
+            for (metric_id, column) in graph.cfg.edges.metrics.ids.iter().zip(provenances) {
+                info!(
+                    "    {}: {:.2} %",
+                    metric_id,
+                    100.0 * column.share(Provenance::Defaulted)
+                );
+            }
+        }
+
         if graph.cfg().edges.metrics.are_normalized {
             info!("DO Normalize metrics:");
 
@@ -1414,11 +2171,7 @@ impl GraphBuilder {
             let means: DimVec<_> = graph
                 .metrics
                 .iter()
-                .fold(smallvec![0.0; graph.metrics().dim()], |acc, b| {
-                    helpers::add(&acc, b)
-                })
-                .iter_mut()
-                .map(|sum| *sum / n)
+                .map(|column| column.iter().sum::<f64>() / n)
                 .collect();
 
             // print mean
@@ -1437,22 +2190,35 @@ impl GraphBuilder {
 
             // normalize
 
-            for edge_metrics in graph.metrics.iter_mut() {
-                edge_metrics
-                    .iter_mut()
-                    .enumerate()
-                    .for_each(|(metric_idx, metric)| {
-                        *metric /= means[metric_idx];
-                        if Approx(*metric) == Approx(0.0) {
-                            *metric = defaults::accuracy::F64_ABS
-                        }
-                    });
+            for (column, mean) in graph.metrics.iter_mut().zip(means.iter()) {
+                column.iter_mut().for_each(|metric| {
+                    *metric /= mean;
+                    if Approx(*metric) == Approx(0.0) {
+                        *metric = defaults::accuracy::F64_ABS
+                    }
+                });
             }
 
             // and remember means
 
             graph.means = Some(means);
         }
+        graph.build_metric_rows();
+
+        // conditions are keyed by stable ids, which distinguish parallel edges
+        if edge_conditions.len() > 0 {
+            let stable_ids = graph.stable_ids();
+            graph.restriction_conditions = edge_conditions
+                .into_iter()
+                .map(|(edge_idx, condition)| (stable_ids[*edge_idx], condition))
+                .collect();
+        }
+
+        debug!(
+            "Offset-arrays and edge-index-maps need {} bytes (compact: {}).",
+            graph.indices_mem_size_b(),
+            graph.cfg.is_using_compact_indices
+        );
 
         info!("FINISHED Finalizing graph has finished.");
         Ok(graph)
@@ -0,0 +1,236 @@
+use super::EdgeIdx;
+use crate::helpers::err;
+use std::{convert::TryFrom, mem, ops::Range};
+
+/// Offsets of an offset-graph, stored as `usize` or, if configured when parsing (see
+/// `configs::parsing::Config::is_using_compact_indices`), as `u32`.
+///
+/// Offsets are edge-counts, hence compact offsets allow at most `u32::MAX` edges.
+#[derive(Clone, Debug)]
+pub enum OffsetVec {
+    Wide(Vec<usize>),
+    Compact(Vec<u32>),
+}
+
+impl OffsetVec {
+    pub fn new(is_compact: bool) -> OffsetVec {
+        OffsetVec::with_capacity(is_compact, 0)
+    }
+
+    pub fn with_capacity(is_compact: bool, capacity: usize) -> OffsetVec {
+        if is_compact {
+            OffsetVec::Compact(Vec::with_capacity(capacity))
+        } else {
+            OffsetVec::Wide(Vec::with_capacity(capacity))
+        }
+    }
+
+    /// Returns an error, if compact offsets are asked for, but an offset doesn't fit into `u32`.
+    pub fn try_from_vec(offsets: Vec<usize>, is_compact: bool) -> err::Result<OffsetVec> {
+        if !is_compact {
+            return Ok(OffsetVec::Wide(offsets));
+        }
+
+        let mut compact_offsets = Vec::with_capacity(offsets.len());
+        for offset in offsets {
+            compact_offsets.push(OffsetVec::try_compact(offset)?);
+        }
+        Ok(OffsetVec::Compact(compact_offsets))
+    }
+
+    fn try_compact(offset: usize) -> err::Result<u32> {
+        u32::try_from(offset).map_err(|_| {
+            err::Msg::from(format!(
+                "Offset {} doesn't fit into compact indices (max {}).",
+                offset,
+                u32::MAX
+            ))
+        })
+    }
+
+    pub fn is_compact(&self) -> bool {
+        match self {
+            OffsetVec::Wide(_) => false,
+            OffsetVec::Compact(_) => true,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            OffsetVec::Wide(offsets) => offsets.len(),
+            OffsetVec::Compact(offsets) => offsets.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Panics if the idx is out of bounds.
+    pub fn get(&self, idx: usize) -> usize {
+        match self {
+            OffsetVec::Wide(offsets) => offsets[idx],
+            OffsetVec::Compact(offsets) => offsets[idx] as usize,
+        }
+    }
+
+    /// Panics if compact and the offset doesn't fit into `u32`, which is checked for the whole
+    /// graph before building its offsets.
+    pub fn push(&mut self, offset: usize) {
+        match self {
+            OffsetVec::Wide(offsets) => offsets.push(offset),
+            OffsetVec::Compact(offsets) => match OffsetVec::try_compact(offset) {
+                Ok(offset) => offsets.push(offset),
+                Err(msg) => panic!("{}", msg),
+            },
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            OffsetVec::Wide(offsets) => offsets.shrink_to_fit(),
+            OffsetVec::Compact(offsets) => offsets.shrink_to_fit(),
+        }
+    }
+
+    /// Memory of the stored offsets in bytes (without unused capacity).
+    pub fn mem_size_b(&self) -> usize {
+        match self {
+            OffsetVec::Wide(offsets) => offsets.len() * mem::size_of::<usize>(),
+            OffsetVec::Compact(offsets) => offsets.len() * mem::size_of::<u32>(),
+        }
+    }
+}
+
+/// Edge-indices, e.g. mapping xwd-edges to fwd-edges, stored as `EdgeIdx` or (if configured) as
+/// `u32`, analogous to `OffsetVec`.
+#[derive(Clone, Debug)]
+pub enum EdgeIdxVec {
+    Wide(Vec<EdgeIdx>),
+    Compact(Vec<u32>),
+}
+
+impl EdgeIdxVec {
+    pub fn new(is_compact: bool) -> EdgeIdxVec {
+        if is_compact {
+            EdgeIdxVec::Compact(Vec::new())
+        } else {
+            EdgeIdxVec::Wide(Vec::new())
+        }
+    }
+
+    /// Returns an error, if compact indices are asked for, but an index doesn't fit into `u32`.
+    pub fn try_from_vec(indices: Vec<EdgeIdx>, is_compact: bool) -> err::Result<EdgeIdxVec> {
+        if !is_compact {
+            return Ok(EdgeIdxVec::Wide(indices));
+        }
+
+        let mut compact_indices = Vec::with_capacity(indices.len());
+        for idx in indices {
+            compact_indices.push(EdgeIdxVec::try_compact(idx)?);
+        }
+        Ok(EdgeIdxVec::Compact(compact_indices))
+    }
+
+    fn try_compact(idx: EdgeIdx) -> err::Result<u32> {
+        u32::try_from(*idx).map_err(|_| {
+            err::Msg::from(format!(
+                "Edge-idx {} doesn't fit into compact indices (max {}).",
+                idx,
+                u32::MAX
+            ))
+        })
+    }
+
+    pub fn is_compact(&self) -> bool {
+        match self {
+            EdgeIdxVec::Wide(_) => false,
+            EdgeIdxVec::Compact(_) => true,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            EdgeIdxVec::Wide(indices) => indices.len(),
+            EdgeIdxVec::Compact(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Panics if the idx is out of bounds.
+    pub fn get(&self, idx: usize) -> EdgeIdx {
+        match self {
+            EdgeIdxVec::Wide(indices) => indices[idx],
+            EdgeIdxVec::Compact(indices) => EdgeIdx(indices[idx] as usize),
+        }
+    }
+
+    /// Panics if the range is out of bounds.
+    pub fn slice<'a>(&'a self, range: Range<usize>) -> EdgeIdxSlice<'a> {
+        match self {
+            EdgeIdxVec::Wide(indices) => EdgeIdxSlice::Wide(&indices[range]),
+            EdgeIdxVec::Compact(indices) => EdgeIdxSlice::Compact(&indices[range]),
+        }
+    }
+
+    /// Panics if compact and the idx doesn't fit into `u32`, which is checked for the whole graph
+    /// before building its indices.
+    pub fn push(&mut self, idx: EdgeIdx) {
+        match self {
+            EdgeIdxVec::Wide(indices) => indices.push(idx),
+            EdgeIdxVec::Compact(indices) => match EdgeIdxVec::try_compact(idx) {
+                Ok(idx) => indices.push(idx),
+                Err(msg) => panic!("{}", msg),
+            },
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            EdgeIdxVec::Wide(indices) => indices.shrink_to_fit(),
+            EdgeIdxVec::Compact(indices) => indices.shrink_to_fit(),
+        }
+    }
+
+    /// Memory of the stored indices in bytes (without unused capacity).
+    pub fn mem_size_b(&self) -> usize {
+        match self {
+            EdgeIdxVec::Wide(indices) => indices.len() * mem::size_of::<EdgeIdx>(),
+            EdgeIdxVec::Compact(indices) => indices.len() * mem::size_of::<u32>(),
+        }
+    }
+}
+
+/// A borrowed part of an `EdgeIdxVec`, e.g. the leaving edges of one node.
+#[derive(Copy, Clone, Debug)]
+pub enum EdgeIdxSlice<'a> {
+    Wide(&'a [EdgeIdx]),
+    Compact(&'a [u32]),
+}
+
+impl<'a> EdgeIdxSlice<'a> {
+    pub fn len(&self) -> usize {
+        match self {
+            EdgeIdxSlice::Wide(indices) => indices.len(),
+            EdgeIdxSlice::Compact(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn split_first(&self) -> Option<(EdgeIdx, EdgeIdxSlice<'a>)> {
+        match self {
+            EdgeIdxSlice::Wide(indices) => indices
+                .split_first()
+                .map(|(idx, remaining)| (*idx, EdgeIdxSlice::Wide(remaining))),
+            EdgeIdxSlice::Compact(indices) => indices
+                .split_first()
+                .map(|(idx, remaining)| (EdgeIdx(*idx as usize), EdgeIdxSlice::Compact(remaining))),
+        }
+    }
+}
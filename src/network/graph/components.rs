@@ -0,0 +1,194 @@
+use super::{Graph, NodeIdx};
+use log::info;
+use std::{
+    fmt::{self, Display},
+    ops::Deref,
+    sync::Arc,
+};
+
+/// Label of a strongly connected component (see `Graph::components()`).
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ComponentId(pub usize);
+
+impl Display for ComponentId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Deref for ComponentId {
+    type Target = usize;
+
+    fn deref(&self) -> &usize {
+        &self.0
+    }
+}
+
+/// Strongly connected components of a graph, meaning two nodes share a component, if each of
+/// them is reachable from the other one.
+#[derive(Debug)]
+pub struct Components {
+    /// indexed by node-indices
+    labels: Vec<ComponentId>,
+    /// indexed by component-ids
+    sizes: Vec<usize>,
+}
+
+impl Components {
+    pub fn component_of(&self, node_idx: NodeIdx) -> ComponentId {
+        self.labels[*node_idx]
+    }
+
+    pub fn same_component(&self, node_a: NodeIdx, node_b: NodeIdx) -> bool {
+        self.component_of(node_a) == self.component_of(node_b)
+    }
+
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn size_of(&self, component: ComponentId) -> usize {
+        self.sizes[*component]
+    }
+
+    /// The nodes' count of every component, indexed by component-ids
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// The component with the most nodes, or None if the graph has no nodes.
+    pub fn largest(&self) -> Option<ComponentId> {
+        (0..self.sizes.len())
+            .max_by_key(|&id| self.sizes[id])
+            .map(ComponentId)
+    }
+}
+
+impl Graph {
+    /// Returns the graph's strongly connected components, which are computed once on demand.
+    ///
+    /// Since a graph's edges don't change after building, the cached components stay valid.
+    /// New graphs (e.g. subgraphs) compute their own components.
+    /// For many queries, keep the returned components instead of calling `component_of(...)`
+    /// repeatedly, which locks the cache every time.
+    pub fn components(&self) -> Arc<Components> {
+        let mut cache = self
+            .components
+            .lock()
+            .expect("The components' cache shouldn't be poisoned.");
+        if let Some(components) = cache.as_ref() {
+            return Arc::clone(components);
+        }
+
+        let components = Arc::new(self.compute_components());
+        *cache = Some(Arc::clone(&components));
+        components
+    }
+
+    pub fn component_of(&self, node_idx: NodeIdx) -> ComponentId {
+        self.components().component_of(node_idx)
+    }
+
+    pub fn same_component(&self, node_a: NodeIdx, node_b: NodeIdx) -> bool {
+        self.components().same_component(node_a, node_b)
+    }
+
+    /// Tarjan's algorithm on forward-edges, but iterative, because recursion would overflow on
+    /// long chains of nodes.
+    /// Shortcuts are skipped, since they don't change reachability.
+    fn compute_components(&self) -> Components {
+        info!("START Compute strongly connected components.");
+
+        let node_count = self.node_ids.len();
+        let fwd_edges = self.fwd_edges();
+
+        // Tarjan's dfs-indices and lowlinks, with None for unvisited nodes
+        let mut indices: Vec<Option<usize>> = vec![None; node_count];
+        let mut lowlinks = vec![0; node_count];
+        let mut is_on_stack = vec![false; node_count];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+
+        let mut labels = vec![ComponentId(0); node_count];
+        let mut sizes = Vec::new();
+
+        // (node, next offset of its leaving edges)
+        let mut call_stack: Vec<(NodeIdx, usize)> = Vec::new();
+
+        for root_idx in (0..node_count).map(NodeIdx) {
+            if indices[*root_idx].is_some() {
+                continue;
+            }
+
+            indices[*root_idx] = Some(next_index);
+            lowlinks[*root_idx] = next_index;
+            next_index += 1;
+            stack.push(root_idx);
+            is_on_stack[*root_idx] = true;
+            call_stack.push((root_idx, self.fwd_offsets.get(*root_idx)));
+
+            while let Some((node_idx, offset)) = call_stack.last_mut() {
+                let node_idx = *node_idx;
+
+                // visit next leaving edge
+                if *offset < self.fwd_offsets.get(*node_idx + 1) {
+                    let edge_idx = self.fwd_to_fwd_map.get(*offset);
+                    *offset += 1;
+                    if fwd_edges.is_shortcut(edge_idx) {
+                        continue;
+                    }
+
+                    let dst_idx = self.fwd_dsts[*edge_idx];
+                    match indices[*dst_idx] {
+                        None => {
+                            indices[*dst_idx] = Some(next_index);
+                            lowlinks[*dst_idx] = next_index;
+                            next_index += 1;
+                            stack.push(dst_idx);
+                            is_on_stack[*dst_idx] = true;
+                            call_stack.push((dst_idx, self.fwd_offsets.get(*dst_idx)));
+                        }
+                        Some(dst_index) => {
+                            if is_on_stack[*dst_idx] {
+                                lowlinks[*node_idx] = lowlinks[*node_idx].min(dst_index);
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                // all leaving edges are visited
+                call_stack.pop();
+                if let Some(&(parent_idx, _)) = call_stack.last() {
+                    lowlinks[*parent_idx] = lowlinks[*parent_idx].min(lowlinks[*node_idx]);
+                }
+
+                // node is root of a component
+                if Some(lowlinks[*node_idx]) == indices[*node_idx] {
+                    let component = ComponentId(sizes.len());
+                    let mut size = 0;
+                    while let Some(member_idx) = stack.pop() {
+                        is_on_stack[*member_idx] = false;
+                        labels[*member_idx] = component;
+                        size += 1;
+                        if member_idx == node_idx {
+                            break;
+                        }
+                    }
+                    sizes.push(size);
+                }
+            }
+        }
+
+        let components = Components { labels, sizes };
+        info!(
+            "FINISHED Found {} components, the largest one with {} nodes.",
+            components.count(),
+            components
+                .largest()
+                .map(|id| components.size_of(id))
+                .unwrap_or(0)
+        );
+        components
+    }
+}
@@ -0,0 +1,65 @@
+use super::NodeIdx;
+
+/// Lookup of node-indices by node-ids, which are sorted ascendingly in the graph.
+///
+/// It borrows the graph's ids, so it can be held instead of the `NodeAccessor`, e.g. when translating many ids.
+#[derive(Clone, Copy, Debug)]
+pub struct IdIndex<'a> {
+    ids: &'a [i64],
+}
+
+impl<'a> IdIndex<'a> {
+    pub(super) fn new(ids: &'a [i64]) -> IdIndex<'a> {
+        IdIndex { ids }
+    }
+
+    /// The sorted node-ids, indexed by node-indices
+    pub fn ids(&self) -> &'a [i64] {
+        self.ids
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn contains(&self, id: i64) -> bool {
+        self.idx_of(id).is_ok()
+    }
+
+    /// Returns the missing id as error.
+    pub fn idx_of(&self, id: i64) -> Result<NodeIdx, i64> {
+        match self.ids.binary_search(&id) {
+            Ok(idx) => Ok(NodeIdx(idx)),
+            Err(_) => Err(id),
+        }
+    }
+
+    /// Translates all ids at once, keeping their order and returning missing ids as errors.
+    ///
+    /// The ids are sorted together with their positions and merged with the graph's sorted ids in
+    /// a single pass of two pointers, so this takes `O(k log k + n)` for `k` given ids.
+    pub fn idx_of_many(&self, ids: &[i64]) -> Vec<Result<NodeIdx, i64>> {
+        let mut sorted_ids: Vec<(i64, usize)> =
+            ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+        sorted_ids.sort_unstable();
+
+        let mut results = vec![Err(0); ids.len()];
+        let mut idx = 0;
+        for (id, i) in sorted_ids {
+            // duplicates in the given ids keep the pointer and get the same idx again
+            while idx < self.ids.len() && self.ids[idx] < id {
+                idx += 1;
+            }
+            results[i] = if idx < self.ids.len() && self.ids[idx] == id {
+                Ok(NodeIdx(idx))
+            } else {
+                Err(id)
+            };
+        }
+        results
+    }
+}
@@ -1,3 +1,4 @@
+use crate::helpers::err;
 use std::{
     cmp::Ord,
     fmt::{self, Display},
@@ -9,6 +10,20 @@ use std::{
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct NodeIdx(pub usize);
 
+impl NodeIdx {
+    /// Errs, if `idx` is not below `count_limit`, which is the number of addressable nodes.
+    pub fn try_new(idx: usize, count_limit: usize) -> err::Result<NodeIdx> {
+        if idx < count_limit {
+            Ok(NodeIdx(idx))
+        } else {
+            Err(err::Msg::from(format!(
+                "Node-idx {} can't be addressed, because at most {} nodes are supported.",
+                idx, count_limit
+            )))
+        }
+    }
+}
+
 impl Display for NodeIdx {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.0.fmt(f)
@@ -60,6 +75,21 @@ impl From<Range<usize>> for NodeIdxIterator {
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct EdgeIdx(pub usize);
 
+impl EdgeIdx {
+    /// Errs, if `idx` is not below `count_limit`, which is the number of addressable edges
+    /// (see `defaults::capacity::max_edge_count(...)`).
+    pub fn try_new(idx: usize, count_limit: usize) -> err::Result<EdgeIdx> {
+        if idx < count_limit {
+            Ok(EdgeIdx(idx))
+        } else {
+            Err(err::Msg::from(format!(
+                "Edge-idx {} can't be addressed, because at most {} edges are supported.",
+                idx, count_limit
+            )))
+        }
+    }
+}
+
 impl Display for EdgeIdx {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.0.fmt(f)
@@ -1,14 +1,40 @@
+mod bbox;
+pub use bbox::{BboxEdges, EdgeGrid};
 pub mod building;
+mod compact;
+pub use compact::{EdgeIdxSlice, EdgeIdxVec, OffsetVec};
+mod components;
+pub use components::{ComponentId, Components};
+mod id_index;
+pub use id_index::IdIndex;
 mod indexing;
 pub use indexing::{EdgeIdx, EdgeIdxIterator, MetricIdx, NodeIdx, NodeIdxIterator};
+mod node_index;
+pub use node_index::NodeIndex;
+mod provenance;
+pub use provenance::{Provenance, ProvenanceColumn};
+mod quantiles;
+mod shortcuts;
+mod stable_ids;
+mod subgraph;
+pub use subgraph::IndexMapping;
+mod turn_restrictions;
+pub use turn_restrictions::{TurnRestriction, TurnRestrictionKind};
+mod view;
+pub use view::{GraphView, LeavingEdge, LeavingEdges};
 
 use crate::{configs::parsing::Config, defaults::capacity::DimVec, helpers::err};
-use kissunits::geo::Coordinate;
+use kissunits::{
+    distance::Kilometers,
+    geo::{self, Coordinate},
+};
 use std::{
+    collections::BTreeMap,
     fmt,
     fmt::Display,
     iter::Iterator,
-    ops::{Index, IndexMut},
+    ops::{Deref, DerefMut, Index},
+    sync::{Arc, Mutex},
 };
 
 /// Stores graph-data as offset-graph in arrays and provides methods and shallow structs for accessing them.
@@ -82,24 +108,52 @@ pub struct Graph {
     node_ids: Vec<i64>,
     // node-metrics
     node_coords: Vec<Coordinate>,
-    node_ch_levels: Vec<usize>,
+    // only ch-graphs have levels
+    node_ch_levels: Option<Vec<usize>>,
+    // custom values of node i are stored at i * node_custom_dim
+    node_customs: Vec<f64>,
+    node_custom_dim: usize,
     // node_heights: Vec<f64>,
     // edges: offset-graph and mappings, e.g. for metrics
     fwd_dsts: Vec<NodeIdx>,
-    fwd_offsets: Vec<usize>,
-    fwd_to_fwd_map: Vec<EdgeIdx>,
+    fwd_offsets: OffsetVec,
+    fwd_to_fwd_map: EdgeIdxVec,
     bwd_dsts: Vec<NodeIdx>,
-    bwd_offsets: Vec<usize>,
-    bwd_to_fwd_map: Vec<EdgeIdx>,
-    // edge-metrics (sorted according to fwd_dsts)
-    metrics: Vec<DimVec<f64>>,
+    bwd_offsets: OffsetVec,
+    bwd_to_fwd_map: EdgeIdxVec,
+    // edge-metrics as columns, so metrics[metric_idx][edge_idx]
+    // (edges sorted according to fwd_dsts)
+    metrics: Vec<Vec<f64>>,
+    // the same edge-metrics as rows, so routing reads an edge's metrics without gathering them
+    // (kept in sync by `MetricAccessorMut`)
+    metric_rows: Vec<DimVec<f64>>,
     means: Option<DimVec<f64>>,
+    // origin of every metric-value as columns like the metrics, only if tracked
+    provenances: Option<Vec<ProvenanceColumn>>,
     // mapping from id to EdgeIdx, sorted by id
     edge_ids: Vec<Option<usize>>,
     edge_ids_to_idx_map: Vec<(usize, EdgeIdx)>,
+    // hashes of (src-id, dst-id, length), which are unique and stable across graph-versions,
+    // computed on demand, but before metrics are changed
+    stable_ids: Mutex<Option<Arc<Vec<u64>>>>,
     // shortcuts (contraction-hierarchies)
     sc_offsets: Vec<usize>,
     sc_edges: Vec<[EdgeIdx; 2]>,
+    // checked when finalizing, since routing can only stop early at lower ch-levels if true
+    are_leaving_edges_level_sorted: bool,
+    // raw conditions of conditionally restricted edges by stable id, if configured
+    restriction_conditions: BTreeMap<u64, String>,
+    // turn-restrictions by the node-ids (from-src, via, to-dst) of their turns
+    turn_restrictions: BTreeMap<(i64, i64, i64), TurnRestriction>,
+    // strongly connected components, computed on demand
+    components: Mutex<Option<Arc<Components>>>,
+    // grid of edges for bounding-box-queries, built on demand
+    edge_grid: Mutex<Option<Arc<EdgeGrid>>>,
+    // k-d-tree of nodes for coordinate-based lookups, built when finalizing
+    node_index: NodeIndex,
+    // values of metric-columns by their rank for quantiles, selected on demand and cleared when
+    // metrics are changed
+    metric_ranks: Mutex<BTreeMap<MetricIdx, BTreeMap<usize, f64>>>,
 }
 
 /// public stuff for accessing the (static) graph
@@ -112,7 +166,9 @@ impl Graph {
         NodeAccessor {
             node_ids: &self.node_ids,
             node_coords: &self.node_coords,
-            node_ch_levels: &self.node_ch_levels,
+            node_ch_levels: self.node_ch_levels.as_ref(),
+            node_customs: &self.node_customs,
+            node_custom_dim: self.node_custom_dim,
         }
     }
 
@@ -145,20 +201,82 @@ impl Graph {
     pub fn metrics<'a>(&'a self) -> MetricAccessor<'a> {
         MetricAccessor {
             cfg: &self.cfg,
-            metrics: &self.metrics,
+            columns: &self.metrics,
+            rows: &self.metric_rows,
             means: self.means.as_ref(),
+            provenances: self.provenances.as_ref().map(|columns| &columns[..]),
         }
     }
 
+    /// Returns the memory (in bytes) of the offset-arrays and the xwd-to-fwd-maps, which depends
+    /// on `Config::is_using_compact_indices`.
+    pub fn indices_mem_size_b(&self) -> usize {
+        self.fwd_offsets.mem_size_b()
+            + self.fwd_to_fwd_map.mem_size_b()
+            + self.bwd_offsets.mem_size_b()
+            + self.bwd_to_fwd_map.mem_size_b()
+    }
+
+    /// Returns true, if every node's leaving fwd- and bwd-edges are sorted descendingly by their
+    /// dst's ch-level.
+    /// Only then, a ch-dijkstra can skip all remaining leaving edges at the first lower level.
+    pub fn are_leaving_edges_level_sorted(&self) -> bool {
+        self.are_leaving_edges_level_sorted
+    }
+
+    /// Returns the number of fwd-edges, which are shortcuts of a contraction.
+    pub fn shortcut_count(&self) -> usize {
+        self.sc_edges.len()
+    }
+
+    /// Returns true, if the graph has ch-levels and at least two nodes differ in their level.
+    /// A graph, whose nodes have all the same level (e.g. 0), is not contracted.
+    pub fn has_nonuniform_levels(&self) -> bool {
+        match &self.node_ch_levels {
+            Some(levels) => levels.windows(2).any(|w| w[0] != w[1]),
+            None => false,
+        }
+    }
+
+    /// Returns the raw condition of a conditionally restricted edge, which is only kept, if
+    /// configured when parsing (see `configs::parsing::edges::conditional`).
+    pub fn restriction_condition(&self, edge_idx: EdgeIdx) -> Option<&str> {
+        // stable ids are computed lazily, hence only if needed
+        if self.restriction_conditions.is_empty() {
+            return None;
+        }
+        self.restriction_conditions
+            .get(&self.stable_ids()[*edge_idx])
+            .map(|condition| condition.as_ref())
+    }
+
     pub fn metrics_mut<'a>(&'a mut self) -> MetricAccessorMut<'a> {
+        // colliding stable ids depend on the metrics, which shouldn't change them afterwards
+        self.stable_ids();
+        self.metric_ranks
+            .get_mut()
+            .expect("The metric-ranks' cache shouldn't be poisoned.")
+            .clear();
         MetricAccessorMut {
             cfg: &self.cfg,
-            metrics: &mut self.metrics,
+            columns: &mut self.metrics,
+            rows: &mut self.metric_rows,
             means: self.means.as_mut(),
         }
     }
 }
 
+/// private stuff for building the graph
+impl Graph {
+    /// Mirrors the metric-columns into rows, which has to be done after building the columns.
+    fn build_metric_rows(&mut self) {
+        let edge_count = self.fwd_dsts.len();
+        self.metric_rows = (0..edge_count)
+            .map(|edge_idx| self.metrics.iter().map(|column| column[edge_idx]).collect())
+            .collect();
+    }
+}
+
 impl Display for Graph {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(
@@ -274,7 +392,9 @@ impl Display for Graph {
                     writeln!(
                         f,
                         "{}offset: {{ node-id: {}, offset: {} }}",
-                        xwd_prefix, i, xwd_offsets[i]
+                        xwd_prefix,
+                        i,
+                        xwd_offsets.get(i)
                     )?;
                 } else {
                     break;
@@ -285,7 +405,9 @@ impl Display for Graph {
             writeln!(
                 f,
                 "{}offset: {{ __: {}, offset: {} }}",
-                xwd_prefix, i, xwd_offsets[i]
+                xwd_prefix,
+                i,
+                xwd_offsets.get(i)
             )?;
 
             writeln!(f, "")?;
@@ -335,7 +457,7 @@ pub struct Node {
     idx: NodeIdx,
     id: i64,
     coord: Coordinate,
-    level: usize,
+    level: Option<usize>,
 }
 
 impl Node {
@@ -351,7 +473,8 @@ impl Node {
         self.coord
     }
 
-    pub fn ch_level(&self) -> usize {
+    /// Returns None, if the graph has no ch-levels.
+    pub fn ch_level(&self) -> Option<usize> {
         self.level
     }
 }
@@ -399,8 +522,8 @@ impl<'a> HalfEdge<'a> {
         self.edge_accessor.sc_edges(self.idx)
     }
 
-    pub fn metrics(&self) -> &DimVec<f64> {
-        &self.edge_accessor.metrics[self.idx]
+    pub fn metrics(&self) -> &'a DimVec<f64> {
+        &self.edge_accessor.metrics.rows[*self.idx]
     }
 }
 
@@ -417,7 +540,7 @@ impl<'a> Display for HalfEdge<'a> {
         write!(
             f,
             "{{ (src)-{:?}->(idx: {}) }}",
-            self.edge_accessor.metrics[self.idx],
+            self.metrics(),
             self.dst_idx(),
         )
     }
@@ -429,7 +552,9 @@ impl<'a> Display for HalfEdge<'a> {
 pub struct NodeAccessor<'a> {
     node_ids: &'a Vec<i64>,
     node_coords: &'a Vec<Coordinate>,
-    node_ch_levels: &'a Vec<usize>,
+    node_ch_levels: Option<&'a Vec<usize>>,
+    node_customs: &'a Vec<f64>,
+    node_custom_dim: usize,
 }
 
 impl IntoIterator for NodeAccessor<'_> {
@@ -467,15 +592,57 @@ impl<'a> NodeAccessor<'a> {
         self.node_coords[*idx]
     }
 
-    pub fn level(&self, idx: NodeIdx) -> usize {
-        self.node_ch_levels[*idx]
+    /// Returns the node nearest to the given coordinate (by haversine-distance) and its distance.
+    /// Every node is part of an edge, hence routable.
+    ///
+    /// All nodes are searched linearly, hence many queries should use the graph's node-index
+    /// instead (see `Graph::nearest_node_idx(...)`).
+    pub fn nearest(&self, coord: &Coordinate) -> Option<(NodeIdx, Kilometers)> {
+        let mut nearest: Option<(NodeIdx, Kilometers)> = None;
+        for (idx, node_coord) in self.node_coords.iter().enumerate() {
+            let distance = geo::haversine_distance_km(coord, node_coord);
+            if nearest.map_or(true, |(_, best_distance)| *distance < *best_distance) {
+                nearest = Some((NodeIdx(idx), distance));
+            }
+        }
+        nearest
+    }
+
+    /// Returns true, if the graph's nodes have ch-levels, which is only the case for configured
+    /// ch-levels (e.g. for contracted graphs).
+    pub fn has_levels(&self) -> bool {
+        self.node_ch_levels.is_some()
     }
 
-    pub fn idx_from(&self, id: i64) -> Result<NodeIdx, NodeIdx> {
-        match self.node_ids.binary_search(&id) {
-            Ok(idx) => Ok(NodeIdx(idx)),
-            Err(idx) => Err(NodeIdx(idx)),
-        }
+    /// Returns None, if the graph has no ch-levels (see `has_levels()`).
+    pub fn level(&self, idx: NodeIdx) -> Option<usize> {
+        self.node_ch_levels.map(|levels| levels[*idx])
+    }
+
+    /// Returns the node's custom value, whose index can be found via the parsing-config's
+    /// `nodes.custom_idx_of(...)`.
+    pub fn custom(&self, idx: NodeIdx, custom_idx: usize) -> f64 {
+        debug_assert!(
+            custom_idx < self.node_custom_dim,
+            "Custom-idx {} is out of bounds.",
+            custom_idx
+        );
+        self.node_customs[*idx * self.node_custom_dim + custom_idx]
+    }
+
+    /// Returns the missing id as error.
+    pub fn idx_from(&self, id: i64) -> Result<NodeIdx, i64> {
+        self.id_index().idx_of(id)
+    }
+
+    /// Translates all ids in one pass, which is faster than calling `idx_from(...)` per id
+    /// (see `IdIndex::idx_of_many(...)`).
+    pub fn idx_from_many(&self, ids: &[i64]) -> Vec<Result<NodeIdx, i64>> {
+        self.id_index().idx_of_many(ids)
+    }
+
+    pub fn id_index(&self) -> IdIndex<'a> {
+        IdIndex::new(self.node_ids)
     }
 
     pub fn create_from(&self, id: i64) -> Option<Node> {
@@ -506,9 +673,9 @@ pub struct EdgeAccessor<'a> {
     edge_ids: &'a Vec<Option<usize>>,
     edge_ids_to_idx_map: &'a Vec<(usize, EdgeIdx)>,
     edge_dsts: &'a Vec<NodeIdx>,
-    offsets: &'a Vec<usize>,
+    offsets: &'a OffsetVec,
     // indirect mapping to save memory
-    xwd_to_fwd_map: &'a Vec<EdgeIdx>,
+    xwd_to_fwd_map: &'a EdgeIdxVec,
     metrics: MetricAccessor<'a>,
     // shortcuts
     sc_offsets: &'a Vec<usize>,
@@ -603,6 +770,12 @@ impl<'a> EdgeAccessor<'a> {
         }
     }
 
+    /// Number of edges starting from the given node (including shortcuts), which is the node's
+    /// out-degree for fwd-edges and its in-degree for bwd-edges.
+    pub fn degree(&self, idx: NodeIdx) -> usize {
+        self.offsets.get(*idx + 1) - self.offsets.get(*idx)
+    }
+
     pub fn starting_from(&'a self, idx: NodeIdx) -> impl Iterator<Item = HalfEdge<'a>> {
         self.offset_indices(idx)
             .map(move |edge_idx| self.half_edge(edge_idx))
@@ -626,19 +799,25 @@ impl<'a> EdgeAccessor<'a> {
         // Use offset-array to get indices for the graph's edges belonging to the given node
         // (idx + 1) guaranteed by offset-array-length
         // i0 <= i1 <-> node has 0 or more leaving edges
-        (self.offsets[*idx]..self.offsets[*idx + 1])
+        (self.offsets.get(*idx)..self.offsets.get(*idx + 1))
             .into_iter()
-            .map(move |i| self.xwd_to_fwd_map[i])
+            .map(move |i| self.xwd_to_fwd_map.get(i))
     }
 }
 
 /// A shallow container for accessing metrics.
 /// Shallow means that it does only contain references to the graph's data-arrays.
+///
+/// Metrics are stored as columns, meaning all values of one metric are contiguous and sorted by
+/// edge-idx.
+/// For routing, they are mirrored as rows, which are indexed by edge-idx.
 #[derive(Debug)]
 pub struct MetricAccessor<'a> {
     cfg: &'a Config,
-    metrics: &'a Vec<DimVec<f64>>,
+    columns: &'a [Vec<f64>],
+    rows: &'a [DimVec<f64>],
     means: Option<&'a DimVec<f64>>,
+    provenances: Option<&'a [ProvenanceColumn]>,
 }
 
 impl<'a> MetricAccessor<'a> {
@@ -649,13 +828,67 @@ impl<'a> MetricAccessor<'a> {
     pub fn mean(&self, idx: MetricIdx) -> Option<f64> {
         Some(self.means?[*idx])
     }
+
+    /// Gathers the edge's values of all metrics into a copy.
+    /// Without a copy, the metric-accessor can be indexed by the edge-idx.
+    pub fn get(&self, edge_idx: EdgeIdx) -> DimVec<f64> {
+        self.columns
+            .iter()
+            .map(|column| column[*edge_idx])
+            .collect()
+    }
+
+    /// Returns the values of the given metric for all edges, sorted by edge-idx.
+    pub fn column(&self, metric_idx: MetricIdx) -> &'a [f64] {
+        &self.columns[*metric_idx]
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = (MetricIdx, &'a [f64])> + 'a {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(metric_idx, column)| (MetricIdx(metric_idx), &column[..]))
+    }
+
+    /// Returns true, if the provenance of every metric-value has been tracked when parsing.
+    pub fn has_provenances(&self) -> bool {
+        self.provenances.is_some()
+    }
+
+    /// Returns the provenances of the given metric for all edges, if tracked.
+    pub fn provenances(&self, metric_idx: MetricIdx) -> Option<&'a ProvenanceColumn> {
+        Some(&self.provenances?[*metric_idx])
+    }
+
+    pub fn try_provenance(
+        &self,
+        edge_idx: EdgeIdx,
+        metric_idx: MetricIdx,
+    ) -> err::Result<Provenance> {
+        match self.provenances(metric_idx) {
+            Some(column) => Ok(column.get(*edge_idx)),
+            None => Err(err::Msg::from(format!(
+                "The provenance of metric {} hasn't been tracked. \
+                 Set `track-provenance` when parsing.",
+                self.cfg.edges.metrics.ids[*metric_idx]
+            ))),
+        }
+    }
+
+    /// Panics if provenances haven't been tracked.
+    pub fn provenance(&self, edge_idx: EdgeIdx, metric_idx: MetricIdx) -> Provenance {
+        match self.try_provenance(edge_idx, metric_idx) {
+            Ok(provenance) => provenance,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
 }
 
 impl<'a> Index<EdgeIdx> for MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.rows[*edge_idx]
     }
 }
 
@@ -663,7 +896,7 @@ impl<'a> Index<EdgeIdx> for &MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.rows[*edge_idx]
     }
 }
 
@@ -671,7 +904,7 @@ impl<'a> Index<&EdgeIdx> for MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.rows[**edge_idx]
     }
 }
 
@@ -679,16 +912,21 @@ impl<'a> Index<&EdgeIdx> for &MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.rows[**edge_idx]
     }
 }
 
 /// A shallow container for accessing metrics.
 /// Shallow means that it does only contain references to the graph's data-arrays.
+///
+/// Metrics are stored as columns, meaning all values of one metric are contiguous and sorted by
+/// edge-idx.
+/// For routing, they are mirrored as rows, which are updated when a mutable column is dropped.
 #[derive(Debug)]
 pub struct MetricAccessorMut<'a> {
     cfg: &'a Config,
-    metrics: &'a mut Vec<DimVec<f64>>,
+    columns: &'a mut [Vec<f64>],
+    rows: &'a mut [DimVec<f64>],
     means: Option<&'a mut DimVec<f64>>,
 }
 
@@ -704,19 +942,46 @@ impl<'a> MetricAccessorMut<'a> {
     pub fn means(&mut self) -> Option<&mut DimVec<f64>> {
         Some(self.means.as_mut()?)
     }
+
+    /// Gathers the edge's values of all metrics into a copy.
+    /// Without a copy, the metric-accessor can be indexed by the edge-idx.
+    pub fn get(&self, edge_idx: EdgeIdx) -> DimVec<f64> {
+        self.columns
+            .iter()
+            .map(|column| column[*edge_idx])
+            .collect()
+    }
+
+    /// Returns the values of the given metric for all edges, sorted by edge-idx.
+    pub fn column(&self, metric_idx: MetricIdx) -> &[f64] {
+        &self.columns[*metric_idx]
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = (MetricIdx, &[f64])> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(metric_idx, column)| (MetricIdx(metric_idx), &column[..]))
+    }
+
+    /// Returns the values of the given metric for all edges, sorted by edge-idx.
+    /// The rows, which are used for routing, are updated, when the returned column is dropped.
+    ///
+    /// ATTENTION! If the metrics are normalized, the column's mean should be updated as well.
+    pub fn column_mut<'b>(&'b mut self, metric_idx: MetricIdx) -> ColumnMut<'b> {
+        ColumnMut {
+            metric_idx,
+            column: &mut self.columns[*metric_idx],
+            rows: &mut *self.rows,
+        }
+    }
 }
 
 impl<'a> Index<EdgeIdx> for MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
-    }
-}
-
-impl<'a> IndexMut<EdgeIdx> for MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[*edge_idx]
+        &self.rows[*edge_idx]
     }
 }
 
@@ -724,7 +989,7 @@ impl<'a> Index<EdgeIdx> for &MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.rows[*edge_idx]
     }
 }
 
@@ -732,13 +997,7 @@ impl<'a> Index<EdgeIdx> for &mut MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
-    }
-}
-
-impl<'a> IndexMut<EdgeIdx> for &mut MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[*edge_idx]
+        &self.rows[*edge_idx]
     }
 }
 
@@ -746,13 +1005,7 @@ impl<'a> Index<&EdgeIdx> for MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
-    }
-}
-
-impl<'a> IndexMut<&EdgeIdx> for MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: &EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[**edge_idx]
+        &self.rows[**edge_idx]
     }
 }
 
@@ -760,7 +1013,7 @@ impl<'a> Index<&EdgeIdx> for &MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.rows[**edge_idx]
     }
 }
 
@@ -768,12 +1021,37 @@ impl<'a> Index<&EdgeIdx> for &mut MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.rows[**edge_idx]
     }
 }
 
-impl<'a> IndexMut<&EdgeIdx> for &mut MetricAccessorMut<'a> {
-    fn index_mut(&mut self, edge_idx: &EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[**edge_idx]
+/// A mutable metric-column (see `MetricAccessorMut::column_mut(...)`), which writes its values
+/// into the metric-rows, when dropped.
+#[derive(Debug)]
+pub struct ColumnMut<'a> {
+    metric_idx: MetricIdx,
+    column: &'a mut [f64],
+    rows: &'a mut [DimVec<f64>],
+}
+
+impl<'a> Deref for ColumnMut<'a> {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        self.column
+    }
+}
+
+impl<'a> DerefMut for ColumnMut<'a> {
+    fn deref_mut(&mut self) -> &mut [f64] {
+        self.column
+    }
+}
+
+impl<'a> Drop for ColumnMut<'a> {
+    fn drop(&mut self) {
+        for (row, &value) in self.rows.iter_mut().zip(self.column.iter()) {
+            row[*self.metric_idx] = value;
+        }
     }
 }
@@ -0,0 +1,253 @@
+use super::{Graph, Node, NodeIdx};
+use crate::defaults;
+use kissunits::{
+    distance::Kilometers,
+    geo::{self, Coordinate},
+};
+use std::cmp::Ordering;
+
+/// A node's coordinate as unit-vector, where the euclidean distance between two unit-vectors
+/// (the chord) grows strictly with their great-circle-distance.
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    pos: [f64; 3],
+    idx: NodeIdx,
+}
+
+/// Spatial index (k-d-tree) of the graph's nodes for coordinate-based lookups.
+///
+/// Coordinates are stored as 3-dimensional unit-vectors instead of (lat, lon), so distances
+/// are correct near the poles and across the antimeridian.
+/// The tree is stored implicitly in one array, where every (sub-)tree's root is the median of
+/// its slice.
+///
+/// The graph builds its index once when being finalized and reuses it for every query (see
+/// `Graph::node_index()`).
+#[derive(Debug)]
+pub struct NodeIndex {
+    entries: Vec<Entry>,
+}
+
+impl NodeIndex {
+    pub(super) fn new(node_coords: &[Coordinate]) -> NodeIndex {
+        let mut entries: Vec<Entry> = node_coords
+            .iter()
+            .enumerate()
+            .map(|(idx, coord)| Entry {
+                pos: unit_vector(coord),
+                idx: NodeIdx(idx),
+            })
+            .collect();
+        build(&mut entries, 0);
+        NodeIndex { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the node nearest to the given coordinate, where ties are broken by the smaller
+    /// node-idx.
+    /// Returns `None`, if the index is empty.
+    pub fn nearest(&self, coord: &Coordinate) -> Option<NodeIdx> {
+        let mut best = None;
+        search_nearest(&self.entries, 0, &unit_vector(coord), &mut best);
+        best.map(|(_, idx)| idx)
+    }
+
+    /// Returns all nodes, whose great-circle-distance to the given coordinate is at most the
+    /// given distance, in no particular order.
+    /// Since the earth's radius is uncertain, a few more nodes could be returned, which should
+    /// be filtered by their exact distance.
+    fn candidates_within(&self, coord: &Coordinate, km: f64) -> Vec<NodeIdx> {
+        // Smaller radii lead to bigger angles, hence to more candidates.
+        let angle = km / defaults::network::nodes::MIN_EARTH_RADIUS_KM;
+        let max_chord = if angle >= std::f64::consts::PI {
+            2.0
+        } else {
+            2.0 * (angle / 2.0).sin()
+        };
+        // rounding-errors shouldn't drop nodes on the border
+        let max_chord = max_chord * (1.0 + 1e-9) + 1e-12;
+
+        let mut candidates = Vec::new();
+        search_within(
+            &self.entries,
+            0,
+            &unit_vector(coord),
+            max_chord * max_chord,
+            &mut candidates,
+        );
+        candidates
+    }
+}
+
+impl Graph {
+    /// Returns the node-index, which has been built when finalizing the graph.
+    pub fn node_index(&self) -> &NodeIndex {
+        &self.node_index
+    }
+
+    /// Returns the node nearest to the given coordinate (by haversine-distance) and its distance.
+    /// Returns `None`, if the graph has no nodes.
+    ///
+    /// In contrast to `NodeAccessor::nearest(...)`, the node-index is used instead of searching
+    /// all nodes.
+    pub fn nearest_node_idx(&self, coord: &Coordinate) -> Option<(NodeIdx, Kilometers)> {
+        let node_idx = self.node_index().nearest(coord)?;
+        let distance = geo::haversine_distance_km(coord, &self.node_coords[*node_idx]);
+        Some((node_idx, distance))
+    }
+
+    /// Returns the node nearest to the given coordinate (see `nearest_node_idx(...)`), e.g. for
+    /// snapping gps-coordinates.
+    pub fn nearest_node(&self, coord: &Coordinate) -> Option<Node> {
+        let (node_idx, _distance) = self.nearest_node_idx(coord)?;
+        Some(self.nodes().create(node_idx))
+    }
+
+    /// Like `nearest_node(...)`, but for gps-coordinates given as latitude and longitude.
+    pub fn nearest_node_at(&self, lat: f32, lon: f32) -> Option<Node> {
+        self.nearest_node(&Coordinate {
+            lat: f64::from(lat),
+            lon: f64::from(lon),
+        })
+    }
+
+    /// Returns all nodes, whose haversine-distance to the given node is at most the given
+    /// kilometers, sorted by their distance (and their indices for equal distances).
+    /// The given node is part of the result, if the kilometers are not negative.
+    pub fn nodes_within_radius_km(&self, center: NodeIdx, km: f64) -> Vec<NodeIdx> {
+        if km.is_nan() || km < 0.0 {
+            return Vec::new();
+        }
+
+        let center = self.node_coords[*center];
+        let mut nodes: Vec<(f64, NodeIdx)> = self
+            .node_index()
+            .candidates_within(&center, km)
+            .into_iter()
+            .map(|idx| {
+                (
+                    *geo::haversine_distance_km(&center, &self.node_coords[*idx]),
+                    idx,
+                )
+            })
+            .filter(|&(distance, _)| distance <= km)
+            .collect();
+        nodes.sort_unstable_by(|(distance_a, idx_a), (distance_b, idx_b)| {
+            distance_a
+                .partial_cmp(distance_b)
+                .unwrap_or(Ordering::Equal)
+                .then(idx_a.cmp(idx_b))
+        });
+        nodes.into_iter().map(|(_, idx)| idx).collect()
+    }
+}
+
+fn unit_vector(coord: &Coordinate) -> [f64; 3] {
+    let (lat, lon) = (coord.lat.to_radians(), coord.lon.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn squared_chord(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|axis| (a[axis] - b[axis]).powi(2)).sum()
+}
+
+/// Orders the slice into an implicit k-d-tree, where the median splits the slice by the
+/// depth's axis and both halves are built recursively.
+///
+/// Only the median is selected per slice instead of sorting it, hence building takes
+/// `O(n log n)`.
+fn build(entries: &mut [Entry], depth: usize) {
+    if entries.len() <= 1 {
+        return;
+    }
+
+    let axis = depth % 3;
+    let mid = entries.len() / 2;
+    entries.select_nth_unstable_by(mid, |a, b| {
+        a.pos[axis]
+            .partial_cmp(&b.pos[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+    let (lower, upper) = entries.split_at_mut(mid);
+    build(lower, depth + 1);
+    build(&mut upper[1..], depth + 1);
+}
+
+fn search_nearest(
+    entries: &[Entry],
+    depth: usize,
+    pos: &[f64; 3],
+    best: &mut Option<(f64, NodeIdx)>,
+) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let mid = entries.len() / 2;
+    let entry = &entries[mid];
+    let squared_distance = squared_chord(&entry.pos, pos);
+    let is_better = match *best {
+        Some((best_distance, best_idx)) => {
+            squared_distance < best_distance
+                || (squared_distance == best_distance && entry.idx < best_idx)
+        }
+        None => true,
+    };
+    if is_better {
+        *best = Some((squared_distance, entry.idx));
+    }
+
+    // The lower half has values up to the median's one, the upper half from the median's one.
+    let axis = depth % 3;
+    let diff = pos[axis] - entry.pos[axis];
+    let (near, far) = if diff < 0.0 {
+        (&entries[..mid], &entries[mid + 1..])
+    } else {
+        (&entries[mid + 1..], &entries[..mid])
+    };
+    search_nearest(near, depth + 1, pos, best);
+    // equal distances are searched as well for breaking ties
+    if best.map_or(true, |(best_distance, _)| diff * diff <= best_distance) {
+        search_nearest(far, depth + 1, pos, best);
+    }
+}
+
+fn search_within(
+    entries: &[Entry],
+    depth: usize,
+    pos: &[f64; 3],
+    max_squared_chord: f64,
+    found: &mut Vec<NodeIdx>,
+) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let mid = entries.len() / 2;
+    let entry = &entries[mid];
+    if squared_chord(&entry.pos, pos) <= max_squared_chord {
+        found.push(entry.idx);
+    }
+
+    let axis = depth % 3;
+    let diff = pos[axis] - entry.pos[axis];
+    if diff <= 0.0 || diff * diff <= max_squared_chord {
+        search_within(&entries[..mid], depth + 1, pos, max_squared_chord, found);
+    }
+    if diff >= 0.0 || diff * diff <= max_squared_chord {
+        search_within(
+            &entries[mid + 1..],
+            depth + 1,
+            pos,
+            max_squared_chord,
+            found,
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::{self, Display};
+
+/// Origin of an edge's metric-value, which is only tracked if configured when parsing
+/// (see `configs::parsing::edges::metrics::Config::is_tracking_provenance`).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Provenance {
+    /// The value has been read from the map-file (or merged from an edges-file).
+    Measured,
+    /// The value is a default, e.g. the maxspeed of a street-category, or a custom metric.
+    Defaulted,
+    /// The value has been generated from other values, e.g. a haversine-distance or a duration.
+    Calculated,
+}
+
+impl Provenance {
+    const BITS: usize = 2;
+    const PER_WORD: usize = 64 / Provenance::BITS;
+
+    fn to_bits(self) -> u64 {
+        match self {
+            Provenance::Measured => 0,
+            Provenance::Defaulted => 1,
+            Provenance::Calculated => 2,
+        }
+    }
+
+    fn from_bits(bits: u64) -> Provenance {
+        match bits {
+            0 => Provenance::Measured,
+            1 => Provenance::Defaulted,
+            _ => Provenance::Calculated,
+        }
+    }
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Provenance::Measured => write!(f, "measured"),
+            Provenance::Defaulted => write!(f, "defaulted"),
+            Provenance::Calculated => write!(f, "calculated"),
+        }
+    }
+}
+
+/// Provenances of one metric for all edges, sorted by edge-idx.
+///
+/// Every provenance needs 2 bits, hence 32 edges share one `u64`.
+#[derive(Clone, Debug, Default)]
+pub struct ProvenanceColumn {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl ProvenanceColumn {
+    pub fn new() -> ProvenanceColumn {
+        ProvenanceColumn::default()
+    }
+
+    pub fn with_value(provenance: Provenance, len: usize) -> ProvenanceColumn {
+        let mut column = ProvenanceColumn::new();
+        column.reserve_exact(len);
+        for _ in 0..len {
+            column.push(provenance);
+        }
+        column
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let word_count = (self.len + additional + Provenance::PER_WORD - 1) / Provenance::PER_WORD;
+        self.words
+            .reserve_exact(word_count.saturating_sub(self.words.len()));
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.words.shrink_to_fit();
+    }
+
+    pub fn push(&mut self, provenance: Provenance) {
+        if self.len % Provenance::PER_WORD == 0 {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, provenance);
+    }
+
+    /// Panics if the idx is out of bounds.
+    pub fn get(&self, idx: usize) -> Provenance {
+        assert!(
+            idx < self.len,
+            "Provenance-idx {} is out of bounds ({}).",
+            idx,
+            self.len
+        );
+        let shift = (idx % Provenance::PER_WORD) * Provenance::BITS;
+        Provenance::from_bits((self.words[idx / Provenance::PER_WORD] >> shift) & 0b11)
+    }
+
+    /// Panics if the idx is out of bounds.
+    pub fn set(&mut self, idx: usize, provenance: Provenance) {
+        assert!(
+            idx < self.len,
+            "Provenance-idx {} is out of bounds ({}).",
+            idx,
+            self.len
+        );
+        let shift = (idx % Provenance::PER_WORD) * Provenance::BITS;
+        let word = &mut self.words[idx / Provenance::PER_WORD];
+        *word = (*word & !(0b11 << shift)) | (provenance.to_bits() << shift);
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = Provenance> + 'a {
+        (0..self.len).map(move |idx| self.get(idx))
+    }
+
+    pub fn count(&self, provenance: Provenance) -> usize {
+        self.iter().filter(|p| p == &provenance).count()
+    }
+
+    /// Returns the share (in `[0.0, 1.0]`) of edges with the given provenance, or `0.0` for an
+    /// empty column.
+    pub fn share(&self, provenance: Provenance) -> f64 {
+        if self.len == 0 {
+            0.0
+        } else {
+            self.count(provenance) as f64 / self.len as f64
+        }
+    }
+}
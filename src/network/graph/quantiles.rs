@@ -0,0 +1,101 @@
+use super::{Graph, MetricIdx};
+use crate::helpers::err;
+use std::collections::{BTreeMap, BTreeSet};
+
+impl Graph {
+    /// Returns the quantiles `qs` (each in `[0, 1]`) of the given metric's values over all edges.
+    ///
+    /// Quantiles are interpolated linearly between the closest ranks, so for the sorted values
+    /// `x[0..n]` and `h = (n - 1) * q`, the quantile is `x[floor(h)] + (h - floor(h)) *
+    /// (x[floor(h) + 1] - x[floor(h)])`.
+    ///
+    /// Only the needed ranks are selected (see `slice::select_nth_unstable_by(...)`) from one copy
+    /// of the metric's column, and they are cached until the metrics are changed (see
+    /// `Graph::metrics_mut()`).
+    pub fn try_metric_quantiles(&self, metric_idx: MetricIdx, qs: &[f64]) -> err::Result<Vec<f64>> {
+        for &q in qs {
+            if !(0.0..=1.0).contains(&q) {
+                return Err(format!("Quantile {} should be in [0, 1].", q).into());
+            }
+        }
+
+        let column = match self.metrics.get(*metric_idx) {
+            Some(column) => column,
+            None => {
+                return Err(format!(
+                    "Metric-idx {} is out of range, since the graph has {} metrics.",
+                    metric_idx,
+                    self.metrics.len()
+                )
+                .into())
+            }
+        };
+        if column.is_empty() {
+            return Err(format!(
+                "Quantiles of metric {} are undefined, since the graph has no edges.",
+                metric_idx
+            )
+            .into());
+        }
+
+        // closest ranks of every quantile
+        let max_rank = column.len() - 1;
+        let ranks: Vec<(f64, usize, usize)> = qs
+            .iter()
+            .map(|&q| {
+                let h = (max_rank as f64) * q;
+                let lo = (h.floor() as usize).min(max_rank);
+                let hi = (lo + 1).min(max_rank);
+                (h, lo, hi)
+            })
+            .collect();
+
+        let mut cache = self
+            .metric_ranks
+            .lock()
+            .expect("The metric-ranks' cache shouldn't be poisoned.");
+        let ranked_values = cache.entry(metric_idx).or_insert_with(BTreeMap::new);
+        let missing_ranks: BTreeSet<usize> = ranks
+            .iter()
+            .flat_map(|&(_, lo, hi)| vec![lo, hi])
+            .filter(|rank| !ranked_values.contains_key(rank))
+            .collect();
+
+        if !missing_ranks.is_empty() {
+            if column.iter().any(|value| value.is_nan()) {
+                return Err(format!("Metric {} contains NaN-values.", metric_idx).into());
+            }
+
+            // Selecting ascending ranks leaves all smaller values left of the selected one,
+            // hence the next rank is selected from the right part only.
+            let mut values = column.clone();
+            let mut rest = &mut values[..];
+            let mut offset = 0;
+            for rank in missing_ranks {
+                let unselected = rest;
+                let (_, value, right) = unselected.select_nth_unstable_by(rank - offset, |a, b| {
+                    a.partial_cmp(b)
+                        .expect("Metric-values should be comparable, since NaNs are rejected.")
+                });
+                ranked_values.insert(rank, *value);
+                rest = right;
+                offset = rank + 1;
+            }
+        }
+
+        Ok(ranks
+            .iter()
+            .map(|&(h, lo, hi)| {
+                let (lo_value, hi_value) = (ranked_values[&lo], ranked_values[&hi]);
+                lo_value + (h - lo as f64) * (hi_value - lo_value)
+            })
+            .collect())
+    }
+
+    pub fn metric_quantiles(&self, metric_idx: MetricIdx, qs: &[f64]) -> Vec<f64> {
+        match self.try_metric_quantiles(metric_idx, qs) {
+            Ok(quantiles) => quantiles,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
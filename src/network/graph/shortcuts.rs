@@ -0,0 +1,48 @@
+use super::{EdgeAccessor, EdgeIdx, Graph, MetricIdx};
+
+impl Graph {
+    /// Sets every shortcut's value of the given metric to the sum of its sc-edges' values, which
+    /// are computed recursively.
+    /// Values of edges, which aren't shortcuts, are kept.
+    ///
+    /// This patches shortcuts in place after their underlying edges have changed, e.g. when the
+    /// balancer updates its workload-metric without contracting the graph again.
+    /// The hierarchy itself (levels and sc-edges) is kept, so CH-Dijkstra stays correct, as long
+    /// as the metric is still a reasonable fit for the hierarchy.
+    ///
+    /// ATTENTION! If the metrics are normalized, the column's mean should be updated as well.
+    pub fn recompute_shortcuts(&mut self, metric_idx: MetricIdx) {
+        if self.shortcut_count() == 0 {
+            return;
+        }
+
+        let mut column = self.metrics().column(metric_idx).to_vec();
+        {
+            let fwd_edges = self.fwd_edges();
+            let mut is_set = vec![false; column.len()];
+            for edge_idx in fwd_edges.iter() {
+                sum_up_shortcut(edge_idx, &fwd_edges, &mut column, &mut is_set);
+            }
+        }
+        self.metrics_mut()
+            .column_mut(metric_idx)
+            .copy_from_slice(&column);
+    }
+}
+
+/// Shortcuts' values are the sum of their sc-edges' values, which are computed recursively.
+fn sum_up_shortcut(
+    edge_idx: EdgeIdx,
+    fwd_edges: &EdgeAccessor,
+    column: &mut [f64],
+    is_set: &mut [bool],
+) -> f64 {
+    if !is_set[*edge_idx] {
+        if let Some(&[sc_edge_0, sc_edge_1]) = fwd_edges.sc_edges(edge_idx) {
+            column[*edge_idx] = sum_up_shortcut(sc_edge_0, fwd_edges, column, is_set)
+                + sum_up_shortcut(sc_edge_1, fwd_edges, column, is_set);
+        }
+        is_set[*edge_idx] = true;
+    }
+    column[*edge_idx]
+}
@@ -0,0 +1,163 @@
+use super::{EdgeIdx, Graph, MetricIdx};
+use crate::{defaults, helpers::Fnv1a};
+use log::{info, warn};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+impl Graph {
+    /// Returns a hash of every edge's src-id, dst-id and rounded length (sorted by edge-idx),
+    /// which stays the same, when the graph is re-parsed or updated, in contrast to edge-indices.
+    ///
+    /// Stable ids are unique per graph.
+    /// Hence, the rare colliding edges, e.g. parallel edges of similar length, include their
+    /// metrics in the hash, which makes them less stable.
+    ///
+    /// The ids are computed on first use, but before any metric is changed (see
+    /// `Graph::metrics_mut()`), so they don't depend on updated metrics.
+    pub fn stable_ids(&self) -> Arc<Vec<u64>> {
+        let mut cache = self
+            .stable_ids
+            .lock()
+            .expect("The stable ids' cache shouldn't be poisoned.");
+        if let Some(stable_ids) = cache.as_ref() {
+            return Arc::clone(stable_ids);
+        }
+
+        info!("START Compute stable edge-ids.");
+        let stable_ids = Arc::new(self.compute_stable_ids());
+        info!("FINISHED");
+        *cache = Some(Arc::clone(&stable_ids));
+        stable_ids
+    }
+
+    /// Maps the stable ids of all edges (see `Graph::stable_ids()`) to their indices, e.g. for
+    /// correlating edges of different graph-versions.
+    pub fn stable_id_index(&self) -> HashMap<u64, EdgeIdx> {
+        self.stable_ids()
+            .iter()
+            .enumerate()
+            .map(|(idx, &stable_id)| (stable_id, EdgeIdx(idx)))
+            .collect()
+    }
+
+    /// Colliding edges include their (denormalized) metrics in their hashes.
+    /// If they still collide, they are distinguished by counting them in the order of their
+    /// edge-ids and exact metrics, which is independent of their edge-indices.
+    /// Edges, which are equal in all of these, are identical, hence it doesn't matter, which of
+    /// them gets which id.
+    fn compute_stable_ids(&self) -> Vec<u64> {
+        let nodes = self.nodes();
+        let fwd_edges = self.fwd_edges();
+        let bwd_edges = self.bwd_edges();
+        let metrics = self.metrics();
+
+        let edge_values = |edge_idx: EdgeIdx| {
+            let src_idx = bwd_edges.dst_idx(edge_idx);
+            let dst_idx = fwd_edges.dst_idx(edge_idx);
+            let meters = *kissunits::geo::haversine_distance_km(
+                &nodes.coord(src_idx),
+                &nodes.coord(dst_idx),
+            ) * 1_000.0;
+            vec![
+                nodes.id(src_idx),
+                nodes.id(dst_idx),
+                (meters / defaults::network::edges::STABLE_ID_LENGTH_BUCKET).round() as i64,
+            ]
+        };
+        let metric_values = |edge_idx: EdgeIdx| -> Vec<f64> {
+            metrics
+                .get(edge_idx)
+                .iter()
+                .enumerate()
+                .map(|(metric_idx, value)| {
+                    value * metrics.mean(MetricIdx(metric_idx)).unwrap_or(1.0)
+                })
+                .collect()
+        };
+        let mut stable_ids: Vec<u64> = fwd_edges
+            .iter()
+            .map(|edge_idx| stable_hash(&edge_values(edge_idx)))
+            .collect();
+
+        // fall back to metrics for colliding edges
+
+        let colliding_edges = find_collisions(&stable_ids);
+        if colliding_edges.len() > 0 {
+            warn!(
+                "{} edges have colliding stable ids, hence their metrics are included.",
+                colliding_edges.len()
+            );
+            for &edge_idx in &colliding_edges {
+                let mut values = edge_values(edge_idx);
+                values.extend(
+                    metric_values(edge_idx)
+                        .iter()
+                        .map(|value| (value / defaults::accuracy::F64_ABS).round() as i64),
+                );
+                stable_ids[*edge_idx] = stable_hash(&values);
+            }
+        }
+
+        // remaining collisions are counted in an order, which only depends on the edges' content
+
+        let mut colliding_edges = find_collisions(&stable_ids);
+        if colliding_edges.len() > 0 {
+            warn!(
+                "{} edges are (almost) identical, hence their stable ids are counted.",
+                colliding_edges.len()
+            );
+            colliding_edges.sort_by(|&a, &b| {
+                stable_ids[*a]
+                    .cmp(&stable_ids[*b])
+                    .then_with(|| fwd_edges.try_id(a).cmp(&fwd_edges.try_id(b)))
+                    .then_with(|| {
+                        metric_values(a)
+                            .iter()
+                            .zip(metric_values(b).iter())
+                            .map(|(value_a, value_b)| {
+                                value_a.partial_cmp(value_b).unwrap_or(Ordering::Equal)
+                            })
+                            .find(|&ordering| ordering != Ordering::Equal)
+                            .unwrap_or(Ordering::Equal)
+                    })
+            });
+            let mut counts = HashMap::new();
+            for &edge_idx in &colliding_edges {
+                let count = counts.entry(stable_ids[*edge_idx]).or_insert(0);
+                stable_ids[*edge_idx] = stable_hash(&[stable_ids[*edge_idx] as i64, *count]);
+                *count += 1;
+            }
+        }
+
+        stable_ids
+    }
+}
+
+fn stable_hash(values: &[i64]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    for &value in values {
+        hasher.write_i64(value);
+    }
+    hasher.finish()
+}
+
+/// Returns all edges, whose stable id is not unique, sorted by their index.
+fn find_collisions(stable_ids: &[u64]) -> Vec<EdgeIdx> {
+    let mut sorted_ids: Vec<_> = stable_ids
+        .iter()
+        .enumerate()
+        .map(|(idx, &stable_id)| (stable_id, EdgeIdx(idx)))
+        .collect();
+    sorted_ids.sort_unstable();
+
+    let mut colliding_edges: Vec<_> = sorted_ids
+        .iter()
+        .enumerate()
+        .filter(|&(i, (stable_id, _idx))| {
+            (i > 0 && sorted_ids[i - 1].0 == *stable_id)
+                || (i + 1 < sorted_ids.len() && sorted_ids[i + 1].0 == *stable_id)
+        })
+        .map(|(_i, &(_stable_id, idx))| idx)
+        .collect();
+    colliding_edges.sort_unstable();
+    colliding_edges
+}
@@ -0,0 +1,236 @@
+use super::{EdgeIdx, EdgeIdxVec, Graph, NodeIdx, NodeIndex, OffsetVec, ProvenanceColumn};
+use crate::helpers::err;
+use log::{info, warn};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// Maps node-indices between a graph and its subgraph (see `Graph::subgraph(...)`).
+#[derive(Clone, Debug)]
+pub struct IndexMapping {
+    /// indexed by the original graph's node-indices, None if the node isn't in the subgraph
+    pub old_to_new: Vec<Option<NodeIdx>>,
+    /// indexed by the subgraph's node-indices
+    pub new_to_old: Vec<NodeIdx>,
+}
+
+impl IndexMapping {
+    pub fn to_new(&self, old_idx: NodeIdx) -> Option<NodeIdx> {
+        self.old_to_new[*old_idx]
+    }
+
+    pub fn to_old(&self, new_idx: NodeIdx) -> NodeIdx {
+        self.new_to_old[*new_idx]
+    }
+}
+
+impl Graph {
+    /// Extracts the subgraph induced by the given nodes, meaning the given nodes and all edges
+    /// having both endpoints in the given nodes are kept.
+    /// The original graph isn't touched, and duplicated node-indices are ignored.
+    ///
+    /// Since remaining nodes keep their order, the new graph is sorted like the original one.
+    /// Ids, coordinates, custom node-values and all metric-columns (with their provenances) are
+    /// copied.
+    /// Turn-restrictions are kept, if all nodes of their turns are kept, since they are stored by
+    /// node-ids instead of edge-indices.
+    /// Conditions of restricted edges are kept with their edges, since they are stored by stable
+    /// ids.
+    /// Ch-levels and shortcuts are dropped (with a warning), because the subgraph isn't
+    /// contracted anymore, hence shortcut-edges are removed as well.
+    pub fn try_subgraph(&self, nodes: &[NodeIdx]) -> err::Result<(Graph, IndexMapping)> {
+        info!("START Extract subgraph of {} nodes.", nodes.len());
+
+        //----------------------------------------------------------------------------------------//
+        // map node-indices
+
+        let node_count = self.nodes().count();
+        let mut old_to_new = vec![None; node_count];
+        for &old_idx in nodes {
+            if *old_idx >= node_count {
+                return Err(format!(
+                    "The node-idx {} is out of bounds for a graph with {} nodes.",
+                    *old_idx, node_count
+                )
+                .into());
+            }
+            old_to_new[*old_idx] = Some(NodeIdx(0));
+        }
+        let mut new_to_old = Vec::with_capacity(nodes.len());
+        for old_idx in 0..node_count {
+            if let Some(new_idx) = &mut old_to_new[old_idx] {
+                *new_idx = NodeIdx(new_to_old.len());
+                new_to_old.push(NodeIdx(old_idx));
+            }
+        }
+
+        if self.node_ch_levels.is_some() {
+            warn!("The subgraph's nodes don't keep their ch-levels.");
+        }
+        if self.sc_edges.len() > 0 {
+            warn!("The subgraph doesn't keep the graph's shortcut-edges.");
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // copy nodes
+
+        let dim = self.node_custom_dim;
+        let mut subgraph = Graph {
+            cfg: self.cfg.clone(),
+            node_ids: new_to_old.iter().map(|&idx| self.node_ids[*idx]).collect(),
+            node_coords: new_to_old
+                .iter()
+                .map(|&idx| self.node_coords[*idx])
+                .collect(),
+            node_ch_levels: None,
+            node_customs: new_to_old
+                .iter()
+                .flat_map(|&idx| self.node_customs[*idx * dim..(*idx + 1) * dim].iter())
+                .cloned()
+                .collect(),
+            node_custom_dim: dim,
+            fwd_dsts: Vec::new(),
+            fwd_offsets: OffsetVec::with_capacity(
+                self.cfg.is_using_compact_indices,
+                new_to_old.len() + 1,
+            ),
+            fwd_to_fwd_map: EdgeIdxVec::new(self.cfg.is_using_compact_indices),
+            bwd_dsts: Vec::new(),
+            bwd_offsets: OffsetVec::new(self.cfg.is_using_compact_indices),
+            bwd_to_fwd_map: EdgeIdxVec::new(self.cfg.is_using_compact_indices),
+            metrics: vec![Vec::new(); self.metrics.len()],
+            metric_rows: Vec::new(),
+            means: self.means.clone(),
+            edge_ids: Vec::new(),
+            edge_ids_to_idx_map: Vec::new(),
+            stable_ids: Mutex::new(None),
+            provenances: None,
+            sc_offsets: Vec::new(),
+            sc_edges: Vec::new(),
+            are_leaving_edges_level_sorted: false,
+            restriction_conditions: BTreeMap::new(),
+            turn_restrictions: BTreeMap::new(),
+            components: Mutex::new(None),
+            edge_grid: Mutex::new(None),
+            node_index: NodeIndex::new(&[]),
+            metric_ranks: Mutex::new(BTreeMap::new()),
+        };
+        subgraph.node_index = NodeIndex::new(&subgraph.node_coords);
+
+        // turn-restrictions of remaining nodes
+        let has_node_id = |id: &i64| subgraph.node_ids.binary_search(id).is_ok();
+        subgraph.turn_restrictions = self
+            .turn_restrictions
+            .iter()
+            .filter(|((from_src_id, via_id, to_dst_id), _turn_restriction)| {
+                has_node_id(from_src_id) && has_node_id(via_id) && has_node_id(to_dst_id)
+            })
+            .map(|(&key, &turn_restriction)| (key, turn_restriction))
+            .collect();
+
+        //----------------------------------------------------------------------------------------//
+        // copy forward-edges
+        // Forward-edges are sorted by their src, which keeps its order.
+
+        let fwd_edges = self.fwd_edges();
+        let mut old_edge_indices = Vec::new();
+        subgraph.fwd_offsets.push(0);
+        for &old_src_idx in new_to_old.iter() {
+            for leaving_edge in fwd_edges.starting_from(old_src_idx) {
+                if leaving_edge.is_shortcut() {
+                    continue;
+                }
+                if let Some(new_dst_idx) = old_to_new[*leaving_edge.dst_idx()] {
+                    let new_edge_idx = EdgeIdx(subgraph.fwd_dsts.len());
+                    subgraph.fwd_dsts.push(new_dst_idx);
+                    subgraph.fwd_to_fwd_map.push(new_edge_idx);
+                    subgraph
+                        .bwd_dsts
+                        .push(old_to_new[*old_src_idx].expect("Src should be in subgraph."));
+                    old_edge_indices.push(leaving_edge.idx());
+                }
+            }
+            subgraph.fwd_offsets.push(subgraph.fwd_dsts.len());
+        }
+
+        // edge-ids and metrics
+        for (new_edge_idx, &old_edge_idx) in old_edge_indices.iter().enumerate() {
+            let edge_id = self.edge_ids[*old_edge_idx];
+            subgraph.edge_ids.push(edge_id);
+            if let Some(id) = edge_id {
+                subgraph
+                    .edge_ids_to_idx_map
+                    .push((id, EdgeIdx(new_edge_idx)));
+            }
+        }
+        subgraph
+            .edge_ids_to_idx_map
+            .sort_unstable_by_key(|&(id, _idx)| id);
+        // stable ids are kept to correlate edges with the graph's edges
+        let stable_ids = self.stable_ids();
+        subgraph.stable_ids = Mutex::new(Some(Arc::new(
+            old_edge_indices
+                .iter()
+                .map(|&old_edge_idx| stable_ids[*old_edge_idx])
+                .collect(),
+        )));
+        // conditions of remaining edges, which are keyed by stable ids
+        subgraph.restriction_conditions = old_edge_indices
+            .iter()
+            .filter_map(|&old_edge_idx| {
+                let stable_id = stable_ids[*old_edge_idx];
+                self.restriction_conditions
+                    .get(&stable_id)
+                    .map(|condition| (stable_id, condition.clone()))
+            })
+            .collect();
+        for (new_column, old_column) in subgraph.metrics.iter_mut().zip(self.metrics.iter()) {
+            *new_column = old_edge_indices
+                .iter()
+                .map(|&old_edge_idx| old_column[*old_edge_idx])
+                .collect();
+        }
+        subgraph.build_metric_rows();
+        subgraph.provenances = self.provenances.as_ref().map(|old_columns| {
+            old_columns
+                .iter()
+                .map(|old_column| {
+                    let mut new_column = ProvenanceColumn::new();
+                    new_column.reserve_exact(old_edge_indices.len());
+                    for &old_edge_idx in old_edge_indices.iter() {
+                        new_column.push(old_column.get(*old_edge_idx));
+                    }
+                    new_column
+                })
+                .collect()
+        });
+        // without shortcuts, every edge's shortcut-offset is 0
+        subgraph.sc_offsets = vec![0; old_edge_indices.len() + 1];
+
+        //----------------------------------------------------------------------------------------//
+        // build backward-edges
+
+        subgraph.build_bwd_edges()?;
+
+        info!(
+            "FINISHED Extracted subgraph with {} nodes and {} edges.",
+            subgraph.nodes().count(),
+            subgraph.fwd_edges().count()
+        );
+        Ok((
+            subgraph,
+            IndexMapping {
+                old_to_new,
+                new_to_old,
+            },
+        ))
+    }
+
+    pub fn subgraph(&self, nodes: &[NodeIdx]) -> (Graph, IndexMapping) {
+        match self.try_subgraph(nodes) {
+            Ok(subgraph) => subgraph,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use super::{EdgeIdx, Graph};
+use std::fmt::{self, Display};
+
+/// The kind of a turn-restriction as given by the tag `restriction` of a relation of type
+/// `restriction` (see [osm-wiki Relation:restriction](https://wiki.openstreetmap.org/wiki/Relation:restriction)).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TurnRestrictionKind {
+    NoLeftTurn,
+    NoRightTurn,
+    NoStraightOn,
+    NoUTurn,
+    NoEntry,
+    NoExit,
+    OnlyLeftTurn,
+    OnlyRightTurn,
+    OnlyStraightOn,
+    OnlyUTurn,
+}
+
+impl TurnRestrictionKind {
+    /// Returns `None` for unknown values.
+    pub fn from_tag(value: &str) -> Option<TurnRestrictionKind> {
+        match value.trim().to_ascii_lowercase().as_ref() {
+            "no_left_turn" => Some(TurnRestrictionKind::NoLeftTurn),
+            "no_right_turn" => Some(TurnRestrictionKind::NoRightTurn),
+            "no_straight_on" => Some(TurnRestrictionKind::NoStraightOn),
+            "no_u_turn" => Some(TurnRestrictionKind::NoUTurn),
+            "no_entry" => Some(TurnRestrictionKind::NoEntry),
+            "no_exit" => Some(TurnRestrictionKind::NoExit),
+            "only_left_turn" => Some(TurnRestrictionKind::OnlyLeftTurn),
+            "only_right_turn" => Some(TurnRestrictionKind::OnlyRightTurn),
+            "only_straight_on" => Some(TurnRestrictionKind::OnlyStraightOn),
+            "only_u_turn" => Some(TurnRestrictionKind::OnlyUTurn),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TurnRestrictionKind::NoLeftTurn => "no_left_turn",
+            TurnRestrictionKind::NoRightTurn => "no_right_turn",
+            TurnRestrictionKind::NoStraightOn => "no_straight_on",
+            TurnRestrictionKind::NoUTurn => "no_u_turn",
+            TurnRestrictionKind::NoEntry => "no_entry",
+            TurnRestrictionKind::NoExit => "no_exit",
+            TurnRestrictionKind::OnlyLeftTurn => "only_left_turn",
+            TurnRestrictionKind::OnlyRightTurn => "only_right_turn",
+            TurnRestrictionKind::OnlyStraightOn => "only_straight_on",
+            TurnRestrictionKind::OnlyUTurn => "only_u_turn",
+        }
+    }
+
+    /// Returns true for `no_*`-restrictions, which forbid exactly their turn.
+    /// `only_*`-restrictions forbid all other turns, which is not enforced when routing.
+    pub fn is_prohibitive(&self) -> bool {
+        match self {
+            TurnRestrictionKind::NoLeftTurn
+            | TurnRestrictionKind::NoRightTurn
+            | TurnRestrictionKind::NoStraightOn
+            | TurnRestrictionKind::NoUTurn
+            | TurnRestrictionKind::NoEntry
+            | TurnRestrictionKind::NoExit => true,
+            TurnRestrictionKind::OnlyLeftTurn
+            | TurnRestrictionKind::OnlyRightTurn
+            | TurnRestrictionKind::OnlyStraightOn
+            | TurnRestrictionKind::OnlyUTurn => false,
+        }
+    }
+}
+
+impl Display for TurnRestrictionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A restricted turn from one way via a node into another way, given by osm-ids.
+///
+/// Since the graph doesn't know ways, the turn is resolved into the node-ids
+/// `(from-src, via, to-dst)` of its two edges when parsing.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TurnRestriction {
+    pub from_way: i64,
+    pub via_node: i64,
+    pub to_way: i64,
+    pub restriction: TurnRestrictionKind,
+}
+
+impl Graph {
+    pub fn has_turn_restrictions(&self) -> bool {
+        !self.turn_restrictions.is_empty()
+    }
+
+    /// Returns all parsed turn-restrictions with the node-ids `(from-src, via, to-dst)` of their
+    /// turns, sorted by these node-ids.
+    pub fn turn_restrictions(&self) -> impl Iterator<Item = ((i64, i64, i64), &TurnRestriction)> {
+        self.turn_restrictions
+            .iter()
+            .map(|(&node_ids, restriction)| (node_ids, restriction))
+    }
+
+    /// Returns the restriction of the turn from the first edge into the second edge, if any.
+    /// Returns `None`, if the first edge doesn't end, where the second edge starts.
+    pub fn turn_restriction(&self, from_idx: EdgeIdx, to_idx: EdgeIdx) -> Option<&TurnRestriction> {
+        if self.turn_restrictions.is_empty() {
+            return None;
+        }
+
+        let via_idx = self.fwd_dsts[*from_idx];
+        if self.bwd_dsts[*to_idx] != via_idx {
+            return None;
+        }
+        let from_src_id = self.node_ids[*self.bwd_dsts[*from_idx]];
+        let to_dst_id = self.node_ids[*self.fwd_dsts[*to_idx]];
+        self.turn_restrictions
+            .get(&(from_src_id, self.node_ids[*via_idx], to_dst_id))
+    }
+
+    /// Returns true, if a `no_*`-restriction forbids turning from the first edge into the second
+    /// edge (see `TurnRestrictionKind::is_prohibitive()`).
+    pub fn is_turn_forbidden(&self, from_idx: EdgeIdx, to_idx: EdgeIdx) -> bool {
+        self.turn_restriction(from_idx, to_idx)
+            .map_or(false, |turn_restriction| {
+                turn_restriction.restriction.is_prohibitive()
+            })
+    }
+}
@@ -0,0 +1,190 @@
+use super::{EdgeIdx, EdgeIdxSlice, Graph, NodeIdx};
+use crate::{configs::parsing::Config, defaults::capacity::DimVec};
+
+/// Read-only access to a graph, as needed by routing-algorithms like
+/// `routing::dijkstra::Dijkstra`.
+///
+/// Besides the `Graph` itself, this is implemented by `network::composite::CompositeGraph`,
+/// which joins multiple graphs without copying them into one graph.
+/// Like with `Graph::fwd_edges()` and `Graph::bwd_edges()`, edge-indices refer to the
+/// forward-edges, so forward- and backward-edges share the same edge-index.
+pub trait GraphView {
+    /// The config of the graph's metrics, which are accessed with `edge_metrics(...)`.
+    fn cfg(&self) -> &Config;
+
+    fn node_count(&self) -> usize;
+
+    fn node_id(&self, idx: NodeIdx) -> i64;
+
+    /// Returns true, if the nodes have ch-levels (see `NodeAccessor::has_levels()`).
+    fn has_levels(&self) -> bool;
+
+    /// Returns None, if the nodes have no ch-levels (see `has_levels()`).
+    fn node_level(&self, idx: NodeIdx) -> Option<usize>;
+
+    /// See `Graph::are_leaving_edges_level_sorted()`
+    fn are_leaving_edges_level_sorted(&self) -> bool;
+
+    /// Returns None, if the edge has no id.
+    fn edge_id(&self, idx: EdgeIdx) -> Option<usize>;
+
+    fn fwd_leaving_edges(&self, idx: NodeIdx) -> LeavingEdges;
+
+    fn bwd_leaving_edges(&self, idx: NodeIdx) -> LeavingEdges;
+
+    fn fwd_dst_idx(&self, idx: EdgeIdx) -> NodeIdx;
+
+    /// Returns the dst of the backward-edge, hence the src of the forward-edge.
+    fn bwd_dst_idx(&self, idx: EdgeIdx) -> NodeIdx;
+
+    fn edge_metrics(&self, idx: EdgeIdx) -> DimVec<f64>;
+
+    /// See `Graph::is_turn_forbidden(...)`
+    fn is_turn_forbidden(&self, from_idx: EdgeIdx, to_idx: EdgeIdx) -> bool;
+}
+
+/// An edge leaving a node, without references to the graph (see `GraphView`).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LeavingEdge {
+    pub(crate) idx: EdgeIdx,
+    pub(crate) dst_idx: NodeIdx,
+}
+
+impl LeavingEdge {
+    pub fn idx(&self) -> EdgeIdx {
+        self.idx
+    }
+
+    pub fn dst_idx(&self) -> NodeIdx {
+        self.dst_idx
+    }
+}
+
+/// Iterates over the leaving edges of a node (see `GraphView`).
+///
+/// Edges of the underlying offset-graph come first and can be shifted by index-offsets, e.g. for
+/// composite graphs.
+/// Additional edges, e.g. connecting graphs, follow.
+#[derive(Clone, Debug)]
+pub struct LeavingEdges<'a> {
+    // slice of the xwd-to-fwd-map, belonging to the node's offsets
+    edge_indices: EdgeIdxSlice<'a>,
+    edge_dsts: &'a [NodeIdx],
+    node_offset: usize,
+    edge_offset: usize,
+    additional_edges: &'a [LeavingEdge],
+}
+
+impl<'a> LeavingEdges<'a> {
+    fn new(edge_indices: EdgeIdxSlice<'a>, edge_dsts: &'a [NodeIdx]) -> LeavingEdges<'a> {
+        LeavingEdges {
+            edge_indices,
+            edge_dsts,
+            node_offset: 0,
+            edge_offset: 0,
+            additional_edges: &[],
+        }
+    }
+
+    /// Shifts the underlying edges' indices and dst-indices.
+    pub(crate) fn with_offsets(
+        mut self,
+        node_offset: usize,
+        edge_offset: usize,
+    ) -> LeavingEdges<'a> {
+        self.node_offset += node_offset;
+        self.edge_offset += edge_offset;
+        self
+    }
+
+    /// Appends the given edges, which are not shifted.
+    pub(crate) fn with_additional_edges(
+        mut self,
+        additional_edges: &'a [LeavingEdge],
+    ) -> LeavingEdges<'a> {
+        self.additional_edges = additional_edges;
+        self
+    }
+}
+
+impl<'a> Iterator for LeavingEdges<'a> {
+    type Item = LeavingEdge;
+
+    fn next(&mut self) -> Option<LeavingEdge> {
+        if let Some((edge_idx, remaining)) = self.edge_indices.split_first() {
+            self.edge_indices = remaining;
+            Some(LeavingEdge {
+                idx: EdgeIdx(*edge_idx + self.edge_offset),
+                dst_idx: NodeIdx(*self.edge_dsts[*edge_idx] + self.node_offset),
+            })
+        } else if let Some((edge, remaining)) = self.additional_edges.split_first() {
+            self.additional_edges = remaining;
+            Some(*edge)
+        } else {
+            None
+        }
+    }
+}
+
+impl GraphView for Graph {
+    fn cfg(&self) -> &Config {
+        &self.cfg
+    }
+
+    fn node_count(&self) -> usize {
+        self.node_ids.len()
+    }
+
+    fn node_id(&self, idx: NodeIdx) -> i64 {
+        self.node_ids[*idx]
+    }
+
+    fn has_levels(&self) -> bool {
+        self.node_ch_levels.is_some()
+    }
+
+    fn node_level(&self, idx: NodeIdx) -> Option<usize> {
+        self.node_ch_levels.as_ref().map(|levels| levels[*idx])
+    }
+
+    fn are_leaving_edges_level_sorted(&self) -> bool {
+        self.are_leaving_edges_level_sorted
+    }
+
+    fn edge_id(&self, idx: EdgeIdx) -> Option<usize> {
+        self.edge_ids[*idx]
+    }
+
+    fn fwd_leaving_edges(&self, idx: NodeIdx) -> LeavingEdges {
+        // (idx + 1) guaranteed by offset-array-length
+        LeavingEdges::new(
+            self.fwd_to_fwd_map
+                .slice(self.fwd_offsets.get(*idx)..self.fwd_offsets.get(*idx + 1)),
+            &self.fwd_dsts,
+        )
+    }
+
+    fn bwd_leaving_edges(&self, idx: NodeIdx) -> LeavingEdges {
+        LeavingEdges::new(
+            self.bwd_to_fwd_map
+                .slice(self.bwd_offsets.get(*idx)..self.bwd_offsets.get(*idx + 1)),
+            &self.bwd_dsts,
+        )
+    }
+
+    fn fwd_dst_idx(&self, idx: EdgeIdx) -> NodeIdx {
+        self.fwd_dsts[*idx]
+    }
+
+    fn bwd_dst_idx(&self, idx: EdgeIdx) -> NodeIdx {
+        self.bwd_dsts[*idx]
+    }
+
+    fn edge_metrics(&self, idx: EdgeIdx) -> DimVec<f64> {
+        self.metrics()[idx].clone()
+    }
+
+    fn is_turn_forbidden(&self, from_idx: EdgeIdx, to_idx: EdgeIdx) -> bool {
+        Graph::is_turn_forbidden(self, from_idx, to_idx)
+    }
+}
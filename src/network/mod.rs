@@ -1,7 +1,11 @@
+pub mod composite;
 mod graph;
 pub use graph::{
     building::{EdgeBuilder, GraphBuilder, NodeBuilder, ProtoEdge, ProtoNode, ProtoShortcut},
-    EdgeAccessor, EdgeIdx, Graph, HalfEdge, MetricAccessor, MetricIdx, Node, NodeAccessor, NodeIdx,
+    BboxEdges, ComponentId, Components, EdgeAccessor, EdgeGrid, EdgeIdx, EdgeIdxSlice, EdgeIdxVec,
+    Graph, GraphView, HalfEdge, IdIndex, IndexMapping, LeavingEdge, LeavingEdges, MetricAccessor,
+    MetricIdx, Node, NodeAccessor, NodeIdx, NodeIndex, OffsetVec, Provenance, ProvenanceColumn,
+    TurnRestriction, TurnRestrictionKind,
 };
 
 mod routes;
@@ -50,6 +54,8 @@ pub use routes::RoutePair;
 /// The speed-limit is used in `km/h`, which is the provided unit by osm.
 /// > Default: See table above
 ///
+/// If the parsing-config enables `infer-link-speeds`, `*_link`-ways get a fraction of their parent's default instead (e.g. `0.7 * 130 = 91` for motorway-links).
+///
 /// ## Distance
 ///
 /// The distance is used in `km`, which is the provided unit by osm.
@@ -0,0 +1,673 @@
+use super::paths::Path;
+use crate::{
+    configs::routing::Config,
+    defaults::{self, accuracy, capacity::DimVec},
+    helpers::{self, err},
+    io,
+    network::{EdgeIdx, Graph, NodeIdx},
+};
+use log::{info, warn};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    ffi::OsString,
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::{self, PathBuf},
+};
+
+/// Distances from and to a few landmarks for every node, which give lower bounds of the
+/// distance between any two nodes via the triangle inequality (ALT: A*, landmarks and triangle
+/// inequality).
+///
+/// For a landmark `L`, the distance `d(v, t)` is at least `d(L, t) - d(L, v)` and
+/// `d(v, L) - d(t, L)`, and the bound of all landmarks is their maximum (see `lower_bound(...)`).
+/// Distances are weighted by the routing-config's alphas, hence the bounds are only valid for
+/// these alphas.
+///
+/// Landmarks are chosen by the farthest-heuristic: every next landmark is the node, which is
+/// farthest from all chosen landmarks, where ties are broken by the smaller node-idx.
+/// The preprocessed data can be stored next to the graph's map-file (see `file_of(...)`), since
+/// building it needs two complete searches per landmark.
+#[derive(Clone, Debug)]
+pub struct LandmarkPreprocessor {
+    alphas: DimVec<f64>,
+    landmarks: Vec<NodeIdx>,
+    /// `fwd_dist[i][v]` is the distance from the i-th landmark to the node `v`.
+    fwd_dist: Vec<Vec<f64>>,
+    /// `bwd_dist[i][v]` is the distance from the node `v` to the i-th landmark.
+    bwd_dist: Vec<Vec<f64>>,
+}
+
+impl LandmarkPreprocessor {
+    pub fn build(
+        graph: &Graph,
+        routing_cfg: &Config,
+        num_landmarks: usize,
+    ) -> LandmarkPreprocessor {
+        match LandmarkPreprocessor::try_build(graph, routing_cfg, num_landmarks) {
+            Ok(preprocessor) => preprocessor,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Chooses at most `num_landmarks` landmarks (at most one per node) and computes their
+    /// distances from and to every node, weighted by the routing-config's alphas, which have to
+    /// be non-negative.
+    pub fn try_build(
+        graph: &Graph,
+        routing_cfg: &Config,
+        num_landmarks: usize,
+    ) -> err::Result<LandmarkPreprocessor> {
+        if routing_cfg.alphas.iter().any(|alpha| alpha < &0.0) {
+            return Err(err::Msg::from(format!(
+                "Landmarks need non-negative alphas, but the alphas are {:?}.",
+                routing_cfg.alphas
+            )));
+        }
+
+        info!("START Choose {} landmarks for ALT.", num_landmarks);
+        let node_count = graph.nodes().count();
+        let mut preprocessor = LandmarkPreprocessor {
+            alphas: routing_cfg.alphas.clone(),
+            landmarks: Vec::new(),
+            fwd_dist: Vec::new(),
+            bwd_dist: Vec::new(),
+        };
+        // min distance of every node to the chosen landmarks
+        let mut min_dist = vec![std::f64::INFINITY; node_count];
+        while preprocessor.landmarks.len() < num_landmarks.min(node_count) {
+            let landmark = if preprocessor.landmarks.is_empty() {
+                // the node farthest from an arbitrary node
+                let dist = distances(graph, &routing_cfg.alphas, NodeIdx(0), Direction::Fwd);
+                farthest(&dist, &[])
+            } else {
+                farthest(&min_dist, &preprocessor.landmarks)
+            };
+            let landmark = match landmark {
+                Some(landmark) => landmark,
+                None => break,
+            };
+
+            let fwd_dist = distances(graph, &routing_cfg.alphas, landmark, Direction::Fwd);
+            let bwd_dist = distances(graph, &routing_cfg.alphas, landmark, Direction::Bwd);
+            for (node_idx, min_dist) in min_dist.iter_mut().enumerate() {
+                *min_dist = min_dist.min(fwd_dist[node_idx].min(bwd_dist[node_idx]));
+            }
+            preprocessor.landmarks.push(landmark);
+            preprocessor.fwd_dist.push(fwd_dist);
+            preprocessor.bwd_dist.push(bwd_dist);
+        }
+        info!("FINISHED");
+
+        Ok(preprocessor)
+    }
+
+    pub fn alphas(&self) -> &DimVec<f64> {
+        &self.alphas
+    }
+
+    pub fn landmarks(&self) -> &[NodeIdx] {
+        &self.landmarks
+    }
+
+    /// Returns the distance from the i-th landmark to the node.
+    pub fn fwd_dist(&self, landmark: usize, node_idx: NodeIdx) -> f64 {
+        self.fwd_dist[landmark][*node_idx]
+    }
+
+    /// Returns the distance from the node to the i-th landmark.
+    pub fn bwd_dist(&self, landmark: usize, node_idx: NodeIdx) -> f64 {
+        self.bwd_dist[landmark][*node_idx]
+    }
+
+    /// Returns a lower bound of the distance from the node to the dst-node.
+    ///
+    /// Landmarks, which don't reach both nodes (or aren't reached by both nodes), are skipped,
+    /// so the bound is 0 without landmarks.
+    pub fn lower_bound(&self, node_idx: NodeIdx, dst_idx: NodeIdx) -> f64 {
+        let mut bound: f64 = 0.0;
+        for landmark in 0..self.landmarks.len() {
+            let (fwd_v, fwd_t) = (
+                self.fwd_dist[landmark][*node_idx],
+                self.fwd_dist[landmark][*dst_idx],
+            );
+            if fwd_v.is_finite() && fwd_t.is_finite() {
+                bound = bound.max(fwd_t - fwd_v);
+            }
+            let (bwd_v, bwd_t) = (
+                self.bwd_dist[landmark][*node_idx],
+                self.bwd_dist[landmark][*dst_idx],
+            );
+            if bwd_v.is_finite() && bwd_t.is_finite() {
+                bound = bound.max(bwd_v - bwd_t);
+            }
+        }
+        bound
+    }
+
+    /// Returns the file next to the map-file, where the landmarks of this graph should be stored,
+    /// e.g. `graph.fmi.landmarks` for `graph.fmi`.
+    pub fn file_of<P: AsRef<path::Path> + ?Sized>(map_file: &P) -> PathBuf {
+        let map_file = map_file.as_ref();
+        let mut file_name = map_file
+            .file_name()
+            .map(OsString::from)
+            .unwrap_or_else(OsString::new);
+        file_name.push(".");
+        file_name.push(defaults::routing::alt::FILE_SUFFIX);
+        map_file.with_file_name(file_name)
+    }
+
+    /// Writes the landmarks into a new file, where nodes are written by their ids.
+    ///
+    /// ```text
+    /// # alphas
+    /// 1 0.5
+    /// # landmark-count
+    /// 2
+    /// # node-count
+    /// 3
+    /// # landmark-ids
+    /// 42 7
+    /// # [NodeId, fwd-dists per landmark, bwd-dists per landmark]
+    /// 7 3 0 3 0
+    /// 42 0 3 0 3
+    /// 51 1 inf 2 5
+    /// ```
+    pub fn write<P: AsRef<path::Path> + ?Sized>(&self, graph: &Graph, file: &P) -> err::Feedback {
+        let file = file.as_ref();
+        info!("START Write landmarks into {}", file.display());
+        let nodes = graph.nodes();
+        if self.fwd_dist.iter().any(|dist| dist.len() != nodes.count()) {
+            return Err(err::Msg::from(format!(
+                "The landmarks don't fit the graph's {} nodes.",
+                nodes.count()
+            )));
+        }
+
+        let mut writer = io::files::AtomicFile::create(file)?;
+        let floats = |values: &mut dyn Iterator<Item = f64>| -> String {
+            values
+                .map(|value| io::fmt::float(value, None))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        writeln!(writer, "# alphas")?;
+        writeln!(writer, "{}", floats(&mut self.alphas.iter().cloned()))?;
+        writeln!(writer, "# landmark-count")?;
+        writeln!(writer, "{}", self.landmarks.len())?;
+        writeln!(writer, "# node-count")?;
+        writeln!(writer, "{}", nodes.count())?;
+        writeln!(writer, "# landmark-ids")?;
+        writeln!(
+            writer,
+            "{}",
+            self.landmarks
+                .iter()
+                .map(|&landmark| nodes.id(landmark).to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        )?;
+        writeln!(
+            writer,
+            "# [NodeId, fwd-dists per landmark, bwd-dists per landmark]"
+        )?;
+        for node_idx in &nodes {
+            let mut dists = self
+                .fwd_dist
+                .iter()
+                .chain(self.bwd_dist.iter())
+                .map(|dist| dist[*node_idx]);
+            if self.landmarks.is_empty() {
+                writeln!(writer, "{}", nodes.id(node_idx))?;
+            } else {
+                writeln!(writer, "{} {}", nodes.id(node_idx), floats(&mut dists))?;
+            }
+        }
+        writer.commit()?;
+        info!("FINISHED");
+        Ok(())
+    }
+
+    pub fn read<P: AsRef<path::Path> + ?Sized>(graph: &Graph, file: &P) -> LandmarkPreprocessor {
+        match LandmarkPreprocessor::try_read(graph, file) {
+            Ok(preprocessor) => preprocessor,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Reads the landmarks written by `write(...)`, which have to fit the graph's nodes (by their
+    /// ids and their order).
+    pub fn try_read<P: AsRef<path::Path> + ?Sized>(
+        graph: &Graph,
+        file: &P,
+    ) -> err::Result<LandmarkPreprocessor> {
+        let file = file.as_ref();
+        info!("START Read landmarks from {}", file.display());
+        let reader = match OpenOptions::new().read(true).open(file) {
+            Ok(reader) => BufReader::new(reader),
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", file.display(), e).into())
+            }
+        };
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .map(|(line_idx, line)| (line_idx + 1, line))
+            .filter(|(_, line)| match line {
+                Ok(line) => !line.trim().is_empty() && !line.trim_start().starts_with('#'),
+                Err(_) => true,
+            });
+        let mut next_line = || -> err::Result<(usize, String)> {
+            match lines.next() {
+                Some((line_nr, Ok(line))) => Ok((line_nr, line)),
+                Some((line_nr, Err(e))) => Err(format!(
+                    "Couldn't read line {} of {} due to error: {}",
+                    line_nr,
+                    file.display(),
+                    e
+                )
+                .into()),
+                None => {
+                    Err(format!("The landmarks-file {} ends too early.", file.display()).into())
+                }
+            }
+        };
+        let parse_msg = |line_nr: usize, value: &str| -> err::Msg {
+            format!(
+                "Couldn't parse {} in line {} of {}.",
+                value,
+                line_nr,
+                file.display()
+            )
+            .into()
+        };
+
+        let (line_nr, line) = next_line()?;
+        let alphas = line
+            .split_whitespace()
+            .map(|value| value.parse::<f64>().map_err(|_| parse_msg(line_nr, value)))
+            .collect::<err::Result<DimVec<f64>>>()?;
+        let (line_nr, line) = next_line()?;
+        let landmark_count = line
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| parse_msg(line_nr, &line))?;
+        let (line_nr, line) = next_line()?;
+        let node_count = line
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| parse_msg(line_nr, &line))?;
+        let nodes = graph.nodes();
+        if node_count != nodes.count() {
+            return Err(format!(
+                "The landmarks of {} are computed for {} nodes, but the graph has {} nodes.",
+                file.display(),
+                node_count,
+                nodes.count()
+            )
+            .into());
+        }
+
+        let landmarks = if landmark_count > 0 {
+            let (line_nr, line) = next_line()?;
+            line.split_whitespace()
+                .map(|value| {
+                    let id = value
+                        .parse::<i64>()
+                        .map_err(|_| parse_msg(line_nr, value))?;
+                    nodes.idx_from(id).map_err(|id| {
+                        err::Msg::from(format!(
+                            "The landmark {} of {} isn't part of the graph.",
+                            id,
+                            file.display()
+                        ))
+                    })
+                })
+                .collect::<err::Result<Vec<NodeIdx>>>()?
+        } else {
+            Vec::new()
+        };
+        if landmarks.len() != landmark_count {
+            return Err(format!(
+                "{} declares {} landmarks, but lists {}.",
+                file.display(),
+                landmark_count,
+                landmarks.len()
+            )
+            .into());
+        }
+
+        let mut fwd_dist = vec![vec![0.0; node_count]; landmark_count];
+        let mut bwd_dist = vec![vec![0.0; node_count]; landmark_count];
+        for node_idx in &nodes {
+            let (line_nr, line) = next_line()?;
+            let mut values = line.split_whitespace();
+            let id = values.next().unwrap_or("");
+            if id.parse::<i64>().ok() != Some(nodes.id(node_idx)) {
+                return Err(format!(
+                    "Line {} of {} should start with the node-id {}, but starts with {}.",
+                    line_nr,
+                    file.display(),
+                    nodes.id(node_idx),
+                    id
+                )
+                .into());
+            }
+            let dists = values
+                .map(|value| value.parse::<f64>().map_err(|_| parse_msg(line_nr, value)))
+                .collect::<err::Result<Vec<f64>>>()?;
+            if dists.len() != 2 * landmark_count {
+                return Err(format!(
+                    "Line {} of {} should have {} distances, but has {}.",
+                    line_nr,
+                    file.display(),
+                    2 * landmark_count,
+                    dists.len()
+                )
+                .into());
+            }
+            for landmark in 0..landmark_count {
+                fwd_dist[landmark][*node_idx] = dists[landmark];
+                bwd_dist[landmark][*node_idx] = dists[landmark_count + landmark];
+            }
+        }
+        info!("FINISHED");
+
+        Ok(LandmarkPreprocessor {
+            alphas,
+            landmarks,
+            fwd_dist,
+            bwd_dist,
+        })
+    }
+}
+
+/// A* using the landmarks' lower bounds (see `LandmarkPreprocessor`) as potentials, which
+/// settles fewer nodes than Dijkstra, the tighter the bounds are.
+///
+/// The routing-config's alphas have to equal the landmarks' alphas, while its routing-algorithm
+/// is ignored.
+/// Like `Dijkstra`, an edge is not relaxed, if a `no_*`-turn-restriction forbids turning into it
+/// (see `Graph::is_turn_forbidden(...)`).
+/// Since nodes are labelled instead of edges, detours through a restricted via-node are missed
+/// like in `Dijkstra::compute_best_path(...)`, and the first skipped turn is logged as warning.
+/// The search is unidirectional, hence found paths on ch-graphs could contain shortcuts.
+///
+/// Routing-data is reused for every query and reset only for the nodes, which the previous
+/// query has touched.
+pub struct AltRouter {
+    preprocessor: LandmarkPreprocessor,
+    queue: BinaryHeap<Reverse<CostNode>>,
+    costs: Vec<f64>,
+    predecessors: Vec<Option<EdgeIdx>>,
+    touched: Vec<NodeIdx>,
+    settled_count: usize,
+    has_warned_about_turns: bool,
+}
+
+impl AltRouter {
+    pub fn new(preprocessor: LandmarkPreprocessor) -> AltRouter {
+        AltRouter {
+            preprocessor,
+            queue: BinaryHeap::new(),
+            costs: Vec::new(),
+            predecessors: Vec::new(),
+            touched: Vec::new(),
+            settled_count: 0,
+            has_warned_about_turns: false,
+        }
+    }
+
+    pub fn preprocessor(&self) -> &LandmarkPreprocessor {
+        &self.preprocessor
+    }
+
+    /// Returns the number of dequeued nodes of the last query, which aren't outdated.
+    pub fn settled_count(&self) -> usize {
+        self.settled_count
+    }
+
+    /// None means no path exists, whereas an empty path is a path from a node to itself.
+    pub fn compute_best_path(
+        &mut self,
+        src_idx: NodeIdx,
+        dst_idx: NodeIdx,
+        graph: &Graph,
+        routing_cfg: &Config,
+    ) -> Option<Path> {
+        match self.try_compute_best_path(src_idx, dst_idx, graph, routing_cfg) {
+            Ok(best_path) => best_path,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    pub fn try_compute_best_path(
+        &mut self,
+        src_idx: NodeIdx,
+        dst_idx: NodeIdx,
+        graph: &Graph,
+        routing_cfg: &Config,
+    ) -> err::Result<Option<Path>> {
+        let is_matching = routing_cfg.alphas.len() == self.preprocessor.alphas.len()
+            && routing_cfg
+                .alphas
+                .iter()
+                .zip(self.preprocessor.alphas.iter())
+                .all(|(a, b)| (a - b).abs() <= accuracy::F64_ABS);
+        if !is_matching {
+            return Err(err::Msg::from(format!(
+                "The landmarks are computed for the alphas {:?}, but the query uses {:?}.",
+                self.preprocessor.alphas, routing_cfg.alphas
+            )));
+        }
+        let node_count = graph.nodes().count();
+        let has_other_node_count = self
+            .preprocessor
+            .fwd_dist
+            .iter()
+            .chain(self.preprocessor.bwd_dist.iter())
+            .any(|dist| dist.len() != node_count);
+        if has_other_node_count {
+            return Err(err::Msg::from(format!(
+                "The landmarks are computed for another graph, since the graph has {} nodes.",
+                node_count
+            )));
+        }
+        self.init_query(node_count);
+
+        self.set_cost(src_idx, 0.0, None);
+        self.queue.push(Reverse(CostNode {
+            idx: src_idx,
+            cost: 0.0,
+            estimation: self.preprocessor.lower_bound(src_idx, dst_idx),
+        }));
+
+        let fwd_edges = graph.fwd_edges();
+        let metrics = graph.metrics();
+        let mut is_found = false;
+        while let Some(Reverse(current)) = self.queue.pop() {
+            // outdated by a cheaper path, since nodes are enqueued again
+            if current.cost > self.costs[*current.idx] {
+                continue;
+            }
+            self.settled_count += 1;
+
+            // lower bounds are admissible, hence the dst's first dequeue is the best one
+            if current.idx == dst_idx {
+                is_found = true;
+                break;
+            }
+
+            let incoming_idx = self.predecessors[*current.idx];
+            for leaving_edge in fwd_edges.starting_from(current.idx) {
+                if let Some(incoming_idx) = incoming_idx {
+                    if graph.is_turn_forbidden(incoming_idx, leaving_edge.idx()) {
+                        if !self.has_warned_about_turns {
+                            self.has_warned_about_turns = true;
+                            warn!(
+                                "The ALT-router has skipped a forbidden turn, \
+                                 but labels nodes instead of edges, \
+                                 hence detours through a restricted via-node are missed. \
+                                 This warning is logged only once."
+                            );
+                        }
+                        continue;
+                    }
+                }
+
+                let increment =
+                    helpers::dot_product(&routing_cfg.alphas, &metrics[leaving_edge.idx()]);
+                let new_cost = current.cost + increment.max(0.0);
+                let dst = leaving_edge.dst_idx();
+                if new_cost < self.costs[*dst] {
+                    self.set_cost(dst, new_cost, Some(leaving_edge.idx()));
+                    self.queue.push(Reverse(CostNode {
+                        idx: dst,
+                        cost: new_cost,
+                        estimation: new_cost + self.preprocessor.lower_bound(dst, dst_idx),
+                    }));
+                }
+            }
+        }
+
+        if !is_found {
+            return Ok(None);
+        }
+
+        let bwd_edges = graph.bwd_edges();
+        let mut edges = Vec::new();
+        let mut cur_idx = dst_idx;
+        while let Some(incoming_idx) = self.predecessors[*cur_idx] {
+            edges.push(incoming_idx);
+            cur_idx = bwd_edges.dst_idx(incoming_idx);
+        }
+        edges.reverse();
+
+        let nodes = graph.nodes();
+        Ok(Some(Path::new(
+            src_idx,
+            nodes.id(src_idx),
+            dst_idx,
+            nodes.id(dst_idx),
+            edges,
+        )))
+    }
+
+    /// Resets the routing-data of the nodes, which the previous query has touched.
+    fn init_query(&mut self, node_count: usize) {
+        if self.costs.len() != node_count {
+            self.costs = vec![std::f64::INFINITY; node_count];
+            self.predecessors = vec![None; node_count];
+            self.touched.clear();
+        }
+        for idx in self.touched.drain(..) {
+            self.costs[*idx] = std::f64::INFINITY;
+            self.predecessors[*idx] = None;
+        }
+        self.queue.clear();
+        self.settled_count = 0;
+    }
+
+    fn set_cost(&mut self, idx: NodeIdx, cost: f64, predecessor: Option<EdgeIdx>) {
+        if self.costs[*idx] == std::f64::INFINITY {
+            self.touched.push(idx);
+        }
+        self.costs[*idx] = cost;
+        self.predecessors[*idx] = predecessor;
+    }
+}
+
+/// A node in the queue, ordered by its estimated total costs (and its idx for equal estimations).
+#[derive(Clone, Copy, Debug)]
+struct CostNode {
+    idx: NodeIdx,
+    cost: f64,
+    estimation: f64,
+}
+
+impl Ord for CostNode {
+    fn cmp(&self, other: &CostNode) -> Ordering {
+        self.estimation
+            .partial_cmp(&other.estimation)
+            .unwrap_or(Ordering::Equal)
+            .then(self.idx.cmp(&other.idx))
+    }
+}
+
+impl PartialOrd for CostNode {
+    fn partial_cmp(&self, other: &CostNode) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Eq for CostNode {}
+
+impl PartialEq for CostNode {
+    fn eq(&self, other: &CostNode) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Fwd,
+    Bwd,
+}
+
+/// Returns the distances of all nodes from (fwd) or to (bwd) the given node, where unreachable
+/// nodes have infinite distances.
+fn distances(graph: &Graph, alphas: &[f64], src_idx: NodeIdx, direction: Direction) -> Vec<f64> {
+    let xwd_edges = match direction {
+        Direction::Fwd => graph.fwd_edges(),
+        Direction::Bwd => graph.bwd_edges(),
+    };
+    let metrics = graph.metrics();
+
+    let mut dist = vec![std::f64::INFINITY; graph.nodes().count()];
+    let mut queue = BinaryHeap::new();
+    dist[*src_idx] = 0.0;
+    queue.push(Reverse(CostNode {
+        idx: src_idx,
+        cost: 0.0,
+        estimation: 0.0,
+    }));
+    while let Some(Reverse(current)) = queue.pop() {
+        if current.cost > dist[*current.idx] {
+            continue;
+        }
+        for half_edge in xwd_edges.starting_from(current.idx) {
+            let increment = helpers::dot_product(alphas, &metrics[half_edge.idx()]);
+            let new_cost = current.cost + increment.max(0.0);
+            let dst = half_edge.dst_idx();
+            if new_cost < dist[*dst] {
+                dist[*dst] = new_cost;
+                queue.push(Reverse(CostNode {
+                    idx: dst,
+                    cost: new_cost,
+                    estimation: new_cost,
+                }));
+            }
+        }
+    }
+    dist
+}
+
+/// Returns the node with the max distance, which is not excluded, where ties are broken by the
+/// smaller node-idx.
+/// Unreachable nodes are infinitely far away, hence other components of disconnected graphs get
+/// landmarks as well.
+fn farthest(dist: &[f64], excluded: &[NodeIdx]) -> Option<NodeIdx> {
+    let mut farthest: Option<(f64, NodeIdx)> = None;
+    for (idx, &value) in dist.iter().enumerate() {
+        let idx = NodeIdx(idx);
+        if excluded.contains(&idx) {
+            continue;
+        }
+        if farthest.map_or(true, |(max, _)| value > max) {
+            farthest = Some((value, idx));
+        }
+    }
+    farthest.map(|(_, idx)| idx)
+}
@@ -0,0 +1,53 @@
+use super::{
+    dijkstra::{self, Dijkstra},
+    paths::Path,
+};
+use crate::{
+    configs::routing::Config,
+    network::{Graph, NodeIdx},
+};
+use std::{cell::RefCell, future::Future, sync::Arc};
+
+thread_local! {
+    // Blocking threads are reused by tokio, hence every thread keeps its Dijkstra and its
+    // allocated datastructures for following queries.
+    static DIJKSTRA: RefCell<Dijkstra> = RefCell::new(Dijkstra::new());
+}
+
+/// Computes the best path on one of tokio's blocking threads, so async servers (e.g. with axum)
+/// don't block their executor with routing.
+///
+/// Dijkstras are pooled per blocking thread (see `tokio::task::spawn_blocking(...)`), so
+/// consecutive queries don't allocate their datastructures again.
+/// There is no shared pool of Dijkstras, since blocking threads are reused by tokio anyways.
+///
+/// The query is spawned when the returned future is polled the first time, hence the future can
+/// be created outside of tokio's runtime, e.g. before `Runtime::spawn(...)`.
+///
+/// Like `Dijkstra::compute_best_path(...)`, this panics (in the returned future) on invalid
+/// queries, e.g. with CH-Dijkstra on a graph without ch-levels.
+pub fn route(
+    graph: Arc<Graph>,
+    routing_cfg: Arc<Config>,
+    src_idx: NodeIdx,
+    dst_idx: NodeIdx,
+) -> impl Future<Output = Option<Path>> {
+    async move {
+        let handle = tokio::task::spawn_blocking(move || {
+            DIJKSTRA.with(|dijkstra| {
+                dijkstra.borrow_mut().compute_best_path(dijkstra::Query {
+                    src_idx,
+                    dst_idx,
+                    graph: &*graph,
+                    routing_cfg: &*routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                })
+            })
+        });
+        match handle.await {
+            Ok(best_path) => best_path,
+            Err(e) => panic!("Routing on a blocking thread failed: {}", e),
+        }
+    }
+}
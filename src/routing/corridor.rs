@@ -0,0 +1,50 @@
+use super::paths::Path;
+use crate::network::{EdgeIdx, Graph};
+
+/// Returns all edges in a corridor of the given radius around the path, e.g. for studying
+/// spill-over effects of an incident on the path.
+///
+/// The corridor's nodes are all nodes within the radius (by haversine-distance) of any node of
+/// the flattened path, looked up via the graph's node-index.
+/// Since edges don't have a geometry, only the path's nodes are used as centers.
+/// An edge is part of the corridor, if both of its nodes are inside the corridor, or if it
+/// crosses the corridor's boundary, meaning one of its nodes is inside.
+/// Hence, the path's own edges are always returned, as well as all edges touching the path's
+/// nodes.
+///
+/// Shortcuts are skipped, since their sc-edges are returned instead.
+/// The edges are deduplicated and sorted by their indices.
+/// The result is empty, if the radius is negative or NaN.
+pub fn edges_near_path(path: &Path, radius_m: f64, graph: &Graph) -> Vec<EdgeIdx> {
+    if radius_m.is_nan() || radius_m < 0.0 {
+        return Vec::new();
+    }
+
+    let nodes = graph.nodes();
+    let mut is_inside = vec![false; nodes.count()];
+    // Every path-node is a center, even if it's inside the radius of a previous center already,
+    // since its own radius covers other nodes.
+    for node_idx in path.clone().flatten(graph).node_indices(graph) {
+        for near_idx in graph.nodes_within_radius_km(node_idx, radius_m / 1_000.0) {
+            is_inside[*near_idx] = true;
+        }
+    }
+
+    // leaving and entering edges of every inside node
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let mut edges = Vec::new();
+    for node_idx in nodes.iter().filter(|&idx| is_inside[*idx]) {
+        for half_edge in fwd_edges
+            .starting_from(node_idx)
+            .chain(bwd_edges.starting_from(node_idx))
+        {
+            if !half_edge.is_shortcut() {
+                edges.push(half_edge.idx());
+            }
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
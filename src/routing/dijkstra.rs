@@ -1,18 +1,130 @@
-use super::paths::Path;
+use super::{paths::Path, sp_tree::SpTree};
 use crate::{
     configs::routing::{Config, RoutingAlgo},
-    defaults::routing::IS_USING_CH_LEVEL_SPEEDUP,
-    helpers,
-    network::{EdgeIdx, Graph, NodeIdx},
+    defaults::{
+        self, accuracy,
+        capacity::DimVec,
+        routing::{IS_USING_CH_LEVEL_SPEEDUP, TIMEOUT_CHECK_INTERVAL},
+    },
+    helpers::{self, cancel::CancellationToken, err},
+    network::{EdgeIdx, Graph, GraphView, LeavingEdges, NodeIdx},
+};
+use log::warn;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::{self, Display},
+    time::{Duration, Instant},
 };
-use std::{cmp::Reverse, collections::BinaryHeap};
 
-#[derive(Copy, Clone)]
-pub struct Query<'a> {
+/// Queries are answered on a `Graph` by default, but every `GraphView` is supported,
+/// e.g. `network::composite::CompositeGraph`.
+pub struct Query<'a, G: GraphView = Graph> {
     pub src_idx: NodeIdx,
     pub dst_idx: NodeIdx,
-    pub graph: &'a Graph,
+    pub graph: &'a G,
     pub routing_cfg: &'a Config,
+    /// Optional bias per edge (indexed by fwd-edge-indices), which is added to the edge's cost
+    /// after the dot-product, e.g. a negative bonus for stabilizing routes
+    /// (see `routing::stabilizing`).
+    ///
+    /// Biased costs are clamped at 0, since Dijkstra needs non-negative costs.
+    /// Hence, for ch-graphs, a bonus should be lower than an edge's cost to keep shortcuts
+    /// consistent.
+    pub edge_bias: Option<&'a [f64]>,
+    /// Expected length of the path, which only affects allocations, but never results.
+    pub path_hint: PathHint,
+}
+
+// derived impls would need G: Clone
+impl<'a, G: GraphView> Clone for Query<'a, G> {
+    fn clone(&self) -> Query<'a, G> {
+        *self
+    }
+}
+
+impl<'a, G: GraphView> Copy for Query<'a, G> {}
+
+/// Callers knowing their queries' lengths can avoid allocations of the found path's edges, which
+/// are collected while walking the predecessors back from the meeting-node.
+///
+/// The routing-data (costs, predecessors, ...) is stored per node in any case, hence a hint
+/// doesn't change the search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathHint {
+    /// E.g. intra-city queries of the balancer, where paths start with a small capacity.
+    ExpectShort,
+    /// E.g. country-length queries of the verification, where paths are allocated for the
+    /// typical edge-count of long paths (see `path_capacity(...)`), so they rarely grow.
+    ExpectLong,
+    /// Paths grow on demand.
+    Auto,
+}
+
+impl Default for PathHint {
+    fn default() -> PathHint {
+        PathHint::Auto
+    }
+}
+
+impl PathHint {
+    /// Returns the initial capacity of a found path's edges for a graph of the given node-count.
+    pub fn path_capacity(&self, node_count: usize) -> usize {
+        match self {
+            PathHint::ExpectShort => defaults::routing::SHORT_PATH_CAPACITY.min(node_count),
+            // Long paths of road-networks have roughly sqrt(n) edges, while allocating n edges
+            // per query would waste memory on huge graphs.
+            PathHint::ExpectLong => ((node_count as f64).sqrt().ceil() as usize)
+                .max(defaults::routing::SHORT_PATH_CAPACITY)
+                .min(node_count),
+            PathHint::Auto => 0,
+        }
+    }
+}
+
+/// Summary of a query, which has taken at least as long as a given threshold.
+#[derive(Clone, Debug)]
+pub struct SlowQuery {
+    pub src_id: i64,
+    pub dst_id: i64,
+    pub elapsed: Duration,
+    /// Settled nodes of all (forward and backward) searches
+    pub settled_count: usize,
+    pub alphas: DimVec<f64>,
+    pub is_ch_dijkstra: bool,
+}
+
+impl Display for SlowQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ src-id: {}, dst-id: {}, elapsed: {} µs, settled-nodes: {}, alphas: {:?}, ch: {} }}",
+            self.src_id,
+            self.dst_id,
+            self.elapsed.as_micros(),
+            self.settled_count,
+            self.alphas,
+            self.is_ch_dijkstra
+        )
+    }
+}
+
+/// Result of an interruptible query (see `Dijkstra::try_compute_best_path_within(...)` and
+/// `Dijkstra::try_compute_best_path_cancellable(...)`).
+#[derive(Debug)]
+pub enum Outcome {
+    /// None means no path exists, like in `Dijkstra::compute_best_path(...)`.
+    Finished(Option<Path>),
+    /// The query has been interrupted, so it's unknown whether a path exists.
+    TimedOut,
+    /// The query has been interrupted by its cancellation-token.
+    Cancelled,
+}
+
+/// Conditions interrupting a query, which are checked only if given.
+struct Interruption<'a> {
+    deadline: Option<Instant>,
+    token: Option<&'a CancellationToken>,
 }
 
 /// A bidirectional implementation of Dijkstra's algorithm.
@@ -20,31 +132,80 @@ pub struct Query<'a> {
 ///
 /// This implementation is correct for contracted and non-contracted graphs.
 /// However, the performance highly depends on a flag in the config, which has to be provided when computing the best path.
+///
+/// Queries taking longer than an optional threshold are logged as warning (see `SlowQuery`).
+///
+/// Routing-data (costs, predecessors and visited-flags) is reset lazily.
+/// Every node-slot stores the generation of the query, which has touched it last, and slots of
+/// older generations are treated as untouched (infinite costs, no predecessor, not visited).
+/// Hence, initializing a query doesn't depend on the graph's size, but only bumps the generation.
 pub struct Dijkstra {
     // general
     is_ch_dijkstra: bool,
+    slow_query_threshold: Option<Duration>,
+    last_slow_query: Option<SlowQuery>,
+    settled_count: usize,
+    has_warned_about_turns: bool,
     // data-structures for a query
     queue: BinaryHeap<Reverse<CostNode>>,
     costs: [Vec<f64>; 2],
     predecessors: [Vec<Option<EdgeIdx>>; 2],
     is_visited: [Vec<bool>; 2],
     has_found_best_meeting_node: [bool; 2],
-    touched: [Vec<usize>; 2],
+    /// The current query's generation, which is never 0, since 0 marks never touched slots.
+    generation: u32,
+    /// Generations of the queries, which have touched the node-slots last
+    generations: [Vec<u32>; 2],
 }
 
 impl Dijkstra {
     pub fn new() -> Dijkstra {
         Dijkstra {
             is_ch_dijkstra: false,
+            slow_query_threshold: None,
+            last_slow_query: None,
+            settled_count: 0,
+            has_warned_about_turns: false,
             queue: BinaryHeap::new(),
             costs: [vec![], vec![]],
             predecessors: [vec![], vec![]],
             is_visited: [vec![], vec![]],
             has_found_best_meeting_node: [false, false],
-            touched: [vec![], vec![]],
+            generation: 0,
+            generations: [vec![], vec![]],
         }
     }
 
+    /// Like `new()`, but counting query-generations from the given one on, which lets tests
+    /// reach the generation's wraparound without running `u32::MAX` queries.
+    #[doc(hidden)]
+    pub fn with_generation(generation: u32) -> Dijkstra {
+        Dijkstra {
+            generation,
+            ..Dijkstra::new()
+        }
+    }
+
+    /// Queries taking at least this duration are logged as warning.
+    /// `None` (default) disables the measurement.
+    pub fn set_slow_query_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_query_threshold = threshold;
+    }
+
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.slow_query_threshold
+    }
+
+    /// Returns the last query's summary, if it has been slow.
+    pub fn last_slow_query(&self) -> Option<&SlowQuery> {
+        self.last_slow_query.as_ref()
+    }
+
+    /// Returns the number of settled nodes of the last query (forward and backward).
+    pub fn settled_count(&self) -> usize {
+        self.settled_count
+    }
+
     fn fwd_idx(&self) -> usize {
         0
     }
@@ -68,38 +229,89 @@ impl Dijkstra {
     }
 
     /// Resizes existing datastructures storing routing-data, like costs, saving re-allocations.
+    ///
+    /// Old routing-data is not overwritten, but outdated by bumping the generation.
+    /// Only if the generation would wrap around (every `u32::MAX` queries), all slots are reset.
     fn init_query(&mut self, new_len: usize) {
+        if self.generation == std::u32::MAX {
+            for &dir in &[Direction::FWD, Direction::BWD] {
+                let dir = self.dir_idx(dir);
+                self.generations[dir].iter_mut().for_each(|g| *g = 0);
+            }
+            self.generation = 0;
+        }
+        self.generation += 1;
+
         // fwd and bwd
         for &dir in &[Direction::FWD, Direction::BWD] {
             let dir = self.dir_idx(dir);
-            if self.costs.len() != new_len {
+            if self.generations[dir].len() != new_len {
+                // New slots are never touched, and old slots are outdated.
+                self.generations[dir].resize(new_len, 0);
                 self.costs[dir].resize(new_len, std::f64::INFINITY);
                 self.predecessors[dir].resize(new_len, None);
-            }
-
-            for i in self.touched[dir].drain(..) {
-                self.costs[dir][i] = std::f64::INFINITY;
-                self.predecessors[dir][i] = None;
-            }
-
-            // assert!(self.costs[dir].iter().all(|&c| c == f64::INFINITY));
-            // assert!(self.predecessors[dir].iter().all(|&p| p == None));
-
-            if !self.is_ch_dijkstra {
                 self.is_visited[dir].resize(new_len, false);
-                self.is_visited[dir].iter_mut().for_each(|v| *v = false);
             }
 
             self.has_found_best_meeting_node[dir] = false;
         }
 
         self.queue.clear();
+        self.settled_count = 0;
+    }
+
+    fn is_touched(&self, dir: usize, idx: NodeIdx) -> bool {
+        self.generations[dir][*idx] == self.generation
+    }
+
+    /// Resets the slot's routing-data, if it has been touched by an older query.
+    fn touch(&mut self, dir: usize, idx: NodeIdx) {
+        if !self.is_touched(dir, idx) {
+            self.generations[dir][*idx] = self.generation;
+            self.costs[dir][*idx] = std::f64::INFINITY;
+            self.predecessors[dir][*idx] = None;
+            self.is_visited[dir][*idx] = false;
+        }
+    }
+
+    fn cost(&self, dir: usize, idx: NodeIdx) -> f64 {
+        if self.is_touched(dir, idx) {
+            self.costs[dir][*idx]
+        } else {
+            std::f64::INFINITY
+        }
+    }
+
+    fn set_cost(&mut self, dir: usize, idx: NodeIdx, cost: f64) {
+        self.touch(dir, idx);
+        self.costs[dir][*idx] = cost;
+    }
+
+    fn predecessor(&self, dir: usize, idx: NodeIdx) -> Option<EdgeIdx> {
+        if self.is_touched(dir, idx) {
+            self.predecessors[dir][*idx]
+        } else {
+            None
+        }
+    }
+
+    fn set_predecessor(&mut self, dir: usize, idx: NodeIdx, edge_idx: EdgeIdx) {
+        self.touch(dir, idx);
+        self.predecessors[dir][*idx] = Some(edge_idx);
+    }
+
+    fn is_visited(&self, dir: usize, idx: NodeIdx) -> bool {
+        self.is_touched(dir, idx) && self.is_visited[dir][*idx]
     }
 
     fn visit(&mut self, costnode: &CostNode) {
+        self.settled_count += 1;
+
         // not needed for ch-dijkstra, because it has to dig through all candidates by cost
         if !self.is_ch_dijkstra {
-            self.is_visited[self.dir_idx(costnode.direction)][*costnode.idx] = true
+            let dir = self.dir_idx(costnode.direction);
+            self.touch(dir, costnode.idx);
+            self.is_visited[dir][*costnode.idx] = true
         }
     }
 
@@ -108,14 +320,14 @@ impl Dijkstra {
         // Costs are updated when costnodes are enqueued, but costnodes have to be dequeued
         // before they can be considered as visited (for bidir Dijkstra).
         if self.is_ch_dijkstra {
-            self.costs[self.opp_dir_idx(costnode.direction)][*costnode.idx] != std::f64::INFINITY
+            self.cost(self.opp_dir_idx(costnode.direction), costnode.idx) != std::f64::INFINITY
         } else {
             // The CostNode has already been dequeued, which is the reason for this assertion.
             debug_assert!(
-                self.is_visited[self.dir_idx(costnode.direction)][*costnode.idx],
+                self.is_visited(self.dir_idx(costnode.direction), costnode.idx),
                 "CostNode should already be visited."
             );
-            self.is_visited[self.opp_dir_idx(costnode.direction)][*costnode.idx]
+            self.is_visited(self.opp_dir_idx(costnode.direction), costnode.idx)
         }
     }
 
@@ -129,19 +341,428 @@ impl Dijkstra {
     /// node-idx (and for this query-direction).
     fn has_costnode_improved(&self, costnode: &CostNode) -> bool {
         // <= instead of < needed for initial costs
-        costnode.cost <= self.costs[self.dir_idx(costnode.direction)][*costnode.idx]
+        costnode.cost <= self.cost(self.dir_idx(costnode.direction), costnode.idx)
+    }
+
+    /// Returns true, if the fwd-path can't turn into the bwd-path at the given meeting-node due
+    /// to a turn-restriction (see `Graph::is_turn_forbidden(...)`).
+    fn is_meeting_turn_forbidden<G: GraphView>(&self, graph: &G, idx: NodeIdx) -> bool {
+        match (
+            self.predecessor(self.fwd_idx(), idx),
+            self.predecessor(self.bwd_idx(), idx),
+        ) {
+            (Some(incoming_idx), Some(leaving_idx)) => {
+                graph.is_turn_forbidden(incoming_idx, leaving_idx)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true, if a `no_*`-turn-restriction forbids turning from the first into the second
+    /// edge (see `Graph::is_turn_forbidden(...)`).
+    /// The first skipped turn is logged as warning, since detours through its via-node could be
+    /// missed (see `compute_best_path(...)`).
+    fn is_skipping_turn<G: GraphView>(
+        &mut self,
+        graph: &G,
+        from_idx: EdgeIdx,
+        to_idx: EdgeIdx,
+    ) -> bool {
+        if !graph.is_turn_forbidden(from_idx, to_idx) {
+            return false;
+        }
+
+        if !self.has_warned_about_turns {
+            self.has_warned_about_turns = true;
+            warn!(
+                "Dijkstra has skipped a forbidden turn from edge {} into edge {}, \
+                 but labels nodes instead of edges. \
+                 Hence paths with a detour through a restricted via-node \
+                 (e.g. turning around behind it) are missed, \
+                 so found paths could be longer than necessary or not found at all. \
+                 This warning is logged only once.",
+                *from_idx, *to_idx
+            );
+        }
+        true
     }
 
     /// Returns the cost of a path, so cost(src->v) + cost(v->dst)
     fn total_cost(&self, costnode: &CostNode) -> f64 {
-        self.costs[self.fwd_idx()][*costnode.idx] + self.costs[self.bwd_idx()][*costnode.idx]
+        self.cost(self.fwd_idx(), costnode.idx) + self.cost(self.bwd_idx(), costnode.idx)
     }
 
     /// None means no path exists, whereas an empty path is a path from a node to itself.
     ///
     /// ATTENTION!
-    /// If any alpha-value in the routing-config is negative, or any metric in the graph is negative, this method won't terminate.
-    pub fn compute_best_path(&mut self, query: Query) -> Option<Path> {
+    /// If any alpha-value in the routing-config is negative, no path is found.
+    /// If any used metric in the graph is negative, the found path could be wrong.
+    /// Debug-builds panic when relaxing such an edge.
+    /// Release-builds return an error only if the routing-config is checking for non-negative costs.
+    /// Since this method panics on errors, it panics in this case as well, hence graphs with
+    /// possibly negative metrics should be routed by `try_compute_best_path(...)`.
+    ///
+    /// An edge is not relaxed, if a `no_*`-turn-restriction forbids turning from the edge, which
+    /// the current node has been reached by, into it (see `Graph::is_turn_forbidden(...)`).
+    /// Since nodes are settled only once, a path turning at a restricted node could be missed,
+    /// if the node's best predecessor is restricted, but another one isn't, e.g. a path turning
+    /// around behind the restricted node to pass it again.
+    /// In the worst case, no path is found, though one exists.
+    /// The first skipped turn is logged as warning.
+    pub fn compute_best_path<G: GraphView>(&mut self, query: Query<G>) -> Option<Path> {
+        match self.try_compute_best_path(query) {
+            Ok(best_path) => best_path,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    pub fn try_compute_best_path<G: GraphView>(
+        &mut self,
+        query: Query<G>,
+    ) -> err::Result<Option<Path>> {
+        match self.try_compute(query, None)? {
+            Outcome::Finished(best_path) => Ok(best_path),
+            Outcome::TimedOut | Outcome::Cancelled => Err(err::Msg::from(
+                "Dijkstra has been interrupted, though no interruption has been given.",
+            )),
+        }
+    }
+
+    pub fn compute_best_path_within<G: GraphView>(
+        &mut self,
+        query: Query<G>,
+        timeout: Duration,
+    ) -> Outcome {
+        match self.try_compute_best_path_within(query, timeout) {
+            Ok(outcome) => outcome,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Like `try_compute_best_path(...)`, but the query is interrupted after the given timeout,
+    /// which is distinguished from not finding a path.
+    /// An interrupted query leaves this Dijkstra ready for the next query.
+    pub fn try_compute_best_path_within<G: GraphView>(
+        &mut self,
+        query: Query<G>,
+        timeout: Duration,
+    ) -> err::Result<Outcome> {
+        let deadline = Instant::now() + timeout;
+        self.try_compute(
+            query,
+            Some(Interruption {
+                deadline: Some(deadline),
+                token: None,
+            }),
+        )
+    }
+
+    pub fn compute_best_path_cancellable<G: GraphView>(
+        &mut self,
+        query: Query<G>,
+        token: Option<&CancellationToken>,
+    ) -> Outcome {
+        match self.try_compute_best_path_cancellable(query, token) {
+            Ok(outcome) => outcome,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Like `try_compute_best_path(...)`, but the query is interrupted as soon as the given token
+    /// is cancelled, which is checked for every dequeued node.
+    /// An interrupted query leaves this Dijkstra ready for the next query.
+    ///
+    /// Without token, the query is never interrupted.
+    pub fn try_compute_best_path_cancellable<G: GraphView>(
+        &mut self,
+        query: Query<G>,
+        token: Option<&CancellationToken>,
+    ) -> err::Result<Outcome> {
+        let interruption = token.map(|token| Interruption {
+            deadline: None,
+            token: Some(token),
+        });
+        self.try_compute(query, interruption)
+    }
+
+    pub fn compute_isochrone<G: GraphView>(
+        &mut self,
+        src_idx: NodeIdx,
+        graph: &G,
+        routing_cfg: &Config,
+        max_cost: f64,
+    ) -> Vec<(NodeIdx, f64)> {
+        match self.try_compute_isochrone(src_idx, graph, routing_cfg, max_cost) {
+            Ok(isochrone) => isochrone,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Returns all nodes, which are reachable from the given src-node with costs of at most
+    /// `max_cost`, together with their costs.
+    /// Costs are weighted by the routing-config's alphas like in `try_compute_best_path(...)`.
+    ///
+    /// The nodes are returned in the order they are settled by a unidirectional search, hence
+    /// sorted by cost (and by idx for equal costs), starting with the src-node at cost 0.
+    /// The result is empty, if `max_cost` is negative or NaN.
+    ///
+    /// Only plain Dijkstra is supported, since a ch-search doesn't settle nodes with their final
+    /// costs.
+    pub fn try_compute_isochrone<G: GraphView>(
+        &mut self,
+        src_idx: NodeIdx,
+        graph: &G,
+        routing_cfg: &Config,
+        max_cost: f64,
+    ) -> err::Result<Vec<(NodeIdx, f64)>> {
+        if routing_cfg.routing_algo != RoutingAlgo::Dijkstra {
+            return Err(err::Msg::from(format!(
+                "Isochrones need the routing-algorithm Dijkstra, but {:?} is configured.",
+                routing_cfg.routing_algo
+            )));
+        }
+        if routing_cfg.alphas.iter().any(|alpha| alpha < &0.0) {
+            return Err(err::Msg::from(format!(
+                "Isochrones need non-negative alphas, but the alphas are {:?}.",
+                routing_cfg.alphas
+            )));
+        }
+        self.is_ch_dijkstra = false;
+        self.last_slow_query = None;
+        self.init_query(graph.node_count());
+        if max_cost.is_nan() || max_cost < 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let dir = self.fwd_idx();
+        let mut isochrone = Vec::new();
+        self.queue.push(Reverse(CostNode {
+            idx: src_idx,
+            cost: 0.0,
+            direction: Direction::FWD,
+        }));
+        self.set_cost(dir, src_idx, 0.0);
+
+        while let Some(Reverse(current)) = self.queue.pop() {
+            if self.is_visited(dir, current.idx) || !self.has_costnode_improved(&current) {
+                continue;
+            }
+            self.visit(&current);
+            isochrone.push((current.idx, current.cost));
+
+            self.relax_fwd(&current, graph, routing_cfg, max_cost)?;
+        }
+
+        Ok(isochrone)
+    }
+
+    pub fn shortest_path_tree<G: GraphView>(
+        &mut self,
+        src_idx: NodeIdx,
+        graph: &G,
+        routing_cfg: &Config,
+    ) -> SpTree {
+        match self.try_shortest_path_tree(src_idx, graph, routing_cfg) {
+            Ok(sp_tree) => sp_tree,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Returns the shortest-path-tree of the given src-node, containing every node of the
+    /// src-node's component (reachable by forward-edges) with its parent-edge and cost.
+    /// Costs are weighted by the routing-config's alphas like in `try_compute_best_path(...)`.
+    ///
+    /// Like isochrones (see `try_compute_isochrone(...)`), only plain Dijkstra is supported.
+    /// On ch-graphs, parent-edges could be shortcuts.
+    pub fn try_shortest_path_tree<G: GraphView>(
+        &mut self,
+        src_idx: NodeIdx,
+        graph: &G,
+        routing_cfg: &Config,
+    ) -> err::Result<SpTree> {
+        let settled_nodes =
+            self.try_compute_isochrone(src_idx, graph, routing_cfg, std::f64::INFINITY)?;
+
+        // predecessors of settled nodes are final
+        let dir = self.fwd_idx();
+        let mut parent_edges = vec![None; graph.node_count()];
+        let mut costs = vec![None; graph.node_count()];
+        for (node_idx, cost) in settled_nodes {
+            parent_edges[*node_idx] = self.predecessor(dir, node_idx);
+            costs[*node_idx] = Some(cost);
+        }
+        Ok(SpTree::new(src_idx, parent_edges, costs))
+    }
+
+    pub fn compute_best_paths_from<G: GraphView>(
+        &mut self,
+        src_idx: NodeIdx,
+        dst_idxs: &[NodeIdx],
+        graph: &G,
+        routing_cfg: &Config,
+    ) -> Vec<Option<Path>> {
+        match self.try_compute_best_paths_from(src_idx, dst_idxs, graph, routing_cfg) {
+            Ok(best_paths) => best_paths,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Returns the best paths from the given src-node to every given dst-node (in the same
+    /// order), like calling `try_compute_best_path(...)` for every pair, but with only one
+    /// unidirectional search.
+    /// The search stops as soon as all dst-nodes are settled, and all paths are reconstructed
+    /// from the shared predecessors.
+    ///
+    /// With `CHDijkstra`, every pair is queried separately, since a ch-search needs its dst-node.
+    /// Like `try_compute_best_path(...)`, no path is found, if any alpha-value is negative.
+    pub fn try_compute_best_paths_from<G: GraphView>(
+        &mut self,
+        src_idx: NodeIdx,
+        dst_idxs: &[NodeIdx],
+        graph: &G,
+        routing_cfg: &Config,
+    ) -> err::Result<Vec<Option<Path>>> {
+        if routing_cfg.routing_algo != RoutingAlgo::Dijkstra {
+            return dst_idxs
+                .iter()
+                .map(|&dst_idx| {
+                    self.try_compute_best_path(Query {
+                        src_idx,
+                        dst_idx,
+                        graph,
+                        routing_cfg,
+                        edge_bias: None,
+                        path_hint: PathHint::Auto,
+                    })
+                })
+                .collect();
+        }
+        if routing_cfg.alphas.iter().any(|alpha| alpha < &0.0) {
+            return Ok(vec![None; dst_idxs.len()]);
+        }
+        self.is_ch_dijkstra = false;
+        self.last_slow_query = None;
+        self.init_query(graph.node_count());
+
+        // every settled node is settled only once, hence every dst-node is counted only once
+        let mut pending_dst_idxs = dst_idxs.to_vec();
+        pending_dst_idxs.sort_unstable();
+        pending_dst_idxs.dedup();
+        let mut pending_count = pending_dst_idxs.len();
+
+        let dir = self.fwd_idx();
+        self.queue.push(Reverse(CostNode {
+            idx: src_idx,
+            cost: 0.0,
+            direction: Direction::FWD,
+        }));
+        self.set_cost(dir, src_idx, 0.0);
+
+        while pending_count > 0 {
+            let current = match self.queue.pop() {
+                Some(Reverse(current)) => current,
+                None => break,
+            };
+            if self.is_visited(dir, current.idx) || !self.has_costnode_improved(&current) {
+                continue;
+            }
+            self.visit(&current);
+            if pending_dst_idxs.binary_search(&current.idx).is_ok() {
+                pending_count -= 1;
+            }
+
+            self.relax_fwd(&current, graph, routing_cfg, std::f64::INFINITY)?;
+        }
+
+        Ok(dst_idxs
+            .iter()
+            .map(|&dst_idx| {
+                if !self.is_visited(dir, dst_idx) {
+                    return None;
+                }
+
+                // iterate backwards over fwd-path
+                let mut proto_path = Vec::new();
+                let mut cur_idx = dst_idx;
+                while let Some(incoming_idx) = self.predecessor(dir, cur_idx) {
+                    proto_path.push(incoming_idx);
+                    cur_idx = graph.bwd_dst_idx(incoming_idx);
+                }
+                proto_path.reverse();
+
+                Some(Path::new(
+                    src_idx,
+                    graph.node_id(src_idx),
+                    dst_idx,
+                    graph.node_id(dst_idx),
+                    proto_path,
+                ))
+            })
+            .collect())
+    }
+
+    /// Relaxes the leaving edges of a settled node for unidirectional searches, where nodes
+    /// beyond the max-cost are never settled, hence never enqueued.
+    /// Turns forbidden by a turn-restriction are skipped (see `try_compute_best_path(...)`).
+    fn relax_fwd<G: GraphView>(
+        &mut self,
+        current: &CostNode,
+        graph: &G,
+        routing_cfg: &Config,
+        max_cost: f64,
+    ) -> err::Feedback {
+        let dir = self.fwd_idx();
+        let incoming_idx = self.predecessor(dir, current.idx);
+        for leaving_edge in graph.fwd_leaving_edges(current.idx) {
+            if let Some(incoming_idx) = incoming_idx {
+                if self.is_skipping_turn(graph, incoming_idx, leaving_edge.idx()) {
+                    continue;
+                }
+            }
+
+            let metrics = graph.edge_metrics(leaving_edge.idx());
+            let increment = helpers::dot_product(&routing_cfg.alphas, &metrics);
+            if routing_cfg.is_checking_non_negative && increment < -accuracy::F64_ABS {
+                return Err(negative_increment_msg(
+                    graph,
+                    routing_cfg,
+                    leaving_edge.idx(),
+                    &metrics,
+                ));
+            }
+            debug_assert!(
+                increment >= -accuracy::F64_ABS,
+                "{}",
+                negative_increment_msg(graph, routing_cfg, leaving_edge.idx(), &metrics)
+            );
+            let new_cost = current.cost + increment;
+            if new_cost <= max_cost && new_cost < self.cost(dir, leaving_edge.dst_idx()) {
+                self.set_predecessor(dir, leaving_edge.dst_idx(), leaving_edge.idx());
+                self.set_cost(dir, leaving_edge.dst_idx(), new_cost);
+                self.queue.push(Reverse(CostNode {
+                    idx: leaving_edge.dst_idx(),
+                    cost: new_cost,
+                    direction: Direction::FWD,
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    fn try_compute<G: GraphView>(
+        &mut self,
+        query: Query<G>,
+        interruption: Option<Interruption>,
+    ) -> err::Result<Outcome> {
+        crate::trace_scope!(
+            "dijkstra::query",
+            src_idx = *query.src_idx,
+            dst_idx = *query.dst_idx
+        );
+        // no measurement without threshold
+        let start = self.slow_query_threshold.map(|_| Instant::now());
+        self.last_slow_query = None;
+
         debug_assert!(
             !query.routing_cfg.alphas.is_empty(),
             "Best path should be computed, but no alphas are specified."
@@ -151,7 +772,7 @@ impl Dijkstra {
             // Dijkstra would not terminate with negative weights
             // -> no path found
             if alpha < &0.0 {
-                return None;
+                return Ok(Outcome::Finished(None));
             }
         }
 
@@ -168,21 +789,19 @@ impl Dijkstra {
         //----------------------------------------------------------------------------------------//
         // initialization-stuff
 
-        let nodes = query.graph.nodes();
-        let xwd_edges = {
-            debug_assert_eq!(
-                0,
-                self.dir_idx(Direction::FWD),
-                "Direction-Idx of FWD is expected to be 0."
-            );
-            debug_assert_eq!(
-                1,
-                self.dir_idx(Direction::BWD),
-                "Direction-Idx of BWD is expected to be 1."
-            );
-            [query.graph.fwd_edges(), query.graph.bwd_edges()]
-        };
-        self.init_query(nodes.count());
+        let graph = query.graph;
+        // Without levels, a ch-dijkstra would silently route on a plain graph.
+        if self.is_ch_dijkstra && !graph.has_levels() {
+            return Err(err::Msg::from(
+                "CHDijkstra needs a graph with ch-levels, but the graph has none. \
+                 Configure the nodes' CHLevel when parsing a contracted graph, \
+                 or use the routing-algorithm Dijkstra.",
+            ));
+        }
+        // Breaking at the first lower level is only correct, if leaving edges are sorted by level.
+        let is_breaking_at_lower_level =
+            IS_USING_CH_LEVEL_SPEEDUP && graph.are_leaving_edges_level_sorted();
+        self.init_query(graph.node_count());
         let mut best_meeting: Option<(NodeIdx, f64)> = None;
 
         //----------------------------------------------------------------------------------------//
@@ -201,17 +820,32 @@ impl Dijkstra {
             direction: Direction::BWD,
         }));
         // update fwd-stats
-        self.costs[self.fwd_idx()][*query.src_idx] = 0.0;
-        self.touched[self.fwd_idx()].push(*query.src_idx);
+        self.set_cost(self.fwd_idx(), query.src_idx, 0.0);
 
         // update bwd-stats
-        self.costs[self.bwd_idx()][*query.dst_idx] = 0.0;
-        self.touched[self.bwd_idx()].push(*query.dst_idx);
+        self.set_cost(self.bwd_idx(), query.dst_idx, 0.0);
 
         //----------------------------------------------------------------------------------------//
         // search for shortest path
 
+        let mut dequeued_count: usize = 0;
         while let Some(Reverse(current)) = self.queue.pop() {
+            // Routing-data is outdated when initializing the next query,
+            // hence returning here is fine.
+            if let Some(interruption) = &interruption {
+                if let Some(token) = interruption.token {
+                    if token.is_cancelled() {
+                        return Ok(Outcome::Cancelled);
+                    }
+                }
+                if let Some(deadline) = interruption.deadline {
+                    if dequeued_count % TIMEOUT_CHECK_INTERVAL == 0 && Instant::now() >= deadline {
+                        return Ok(Outcome::TimedOut);
+                    }
+                }
+            }
+            dequeued_count += 1;
+
             // For non-contracted graphs, this could be a slight improvement.
             // For contracted graphs, this is the only stop-criterion.
             // This is needed, because the bidirectional Dijkstra processes sub-graphs,
@@ -250,35 +884,71 @@ impl Dijkstra {
                 }
 
                 let new_total_cost = self.total_cost(&current);
-                if new_total_cost < best_total_cost {
+                if new_total_cost < best_total_cost
+                    && !self.is_meeting_turn_forbidden(graph, current.idx)
+                {
                     best_meeting = Some((current.idx, new_total_cost));
                 }
             }
             // if meeting-node is found for the first time, remember it
-            else if self.is_meeting_costnode(&current) {
+            else if self.is_meeting_costnode(&current)
+                && !self.is_meeting_turn_forbidden(graph, current.idx)
+            {
                 let new_total_cost = self.total_cost(&current);
                 best_meeting = Some((current.idx, new_total_cost));
             }
 
             // update costs and add predecessors of nodes, which are dst of current's leaving edges
-            for leaving_edge in xwd_edges[dir].starting_from(current.idx) {
+            let incoming_idx = self.predecessor(dir, current.idx);
+            for leaving_edge in leaving_edges(graph, current.direction, current.idx) {
+                if let Some(incoming_idx) = incoming_idx {
+                    let is_skipped = match current.direction {
+                        Direction::FWD => {
+                            self.is_skipping_turn(graph, incoming_idx, leaving_edge.idx())
+                        }
+                        Direction::BWD => {
+                            self.is_skipping_turn(graph, leaving_edge.idx(), incoming_idx)
+                        }
+                    };
+                    if is_skipped {
+                        continue;
+                    }
+                }
+
                 if self.is_ch_dijkstra
-                    && nodes.level(current.idx) > nodes.level(leaving_edge.dst_idx())
+                    && graph.node_level(current.idx) > graph.node_level(leaving_edge.dst_idx())
                 {
-                    if !IS_USING_CH_LEVEL_SPEEDUP {
-                        continue;
-                    } else {
+                    if is_breaking_at_lower_level {
                         // break because leaving-edges are sorted by level
                         break;
+                    } else {
+                        continue;
                     }
                 }
 
-                let new_cost = current.cost
-                    + helpers::dot_product(&query.routing_cfg.alphas, &leaving_edge.metrics());
-                if new_cost < self.costs[dir][*leaving_edge.dst_idx()] {
-                    self.predecessors[dir][*leaving_edge.dst_idx()] = Some(leaving_edge.idx());
-                    self.costs[dir][*leaving_edge.dst_idx()] = new_cost;
-                    self.touched[dir].push(*leaving_edge.dst_idx());
+                let metrics = graph.edge_metrics(leaving_edge.idx());
+                let increment = helpers::dot_product(&query.routing_cfg.alphas, &metrics);
+                if query.routing_cfg.is_checking_non_negative && increment < -accuracy::F64_ABS {
+                    return Err(negative_increment_msg(
+                        graph,
+                        query.routing_cfg,
+                        leaving_edge.idx(),
+                        &metrics,
+                    ));
+                }
+                debug_assert!(
+                    increment >= -accuracy::F64_ABS,
+                    "{}",
+                    negative_increment_msg(graph, query.routing_cfg, leaving_edge.idx(), &metrics)
+                );
+                let increment = match query.edge_bias {
+                    Some(edge_bias) => (increment + edge_bias[*leaving_edge.idx()]).max(0.0),
+                    None => increment,
+                };
+                let new_cost = current.cost + increment;
+                if new_cost < self.cost(dir, leaving_edge.dst_idx()) {
+                    self.set_predecessor(dir, leaving_edge.dst_idx(), leaving_edge.idx());
+                    self.set_cost(dir, leaving_edge.dst_idx(), new_cost);
 
                     // if path is found
                     // -> Run until queue is empty
@@ -301,18 +971,18 @@ impl Dijkstra {
         //----------------------------------------------------------------------------------------//
         // create path if found
 
-        if let Some((meeting_node_idx, _best_total_cost)) = best_meeting {
-            let mut proto_path = Vec::new();
+        let best_path = if let Some((meeting_node_idx, _best_total_cost)) = best_meeting {
+            let mut proto_path =
+                Vec::with_capacity(query.path_hint.path_capacity(graph.node_count()));
 
             // iterate backwards over fwd-path
             let mut cur_idx = meeting_node_idx;
             let dir = self.fwd_idx();
-            let opp_dir = self.bwd_idx();
-            while let Some(incoming_idx) = self.predecessors[dir][*cur_idx] {
+            while let Some(incoming_idx) = self.predecessor(dir, cur_idx) {
                 proto_path.push(incoming_idx);
 
                 // get incoming edge, but reversed to get the forward's src-node
-                cur_idx = xwd_edges[opp_dir].dst_idx(incoming_idx);
+                cur_idx = graph.bwd_dst_idx(incoming_idx);
             }
 
             // take fwd-part in the right order
@@ -321,27 +991,79 @@ impl Dijkstra {
             // iterate backwards over bwd-path
             let mut cur_idx = meeting_node_idx;
             let dir = self.bwd_idx();
-            let opp_dir = self.fwd_idx();
-            while let Some(leaving_idx) = self.predecessors[dir][*cur_idx] {
+            while let Some(leaving_idx) = self.predecessor(dir, cur_idx) {
                 proto_path.push(leaving_idx);
 
                 // get leaving edge, but reversed to get the backward's src-node
-                cur_idx = xwd_edges[opp_dir].dst_idx(leaving_idx);
+                cur_idx = graph.fwd_dst_idx(leaving_idx);
             }
 
             Some(Path::new(
                 query.src_idx,
-                nodes.id(query.src_idx),
+                graph.node_id(query.src_idx),
                 query.dst_idx,
-                nodes.id(query.dst_idx),
+                graph.node_id(query.dst_idx),
                 proto_path,
             ))
         } else {
             None
+        };
+
+        //----------------------------------------------------------------------------------------//
+        // check for slow query
+
+        if let (Some(threshold), Some(start)) = (self.slow_query_threshold, start) {
+            let elapsed = start.elapsed();
+            if elapsed >= threshold {
+                let slow_query = SlowQuery {
+                    src_id: graph.node_id(query.src_idx),
+                    dst_id: graph.node_id(query.dst_idx),
+                    elapsed,
+                    settled_count: self.settled_count,
+                    alphas: query.routing_cfg.alphas.clone(),
+                    is_ch_dijkstra: self.is_ch_dijkstra,
+                };
+                warn!("Slow query {}", slow_query);
+                self.last_slow_query = Some(slow_query);
+            }
         }
+
+        Ok(Outcome::Finished(best_path))
+    }
+}
+
+fn leaving_edges<G: GraphView>(graph: &G, direction: Direction, idx: NodeIdx) -> LeavingEdges {
+    match direction {
+        Direction::FWD => graph.fwd_leaving_edges(idx),
+        Direction::BWD => graph.bwd_leaving_edges(idx),
     }
 }
 
+/// Names the first metric, which makes the edge's costs negative.
+fn negative_increment_msg<G: GraphView>(
+    graph: &G,
+    routing_cfg: &Config,
+    edge_idx: EdgeIdx,
+    metrics: &DimVec<f64>,
+) -> err::Msg {
+    let metrics_cfg = &graph.cfg().edges.metrics;
+    let metric_id = routing_cfg
+        .alphas
+        .iter()
+        .zip(metrics.iter())
+        .position(|(alpha, value)| alpha * value < 0.0)
+        .map(|metric_idx| metrics_cfg.ids[metric_idx].0.clone())
+        .unwrap_or_else(|| String::from("unknown"));
+    err::Msg::from(format!(
+        "Dijkstra relaxed the edge of id {} with negative costs of metric {}, but needs non-negative costs.",
+        graph
+            .edge_id(edge_idx)
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| format!("None (idx {})", edge_idx)),
+        metric_id
+    ))
+}
+
 #[derive(Copy, Clone, Debug)]
 enum Direction {
     FWD,
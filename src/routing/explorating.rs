@@ -5,12 +5,15 @@
 
 use crate::{
     approximating::Approx,
-    configs,
+    configs::{
+        self,
+        routing::{ExploratorAlgo, RoutingAlgo},
+    },
     defaults::{self, capacity::DimVec},
-    helpers::{self, algebra},
+    helpers::{self, algebra, cancel::CancellationToken},
     network::{Graph, NodeIdx},
     routing::{
-        dijkstra::{self, Dijkstra},
+        dijkstra::{self, Dijkstra, Outcome, SlowQuery},
         paths::Path,
     },
 };
@@ -19,7 +22,9 @@ use nd_triangulation::Triangulation;
 use smallvec::smallvec;
 use std::{
     collections::{HashMap, HashSet},
+    fmt::{self, Display},
     ops::Deref,
+    time::{Duration, Instant},
 };
 
 // needed because convex-hull has dim+1 points per cell
@@ -30,10 +35,13 @@ struct Query<'a> {
     dst_idx: NodeIdx,
     graph: &'a Graph,
     routing_cfg: configs::routing::Config,
+    path_hint: dijkstra::PathHint,
     graph_dim: usize,
     triangulation_dim: usize,
     tolerances: DimVec<f64>,
     is_metric_considered: DimVec<bool>,
+    // settled nodes of all dijkstra-queries
+    settled_count: usize,
 }
 
 impl<'a> Query<'a> {
@@ -44,6 +52,7 @@ impl<'a> Query<'a> {
         let dst_idx = query.dst_idx;
         let graph = query.graph;
         let routing_cfg = query.routing_cfg.clone();
+        let path_hint = query.path_hint;
 
         // config and stuff
         let graph_dim = graph.metrics().dim();
@@ -62,6 +71,7 @@ impl<'a> Query<'a> {
             dst_idx,
             graph,
             routing_cfg,
+            path_hint,
             graph_dim,
             triangulation_dim: is_metric_considered
                 .iter()
@@ -69,6 +79,7 @@ impl<'a> Query<'a> {
                 .count(),
             tolerances,
             is_metric_considered,
+            settled_count: 0,
         }
     }
 }
@@ -117,10 +128,90 @@ impl<'a> Cell<'a> {
     }
 }
 
+/// Result of a cancellable exploration (see `ConvexHullExplorator::fully_explorate_cancellable(...)`).
+#[derive(Debug)]
+pub enum Exploration {
+    Finished(Vec<Path>),
+    /// The exploration has been interrupted by its cancellation-token, so found paths are dropped.
+    Cancelled,
+}
+
+/// Counts of an exploration, explaining why it has stopped, e.g. for tuning tolerances
+/// (see `ConvexHullExplorator::fully_explorate_with_summary(...)`).
+///
+/// Every seen convex-hull-cell is either visited (meaning explored by a Dijkstra-query) or
+/// skipped for exactly one reason.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExplorationSummary {
+    /// Alpha-combinations tried for the initial convex-hull
+    pub initial_alphas: usize,
+    pub seen_cells: usize,
+    pub visited_cells: usize,
+    /// Cells, which have been visited in a previous iteration
+    pub already_visited_cells: usize,
+    /// Cells, whose paths can't undercut at least one tolerance
+    pub tolerance_pruned_cells: usize,
+    /// Cells, whose linear system for the next alphas can't be solved
+    pub singular_cells: usize,
+    /// Cells, whose alphas have negative components even after re-solving (see
+    /// `configs::routing::Config::is_allowing_negative_alphas`)
+    pub negative_alpha_cells: usize,
+    /// Visited cells, whose alphas have been re-solved with zeros instead of negative components
+    pub resolved_alpha_cells: usize,
+    pub dijkstra_runs: usize,
+    /// Paths, which have been added to the triangulation
+    pub found_paths: usize,
+    /// Found paths, which are not returned, because their costs exceed the tolerances
+    pub rejected_paths: usize,
+}
+
+impl ExplorationSummary {
+    pub fn skipped_cells(&self) -> usize {
+        self.already_visited_cells
+            + self.tolerance_pruned_cells
+            + self.singular_cells
+            + self.negative_alpha_cells
+    }
+}
+
+impl Display for ExplorationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ initial-alphas: {}, cells: {} (visited: {} (re-solved alphas: {}), skipped: {} \
+             (already visited: {}, tolerance-pruned: {}, singular: {}, negative alphas: {})), \
+             dijkstra-runs: {}, found-paths: {}, rejected-paths: {} }}",
+            self.initial_alphas,
+            self.seen_cells,
+            self.visited_cells,
+            self.resolved_alpha_cells,
+            self.skipped_cells(),
+            self.already_visited_cells,
+            self.tolerance_pruned_cells,
+            self.singular_cells,
+            self.negative_alpha_cells,
+            self.dijkstra_runs,
+            self.found_paths,
+            self.rejected_paths
+        )
+    }
+}
+
+/// Marks an interrupted Dijkstra-query inside an exploration.
+struct Cancelled;
+
+/// Explorations taking longer than an optional threshold are logged as warning (see `SlowQuery`).
+///
+/// Paths are added to the triangulation only once, identified by their flattened edges
+/// (see `Path::flattened_hash()`) or by their costs.
 pub struct ConvexHullExplorator {
     found_paths: HashMap<VertexId, Path>,
     tolerated_found_paths: Vec<VertexId>,
     visited_cells: HashSet<CellId>,
+    duplicate_count: usize,
+    summary: ExplorationSummary,
+    slow_query_threshold: Option<Duration>,
+    last_slow_query: Option<SlowQuery>,
 }
 
 impl ConvexHullExplorator {
@@ -129,9 +220,34 @@ impl ConvexHullExplorator {
             found_paths: HashMap::new(),
             tolerated_found_paths: Vec::new(),
             visited_cells: HashSet::new(),
+            duplicate_count: 0,
+            summary: ExplorationSummary::default(),
+            slow_query_threshold: None,
+            last_slow_query: None,
         }
     }
 
+    /// Explorations taking at least this duration are logged as warning.
+    /// `None` (default) disables the measurement.
+    pub fn set_slow_query_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_query_threshold = threshold;
+    }
+
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.slow_query_threshold
+    }
+
+    /// Returns the last exploration's summary, if it has been slow.
+    pub fn last_slow_query(&self) -> Option<&SlowQuery> {
+        self.last_slow_query.as_ref()
+    }
+
+    /// Returns the number of paths, which have been skipped in the last exploration,
+    /// because their route or their costs have been found already.
+    pub fn duplicate_count(&self) -> usize {
+        self.duplicate_count
+    }
+
     // TODO cap exploration with epsilon for routing-costs (1 + eps) * costs[i]
     //
     // New paths of a facet are linear-combinations of its defining paths
@@ -142,9 +258,49 @@ impl ConvexHullExplorator {
         query: dijkstra::Query,
         dijkstra: &mut Dijkstra,
     ) -> Vec<Path> {
+        match self.fully_explorate_cancellable(query, dijkstra, None) {
+            Exploration::Finished(paths) => paths,
+            Exploration::Cancelled => {
+                unreachable!("Exploration has been cancelled without any token.")
+            }
+        }
+    }
+
+    /// Like `fully_explorate(...)`, but returns the exploration's counts as well.
+    pub fn fully_explorate_with_summary(
+        &mut self,
+        query: dijkstra::Query,
+        dijkstra: &mut Dijkstra,
+    ) -> (Vec<Path>, ExplorationSummary) {
+        let paths = self.fully_explorate(query, dijkstra);
+        (paths, self.summary.clone())
+    }
+
+    /// Like `fully_explorate(...)`, but the exploration is interrupted as soon as the given token
+    /// is cancelled, which is checked for every convex-hull-cell and inside every Dijkstra-query.
+    /// An interrupted exploration leaves this explorator and the Dijkstra ready for the next
+    /// query.
+    pub fn fully_explorate_cancellable(
+        &mut self,
+        query: dijkstra::Query,
+        dijkstra: &mut Dijkstra,
+        token: Option<&CancellationToken>,
+    ) -> Exploration {
+        crate::trace_scope!(
+            "explorator::query",
+            src_idx = *query.src_idx,
+            dst_idx = *query.dst_idx,
+            dim = query.routing_cfg.alphas.len()
+        );
+        // no measurement without threshold
+        let start = self.slow_query_threshold.map(|_| Instant::now());
+        self.last_slow_query = None;
+
         // init query
 
         let mut query = Query::with(query);
+        // alphas are changed during exploration
+        let alphas = query.routing_cfg.alphas.clone();
 
         if query.src_idx == query.dst_idx {
             warn!(
@@ -161,8 +317,20 @@ impl ConvexHullExplorator {
         self.found_paths.clear();
         self.tolerated_found_paths.clear();
         self.visited_cells.clear();
+        self.duplicate_count = 0;
+        self.summary = ExplorationSummary::default();
         let mut new_found_paths = Vec::new();
-        ConvexHullExplorator::explore_initial_paths(&mut new_found_paths, &mut query, dijkstra);
+        if ConvexHullExplorator::explore_initial_paths(
+            &mut new_found_paths,
+            &mut query,
+            dijkstra,
+            token,
+            &mut self.summary,
+        )
+        .is_err()
+        {
+            return Exploration::Cancelled;
+        }
         self.update(
             &query,
             &mut is_triangulation_dirty,
@@ -189,12 +357,20 @@ impl ConvexHullExplorator {
             while is_triangulation_dirty {
                 trace!("Found {} paths yet.", self.found_paths.len());
                 for raw_cell in triangulation.convex_hull_cells() {
+                    if let Some(token) = token {
+                        if token.is_cancelled() {
+                            return Exploration::Cancelled;
+                        }
+                    }
+                    self.summary.seen_cells += 1;
+
                     // don't look at cells twice
                     if self.visited_cells.contains(&CellId(raw_cell.id())) {
                         trace!(
                             "Jump over already explored cell of cell-id {}",
                             raw_cell.id()
                         );
+                        self.summary.already_visited_cells += 1;
                         continue;
                     }
 
@@ -237,6 +413,7 @@ impl ConvexHullExplorator {
                             **cell.id(),
                             "), that can't undercut at least one tolerance."
                         );
+                        self.summary.tolerance_pruned_cells += 1;
                         continue;
                     }
                     trace!("Explore cell of cell-id {}", **cell.id());
@@ -248,8 +425,11 @@ impl ConvexHullExplorator {
                     // the path's cost is part of the convex-hull.
 
                     let (rows, b) = if let Some((rows, b)) =
-                        ConvexHullExplorator::create_linear_system(&cell, &query)
-                    {
+                        ConvexHullExplorator::create_linear_system(
+                            &cell,
+                            &query,
+                            &query.is_metric_considered,
+                        ) {
                         (rows, b)
                     } else {
                         debug!(
@@ -265,16 +445,43 @@ impl ConvexHullExplorator {
                             cell.vertices().len(),
                             ")",
                         );
+                        self.summary.singular_cells += 1;
                         continue;
                     };
 
                     // calculate alphas
-                    query.routing_cfg.alphas =
+                    let mut alphas =
                         if let Some(x) = algebra::Matrix::from_rows(rows).lu().solve(&b) {
                             x
                         } else {
+                            self.summary.singular_cells += 1;
                             continue;
                         };
+
+                    // Negative components are valid for the convex-hull's facet, but would let
+                    // Dijkstra maximize the respective metric.
+                    // -> Re-solve with zeros instead, like for unconsidered metrics.
+                    if !query.routing_cfg.is_allowing_negative_alphas
+                        && ConvexHullExplorator::has_negative_alphas(&alphas)
+                    {
+                        trace!("Re-solve negative alphas = {:?}", alphas);
+                        match ConvexHullExplorator::resolve_negative_alphas(&alphas, &cell, &query)
+                        {
+                            Some(resolved_alphas) => {
+                                self.summary.resolved_alpha_cells += 1;
+                                alphas = resolved_alphas;
+                            }
+                            None => {
+                                trace!(
+                                    "Jump over cell (id: {}) with negative alphas.",
+                                    **cell.id()
+                                );
+                                self.summary.negative_alpha_cells += 1;
+                                continue;
+                            }
+                        }
+                    }
+                    query.routing_cfg.alphas = alphas;
                     trace!("alphas = {:?}", query.routing_cfg.alphas);
                     for (i, vertex) in cell.vertices().iter().enumerate() {
                         // for i in 0..candidate.len() {
@@ -287,34 +494,48 @@ impl ConvexHullExplorator {
 
                     // find new path with new alpha
 
-                    if let Some(mut best_path) = dijkstra.compute_best_path(dijkstra::Query {
-                        src_idx: query.src_idx,
-                        dst_idx: query.dst_idx,
-                        graph: query.graph,
-                        routing_cfg: &query.routing_cfg,
-                    }) {
+                    self.summary.visited_cells += 1;
+                    self.summary.dijkstra_runs += 1;
+                    let best_path =
+                        match ConvexHullExplorator::compute_best_path(&query, dijkstra, token) {
+                            Ok(best_path) => best_path,
+                            Err(Cancelled) => return Exploration::Cancelled,
+                        };
+                    if let Some(mut best_path) = best_path {
+                        query.settled_count += dijkstra.settled_count();
                         best_path.calc_costs(query.graph);
                         let new_path = best_path;
 
                         let new_alpha_cost =
                             helpers::dot_product(&query.routing_cfg.alphas, new_path.costs());
                         trace!("alphas * new_path.costs() = {:?}", new_alpha_cost);
-                        // take any vertex, since alpha is chosen s.t. all dot-products are equal
-                        let any_alpha_cost = helpers::dot_product(
-                            &query.routing_cfg.alphas,
-                            cell.vertices()[0].path.costs(),
-                        );
+                        // Alpha is chosen s.t. all dot-products are equal, except for re-solved
+                        // alphas, hence take the best vertex.
+                        let best_alpha_cost = cell
+                            .vertices()
+                            .iter()
+                            .map(|vertex| {
+                                helpers::dot_product(&query.routing_cfg.alphas, vertex.path.costs())
+                            })
+                            .fold(std::f64::INFINITY, f64::min);
 
                         // Add new path if it's cost-vector's projection onto the alpha-vector
                         // is smaller.
 
-                        let is_path_new = Approx(new_alpha_cost) < Approx(any_alpha_cost)
-                            && !new_found_paths.contains(&new_path);
-                        if is_path_new {
-                            trace!("Push {}", new_path);
-                            new_found_paths.push(new_path);
+                        if Approx(new_alpha_cost) < Approx(best_alpha_cost) {
+                            if ConvexHullExplorator::is_duplicate(
+                                &new_path,
+                                &self.found_paths,
+                                &new_found_paths,
+                            ) {
+                                self.duplicate_count += 1;
+                                trace!("Already found path {}", new_path);
+                            } else {
+                                trace!("Push {}", new_path);
+                                new_found_paths.push(new_path);
+                            }
                         } else {
-                            trace!("Already found path {}", new_path);
+                            trace!("Path {} doesn't improve the cell", new_path);
                         }
                     } else {
                         trace!("No path found");
@@ -330,18 +551,49 @@ impl ConvexHullExplorator {
             }
         }
 
+        self.summary.found_paths = self.found_paths.len();
+        self.summary.rejected_paths = self.found_paths.len() - self.tolerated_found_paths.len();
+
         // if paths were found but no one is tolerated
         if self.found_paths.len() > 0 && self.tolerated_found_paths.len() == 0 {
             warn!(
-                "{}{}{}{}{}",
+                "{}{}{}{}{}{}",
                 "Exploration found paths from src-id ",
                 query.graph.nodes().id(query.src_idx),
                 " to dst-id ",
                 query.graph.nodes().id(query.dst_idx),
-                ", but should not tolerate any path. Maybe your tolerances are too tight?"
+                ", but should not tolerate any path. Maybe your tolerances are too tight? ",
+                self.summary
             );
         }
 
+        // check for slow query
+
+        if let (Some(threshold), Some(start)) = (self.slow_query_threshold, start) {
+            let elapsed = start.elapsed();
+            if elapsed >= threshold {
+                let slow_query = SlowQuery {
+                    src_id: query.graph.nodes().id(query.src_idx),
+                    dst_id: query.graph.nodes().id(query.dst_idx),
+                    elapsed,
+                    settled_count: query.settled_count,
+                    alphas,
+                    is_ch_dijkstra: match query.routing_cfg.routing_algo {
+                        RoutingAlgo::CHDijkstra
+                        | RoutingAlgo::Explorator {
+                            algo: ExploratorAlgo::CHDijkstra,
+                        } => true,
+                        RoutingAlgo::Dijkstra
+                        | RoutingAlgo::Explorator {
+                            algo: ExploratorAlgo::Dijkstra,
+                        } => false,
+                    },
+                };
+                warn!("Slow exploration {}", slow_query);
+                self.last_slow_query = Some(slow_query);
+            }
+        }
+
         let mut result = Vec::with_capacity(self.tolerated_found_paths.len());
         for vertex_id in &self.tolerated_found_paths {
             result.push(
@@ -350,7 +602,7 @@ impl ConvexHullExplorator {
                     .expect("A tolerated found path should have been found."),
             )
         }
-        result
+        Exploration::Finished(result)
 
         // self.found_paths
         //     .drain()
@@ -369,7 +621,9 @@ impl ConvexHullExplorator {
         new_found_paths: &mut Vec<Path>,
         query: &mut Query,
         dijkstra: &mut Dijkstra,
-    ) {
+        token: Option<&CancellationToken>,
+        summary: &mut ExplorationSummary,
+    ) -> Result<(), Cancelled> {
         // find initial convex-hull
         // -> go through all combinations, where at least one alpha-entry is > 0.0
         // -> at least d+1 points for dimension d
@@ -418,12 +672,12 @@ impl ConvexHullExplorator {
             trace!("Trying init-alpha {:?}", alphas);
 
             query.routing_cfg.alphas = alphas;
-            if let Some(mut best_path) = dijkstra.compute_best_path(dijkstra::Query {
-                src_idx: query.src_idx,
-                dst_idx: query.dst_idx,
-                graph: query.graph,
-                routing_cfg: &query.routing_cfg,
-            }) {
+            summary.initial_alphas += 1;
+            summary.dijkstra_runs += 1;
+            if let Some(mut best_path) =
+                ConvexHullExplorator::compute_best_path(query, dijkstra, token)?
+            {
+                query.settled_count += dijkstra.settled_count();
                 best_path.calc_costs(query.graph);
 
                 // Remember tolerated costs for filtering in the end.
@@ -454,6 +708,46 @@ impl ConvexHullExplorator {
         for path in found_paths {
             new_found_paths.push(path);
         }
+
+        Ok(())
+    }
+
+    fn compute_best_path(
+        query: &Query,
+        dijkstra: &mut Dijkstra,
+        token: Option<&CancellationToken>,
+    ) -> Result<Option<Path>, Cancelled> {
+        let query = dijkstra::Query {
+            src_idx: query.src_idx,
+            dst_idx: query.dst_idx,
+            graph: query.graph,
+            routing_cfg: &query.routing_cfg,
+            // biased paths could be non-optimal for the alphas, breaking the convex hull
+            edge_bias: None,
+            path_hint: query.path_hint,
+        };
+        match dijkstra.compute_best_path_cancellable(query, token) {
+            Outcome::Finished(best_path) => Ok(best_path),
+            Outcome::Cancelled => Err(Cancelled),
+            Outcome::TimedOut => unreachable!("Dijkstra has timed out without any timeout."),
+        }
+    }
+
+    /// A path is a duplicate, if its route (independent of shortcuts) has been found already.
+    /// Further, a path with the same costs as an already found path can't be used by the
+    /// triangulation.
+    fn is_duplicate(
+        path: &Path,
+        found_paths: &HashMap<VertexId, Path>,
+        new_found_paths: &[Path],
+    ) -> bool {
+        found_paths
+            .values()
+            .chain(new_found_paths.iter())
+            .any(|found_path| {
+                found_path.flattened_hash() == path.flattened_hash()
+                    || Approx(found_path.costs()) == Approx(path.costs())
+            })
     }
 
     fn cell_from<'a>(
@@ -476,9 +770,47 @@ impl ConvexHullExplorator {
         }
     }
 
+    fn has_negative_alphas(alphas: &[f64]) -> bool {
+        alphas
+            .iter()
+            .any(|&alpha| alpha < -defaults::accuracy::F64_ABS)
+    }
+
+    /// Solves the cell's linear system again, but with zero-constraints for the metrics of
+    /// negative alphas (like for unconsidered metrics).
+    /// Since every zero-constraint replaces one equality of the cell-vertices' costs, not all
+    /// vertices have the same costs wrt the resulting alphas.
+    ///
+    /// Returns None, if the system is singular or if the resulting alphas are still negative.
+    fn resolve_negative_alphas(alphas: &[f64], cell: &Cell, query: &Query) -> Option<DimVec<f64>> {
+        let is_alpha_free: DimVec<bool> = query
+            .is_metric_considered
+            .iter()
+            .zip(alphas)
+            .map(|(&is_considered, &alpha)| is_considered && alpha >= -defaults::accuracy::F64_ABS)
+            .collect();
+        // at least one metric has to be free, otherwise alphas would be zero
+        if !is_alpha_free.iter().any(|&is_free| is_free) {
+            return None;
+        }
+
+        let (rows, b) = ConvexHullExplorator::create_linear_system(cell, query, &is_alpha_free)?;
+        let resolved_alphas = algebra::Matrix::from_rows(rows).lu().solve(&b)?;
+        trace!("resolved alphas = {:?}", resolved_alphas);
+        if ConvexHullExplorator::has_negative_alphas(&resolved_alphas) {
+            None
+        } else {
+            Some(resolved_alphas)
+        }
+    }
+
+    /// Alphas of metrics, which are not free, are constrained to zero.
+    /// If the cell has more vertices than needed, the equalities of the last vertices are left
+    /// out.
     fn create_linear_system(
         cell: &Cell,
         query: &Query,
+        is_alpha_free: &[bool],
     ) -> Option<(DimVec<DimVec<f64>>, DimVec<f64>)> {
         trace!("Create linear system with paths:");
         for vertex in cell.vertices() {
@@ -493,18 +825,24 @@ impl ConvexHullExplorator {
         let mut b = DimVec::new();
 
         // all lines describe the equality of each dot-product between cost-vector and alpha
+        // (one row is left for normalizing alpha)
+        let free_count = is_alpha_free.iter().filter(|&&is_free| is_free).count();
+        let max_equality_count = if free_count < query.triangulation_dim {
+            free_count.saturating_sub(1)
+        } else {
+            free_count
+        };
         let vertex_0 = &cell.vertices()[0];
-        for vertex_i in &cell.vertices()[1..] {
+        for vertex_i in cell.vertices()[1..].iter().take(max_equality_count) {
             rows.push(helpers::sub(vertex_0.path.costs(), vertex_i.path.costs()));
             b.push(0.0);
         }
 
         // but ignored metrics should lead to zero alpha
-        for (i, _) in query
-            .is_metric_considered
+        for (i, _) in is_alpha_free
             .iter()
             .enumerate()
-            .filter(|&(_, imc)| !imc)
+            .filter(|&(_, is_free)| !is_free)
         {
             // set [0, ..., 0, 1, 0, ..., 0] to 0.0
             let mut row = smallvec![0.0; query.graph_dim];
@@ -0,0 +1,213 @@
+use crate::{
+    configs::routing::{Config, RoutingAlgo},
+    defaults::routing::{
+        golden::{PRECISION, UPDATE_ENV_VAR},
+        EMBEDDED_CFG_PREFIX,
+    },
+    helpers::err,
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Read,
+    path::Path,
+};
+
+/// Routes every query `(src-id, dst-id)` and returns a text-block per query, which is stable and
+/// meant to be checked into the repo and diffed (see `assert_matches(...)`).
+///
+/// The dump starts with the embedded routing-config (see `Config::to_embedded(...)`).
+/// Every block starts with the query's ids, followed by the found route's node-ids and the
+/// route's costs of every metric in the graph's metric-order, written with fixed precision.
+/// Costs are the graph's stored values, hence normalized metrics are not denormalized.
+/// Dijkstra's queue breaks ties by node-indices, hence equally expensive routes are chosen
+/// deterministically.
+///
+/// ```text
+/// # osmgraphing-routing-config: {"routing": {"algorithm": "Dijkstra", ...}}
+///
+/// src-id: 26033921, dst-id: 26160028
+///   nodes: 26033921 26160028
+///   kilometers: 8.000000
+/// ```
+pub fn try_dump(
+    queries: &[(i64, i64)],
+    graph: &Graph,
+    routing_cfg: &Config,
+) -> err::Result<String> {
+    match routing_cfg.routing_algo {
+        RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => (),
+        #[cfg(feature = "gpl")]
+        RoutingAlgo::Explorator { algo: _ } => {
+            return Err(err::Msg::from(
+                "Dumping routes needs a routing-algorithm returning one best path.",
+            ))
+        }
+    }
+
+    let nodes = graph.nodes();
+    let metric_ids = &graph.cfg().edges.metrics.ids;
+    let mut dijkstra = Dijkstra::new();
+    let mut lines = vec![
+        format!(
+            "{} {}",
+            EMBEDDED_CFG_PREFIX,
+            routing_cfg.to_embedded(graph.cfg())
+        ),
+        String::new(),
+    ];
+
+    for &(src_id, dst_id) in queries {
+        lines.push(format!("src-id: {}, dst-id: {}", src_id, dst_id));
+
+        let (src_idx, dst_idx) = match (nodes.idx_from(src_id), nodes.idx_from(dst_id)) {
+            (Ok(src_idx), Ok(dst_idx)) => (src_idx, dst_idx),
+            _ => {
+                lines.push(String::from("  unknown id"));
+                lines.push(String::new());
+                continue;
+            }
+        };
+
+        let path = match dijkstra.compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        }) {
+            Some(path) => path.try_flatten(graph)?,
+            None => {
+                lines.push(String::from("  unreachable"));
+                lines.push(String::new());
+                continue;
+            }
+        };
+
+        let node_ids: Vec<String> = path
+            .node_indices(graph)
+            .into_iter()
+            .map(|node_idx| nodes.id(node_idx).to_string())
+            .collect();
+        lines.push(format!("  nodes: {}", node_ids.join(" ")));
+        for (metric_id, cost) in metric_ids.iter().zip(path.costs().iter()) {
+            lines.push(format!("  {}: {:.*}", metric_id.0, PRECISION, cost));
+        }
+        lines.push(String::new());
+    }
+
+    Ok(lines.into_iter().map(|line| line + "\n").collect())
+}
+
+pub fn dump(queries: &[(i64, i64)], graph: &Graph, routing_cfg: &Config) -> String {
+    match try_dump(queries, graph, routing_cfg) {
+        Ok(dump) => dump,
+        Err(msg) => panic!("{}", msg),
+    }
+}
+
+/// Compares the expected file's content with the actual dump line by line.
+/// Returns a readable diff, if they differ, where lines only in the expected file start with `-`
+/// and lines only in the actual dump start with `+`.
+pub fn try_diff<P: AsRef<Path> + ?Sized>(
+    expected_file: &P,
+    actual: &str,
+) -> err::Result<Option<String>> {
+    let expected_file = expected_file.as_ref();
+    let mut expected = String::new();
+    match OpenOptions::new().read(true).open(expected_file) {
+        Ok(mut file) => {
+            if let Err(e) = file.read_to_string(&mut expected) {
+                return Err(format!(
+                    "Couldn't read {} due to error: {}",
+                    expected_file.display(),
+                    e
+                )
+                .into());
+            }
+        }
+        Err(e) => {
+            return Err(format!(
+                "Couldn't open {} due to error: {}",
+                expected_file.display(),
+                e
+            )
+            .into())
+        }
+    }
+
+    if expected == actual {
+        return Ok(None);
+    }
+    Ok(Some(diff_lines(
+        &expected.lines().collect::<Vec<_>>(),
+        &actual.lines().collect::<Vec<_>>(),
+    )))
+}
+
+/// Panics with a readable diff, if the actual dump doesn't match the expected file.
+///
+/// If the environment-variable `OSMGRAPHING_UPDATE_GOLDEN` is set, the expected file is
+/// overwritten with the actual dump instead, so changed routing-results have to be accepted
+/// consciously (and show up in the repo's diff).
+pub fn assert_matches<P: AsRef<Path> + ?Sized>(expected_file: &P, actual: &str) {
+    let expected_file = expected_file.as_ref();
+
+    if env::var_os(UPDATE_ENV_VAR).is_some() {
+        if let Err(e) = fs::write(expected_file, actual) {
+            panic!(
+                "Couldn't update {} due to error: {}",
+                expected_file.display(),
+                e
+            );
+        }
+        return;
+    }
+
+    match try_diff(expected_file, actual) {
+        Ok(None) => (),
+        Ok(Some(diff)) => panic!(
+            "Routing-results don't match {} (set {} to update it):\n{}",
+            expected_file.display(),
+            UPDATE_ENV_VAR,
+            diff
+        ),
+        Err(msg) => panic!("{}", msg),
+    }
+}
+
+/// Diffs by the longest common subsequence of lines, which is fine for golden-files of a few
+/// hundred lines.
+fn diff_lines(expected: &[&str], actual: &[&str]) -> String {
+    // lcs[i][j] is the lcs-length of expected[i..] and actual[j..]
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push(format!("  {}", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(format!("- {}", expected[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", actual[j]));
+            j += 1;
+        }
+    }
+    diff.join("\n")
+}
@@ -0,0 +1,182 @@
+use crate::{defaults, network::Graph, routing::paths::Path};
+use kissunits::{
+    distance::Meters,
+    geo::{self, Coordinate},
+};
+use std::fmt::{self, Display};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Maneuver {
+    Depart,
+    Continue,
+    SlightLeft,
+    Left,
+    SharpLeft,
+    SlightRight,
+    Right,
+    SharpRight,
+    UTurn,
+    Arrive,
+}
+
+impl Maneuver {
+    /// The turn-angle is the bearing-change in degrees, where positive values turn right.
+    pub fn from_turn_angle(turn_angle: f64) -> Maneuver {
+        let abs_angle = turn_angle.abs();
+        let is_right = turn_angle > 0.0;
+
+        if abs_angle < defaults::routing::instructions::SLIGHT_TURN_ANGLE {
+            Maneuver::Continue
+        } else if abs_angle < defaults::routing::instructions::TURN_ANGLE {
+            if is_right {
+                Maneuver::SlightRight
+            } else {
+                Maneuver::SlightLeft
+            }
+        } else if abs_angle < defaults::routing::instructions::SHARP_TURN_ANGLE {
+            if is_right {
+                Maneuver::Right
+            } else {
+                Maneuver::Left
+            }
+        } else if abs_angle < defaults::routing::instructions::U_TURN_ANGLE {
+            if is_right {
+                Maneuver::SharpRight
+            } else {
+                Maneuver::SharpLeft
+            }
+        } else {
+            Maneuver::UTurn
+        }
+    }
+}
+
+impl Display for Maneuver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Maneuver::Depart => "depart",
+            Maneuver::Continue => "continue",
+            Maneuver::SlightLeft => "turn slight left",
+            Maneuver::Left => "turn left",
+            Maneuver::SharpLeft => "turn sharp left",
+            Maneuver::SlightRight => "turn slight right",
+            Maneuver::Right => "turn right",
+            Maneuver::SharpRight => "turn sharp right",
+            Maneuver::UTurn => "make a u-turn",
+            Maneuver::Arrive => "arrive",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A maneuver at a junction and the distance, which is driven afterwards until the next maneuver.
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    pub maneuver: Maneuver,
+    pub distance: Meters,
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.maneuver {
+            Maneuver::Depart | Maneuver::Arrive => write!(f, "{}", self.maneuver),
+            _ => {
+                if self.distance.0 < 1_000.0 {
+                    write!(f, "{} for {:.0} m", self.maneuver, self.distance.0)
+                } else {
+                    write!(
+                        f,
+                        "{} for {:.1} km",
+                        self.maneuver,
+                        self.distance.0 / 1_000.0
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Generates human-readable instructions for the given path.
+///
+/// The instructions start with `Depart` and end with `Arrive`, both without distance.
+/// In between, every instruction covers the distance until the next turn, where the first one is `Continue`.
+/// Consecutive edges without (or with only a slight) bearing-change are collapsed into one instruction.
+///
+/// Since the graph doesn't store street-names, instructions are purely geometric.
+/// Distances are calculated from the nodes' coordinates, independent of the graph's metrics.
+pub fn generate(path: &Path, graph: &Graph) -> Vec<Instruction> {
+    let path = path.clone().flatten(graph);
+    let nodes = graph.nodes();
+    let coords: Vec<Coordinate> = path
+        .node_indices(graph)
+        .into_iter()
+        .map(|node_idx| nodes.coord(node_idx))
+        .collect();
+
+    let mut instructions = vec![Instruction {
+        maneuver: Maneuver::Depart,
+        distance: Meters(0.0),
+    }];
+
+    if coords.len() > 1 {
+        let mut maneuver = Maneuver::Continue;
+        let mut distance = 0.0;
+        // Edges of zero length don't have a bearing, hence the last valid one is remembered.
+        let mut bearing_in = None;
+
+        for segment in coords.windows(2) {
+            let segment_distance = *geo::haversine_distance_km(&segment[0], &segment[1]) * 1_000.0;
+            if segment_distance > 0.0 {
+                let bearing_out = bearing(&segment[0], &segment[1]);
+                if let Some(bearing_in) = bearing_in {
+                    let next_maneuver =
+                        Maneuver::from_turn_angle(turn_angle(bearing_in, bearing_out));
+                    if next_maneuver != Maneuver::Continue {
+                        instructions.push(Instruction {
+                            maneuver,
+                            distance: Meters(distance),
+                        });
+                        maneuver = next_maneuver;
+                        distance = 0.0;
+                    }
+                }
+                bearing_in = Some(bearing_out);
+            }
+            distance += segment_distance;
+        }
+
+        instructions.push(Instruction {
+            maneuver,
+            distance: Meters(distance),
+        });
+    }
+
+    instructions.push(Instruction {
+        maneuver: Maneuver::Arrive,
+        distance: Meters(0.0),
+    });
+    instructions
+}
+
+/// Returns the initial bearing in degrees in [0, 360), where 0 is north and 90 is east.
+pub fn bearing(from: &Coordinate, to: &Coordinate) -> f64 {
+    let from_lat = from.lat.to_radians();
+    let to_lat = to.lat.to_radians();
+    let delta_lon = (to.lon - from.lon).to_radians();
+
+    let y = delta_lon.sin() * to_lat.cos();
+    let x = from_lat.cos() * to_lat.sin() - from_lat.sin() * to_lat.cos() * delta_lon.cos();
+    (y.atan2(x).to_degrees() + 360.0) % 360.0
+}
+
+/// Returns the bearing-change in degrees in (-180, 180], where positive values turn right.
+fn turn_angle(bearing_in: f64, bearing_out: f64) -> f64 {
+    let turn_angle = (bearing_out - bearing_in) % 360.0;
+    if turn_angle > 180.0 {
+        turn_angle - 360.0
+    } else if turn_angle <= -180.0 {
+        turn_angle + 360.0
+    } else {
+        turn_angle
+    }
+}
@@ -1,5 +1,34 @@
+pub mod alt;
+pub mod corridor;
 pub mod dijkstra;
+pub mod golden;
+pub mod instructions;
 pub mod paths;
+pub mod sp_tree;
+pub mod stabilizing;
+pub mod time_window;
+pub mod verification;
+pub mod yen;
 
 #[cfg(feature = "gpl")]
 pub mod explorating;
+
+#[cfg(feature = "tokio")]
+pub mod blocking;
+
+/// Routing is expected to be moved onto other threads, e.g. by async servers (see `blocking`),
+/// hence this doesn't compile, if any routing-structure loses `Send` (e.g. by using `Rc`).
+#[allow(dead_code)]
+fn assert_send() {
+    fn is_send<T: Send>() {}
+    fn is_send_and_sync<T: Send + Sync>() {}
+
+    is_send_and_sync::<crate::network::Graph>();
+    is_send_and_sync::<crate::configs::routing::Config>();
+    is_send::<alt::AltRouter>();
+    is_send::<dijkstra::Dijkstra>();
+    is_send::<paths::Path>();
+    is_send::<yen::KShortestPaths>();
+    #[cfg(feature = "gpl")]
+    is_send::<explorating::ConvexHullExplorator>();
+}
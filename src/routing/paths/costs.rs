@@ -0,0 +1,98 @@
+use crate::{
+    approximating::Approx,
+    configs::parsing::edges::metrics::{self, UnitInfo},
+    defaults::{self, capacity::DimVec},
+    helpers::err,
+    io,
+    network::{Graph, MetricIdx},
+};
+use std::fmt::{self, Display};
+
+/// A path's costs, where every value is paired with its metric's id and unit, avoiding mixups
+/// of positional indices (see `Path::costs_view(...)`).
+///
+/// Values are denormalized, if the graph's metrics are normalized, hence they are given in the
+/// metric's unit.
+#[derive(Clone, Debug)]
+pub struct Costs<'g> {
+    metrics_cfg: &'g metrics::Config,
+    values: DimVec<f64>,
+}
+
+impl<'g> Costs<'g> {
+    pub(super) fn new(raw_costs: &DimVec<f64>, graph: &'g Graph) -> Costs<'g> {
+        let metrics = graph.metrics();
+        let values = raw_costs
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| value * metrics.mean(MetricIdx(idx)).unwrap_or(1.0))
+            .collect();
+        Costs {
+            metrics_cfg: &graph.cfg().edges.metrics,
+            values,
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the cost of the metric of the given id, or `None`, if the id doesn't exist.
+    pub fn get<S: AsRef<str>>(&self, id: S) -> Option<f64> {
+        let metric_idx = self.metrics_cfg.try_idx_of(id).ok()?;
+        Some(self.values[*metric_idx])
+    }
+
+    /// Returns the cost of the metric of the given id, converted into the given unit.
+    ///
+    /// Errs, if the id doesn't exist, or if the metric's unit can't be converted, e.g. from
+    /// `Hours` into `Kilometers`.
+    pub fn try_get_as<S: AsRef<str>>(&self, id: S, unit: &UnitInfo) -> err::Result<f64> {
+        let metric_idx = self.metrics_cfg.try_idx_of(id)?;
+        self.metrics_cfg.units[*metric_idx].try_convert(unit, self.values[*metric_idx])
+    }
+
+    /// Iterates over all metrics as `(id, unit, value)` in the graph's metric-order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'g str, UnitInfo, f64)> + '_ {
+        self.metrics_cfg
+            .ids
+            .iter()
+            .zip(self.metrics_cfg.units.iter())
+            .zip(self.values.iter())
+            .map(|((id, unit), &value)| (id.0.as_str(), *unit, value))
+    }
+
+    /// Returns true, if both costs have the same metric-ids and approximately equal values.
+    pub fn approx_eq(&self, other: &Costs) -> bool {
+        self.dim() == other.dim()
+            && self.iter().zip(other.iter()).all(
+                |((id, unit, value), (other_id, other_unit, other_value))| {
+                    id == other_id && unit == other_unit && Approx(value) == Approx(other_value)
+                },
+            )
+    }
+}
+
+/// Writes all costs like `48 km, 0.52 h`, where values are rounded to the formatter's precision
+/// (or `defaults::routing::COSTS_DISPLAY_PRECISION`) and unitless values don't have a symbol.
+impl<'g> Display for Costs<'g> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (_id, unit, value)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match f.precision() {
+                Some(precision) => write!(f, "{:.*}", precision, value)?,
+                None => write!(
+                    f,
+                    "{}",
+                    io::fmt::float(value, Some(defaults::routing::COSTS_DISPLAY_PRECISION))
+                )?,
+            }
+            if !unit.symbol().is_empty() {
+                write!(f, " {}", unit.symbol())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,615 @@
+mod costs;
+pub mod similarity;
+pub use costs::Costs;
+
+use crate::{
+    approximating::Approx,
+    configs::parsing::edges::metrics::UnitInfo,
+    defaults::capacity::DimVec,
+    helpers::{self, err},
+    network::{EdgeIdx, Graph, MetricIdx, NodeIdx},
+};
+use kissunits::{
+    distance::{Kilometers, Meters},
+    geo::{self, Coordinate},
+};
+use smallvec::smallvec;
+use std::{
+    cmp::{Eq, PartialEq},
+    collections::hash_map::DefaultHasher,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+};
+
+/// A path from a src to a dst storing all edges in between.
+#[derive(Clone, Debug)]
+pub struct Path {
+    src_idx: NodeIdx,
+    src_id: i64,
+    dst_idx: NodeIdx,
+    dst_id: i64,
+    edges: Vec<EdgeIdx>,
+    costs: Option<DimVec<f64>>,
+    // identifies the route independent of its shortcuts
+    flattened_hash: Option<u64>,
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ src-id: {}, dst-id: {}, costs: {:?}, hop-distance: {:?} }}",
+            self.src_id,
+            self.dst_id,
+            self.costs,
+            self.edges.len()
+        )
+    }
+}
+
+impl Path {
+    /// ATTENTION! This method does not calculate the path's cost.
+    /// This can be done, e.g., with `calc_cost(...)` or `flatten(...)`.
+    /// Accessing the costs without calculating them will lead to panics.
+    pub fn new(
+        src_idx: NodeIdx,
+        src_id: i64,
+        dst_idx: NodeIdx,
+        dst_id: i64,
+        edges: Vec<EdgeIdx>,
+    ) -> Path {
+        Path {
+            src_idx,
+            src_id,
+            dst_idx,
+            dst_id,
+            edges,
+            costs: None,
+            flattened_hash: None,
+        }
+    }
+
+    pub fn src_idx(&self) -> NodeIdx {
+        self.src_idx
+    }
+
+    pub fn dst_idx(&self) -> NodeIdx {
+        self.dst_idx
+    }
+
+    pub fn src_id(&self) -> i64 {
+        self.src_id
+    }
+
+    pub fn dst_id(&self) -> i64 {
+        self.dst_id
+    }
+
+    /// Returns the capacity of the path's edges, e.g. to check the effect of a
+    /// `dijkstra::PathHint`.
+    pub fn capacity(&self) -> usize {
+        self.edges.capacity()
+    }
+
+    /// ATTENTION! This method panics if the costs hasn't been calculated (e.g. `calc_cost(...)` or `flatten(...)`).
+    pub fn costs(&self) -> &DimVec<f64> {
+        self.costs
+            .as_ref()
+            .expect("Path's cost has to be calculated.")
+    }
+
+    /// Returns the path's costs paired with their metrics' ids and units, e.g. for looking them
+    /// up by id or displaying them.
+    /// Hot paths should prefer the positional `costs()`.
+    ///
+    /// ATTENTION! This method panics if the costs hasn't been calculated (e.g. `calc_cost(...)` or `flatten(...)`).
+    pub fn costs_view<'g>(&self, graph: &'g Graph) -> Costs<'g> {
+        Costs::new(self.costs(), graph)
+    }
+
+    /// Returns the path's cost of the given metric.
+    ///
+    /// ATTENTION! This method panics if the costs hasn't been calculated (e.g. `calc_cost(...)` or `flatten(...)`).
+    pub fn length(&self, metric_idx: MetricIdx) -> f64 {
+        self.costs()[*metric_idx]
+    }
+
+    /// Returns a hash of the flattened path's edges.
+    /// Hence, two paths with equal hashes describe the same route, even if one of them uses
+    /// shortcuts and the other doesn't.
+    ///
+    /// ATTENTION! This method panics if the costs hasn't been calculated (e.g. `calc_cost(...)` or `flatten(...)`).
+    pub fn flattened_hash(&self) -> u64 {
+        self.flattened_hash
+            .expect("Path's flattened hash is calculated with its cost.")
+    }
+
+    /// Calculates the path's cost (and flattened hash), but only if not calculated already.
+    pub fn calc_costs(&mut self, graph: &Graph) -> &DimVec<f64> {
+        if self.flattened_hash.is_none() {
+            self.flattened_hash = Some(Path::calc_flattened_hash(&self.edges, graph));
+        }
+        if self.costs.is_none() {
+            let edges = &self.edges;
+            self.costs = Some(
+                graph
+                    .metrics()
+                    .columns()
+                    .map(|(_metric_idx, column)| {
+                        edges.iter().map(|edge_idx| column[**edge_idx]).sum()
+                    })
+                    .collect(),
+            );
+        }
+        self.costs
+            .as_ref()
+            .expect("Costs have just been calculated.")
+    }
+
+    /// Flattens shortcuts, out-of-place, and calculates the flattened path's cost.
+    pub fn try_flatten(self, graph: &Graph) -> err::Result<Path> {
+        // setup new edges
+        let mut flattened_path = Path {
+            src_idx: self.src_idx,
+            src_id: self.src_id,
+            dst_idx: self.dst_idx,
+            dst_id: self.dst_id,
+            edges: Vec::with_capacity(self.edges.capacity()),
+            costs: Some(smallvec![0.0; graph.metrics().dim()]),
+            flattened_hash: None,
+        };
+
+        // interpret old edges as stack, beginning with src
+        let mut old_edges = self.edges;
+        old_edges.reverse();
+
+        let fwd_edges = graph.fwd_edges();
+        while let Some(mut edge_idx) = old_edges.pop() {
+            // if edge is shortcut
+            // -> push on old-edges
+            while let Some(sc_edges) = fwd_edges.sc_edges(edge_idx) {
+                old_edges.push(sc_edges[1]);
+                edge_idx = sc_edges[0];
+
+                // max path-length contains all edges in a graph
+                if old_edges.len() > fwd_edges.count() {
+                    return Err("There is a cycle of shortcut-references in the graph.".into());
+                }
+            }
+
+            // edge-idx is not a shortcut
+            // -> push to flattened path
+            flattened_path.edges.push(edge_idx);
+            helpers::add_assign(
+                flattened_path
+                    .costs
+                    .as_mut()
+                    .expect("Flattened path should have calculated costs."),
+                &graph.metrics()[edge_idx],
+            );
+        }
+
+        flattened_path.edges.shrink_to_fit();
+        flattened_path.flattened_hash =
+            Some(Path::calc_flattened_hash(&flattened_path.edges, graph));
+        Ok(flattened_path)
+    }
+
+    fn calc_flattened_hash(edges: &[EdgeIdx], graph: &Graph) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        // interpret edges as stack, beginning with src
+        let mut stack: Vec<EdgeIdx> = edges.iter().rev().cloned().collect();
+        let fwd_edges = graph.fwd_edges();
+        while let Some(edge_idx) = stack.pop() {
+            if let Some(sc_edges) = fwd_edges.sc_edges(edge_idx) {
+                // cycles of shortcut-references are reported when flattening
+                if stack.len() > fwd_edges.count() {
+                    break;
+                }
+                stack.push(sc_edges[1]);
+                stack.push(sc_edges[0]);
+            } else {
+                edge_idx.hash(&mut hasher);
+            }
+        }
+
+        hasher.finish()
+    }
+
+    pub fn flatten(self, graph: &Graph) -> Path {
+        match self.try_flatten(graph) {
+            Ok(path) => path,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Returns the path from dst to src, which uses the reverse edge of every (flattened) edge.
+    /// This is useful for symmetric graphs, e.g. for the return leg of a route.
+    ///
+    /// The reverse edge of `u -> v` is the fwd-edge `v -> u` (not the bwd-edge `u <- v`, which
+    /// is the same edge again).
+    /// Parallel reverse edges are resolved by taking the first one.
+    /// The costs are calculated from the reverse edges' metrics, since they may differ.
+    ///
+    /// Fails with the offending edge, if an edge has no reverse edge (e.g. a oneway).
+    pub fn reversed(&self, graph: &Graph) -> err::Result<Path> {
+        let flattened_path = self.clone().try_flatten(graph)?;
+
+        let fwd_edges = graph.fwd_edges();
+        let nodes = graph.nodes();
+        let mut edges = Vec::with_capacity(flattened_path.edges.len());
+        let mut src_idx = flattened_path.src_idx;
+        for &edge_idx in &flattened_path.edges {
+            let dst_idx = fwd_edges.dst_idx(edge_idx);
+            let reverse_edge = fwd_edges.starting_from(dst_idx).find(|leaving_edge| {
+                leaving_edge.dst_idx() == src_idx && !leaving_edge.is_shortcut()
+            });
+            match reverse_edge {
+                Some(reverse_edge) => edges.push(reverse_edge.idx()),
+                None => {
+                    return Err(format!(
+                        "The edge (idx: {}, id: {:?}) from node-id {} to node-id {} \
+                         has no reverse edge, e.g. because it's a oneway.",
+                        edge_idx,
+                        fwd_edges.try_id(edge_idx),
+                        nodes.id(src_idx),
+                        nodes.id(dst_idx)
+                    )
+                    .into())
+                }
+            }
+            src_idx = dst_idx;
+        }
+        edges.reverse();
+
+        let mut reversed_path = Path::new(
+            flattened_path.dst_idx,
+            flattened_path.dst_id,
+            flattened_path.src_idx,
+            flattened_path.src_id,
+            edges,
+        );
+        reversed_path.calc_costs(graph);
+        Ok(reversed_path)
+    }
+
+    /// Appends the other path, which has to start where this path ends, e.g. for stitching the
+    /// legs of a route.
+    ///
+    /// The merged edges are the concatenation of both paths' edges, hence shortcuts are kept.
+    /// The costs are the sum of both paths' costs.
+    ///
+    /// Fails, if the endpoints don't match or if the merged path would visit a node twice
+    /// (besides the shared node), since this would add a cycle.
+    pub fn merge(mut self, mut other: Path, graph: &Graph) -> err::Result<Path> {
+        if self.dst_idx != other.src_idx {
+            return Err(format!(
+                "Paths can't be merged, because the first one ends in node-id {}, \
+                 but the second one starts in node-id {}.",
+                self.dst_id, other.src_id
+            )
+            .into());
+        }
+
+        // Both paths are simple on their own, but may share nodes.
+
+        let flattened_nodes = |path: &Path| -> err::Result<Vec<NodeIdx>> {
+            let fwd_edges = graph.fwd_edges();
+            let mut nodes = vec![path.src_idx];
+            nodes.extend(
+                path.clone()
+                    .try_flatten(graph)?
+                    .iter()
+                    .map(|&edge_idx| fwd_edges.dst_idx(edge_idx)),
+            );
+            Ok(nodes)
+        };
+        let mut first_nodes = flattened_nodes(&self)?;
+        // the shared node is visited once
+        first_nodes.pop();
+        first_nodes.sort_unstable();
+        let mut repeated_ids: Vec<i64> = flattened_nodes(&other)?
+            .into_iter()
+            .filter(|node_idx| first_nodes.binary_search(node_idx).is_ok())
+            .map(|node_idx| graph.nodes().id(node_idx))
+            .collect();
+        if !repeated_ids.is_empty() {
+            repeated_ids.sort_unstable();
+            repeated_ids.dedup();
+            return Err(format!(
+                "Paths can't be merged, because the merged path would visit the node-ids {:?} \
+                 twice.",
+                repeated_ids
+            )
+            .into());
+        }
+
+        // merge
+
+        let mut costs = self.calc_costs(graph).clone();
+        helpers::add_assign(&mut costs, other.calc_costs(graph));
+        let mut edges = self.edges;
+        edges.append(&mut other.edges);
+        let flattened_hash = Path::calc_flattened_hash(&edges, graph);
+
+        Ok(Path {
+            src_idx: self.src_idx,
+            src_id: self.src_id,
+            dst_idx: other.dst_idx,
+            dst_id: other.dst_id,
+            edges,
+            costs: Some(costs),
+            flattened_hash: Some(flattened_hash),
+        })
+    }
+
+    /// Returns the coordinate, which is reached after the given distance along the path.
+    ///
+    /// Edge-lengths are taken from the graph's metric of unit `Kilometers` (or `Meters`).
+    /// Within an edge, the coordinate is interpolated linearly between the edge's nodes,
+    /// since edges don't have a geometry.
+    /// Distances beyond the path's length result in the dst's coordinate.
+    pub fn try_point_at(&self, distance: Kilometers, graph: &Graph) -> err::Result<Coordinate> {
+        if !(*distance >= 0.0) {
+            return Err(format!(
+                "The distance along a path has to be non-negative, but is {} km.",
+                *distance
+            )
+            .into());
+        }
+
+        let units = &graph.cfg().edges.metrics.units;
+        let (metric_idx, km_per_unit) =
+            if let Some(idx) = units.iter().position(|unit| unit == &UnitInfo::Kilometers) {
+                (MetricIdx(idx), 1.0)
+            } else if let Some(idx) = units.iter().position(|unit| unit == &UnitInfo::Meters) {
+                (MetricIdx(idx), 0.001)
+            } else {
+                return Err("The graph doesn't have a metric of unit Kilometers or Meters.".into());
+            };
+        let metrics = graph.metrics();
+        // metrics could be normalized
+        let km_per_value = km_per_unit * metrics.mean(metric_idx).unwrap_or(1.0);
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+
+        let mut remaining_km = *distance;
+        for edge_idx in self.clone().try_flatten(graph)? {
+            let edge_km = metrics.column(metric_idx)[*edge_idx] * km_per_value;
+
+            if remaining_km <= edge_km {
+                let src_coord = nodes.coord(bwd_edges.dst_idx(edge_idx));
+                let dst_coord = nodes.coord(fwd_edges.dst_idx(edge_idx));
+                let t = if edge_km > 0.0 {
+                    remaining_km / edge_km
+                } else {
+                    0.0
+                };
+                return Ok(Coordinate {
+                    lat: src_coord.lat + t * (dst_coord.lat - src_coord.lat),
+                    lon: src_coord.lon + t * (dst_coord.lon - src_coord.lon),
+                });
+            }
+
+            remaining_km -= edge_km;
+        }
+
+        Ok(nodes.coord(self.dst_idx))
+    }
+
+    /// Like `try_point_at(...)`, but returns `None` in case of an error.
+    pub fn point_at(&self, distance: Kilometers, graph: &Graph) -> Option<Coordinate> {
+        self.try_point_at(distance, graph).ok()
+    }
+
+    /// Returns the cumulative costs `(x, y)` of both metrics along the flattened path,
+    /// starting with `(0, 0)` at the src and adding one point after every edge,
+    /// e.g. for plotting the duration over the distance.
+    ///
+    /// Edges without cost in `metric_x` are merged into the previous point, so x-values are unique.
+    /// Costs are the graph's stored values, hence normalized metrics are not denormalized
+    /// (in contrast to `io::routing::cost_profile::Writer`).
+    pub fn try_cost_profile(
+        &self,
+        metric_x: MetricIdx,
+        metric_y: MetricIdx,
+        graph: &Graph,
+    ) -> err::Result<Vec<(f64, f64)>> {
+        let metrics = graph.metrics();
+        let flattened_path = self.clone().try_flatten(graph)?;
+
+        let mut profile = Vec::with_capacity(flattened_path.edges.len() + 1);
+        let (mut x, mut y) = (0.0, 0.0);
+        profile.push((x, y));
+        for edge_idx in flattened_path {
+            let edge_x = metrics.column(metric_x)[*edge_idx];
+            x += edge_x;
+            y += metrics.column(metric_y)[*edge_idx];
+
+            if edge_x == 0.0 {
+                let last = profile.last_mut().expect("Profile starts with the src.");
+                last.1 = y;
+            } else {
+                profile.push((x, y));
+            }
+        }
+
+        Ok(profile)
+    }
+
+    pub fn cost_profile(
+        &self,
+        metric_x: MetricIdx,
+        metric_y: MetricIdx,
+        graph: &Graph,
+    ) -> Vec<(f64, f64)> {
+        match self.try_cost_profile(metric_x, metric_y, graph) {
+            Ok(profile) => profile,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+/// Describes how an alternative path differs from an optimal path, e.g. for presenting
+/// alternatives nicely.
+#[derive(Clone, Debug)]
+pub struct PathComparison {
+    /// Per metric, the alternative's cost divided by the optimal cost (detour-factor)
+    pub cost_ratios: DimVec<f64>,
+    /// Fraction of the alternative's flattened edges, which are part of the optimal path
+    pub shared_edge_fraction: f64,
+    /// Maximum distance of any alternative's node to the optimal path's nodes
+    pub max_divergence: Meters,
+    /// Mean distance of the alternative's nodes to the optimal path's nodes
+    pub mean_divergence: Meters,
+}
+
+impl Display for PathComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ cost-ratios: {:?}, shared-edges: {:.1} %, divergence: (max: {:.1} m, mean: {:.1} m) }}",
+            self.cost_ratios,
+            100.0 * self.shared_edge_fraction,
+            self.max_divergence.0,
+            self.mean_divergence.0,
+        )
+    }
+}
+
+/// Compares an alternative path with an optimal path, after flattening both.
+///
+/// Identical paths result in cost-ratios of 1.0, a shared-edge-fraction of 1.0 and no divergence.
+/// If the optimal cost of a metric is 0.0, the cost-ratio is 1.0 for an alternative cost of 0.0
+/// and infinity otherwise.
+pub fn compare(optimal: &Path, alternative: &Path, graph: &Graph) -> PathComparison {
+    let optimal = optimal.clone().flatten(graph);
+    let alternative = alternative.clone().flatten(graph);
+
+    // detour-factors
+
+    let cost_ratios = optimal
+        .costs()
+        .iter()
+        .zip(alternative.costs().iter())
+        .map(|(&optimal_cost, &alternative_cost)| {
+            if Approx(optimal_cost) == Approx(0.0) {
+                if Approx(alternative_cost) == Approx(0.0) {
+                    1.0
+                } else {
+                    std::f64::INFINITY
+                }
+            } else {
+                alternative_cost / optimal_cost
+            }
+        })
+        .collect();
+
+    // shared edges
+
+    let shared_edge_fraction = if alternative.edges.is_empty() {
+        1.0
+    } else {
+        let mut optimal_edges = optimal.edges.clone();
+        optimal_edges.sort();
+        let shared_count = alternative
+            .iter()
+            .filter(|edge_idx| optimal_edges.binary_search(edge_idx).is_ok())
+            .count();
+        shared_count as f64 / alternative.edges.len() as f64
+    };
+
+    // geometric divergence
+
+    let nodes = graph.nodes();
+    let optimal_coords: Vec<_> = optimal
+        .node_indices(graph)
+        .into_iter()
+        .map(|node_idx| nodes.coord(node_idx))
+        .collect();
+    let divergences: Vec<f64> = alternative
+        .node_indices(graph)
+        .into_iter()
+        .map(|node_idx| {
+            let coord = nodes.coord(node_idx);
+            optimal_coords
+                .iter()
+                .map(|optimal_coord| *geo::haversine_distance_km(&coord, optimal_coord) * 1_000.0)
+                .fold(std::f64::INFINITY, f64::min)
+        })
+        .collect();
+    let max_divergence = divergences.iter().cloned().fold(0.0, f64::max);
+    let mean_divergence = divergences.iter().sum::<f64>() / divergences.len() as f64;
+
+    PathComparison {
+        cost_ratios,
+        shared_edge_fraction,
+        max_divergence: Meters(max_divergence),
+        mean_divergence: Meters(mean_divergence),
+    }
+}
+
+impl Eq for Path {}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Path) -> bool {
+        // length before edges and edges last because of performance
+        self.src_id == other.src_id
+            && self.dst_id == other.dst_id
+            // length is compared by comparing Vecs
+            // -> see libcore in rust-lang/rust
+            // && self.edges.len() == other.edges.len()
+            && self.edges == other.edges
+    }
+}
+
+impl IntoIterator for Path {
+    type Item = EdgeIdx;
+    type IntoIter = std::vec::IntoIter<EdgeIdx>;
+
+    fn into_iter(self) -> std::vec::IntoIter<EdgeIdx> {
+        self.edges.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Path {
+    type Item = &'a EdgeIdx;
+    type IntoIter = std::slice::Iter<'a, EdgeIdx>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, EdgeIdx> {
+        self.edges.iter()
+    }
+}
+
+impl Path {
+    pub fn iter(&self) -> std::slice::Iter<'_, EdgeIdx> {
+        self.edges.iter()
+    }
+
+    /// Returns the visited nodes, beginning with src and ending with dst.
+    ///
+    /// For unflattened paths, nodes skipped by shortcuts are not returned.
+    pub fn node_indices(&self, graph: &Graph) -> Vec<NodeIdx> {
+        let fwd_edges = graph.fwd_edges();
+
+        let mut node_indices = Vec::with_capacity(self.edges.len() + 1);
+        node_indices.push(self.src_idx);
+        for &edge_idx in &self.edges {
+            node_indices.push(fwd_edges.dst_idx(edge_idx));
+        }
+        node_indices
+    }
+}
+
+// pub struct PathIntoIter(std::vec::IntoIter<EdgeIdx>);
+//
+// impl Iterator for PathIntoIter {
+//     type Item = EdgeIdx;
+//
+//     fn next(&mut self) -> Option<EdgeIdx> {
+//         self.0.next()
+//     }
+// }
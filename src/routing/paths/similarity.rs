@@ -0,0 +1,91 @@
+use super::Path;
+use crate::network::Graph;
+use kissunits::geo::{self, Coordinate};
+
+/// Returns the node-coordinates of the flattened path, beginning with src and ending with dst.
+pub fn polyline(path: &Path, graph: &Graph) -> Vec<Coordinate> {
+    let nodes = graph.nodes();
+    path.clone()
+        .flatten(graph)
+        .node_indices(graph)
+        .into_iter()
+        .map(|node_idx| nodes.coord(node_idx))
+        .collect()
+}
+
+/// Discrete Fréchet-distance in meters between the node-coordinates of both flattened paths.
+pub fn frechet(path_a: &Path, path_b: &Path, graph: &Graph) -> f64 {
+    discrete_frechet(&polyline(path_a, graph), &polyline(path_b, graph))
+}
+
+/// Hausdorff-distance in meters between the node-coordinates of both flattened paths.
+pub fn hausdorff(path_a: &Path, path_b: &Path, graph: &Graph) -> f64 {
+    discrete_hausdorff(&polyline(path_a, graph), &polyline(path_b, graph))
+}
+
+/// Discrete Fréchet-distance in meters via dynamic programming in `O(n*m)`.
+///
+/// If a polyline is empty, the distance is infinite (or zero, if both are empty).
+pub fn discrete_frechet(polyline_a: &[Coordinate], polyline_b: &[Coordinate]) -> f64 {
+    if polyline_a.is_empty() || polyline_b.is_empty() {
+        return empty_distance(polyline_a, polyline_b);
+    }
+
+    // Only the previous row is needed.
+    // couplings[j] is the Fréchet-distance of a[..=i] and b[..=j]
+    let m = polyline_b.len();
+    let mut prev_couplings = vec![std::f64::INFINITY; m];
+    let mut couplings = vec![std::f64::INFINITY; m];
+    for (i, coord_a) in polyline_a.iter().enumerate() {
+        for (j, coord_b) in polyline_b.iter().enumerate() {
+            let dist = meters_between(coord_a, coord_b);
+            let predecessor = match (i, j) {
+                (0, 0) => 0.0,
+                (0, _) => couplings[j - 1],
+                (_, 0) => prev_couplings[0],
+                (_, _) => prev_couplings[j]
+                    .min(prev_couplings[j - 1])
+                    .min(couplings[j - 1]),
+            };
+            couplings[j] = predecessor.max(dist);
+        }
+        std::mem::swap(&mut prev_couplings, &mut couplings);
+    }
+
+    prev_couplings[m - 1]
+}
+
+/// Hausdorff-distance in meters, so the maximum distance of any coordinate to the other polyline.
+///
+/// If a polyline is empty, the distance is infinite (or zero, if both are empty).
+pub fn discrete_hausdorff(polyline_a: &[Coordinate], polyline_b: &[Coordinate]) -> f64 {
+    if polyline_a.is_empty() || polyline_b.is_empty() {
+        return empty_distance(polyline_a, polyline_b);
+    }
+
+    directed_hausdorff(polyline_a, polyline_b).max(directed_hausdorff(polyline_b, polyline_a))
+}
+
+fn directed_hausdorff(polyline_a: &[Coordinate], polyline_b: &[Coordinate]) -> f64 {
+    polyline_a
+        .iter()
+        .map(|coord_a| {
+            polyline_b
+                .iter()
+                .map(|coord_b| meters_between(coord_a, coord_b))
+                .fold(std::f64::INFINITY, f64::min)
+        })
+        .fold(0.0, f64::max)
+}
+
+fn empty_distance(polyline_a: &[Coordinate], polyline_b: &[Coordinate]) -> f64 {
+    if polyline_a.is_empty() && polyline_b.is_empty() {
+        0.0
+    } else {
+        std::f64::INFINITY
+    }
+}
+
+fn meters_between(coord_a: &Coordinate, coord_b: &Coordinate) -> f64 {
+    *geo::haversine_distance_km(coord_a, coord_b) * 1_000.0
+}
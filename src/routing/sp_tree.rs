@@ -0,0 +1,177 @@
+use super::paths::Path;
+use crate::{
+    defaults::routing::sp_tree as defaults,
+    helpers::err,
+    io,
+    network::{EdgeIdx, Graph, NodeIdx},
+};
+use serde_json::json;
+use std::{cmp::Ordering, io::Write, path};
+
+/// The shortest-path-tree of a src-node after a completed forward-search (see
+/// `Dijkstra::shortest_path_tree(...)`), storing every reached node's parent-edge and cost.
+///
+/// Nodes are accessed by their indices, where unreached nodes have neither a parent-edge nor a
+/// cost, and the src-node has cost 0, but no parent-edge.
+#[derive(Clone, Debug)]
+pub struct SpTree {
+    src_idx: NodeIdx,
+    parent_edges: Vec<Option<EdgeIdx>>,
+    costs: Vec<Option<f64>>,
+}
+
+impl SpTree {
+    pub(crate) fn new(
+        src_idx: NodeIdx,
+        parent_edges: Vec<Option<EdgeIdx>>,
+        costs: Vec<Option<f64>>,
+    ) -> SpTree {
+        SpTree {
+            src_idx,
+            parent_edges,
+            costs,
+        }
+    }
+
+    pub fn src_idx(&self) -> NodeIdx {
+        self.src_idx
+    }
+
+    /// Returns the edge, which the node is reached by, or `None` for the src-node and unreached
+    /// nodes.
+    pub fn parent_edge(&self, node_idx: NodeIdx) -> Option<EdgeIdx> {
+        self.parent_edges
+            .get(*node_idx)
+            .and_then(|&edge_idx| edge_idx)
+    }
+
+    /// Returns the node's cost (weighted by the routing-config's alphas), or `None` for unreached
+    /// nodes.
+    pub fn cost(&self, node_idx: NodeIdx) -> Option<f64> {
+        self.costs.get(*node_idx).and_then(|&cost| cost)
+    }
+
+    pub fn is_reached(&self, node_idx: NodeIdx) -> bool {
+        self.cost(node_idx).is_some()
+    }
+
+    /// Returns the number of reached nodes, including the src-node.
+    pub fn reached_count(&self) -> usize {
+        self.costs.iter().filter(|cost| cost.is_some()).count()
+    }
+
+    /// Returns the tree's edges, ordered by the indices of the nodes they reach.
+    pub fn edges(&self) -> impl Iterator<Item = EdgeIdx> + '_ {
+        self.parent_edges.iter().flatten().cloned()
+    }
+
+    /// Returns the path from the src-node to the given node by walking the parent-edges back, or
+    /// `None`, if the node is unreached.
+    pub fn path_to(&self, dst_idx: NodeIdx, graph: &Graph) -> Option<Path> {
+        if !self.is_reached(dst_idx) {
+            return None;
+        }
+
+        let bwd_edges = graph.bwd_edges();
+        let mut edges = Vec::new();
+        let mut cur_idx = dst_idx;
+        while let Some(edge_idx) = self.parent_edge(cur_idx) {
+            edges.push(edge_idx);
+            // reversed edge -> src-node
+            cur_idx = bwd_edges.dst_idx(edge_idx);
+        }
+        edges.reverse();
+
+        let nodes = graph.nodes();
+        let mut path = Path::new(
+            self.src_idx,
+            nodes.id(self.src_idx),
+            dst_idx,
+            nodes.id(dst_idx),
+            edges,
+        );
+        path.calc_costs(graph);
+        Some(path)
+    }
+
+    /// Returns the tree as GeoJSON-FeatureCollection, where every tree-edge is a LineString with
+    /// the properties `src-id`, `dst-id`, `cost` (of the reached node), `quantile` and `stroke`.
+    ///
+    /// The reached nodes' costs are split into equally sized quantiles, one per color of
+    /// `defaults::routing::sp_tree::COLORS`, and every edge is colored (`stroke`) by the quantile
+    /// of the node it reaches, so cheap edges are green and expensive edges are red.
+    pub fn to_geojson(&self, graph: &Graph) -> serde_json::Value {
+        let mut sorted_costs: Vec<f64> = self.costs.iter().flatten().cloned().collect();
+        sorted_costs.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let nodes = graph.nodes();
+        let bwd_edges = graph.bwd_edges();
+        let features: Vec<_> = (0..self.parent_edges.len())
+            .map(NodeIdx)
+            .filter_map(|dst_idx| {
+                let edge_idx = self.parent_edge(dst_idx)?;
+                let cost = self.cost(dst_idx)?;
+                let src_idx = bwd_edges.dst_idx(edge_idx);
+                let (src_coord, dst_coord) = (nodes.coord(src_idx), nodes.coord(dst_idx));
+                let quantile = quantile_of(cost, &sorted_costs);
+
+                Some(json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "LineString",
+                        "coordinates": [
+                            [src_coord.lon, src_coord.lat],
+                            [dst_coord.lon, dst_coord.lat]
+                        ]
+                    },
+                    "properties": {
+                        "src-id": nodes.id(src_idx),
+                        "dst-id": nodes.id(dst_idx),
+                        "cost": cost,
+                        "quantile": quantile,
+                        "stroke": defaults::COLORS[quantile]
+                    }
+                }))
+            })
+            .collect();
+
+        json!({
+            "type": "FeatureCollection",
+            "features": features
+        })
+    }
+
+    /// Writes the tree as GeoJSON (see `to_geojson(...)`) into a new file.
+    pub fn write_geojson<P: AsRef<path::Path> + ?Sized>(
+        &self,
+        graph: &Graph,
+        file: &P,
+    ) -> err::Feedback {
+        let mut writer = io::files::AtomicFile::create(file)?;
+        let geojson = serde_json::to_string_pretty(&self.to_geojson(graph)).map_err(|e| {
+            err::Msg::from(format!(
+                "Serde couldn't serialize the shortest-path-tree due to error: {}",
+                e
+            ))
+        })?;
+        writeln!(writer, "{}", geojson)?;
+        writer.commit()
+    }
+}
+
+/// Returns the quantile (as index of the colors), whose range contains the given cost, where
+/// equal costs share their quantile.
+fn quantile_of(cost: f64, sorted_costs: &[f64]) -> usize {
+    // number of cheaper costs
+    let rank = match sorted_costs.binary_search_by(|probe| {
+        if *probe < cost {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }) {
+        Ok(rank) | Err(rank) => rank,
+    };
+    let quantile = rank * defaults::COLORS.len() / sorted_costs.len().max(1);
+    quantile.min(defaults::COLORS.len() - 1)
+}
@@ -0,0 +1,168 @@
+use crate::{
+    network::{EdgeIdx, Graph},
+    routing::paths::Path,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Remembers the edges, which every route-pair `(src-id, dst-id)` has used, e.g. in the previous
+/// iteration of the balancer.
+///
+/// Edges are remembered by their nodes' ids `(src-id, dst-id)`, since edge-indices change with
+/// every newly parsed (or contracted) graph.
+#[derive(Clone, Debug, Default)]
+pub struct RouteMemory {
+    edges: BTreeMap<(i64, i64), BTreeSet<(i64, i64)>>,
+}
+
+impl RouteMemory {
+    pub fn new() -> RouteMemory {
+        RouteMemory::default()
+    }
+
+    /// Adds the path's edges to its route-pair, so several paths per route-pair are united.
+    ///
+    /// The path should be flattened, since shortcuts are remembered by their nodes as well.
+    pub fn remember(&mut self, path: &Path, graph: &Graph) {
+        let nodes = graph.nodes();
+        let node_idxs = path.node_indices(graph);
+        self.edges
+            .entry((path.src_id(), path.dst_id()))
+            .or_insert_with(BTreeSet::new)
+            .extend(
+                node_idxs
+                    .windows(2)
+                    .map(|w| (nodes.id(w[0]), nodes.id(w[1]))),
+            );
+    }
+
+    pub fn edges_of(&self, src_id: i64, dst_id: i64) -> Option<&BTreeSet<(i64, i64)>> {
+        self.edges.get(&(src_id, dst_id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+
+/// Creates the edge-bias of queries (see `dijkstra::Query::edge_bias`), giving every remembered
+/// edge of the query's route-pair a bonus (as negative bias).
+///
+/// Shortcuts get the sum of their replaced edges' biases, so contracted graphs are supported.
+/// The bias is reused for every query and only the previously biased edges are reset.
+#[derive(Clone, Debug)]
+pub struct EdgeBias {
+    bonus: f64,
+    values: Vec<f64>,
+    biased_edges: Vec<EdgeIdx>,
+    /// shortcuts, which replace the edge directly
+    parents: Vec<Vec<EdgeIdx>>,
+}
+
+impl EdgeBias {
+    pub fn new(bonus: f64, graph: &Graph) -> EdgeBias {
+        let fwd_edges = graph.fwd_edges();
+        let mut parents = vec![Vec::new(); fwd_edges.count()];
+        for edge_idx in fwd_edges.iter() {
+            if let Some(sc_edges) = fwd_edges.sc_edges(edge_idx) {
+                for child_idx in sc_edges {
+                    parents[**child_idx].push(edge_idx);
+                }
+            }
+        }
+
+        EdgeBias {
+            bonus,
+            values: vec![0.0; fwd_edges.count()],
+            biased_edges: Vec::new(),
+            parents,
+        }
+    }
+
+    /// Returns None, if the route-pair isn't remembered.
+    pub fn of(
+        &mut self,
+        src_id: i64,
+        dst_id: i64,
+        memory: &RouteMemory,
+        graph: &Graph,
+    ) -> Option<&[f64]> {
+        for edge_idx in self.biased_edges.drain(..) {
+            self.values[*edge_idx] = 0.0;
+        }
+
+        let remembered_edges = memory.edges_of(src_id, dst_id)?;
+
+        // bias remembered edges, which are no shortcuts
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        for &(edge_src_id, edge_dst_id) in remembered_edges {
+            let (edge_src_idx, edge_dst_idx) =
+                match (nodes.idx_from(edge_src_id), nodes.idx_from(edge_dst_id)) {
+                    (Ok(edge_src_idx), Ok(edge_dst_idx)) => (edge_src_idx, edge_dst_idx),
+                    // the edge doesn't exist anymore
+                    _ => continue,
+                };
+            for leaving_edge in fwd_edges.starting_from(edge_src_idx) {
+                if leaving_edge.dst_idx() == edge_dst_idx
+                    && !fwd_edges.is_shortcut(leaving_edge.idx())
+                {
+                    self.values[*leaving_edge.idx()] -= self.bonus;
+                    self.biased_edges.push(leaving_edge.idx());
+                }
+            }
+        }
+
+        // collect all shortcuts replacing biased edges
+
+        let mut shortcuts = BTreeSet::new();
+        let mut stack = self.biased_edges.clone();
+        while let Some(edge_idx) = stack.pop() {
+            for &parent_idx in &self.parents[*edge_idx] {
+                if shortcuts.insert(parent_idx) {
+                    stack.push(parent_idx);
+                }
+            }
+        }
+
+        // and sum up their replaced edges' biases
+
+        let mut summed = BTreeSet::new();
+        for &sc_idx in &shortcuts {
+            sum_up(sc_idx, &shortcuts, &mut summed, &mut self.values, graph);
+        }
+        self.biased_edges.extend(shortcuts);
+
+        Some(&self.values)
+    }
+}
+
+/// Sums up the shortcut's replaced edges' biases recursively, where shortcuts not replacing any
+/// biased edge have no bias.
+fn sum_up(
+    edge_idx: EdgeIdx,
+    shortcuts: &BTreeSet<EdgeIdx>,
+    summed: &mut BTreeSet<EdgeIdx>,
+    values: &mut [f64],
+    graph: &Graph,
+) -> f64 {
+    if !shortcuts.contains(&edge_idx) || summed.contains(&edge_idx) {
+        return values[*edge_idx];
+    }
+
+    let fwd_edges = graph.fwd_edges();
+    let sc_edges = *fwd_edges
+        .sc_edges(edge_idx)
+        .expect("Only shortcuts should be summed up.");
+    let value = sc_edges
+        .iter()
+        .map(|&child_idx| sum_up(child_idx, shortcuts, summed, values, graph))
+        .sum();
+    values[*edge_idx] = value;
+    summed.insert(edge_idx);
+    value
+}
@@ -0,0 +1,171 @@
+use super::paths::Path;
+use crate::{
+    approximating::Approx,
+    network::{EdgeIdx, Graph, MetricIdx, NodeIdx},
+};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+/// All metrics (duration and availability-window) are expected in the same time-unit, e.g. hours.
+#[derive(Copy, Clone)]
+pub struct Query<'a> {
+    pub src_idx: NodeIdx,
+    pub dst_idx: NodeIdx,
+    pub graph: &'a Graph,
+    pub duration_idx: MetricIdx,
+    /// Edges can't be entered before this point in time.
+    pub open_from_idx: MetricIdx,
+    /// Edges can't be entered after this point in time.
+    pub open_until_idx: MetricIdx,
+    /// Point in time, when leaving the src
+    pub departure: f64,
+    /// If true, an edge being entered before its opening is waited for.
+    /// Otherwise, the edge can't be used.
+    pub is_waiting_allowed: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Arrival {
+    /// Point in time, when reaching the dst
+    pub time: f64,
+    /// ATTENTION! The path's costs are not calculated, since they are sums of metrics and not arrival-times.
+    pub path: Path,
+}
+
+/// Computes the earliest arrival at the dst, where every edge can only be entered within its availability-window `[open-from, open-until]`.
+/// Hence costs are arrival-times instead of summed up metrics.
+///
+/// None means no path exists, e.g. because every path has a closed edge.
+///
+/// ATTENTION!
+/// Every node stores its earliest arrival only.
+/// Without waiting, a later arrival at a node could reach edges, which are closed for the earliest arrival.
+/// Such paths are not found.
+/// Further, shortcuts are treated as edges, so the graph should not be contracted.
+pub fn earliest_arrival(query: Query) -> Option<Arrival> {
+    let nodes = query.graph.nodes();
+    let fwd_edges = query.graph.fwd_edges();
+    let bwd_edges = query.graph.bwd_edges();
+    let metrics = query.graph.metrics();
+
+    // metrics could be normalized
+    let denormalize = |metric_idx: MetricIdx, value: f64| -> f64 {
+        value * metrics.mean(metric_idx).unwrap_or(1.0)
+    };
+
+    let mut arrivals = vec![std::f64::INFINITY; nodes.count()];
+    let mut predecessors: Vec<Option<EdgeIdx>> = vec![None; nodes.count()];
+    let mut queue = BinaryHeap::new();
+
+    arrivals[*query.src_idx] = query.departure;
+    queue.push(Reverse(TimeNode {
+        idx: query.src_idx,
+        time: query.departure,
+    }));
+
+    while let Some(Reverse(current)) = queue.pop() {
+        // node has already been visited with an earlier arrival
+        if current.time > arrivals[*current.idx] {
+            continue;
+        }
+        if current.idx == query.dst_idx {
+            break;
+        }
+
+        for leaving_edge in fwd_edges.starting_from(current.idx) {
+            let edge_idx = *leaving_edge.idx();
+            let open_from = denormalize(
+                query.open_from_idx,
+                metrics.column(query.open_from_idx)[edge_idx],
+            );
+            let open_until = denormalize(
+                query.open_until_idx,
+                metrics.column(query.open_until_idx)[edge_idx],
+            );
+
+            let entry = if current.time < open_from {
+                if query.is_waiting_allowed {
+                    open_from
+                } else {
+                    continue;
+                }
+            } else {
+                current.time
+            };
+            if entry > open_until {
+                continue;
+            }
+
+            let new_arrival = entry
+                + denormalize(
+                    query.duration_idx,
+                    metrics.column(query.duration_idx)[edge_idx],
+                );
+            if new_arrival < arrivals[*leaving_edge.dst_idx()] {
+                arrivals[*leaving_edge.dst_idx()] = new_arrival;
+                predecessors[*leaving_edge.dst_idx()] = Some(leaving_edge.idx());
+                queue.push(Reverse(TimeNode {
+                    idx: leaving_edge.dst_idx(),
+                    time: new_arrival,
+                }));
+            }
+        }
+    }
+
+    let time = arrivals[*query.dst_idx];
+    if time == std::f64::INFINITY {
+        return None;
+    }
+
+    // iterate backwards over path
+    let mut proto_path = Vec::new();
+    let mut cur_idx = query.dst_idx;
+    while let Some(incoming_idx) = predecessors[*cur_idx] {
+        proto_path.push(incoming_idx);
+
+        // get incoming edge, but reversed to get the forward's src-node
+        cur_idx = bwd_edges.dst_idx(incoming_idx);
+    }
+    proto_path.reverse();
+
+    Some(Arrival {
+        time,
+        path: Path::new(
+            query.src_idx,
+            nodes.id(query.src_idx),
+            query.dst_idx,
+            nodes.id(query.dst_idx),
+            proto_path,
+        ),
+    })
+}
+
+#[derive(Clone)]
+struct TimeNode {
+    idx: NodeIdx,
+    time: f64,
+}
+
+impl Ord for TimeNode {
+    fn cmp(&self, other: &TimeNode) -> Ordering {
+        Approx(self.time)
+            .cmp(&Approx(other.time))
+            .then_with(|| self.idx.cmp(&other.idx))
+    }
+}
+
+impl PartialOrd for TimeNode {
+    fn partial_cmp(&self, other: &TimeNode) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Eq for TimeNode {}
+
+impl PartialEq for TimeNode {
+    fn eq(&self, other: &TimeNode) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
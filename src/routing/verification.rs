@@ -0,0 +1,298 @@
+use crate::{
+    configs::{
+        parsing::edges::metrics::UnitInfo,
+        routing::{Config, RoutingAlgo},
+    },
+    helpers::err,
+    network::{Graph, MetricIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+use log::info;
+use serde::Deserialize;
+use std::{
+    fmt::{self, Display},
+    fs::OpenOptions,
+    path::Path,
+};
+
+/// Costs of a route, as far as they are known.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Costs {
+    pub kilometers: Option<f64>,
+    pub hours: Option<f64>,
+}
+
+/// The result of routing one pair of the reference-file.
+///
+/// Deviations are relative to the expected costs, e.g. `0.1` means the found route is 10 % more expensive than expected.
+#[derive(Clone, Debug)]
+pub struct PairVerification {
+    pub src_id: i64,
+    pub dst_id: i64,
+    pub expected: Costs,
+    pub actual: Costs,
+    pub deviations: Costs,
+    pub is_passed: bool,
+}
+
+impl Display for PairVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{ src-id: {}, dst-id: {}, passed: {}, expected: {:?}, actual: {:?}, deviations: {:?} }}",
+            self.src_id, self.dst_id, self.is_passed, self.expected, self.actual, self.deviations
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VerificationReport {
+    pub tolerance: f64,
+    /// All pairs, which could be routed.
+    pub pairs: Vec<PairVerification>,
+    /// Pairs `(src-id, dst-id)`, where at least one id is not in the graph.
+    pub unknown_ids: Vec<(i64, i64)>,
+    /// Pairs `(src-id, dst-id)`, where no route exists.
+    pub unreachable: Vec<(i64, i64)>,
+}
+
+impl VerificationReport {
+    pub fn passed_count(&self) -> usize {
+        self.pairs.iter().filter(|pair| pair.is_passed).count()
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.pairs.iter().filter(|pair| !pair.is_passed).count()
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &PairVerification> {
+        self.pairs.iter().filter(|pair| !pair.is_passed)
+    }
+}
+
+impl Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Verified {} route(s) with tolerance {}: {} passed, {} failed, {} with unknown ids, {} unreachable",
+            self.pairs.len() + self.unknown_ids.len() + self.unreachable.len(),
+            self.tolerance,
+            self.passed_count(),
+            self.failed_count(),
+            self.unknown_ids.len(),
+            self.unreachable.len()
+        )?;
+        for pair in self.failed() {
+            writeln!(f, "  failed: {}", pair)?;
+        }
+        for (src_id, dst_id) in &self.unknown_ids {
+            writeln!(f, "  unknown ids: {} -> {}", src_id, dst_id)?;
+        }
+        for (src_id, dst_id) in &self.unreachable {
+            writeln!(f, "  unreachable: {} -> {}", src_id, dst_id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Routes every pair of the provided reference-file (e.g. a dump of OSRM or Valhalla) and compares the found costs with the expected ones.
+///
+/// The reference-file is a json-array like
+///
+/// ```json
+/// [
+///   { "src_id": 26033921, "dst_id": 2933335353, "expected": { "kilometers": 48.0, "hours": 0.52 } }
+/// ]
+/// ```
+///
+/// where both expected costs are optional.
+/// A pair passes, if every expected cost deviates at most by the relative `tolerance`.
+/// Unknown ids and unreachable pairs don't fail the verification, but are reported separately.
+pub fn verify<P: AsRef<Path> + ?Sized>(
+    reference_file: &P,
+    graph: &Graph,
+    routing_cfg: &Config,
+    tolerance: f64,
+) -> err::Result<VerificationReport> {
+    let reference_file = reference_file.as_ref();
+    info!("START Verify routes of {}", reference_file.display());
+
+    match routing_cfg.routing_algo {
+        RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => (),
+        #[cfg(feature = "gpl")]
+        RoutingAlgo::Explorator { algo: _ } => {
+            return Err(err::Msg::from(
+                "Verifying routes needs a routing-algorithm returning one best path.",
+            ))
+        }
+    }
+
+    let references = read_references(reference_file)?;
+
+    let mut report = VerificationReport {
+        tolerance,
+        pairs: Vec::new(),
+        unknown_ids: Vec::new(),
+        unreachable: Vec::new(),
+    };
+    if references.is_empty() {
+        info!("FINISHED");
+        return Ok(report);
+    }
+
+    let km_metric = if references
+        .iter()
+        .any(|reference| reference.expected.kilometers.is_some())
+    {
+        Some(find_metric(
+            graph,
+            &[UnitInfo::Kilometers, UnitInfo::Meters],
+        )?)
+    } else {
+        None
+    };
+    let h_metric = if references
+        .iter()
+        .any(|reference| reference.expected.hours.is_some())
+    {
+        Some(find_metric(
+            graph,
+            &[UnitInfo::Hours, UnitInfo::Minutes, UnitInfo::Seconds],
+        )?)
+    } else {
+        None
+    };
+
+    let nodes = graph.nodes();
+    let metrics = graph.metrics();
+    let mut dijkstra = Dijkstra::new();
+    for reference in references {
+        let (src_idx, dst_idx) = match (
+            nodes.idx_from(reference.src_id),
+            nodes.idx_from(reference.dst_id),
+        ) {
+            (Ok(src_idx), Ok(dst_idx)) => (src_idx, dst_idx),
+            _ => {
+                report
+                    .unknown_ids
+                    .push((reference.src_id, reference.dst_id));
+                continue;
+            }
+        };
+
+        let path = match dijkstra.compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::ExpectLong,
+        }) {
+            Some(path) => path.try_flatten(graph)?,
+            None => {
+                report
+                    .unreachable
+                    .push((reference.src_id, reference.dst_id));
+                continue;
+            }
+        };
+
+        // metrics could be normalized
+        let actual_cost = |metric: Option<(MetricIdx, &UnitInfo)>, to: UnitInfo| {
+            metric.map(|(metric_idx, unit)| {
+                let value = path.costs()[*metric_idx] * metrics.mean(metric_idx).unwrap_or(1.0);
+                unit.convert(&to, value)
+            })
+        };
+        let actual = Costs {
+            kilometers: actual_cost(km_metric, UnitInfo::Kilometers),
+            hours: actual_cost(h_metric, UnitInfo::Hours),
+        };
+        let expected = Costs {
+            kilometers: reference.expected.kilometers,
+            hours: reference.expected.hours,
+        };
+        let deviations = Costs {
+            kilometers: deviation(expected.kilometers, actual.kilometers),
+            hours: deviation(expected.hours, actual.hours),
+        };
+        let is_passed = [deviations.kilometers, deviations.hours]
+            .iter()
+            .flatten()
+            .all(|deviation| deviation.abs() <= tolerance);
+
+        report.pairs.push(PairVerification {
+            src_id: reference.src_id,
+            dst_id: reference.dst_id,
+            expected,
+            actual,
+            deviations,
+            is_passed,
+        });
+    }
+
+    info!("FINISHED");
+    Ok(report)
+}
+
+/// Returns the first metric having one of the given units, which are ordered by preference.
+fn find_metric<'a>(graph: &'a Graph, units: &[UnitInfo]) -> err::Result<(MetricIdx, &'a UnitInfo)> {
+    let graph_units = &graph.cfg().edges.metrics.units;
+    for unit in units {
+        if let Some(idx) = graph_units.iter().position(|graph_unit| graph_unit == unit) {
+            return Ok((MetricIdx(idx), &graph_units[idx]));
+        }
+    }
+    Err(format!("The graph doesn't have a metric of any unit {:?}.", units).into())
+}
+
+fn deviation(expected: Option<f64>, actual: Option<f64>) -> Option<f64> {
+    let (expected, actual) = (expected?, actual?);
+    if expected == 0.0 {
+        if actual == 0.0 {
+            Some(0.0)
+        } else {
+            Some(std::f64::INFINITY)
+        }
+    } else {
+        Some((actual - expected) / expected)
+    }
+}
+
+fn read_references(reference_file: &Path) -> err::Result<Vec<RawReference>> {
+    let file = match OpenOptions::new().read(true).open(reference_file) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(err::Msg::from(format!(
+                "Couldn't open {} due to error: {}",
+                reference_file.display(),
+                e
+            )))
+        }
+    };
+
+    // json is valid yaml
+    match serde_yaml::from_reader(file) {
+        Ok(references) => Ok(references),
+        Err(e) => Err(err::Msg::from(format!(
+            "Serde couldn't read {} due to error: {}",
+            reference_file.display(),
+            e
+        ))),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawReference {
+    src_id: i64,
+    dst_id: i64,
+    expected: RawCosts,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCosts {
+    kilometers: Option<f64>,
+    hours: Option<f64>,
+}
@@ -0,0 +1,249 @@
+use super::{
+    dijkstra::{self, Dijkstra},
+    paths::Path,
+};
+use crate::{
+    configs::routing::{Config, RoutingAlgo},
+    helpers::{self, err},
+    network::{EdgeIdx, Graph, NodeIdx},
+};
+use std::cmp::Ordering;
+
+/// Yen's algorithm computing the K best loop-free paths between two nodes, e.g. for showing
+/// alternatives, when the best path is unavailable.
+///
+/// Every further path deviates from a previous path at one of its nodes (the spur-node), where
+/// the previous paths' next edges and all nodes before the spur-node are blocked for the
+/// spur-query.
+/// Edges are blocked via an infinite edge-bias (see `dijkstra::Query::edge_bias`), which is
+/// reused for every spur-query, and the internal `Dijkstra` is reused as well.
+///
+/// Shortcuts are blocked in every query, since they could bypass blocked edges.
+/// Hence, the paths are flattened, even on ch-graphs.
+pub struct KShortestPaths {
+    dijkstra: Dijkstra,
+    edge_bias: Vec<f64>,
+    blocked_edges: Vec<EdgeIdx>,
+}
+
+impl KShortestPaths {
+    pub fn new() -> KShortestPaths {
+        KShortestPaths {
+            dijkstra: Dijkstra::new(),
+            edge_bias: Vec::new(),
+            blocked_edges: Vec::new(),
+        }
+    }
+
+    pub fn compute(
+        &mut self,
+        src_idx: NodeIdx,
+        dst_idx: NodeIdx,
+        k: usize,
+        graph: &Graph,
+        routing_cfg: &Config,
+    ) -> Vec<Path> {
+        match self.try_compute(src_idx, dst_idx, k, graph, routing_cfg) {
+            Ok(paths) => paths,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+
+    /// Returns up to k loop-free paths from src to dst, sorted by their costs (weighted by the
+    /// routing-config's alphas), where equally expensive paths are sorted by their hop-distance
+    /// and their edges.
+    /// Fewer paths are returned, if there are no more, e.g. no path at all for disconnected
+    /// nodes.
+    /// Every returned path has its costs calculated.
+    ///
+    /// Only plain Dijkstra is supported, since a ch-search can't avoid blocked edges.
+    /// Like `Dijkstra::try_compute_best_path(...)`, no path is found, if any alpha-value is
+    /// negative.
+    pub fn try_compute(
+        &mut self,
+        src_idx: NodeIdx,
+        dst_idx: NodeIdx,
+        k: usize,
+        graph: &Graph,
+        routing_cfg: &Config,
+    ) -> err::Result<Vec<Path>> {
+        if routing_cfg.routing_algo != RoutingAlgo::Dijkstra {
+            return Err(err::Msg::from(format!(
+                "K-shortest-paths need the routing-algorithm Dijkstra, but {:?} is configured.",
+                routing_cfg.routing_algo
+            )));
+        }
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        self.init_edge_bias(graph);
+
+        let nodes = graph.nodes();
+        let (src_id, dst_id) = (nodes.id(src_idx), nodes.id(dst_idx));
+
+        let mut best_paths = match self.spur_query(src_idx, dst_idx, graph, routing_cfg)? {
+            Some(best_path) => vec![SortedPath::new(
+                best_path.into_iter().collect(),
+                graph,
+                routing_cfg,
+            )],
+            None => return Ok(Vec::new()),
+        };
+        let mut candidates: Vec<SortedPath> = Vec::new();
+
+        while best_paths.len() < k {
+            let prev_edges = best_paths[best_paths.len() - 1].edges.clone();
+            let prev_nodes = node_indices(src_idx, &prev_edges, graph);
+
+            for spur_pos in 0..prev_edges.len() {
+                let spur_idx = prev_nodes[spur_pos];
+                let root_edges = &prev_edges[..spur_pos];
+
+                // the best paths sharing the root leave the spur-node differently
+                for best_path in &best_paths {
+                    if best_path.edges.len() > spur_pos
+                        && best_path.edges[..spur_pos] == *root_edges
+                    {
+                        self.block(best_path.edges[spur_pos]);
+                    }
+                }
+                // loop-free -> root-nodes can't be visited again
+                for &root_idx in &prev_nodes[..spur_pos] {
+                    self.block_node(root_idx, graph);
+                }
+                // The spur-query doesn't know the root's last edge.
+                // Blocking the forbidden edges by their indices is fine, since loop-free paths
+                // leave the spur-node only once.
+                if let Some(&incoming_idx) = root_edges.last() {
+                    for leaving_edge in graph.fwd_edges().starting_from(spur_idx) {
+                        if graph.is_turn_forbidden(incoming_idx, leaving_edge.idx()) {
+                            self.block(leaving_edge.idx());
+                        }
+                    }
+                }
+
+                let spur_path = self.spur_query(spur_idx, dst_idx, graph, routing_cfg);
+                self.unblock_all();
+
+                if let Some(spur_path) = spur_path? {
+                    let mut edges = root_edges.to_vec();
+                    edges.extend(spur_path);
+                    if !best_paths.iter().any(|path| path.edges == edges)
+                        && !candidates.iter().any(|path| path.edges == edges)
+                    {
+                        candidates.push(SortedPath::new(edges, graph, routing_cfg));
+                    }
+                }
+            }
+
+            // next best path is the cheapest candidate
+            let best_idx =
+                match (0..candidates.len()).min_by(|&a, &b| candidates[a].cmp(&candidates[b])) {
+                    Some(best_idx) => best_idx,
+                    None => break,
+                };
+            best_paths.push(candidates.swap_remove(best_idx));
+        }
+
+        Ok(best_paths
+            .into_iter()
+            .map(|best_path| {
+                let mut path = Path::new(src_idx, src_id, dst_idx, dst_id, best_path.edges);
+                path.calc_costs(graph);
+                path
+            })
+            .collect())
+    }
+
+    /// Shortcuts are blocked in every query.
+    fn init_edge_bias(&mut self, graph: &Graph) {
+        let fwd_edges = graph.fwd_edges();
+        self.edge_bias.clear();
+        self.edge_bias.extend(fwd_edges.iter().map(|edge_idx| {
+            if fwd_edges.is_shortcut(edge_idx) {
+                std::f64::INFINITY
+            } else {
+                0.0
+            }
+        }));
+        self.blocked_edges.clear();
+    }
+
+    /// Remembers the blocked edge for unblocking it after the spur-query.
+    fn block(&mut self, edge_idx: EdgeIdx) {
+        if self.edge_bias[*edge_idx] == 0.0 {
+            self.edge_bias[*edge_idx] = std::f64::INFINITY;
+            self.blocked_edges.push(edge_idx);
+        }
+    }
+
+    /// Blocks all leaving and incoming edges of the node.
+    fn block_node(&mut self, node_idx: NodeIdx, graph: &Graph) {
+        let (fwd_edges, bwd_edges) = (graph.fwd_edges(), graph.bwd_edges());
+        for half_edge in fwd_edges
+            .starting_from(node_idx)
+            .chain(bwd_edges.starting_from(node_idx))
+        {
+            self.block(half_edge.idx());
+        }
+    }
+
+    /// Unblocks all blocked edges, but keeps blocking shortcuts, which are never blocked
+    /// explicitly.
+    fn unblock_all(&mut self) {
+        for edge_idx in self.blocked_edges.drain(..) {
+            self.edge_bias[*edge_idx] = 0.0;
+        }
+    }
+
+    fn spur_query(
+        &mut self,
+        src_idx: NodeIdx,
+        dst_idx: NodeIdx,
+        graph: &Graph,
+        routing_cfg: &Config,
+    ) -> err::Result<Option<Path>> {
+        self.dijkstra.try_compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg,
+            edge_bias: Some(&self.edge_bias),
+            path_hint: dijkstra::PathHint::Auto,
+        })
+    }
+}
+
+/// A found path's edges with its weighted cost, ordered by cost, hop-distance and edges.
+struct SortedPath {
+    cost: f64,
+    edges: Vec<EdgeIdx>,
+}
+
+impl SortedPath {
+    fn new(edges: Vec<EdgeIdx>, graph: &Graph, routing_cfg: &Config) -> SortedPath {
+        let metrics = graph.metrics();
+        let cost = edges
+            .iter()
+            .map(|&edge_idx| helpers::dot_product(&routing_cfg.alphas, &metrics[edge_idx]))
+            .sum();
+        SortedPath { cost, edges }
+    }
+
+    fn cmp(&self, other: &SortedPath) -> Ordering {
+        self.cost
+            .partial_cmp(&other.cost)
+            .unwrap_or(Ordering::Equal)
+            .then(self.edges.len().cmp(&other.edges.len()))
+            .then(self.edges.cmp(&other.edges))
+    }
+}
+
+/// Returns the visited nodes of the (flattened) edges, beginning with src.
+fn node_indices(src_idx: NodeIdx, edges: &[EdgeIdx], graph: &Graph) -> Vec<NodeIdx> {
+    let fwd_edges = graph.fwd_edges();
+    let mut node_indices = Vec::with_capacity(edges.len() + 1);
+    node_indices.push(src_idx);
+    node_indices.extend(edges.iter().map(|&edge_idx| fwd_edges.dst_idx(edge_idx)));
+    node_indices
+}
@@ -6,7 +6,7 @@ use std::path::PathBuf;
 fn wrong_extension() {
     let mut parsing_cfg =
         configs::parsing::Config::from_yaml(defaults::paths::resources::small::FMI_YAML);
-    parsing_cfg.map_file = PathBuf::from("foo.asdf");
+    parsing_cfg.map_files = vec![PathBuf::from("foo.asdf")];
     assert!(
         Parser::parse(parsing_cfg).is_err(),
         "File-extension 'asdf' should not be supported."
@@ -34,7 +34,7 @@ fn fmi_graph() {
         ("top-left", 4, Coordinate::zero()),
     ]
     .into_iter()
-    .map(|(name, id, coord)| TestNode::new(name, id, coord, 0, &graph))
+    .map(|(name, id, coord)| TestNode::new(name, id, coord, None, &graph))
     .collect();
     let node_ll = &test_nodes[0];
     let node_bb = &test_nodes[1];
@@ -13,16 +13,6 @@ use smallvec::smallvec;
 
 const METRIC_ID: &str = defaults::DURATION_ID;
 
-#[test]
-fn chdijkstra_on_map() {
-    test_dijkstra(
-        resources::FMI_YAML,
-        METRIC_ID,
-        RoutingAlgo::CHDijkstra,
-        Box::new(expected_paths),
-    )
-}
-
 #[test]
 fn dijkstra_on_map() {
     test_dijkstra(
@@ -59,7 +49,7 @@ fn expected_paths(
             idx: NodeIdx(idx),
             id: idx as i64,
             coord: Coordinate::zero(),
-            ch_level: 0,
+            ch_level: None,
         })
         .collect();
 
@@ -1,2 +1,3 @@
 mod fastest;
+mod reversed;
 mod shortest;
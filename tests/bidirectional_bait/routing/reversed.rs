@@ -0,0 +1,62 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::bidirectional_bait as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn symmetric_costs() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'Dijkstra', metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        graph.cfg(),
+    );
+
+    let nodes = graph.nodes();
+    let ids: Vec<i64> = nodes.iter().map(|idx| nodes.id(idx)).collect();
+    let mut dijkstra = Dijkstra::new();
+    for &src_id in &ids {
+        for &dst_id in &ids {
+            let path = dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx: nodes.idx_from(src_id).expect("Src-id should exist."),
+                    dst_idx: nodes.idx_from(dst_id).expect("Dst-id should exist."),
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                })
+                .expect("Every node should be reachable.")
+                .flatten(&graph);
+
+            let reversed_path = match path.reversed(&graph) {
+                Ok(reversed_path) => reversed_path,
+                Err(msg) => panic!("Path {} should be reversible. ERROR: {}", path, msg),
+            };
+            assert_eq!(reversed_path.src_id(), dst_id);
+            assert_eq!(reversed_path.dst_id(), src_id);
+            assert_eq!(reversed_path.iter().count(), path.iter().count());
+            assert!(
+                Approx(reversed_path.costs()) == Approx(path.costs()),
+                "Reversed path {} should have the costs of path {}.",
+                reversed_path,
+                path
+            );
+
+            // reversing twice results in the same edges
+            let twice_reversed_path = reversed_path
+                .reversed(&graph)
+                .expect("Reversed path should be reversible.");
+            assert_eq!(
+                twice_reversed_path.iter().collect::<Vec<_>>(),
+                path.iter().collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use osmgraphing::cli::{constants::subcommands, CmdlineArgs, Command};
+use std::time::Duration;
+
+const CFG: &str = "resources/small/fmi.yaml";
+
+fn try_parse(args: &[&str]) -> Result<CmdlineArgs, String> {
+    CmdlineArgs::try_from_iter(std::iter::once("osmgraphing").chain(args.iter().cloned()))
+        .map_err(|msg| format!("{}", msg))
+}
+
+#[test]
+fn without_subcommand() {
+    assert!(try_parse(&[]).is_err());
+    assert!(try_parse(&["--config", CFG]).is_err());
+}
+
+#[test]
+fn without_cfg() {
+    for subcommand in &[
+        subcommands::PARSE,
+        subcommands::WRITE_GRAPH,
+        subcommands::WRITE_EDGES,
+        subcommands::WRITE_SIMPLE_FMI,
+        subcommands::WRITE_GEOJSON,
+        subcommands::WRITE_ROUTES,
+        subcommands::ROUTE,
+    ] {
+        assert!(
+            try_parse(&[subcommand]).is_err(),
+            "Subcommand {} should require a config.",
+            subcommand
+        );
+    }
+}
+
+#[test]
+fn parse() {
+    let args = try_parse(&[subcommands::PARSE, "--config", CFG]).expect("Parsing should work.");
+    assert_eq!(args.command, Command::Parse);
+    assert_eq!(args.cfg, CFG);
+    assert_eq!(args.max_log_level, "INFO");
+    assert!(!args.is_ignoring_embedded_cfg);
+    assert!(!args.is_printing_effective_cfg);
+
+    let args = try_parse(&[
+        subcommands::PARSE,
+        "--config",
+        CFG,
+        "--print-effective-config",
+    ])
+    .expect("Parsing should work.");
+    assert!(args.is_printing_effective_cfg);
+
+    assert!(
+        try_parse(&[subcommands::PARSE, "--config", CFG, "--profile", "fastest"]).is_err(),
+        "Parsing doesn't need a routing-profile."
+    );
+}
+
+#[test]
+fn route() {
+    let args = try_parse(&[
+        subcommands::ROUTE,
+        "--config",
+        CFG,
+        "--profile",
+        "fastest",
+        "--log",
+        "DEBUG",
+    ])
+    .expect("Parsing should work.");
+    assert_eq!(
+        args.command,
+        Command::Route {
+            profile: Some(String::from("fastest")),
+            timeout: None,
+        }
+    );
+    assert_eq!(args.max_log_level, "DEBUG");
+
+    let args = try_parse(&[subcommands::ROUTE, "--config", CFG]).expect("Parsing should work.");
+    assert_eq!(
+        args.command,
+        Command::Route {
+            profile: None,
+            timeout: None,
+        }
+    );
+}
+
+#[test]
+fn route_with_timeout() {
+    let args = try_parse(&[subcommands::ROUTE, "--config", CFG, "--timeout", "250"])
+        .expect("Parsing should work.");
+    assert_eq!(
+        args.command,
+        Command::Route {
+            profile: None,
+            timeout: Some(Duration::from_millis(250)),
+        }
+    );
+
+    let msg = match try_parse(&[subcommands::ROUTE, "--config", CFG, "--timeout", "1.5s"]) {
+        Ok(_) => panic!("The timeout should be in whole milliseconds."),
+        Err(msg) => msg,
+    };
+    assert!(
+        msg.contains("should be a number of milliseconds, but parsing failed"),
+        "The message should be one sentence, but is: {}",
+        msg
+    );
+    assert!(
+        try_parse(&[subcommands::PARSE, "--config", CFG, "--timeout", "250"]).is_err(),
+        "Parsing doesn't support a timeout."
+    );
+}
+
+#[test]
+fn verify() {
+    assert!(
+        try_parse(&[subcommands::VERIFY, "--config", CFG]).is_err(),
+        "Verifying should require a reference-file."
+    );
+
+    let args = try_parse(&[
+        subcommands::VERIFY,
+        "--config",
+        CFG,
+        "--reference",
+        "routes.csv",
+    ])
+    .expect("Parsing should work.");
+    assert_eq!(
+        args.command,
+        Command::Verify {
+            profile: None,
+            reference_file: String::from("routes.csv"),
+        }
+    );
+}
+
+#[test]
+fn balance() {
+    let result = try_parse(&[subcommands::BALANCE, "--config", CFG]);
+    if cfg!(feature = "gpl") {
+        assert_eq!(
+            result
+                .expect("Parsing should work with feature gpl.")
+                .command,
+            Command::Balance
+        );
+    } else {
+        assert!(
+            result.is_err(),
+            "Balancing should require the cargo-feature gpl."
+        );
+    }
+
+    assert!(
+        try_parse(&[
+            subcommands::BALANCE,
+            "--config",
+            CFG,
+            "--profile",
+            "fastest"
+        ])
+        .is_err(),
+        "Balancing doesn't take a routing-profile."
+    );
+}
@@ -0,0 +1,98 @@
+use super::srtm::{tmp_dir, write_tile, N48E009};
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::elevation as resources;
+use osmgraphing::{approximating::Approx, configs, io};
+use std::fs;
+
+fn elevations(parsing_cfg: configs::parsing::Config) -> Vec<(i64, f64)> {
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let elevation_idx = graph.cfg().nodes.custom_idx_of("elevation");
+    nodes
+        .iter()
+        .map(|node_idx| (nodes.id(node_idx), nodes.custom(node_idx, elevation_idx)))
+        .collect()
+}
+
+fn assert_elevations(actual: &[(i64, f64)], expected: &[(i64, f64)]) {
+    assert_eq!(actual.len(), expected.len());
+    for (&(id, elevation), &(expected_id, expected_elevation)) in actual.iter().zip(expected) {
+        assert_eq!(id, expected_id);
+        assert!(
+            Approx(elevation) == Approx(expected_elevation),
+            "Node {} has elevation {}, but should have {}.",
+            id,
+            elevation,
+            expected_elevation
+        );
+    }
+}
+
+#[test]
+fn ele_tags_and_default() {
+    // without srtm-dir, untagged nodes are defaulted
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    assert_elevations(
+        &elevations(parsing_cfg),
+        &[
+            (1, 500.0),
+            (2, 432.5),
+            (3, -1.0),
+            (4, -1.0),
+            (5, -1.0),
+            (6, -1.0),
+        ],
+    );
+}
+
+#[test]
+fn srtm_tiles() {
+    let dir = tmp_dir("parsing");
+    write_tile(&dir, 48, 9, &N48E009);
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    parsing_cfg
+        .elevation
+        .as_mut()
+        .expect("The elevation should be configured.")
+        .srtm_dir = Some(dir.to_path_buf());
+    let actual = elevations(parsing_cfg.clone());
+
+    // ele-tags are preferred, node 5 is on the tile's border and node 6 isn't covered
+    assert_elevations(
+        &actual,
+        &[
+            (1, 500.0),
+            (2, 432.5),
+            (3, 400.0),
+            (4, 300.0),
+            (5, 200.0),
+            (6, -1.0),
+        ],
+    );
+
+    // node 6 isn't covered by any tile, which is an error if elevations are required
+    parsing_cfg
+        .elevation
+        .as_mut()
+        .expect("The elevation should be configured.")
+        .is_requiring_elevation = true;
+    let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg.clone());
+    let msg = match result {
+        Ok(_) => panic!("Node 6 without elevation should be an error."),
+        Err(msg) => format!("{}", msg),
+    };
+    assert!(msg.contains("N49E009.hgt"), "Unexpected error: {}", msg);
+
+    // with the missing tile, every node has an elevation
+    write_tile(&dir, 49, 9, &[[0, 0, 0], [0, 50, 0], [0, 0, 0]]);
+    let actual = elevations(parsing_cfg);
+    assert_eq!(actual[5], (6, 50.0));
+}
+
+#[test]
+fn elevation_id_has_to_be_custom() {
+    let yaml = fs::read_to_string(resources::OSM_PBF_YAML).expect("Config should be readable.");
+    let yaml = yaml.replace("id: 'elevation'\n", "id: 'latitude'\n");
+    assert!(configs::parsing::Config::try_from_str(&yaml).is_err());
+}
@@ -0,0 +1,142 @@
+use crate::helpers::{temp_file, TempFile};
+use kissunits::geo::Coordinate;
+use osmgraphing::{approximating::Approx, io::elevation::SrtmReader};
+use std::{fs, path::Path};
+
+const VOID: i16 = -32768;
+
+/// Samples of N48E009 from north (lat 49) to south (lat 48) and from west (lon 9) to east
+/// (lon 10), spaced by 0.5 degrees.
+pub const N48E009: [[i16; 3]; 3] = [[100, 200, 300], [400, 500, 600], [700, 800, 900]];
+
+pub fn tmp_dir(name: &str) -> TempFile {
+    let dir = temp_file(&format!("srtm_{}", name));
+    fs::create_dir_all(&dir).expect("Tmp-dir should be creatable.");
+    dir
+}
+
+pub fn write_tile<R: AsRef<[i16]>>(dir: &Path, lat: i32, lon: i32, rows: &[R]) {
+    let bytes: Vec<u8> = rows
+        .iter()
+        .flat_map(|row| {
+            row.as_ref()
+                .iter()
+                .flat_map(|sample| sample.to_be_bytes().to_vec())
+        })
+        .collect();
+    fs::write(dir.join(SrtmReader::tile_name(lat, lon)), bytes).expect("Tile should be writable.");
+}
+
+/// Reference of the bilinear interpolation in N48E009
+fn expected_n48e009(lat: f64, lon: f64) -> f64 {
+    let row = (49.0 - lat) * 2.0;
+    let col = (lon - 9.0) * 2.0;
+    let (row_0, col_0) = ((row.floor() as usize).min(1), (col.floor() as usize).min(1));
+    let (row_frac, col_frac) = (row - row_0 as f64, col - col_0 as f64);
+    let sample = |row: usize, col: usize| N48E009[row][col] as f64;
+    (1.0 - row_frac)
+        * ((1.0 - col_frac) * sample(row_0, col_0) + col_frac * sample(row_0, col_0 + 1))
+        + row_frac
+            * ((1.0 - col_frac) * sample(row_0 + 1, col_0)
+                + col_frac * sample(row_0 + 1, col_0 + 1))
+}
+
+fn elevation(srtm_reader: &mut SrtmReader, lat: f64, lon: f64) -> Option<f64> {
+    srtm_reader
+        .elevation(&Coordinate { lat, lon })
+        .expect("Reading tiles should work.")
+}
+
+#[test]
+fn tile_names() {
+    assert_eq!(SrtmReader::tile_name(48, 9), "N48E009.hgt");
+    assert_eq!(SrtmReader::tile_name(-5, -1), "S05W001.hgt");
+    assert_eq!(SrtmReader::tile_name(0, -180), "N00W180.hgt");
+    assert_eq!(
+        SrtmReader::tile_names_of(&Coordinate {
+            lat: 48.3,
+            lon: 9.7
+        }),
+        vec!["N48E009.hgt"]
+    );
+    assert_eq!(
+        SrtmReader::tile_names_of(&Coordinate {
+            lat: 49.0,
+            lon: 9.7
+        }),
+        vec!["N49E009.hgt", "N48E009.hgt"]
+    );
+}
+
+#[test]
+fn bilinear_interpolation() {
+    let dir = tmp_dir("bilinear");
+    write_tile(&dir, 48, 9, &N48E009);
+    let mut srtm_reader = SrtmReader::new(&dir);
+
+    // samples
+    assert_eq!(elevation(&mut srtm_reader, 48.5, 9.5), Some(500.0));
+    assert_eq!(elevation(&mut srtm_reader, 48.0, 9.0), Some(700.0));
+    assert_eq!(elevation(&mut srtm_reader, 49.0, 10.0), Some(300.0));
+
+    // between samples
+    assert!(Approx(elevation(&mut srtm_reader, 48.75, 9.75)) == Approx(Some(400.0)));
+    for &(lat, lon) in &[(48.1, 9.3), (48.6, 9.05), (48.99, 9.51), (48.25, 9.8)] {
+        let actual = elevation(&mut srtm_reader, lat, lon);
+        let expected = expected_n48e009(lat, lon);
+        assert!(
+            Approx(actual) == Approx(Some(expected)),
+            "Elevation at ({}, {}) is {:?}, but should be {}.",
+            lat,
+            lon,
+            actual,
+            expected
+        );
+    }
+
+    // not covered
+    assert_eq!(elevation(&mut srtm_reader, 47.5, 9.5), None);
+    assert_eq!(elevation(&mut srtm_reader, 48.5, 10.5), None);
+}
+
+#[test]
+fn tile_borders() {
+    let dir = tmp_dir("borders");
+    write_tile(&dir, 48, 9, &N48E009);
+    // only one tile in memory, hence tiles are re-read
+    let mut srtm_reader = SrtmReader::with_capacity(&dir, 1);
+
+    // N49E009 and N48E010 are missing, hence their borders are read from N48E009
+    assert_eq!(elevation(&mut srtm_reader, 49.0, 9.5), Some(200.0));
+    assert_eq!(elevation(&mut srtm_reader, 48.5, 10.0), Some(600.0));
+    assert!(Approx(elevation(&mut srtm_reader, 49.0, 9.75)) == Approx(Some(250.0)));
+    assert_eq!(elevation(&mut srtm_reader, 49.25, 9.5), None);
+
+    // with N49E009, its own (southern) border is preferred
+    write_tile(&dir, 49, 9, &[[10, 20, 30], [40, 50, 60], [110, 210, 310]]);
+    let mut srtm_reader = SrtmReader::with_capacity(&dir, 1);
+    assert_eq!(elevation(&mut srtm_reader, 49.0, 9.5), Some(210.0));
+    assert_eq!(elevation(&mut srtm_reader, 49.25, 9.5), Some(130.0));
+    assert_eq!(elevation(&mut srtm_reader, 48.5, 9.5), Some(500.0));
+    assert_eq!(elevation(&mut srtm_reader, 49.5, 9.5), Some(50.0));
+}
+
+#[test]
+fn voids_and_invalid_tiles() {
+    let dir = tmp_dir("voids");
+    write_tile(&dir, -1, -1, &[[VOID, 10], [20, 30]]);
+    let mut srtm_reader = SrtmReader::new(&dir);
+
+    // voids are skipped
+    assert_eq!(elevation(&mut srtm_reader, -0.5, -0.5), Some(20.0));
+    assert_eq!(elevation(&mut srtm_reader, 0.0, -1.0), None);
+
+    // not n x n samples
+    fs::write(dir.join(SrtmReader::tile_name(10, 10)), [0u8; 6]).expect("Tile should be writable.");
+    assert!(srtm_reader
+        .elevation(&Coordinate {
+            lat: 10.5,
+            lon: 10.5
+        })
+        .is_err());
+}
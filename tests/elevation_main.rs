@@ -0,0 +1,2 @@
+mod elevation;
+mod helpers;
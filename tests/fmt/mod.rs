@@ -0,0 +1,64 @@
+use osmgraphing::io::fmt;
+
+#[test]
+fn representative_values() {
+    let table: Vec<(f64, Option<usize>, &str)> = vec![
+        // fractions
+        (1.0 / 3.0, Some(6), "0.333333"),
+        (1.0 / 3.0, None, "0.3333333333333333"),
+        (0.1 + 0.2, None, "0.30000000000000004"),
+        (123456.789, Some(2), "123456.79"),
+        // trailing zeros
+        (2.0, Some(6), "2"),
+        (2.0, None, "2"),
+        (100.0, Some(2), "100"),
+        (100.0, Some(0), "100"),
+        (-0.5, Some(3), "-0.5"),
+        // tiny
+        (1e-9, None, "0.000000001"),
+        (1e-9, Some(6), "0"),
+        // negative zero
+        (-0.0, None, "0"),
+        (-0.0, Some(3), "0"),
+        (-1e-9, Some(6), "0"),
+        // huge
+        (999_999_999_999_999.0, None, "999999999999999"),
+        (1e15, None, "1e15"),
+        (1e20, None, "1e20"),
+        (1.5e20, Some(3), "1.5e20"),
+        (-2.5e16, None, "-2.5e16"),
+        // non-finite
+        (f64::INFINITY, None, "inf"),
+        (f64::NEG_INFINITY, Some(3), "-inf"),
+        (f64::NAN, None, "NaN"),
+    ];
+
+    for (value, precision, expected) in table {
+        assert_eq!(
+            fmt::float(value, precision),
+            expected,
+            "Value {:?} with precision {:?} is formatted wrongly.",
+            value,
+            precision
+        );
+    }
+}
+
+#[test]
+fn exact_round_trip() {
+    for &value in &[
+        1.0 / 3.0,
+        0.1,
+        48.7758459,
+        9.1829321,
+        1e-300,
+        123456789.0123,
+    ] {
+        assert_eq!(
+            fmt::float(value, None).parse::<f64>(),
+            Ok(value),
+            "Value {:?} should be parsed back exactly.",
+            value
+        );
+    }
+}
@@ -14,7 +14,7 @@ pub struct TestNode {
     pub id: i64,
     pub idx: NodeIdx,
     pub coord: Coordinate,
-    pub ch_level: usize,
+    pub ch_level: Option<usize>,
 }
 
 impl From<Node> for TestNode {
@@ -46,7 +46,13 @@ impl PartialEq for TestNode {
 
 impl TestNode {
     #[allow(dead_code)]
-    pub fn new(name: &str, id: i64, coord: Coordinate, ch_level: usize, graph: &Graph) -> TestNode {
+    pub fn new(
+        name: &str,
+        id: i64,
+        coord: Coordinate,
+        ch_level: Option<usize>,
+        graph: &Graph,
+    ) -> TestNode {
         let idx = graph
             .nodes()
             .idx_from(id)
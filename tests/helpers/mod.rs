@@ -9,6 +9,13 @@ use osmgraphing::{
     network::{Graph, MetricIdx, RoutePair},
     routing::dijkstra::{self, Dijkstra},
 };
+use std::{
+    env, fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{self, AtomicUsize},
+};
 
 #[allow(dead_code)]
 pub mod defaults {
@@ -24,7 +31,13 @@ pub mod defaults {
                 pub const FMI_YAML: &str = "resources/bidirectional_bait/fmi.yaml";
             }
 
+            pub mod elevation {
+                pub const OSM_PBF_YAML: &str = "resources/elevation/osm.pbf.yaml";
+            }
+
             pub mod isle_of_man {
+                pub const CAR_DEFAULT_OSM_PBF_YAML: &str =
+                    "resources/isle_of_man_2020-03-14/car-default.osm.pbf.yaml";
                 pub const FMI_YAML: &str = "resources/isle_of_man_2020-03-14/fmi.yaml";
                 pub const CH_FMI_YAML: &str = "resources/isle_of_man_2020-03-14/ch.fmi.yaml";
                 pub const OSM_PBF_YAML: &str = "resources/isle_of_man_2020-03-14/osm.pbf.yaml";
@@ -47,15 +60,89 @@ pub mod defaults {
                 pub const OSM_PBF_YAML: &str = "resources/saarland_2020-07-30/osm.pbf.yaml";
             }
 
+            pub mod missing_maxspeeds {
+                pub const OSM_PBF_YAML: &str = "resources/missing_maxspeeds/osm.pbf.yaml";
+            }
+
+            pub mod node_conflict {
+                pub const FMI_YAML: &str = "resources/node_conflict/fmi.yaml";
+            }
+
+            pub mod reverse_oneway {
+                pub const OSM_PBF_YAML: &str = "resources/reverse_oneway/osm.pbf.yaml";
+                pub const OSM_YAML: &str = "resources/reverse_oneway/osm.yaml";
+            }
+
+            pub mod stacked_nodes {
+                pub const FMI_YAML: &str = "resources/stacked_nodes/fmi.yaml";
+                pub const MERGED_FMI_YAML: &str = "resources/stacked_nodes/merged.fmi.yaml";
+                pub const UNMERGEABLE_FMI_YAML: &str =
+                    "resources/stacked_nodes/unmergeable.fmi.yaml";
+            }
+
+            pub mod turn_restrictions {
+                pub const DETOUR_OSM_PBF_YAML: &str =
+                    "resources/turn_restrictions/detour.osm.pbf.yaml";
+                pub const OSM_PBF_YAML: &str = "resources/turn_restrictions/osm.pbf.yaml";
+            }
+
+            pub mod turns {
+                pub const FMI_YAML: &str = "resources/turns/fmi.yaml";
+            }
+
             pub mod simple_stuttgart {
+                pub const BUNDLE_FMI_YAML: &str = "resources/simple_stuttgart/bundle.fmi.yaml";
+                pub const COORDINATES_ROUTE_PAIRS: &str =
+                    "resources/simple_stuttgart/coordinates.route-pairs";
+                pub const CORRIDOR_FMI_YAML: &str = "resources/simple_stuttgart/corridor.fmi.yaml";
+                pub const EXTRA_COLUMNS_FMI_YAML: &str =
+                    "resources/simple_stuttgart/extra_columns.fmi.yaml";
                 pub const FMI_YAML: &str = "resources/simple_stuttgart/fmi.yaml";
+                pub const MERGED_FMI_YAML: &str = "resources/simple_stuttgart/merged.fmi.yaml";
                 pub const NORMALIZED_FMI_YAML: &str =
                     "resources/simple_stuttgart/normalized_fmi.yaml";
+                pub const SHORTEST_GOLDEN: &str = "resources/simple_stuttgart/shortest.golden";
+                pub const VERIFICATION_JSON: &str = "resources/simple_stuttgart/verification.json";
             }
 
             pub mod small {
+                pub const ALPHAS_FMI_YAML: &str = "resources/small/alphas.fmi.yaml";
+                pub const ALL_ROUTE_PAIRS: &str = "resources/small/all_43.fmi.route-pairs";
+                pub const BALANCING_ITERATION_YAML: &str =
+                    "resources/small/balancing/iteration.yaml";
+                pub const BALANCING_DELTA_RESULTS: &str = "resources/small/balancing/delta";
+                pub const BALANCING_FREEFLOW_YAML: &str = "resources/small/balancing/freeflow.yaml";
+                pub const BALANCING_RUNNER_YAML: &str = "resources/small/balancing/runner.yaml";
+                pub const CAPPED_FMI_YAML: &str = "resources/small/capped.fmi.yaml";
                 pub const FMI_YAML: &str = "resources/small/fmi.yaml";
+                pub const GRAPH_FMI: &str = "resources/small/graph.fmi";
+                pub const GRAPH_BOM_FMI: &str = "resources/small/graph_bom.fmi";
+                pub const GRAPH_LATIN1_FMI: &str = "resources/small/graph_latin1.fmi";
+                pub const GRAPH_OVER_DECLARED_NODES_FMI: &str =
+                    "resources/small/graph_over_declared_nodes.fmi";
+                pub const GRAPH_UNDER_DECLARED_NODES_FMI: &str =
+                    "resources/small/graph_under_declared_nodes.fmi";
+                pub const FRACTIONAL_ROUTE_PAIRS: &str = "resources/small/fractional.route-pairs";
+                pub const GRADIENT_FMI_YAML: &str = "resources/small/gradient.fmi.yaml";
                 pub const CH_FMI_YAML: &str = "resources/small/ch.fmi.yaml";
+                pub const CONDITIONAL_OSM_PBF_YAML: &str =
+                    "resources/small/conditional.osm.pbf.yaml";
+                pub const CYCLEWAY_FMI_YAML: &str = "resources/small/cycleway.fmi.yaml";
+                pub const CYCLEWAY_OSM_PBF_YAML: &str = "resources/small/cycleway.osm.pbf.yaml";
+                pub const NEGATIVE_FMI_YAML: &str = "resources/small/negative.fmi.yaml";
+                pub const OSM_PBF_YAML: &str = "resources/small/osm.pbf.yaml";
+                pub const PROFILES_YAML: &str = "resources/small/profiles.yaml";
+                pub const RESTRICTED_FMI_YAML: &str = "resources/small/restricted.fmi.yaml";
+                pub const ROUGHNESS_FMI_YAML: &str = "resources/small/roughness.fmi.yaml";
+                pub const SHORTEST_GOLDEN: &str = "resources/small/shortest.golden";
+                pub const SPLIT_FMI_YAML: &str = "resources/small/split.fmi.yaml";
+                pub const TIE_FMI_YAML: &str = "resources/small/tie.fmi.yaml";
+                pub const TIME_WINDOWS_FMI_YAML: &str = "resources/small/time_windows.fmi.yaml";
+                pub const UNSORTED_CH_FMI_YAML: &str = "resources/small/unsorted_ch.fmi.yaml";
+                pub const WRITTEN_FMI_YAML: &str = "resources/small/written.fmi.yaml";
+                pub const WRITTEN_REORDERED_FMI_YAML: &str =
+                    "resources/small/written_reordered.fmi.yaml";
+                pub const ZONES_FMI_YAML: &str = "resources/small/zones.fmi.yaml";
             }
         }
     }
@@ -64,12 +151,110 @@ pub mod defaults {
 mod components;
 pub use components::{TestEdge, TestNode, TestPath};
 
+/// Returns a config for writing the graph with the given node- and edge-columns into the given
+/// map-file, which can be adjusted by tests, e.g. for merging bidirectional edges.
+#[allow(dead_code)]
+pub fn graph_writing_cfg(
+    map_file: &Path,
+    node_ids: &[&str],
+    edge_ids: &[&str],
+) -> configs::writing::network::graph::Config {
+    let ids = |ids: &[&str]| {
+        ids.iter()
+            .map(|&id| Some(configs::SimpleId::from(id)))
+            .collect::<Vec<_>>()
+    };
+    configs::writing::network::graph::Config {
+        map_file: map_file.to_path_buf(),
+        nodes: configs::writing::network::graph::nodes::Config { ids: ids(node_ids) },
+        edges: configs::writing::network::edges::Config {
+            file: map_file.to_path_buf(),
+            is_writing_shortcuts: false,
+            is_writing_header: false,
+            is_denormalizing: true,
+            is_writing_provenance: false,
+            ids: ids(edge_ids),
+            precision: configs::writing::network::Precision::default(),
+        },
+        is_merging_bidirectional: false,
+        extra_node_columns: vec![],
+    }
+}
+
+#[allow(dead_code)]
+pub fn write_graph(graph: &Graph, writing_cfg: &configs::writing::network::graph::Config) {
+    if let Err(msg) = io::network::graph::Writer::write(graph, writing_cfg) {
+        panic!(
+            "Could not write {}. ERROR: {}",
+            writing_cfg.map_file.display(),
+            msg
+        );
+    }
+}
+
+/// A path in the temporary directory, which is removed (as file or as directory) when dropped,
+/// even if the test panics.
+pub struct TempFile {
+    path: PathBuf,
+}
+
+/// Returns a unique temporary path ending with the given name, e.g. `graph.fmi`.
+///
+/// Tests run in parallel threads of one process, hence the path contains a counter next to the
+/// process-id.
+#[allow(dead_code)]
+pub fn temp_file(name: &str) -> TempFile {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let temp_file = TempFile {
+        path: env::temp_dir().join(format!(
+            "osmgraphing_{}_{}_{}",
+            process::id(),
+            COUNTER.fetch_add(1, atomic::Ordering::SeqCst),
+            name
+        )),
+    };
+    // left behind by a killed process with the same process-id
+    temp_file.remove();
+    temp_file
+}
+
+impl TempFile {
+    fn remove(&self) {
+        if self.path.is_dir() {
+            let _ = fs::remove_dir_all(&self.path);
+        } else {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl Deref for TempFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
 pub fn parse(cfg: configs::parsing::Config) -> Graph {
-    let map_file = cfg.map_file.clone();
+    let map_files = cfg.map_files.clone();
     match io::network::graph::Parser::parse_and_finalize(cfg) {
         Ok(graph) => graph,
         Err(msg) => {
-            panic!("Could not parse {}. ERROR: {}", map_file.display(), msg);
+            panic!("Could not parse {:?}. ERROR: {}", map_files, msg);
         }
     }
 }
@@ -115,12 +300,29 @@ pub fn test_dijkstra(
 
     // test
 
+    assert_expected_paths(&mut dijkstra, &graph, &routing_cfg, expected_paths);
+}
+
+#[allow(dead_code)]
+pub fn assert_expected_paths(
+    dijkstra: &mut Dijkstra,
+    graph: &Graph,
+    routing_cfg: &configs::routing::Config,
+    expected_paths: Vec<(
+        TestNode,
+        TestNode,
+        DimVec<MetricIdx>,
+        Option<(DimVec<f64>, Vec<Vec<TestNode>>)>,
+    )>,
+) {
     for (src, dst, metric_indices, option_specs) in expected_paths {
         let option_path = dijkstra.compute_best_path(dijkstra::Query {
             src_idx: src.idx,
             dst_idx: dst.idx,
-            graph: &graph,
-            routing_cfg: &routing_cfg,
+            graph,
+            routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
         });
         assert_eq!(
             option_path.is_some(),
@@ -137,7 +339,7 @@ pub fn test_dijkstra(
 
         if let (Some((cost, nodes)), Some(actual_path)) = (option_specs, option_path) {
             TestPath::from_alternatives(src, dst, cost, metric_indices, nodes)
-                .assert_correct(&actual_path, &graph);
+                .assert_correct(&actual_path, graph);
         }
     }
 }
@@ -151,6 +353,11 @@ pub fn compare_dijkstras(ch_fmi_config_file: &str, metric_id: &str) {
         .expect("Expect parser to be successful when comparing Dijkstras.");
 
     let metric_idx = graph.cfg().edges.metrics.idx_of(metric_id);
+    // otherwise, CH-Dijkstra wouldn't stop early at lower levels, which should be compared
+    assert!(
+        graph.are_leaving_edges_level_sorted(),
+        "Leaving edges of the ch-graph should be sorted by their dst's level."
+    );
 
     // get route-pairs from writing-section
     let routes_cfg = configs::writing::routing::Config::from_yaml(ch_fmi_config_file);
@@ -186,12 +393,16 @@ pub fn compare_dijkstras(ch_fmi_config_file: &str, metric_id: &str) {
             dst_idx: dst.idx(),
             graph: &graph,
             routing_cfg: &ch_routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
         });
         let option_path = dijkstra.compute_best_path(dijkstra::Query {
             src_idx: src.idx(),
             dst_idx: dst.idx(),
             graph: &graph,
             routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
         });
 
         // check if both are none/not-none
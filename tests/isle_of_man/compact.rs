@@ -0,0 +1,99 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs,
+    network::{Graph, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::mem;
+
+const NUM_OF_QUERIES: usize = 32;
+
+fn parse_both(yaml: &str) -> (Graph, Graph) {
+    let parsing_cfg = configs::parsing::Config::from_yaml(yaml);
+    assert!(!parsing_cfg.is_using_compact_indices);
+    let graph = parse(parsing_cfg.clone());
+
+    let mut compact_cfg = parsing_cfg;
+    compact_cfg.is_using_compact_indices = true;
+    let compact_graph = parse(compact_cfg);
+
+    (graph, compact_graph)
+}
+
+fn assert_equal_routes(graph: &Graph, compact_graph: &Graph, algo: &str) {
+    assert_eq!(graph.nodes().count(), compact_graph.nodes().count());
+    assert_eq!(graph.fwd_edges().count(), compact_graph.fwd_edges().count());
+
+    let routing_str = format!(
+        "routing: {{ algorithm: '{}', metrics: [{{ id: 'kilometers' }}] }}",
+        algo
+    );
+    let routing_cfg = configs::routing::Config::from_str(&routing_str, graph.cfg());
+    let compact_routing_cfg = configs::routing::Config::from_str(&routing_str, compact_graph.cfg());
+
+    let node_count = graph.nodes().count();
+    let mut dijkstra = Dijkstra::new();
+    for i in 0..NUM_OF_QUERIES {
+        let src_idx = NodeIdx(i * node_count / NUM_OF_QUERIES);
+        let dst_idx = NodeIdx((*src_idx + node_count / 3) % node_count);
+
+        let path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .map(|path| path.flatten(graph).iter().cloned().collect::<Vec<_>>());
+        let compact_path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: compact_graph,
+                routing_cfg: &compact_routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .map(|path| {
+                path.flatten(compact_graph)
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            });
+        assert_eq!(
+            path, compact_path,
+            "Paths from {} to {} should be equal with compact indices.",
+            src_idx, dst_idx
+        );
+    }
+}
+
+fn assert_halved_indices(graph: &Graph, compact_graph: &Graph) {
+    // 2 * (n + 1) offsets and 2 * m edge-indices
+    let n = graph.nodes().count();
+    let m = graph.fwd_edges().count();
+    let num_of_entries = 2 * (n + 1) + 2 * m;
+    assert_eq!(
+        graph.indices_mem_size_b(),
+        num_of_entries * mem::size_of::<usize>()
+    );
+    assert_eq!(compact_graph.indices_mem_size_b(), num_of_entries * 4);
+    assert!(2 * compact_graph.indices_mem_size_b() <= graph.indices_mem_size_b());
+}
+
+#[test]
+fn dijkstra_with_compact_indices() {
+    let (graph, compact_graph) = parse_both(resources::FMI_YAML);
+    assert_halved_indices(&graph, &compact_graph);
+    assert_equal_routes(&graph, &compact_graph, "Dijkstra");
+}
+
+#[test]
+fn chdijkstra_with_compact_indices() {
+    let (graph, compact_graph) = parse_both(resources::CH_FMI_YAML);
+    assert_halved_indices(&graph, &compact_graph);
+    assert_equal_routes(&graph, &compact_graph, "CHDijkstra");
+}
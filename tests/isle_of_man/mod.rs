@@ -1,2 +1,6 @@
+mod compact;
+mod node_index;
 mod parsing;
+mod profiles;
 mod routing;
+mod writing;
@@ -0,0 +1,91 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use kissunits::geo::{self, Coordinate};
+use osmgraphing::{approximating::Approx, configs, network::NodeIdx};
+use rand::{
+    distributions::{Distribution, Uniform},
+    SeedableRng,
+};
+
+/// The tree of this many nodes is split many times, in contrast to small fixtures.
+#[test]
+fn nearest_node_in_big_tree() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    assert!(nodes.count() > 1_000);
+    assert_eq!(graph.node_index().len(), nodes.count());
+
+    let (mut min, mut max) = (nodes.coord(NodeIdx(0)), nodes.coord(NodeIdx(0)));
+    for node_idx in &nodes {
+        let coord = nodes.coord(node_idx);
+        min.lat = min.lat.min(coord.lat);
+        min.lon = min.lon.min(coord.lon);
+        max.lat = max.lat.max(coord.lat);
+        max.lon = max.lon.max(coord.lon);
+    }
+
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(osmgraphing::defaults::SEED);
+    let lat_die = Uniform::new_inclusive(min.lat, max.lat);
+    let lon_die = Uniform::new_inclusive(min.lon, max.lon);
+    for _ in 0..100 {
+        let coord = Coordinate {
+            lat: lat_die.sample(&mut rng),
+            lon: lon_die.sample(&mut rng),
+        };
+        let (expected_idx, expected_distance) =
+            nodes.nearest(&coord).expect("The graph should have nodes.");
+        let (node_idx, distance) = graph
+            .nearest_node_idx(&coord)
+            .expect("The graph should have nodes.");
+        // nodes of equal distance could be found both
+        assert_eq!(
+            Approx(*distance),
+            Approx(*expected_distance),
+            "Nearest node of ({}, {}) should be idx {}, but is idx {}.",
+            coord.lat,
+            coord.lon,
+            expected_idx,
+            node_idx
+        );
+    }
+}
+
+#[test]
+fn nodes_within_radius_in_big_tree() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(osmgraphing::defaults::SEED);
+    let die = Uniform::from(0..nodes.count());
+    for _ in 0..20 {
+        let center = NodeIdx(die.sample(&mut rng));
+        let center_coord = nodes.coord(center);
+        for &km in &[0.1, 1.0, 5.0] {
+            let mut expected: Vec<(f64, NodeIdx)> = nodes
+                .iter()
+                .map(|node_idx| {
+                    (
+                        *geo::haversine_distance_km(&center_coord, &nodes.coord(node_idx)),
+                        node_idx,
+                    )
+                })
+                .filter(|&(distance, _)| distance <= km)
+                .collect();
+            expected.sort_by(|(distance_a, idx_a), (distance_b, idx_b)| {
+                distance_a
+                    .partial_cmp(distance_b)
+                    .expect("Distances should be comparable.")
+                    .then(idx_a.cmp(idx_b))
+            });
+            let expected: Vec<NodeIdx> = expected.into_iter().map(|(_, idx)| idx).collect();
+
+            assert_eq!(
+                graph.nodes_within_radius_km(center, km),
+                expected,
+                "Nodes within {} km of idx {} are wrong.",
+                km,
+                center
+            );
+        }
+    }
+}
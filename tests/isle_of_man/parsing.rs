@@ -1,6 +1,13 @@
-use crate::helpers::{assert_graph_sloppy, defaults, parse};
+use crate::helpers::{assert_graph_sloppy, defaults, parse, temp_file};
 use defaults::paths::resources::isle_of_man as resources;
-use osmgraphing::configs;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    network::{Graph, GraphBuilder, NodeIdx, ProtoEdge, ProtoNode, ProtoShortcut, StreetCategory},
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::{cmp::Reverse, fs};
 
 #[test]
 fn pbf_yaml() {
@@ -42,6 +49,127 @@ fn pbf_graph() {
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
 }
 
+#[test]
+fn pbf_graph_with_parallel_decoding() {
+    let parse_with_threads = |num_threads| {
+        let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+        parsing_cfg.num_threads = num_threads;
+        parse(parsing_cfg)
+    };
+    let graph = parse_with_threads(1);
+
+    for &num_threads in &[2, 8] {
+        let par_graph = parse_with_threads(num_threads);
+
+        // nodes, edges and their indices have to be identical
+
+        let nodes = graph.nodes();
+        let par_nodes = par_graph.nodes();
+        assert_eq!(nodes.count(), par_nodes.count());
+        for node_idx in nodes.iter() {
+            assert_eq!(nodes.id(node_idx), par_nodes.id(node_idx));
+            let (coord, par_coord) = (nodes.coord(node_idx), par_nodes.coord(node_idx));
+            assert_eq!((coord.lat, coord.lon), (par_coord.lat, par_coord.lon));
+        }
+
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let par_fwd_edges = par_graph.fwd_edges();
+        let par_bwd_edges = par_graph.bwd_edges();
+        assert_eq!(fwd_edges.count(), par_fwd_edges.count());
+        for edge_idx in fwd_edges.iter() {
+            assert_eq!(
+                (
+                    bwd_edges.dst_idx(edge_idx),
+                    fwd_edges.dst_idx(edge_idx),
+                    graph.metrics().get(edge_idx)
+                ),
+                (
+                    par_bwd_edges.dst_idx(edge_idx),
+                    par_fwd_edges.dst_idx(edge_idx),
+                    par_graph.metrics().get(edge_idx)
+                ),
+                "Edge {} differs when decoding with {} threads.",
+                edge_idx,
+                num_threads
+            );
+        }
+    }
+}
+
+#[test]
+fn truncated_pbf_is_an_error() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let bytes = fs::read(&parsing_cfg.map_files[0]).expect("The pbf-file should be readable.");
+    let map_file = temp_file("truncated.pbf");
+    // cutting the file in the middle of a blob
+    fs::write(&map_file, &bytes[..bytes.len() / 2]).expect("The tmp-file should be writable.");
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+
+    for &num_threads in &[1, 4] {
+        parsing_cfg.num_threads = num_threads;
+        let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg.clone());
+        assert!(
+            result.is_err(),
+            "A truncated pbf-file shouldn't result in a truncated graph (with {} threads).",
+            num_threads
+        );
+    }
+}
+
+#[test]
+fn pbf_way_scan() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let way_scan =
+        io::network::graph::Parser::scan_ways(&parsing_cfg).expect("Scanning should work.");
+
+    assert_eq!(
+        way_scan.num_of_accepted() + way_scan.num_of_rejected(),
+        way_scan.num_of_ways,
+        "Every seen way should be accepted or rejected."
+    );
+    assert!(way_scan.num_of_accepted() > 0);
+
+    let highways = way_scan
+        .histogram("highway")
+        .expect("Highway-values should be collected.");
+    for category in &["residential", "service", "track", "unclassified"] {
+        assert!(
+            highways.get(*category).map_or(false, |&count| count > 0),
+            "The isle-of-man should contain ways of highway:{}.",
+            category
+        );
+    }
+    for category in &["residential", "unclassified"] {
+        assert!(
+            way_scan
+                .categories
+                .get(*category)
+                .map_or(false, |counts| counts.accepted > 0),
+            "Ways of highway:{} should be accepted for cars.",
+            category
+        );
+    }
+    assert!(
+        way_scan
+            .categories
+            .get("service")
+            .map_or(false, |counts| counts.accepted == 0 && counts.rejected > 0),
+        "Ways of highway:service should be rejected for cars."
+    );
+    for tag in &["maxspeed", "oneway", "lanes", "surface"] {
+        assert!(
+            way_scan.histogram(tag).is_some(),
+            "Values of tag {} should be collected.",
+            tag
+        );
+    }
+
+    let json = way_scan.to_json();
+    assert!(json.contains("\"histograms\""));
+    assert!(format!("{}", way_scan).starts_with(&format!("ways: {}", way_scan.num_of_ways)));
+}
+
 #[test]
 fn fmi_graph() {
     let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
@@ -61,3 +189,353 @@ fn ch_fmi_graph() {
     let expected_edge_count = 110_619;
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
 }
+
+#[test]
+fn fmi_graph_equals_buffered_parsing() {
+    for yaml in &[resources::FMI_YAML, resources::CH_FMI_YAML] {
+        let parsing_cfg = configs::parsing::Config::from_yaml(yaml);
+        let graph = parse(parsing_cfg.clone());
+        let expected = parse_fmi_buffered(parsing_cfg);
+        assert_eq_graphs(&graph, &expected);
+    }
+}
+
+/// Parses the fmi-file like the previous parser, which has read all lines into memory and has
+/// inserted the nodes after the edges.
+fn parse_fmi_buffered(parsing_cfg: configs::parsing::Config) -> Graph {
+    let content =
+        fs::read_to_string(&parsing_cfg.map_files[0]).expect("Fmi-file should be readable.");
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| line.len() > 0 && !line.starts_with('#'))
+        .collect();
+
+    // the header ends with the node- and edge-count
+    let header_len = lines
+        .iter()
+        .position(|line| line.split_whitespace().count() > 1)
+        .expect("Fmi-file should have node-lines.");
+    let counts: Vec<usize> = lines[..header_len]
+        .iter()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect();
+    let node_lines = header_len..(header_len + counts[counts.len() - 2]);
+    let edge_lines = node_lines.end..(node_lines.end + counts[counts.len() - 1]);
+
+    let mut builder = GraphBuilder::new(parsing_cfg);
+    for line in &lines[edge_lines] {
+        let (proto_edge, is_bidirectional) =
+            ProtoShortcut::try_from_str(line, &builder.cfg().edges.categories)
+                .expect("Edge-line should be parsable.");
+        let reversed_edge = if is_bidirectional {
+            Some(ProtoShortcut {
+                proto_edge: ProtoEdge {
+                    id: None,
+                    src_id: proto_edge.proto_edge.dst_id,
+                    dst_id: proto_edge.proto_edge.src_id,
+                    metrics: proto_edge.proto_edge.metrics.clone(),
+                },
+                sc_edges: None,
+            })
+        } else {
+            None
+        };
+        builder
+            .insert(proto_edge)
+            .expect("Edge should be insertable.");
+        if let Some(reversed_edge) = reversed_edge {
+            builder
+                .insert(reversed_edge)
+                .expect("Edge should be insertable.");
+        }
+    }
+    let mut builder = builder.next();
+    for line in &lines[node_lines] {
+        let proto_node =
+            ProtoNode::from_str(line, &builder.cfg().nodes).expect("Node-line should be parsable.");
+        builder.insert(proto_node);
+    }
+    builder
+        .next()
+        .and_then(|builder| builder.finalize())
+        .expect("Buffered graph should be finalizable.")
+}
+
+fn assert_eq_graphs(graph: &Graph, expected: &Graph) {
+    let (nodes, expected_nodes) = (graph.nodes(), expected.nodes());
+    assert_eq!(nodes.count(), expected_nodes.count());
+    for idx in expected_nodes.iter() {
+        assert_eq!(nodes.id(idx), expected_nodes.id(idx));
+        assert_eq!(nodes.coord(idx).lat, expected_nodes.coord(idx).lat);
+        assert_eq!(nodes.coord(idx).lon, expected_nodes.coord(idx).lon);
+        assert_eq!(nodes.level(idx), expected_nodes.level(idx));
+    }
+
+    let (fwd_edges, expected_fwd_edges) = (graph.fwd_edges(), expected.fwd_edges());
+    assert_eq!(fwd_edges.count(), expected_fwd_edges.count());
+    for edge_idx in expected_fwd_edges.iter() {
+        assert_eq!(
+            fwd_edges.dst_idx(edge_idx),
+            expected_fwd_edges.dst_idx(edge_idx)
+        );
+        assert_eq!(
+            fwd_edges.sc_edges(edge_idx),
+            expected_fwd_edges.sc_edges(edge_idx)
+        );
+    }
+    for (metric_idx, column) in expected.metrics().columns() {
+        assert_eq!(graph.metrics().column(metric_idx), column);
+    }
+}
+
+#[test]
+fn fmi_graph_bwd_edges() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    assert_bwd_edges_sorted(&graph);
+}
+
+#[test]
+fn ch_fmi_graph_bwd_edges() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    assert_bwd_edges_sorted(&graph);
+}
+
+/// The backward-edges are built by counting-sort, which should result in the same backward-edges
+/// as sorting all forward-edges by their dst-idx, then by the level of their src (descending),
+/// then by their src-idx (stable, hence by edge-id).
+fn assert_bwd_edges_sorted(graph: &Graph) {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+
+    let mut expected: Vec<_> = fwd_edges
+        .iter()
+        .map(|edge_idx| {
+            let src_idx = bwd_edges.dst_idx(edge_idx);
+            (fwd_edges.dst_idx(edge_idx), src_idx, edge_idx)
+        })
+        .collect();
+    expected.sort_by_key(|&(dst_idx, src_idx, _edge_idx)| {
+        (dst_idx, Reverse(nodes.level(src_idx)), src_idx)
+    });
+
+    let actual: Vec<_> = nodes
+        .iter()
+        .flat_map(|node_idx| {
+            bwd_edges
+                .starting_from(node_idx)
+                .map(move |half_edge| (node_idx, half_edge.dst_idx(), half_edge.idx()))
+        })
+        .collect();
+    assert_eq!(actual.len(), fwd_edges.count());
+
+    let first_mismatch = expected
+        .iter()
+        .zip(actual.iter())
+        .position(|(expected, actual)| expected != actual);
+    assert!(
+        first_mismatch.is_none(),
+        "Backward-edges should be sorted, but position {:?} differs (expected {:?}, actual {:?}).",
+        first_mismatch,
+        first_mismatch.map(|i| expected[i]),
+        first_mismatch.map(|i| actual[i]),
+    );
+}
+
+#[test]
+fn pbf_stats_of_picky_drivers() {
+    let parse_with_stats = |are_drivers_picky| {
+        let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+        parsing_cfg.vehicles.are_drivers_picky = are_drivers_picky;
+        io::network::graph::Parser::parse_and_finalize_with_stats(parsing_cfg)
+            .expect("Parsing isle-of-man should work.")
+    };
+    let (graph, stats) = parse_with_stats(false);
+    let (picky_graph, picky_stats) = parse_with_stats(true);
+
+    // every way is counted, independent of pickiness
+
+    assert_eq!(
+        stats.num_of_accepted() + stats.num_of_rejected(),
+        picky_stats.num_of_accepted() + picky_stats.num_of_rejected()
+    );
+    assert!(picky_stats.num_of_accepted() < stats.num_of_accepted());
+    assert!(picky_graph.fwd_edges().count() < graph.fwd_edges().count());
+
+    // picky drivers avoid tracks, but service-roads are never accepted for cars
+
+    let track = stats.counts(&StreetCategory::Track);
+    let picky_track = picky_stats.counts(&StreetCategory::Track);
+    assert!(track.accepted > 0);
+    assert_eq!(picky_track.accepted, 0);
+    assert_eq!(picky_track.rejected, track.total());
+
+    let service = stats.counts(&StreetCategory::Service);
+    let picky_service = picky_stats.counts(&StreetCategory::Service);
+    assert!(service.rejected > 0);
+    assert_eq!(service.accepted, 0);
+    assert_eq!(picky_service, service);
+
+    // motorways (if any) and primary streets are accepted anyways
+
+    for category in &[StreetCategory::Motorway, StreetCategory::Primary] {
+        let counts = stats.counts(category);
+        assert_eq!(picky_stats.counts(category), counts);
+        assert_eq!(counts.rejected, 0);
+    }
+    assert!(stats.counts(&StreetCategory::Primary).accepted > 0);
+
+    // picky drivers only lose streets, hence their routes can't be shorter
+
+    assert_picky_routes_not_shorter(&graph, &picky_graph);
+}
+
+/// Compares the best routes of spread node-pairs of the picky graph with the same routes in the
+/// graph of non-picky drivers.
+fn assert_picky_routes_not_shorter(graph: &Graph, picky_graph: &Graph) {
+    let routing_str = "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }";
+    let routing_cfg = configs::routing::Config::from_str(routing_str, graph.cfg());
+    let picky_routing_cfg = configs::routing::Config::from_str(routing_str, picky_graph.cfg());
+    let metric_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    let picky_metric_idx = picky_graph.cfg().edges.metrics.idx_of("kilometers");
+
+    let nodes = graph.nodes();
+    let picky_nodes = picky_graph.nodes();
+    let node_count = picky_nodes.count();
+    let num_of_queries = 32;
+    let mut num_of_found_routes = 0;
+    let mut dijkstra = Dijkstra::new();
+    for i in 0..num_of_queries {
+        let picky_src_idx = NodeIdx(i * node_count / num_of_queries);
+        let picky_dst_idx = NodeIdx((*picky_src_idx + node_count / 2) % node_count);
+        let picky_path = dijkstra.compute_best_path(dijkstra::Query {
+            src_idx: picky_src_idx,
+            dst_idx: picky_dst_idx,
+            graph: picky_graph,
+            routing_cfg: &picky_routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        });
+        let picky_cost = match picky_path {
+            Some(picky_path) => picky_path.flatten(picky_graph).length(picky_metric_idx),
+            None => continue,
+        };
+
+        // every node of picky drivers is a node of non-picky drivers
+        let src_id = picky_nodes.id(picky_src_idx);
+        let dst_id = picky_nodes.id(picky_dst_idx);
+        let path = dijkstra.compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(src_id).expect("Picky src should exist."),
+            dst_idx: nodes.idx_from(dst_id).expect("Picky dst should exist."),
+            graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        });
+        let cost = path
+            .expect("Routes of picky drivers should exist for non-picky drivers.")
+            .flatten(graph)
+            .length(metric_idx);
+        assert!(
+            Approx(cost) <= Approx(picky_cost),
+            "Route from id:{} to id:{} has {} km for non-picky, but {} km for picky drivers.",
+            src_id,
+            dst_id,
+            cost,
+            picky_cost
+        );
+        num_of_found_routes += 1;
+    }
+    assert!(num_of_found_routes > 0);
+}
+
+/// Returns the pbf-config with the given bounding-box (as yaml-map) added.
+fn pbf_yaml_with_bbox(bbox: &str) -> String {
+    let yaml = fs::read_to_string(resources::OSM_PBF_YAML).expect("The pbf-config should exist.");
+    yaml.replacen("parsing:\n", &format!("parsing:\n  bbox: {}\n", bbox), 1)
+}
+
+#[test]
+fn pbf_graph_within_bbox() {
+    // Douglas
+    let (min, max) = (
+        Coordinate {
+            lat: 54.13,
+            lon: -4.52,
+        },
+        Coordinate {
+            lat: 54.18,
+            lon: -4.45,
+        },
+    );
+    let parsing_cfg = configs::parsing::Config::from_str(&pbf_yaml_with_bbox(&format!(
+        "{{ min-lat: {}, min-lon: {}, max-lat: {}, max-lon: {} }}",
+        min.lat, min.lon, max.lat, max.lon
+    )));
+    let graph = parse(parsing_cfg);
+    let full_graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    assert!(nodes.count() > 0);
+    assert!(nodes.count() < full_graph.nodes().count() / 2);
+    assert_graph_sloppy(nodes.count(), fwd_edges.count(), &graph);
+    for node_idx in &nodes {
+        let coord = nodes.coord(node_idx);
+        assert!(
+            min.lat <= coord.lat && coord.lat <= max.lat,
+            "Node {} at {} should be within the bounding-box.",
+            nodes.id(node_idx),
+            coord
+        );
+        assert!(
+            min.lon <= coord.lon && coord.lon <= max.lon,
+            "Node {} at {} should be within the bounding-box.",
+            nodes.id(node_idx),
+            coord
+        );
+    }
+
+    // Clipping ways keeps exactly the edges, whose endpoints are both within the bounding-box.
+    // In contrast to the subgraph, nodes without any of these edges aren't parsed at all.
+
+    let (subgraph, _mapping) = full_graph.subgraph_within_bbox(min, max);
+    assert_eq!(fwd_edges.count(), subgraph.fwd_edges().count());
+    assert!(nodes.count() <= subgraph.nodes().count());
+    for node_idx in &nodes {
+        assert!(subgraph.nodes().idx_from(nodes.id(node_idx)).is_ok());
+    }
+}
+
+#[test]
+fn bbox_is_validated() {
+    // min is north-east of max
+    assert!(configs::parsing::Config::try_from_str(&pbf_yaml_with_bbox(
+        "{ min-lat: 54.18, min-lon: -4.45, max-lat: 54.13, max-lon: -4.52 }"
+    ))
+    .is_err());
+    assert!(configs::parsing::Config::try_from_str(&pbf_yaml_with_bbox(
+        "{ min-lat: 54.13, min-lon: -4.52, max-lat: 91.0, max-lon: -4.45 }"
+    ))
+    .is_err());
+
+    // borders may coincide, like for subgraphs within a bbox
+    let bbox = configs::parsing::Config::try_from_str(&pbf_yaml_with_bbox(
+        "{ min-lat: 54.13, min-lon: -4.52, max-lat: 54.13, max-lon: -4.52 }",
+    ))
+    .expect("A bbox with equal min and max should be valid.")
+    .bbox
+    .expect("The bbox should be set.");
+    assert!(bbox.contains(&bbox.min));
+
+    // fmi-files are extracts already
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg.bbox = configs::parsing::Config::from_str(&pbf_yaml_with_bbox(
+        "{ min-lat: 54.13, min-lon: -4.52, max-lat: 54.18, max-lon: -4.45 }",
+    ))
+    .bbox;
+    assert!(parsing_cfg.bbox.is_some());
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+}
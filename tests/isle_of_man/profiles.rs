@@ -0,0 +1,153 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, parsing::edges::metrics::UnitInfo},
+    network::NodeIdx,
+    routing::dijkstra::{self, Dijkstra},
+};
+use rand::{
+    distributions::{Distribution, Uniform},
+    SeedableRng,
+};
+
+const MAP_FILE: &str = "resources/isle_of_man_2020-03-14/graph.osm.pbf";
+
+#[test]
+fn car_default_parses_and_routes() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CAR_DEFAULT_OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+    assert!(graph.nodes().count() > 0);
+    assert!(graph.fwd_edges().count() > 0);
+
+    let metrics_cfg = &graph.cfg().edges.metrics;
+    for (id, unit) in &[
+        ("kmph", UnitInfo::KilometersPerHour),
+        ("lane-count", UnitInfo::LaneCount),
+        ("kilometers", UnitInfo::Kilometers),
+        ("duration", UnitInfo::Hours),
+    ] {
+        let metric_idx = metrics_cfg
+            .try_idx_of(id)
+            .expect("The preset's metric should be part of the graph.");
+        assert_eq!(&metrics_cfg.units[*metric_idx], unit);
+    }
+
+    let routing_cfg =
+        configs::routing::Config::from_yaml(resources::CAR_DEFAULT_OSM_PBF_YAML, graph.cfg());
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(osmgraphing::defaults::SEED);
+    let die = Uniform::from(0..graph.nodes().count());
+    let mut dijkstra = Dijkstra::new();
+    let found_count = (0..100)
+        .filter(|_| {
+            dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx: NodeIdx(die.sample(&mut rng)),
+                    dst_idx: NodeIdx(die.sample(&mut rng)),
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                })
+                .is_some()
+        })
+        .count();
+    assert!(
+        found_count > 0,
+        "Some random route-pairs should be routable."
+    );
+}
+
+#[test]
+fn overriding_preset() {
+    // duration in minutes instead of hours
+    let parsing_cfg = configs::parsing::Config::from_str(&format!(
+        "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+        "parsing:",
+        format!("  map-file: '{}'", MAP_FILE),
+        "  profile: 'car-default'",
+        "  generating:",
+        "    edges:",
+        "    - calc:",
+        "        result: { unit: 'Minutes', id: 'duration' }",
+        "        a: { unit: 'Kilometers', id: 'kilometers' }",
+        "        b: { unit: 'KilometersPerHour', id: 'kmph' }",
+    ));
+    let graph = parse(parsing_cfg);
+
+    let metrics_cfg = &graph.cfg().edges.metrics;
+    assert_eq!(
+        metrics_cfg
+            .ids
+            .iter()
+            .filter(|id| id.0 == "duration")
+            .count(),
+        1,
+        "The override should replace the preset's duration."
+    );
+    let duration_idx = metrics_cfg.idx_of("duration");
+    assert_eq!(metrics_cfg.units[*duration_idx], UnitInfo::Minutes);
+
+    let metrics = graph.metrics();
+    let kilometers = metrics.column(metrics_cfg.idx_of("kilometers"));
+    let kmph = metrics.column(metrics_cfg.idx_of("kmph"));
+    let durations = metrics.column(duration_idx);
+    for edge_idx in 0..100 {
+        assert_eq!(
+            Approx(durations[edge_idx]),
+            Approx(kilometers[edge_idx] / kmph[edge_idx] * 60.0)
+        );
+    }
+}
+
+#[test]
+fn unknown_profile() {
+    let result = configs::parsing::Config::try_from_str(&format!(
+        "{}\n{}\n{}",
+        "parsing:",
+        format!("  map-file: '{}'", MAP_FILE),
+        "  profile: 'truck-default'",
+    ));
+    let msg = match result {
+        Ok(_) => panic!("Unknown profiles should be refused."),
+        Err(msg) => format!("{}", msg),
+    };
+    for name in &[
+        "car-default",
+        "bicycle-default",
+        "pedestrian-default",
+        "balancing-default",
+    ] {
+        assert!(
+            msg.contains(name),
+            "The error should list the valid profile {}, but is: {}",
+            name,
+            msg
+        );
+    }
+}
+
+#[test]
+fn printing_effective_cfg() {
+    let effective_cfg =
+        configs::parsing::profiles::try_expand_yaml(resources::CAR_DEFAULT_OSM_PBF_YAML)
+            .expect("Expanding the profile should work.");
+    assert!(!effective_cfg.contains("profile"));
+
+    // the effective config is a hand-written config of the same content
+    let parsing_cfg = configs::parsing::Config::from_str(&effective_cfg);
+    let expected_cfg = configs::parsing::Config::from_yaml(resources::CAR_DEFAULT_OSM_PBF_YAML);
+    assert_eq!(parsing_cfg.map_files, expected_cfg.map_files);
+    assert_eq!(
+        parsing_cfg.edges.metrics.ids,
+        expected_cfg.edges.metrics.ids
+    );
+    assert_eq!(
+        parsing_cfg.edges.metrics.units,
+        expected_cfg.edges.metrics.units
+    );
+    assert_eq!(
+        format!("{:?}", parsing_cfg.generating),
+        format!("{:?}", expected_cfg.generating)
+    );
+}
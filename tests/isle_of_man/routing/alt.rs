@@ -0,0 +1,77 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs,
+    network::NodeIdx,
+    routing::{
+        alt::{AltRouter, LandmarkPreprocessor},
+        dijkstra::{self, Dijkstra},
+    },
+};
+
+const NUM_OF_QUERIES: usize = 16;
+const NUM_OF_LANDMARKS: usize = 8;
+
+#[test]
+fn fewer_settled_nodes_than_dijkstra() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    let mut alt = AltRouter::new(LandmarkPreprocessor::build(
+        &graph,
+        &routing_cfg,
+        NUM_OF_LANDMARKS,
+    ));
+    let mut dijkstra = Dijkstra::new();
+
+    // spread route-pairs over the whole graph
+    let node_count = graph.nodes().count();
+    let mut alt_settled_count = 0;
+    let mut dijkstra_settled_count = 0;
+    let mut found_count = 0;
+    for i in 0..NUM_OF_QUERIES {
+        let src_idx = NodeIdx(i * node_count / NUM_OF_QUERIES);
+        let dst_idx = NodeIdx((*src_idx + node_count / 2) % node_count);
+
+        let expected = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .map(|mut path| path.calc_costs(&graph)[0]);
+        let actual = alt
+            .compute_best_path(src_idx, dst_idx, &graph, &routing_cfg)
+            .map(|mut path| path.calc_costs(&graph)[0]);
+        match (expected, actual) {
+            (Some(expected), Some(actual)) => {
+                assert!(
+                    (expected - actual).abs() <= 1e-6,
+                    "ALT's cost {} should be Dijkstra's cost {}.",
+                    actual,
+                    expected
+                );
+                found_count += 1;
+                alt_settled_count += alt.settled_count();
+                dijkstra_settled_count += dijkstra.settled_count();
+            }
+            (None, None) => (),
+            _ => panic!("ALT should find a path, iff Dijkstra finds one."),
+        }
+    }
+
+    // the landmarks' lower bounds guide the search towards the destination
+    assert!(found_count > 0);
+    assert!(
+        alt_settled_count < dijkstra_settled_count,
+        "ALT has settled {} nodes, which should be less than Dijkstra's {} nodes.",
+        alt_settled_count,
+        dijkstra_settled_count
+    );
+}
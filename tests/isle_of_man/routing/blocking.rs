@@ -0,0 +1,99 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs,
+    network::NodeIdx,
+    routing::{
+        blocking,
+        dijkstra::{self, Dijkstra},
+    },
+};
+use std::sync::Arc;
+
+const NUM_OF_QUERIES: usize = 16;
+
+#[test]
+fn concurrent_routes_match_sync_routes() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = Arc::new(parse(parsing_cfg));
+    let routing_cfg = Arc::new(configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    ));
+
+    // spread route-pairs over the whole graph
+    let node_count = graph.nodes().count();
+    let route_pairs: Vec<(NodeIdx, NodeIdx)> = (0..NUM_OF_QUERIES)
+        .map(|i| {
+            let src_idx = NodeIdx(i * node_count / NUM_OF_QUERIES);
+            let dst_idx = NodeIdx((*src_idx + node_count / 2) % node_count);
+            (src_idx, dst_idx)
+        })
+        .collect();
+
+    // compute all routes concurrently
+
+    let mut runtime = tokio::runtime::Builder::new()
+        .threaded_scheduler()
+        .build()
+        .expect("Tokio-runtime should be buildable.");
+    let handles: Vec<_> = route_pairs
+        .iter()
+        .map(|&(src_idx, dst_idx)| {
+            runtime.spawn(blocking::route(
+                Arc::clone(&graph),
+                Arc::clone(&routing_cfg),
+                src_idx,
+                dst_idx,
+            ))
+        })
+        .collect();
+    let async_paths = runtime.block_on(async {
+        let mut async_paths = Vec::with_capacity(handles.len());
+        for handle in handles {
+            async_paths.push(handle.await.expect("Routing-future should finish."));
+        }
+        async_paths
+    });
+
+    // compare with synchronous computations
+
+    let mut dijkstra = Dijkstra::new();
+    for (&(src_idx, dst_idx), async_path) in route_pairs.iter().zip(async_paths) {
+        let sync_path = dijkstra.compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph: &*graph,
+            routing_cfg: &*routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        });
+        match (sync_path, async_path) {
+            (Some(sync_path), Some(async_path)) => {
+                assert_eq!(
+                    sync_path
+                        .flatten(&graph)
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                    async_path
+                        .flatten(&graph)
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                    "Paths from {} to {} should be equal.",
+                    src_idx,
+                    dst_idx
+                );
+            }
+            (None, None) => (),
+            (sync_path, async_path) => panic!(
+                "Path from {} to {} is {:?} synchronously, but {:?} asynchronously.",
+                src_idx,
+                dst_idx,
+                sync_path.is_some(),
+                async_path.is_some()
+            ),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::helpers::defaults;
+use defaults::paths::resources::isle_of_man as resources;
+#[cfg(feature = "gpl")]
+use osmgraphing::routing::explorating::{ConvexHullExplorator, Exploration};
+use osmgraphing::{
+    configs,
+    helpers::cancel::CancellationToken,
+    io,
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra, Outcome},
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+const CANCEL_DELAY: Duration = Duration::from_millis(20);
+const TOLERATED_DELAY: Duration = Duration::from_secs(1);
+// Stop repeating queries, if the cancellation is never noticed.
+const SAFETY_CAP: Duration = Duration::from_secs(10);
+
+fn parse() -> Graph {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    io::network::graph::Parser::parse_and_finalize(parsing_cfg)
+        .expect("Parsing isle-of-man should work.")
+}
+
+/// Cancels the returned token from another thread after `CANCEL_DELAY`.
+fn cancel_later() -> (CancellationToken, Instant) {
+    let token = CancellationToken::new();
+    let cancelled_at = Instant::now() + CANCEL_DELAY;
+    let remote_token = token.clone();
+    thread::spawn(move || {
+        thread::sleep(CANCEL_DELAY);
+        remote_token.cancel();
+    });
+    (token, cancelled_at)
+}
+
+#[test]
+fn cancelled_dijkstra_is_reusable() {
+    let graph = parse();
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+
+    // first pair of the route-pairs-file
+    let nodes = graph.nodes();
+    let src_idx = nodes.idx_from(283_477_868).expect("Src-node should exist.");
+    let dst_idx = nodes.idx_from(283_484_859).expect("Dst-node should exist.");
+    let query = dijkstra::Query {
+        src_idx,
+        dst_idx,
+        graph: &graph,
+        routing_cfg: &routing_cfg,
+        edge_bias: None,
+        path_hint: dijkstra::PathHint::Auto,
+    };
+
+    // a query without token shouldn't be interrupted
+    let mut dijkstra = Dijkstra::new();
+    let expected = Dijkstra::new().compute_best_path(query);
+    assert!(expected.is_some(), "Nodes of the test should be connected.");
+    match dijkstra.compute_best_path_cancellable(query, None) {
+        Outcome::Finished(best_path) => assert_eq!(best_path, expected),
+        outcome => panic!(
+            "A query without token should finish, but got {:?}.",
+            outcome
+        ),
+    }
+
+    // repeat the query until the token, cancelled by another thread, is noticed
+    let (token, cancelled_at) = cancel_later();
+    loop {
+        match dijkstra.compute_best_path_cancellable(query, Some(&token)) {
+            Outcome::Cancelled => break,
+            Outcome::Finished(best_path) => assert_eq!(best_path, expected),
+            Outcome::TimedOut => panic!("A query without timeout shouldn't time out."),
+        }
+        assert!(
+            cancelled_at.elapsed() < SAFETY_CAP,
+            "The cancellation should be noticed by the query."
+        );
+    }
+    assert!(
+        Instant::now().saturating_duration_since(cancelled_at) < TOLERATED_DELAY,
+        "A cancelled query should return promptly."
+    );
+
+    // the cancelled query shouldn't influence the next one
+    assert_eq!(dijkstra.compute_best_path(query), expected);
+}
+
+/// The order of explored paths isn't specified, hence paths are compared by their routes.
+#[cfg(feature = "gpl")]
+fn routes(paths: &[osmgraphing::routing::paths::Path]) -> Vec<u64> {
+    let mut routes: Vec<_> = paths.iter().map(|path| path.flattened_hash()).collect();
+    routes.sort();
+    routes
+}
+
+#[cfg(feature = "gpl")]
+#[test]
+fn cancelled_exploration_is_reusable() {
+    let graph = parse();
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: { Explorator: { algo: 'Dijkstra' } }, \
+         metrics: [{ id: 'kilometers' }, { id: 'hours' }] }",
+        graph.cfg(),
+    );
+
+    let nodes = graph.nodes();
+    let src_idx = nodes.idx_from(283_477_868).expect("Src-node should exist.");
+    let dst_idx = nodes.idx_from(283_484_859).expect("Dst-node should exist.");
+    let query = dijkstra::Query {
+        src_idx,
+        dst_idx,
+        graph: &graph,
+        routing_cfg: &routing_cfg,
+        edge_bias: None,
+        path_hint: dijkstra::PathHint::Auto,
+    };
+
+    let mut dijkstra = Dijkstra::new();
+    let mut explorator = ConvexHullExplorator::new();
+    let expected =
+        routes(&ConvexHullExplorator::new().fully_explorate(query, &mut Dijkstra::new()));
+    assert!(
+        !expected.is_empty(),
+        "Nodes of the test should be connected."
+    );
+
+    let (token, cancelled_at) = cancel_later();
+    loop {
+        match explorator.fully_explorate_cancellable(query, &mut dijkstra, Some(&token)) {
+            Exploration::Cancelled => break,
+            Exploration::Finished(paths) => assert_eq!(routes(&paths), expected),
+        }
+        assert!(
+            cancelled_at.elapsed() < SAFETY_CAP,
+            "The cancellation should be noticed by the exploration."
+        );
+    }
+    assert!(
+        Instant::now().saturating_duration_since(cancelled_at) < TOLERATED_DELAY,
+        "A cancelled exploration should return promptly."
+    );
+
+    // the cancelled exploration shouldn't influence the next one
+    assert_eq!(
+        routes(&explorator.fully_explorate(query, &mut dijkstra)),
+        expected
+    );
+}
@@ -35,17 +35,6 @@ fn dijkstra_on_ch_fmi_map() {
     )
 }
 
-#[test]
-#[ignore]
-fn chdijkstra_on_fmi_map() {
-    test_dijkstra(
-        resources::FMI_YAML,
-        METRIC_ID,
-        RoutingAlgo::CHDijkstra,
-        Box::new(expected_paths),
-    )
-}
-
 #[test]
 #[ignore]
 fn dijkstra_on_fmi_map() {
@@ -57,17 +46,6 @@ fn dijkstra_on_fmi_map() {
     )
 }
 
-#[test]
-#[ignore]
-fn chdijkstra_on_pbf_map() {
-    test_dijkstra(
-        resources::OSM_PBF_YAML,
-        METRIC_ID,
-        RoutingAlgo::CHDijkstra,
-        Box::new(expected_paths),
-    )
-}
-
 #[test]
 #[ignore]
 fn dijkstra_on_pbf_map() {
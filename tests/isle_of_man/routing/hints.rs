@@ -0,0 +1,117 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs,
+    network::{Graph, NodeIdx},
+    routing::dijkstra::{self, Dijkstra, PathHint},
+};
+use rand::{
+    distributions::{Distribution, Uniform},
+    SeedableRng,
+};
+
+const NUM_OF_QUERIES: usize = 100;
+
+/// A path-hint only affects the allocation of found paths, so every hint should lead to the same
+/// paths and costs.
+fn assert_hints_like_auto(graph: &Graph, algo: &str) {
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: '{}', metrics: [{{ id: 'kilometers' }}] }}",
+            algo
+        ),
+        graph.cfg(),
+    );
+
+    let node_count = graph.nodes().count();
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(osmgraphing::defaults::SEED);
+    let die = Uniform::from(0..node_count);
+    let mut dijkstra = Dijkstra::new();
+    for _ in 0..NUM_OF_QUERIES {
+        let query = dijkstra::Query {
+            src_idx: NodeIdx(die.sample(&mut rng)),
+            dst_idx: NodeIdx(die.sample(&mut rng)),
+            graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: PathHint::Auto,
+        };
+        let expected_path = dijkstra.compute_best_path(query).map(|path| {
+            let path = path.flatten(graph);
+            (
+                path.iter().cloned().collect::<Vec<_>>(),
+                path.costs().clone(),
+            )
+        });
+
+        for &path_hint in &[PathHint::ExpectShort, PathHint::ExpectLong] {
+            let path = dijkstra.compute_best_path(dijkstra::Query { path_hint, ..query });
+
+            // check capacities before flattening
+
+            if let Some(path) = &path {
+                let edge_count = path.iter().count();
+                match path_hint {
+                    PathHint::ExpectShort => {
+                        assert!(path.capacity() >= path_hint.path_capacity(node_count));
+                        if edge_count <= path_hint.path_capacity(node_count) {
+                            assert!(path.capacity() < node_count);
+                        }
+                    }
+                    PathHint::ExpectLong => {
+                        assert!(path.capacity() >= path_hint.path_capacity(node_count))
+                    }
+                    PathHint::Auto => unreachable!(),
+                }
+            }
+
+            let path = path.map(|path| {
+                let path = path.flatten(graph);
+                (
+                    path.iter().cloned().collect::<Vec<_>>(),
+                    path.costs().clone(),
+                )
+            });
+            assert_eq!(
+                path,
+                expected_path,
+                "{} with {:?} should find the same path from {} to {} as with {:?}.",
+                algo,
+                path_hint,
+                query.src_idx,
+                query.dst_idx,
+                PathHint::Auto
+            );
+        }
+    }
+}
+
+#[test]
+fn path_hint_capacities() {
+    assert_eq!(PathHint::default(), PathHint::Auto);
+    assert_eq!(PathHint::Auto.path_capacity(1_000), 0);
+    // capped at sqrt(n), but not shorter than short paths
+    assert_eq!(PathHint::ExpectLong.path_capacity(1_000_000), 1_000);
+    assert_eq!(
+        PathHint::ExpectLong.path_capacity(1_000),
+        osmgraphing::defaults::routing::SHORT_PATH_CAPACITY
+    );
+    assert_eq!(PathHint::ExpectLong.path_capacity(10), 10);
+    assert_eq!(
+        PathHint::ExpectShort.path_capacity(1_000_000),
+        osmgraphing::defaults::routing::SHORT_PATH_CAPACITY
+    );
+    assert_eq!(PathHint::ExpectShort.path_capacity(10), 10);
+}
+
+#[test]
+fn dijkstra_with_path_hints() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    assert_hints_like_auto(&graph, "Dijkstra");
+}
+
+#[test]
+fn chdijkstra_with_path_hints() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    assert_hints_like_auto(&graph, "CHDijkstra");
+}
@@ -1,2 +1,9 @@
+mod alt;
+#[cfg(feature = "tokio")]
+mod blocking;
+mod cancel;
 mod fastest;
+mod hints;
+mod reused;
 mod shortest;
+mod timeout;
@@ -0,0 +1,137 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, MetricIdx, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+use rand::{
+    distributions::{Distribution, Uniform},
+    SeedableRng,
+};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+const NUM_OF_QUERIES: usize = 1_000;
+const METRIC_ID: &str = "kilometers";
+
+/// Like the implementation before lazy resets, the reference resets all of its routing-data
+/// for every query, but searches only forward and without any speedup.
+fn reference_cost(
+    graph: &Graph,
+    metric_idx: MetricIdx,
+    src_idx: NodeIdx,
+    dst_idx: NodeIdx,
+) -> Option<f64> {
+    let fwd_edges = graph.fwd_edges();
+    let metrics = graph.metrics().column(metric_idx);
+    let mut costs = vec![std::f64::INFINITY; graph.nodes().count()];
+    let mut is_visited = vec![false; graph.nodes().count()];
+
+    // non-negative floats are ordered like their bits
+    let mut queue = BinaryHeap::new();
+    costs[*src_idx] = 0.0;
+    queue.push(Reverse((0.0f64.to_bits(), src_idx)));
+    while let Some(Reverse((_, idx))) = queue.pop() {
+        if idx == dst_idx {
+            return Some(costs[*idx]);
+        }
+        if is_visited[*idx] {
+            continue;
+        }
+        is_visited[*idx] = true;
+
+        for leaving_edge in fwd_edges.starting_from(idx) {
+            let new_cost = costs[*idx] + metrics[*leaving_edge.idx()];
+            if new_cost < costs[*leaving_edge.dst_idx()] {
+                costs[*leaving_edge.dst_idx()] = new_cost;
+                queue.push(Reverse((new_cost.to_bits(), leaving_edge.dst_idx())));
+            }
+        }
+    }
+    None
+}
+
+/// A reused Dijkstra resets its routing-data lazily, so it should find the same paths as a new
+/// Dijkstra and the same costs as the reference for every query.
+fn assert_reused_like_reference(
+    graph: &Graph,
+    algo: &str,
+    mut reused_dijkstra: Dijkstra,
+    num_of_queries: usize,
+) {
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: '{}', metrics: [{{ id: '{}' }}] }}",
+            algo, METRIC_ID
+        ),
+        graph.cfg(),
+    );
+    let metric_idx = graph.cfg().edges.metrics.idx_of(METRIC_ID);
+
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(osmgraphing::defaults::SEED);
+    let die = Uniform::from(0..graph.nodes().count());
+    for _ in 0..num_of_queries {
+        let query = dijkstra::Query {
+            src_idx: NodeIdx(die.sample(&mut rng)),
+            dst_idx: NodeIdx(die.sample(&mut rng)),
+            graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        };
+
+        let path = reused_dijkstra
+            .compute_best_path(query)
+            .map(|path| path.flatten(graph));
+        let expected_path = Dijkstra::new()
+            .compute_best_path(query)
+            .map(|path| path.flatten(graph));
+        assert_eq!(
+            path.as_ref()
+                .map(|path| path.iter().cloned().collect::<Vec<_>>()),
+            expected_path
+                .as_ref()
+                .map(|path| path.iter().cloned().collect::<Vec<_>>()),
+            "Reused {} should find the same path from {} to {} as a new one.",
+            algo,
+            query.src_idx,
+            query.dst_idx
+        );
+
+        let cost = path.map(|path| path.length(metric_idx));
+        let expected_cost = reference_cost(graph, metric_idx, query.src_idx, query.dst_idx);
+        assert_eq!(
+            cost.map(Approx),
+            expected_cost.map(Approx),
+            "Reused {} should find the reference's cost from {} to {}.",
+            algo,
+            query.src_idx,
+            query.dst_idx
+        );
+    }
+}
+
+#[test]
+fn reused_dijkstra() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    assert_reused_like_reference(&graph, "Dijkstra", Dijkstra::new(), NUM_OF_QUERIES);
+}
+
+#[test]
+fn reused_chdijkstra() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    assert_reused_like_reference(&graph, "CHDijkstra", Dijkstra::new(), NUM_OF_QUERIES);
+}
+
+#[test]
+fn reused_dijkstra_at_generation_wraparound() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    // the generation wraps around after 3 queries, so slots of older generations are reset
+    let dijkstra = Dijkstra::with_generation(std::u32::MAX - 3);
+    assert_reused_like_reference(&graph, "Dijkstra", dijkstra, 10);
+
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let dijkstra = Dijkstra::with_generation(std::u32::MAX - 3);
+    assert_reused_like_reference(&graph, "CHDijkstra", dijkstra, 10);
+}
@@ -0,0 +1,49 @@
+use crate::helpers::defaults;
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs, io,
+    routing::dijkstra::{self, Dijkstra, Outcome},
+};
+use std::time::{Duration, Instant};
+
+#[test]
+fn zero_timeout_leaves_dijkstra_reusable() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = io::network::graph::Parser::parse_and_finalize(parsing_cfg)
+        .expect("Parsing isle-of-man should work.");
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+
+    // first pair of the route-pairs-file
+    let nodes = graph.nodes();
+    let src_idx = nodes.idx_from(283_477_868).expect("Src-node should exist.");
+    let dst_idx = nodes.idx_from(283_484_859).expect("Dst-node should exist.");
+    let query = dijkstra::Query {
+        src_idx,
+        dst_idx,
+        graph: &graph,
+        routing_cfg: &routing_cfg,
+        edge_bias: None,
+        path_hint: dijkstra::PathHint::Auto,
+    };
+
+    let mut dijkstra = Dijkstra::new();
+    let now = Instant::now();
+    match dijkstra.compute_best_path_within(query, Duration::from_nanos(0)) {
+        Outcome::TimedOut => (),
+        Outcome::Finished(_) => panic!("A query without time should time out."),
+        Outcome::Cancelled => panic!("A query without token shouldn't be cancelled."),
+    }
+    assert!(
+        now.elapsed() < Duration::from_secs(1),
+        "A query with zero timeout should return immediately."
+    );
+
+    // the interrupted query shouldn't influence the next one
+    let expected = Dijkstra::new().compute_best_path(query);
+    let actual = dijkstra.compute_best_path(query);
+    assert!(expected.is_some(), "Nodes of the test should be connected.");
+    assert_eq!(actual, expected);
+}
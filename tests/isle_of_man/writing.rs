@@ -0,0 +1,71 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::isle_of_man as resources;
+use kissunits::{distance::Kilometers, geo};
+use osmgraphing::{configs, io};
+
+#[test]
+fn stratified_route_pairs() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let mut routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+
+    // write route-pairs
+
+    let file = temp_file("isle_of_man.route-pairs");
+    let bands = vec![(5.0, 10), (30.0, 10)];
+    let writing_cfg = configs::writing::routing::Config {
+        file: file.to_path_buf(),
+        category: configs::writing::routing::Category::StratifiedByDistance {
+            seed: 42,
+            bands: bands
+                .iter()
+                .map(|&(max_km, count)| (Kilometers(max_km), count))
+                .collect(),
+        },
+    };
+    if let Err(msg) = io::routing::Writer::write(&graph, &routing_cfg, &writing_cfg) {
+        panic!("Could not write {}. ERROR: {}", file.display(), msg);
+    }
+
+    // parse them again
+
+    routing_cfg.route_pairs_file = Some(file.to_path_buf());
+    let route_pairs = match io::routing::Parser::parse(&routing_cfg) {
+        Ok(route_pairs) => route_pairs,
+        Err(msg) => panic!("{}", msg),
+    };
+
+    // check bands
+
+    let nodes = graph.nodes();
+    let mut counts = vec![0; bands.len()];
+    for (route_pair, _count) in route_pairs.iter() {
+        let src = nodes
+            .create_from(route_pair.src)
+            .expect("Written src-id should be in the graph.");
+        let dst = nodes
+            .create_from(route_pair.dst)
+            .expect("Written dst-id should be in the graph.");
+        let distance = *geo::haversine_distance_km(&src.coord(), &dst.coord());
+
+        let band_idx = bands
+            .iter()
+            .position(|&(max_km, _count)| distance < max_km)
+            .expect(&format!(
+                "Route-pair ({}, {}) with {} km should be in a band.",
+                route_pair.src, route_pair.dst, distance
+            ));
+        counts[band_idx] += 1;
+    }
+    assert_eq!(
+        counts,
+        bands
+            .iter()
+            .map(|&(_max_km, count)| count)
+            .collect::<Vec<_>>(),
+        "Every band should be filled with its requested count of route-pairs."
+    );
+}
@@ -0,0 +1,102 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::missing_maxspeeds as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{MetricIdx, Provenance},
+};
+
+#[test]
+fn provenances_of_defaulted_maxspeeds() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let metrics = graph.metrics();
+    let metric_idx = |id| graph.cfg().edges.metrics.idx_of(id);
+    let kmph_idx = metric_idx("kmph");
+
+    // ways 20 and 22 are tagged, ways 21 and 23 are not
+
+    assert_eq!(fwd_edges.count(), 6);
+    assert!(metrics.has_provenances());
+    for &(src_id, dst_id, is_defaulted) in &[
+        (1, 2, false),
+        (2, 3, false),
+        (3, 4, true),
+        (4, 5, true),
+        (5, 6, false),
+        (6, 1, true),
+    ] {
+        let edge_idx = fwd_edges
+            .between(
+                nodes.idx_from(src_id).expect("Src-id should exist."),
+                nodes.idx_from(dst_id).expect("Dst-id should exist."),
+            )
+            .expect(&format!(
+                "Edge (id:{})->(id:{}) should exist.",
+                src_id, dst_id
+            ))
+            .idx();
+
+        let (expected_provenance, expected_kmph) = if is_defaulted {
+            (Provenance::Defaulted, 50.0)
+        } else {
+            (Provenance::Measured, 30.0)
+        };
+        assert_eq!(
+            metrics.provenance(edge_idx, kmph_idx),
+            expected_provenance,
+            "Maxspeed of edge (id:{})->(id:{}) has wrong provenance.",
+            src_id,
+            dst_id
+        );
+        assert!(Approx(metrics.column(kmph_idx)[*edge_idx]) == Approx(expected_kmph));
+
+        // copies keep the provenance, generated values are calculated or defaulted
+
+        assert_eq!(
+            metrics.provenance(edge_idx, metric_idx("copied-kmph")),
+            expected_provenance
+        );
+        assert_eq!(
+            metrics.provenance(edge_idx, metric_idx("kilometers")),
+            Provenance::Calculated
+        );
+        assert_eq!(
+            metrics.provenance(edge_idx, metric_idx("hours")),
+            Provenance::Calculated
+        );
+        assert_eq!(
+            metrics.provenance(edge_idx, metric_idx("workload")),
+            Provenance::Defaulted
+        );
+    }
+
+    // shares per metric
+
+    let defaulted_share = |metric_idx: MetricIdx| {
+        metrics
+            .provenances(metric_idx)
+            .expect("Provenances should be tracked.")
+            .share(Provenance::Defaulted)
+    };
+    assert!(Approx(defaulted_share(kmph_idx)) == Approx(0.5));
+    assert!(Approx(defaulted_share(metric_idx("hours"))) == Approx(0.0));
+    assert!(Approx(defaulted_share(metric_idx("workload"))) == Approx(1.0));
+}
+
+#[test]
+fn untracked_provenances() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    parsing_cfg.edges.metrics.is_tracking_provenance = false;
+    let graph = parse(parsing_cfg);
+    let metrics = graph.metrics();
+    let kmph_idx = graph.cfg().edges.metrics.idx_of("kmph");
+
+    assert!(!metrics.has_provenances());
+    assert!(metrics.provenances(kmph_idx).is_none());
+    assert!(metrics
+        .try_provenance(graph.fwd_edges().iter().next().unwrap(), kmph_idx)
+        .is_err());
+}
@@ -0,0 +1,76 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::missing_maxspeeds as resources;
+use osmgraphing::{configs, io};
+use std::{fs, path::Path};
+
+fn edges_info_cfg(file: &Path) -> configs::writing::network::edges::Config {
+    configs::writing::network::edges::Config {
+        file: file.to_path_buf(),
+        is_writing_shortcuts: false,
+        is_writing_header: true,
+        is_denormalizing: false,
+        is_writing_provenance: true,
+        ids: vec!["src-id", "dst-id", "kmph", "hours"]
+            .into_iter()
+            .map(|id| Some(configs::SimpleId::from(id)))
+            .collect(),
+        precision: configs::writing::network::Precision::default(),
+    }
+}
+
+#[test]
+fn edges_info_with_provenances() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+
+    let file = temp_file("missing_maxspeeds.csv");
+    let writing_cfg = edges_info_cfg(&file);
+    if let Err(msg) = io::network::edges::Writer::write(&graph, &writing_cfg) {
+        panic!(
+            "Could not write {}. ERROR: {}",
+            writing_cfg.file.display(),
+            msg
+        );
+    }
+    let content = fs::read_to_string(&file).expect("Written edges-info should be readable.");
+
+    // every metric is followed by its provenance
+
+    let mut lines = content.lines();
+    assert_eq!(
+        lines.next(),
+        Some("src-id dst-id kmph kmph-provenance hours hours-provenance")
+    );
+    let mut rows: Vec<Vec<String>> = lines
+        .map(|line| line.split_whitespace().map(String::from).collect())
+        .collect();
+    rows.sort();
+    let expected_rows = vec![
+        ("1", "2", "30", "measured"),
+        ("2", "3", "30", "measured"),
+        ("3", "4", "50", "defaulted"),
+        ("4", "5", "50", "defaulted"),
+        ("5", "6", "30", "measured"),
+        ("6", "1", "50", "defaulted"),
+    ];
+    assert_eq!(rows.len(), expected_rows.len());
+    for (row, (src_id, dst_id, kmph, provenance)) in rows.iter().zip(expected_rows) {
+        assert_eq!(row.len(), 6);
+        assert_eq!(
+            (&row[0][..], &row[1][..], &row[2][..], &row[3][..]),
+            (src_id, dst_id, kmph, provenance)
+        );
+        assert_eq!(row[5], "calculated");
+    }
+}
+
+#[test]
+fn edges_info_without_tracked_provenances() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    parsing_cfg.edges.metrics.is_tracking_provenance = false;
+    let graph = parse(parsing_cfg);
+
+    let file = temp_file("missing_maxspeeds_untracked.csv");
+    let writing_cfg = edges_info_cfg(&file);
+    assert!(io::network::edges::Writer::write(&graph, &writing_cfg).is_err());
+}
@@ -0,0 +1,2 @@
+mod helpers;
+mod missing_maxspeeds;
@@ -0,0 +1,110 @@
+use crate::helpers::defaults;
+use defaults::paths::resources::node_conflict as resources;
+use kissunits::geo::{self, Coordinate};
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, parsing::nodes::ConflictPolicy},
+    io,
+};
+
+#[test]
+fn fmi_yaml() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    assert_eq!(parsing_cfg.on_node_conflict, ConflictPolicy::Error);
+}
+
+#[test]
+fn error_by_default() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+    let msg = match result {
+        Ok(_) => panic!("Parsing conflicting node-coordinates should fail by default."),
+        Err(msg) => format!("{}", msg),
+    };
+    assert!(
+        msg.contains("Node-id 1"),
+        "The error-msg should name the conflicting node-id, but is: {}",
+        msg
+    );
+}
+
+#[test]
+fn keep_first() {
+    assert_resolved(ConflictPolicy::KeepFirst, 9.01, 9.04);
+}
+
+#[test]
+fn keep_last() {
+    assert_resolved(ConflictPolicy::KeepLast, 9.03, 9.04);
+}
+
+#[test]
+fn average() {
+    assert_resolved(ConflictPolicy::Average, 9.02, 9.05);
+}
+
+/// Node b is inserted twice, node c thrice with coordinates A, B, A.
+fn assert_resolved(policy: ConflictPolicy, expected_lon_b: f64, expected_lon_c: f64) {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg.on_node_conflict = policy;
+    let (graph, stats) =
+        match io::network::graph::Parser::parse_and_finalize_with_stats(parsing_cfg) {
+            Ok(graph_and_stats) => graph_and_stats,
+            Err(msg) => panic!("Could not parse node-conflicts. ERROR: {}", msg),
+        };
+    assert_eq!(
+        stats.num_of_coord_conflicts, 2,
+        "Nodes b and c should be counted as conflicts."
+    );
+    assert!(stats
+        .to_string()
+        .lines()
+        .any(|line| line.split_whitespace().collect::<Vec<_>>() == ["coord-conflicts", "2"]));
+
+    let nodes = graph.nodes();
+    assert_eq!(nodes.count(), 3, "Conflicting nodes should be merged.");
+    let idx_a = nodes.idx_from(0).expect("Node-id 0 should exist.");
+    let idx_b = nodes.idx_from(1).expect("Node-id 1 should exist.");
+    let idx_c = nodes.idx_from(2).expect("Node-id 2 should exist.");
+
+    // coordinates
+
+    let expected_coord = Coordinate {
+        lat: 48.0,
+        lon: expected_lon_b,
+    };
+    assert!(
+        Approx(nodes.coord(idx_b)) == Approx(expected_coord),
+        "Policy {:?} should result in coordinate {} for node b, but node has {}.",
+        policy,
+        expected_coord,
+        nodes.coord(idx_b)
+    );
+    let expected_coord_c = Coordinate {
+        lat: 48.0,
+        lon: expected_lon_c,
+    };
+    assert!(
+        Approx(nodes.coord(idx_c)) == Approx(expected_coord_c),
+        "Policy {:?} should result in coordinate {} for node c, but node has {}.",
+        policy,
+        expected_coord_c,
+        nodes.coord(idx_c)
+    );
+
+    // derived edge-length
+
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    let fwd_edges = graph.fwd_edges();
+    let edge = fwd_edges
+        .between(idx_a, idx_b)
+        .expect("Edge a->b should exist.");
+    let expected_km = *geo::haversine_distance_km(&nodes.coord(idx_a), &expected_coord);
+    assert!(
+        Approx(edge.metrics()[*km_idx]) == Approx(expected_km),
+        "Policy {:?} should result in edge-length {} km, but edge has {} km.",
+        policy,
+        expected_km,
+        edge.metrics()[*km_idx]
+    );
+}
@@ -0,0 +1,115 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::reverse_oneway as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    network::NodeIdx,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn only_reversed_edges() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let idx = |id| nodes.idx_from(id).expect("Node-id should exist.");
+
+    // way 10 (1->2->3) is a reverse oneway, way 11 (3->4->1) is bidirectional
+    assert_eq!(nodes.count(), 4);
+    assert_eq!(fwd_edges.count(), 6);
+    for &(src_id, dst_id) in &[(3, 2), (2, 1), (3, 4), (4, 3), (4, 1), (1, 4)] {
+        assert!(
+            fwd_edges.between(idx(src_id), idx(dst_id)).is_some(),
+            "Edge (id:{})->(id:{}) should exist.",
+            src_id,
+            dst_id
+        );
+    }
+    for &(src_id, dst_id) in &[(1, 2), (2, 3)] {
+        assert!(
+            fwd_edges.between(idx(src_id), idx(dst_id)).is_none(),
+            "Edge (id:{})->(id:{}) shouldn't exist, since its way is a reverse oneway.",
+            src_id,
+            dst_id
+        );
+    }
+
+    // routing has to take the detour via 4 and 3 to traverse the reverse oneway forwards
+
+    let routing_cfg = configs::routing::Config::from_yaml(resources::OSM_PBF_YAML, graph.cfg());
+    let mut dijkstra = Dijkstra::new();
+    let mut best_path = |src_idx: NodeIdx, dst_idx: NodeIdx| {
+        dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .expect("Nodes of the reverse oneway should be connected.")
+            .flatten(&graph)
+    };
+
+    let path = best_path(idx(2), idx(1));
+    assert_eq!(path.iter().count(), 1);
+
+    let path = best_path(idx(1), idx(2));
+    let visited_ids: Vec<i64> = path
+        .iter()
+        .map(|&edge_idx| nodes.id(fwd_edges.dst_idx(edge_idx)))
+        .collect();
+    assert_eq!(visited_ids, vec![4, 3, 2]);
+}
+
+#[test]
+fn osm_xml_equals_pbf() {
+    let pbf_graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_YAML));
+    let (pbf_nodes, nodes) = (pbf_graph.nodes(), graph.nodes());
+    let (pbf_fwd_edges, fwd_edges) = (pbf_graph.fwd_edges(), graph.fwd_edges());
+
+    // node 5 is only part of a building
+    assert_eq!(nodes.count(), pbf_nodes.count());
+    assert_eq!(fwd_edges.count(), pbf_fwd_edges.count());
+    for node_idx in &nodes {
+        assert_eq!(nodes.id(node_idx), pbf_nodes.id(node_idx));
+        let (coord, pbf_coord) = (nodes.coord(node_idx), pbf_nodes.coord(node_idx));
+        assert!(Approx(coord.lat) == Approx(pbf_coord.lat));
+        assert!(Approx(coord.lon) == Approx(pbf_coord.lon));
+    }
+
+    for src_idx in &nodes {
+        for dst_idx in &nodes {
+            match (
+                fwd_edges.between(src_idx, dst_idx),
+                pbf_fwd_edges.between(src_idx, dst_idx),
+            ) {
+                (None, None) => (),
+                (Some(edge), Some(pbf_edge)) => assert!(
+                    Approx(edge.metrics()) == Approx(pbf_edge.metrics()),
+                    "Edge (id:{})->(id:{}) should have the same metrics in both files.",
+                    nodes.id(src_idx),
+                    nodes.id(dst_idx)
+                ),
+                (edge, _) => panic!(
+                    "Edge (id:{})->(id:{}) is parsed from {} file only.",
+                    nodes.id(src_idx),
+                    nodes.id(dst_idx),
+                    if edge.is_some() {
+                        "the osm-xml-"
+                    } else {
+                        "the pbf-"
+                    }
+                ),
+            }
+        }
+    }
+
+    let way_scan =
+        io::network::graph::Parser::scan_ways(graph.cfg()).expect("Scanning should work.");
+    assert_eq!(way_scan.num_of_ways, 3);
+    assert_eq!(way_scan.num_of_accepted(), 2);
+}
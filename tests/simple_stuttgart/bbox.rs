@@ -0,0 +1,206 @@
+use crate::helpers::{assert_graph, defaults, parse, TestEdge, TestNode};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::{
+    distance::Kilometers,
+    geo::Coordinate,
+    speed::KilometersPerHour,
+    time::{Hours, Seconds},
+};
+use osmgraphing::{
+    configs,
+    network::{EdgeIdx, Graph},
+};
+
+const BAC: i64 = 26_160_028;
+const DEA: i64 = 1_621_605_361;
+const END: i64 = 298_249_467;
+const WAI: i64 = 252_787_940;
+
+/// Covers only the nodes bac and dea, but the edges between end and wai cross it.
+fn bbox() -> (Coordinate, Coordinate) {
+    (
+        Coordinate {
+            lat: 48.815,
+            lon: 9.33,
+        },
+        Coordinate {
+            lat: 48.95,
+            lon: 9.44,
+        },
+    )
+}
+
+#[test]
+fn edges_in_bbox() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let idx = |id| nodes.idx_from(id).expect("Node-id should exist.");
+    let (min, max) = bbox();
+
+    let inside: Vec<_> = nodes
+        .iter()
+        .filter(|&node_idx| is_inside(&nodes.coord(node_idx), &min, &max))
+        .map(|node_idx| nodes.id(node_idx))
+        .collect();
+    assert_eq!(inside.len(), 2);
+    assert!(inside.contains(&BAC) && inside.contains(&DEA));
+
+    let bbox_edges = graph.edges_in_bbox(min, max, None);
+    assert!(!bbox_edges.is_truncated());
+    let actual: Vec<EdgeIdx> = bbox_edges.iter().collect();
+    assert_eq!(actual, brute_force(&graph, &min, &max));
+    assert_eq!(actual.len(), 9);
+
+    // straddling edges have no node inside
+    for &(src_id, dst_id) in &[(END, WAI), (WAI, END)] {
+        let edge_idx = fwd_edges
+            .between(idx(src_id), idx(dst_id))
+            .expect("Straddling edge should exist.")
+            .idx();
+        assert!(
+            actual.contains(&edge_idx),
+            "Edge (id:{})->(id:{}) crosses the bbox.",
+            src_id,
+            dst_id
+        );
+    }
+
+    // cap results
+
+    let bbox_edges = graph.edges_in_bbox(min, max, Some(3));
+    assert!(bbox_edges.is_truncated());
+    assert_eq!(bbox_edges.iter().collect::<Vec<_>>(), actual[..3].to_vec());
+    let bbox_edges = graph.edges_in_bbox(min, max, Some(9));
+    assert!(!bbox_edges.is_truncated());
+    assert_eq!(bbox_edges.len(), 9);
+}
+
+#[test]
+fn subgraph_within_bbox() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+
+    // opp, bac and dea
+    let (min, max) = (
+        Coordinate {
+            lat: 48.93,
+            lon: 9.41,
+        },
+        Coordinate {
+            lat: 48.99,
+            lon: 9.46,
+        },
+    );
+    let (subgraph, mapping) = graph.subgraph_within_bbox(min, max);
+
+    let test_nodes: Vec<_> = vec![
+        ("Oppenweiler", 26_033_921, (48.9840100, 9.4589188)),
+        ("Backnang", 26_160_028, (48.9416023, 9.4332023)),
+        ("Dead-end", 1_621_605_361, (48.9396327, 9.4188681)),
+    ]
+    .into_iter()
+    .map(|(name, id, (lat, lon))| TestNode::new(name, id, Coordinate { lat, lon }, None, &subgraph))
+    .collect();
+    let node_opp = &test_nodes[0];
+    let node_bac = &test_nodes[1];
+    let node_dea = &test_nodes[2];
+
+    // edges are remapped, but keep their order
+    let fwd_test_edges: Vec<_> = vec![
+        // idx, src, dst, kilometers, kmph, s
+        (0, &node_opp, &node_bac, 8.0, 50.0, 576.0),
+        (1, &node_bac, &node_opp, 8.0, 50.0, 576.0),
+        (2, &node_bac, &node_dea, 1.069, 30.0, 128.28),
+    ]
+    .into_iter()
+    .map(|(idx, src, dst, kilometers, kmph, s)| {
+        TestEdge::new_fwd(
+            None,
+            EdgeIdx(idx),
+            src,
+            dst,
+            Kilometers(kilometers),
+            KilometersPerHour(kmph),
+            Hours::from(Seconds(s)),
+        )
+    })
+    .collect();
+    let bwd_test_edges: Vec<_> = vec![
+        // idx, src, dst, kilometers, kmph, s
+        (0, &node_bac, &node_opp, 8.0, 50.0, 576.0),
+        (1, &node_opp, &node_bac, 8.0, 50.0, 576.0),
+        (2, &node_dea, &node_bac, 1.069, 30.0, 128.28),
+    ]
+    .into_iter()
+    .map(|(idx, src, dst, kilometers, kmph, s)| {
+        TestEdge::new_bwd(
+            None,
+            EdgeIdx(idx),
+            src,
+            dst,
+            Kilometers(kilometers),
+            KilometersPerHour(kmph),
+            Hours::from(Seconds(s)),
+        )
+    })
+    .collect();
+    assert_graph(test_nodes, fwd_test_edges, bwd_test_edges, &subgraph);
+
+    // ids are preserved
+    let subnodes = subgraph.nodes();
+    for new_idx in subnodes.iter() {
+        assert_eq!(subnodes.id(new_idx), nodes.id(mapping.to_old(new_idx)));
+    }
+    assert_eq!(
+        mapping.to_new(nodes.idx_from(WAI).expect("Node-id should exist.")),
+        None
+    );
+
+    // swapped corners
+    assert!(graph.try_subgraph_within_bbox(max, min).is_err());
+}
+
+fn is_inside(coord: &Coordinate, min: &Coordinate, max: &Coordinate) -> bool {
+    min.lat <= coord.lat && coord.lat <= max.lat && min.lon <= coord.lon && coord.lon <= max.lon
+}
+
+/// Tests every edge for a node inside the bbox or a crossing with one of the bbox's borders.
+fn brute_force(graph: &Graph, min: &Coordinate, max: &Coordinate) -> Vec<EdgeIdx> {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+
+    let corners = [
+        (min.lon, min.lat),
+        (max.lon, min.lat),
+        (max.lon, max.lat),
+        (min.lon, max.lat),
+    ];
+    fwd_edges
+        .iter()
+        .filter(|&edge_idx| {
+            let src = nodes.coord(bwd_edges.dst_idx(edge_idx));
+            let dst = nodes.coord(fwd_edges.dst_idx(edge_idx));
+            if is_inside(&src, min, max) || is_inside(&dst, min, max) {
+                return true;
+            }
+            (0..4).any(|i| {
+                are_crossing(
+                    ((src.lon, src.lat), (dst.lon, dst.lat)),
+                    (corners[i], corners[(i + 1) % 4]),
+                )
+            })
+        })
+        .collect()
+}
+
+fn are_crossing(a: ((f64, f64), (f64, f64)), b: ((f64, f64), (f64, f64))) -> bool {
+    let orientation = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        ((q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)).signum()
+    };
+    orientation(a.0, a.1, b.0) != orientation(a.0, a.1, b.1)
+        && orientation(b.0, b.1, a.0) != orientation(b.0, b.1, a.1)
+}
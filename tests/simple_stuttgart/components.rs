@@ -0,0 +1,78 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    configs::{self, routing::RoutingAlgo},
+    network::NodeIdx,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+const OPP: i64 = 26_033_921;
+const BAC: i64 = 26_160_028;
+const DEA: i64 = 1_621_605_361;
+const END: i64 = 298_249_467;
+const WAI: i64 = 252_787_940;
+const STU: i64 = 2_933_335_353;
+
+#[test]
+fn dead_end_is_own_component() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let idx = |id| nodes.idx_from(id).expect("Node-id should exist.");
+
+    let components = graph.components();
+    assert_eq!(components.count(), 2);
+    let mut sizes = components.sizes().to_vec();
+    sizes.sort();
+    assert_eq!(sizes, vec![1, 5]);
+    assert_eq!(components.size_of(graph.component_of(idx(DEA))), 1);
+    assert_eq!(components.largest(), Some(graph.component_of(idx(BAC))));
+
+    // dea is reachable from all other nodes, but not vice versa
+
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "{}\n{}\n{}\n{}",
+            "routing:",
+            format!("  algorithm: {}", RoutingAlgo::Dijkstra.name()),
+            "  metrics:",
+            format!("  - id: '{}'", defaults::DISTANCE_ID),
+        ),
+        graph.cfg(),
+    );
+    let mut dijkstra = Dijkstra::new();
+    let mut is_reachable = |src_idx: NodeIdx, dst_idx: NodeIdx| {
+        dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .is_some()
+    };
+    for &id in &[OPP, BAC, END, WAI, STU] {
+        assert!(is_reachable(idx(id), idx(DEA)));
+        assert!(!is_reachable(idx(DEA), idx(id)));
+    }
+
+    // Routing-fixtures expect no path only from dea to other nodes.
+    // Hence, only dea is separated from the others.
+
+    let ids = [OPP, BAC, DEA, END, WAI, STU];
+    for &src_id in &ids {
+        for &dst_id in &ids {
+            let is_expected = (src_id == DEA) == (dst_id == DEA);
+            assert_eq!(
+                graph.same_component(idx(src_id), idx(dst_id)),
+                is_expected,
+                "Nodes (id:{}, id:{}) should{} share a component.",
+                src_id,
+                dst_id,
+                if is_expected { "" } else { " not" }
+            );
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{configs, network::NodeIdx};
+
+#[test]
+fn translating_many_ids() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+
+    // all ids in reversed order, with unknown ids and a duplicate in between
+
+    let mut ids: Vec<i64> = nodes.iter().map(|idx| nodes.id(idx)).rev().collect();
+    let fake_ids = [-1, 1_000_000_000_000];
+    ids.insert(2, fake_ids[1]);
+    ids.insert(0, fake_ids[0]);
+    ids.push(nodes.id(NodeIdx(0)));
+
+    let results = nodes.idx_from_many(&ids);
+    assert_eq!(results.len(), ids.len());
+    for (&id, result) in ids.iter().zip(results.iter()) {
+        if fake_ids.contains(&id) {
+            assert_eq!(*result, Err(id), "Id {} shouldn't be found.", id);
+        } else {
+            let idx = result.expect("Node-id should exist.");
+            assert_eq!(nodes.id(idx), id);
+        }
+        assert_eq!(*result, nodes.idx_from(id));
+    }
+    assert_eq!(results.iter().filter(|result| result.is_err()).count(), 2);
+
+    // the index can be held on its own
+
+    let id_index = nodes.id_index();
+    assert_eq!(id_index.len(), nodes.count());
+    assert!(id_index.ids().windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(id_index.idx_of_many(&ids), results);
+    assert!(!id_index.contains(fake_ids[0]));
+    assert!(id_index.contains(nodes.id(NodeIdx(0))));
+}
@@ -0,0 +1,74 @@
+use crate::helpers::defaults;
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    configs, io,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn loading_bundle() {
+    let bundle_cfg = configs::bundle::Config::from_yaml(resources::BUNDLE_FMI_YAML)
+        .with_routing(resources::BUNDLE_FMI_YAML, None)
+        .with_spatial_index();
+    let artifacts = io::load(bundle_cfg).expect("Loading the bundle should work.");
+    let graph = &artifacts.graph;
+    assert_eq!(graph.nodes().count(), 6);
+
+    let routing_cfg = artifacts
+        .routing_cfg
+        .as_ref()
+        .expect("The routing-config should have been loaded.");
+    let route_pairs = artifacts
+        .route_pairs
+        .as_ref()
+        .expect("The route-pairs should have been loaded.");
+    assert!(!route_pairs.is_empty());
+
+    // the spatial index is the graph's cached edge-grid
+    let spatial_index = artifacts
+        .spatial_index
+        .as_ref()
+        .expect("The spatial index should have been built.");
+    assert!(spatial_index.rows() * spatial_index.cols() > 0);
+    let bbox_edges = graph.edges_in_bbox(
+        Coordinate {
+            lat: -90.0,
+            lon: -180.0,
+        },
+        Coordinate {
+            lat: 90.0,
+            lon: 180.0,
+        },
+        None,
+    );
+    assert_eq!(bbox_edges.len(), graph.fwd_edges().count());
+
+    let mut dijkstra = Dijkstra::new();
+    let found_count = route_pairs
+        .iter()
+        .map(|(route_pair, _route_count)| route_pair.into_node(graph))
+        .filter(|route_pair| {
+            dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx: route_pair.src.idx(),
+                    dst_idx: route_pair.dst.idx(),
+                    graph,
+                    routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                })
+                .is_some()
+        })
+        .count();
+    assert!(found_count > 0, "Some route-pairs should be routable.");
+}
+
+#[test]
+fn loading_graph_only() {
+    let artifacts = io::load(configs::bundle::Config::from_yaml(resources::FMI_YAML))
+        .expect("Loading the graph should work.");
+    assert!(artifacts.routing_cfg.is_none());
+    assert!(artifacts.route_pairs.is_none());
+    assert!(artifacts.spatial_index.is_none());
+}
@@ -1,2 +1,8 @@
+mod bbox;
+mod components;
+mod id_index;
+mod loading;
+mod node_index;
 mod parsing;
 mod routing;
+mod writing;
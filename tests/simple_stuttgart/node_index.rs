@@ -0,0 +1,127 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::geo::{self, Coordinate};
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, NodeIdx},
+};
+
+/// A grid of coordinates around (and beyond) the graph's nodes
+fn queries(graph: &Graph) -> Vec<Coordinate> {
+    let nodes = graph.nodes();
+    let (mut min, mut max) = (nodes.coord(NodeIdx(0)), nodes.coord(NodeIdx(0)));
+    for node_idx in &nodes {
+        let coord = nodes.coord(node_idx);
+        min.lat = min.lat.min(coord.lat);
+        min.lon = min.lon.min(coord.lon);
+        max.lat = max.lat.max(coord.lat);
+        max.lon = max.lon.max(coord.lon);
+    }
+
+    let steps = 12;
+    let mut queries = Vec::new();
+    for row in 0..=steps {
+        for col in 0..=steps {
+            // 50 % margin around the nodes' bounding-box
+            let lat_frac = -0.5 + 2.0 * row as f64 / steps as f64;
+            let lon_frac = -0.5 + 2.0 * col as f64 / steps as f64;
+            queries.push(Coordinate {
+                lat: min.lat + lat_frac * (max.lat - min.lat),
+                lon: min.lon + lon_frac * (max.lon - min.lon),
+            });
+        }
+    }
+    queries
+}
+
+#[test]
+fn nearest_node() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    assert_eq!(graph.node_index().len(), nodes.count());
+
+    let mut coords = queries(&graph);
+    coords.extend(nodes.iter().map(|node_idx| nodes.coord(node_idx)));
+    for coord in &coords {
+        let (expected_idx, expected_distance) =
+            nodes.nearest(coord).expect("The graph should have nodes.");
+        let (node_idx, distance) = graph
+            .nearest_node_idx(coord)
+            .expect("The graph should have nodes.");
+        // nodes of equal distance could be found both
+        assert_eq!(
+            Approx(*distance),
+            Approx(*expected_distance),
+            "Nearest node of ({}, {}) should be idx {}, but is idx {}.",
+            coord.lat,
+            coord.lon,
+            expected_idx,
+            node_idx
+        );
+
+        let node = graph
+            .nearest_node(coord)
+            .expect("The graph should have nodes.");
+        assert_eq!(node.idx(), node_idx);
+        assert_eq!(node.id(), nodes.id(node_idx));
+
+        // gps-coordinates are less precise
+        let (lat, lon) = (coord.lat as f32, coord.lon as f32);
+        let gps_coord = Coordinate {
+            lat: f64::from(lat),
+            lon: f64::from(lon),
+        };
+        let node = graph
+            .nearest_node_at(lat, lon)
+            .expect("The graph should have nodes.");
+        let expected_node = graph
+            .nearest_node(&gps_coord)
+            .expect("The graph should have nodes.");
+        assert_eq!(node.idx(), expected_node.idx());
+    }
+}
+
+#[test]
+fn nodes_within_radius() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+
+    for center in &nodes {
+        let center_coord = nodes.coord(center);
+        for &km in &[0.0, 1.0, 5.0, 20.0, 1_000.0] {
+            let mut expected: Vec<(f64, NodeIdx)> = nodes
+                .iter()
+                .map(|node_idx| {
+                    (
+                        *geo::haversine_distance_km(&center_coord, &nodes.coord(node_idx)),
+                        node_idx,
+                    )
+                })
+                .filter(|&(distance, _)| distance <= km)
+                .collect();
+            expected.sort_by(|(distance_a, idx_a), (distance_b, idx_b)| {
+                distance_a
+                    .partial_cmp(distance_b)
+                    .expect("Distances should be comparable.")
+                    .then(idx_a.cmp(idx_b))
+            });
+            let expected: Vec<NodeIdx> = expected.into_iter().map(|(_, idx)| idx).collect();
+
+            let actual = graph.nodes_within_radius_km(center, km);
+            assert_eq!(
+                actual, expected,
+                "Nodes within {} km of idx {} are wrong.",
+                km, center
+            );
+            assert!(actual.contains(&center));
+        }
+        assert!(graph.nodes_within_radius_km(center, -1.0).is_empty());
+    }
+
+    // the whole graph is within a few hundred kilometers
+    assert_eq!(
+        graph.nodes_within_radius_km(NodeIdx(0), 1_000.0).len(),
+        nodes.count()
+    );
+}
@@ -6,7 +6,11 @@ use kissunits::{
     speed::KilometersPerHour,
     time::{Hours, Seconds},
 };
-use osmgraphing::{configs, network::EdgeIdx};
+use osmgraphing::{
+    configs::{self, routing::RoutingAlgo},
+    io,
+    network::{EdgeIdx, RoutePair},
+};
 
 #[test]
 fn fmi_yaml() {
@@ -16,6 +20,44 @@ fn fmi_yaml() {
     assert!(configs::routing::Config::try_from_yaml(resources::FMI_YAML, &parsing_cfg).is_err());
 }
 
+#[test]
+fn coordinates_route_pairs() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        format!(
+            "  route-pairs-file: '{}'",
+            resources::COORDINATES_ROUTE_PAIRS
+        ),
+        format!("  algorithm: {}", RoutingAlgo::Dijkstra.name()),
+        "  metrics:",
+        format!("  - id: '{}'", defaults::DISTANCE_ID),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+
+    assert!(
+        io::routing::Parser::parse(&routing_cfg).is_err(),
+        "Coordinates should need a graph to be snapped to."
+    );
+
+    // the coordinate in the ocean is rejected
+    let route_pairs = io::routing::Parser::parse_and_snap(&routing_cfg, &graph)
+        .expect("Parsing and snapping should work.");
+    let route_pairs: Vec<_> = route_pairs
+        .into_iter()
+        .map(|(RoutePair { src, dst }, count)| (src, dst, count))
+        .collect();
+    assert_eq!(
+        route_pairs,
+        vec![
+            (26_033_921, 2_933_335_353, 1.0),
+            (26_160_028, 252_787_940, 2.0),
+        ]
+    );
+}
+
 #[test]
 fn fmi_graph() {
     let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
@@ -35,7 +77,7 @@ fn fmi_graph() {
         ("Stuttgart", 2_933_335_353, (48.7701757, 9.1565768)),
     ]
     .into_iter()
-    .map(|(name, id, (lat, lon))| TestNode::new(name, id, Coordinate { lat, lon }, 0, &graph))
+    .map(|(name, id, (lat, lon))| TestNode::new(name, id, Coordinate { lat, lon }, None, &graph))
     .collect();
     let node_opp = &test_nodes[0];
     let node_bac = &test_nodes[1];
@@ -0,0 +1,81 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::geo;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, NodeIdx},
+    routing::paths::{self, Path},
+};
+
+const BAC_ID: i64 = 26_160_028;
+const END_ID: i64 = 298_249_467;
+const WAI_ID: i64 = 252_787_940;
+const STU_ID: i64 = 2_933_335_353;
+
+fn path_from(node_ids: &[i64], graph: &Graph) -> Path {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node_indices: Vec<NodeIdx> = node_ids
+        .iter()
+        .map(|&id| nodes.idx_from(id).expect("Node-id should exist."))
+        .collect();
+    let edges = node_indices
+        .windows(2)
+        .map(|pair| {
+            fwd_edges
+                .between(pair[0], pair[1])
+                .expect("Edge should exist.")
+                .idx()
+        })
+        .collect();
+
+    let src_idx = node_indices[0];
+    let dst_idx = node_indices[node_indices.len() - 1];
+    Path::new(
+        src_idx,
+        nodes.id(src_idx),
+        dst_idx,
+        nodes.id(dst_idx),
+        edges,
+    )
+}
+
+#[test]
+fn diverging_alternative() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // Backnang -> Waiblingen -> Stuttgart (40 km) and Backnang -> Endersbach -> Stuttgart (43 km)
+    let optimal = path_from(&[BAC_ID, WAI_ID, STU_ID], &graph);
+    let alternative = path_from(&[BAC_ID, END_ID, STU_ID], &graph);
+
+    let comparison = paths::compare(&optimal, &alternative, &graph);
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    assert!(
+        Approx(comparison.cost_ratios[*km_idx]) == Approx(43.0 / 40.0),
+        "Distance-ratio should be 1.075, but is {}.",
+        comparison.cost_ratios[*km_idx]
+    );
+    assert!(Approx(comparison.shared_edge_fraction) == Approx(0.0));
+
+    // Only Endersbach diverges, and Waiblingen is its closest node of the optimal path.
+    let nodes = graph.nodes();
+    let end_coord = nodes.coord(nodes.idx_from(END_ID).expect("Node-id should exist."));
+    let wai_coord = nodes.coord(nodes.idx_from(WAI_ID).expect("Node-id should exist."));
+    let divergence = *geo::haversine_distance_km(&end_coord, &wai_coord) * 1_000.0;
+    // roughly 4.6 km
+    assert!(4_500.0 < divergence && divergence < 4_700.0);
+    assert!(
+        Approx(comparison.max_divergence.0) == Approx(divergence),
+        "Max-divergence should be {} m, but is {} m.",
+        divergence,
+        comparison.max_divergence.0
+    );
+    assert!(
+        Approx(comparison.mean_divergence.0) == Approx(divergence / 3.0),
+        "Mean-divergence should be {} m, but is {} m.",
+        divergence / 3.0,
+        comparison.mean_divergence.0
+    );
+}
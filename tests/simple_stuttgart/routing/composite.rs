@@ -0,0 +1,84 @@
+use super::{shortest_path, BAC_ID, OPP_ID, STU_ID, WAI_ID};
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{composite::CompositeGraph, Graph},
+    routing::dijkstra::{self, Dijkstra},
+};
+
+const DEAD_END_ID: i64 = 1_621_605_361;
+const END_ID: i64 = 298_249_467;
+
+/// Splits the graph into a northern and a southern half, both containing bac.
+fn halves(graph: &Graph) -> Vec<Graph> {
+    let nodes = graph.nodes();
+    vec![
+        vec![OPP_ID, BAC_ID, DEAD_END_ID],
+        vec![BAC_ID, END_ID, WAI_ID, STU_ID],
+    ]
+    .into_iter()
+    .map(|ids| {
+        let indices: Vec<_> = ids
+            .into_iter()
+            .map(|id| nodes.idx_from(id).expect("Node-id should exist."))
+            .collect();
+        graph.subgraph(&indices).0
+    })
+    .collect()
+}
+
+#[test]
+fn route_across_joined_halves() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let expected_path = shortest_path(OPP_ID, STU_ID, &graph);
+
+    let composite = CompositeGraph::new(halves(&graph), vec![(BAC_ID, BAC_ID)]);
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let path = Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: composite
+                .idx_from(0, OPP_ID)
+                .expect("Opp should be in the north."),
+            dst_idx: composite
+                .idx_from(1, STU_ID)
+                .expect("Stu should be in the south."),
+            graph: &composite,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .expect("Path from opp to stu should exist across the join.");
+
+    assert_eq!(path.src_id(), OPP_ID);
+    assert_eq!(path.dst_id(), STU_ID);
+    assert!(
+        path.iter()
+            .any(|&edge_idx| composite.locate_edge(edge_idx).is_none()),
+        "Path should use the connector at bac."
+    );
+
+    let costs = composite.calc_costs(&path);
+    for (cost, expected_cost) in costs.iter().zip(expected_path.costs()) {
+        assert!(
+            Approx(*cost) == Approx(*expected_cost),
+            "Costs {:?} over the composite graph should be the single graph's costs {:?}.",
+            costs,
+            expected_path.costs()
+        );
+    }
+}
+
+#[test]
+fn join_without_nodes() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // opp is only in the north
+    assert!(
+        CompositeGraph::try_new(halves(&graph), vec![(OPP_ID, OPP_ID)]).is_err(),
+        "A join of nodes in the same graph shouldn't connect anything."
+    );
+}
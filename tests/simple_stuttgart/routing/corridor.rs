@@ -0,0 +1,121 @@
+use super::{shortest_path, BAC_ID, OPP_ID, STU_ID, WAI_ID};
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    configs,
+    network::{EdgeIdx, Graph},
+    routing::corridor,
+};
+
+const DEAD_END_ID: i64 = 1_621_605_361;
+const END_ID: i64 = 298_249_467;
+
+/// Replaces edge-indices by (src-id, dst-id) for readable assertions.
+fn id_pairs(graph: &Graph, edges: &[EdgeIdx]) -> Vec<(i64, i64)> {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let mut id_pairs: Vec<_> = edges
+        .iter()
+        .map(|&edge_idx| {
+            (
+                nodes.id(bwd_edges.dst_idx(edge_idx)),
+                nodes.id(fwd_edges.dst_idx(edge_idx)),
+            )
+        })
+        .collect();
+    id_pairs.sort_unstable();
+    id_pairs
+}
+
+#[test]
+fn generous_radius() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let path = shortest_path(OPP_ID, STU_ID, &graph);
+
+    let edges = corridor::edges_near_path(&path, 100_000.0, &graph);
+    let all_edges: Vec<EdgeIdx> = graph.fwd_edges().iter().collect();
+    assert_eq!(edges, all_edges);
+}
+
+#[test]
+fn tiny_radius() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let path = shortest_path(OPP_ID, STU_ID, &graph);
+    let path_nodes = path.node_indices(&graph);
+
+    let edges = corridor::edges_near_path(&path, 1.0, &graph);
+    assert!(
+        edges.windows(2).all(|pair| pair[0] < pair[1]),
+        "Edges should be sorted and deduplicated."
+    );
+    for edge_idx in &path {
+        assert!(
+            edges.contains(edge_idx),
+            "The path's edge {} should be in its corridor.",
+            edge_idx
+        );
+    }
+
+    // Only the path's nodes are inside, so every edge touching them is returned.
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let expected: Vec<EdgeIdx> = fwd_edges
+        .iter()
+        .filter(|&edge_idx| {
+            path_nodes.contains(&bwd_edges.dst_idx(edge_idx))
+                || path_nodes.contains(&fwd_edges.dst_idx(edge_idx))
+        })
+        .collect();
+    assert_eq!(edges, expected);
+
+    assert!(corridor::edges_near_path(&path, -1.0, &graph).is_empty());
+}
+
+#[test]
+fn boundary_edges() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let path = shortest_path(BAC_ID, DEAD_END_ID, &graph);
+
+    // the dead-end is about 1 km from bac, but every other node is far away
+    let edges = corridor::edges_near_path(&path, 500.0, &graph);
+    assert_eq!(
+        id_pairs(&graph, &edges),
+        id_pairs(&graph, &corridor::edges_near_path(&path, 1.0, &graph))
+    );
+    let mut expected = vec![
+        (OPP_ID, BAC_ID),
+        (BAC_ID, OPP_ID),
+        (BAC_ID, WAI_ID),
+        (BAC_ID, END_ID),
+        (BAC_ID, DEAD_END_ID),
+        (END_ID, BAC_ID),
+        (WAI_ID, BAC_ID),
+    ];
+    expected.sort_unstable();
+    assert_eq!(id_pairs(&graph, &edges), expected);
+}
+
+#[test]
+fn radius_beyond_node_spacing() {
+    let graph = parse(configs::parsing::Config::from_yaml(
+        resources::CORRIDOR_FMI_YAML,
+    ));
+    // The street's nodes are 1 km apart and node 10 is 1.2 km away from the street's end,
+    // but more than 1.4 km away from every other node of the street.
+    let path = shortest_path(0, 3, &graph);
+
+    let edges = corridor::edges_near_path(&path, 1_400.0, &graph);
+    let mut expected = vec![
+        (0, 1),
+        (1, 0),
+        (1, 2),
+        (2, 1),
+        (2, 3),
+        (3, 2),
+        (10, 11),
+        (11, 10),
+    ];
+    expected.sort_unstable();
+    assert_eq!(id_pairs(&graph, &edges), expected);
+}
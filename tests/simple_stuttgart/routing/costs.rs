@@ -0,0 +1,79 @@
+use super::{shortest_path, BAC_ID, OPP_ID, STU_ID};
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, parsing::edges::metrics::UnitInfo},
+};
+
+#[test]
+fn named_lookups() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let path = shortest_path(OPP_ID, STU_ID, &graph);
+    let costs = path.costs_view(&graph);
+    let metrics_cfg = &graph.cfg().edges.metrics;
+
+    assert_eq!(costs.dim(), path.costs().len());
+    for (idx, (id, unit, value)) in costs.iter().enumerate() {
+        assert_eq!(id, metrics_cfg.ids[idx].0);
+        assert_eq!(unit, metrics_cfg.units[idx]);
+        assert_eq!(Approx(value), Approx(path.costs()[idx]));
+        assert_eq!(
+            costs.get(id).map(Approx),
+            Some(Approx(path.length(metrics_cfg.idx_of(id))))
+        );
+    }
+    assert_eq!(costs.get("kilometers").map(Approx), Some(Approx(48.0)));
+    assert!(costs.get("unknown").is_none());
+
+    // checked conversions
+    match costs.try_get_as("meters", &UnitInfo::Kilometers) {
+        Ok(km) => assert_eq!(Approx(km), Approx(48.0)),
+        Err(msg) => panic!("Meters should be convertible into kilometers, but: {}", msg),
+    }
+    assert!(costs.try_get_as("hours", &UnitInfo::Kilometers).is_err());
+    assert!(costs.try_get_as("unknown", &UnitInfo::Kilometers).is_err());
+
+    assert!(costs.approx_eq(&path.clone().costs_view(&graph)));
+    let other_path = shortest_path(OPP_ID, BAC_ID, &graph);
+    assert!(!costs.approx_eq(&other_path.costs_view(&graph)));
+}
+
+#[test]
+fn display() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let path = shortest_path(OPP_ID, STU_ID, &graph);
+    let costs = path.costs_view(&graph);
+
+    // opp -> bac -> wai -> stu
+    let expected = |id: &str, precision: Option<usize>| -> String {
+        match (id, precision) {
+            ("meters", None) => String::from("48000 m"),
+            ("meters", Some(_)) => String::from("48000.0 m"),
+            ("kilometers", None) => String::from("48 km"),
+            ("kilometers", Some(_)) => String::from("48.0 km"),
+            ("kmph", None) => String::from("270 km/h"),
+            ("kmph", Some(_)) => String::from("270.0 km/h"),
+            ("hours", None) => String::from("0.52 h"),
+            ("hours", Some(_)) => String::from("0.5 h"),
+            _ => panic!("Metric {} is unexpected.", id),
+        }
+    };
+    let metric_ids: Vec<&str> = costs.iter().map(|(id, _unit, _value)| id).collect();
+    assert_eq!(
+        format!("{}", costs),
+        metric_ids
+            .iter()
+            .map(|id| expected(id, None))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    assert_eq!(
+        format!("{:.1}", costs),
+        metric_ids
+            .iter()
+            .map(|id| expected(id, Some(1)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
@@ -0,0 +1,32 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{configs, routing::golden};
+
+const OPP_ID: i64 = 26_033_921;
+const BAC_ID: i64 = 26_160_028;
+const WAI_ID: i64 = 252_787_940;
+const END_ID: i64 = 298_249_467;
+const DEA_ID: i64 = 1_621_605_361;
+const STU_ID: i64 = 2_933_335_353;
+
+#[test]
+fn shortest() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+
+    let queries = [
+        (OPP_ID, STU_ID),
+        (OPP_ID, END_ID),
+        (BAC_ID, DEA_ID),
+        (STU_ID, DEA_ID),
+        (END_ID, OPP_ID),
+        (WAI_ID, WAI_ID),
+        // unreachable
+        (DEA_ID, OPP_ID),
+        // unknown
+        (42, STU_ID),
+    ];
+    let actual = golden::dump(&queries, &graph, &routing_cfg);
+    golden::assert_matches(resources::SHORTEST_GOLDEN, &actual);
+}
@@ -0,0 +1,58 @@
+use super::{shortest_path, BAC_ID, OPP_ID, STU_ID, WAI_ID};
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{approximating::Approx, configs};
+
+#[test]
+fn merged_legs_equal_direct_path() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let direct_path = shortest_path(OPP_ID, WAI_ID, &graph);
+    let merged_path = shortest_path(OPP_ID, BAC_ID, &graph)
+        .merge(shortest_path(BAC_ID, WAI_ID, &graph), &graph)
+        .expect("Legs opp->bac and bac->wai should be mergeable.");
+
+    assert_eq!(merged_path, direct_path);
+    assert_eq!(merged_path.flattened_hash(), direct_path.flattened_hash());
+    for (merged_cost, direct_cost) in merged_path.costs().iter().zip(direct_path.costs()) {
+        assert!(
+            Approx(*merged_cost) == Approx(*direct_cost),
+            "Merged costs {:?} should be the direct costs {:?}.",
+            merged_path.costs(),
+            direct_path.costs()
+        );
+    }
+}
+
+#[test]
+fn mismatched_endpoints() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let result =
+        shortest_path(OPP_ID, BAC_ID, &graph).merge(shortest_path(WAI_ID, STU_ID, &graph), &graph);
+    assert!(
+        result.is_err(),
+        "Legs opp->bac and wai->stu shouldn't be mergeable."
+    );
+}
+
+#[test]
+fn repeated_nodes() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // opp -> bac -> wai -> bac -> opp
+    let msg = match shortest_path(OPP_ID, WAI_ID, &graph)
+        .merge(shortest_path(WAI_ID, OPP_ID, &graph), &graph)
+    {
+        Ok(_) => panic!("Merging a path with its return leg should fail."),
+        Err(msg) => format!("{}", msg),
+    };
+    assert!(
+        msg.contains(&format!("{}", OPP_ID)) && msg.contains(&format!("{}", BAC_ID)),
+        "Error-msg should name the repeated nodes, but is: {}",
+        msg
+    );
+}
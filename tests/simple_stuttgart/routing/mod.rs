@@ -1,2 +1,46 @@
+mod comparing;
+mod composite;
+mod corridor;
+mod costs;
 mod fastest;
+mod golden;
+mod merging;
+mod one_to_many;
+mod points;
 mod shortest;
+mod similarity;
+mod sp_tree;
+mod verification;
+
+use crate::helpers::defaults;
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    configs,
+    network::Graph,
+    routing::{
+        dijkstra::{self, Dijkstra},
+        paths::Path,
+    },
+};
+
+const OPP_ID: i64 = 26_033_921;
+const BAC_ID: i64 = 26_160_028;
+const WAI_ID: i64 = 252_787_940;
+const STU_ID: i64 = 2_933_335_353;
+
+fn shortest_path(src_id: i64, dst_id: i64, graph: &Graph) -> Path {
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let nodes = graph.nodes();
+    let mut dijkstra = Dijkstra::new();
+    dijkstra
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(src_id).expect("Src-id should exist."),
+            dst_idx: nodes.idx_from(dst_id).expect("Dst-id should exist."),
+            graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .expect("Path should exist.")
+        .flatten(graph)
+}
@@ -0,0 +1,97 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, NodeIdx},
+    routing::{
+        dijkstra::{self, Dijkstra},
+        paths::Path,
+    },
+};
+
+fn assert_per_pair_costs(graph: &Graph, routing_cfg: &configs::routing::Config) {
+    let nodes = graph.nodes();
+    let mut dijkstra = Dijkstra::new();
+    let mut per_pair_dijkstra = Dijkstra::new();
+
+    // duplicates and unordered dst-nodes are answered in the given order
+    let mut dst_idxs: Vec<NodeIdx> = nodes.iter().collect();
+    dst_idxs.reverse();
+    dst_idxs.push(dst_idxs[0]);
+
+    for src_idx in &nodes {
+        let best_paths = dijkstra.compute_best_paths_from(src_idx, &dst_idxs, graph, routing_cfg);
+        assert_eq!(best_paths.len(), dst_idxs.len());
+
+        for (&dst_idx, best_path) in dst_idxs.iter().zip(best_paths) {
+            let expected_path = per_pair_dijkstra.compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph,
+                routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            });
+            match (best_path, expected_path) {
+                (None, None) => (),
+                (Some(best_path), Some(expected_path)) => {
+                    assert_eq!(best_path.src_idx(), src_idx);
+                    assert_eq!(best_path.dst_idx(), dst_idx);
+                    let cost = |path: Path| {
+                        let path = path.flatten(graph);
+                        routing_cfg
+                            .alphas
+                            .iter()
+                            .zip(path.costs().iter())
+                            .map(|(alpha, value)| alpha * value)
+                            .sum::<f64>()
+                    };
+                    assert!(
+                        Approx(cost(best_path)) == Approx(cost(expected_path)),
+                        "Path (id:{})->(id:{}) should have the same cost as the per-pair query.",
+                        nodes.id(src_idx),
+                        nodes.id(dst_idx)
+                    );
+                }
+                (best_path, _) => panic!(
+                    "Path (id:{})->(id:{}) is {} by the one-to-many query only.",
+                    nodes.id(src_idx),
+                    nodes.id(dst_idx),
+                    if best_path.is_some() {
+                        "found"
+                    } else {
+                        "missed"
+                    }
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn shortest_equals_per_pair() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    assert_per_pair_costs(&graph, &routing_cfg);
+}
+
+#[test]
+fn fastest_equals_per_pair() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'hours' }] }",
+        graph.cfg(),
+    );
+    assert_per_pair_costs(&graph, &routing_cfg);
+}
+
+#[test]
+fn without_dst_nodes() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let mut dijkstra = Dijkstra::new();
+    assert!(dijkstra
+        .compute_best_paths_from(NodeIdx(0), &[], &graph, &routing_cfg)
+        .is_empty());
+}
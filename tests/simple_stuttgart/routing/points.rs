@@ -0,0 +1,152 @@
+use super::{shortest_path, BAC_ID, OPP_ID, STU_ID, WAI_ID};
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::{
+    distance::Kilometers,
+    time::{Hours, Minutes},
+};
+use osmgraphing::{
+    approximating::Approx,
+    configs, io,
+    network::{Graph, MetricIdx},
+    routing::paths::Path,
+};
+use std::fs;
+
+#[test]
+fn points_along_opp_to_stu() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let coord_of = |id: i64| nodes.coord(nodes.idx_from(id).expect("Node-id should exist."));
+
+    // opp -> bac -> wai -> stu
+    let path = shortest_path(OPP_ID, STU_ID, &graph);
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    let length = path.length(km_idx);
+    assert!(Approx(length) == Approx(48.0));
+
+    for &(km, id) in &[(0.0, OPP_ID), (length, STU_ID), (2.0 * length, STU_ID)] {
+        let point = path
+            .point_at(Kilometers(km), &graph)
+            .expect("Point should exist.");
+        assert!(
+            Approx(point) == Approx(coord_of(id)),
+            "Point after {} km should be {}, but is {}.",
+            km,
+            coord_of(id),
+            point
+        );
+    }
+
+    // 24 km is 16 km into bac -> wai, which is 23 km long
+    let point = path
+        .point_at(Kilometers(0.5 * length), &graph)
+        .expect("Midpoint should exist.");
+    let bac = coord_of(BAC_ID);
+    let wai = coord_of(WAI_ID);
+    let t = 16.0 / 23.0;
+    assert!(Approx(point.lat) == Approx(bac.lat + t * (wai.lat - bac.lat)));
+    assert!(Approx(point.lon) == Approx(bac.lon + t * (wai.lon - bac.lon)));
+    assert!(wai.lat < point.lat && point.lat < bac.lat);
+    assert!(wai.lon < point.lon && point.lon < bac.lon);
+
+    assert!(path.try_point_at(Kilometers(-1.0), &graph).is_err());
+}
+
+#[test]
+fn cost_profile_of_opp_to_stu() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // opp -> bac -> wai -> stu
+    let path = shortest_path(OPP_ID, STU_ID, &graph);
+    let km_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let h_idx = graph.cfg().edges.metrics.idx_of(defaults::DURATION_ID);
+    let profile = path.cost_profile(km_idx, h_idx, &graph);
+
+    assert_eq!(
+        profile.len(),
+        4,
+        "Profile should have src and one point per edge."
+    );
+    assert_eq!(profile[0], (0.0, 0.0));
+    for pair in profile.windows(2) {
+        assert!(
+            pair[0].0 < pair[1].0,
+            "Distances of the profile should be increasing, but are {} and {}.",
+            pair[0].0,
+            pair[1].0
+        );
+    }
+    let &(km, h) = profile.last().expect("Profile shouldn't be empty.");
+    assert!(Approx(km) == Approx(48.0));
+    assert!(Approx(h) == Approx(*Hours::from(Minutes(31.3))));
+
+    // export
+
+    let (header, written_profile) = write_cost_profile(&path, km_idx, h_idx, &graph);
+    assert_eq!(
+        header,
+        format!("{} {}", defaults::DISTANCE_ID, defaults::DURATION_ID)
+    );
+    assert_eq!(written_profile.len(), profile.len());
+    for (&(written_km, written_h), &(km, h)) in written_profile.iter().zip(profile.iter()) {
+        assert!(Approx(written_km) == Approx(km));
+        assert!(Approx(written_h) == Approx(h));
+    }
+}
+
+#[test]
+fn written_cost_profile_of_normalized_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::NORMALIZED_FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // opp -> bac -> wai -> stu
+    let path = shortest_path(OPP_ID, STU_ID, &graph);
+    let km_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let h_idx = graph.cfg().edges.metrics.idx_of(defaults::DURATION_ID);
+
+    // written values are denormalized, in contrast to the stored ones
+    let (_header, written_profile) = write_cost_profile(&path, km_idx, h_idx, &graph);
+    let &(km, h) = written_profile.last().expect("Profile shouldn't be empty.");
+    assert!(
+        Approx(km) == Approx(48.0),
+        "Written distance should be 48 km, but is {} km.",
+        km
+    );
+    assert!(
+        Approx(h) == Approx(*Hours::from(Minutes(31.3))),
+        "Written duration should be {} h, but is {} h.",
+        *Hours::from(Minutes(31.3)),
+        h
+    );
+}
+
+/// Writes the path's cost-profile and parses the written header and values.
+fn write_cost_profile(
+    path: &Path,
+    km_idx: MetricIdx,
+    h_idx: MetricIdx,
+    graph: &Graph,
+) -> (String, Vec<(f64, f64)>) {
+    let file = temp_file("cost_profile.csv");
+    if let Err(msg) = io::routing::cost_profile::Writer::write(path, km_idx, h_idx, graph, &file) {
+        panic!("Could not write cost-profile. ERROR: {}", msg);
+    }
+    let content = fs::read_to_string(&file).expect("Cost-profile should be readable.");
+
+    let mut lines = content.lines();
+    let header = lines.next().expect("Cost-profile should have a header.");
+    let profile = lines
+        .map(|line| {
+            let values: Vec<f64> = line
+                .split_whitespace()
+                .map(|value| value.parse().expect("Written values should be floats."))
+                .collect();
+            assert_eq!(values.len(), 2, "Every line should have two values.");
+            (values[0], values[1])
+        })
+        .collect();
+    (header.to_owned(), profile)
+}
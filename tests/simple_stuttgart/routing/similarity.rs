@@ -0,0 +1,122 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::geo::{self, Coordinate};
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    routing::{
+        dijkstra::{self, Dijkstra},
+        paths::similarity,
+    },
+};
+
+const OPP_ID: i64 = 26_033_921;
+const WAI_ID: i64 = 252_787_940;
+const STU_ID: i64 = 2_933_335_353;
+
+fn meters_between(coord_a: &Coordinate, coord_b: &Coordinate) -> f64 {
+    *geo::haversine_distance_km(coord_a, coord_b) * 1_000.0
+}
+
+#[test]
+fn shifted_polylines() {
+    let polyline_a = vec![
+        Coordinate {
+            lat: 48.0,
+            lon: 9.0,
+        },
+        Coordinate {
+            lat: 48.0,
+            lon: 9.1,
+        },
+        Coordinate {
+            lat: 48.0,
+            lon: 9.2,
+        },
+    ];
+    // same lons, but shifted to the north
+    let polyline_b: Vec<_> = polyline_a
+        .iter()
+        .map(|coord| Coordinate {
+            lat: coord.lat + 0.01,
+            lon: coord.lon,
+        })
+        .collect();
+
+    // every coupling of same lons has the same distance
+    let expected = meters_between(&polyline_a[0], &polyline_b[0]);
+    let frechet = similarity::discrete_frechet(&polyline_a, &polyline_b);
+    assert!(
+        Approx(frechet) == Approx(expected),
+        "Fréchet-distance should be {} m, but is {} m.",
+        expected,
+        frechet
+    );
+    let hausdorff = similarity::discrete_hausdorff(&polyline_a, &polyline_b);
+    assert!(Approx(hausdorff) == Approx(expected));
+}
+
+#[test]
+fn reversed_polylines() {
+    let polyline_a = vec![
+        Coordinate {
+            lat: 48.0,
+            lon: 9.0,
+        },
+        Coordinate {
+            lat: 48.0,
+            lon: 9.1,
+        },
+        Coordinate {
+            lat: 48.0,
+            lon: 9.2,
+        },
+    ];
+    let polyline_b: Vec<_> = polyline_a.iter().rev().cloned().collect();
+
+    // Hausdorff ignores the order, but Fréchet has to couple both starts and both ends.
+    let hausdorff = similarity::discrete_hausdorff(&polyline_a, &polyline_b);
+    assert!(Approx(hausdorff) == Approx(0.0));
+    let frechet = similarity::discrete_frechet(&polyline_a, &polyline_b);
+    let expected = meters_between(&polyline_a[0], &polyline_a[2]);
+    assert!(
+        Approx(frechet) == Approx(expected),
+        "Fréchet-distance should be {} m, but is {} m.",
+        expected,
+        frechet
+    );
+}
+
+#[test]
+fn identical_paths() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let nodes = graph.nodes();
+    let mut dijkstra = Dijkstra::new();
+    let mut best_path = |src_id: i64, dst_id: i64| {
+        dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx: nodes.idx_from(src_id).expect("Src-id should exist."),
+                dst_idx: nodes.idx_from(dst_id).expect("Dst-id should exist."),
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .expect("Path should exist.")
+    };
+
+    let opp_to_stu = best_path(OPP_ID, STU_ID);
+    assert!(Approx(similarity::frechet(&opp_to_stu, &opp_to_stu, &graph)) == Approx(0.0));
+    assert!(Approx(similarity::hausdorff(&opp_to_stu, &opp_to_stu, &graph)) == Approx(0.0));
+
+    // opp -> wai is a prefix of opp -> stu, so the remaining distance is from stu to wai
+    let opp_to_wai = best_path(OPP_ID, WAI_ID);
+    let expected = meters_between(
+        &nodes.coord(nodes.idx_from(STU_ID).expect("Node-id should exist.")),
+        &nodes.coord(nodes.idx_from(WAI_ID).expect("Node-id should exist.")),
+    );
+    assert!(Approx(similarity::frechet(&opp_to_stu, &opp_to_wai, &graph)) == Approx(expected));
+    assert!(Approx(similarity::hausdorff(&opp_to_stu, &opp_to_wai, &graph)) == Approx(expected));
+}
@@ -0,0 +1,108 @@
+use super::{BAC_ID, OPP_ID, STU_ID, WAI_ID};
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx, configs, defaults::routing::sp_tree::COLORS, routing::dijkstra::Dijkstra,
+};
+use std::fs;
+
+const END_ID: i64 = 298_249_467;
+const DEA_ID: i64 = 1_621_605_361;
+
+#[test]
+fn costs_and_parents_from_opp() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let nodes = graph.nodes();
+    let idx = |id: i64| nodes.idx_from(id).expect("Node-id should exist.");
+
+    let sp_tree = Dijkstra::new().shortest_path_tree(idx(OPP_ID), &graph, &routing_cfg);
+    assert_eq!(sp_tree.src_idx(), idx(OPP_ID));
+    assert_eq!(sp_tree.reached_count(), nodes.count());
+    assert_eq!(sp_tree.edges().count(), nodes.count() - 1);
+
+    // the same costs as the best paths
+    for &(id, expected) in &[
+        (OPP_ID, 0.0),
+        (BAC_ID, 8.0),
+        (WAI_ID, 31.0),
+        (END_ID, 30.0),
+        (DEA_ID, 9.069),
+        (STU_ID, 48.0),
+    ] {
+        assert_eq!(
+            sp_tree.cost(idx(id)).map(Approx),
+            Some(Approx(expected)),
+            "Cost of node-id {} is wrong.",
+            id
+        );
+    }
+
+    // dea <- bac <- opp
+    let bwd_edges = graph.bwd_edges();
+    let parent_id = |id: i64| {
+        sp_tree
+            .parent_edge(idx(id))
+            .map(|edge_idx| nodes.id(bwd_edges.dst_idx(edge_idx)))
+    };
+    assert_eq!(parent_id(DEA_ID), Some(BAC_ID));
+    assert_eq!(parent_id(BAC_ID), Some(OPP_ID));
+    assert_eq!(parent_id(OPP_ID), None);
+    let path = sp_tree
+        .path_to(idx(DEA_ID), &graph)
+        .expect("Dea should be reached.");
+    assert_eq!(
+        path.node_indices(&graph)
+            .into_iter()
+            .map(|node_idx| nodes.id(node_idx))
+            .collect::<Vec<_>>(),
+        vec![OPP_ID, BAC_ID, DEA_ID]
+    );
+}
+
+#[test]
+fn geojson_by_cost_quantiles() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_yaml(resources::FMI_YAML, graph.cfg());
+    let nodes = graph.nodes();
+    let src_idx = nodes.idx_from(OPP_ID).expect("Node-id should exist.");
+    let sp_tree = Dijkstra::new().shortest_path_tree(src_idx, &graph, &routing_cfg);
+
+    let file = temp_file("sp-tree.geojson");
+    sp_tree
+        .write_geojson(&graph, &file)
+        .expect("Writing geojson should work.");
+    let geojson: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&file).expect("Geojson-file should be readable."))
+            .expect("Geojson-file should be valid json.");
+    assert_eq!(geojson, sp_tree.to_geojson(&graph));
+
+    let features = geojson["features"]
+        .as_array()
+        .expect("Features should be an array.");
+    assert_eq!(features.len(), nodes.count() - 1);
+
+    // 6 reached nodes in 5 quantiles, where opp is cheapest and stu most expensive
+    let quantile_of = |dst_id: i64| {
+        let feature = features
+            .iter()
+            .find(|feature| feature["properties"]["dst-id"] == dst_id)
+            .expect("Every reached node should have its edge.");
+        assert_eq!(
+            feature["geometry"]["coordinates"]
+                .as_array()
+                .map(|coords| coords.len()),
+            Some(2)
+        );
+        let quantile = feature["properties"]["quantile"]
+            .as_u64()
+            .expect("Quantile should be an integer.") as usize;
+        assert_eq!(feature["properties"]["stroke"], COLORS[quantile]);
+        quantile
+    };
+    assert_eq!(quantile_of(BAC_ID), 0);
+    assert_eq!(quantile_of(DEA_ID), 1);
+    assert_eq!(quantile_of(END_ID), 2);
+    assert_eq!(quantile_of(WAI_ID), 3);
+    assert_eq!(quantile_of(STU_ID), 4);
+}
@@ -0,0 +1,54 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{approximating::Approx, configs, routing::verification};
+
+const OPP_ID: i64 = 26_033_921;
+const BAC_ID: i64 = 26_160_028;
+const WAI_ID: i64 = 252_787_940;
+const DEA_ID: i64 = 1_621_605_361;
+const STU_ID: i64 = 2_933_335_353;
+
+#[test]
+fn fmi_graph() {
+    verify_with(resources::FMI_YAML);
+}
+
+#[test]
+fn normalized_fmi_graph() {
+    verify_with(resources::NORMALIZED_FMI_YAML);
+}
+
+fn verify_with(parsing_yaml: &str) {
+    let parsing_cfg = configs::parsing::Config::from_yaml(parsing_yaml);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_yaml(parsing_yaml, graph.cfg());
+
+    let report = match verification::verify(
+        resources::VERIFICATION_JSON,
+        &graph,
+        &routing_cfg,
+        osmgraphing::defaults::routing::verification::TOLERANCE,
+    ) {
+        Ok(report) => report,
+        Err(msg) => panic!("Could not verify routes. ERROR: {}", msg),
+    };
+
+    assert_eq!(report.pairs.len(), 4, "{}", report);
+    assert_eq!(report.passed_count(), 3, "{}", report);
+    assert_eq!(report.failed_count(), 1, "{}", report);
+    assert_eq!(report.unknown_ids, vec![(42, STU_ID)]);
+    assert_eq!(report.unreachable, vec![(DEA_ID, OPP_ID)]);
+
+    // bac -> wai takes 23 km with 120 kmph, but the reference expects 15 minutes
+    let failed = report.failed().next().expect("One route should fail.");
+    assert_eq!((failed.src_id, failed.dst_id), (BAC_ID, WAI_ID));
+    let deviations = failed.deviations;
+    assert!(Approx(deviations.kilometers.expect("Expected km are given.")) == Approx(0.0));
+    let expected_deviation = (23.0 / 120.0 - 0.25) / 0.25;
+    assert!(
+        Approx(deviations.hours.expect("Expected hours are given.")) == Approx(expected_deviation),
+        "Deviation of hours should be {}, but is {:?}.",
+        expected_deviation,
+        deviations.hours
+    );
+}
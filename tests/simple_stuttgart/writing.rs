@@ -0,0 +1,173 @@
+use crate::helpers::{defaults, graph_writing_cfg, parse, temp_file, write_graph};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, writing::network::graph::nodes::ExtraColumn},
+};
+use std::fs;
+
+#[test]
+fn fmi_with_merged_bidirectional_edges() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // write graph merged
+
+    let map_file = temp_file("merged.fmi");
+    let mut writing_cfg = graph_writing_cfg(
+        &map_file,
+        &["node-id", "latitude", "longitude"],
+        &["src-id", "dst-id", "kilometers", "kmph"],
+    );
+    writing_cfg.is_merging_bidirectional = true;
+    write_graph(&graph, &writing_cfg);
+
+    // 6 two-way streets and the one-way street to the dead-end
+
+    let content = fs::read_to_string(&map_file).expect("Written map-file should be readable.");
+    let rows: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| line.split_whitespace().count() > 1)
+        .collect();
+    let edge_rows = rows.len() - graph.nodes().count();
+    assert!(edge_rows < graph.fwd_edges().count());
+    assert_eq!(edge_rows, 7, "Every two-way street should be merged.");
+
+    // re-parse and compare with original
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::MERGED_FMI_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let new_graph = parse(parsing_cfg);
+
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let new_nodes = new_graph.nodes();
+    let new_fwd_edges = new_graph.fwd_edges();
+    assert_eq!(new_nodes.count(), nodes.count());
+    assert_eq!(new_fwd_edges.count(), fwd_edges.count());
+
+    let metric_ids = ["kilometers", "kmph", "hours"];
+    for edge_idx in fwd_edges.iter() {
+        let src_idx = bwd_edges.dst_idx(edge_idx);
+        let dst_idx = fwd_edges.dst_idx(edge_idx);
+        let new_src_idx = new_nodes
+            .idx_from(nodes.id(src_idx))
+            .expect("Written node should be re-parsed.");
+        let new_dst_idx = new_nodes
+            .idx_from(nodes.id(dst_idx))
+            .expect("Written node should be re-parsed.");
+        let new_edge = new_fwd_edges
+            .between(new_src_idx, new_dst_idx)
+            .expect("Written edge should be re-parsed.");
+
+        for metric_id in metric_ids.iter() {
+            let metric_idx = graph.cfg().edges.metrics.idx_of(*metric_id);
+            let new_metric_idx = new_graph.cfg().edges.metrics.idx_of(*metric_id);
+            let value = graph.metrics().column(metric_idx)[*edge_idx];
+            let new_value = new_edge.metrics()[*new_metric_idx];
+            assert!(
+                Approx(new_value) == Approx(value),
+                "Re-parsed {} {} of edge (id:{}->id:{}) should be {}.",
+                metric_id,
+                new_value,
+                nodes.id(src_idx),
+                nodes.id(dst_idx),
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn fmi_with_extra_node_columns() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // write graph with extra columns
+
+    let map_file = temp_file("extra_columns.fmi");
+    let mut writing_cfg = graph_writing_cfg(
+        &map_file,
+        &["node-id", "latitude", "longitude"],
+        &["src-id", "dst-id", "kilometers", "kmph"],
+    );
+    writing_cfg.extra_node_columns = vec![
+        ExtraColumn::OutDegree,
+        ExtraColumn::InDegree,
+        ExtraColumn::Component,
+    ];
+    write_graph(&graph, &writing_cfg);
+
+    // check node-rows: (node-id, out-degree, in-degree)
+
+    let expected_degrees = [
+        (26_033_921, 1, 1),    // Oppenweiler
+        (26_160_028, 4, 3),    // Backnang
+        (1_621_605_361, 0, 1), // Dead-end in Backnang
+        (298_249_467, 3, 3),   // Endersbach
+        (252_787_940, 3, 3),   // Waiblingen
+        (2_933_335_353, 2, 2), // Stuttgart (Schwabstrasse)
+    ];
+    let content = fs::read_to_string(&map_file).expect("Written map-file should be readable.");
+    let node_rows: Vec<Vec<&str>> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|params| params.len() > 1)
+        .take(graph.nodes().count())
+        .collect();
+    assert_eq!(node_rows.len(), expected_degrees.len());
+
+    let mut dead_end_component = None;
+    let mut main_components = Vec::new();
+    for params in node_rows.iter() {
+        assert_eq!(
+            params.len(),
+            6,
+            "Node-row {:?} should have 6 columns.",
+            params
+        );
+        let id: i64 = params[0].parse().expect("Node-id should be an integer.");
+        let out_degree: usize = params[3].parse().expect("Out-degree should be an integer.");
+        let in_degree: usize = params[4].parse().expect("In-degree should be an integer.");
+        let component: usize = params[5].parse().expect("Component should be an integer.");
+
+        let &(_id, expected_out_degree, expected_in_degree) = expected_degrees
+            .iter()
+            .find(|(expected_id, _, _)| *expected_id == id)
+            .expect(&format!("Node-id {} should be written.", id));
+        assert_eq!(
+            out_degree, expected_out_degree,
+            "Out-degree of node-id {}",
+            id
+        );
+        assert_eq!(in_degree, expected_in_degree, "In-degree of node-id {}", id);
+
+        if id == 1_621_605_361 {
+            dead_end_component = Some(component);
+        } else {
+            main_components.push(component);
+        }
+    }
+    let dead_end_component = dead_end_component.expect("Dead-end should be written.");
+    assert!(
+        main_components
+            .iter()
+            .all(|&component| component == main_components[0]),
+        "All nodes except the dead-end should share their component."
+    );
+    assert_ne!(dead_end_component, main_components[0]);
+
+    // re-parse, skipping the extra columns
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::EXTRA_COLUMNS_FMI_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let new_graph = parse(parsing_cfg);
+
+    assert_eq!(new_graph.nodes().count(), graph.nodes().count());
+    assert_eq!(new_graph.fwd_edges().count(), graph.fwd_edges().count());
+}
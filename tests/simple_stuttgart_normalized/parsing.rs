@@ -41,7 +41,7 @@ fn fmi_graph() {
         ("Stuttgart", 2_933_335_353, (48.7701757, 9.1565768)),
     ]
     .into_iter()
-    .map(|(name, id, (lat, lon))| TestNode::new(name, id, Coordinate { lat, lon }, 0, &graph))
+    .map(|(name, id, (lat, lon))| TestNode::new(name, id, Coordinate { lat, lon }, None, &graph))
     .collect();
     let node_opp = &test_nodes[0];
     let node_bac = &test_nodes[1];
@@ -0,0 +1,635 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    balancing::edge_users::{EdgeUser, EdgeUsers},
+    configs::{
+        self,
+        balancing::{DemandSampling, EdgeUsersTracking, InitialWorkload, ResultsFormat},
+        routing::RoutingAlgo,
+    },
+    defaults::balancing as balancing_defaults,
+    io,
+    network::{EdgeIdx, MetricIdx, RoutePair},
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::{collections::BTreeMap, fs};
+
+#[test]
+fn expected_workloads() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        format!(
+            "  route-pairs-file: '{}'",
+            resources::FRACTIONAL_ROUTE_PAIRS
+        ),
+        format!("  algorithm: {}", RoutingAlgo::Dijkstra.name()),
+        "  metrics:",
+        format!("  - id: '{}'", defaults::DISTANCE_ID),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+
+    let route_pairs = io::routing::Parser::parse(&routing_cfg).expect("Parsing should work.");
+    let counts: Vec<f64> = route_pairs.iter().map(|(_, count)| *count).collect();
+    assert_eq!(counts, vec![2.7, 0.5, 1.0, 0.25]);
+
+    let route_pairs =
+        balancing_defaults::sample_route_counts(&route_pairs, 0, &DemandSampling::Expected);
+    assert_eq!(
+        route_pairs
+            .iter()
+            .map(|(_, count)| *count)
+            .collect::<Vec<f64>>(),
+        counts,
+        "Expected counts shouldn't be changed."
+    );
+
+    let mut dijkstra = Dijkstra::new();
+    let mut abs_workloads = vec![0.0; graph.fwd_edges().count()];
+    for (route_pair, count) in &route_pairs {
+        let RoutePair { src, dst } = route_pair.into_node(&graph);
+        let path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx: src.idx(),
+                dst_idx: dst.idx(),
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .expect("Every route-pair should have a path.")
+            .flatten(&graph);
+        balancing_defaults::add_workloads(&mut abs_workloads, &path, *count);
+    }
+
+    // shortest paths by distance:
+    // d->h (2.7), g->f->h (0.5), f->h->d->b->c (1.0), c->b (0.25)
+    let expected_workloads = vec![
+        (3, 7, 2.7),
+        (6, 5, 0.5),
+        (5, 7, 1.5),
+        (7, 3, 1.0),
+        (3, 1, 1.0),
+        (1, 2, 1.0),
+        (2, 1, 0.25),
+    ];
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    for edge_idx in fwd_edges.iter() {
+        // a backward-edge's dst is its forward-edge's src
+        let src_id = nodes.id(bwd_edges.dst_idx(edge_idx));
+        let dst_id = nodes.id(fwd_edges.dst_idx(edge_idx));
+        let expected = expected_workloads
+            .iter()
+            .find(|&&(src, dst, _)| src == src_id && dst == dst_id)
+            .map_or(0.0, |&(_, _, workload)| workload);
+        assert!(
+            Approx(abs_workloads[*edge_idx]) == Approx(expected),
+            "Edge {}->{} has workload {}, but expected {}.",
+            src_id,
+            dst_id,
+            abs_workloads[*edge_idx],
+            expected
+        );
+    }
+}
+
+#[test]
+fn rounded_workloads() {
+    // rounding is the default, expected counts are opt-in
+    assert_eq!(balancing_defaults::DEMAND_SAMPLING, DemandSampling::Rounded);
+
+    let route_pairs: Vec<_> = [2.7, 0.5, 1.0, 0.25, 0.0]
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            (
+                RoutePair {
+                    src: i as i64,
+                    dst: 7,
+                },
+                count,
+            )
+        })
+        .collect();
+    let sampled =
+        balancing_defaults::sample_route_counts(&route_pairs, 0, &DemandSampling::Rounded);
+    assert_eq!(
+        sampled
+            .iter()
+            .map(|(route_pair, count)| (route_pair.src, *count))
+            .collect::<Vec<_>>(),
+        vec![(0, 3.0), (1, 1.0), (2, 1.0)],
+        "Counts should be rounded, and route-pairs of count 0 should be removed."
+    );
+}
+
+#[test]
+fn poisson_workloads() {
+    let lambda = 2.7;
+    let num_of_route_pairs = 10_000;
+    let route_pairs = vec![(RoutePair { src: 3, dst: 7 }, lambda); num_of_route_pairs];
+    let demand_sampling = DemandSampling::Poisson { seed: 42 };
+
+    let total = |iteration| -> f64 {
+        let sampled =
+            balancing_defaults::sample_route_counts(&route_pairs, iteration, &demand_sampling);
+        assert!(
+            sampled
+                .iter()
+                .all(|(_, count)| *count >= 1.0 && count.fract() == 0.0),
+            "Sampled counts should be positive integers."
+        );
+        sampled.iter().map(|(_, count)| count).sum()
+    };
+
+    // reproducible
+    assert_eq!(total(0), total(0));
+    assert_eq!(total(1), total(1));
+    assert_ne!(
+        total(0),
+        total(1),
+        "Every iteration should be sampled anew."
+    );
+
+    // mean with standard-error sqrt(lambda / n) ~ 0.016
+    for iteration in 0..3 {
+        let mean = total(iteration) / (num_of_route_pairs as f64);
+        assert!(
+            (mean - lambda).abs() < 0.1,
+            "Sampled mean {} of iteration {} differs too much from {}.",
+            mean,
+            iteration,
+            lambda
+        );
+    }
+}
+
+#[test]
+fn lazily_sampled_workloads() {
+    let route_pairs: Vec<_> = (0..1_000)
+        .map(|i| (RoutePair { src: i, dst: i + 1 }, (i % 4) as f64 * 0.75))
+        .collect();
+
+    for demand_sampling in &[
+        DemandSampling::Rounded,
+        DemandSampling::Expected,
+        DemandSampling::Poisson { seed: 42 },
+    ] {
+        for iteration in 0..2 {
+            let sampled: Vec<_> =
+                balancing_defaults::sample_route_counts(&route_pairs, iteration, demand_sampling)
+                    .into_iter()
+                    .map(|(route_pair, count)| (route_pair.src, route_pair.dst, count))
+                    .collect();
+            let lazily_sampled = balancing_defaults::sample_route_counts_lazily(
+                route_pairs.iter().map(|&route_pair| Ok(route_pair)),
+                iteration,
+                demand_sampling,
+            )
+            .map(|route_pair| {
+                route_pair.map(|(route_pair, count)| (route_pair.src, route_pair.dst, count))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Sampling should work.");
+            assert_eq!(
+                lazily_sampled, sampled,
+                "Lazily sampled route-pairs should equal the sampled ones for {:?}.",
+                demand_sampling
+            );
+        }
+    }
+}
+
+#[test]
+fn delta_results() {
+    // setup results-dir with base-graph of iteration 0
+
+    let results_dir = temp_file("delta-results");
+    let base_dir = io::balancing::Reader::iter_dir(balancing_defaults::BASE_ITER, &results_dir);
+    fs::create_dir_all(&base_dir).expect("Results-dir should be creatable.");
+    fs::copy(
+        resources::BALANCING_ITERATION_YAML,
+        base_dir.join(balancing_defaults::files::ITERATION_CFG),
+    )
+    .expect("Iteration-cfg should be copyable.");
+    fs::copy(resources::GRAPH_FMI, base_dir.join("graph.fmi"))
+        .expect("Map-file should be copyable.");
+
+    let balancing_cfg = configs::balancing::Config::from_str(&format!(
+        "{}\n{}\n{}",
+        "balancing:",
+        format!("  results-dir: '{}'", results_dir.display()),
+        "  iter-0-cfg: 'iteration.yaml'
+  iter-i-cfg: 'iteration.yaml'
+  multi-ch-constructor:
+    fmi-graph: 'graph.fmi'
+    contracted-graph: 'graph.ch.fmi'
+    dimension: 3
+    is_printing_osm-ids: false
+    is_using_external_edge-ids: false
+  number_of_metric-updates: 2
+  monitoring:
+    edges-info:
+      file: 'edges-info.csv'
+      ids: []
+  optimizing_with:
+    metric-id: 'workload'
+    method: averaging
+  min_new_metric: 0.001
+  results-format: delta",
+    ));
+    assert_eq!(balancing_cfg.results_format, ResultsFormat::Delta);
+
+    let parsing_cfg =
+        io::balancing::Reader::try_parsing_cfg_of(&base_dir).expect("Base-cfg should be readable.");
+    let mut graph = parse(parsing_cfg);
+    io::balancing::Writer::write_base(&graph, &base_dir).expect("Base should be writable.");
+
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "{}\n{}\n{}\n{}\n{}",
+            "routing:",
+            format!(
+                "  route-pairs-file: '{}'",
+                resources::FRACTIONAL_ROUTE_PAIRS
+            ),
+            format!("  algorithm: {}", RoutingAlgo::Dijkstra.name()),
+            "  metrics:",
+            format!("  - id: '{}'", defaults::DISTANCE_ID),
+        ),
+        graph.cfg(),
+    );
+    let route_pairs = io::routing::Parser::parse(&routing_cfg).expect("Parsing should work.");
+
+    // run 2 iterations, each writing the delta of the next iteration's graph
+
+    let mut dijkstra = Dijkstra::new();
+    let mut expected_columns = Vec::new();
+    for iter in 0..2 {
+        let mut abs_workloads = vec![0.0; graph.fwd_edges().count()];
+        for (route_pair, count) in &route_pairs {
+            let RoutePair { src, dst } = route_pair.into_node(&graph);
+            let path = dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx: src.idx(),
+                    dst_idx: dst.idx(),
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                })
+                .expect("Every route-pair should have a path.")
+                .flatten(&graph);
+            balancing_defaults::add_workloads(&mut abs_workloads, &path, *count);
+        }
+        balancing_defaults::update_new_metric(iter, &abs_workloads, &mut graph, &balancing_cfg)
+            .expect("Updating the metric should work.");
+
+        let next_dir = io::balancing::Reader::iter_dir(iter + 1, &results_dir);
+        fs::create_dir_all(&next_dir).expect("Iteration-dir should be creatable.");
+        io::balancing::Writer::write_delta(iter + 1, &graph, &next_dir, &balancing_cfg)
+            .expect("Delta should be writable.");
+        let delta = fs::read_to_string(next_dir.join(balancing_defaults::files::DELTA))
+            .expect("Delta should be readable.");
+        assert_eq!(
+            delta.lines().next(),
+            Some("stable-id workload"),
+            "Without edge-ids, the delta should be keyed by stable ids."
+        );
+        let metrics = graph.metrics();
+        expected_columns.push(
+            (0..graph.cfg().edges.metrics.units.len())
+                .map(|metric_idx| metrics.column(MetricIdx(metric_idx)).to_vec())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    // reconstruct iterations and compare with the graphs held in memory
+
+    for (iter, expected_columns) in (1..).zip(expected_columns.iter()) {
+        let reconstructed = io::balancing::Reader::reconstruct(iter, &results_dir);
+        assert_eq!(reconstructed.nodes().count(), graph.nodes().count());
+        assert_eq!(reconstructed.fwd_edges().count(), graph.fwd_edges().count());
+
+        let metrics = reconstructed.metrics();
+        for (metric_idx, expected_column) in expected_columns.iter().enumerate() {
+            let actual_column = metrics.column(MetricIdx(metric_idx));
+            assert_eq!(actual_column.len(), expected_column.len());
+            for (edge_idx, (&actual, &expected)) in
+                actual_column.iter().zip(expected_column.iter()).enumerate()
+            {
+                assert!(
+                    Approx(actual) == Approx(expected),
+                    "Iteration {} has reconstructed {} for metric {} of edge {}, \
+                     but the balancer had {}.",
+                    iter,
+                    actual,
+                    metric_idx,
+                    edge_idx,
+                    expected
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn delta_results_by_stable_ids() {
+    // The fixture's delta lists its edges in reverse order, hence the values have to be matched
+    // by their stable ids.
+    let graph = io::balancing::Reader::reconstruct(1, resources::BALANCING_DELTA_RESULTS);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let workload_idx = graph.cfg().edges.metrics.idx_of("workload");
+    let node = |id| nodes.idx_from(id).expect("Node should exist.");
+
+    let expected_workloads = vec![
+        ((0, 1), 0.5),
+        ((1, 2), 1.5),
+        ((2, 0), 2.5),
+        ((2, 3), 3.5),
+        ((3, 1), 4.5),
+    ];
+    assert_eq!(fwd_edges.count(), expected_workloads.len());
+    for ((src_id, dst_id), expected) in expected_workloads {
+        let edge_idx = fwd_edges
+            .between(node(src_id), node(dst_id))
+            .expect("Edge should exist.")
+            .idx();
+        let actual = graph.metrics().column(workload_idx)[*edge_idx];
+        assert!(
+            Approx(actual) == Approx(expected),
+            "Edge ({} -> {}) has reconstructed workload {}, but the delta has {}.",
+            src_id,
+            dst_id,
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn freeflow_initial_workload() {
+    let balancing_cfg = configs::balancing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    assert_eq!(
+        balancing_cfg.initial_workload,
+        InitialWorkload::Freeflow {
+            lane_count_id: "lane-count".into(),
+            distance_id: defaults::DISTANCE_ID.into(),
+        }
+    );
+    assert!(
+        balancing_defaults::is_using_new_metric(0, &balancing_cfg),
+        "Iteration 0 should route with the workload already."
+    );
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    let mut graph = parse(parsing_cfg);
+    balancing_defaults::init_new_metric(&mut graph, &balancing_cfg)
+        .expect("Initializing the workload should work.");
+
+    // lane-count * distance, normalized by its mean
+
+    let metrics_cfg = &graph.cfg().edges.metrics;
+    let metrics = graph.metrics();
+    let products: Vec<f64> = metrics
+        .column(metrics_cfg.idx_of("lane-count"))
+        .iter()
+        .zip(
+            metrics
+                .column(metrics_cfg.idx_of(defaults::DISTANCE_ID))
+                .iter(),
+        )
+        .map(|(lane_count, distance)| lane_count * distance)
+        .collect();
+    let mean = products.iter().sum::<f64>() / products.len() as f64;
+    let workloads = metrics.column(metrics_cfg.idx_of("workload"));
+    assert_eq!(workloads.len(), products.len());
+    for (workload, product) in workloads.iter().zip(products.iter()) {
+        assert_eq!(Approx(*workload), Approx(product / mean));
+    }
+    assert!(workloads.iter().all(|&workload| workload > 0.0));
+
+    // default keeps today's behaviour
+
+    let mut balancing_cfg = balancing_cfg;
+    balancing_cfg.initial_workload = InitialWorkload::Zero;
+    assert!(!balancing_defaults::is_using_new_metric(0, &balancing_cfg));
+    assert!(balancing_defaults::is_using_new_metric(1, &balancing_cfg));
+}
+
+#[test]
+fn uniform_initial_workload() {
+    let mut balancing_cfg =
+        configs::balancing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    balancing_cfg.initial_workload = InitialWorkload::Uniform(3.0);
+    assert!(balancing_defaults::is_using_new_metric(0, &balancing_cfg));
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    parsing_cfg.edges.metrics.are_normalized = true;
+    let mut graph = parse(parsing_cfg);
+    balancing_defaults::init_new_metric(&mut graph, &balancing_cfg)
+        .expect("Initializing the workload should work.");
+
+    // normalized by its mean, which is denormalized to the configured value
+    let workload_idx = graph.cfg().edges.metrics.idx_of("workload");
+    let metrics = graph.metrics();
+    assert!(metrics
+        .column(workload_idx)
+        .iter()
+        .all(|&workload| Approx(workload) == Approx(1.0)));
+    assert_eq!(metrics.mean(workload_idx), Some(3.0));
+
+    balancing_cfg.initial_workload = InitialWorkload::Uniform(0.0);
+    assert!(balancing_defaults::init_new_metric(&mut graph, &balancing_cfg).is_err());
+}
+
+#[test]
+fn zero_workloads_reset_mean() {
+    let mut balancing_cfg =
+        configs::balancing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    balancing_cfg.initial_workload = InitialWorkload::Uniform(3.0);
+    balancing_cfg.optimization.method =
+        configs::balancing::OptimizationMethod::ExplicitEuler { correction: 1.0 };
+    balancing_cfg.min_new_metric = None;
+    balancing_cfg.is_err_when_metric_is_zero = false;
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    parsing_cfg.edges.metrics.are_normalized = true;
+    let mut graph = parse(parsing_cfg);
+    balancing_defaults::init_new_metric(&mut graph, &balancing_cfg)
+        .expect("Initializing the workload should work.");
+    let workload_idx = graph.cfg().edges.metrics.idx_of("workload");
+    assert_eq!(graph.metrics().mean(workload_idx), Some(3.0));
+
+    // nothing has been routed, hence the zero workloads can't be normalized
+    let abs_workloads = vec![0.0; graph.fwd_edges().count()];
+    balancing_defaults::update_new_metric(1, &abs_workloads, &mut graph, &balancing_cfg)
+        .expect("Zero workloads should be accepted.");
+    let metrics = graph.metrics();
+    assert!(metrics
+        .column(workload_idx)
+        .iter()
+        .all(|&workload| workload == 0.0));
+    assert_eq!(
+        metrics.mean(workload_idx),
+        Some(1.0),
+        "The zero workloads are kept as they are, hence the old mean shouldn't be kept."
+    );
+}
+
+#[test]
+fn validating_balancing_cfg() {
+    // generated metrics are part of the graph's config after parsing
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    let graph = parse(parsing_cfg);
+    let mut balancing_cfg =
+        configs::balancing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    assert!(balancing_cfg.validate(graph.cfg()).is_ok());
+
+    balancing_cfg.edge_users_tracking = Some(EdgeUsersTracking {
+        top_k: 0,
+        min_workload: 1.0,
+    });
+    assert!(
+        balancing_cfg.validate(graph.cfg()).is_err(),
+        "Tracking edge-users needs a top-k of at least 1."
+    );
+    balancing_cfg.edge_users_tracking = None;
+
+    balancing_cfg.optimization.metric_id = "not-a-metric".into();
+    assert!(
+        balancing_cfg.validate(graph.cfg()).is_err(),
+        "The optimized metric should be part of the graph."
+    );
+}
+
+#[test]
+fn tracking_edge_users() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        "routing:",
+        format!(
+            "  route-pairs-file: '{}'",
+            resources::FRACTIONAL_ROUTE_PAIRS
+        ),
+        format!("  algorithm: {}", RoutingAlgo::Dijkstra.name()),
+        "  metrics:",
+        format!("  - id: '{}'", defaults::DISTANCE_ID),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+
+    // 3 pairs of counts 2.7, 0.5 and 1.0
+    let mut route_pairs = io::routing::Parser::parse(&routing_cfg).expect("Parsing should work.");
+    route_pairs.truncate(3);
+    let mut pair_indices = BTreeMap::new();
+    for (pair_idx, (route_pair, _count)) in route_pairs.iter().enumerate() {
+        pair_indices.insert((route_pair.src, route_pair.dst), pair_idx);
+    }
+
+    let mut dijkstra = Dijkstra::new();
+    let mut abs_workloads = vec![0.0; graph.fwd_edges().count()];
+    let mut paths = Vec::with_capacity(route_pairs.len());
+    for (route_pair, count) in &route_pairs {
+        let RoutePair { src, dst } = route_pair.into_node(&graph);
+        let path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx: src.idx(),
+                dst_idx: dst.idx(),
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .expect("Every route-pair should have a path.")
+            .flatten(&graph);
+        balancing_defaults::add_workloads(&mut abs_workloads, &path, *count);
+        paths.push((path, *count));
+    }
+
+    let tracking = EdgeUsersTracking {
+        top_k: 2,
+        min_workload: 1.0,
+    };
+    let edge_users = EdgeUsers::from_paths(&paths, &abs_workloads, &pair_indices, &tracking);
+
+    let mut tracked_count = 0;
+    for edge_idx in (0..abs_workloads.len()).map(EdgeIdx) {
+        if abs_workloads[*edge_idx] < tracking.min_workload {
+            assert!(
+                edge_users.top_users(edge_idx).is_none(),
+                "Edge {} is below the threshold and shouldn't be tracked.",
+                *edge_idx
+            );
+            continue;
+        }
+        tracked_count += 1;
+
+        // every pair has one path, so its contribution is its count
+        let mut expected: Vec<(usize, f64)> = paths
+            .iter()
+            .enumerate()
+            .filter(|(_, (path, _))| path.iter().any(|&idx| idx == edge_idx))
+            .map(|(pair_idx, (_, count))| (pair_idx, *count))
+            .collect();
+        expected.sort_by(|a, b| Approx(b.1).cmp(&Approx(a.1)).then(a.0.cmp(&b.0)));
+        expected.truncate(tracking.top_k);
+
+        let actual: Vec<(usize, f64)> = edge_users
+            .top_users(edge_idx)
+            .expect("Edges above the threshold should be tracked.")
+            .iter()
+            .map(|user| (user.pair_idx, user.count))
+            .collect();
+        assert_eq!(actual, expected, "Edge {} has wrong top-users.", *edge_idx);
+    }
+    assert_eq!(edge_users.len(), tracked_count);
+    assert!(tracked_count > 0);
+
+    // the counts of a route-pair's paths are summed up, whether they are consecutive or not
+
+    let (path, count) = paths[0].clone();
+    let mut split_paths = vec![(path.clone(), count - 1.0), (path.clone(), 0.5)];
+    split_paths.extend(paths[1..].iter().cloned());
+    split_paths.push((path, 0.5));
+    let split_edge_users =
+        EdgeUsers::from_paths(&split_paths, &abs_workloads, &pair_indices, &tracking);
+    assert_eq!(split_edge_users.len(), edge_users.len());
+    for edge_idx in edge_users.tracked_edges() {
+        let to_approx = |users: &[EdgeUser]| -> Vec<(usize, Approx<f64>)> {
+            users
+                .iter()
+                .map(|user| (user.pair_idx, Approx(user.count)))
+                .collect()
+        };
+        assert_eq!(
+            split_edge_users.top_users(edge_idx).map(to_approx),
+            edge_users.top_users(edge_idx).map(to_approx),
+            "Edge {} has wrong top-users after splitting a route-pair's count.",
+            *edge_idx
+        );
+    }
+
+    // one line per user and the header
+    let file = temp_file("edge-users.csv");
+    edge_users
+        .write_csv(&graph, &file)
+        .expect("Writing edge-users should work.");
+    let content = fs::read_to_string(&file).expect("Edge-users should be readable.");
+    let user_count: usize = edge_users
+        .tracked_edges()
+        .map(|edge_idx| {
+            edge_users
+                .top_users(edge_idx)
+                .map_or(0, |users| users.len())
+        })
+        .sum();
+    assert_eq!(content.lines().count(), 1 + user_count);
+}
@@ -0,0 +1,287 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    balancing::ch_rebuild::{Decision, Reason, Trigger},
+    configs::{self, balancing::ChRebuild, SimpleId},
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::fs;
+
+const METRIC_ID: &str = "kilometers";
+
+fn thresholds() -> Option<ChRebuild> {
+    Some(ChRebuild {
+        max_relative_delta: 0.5,
+        mean_relative_delta: 0.05,
+    })
+}
+
+/// Multiplies the metric's value of the edge from src to dst, which is no shortcut, leaving the
+/// shortcuts stale.
+fn scale_edge(graph: &mut Graph, src_id: i64, dst_id: i64, factor: f64) {
+    let metric_idx = graph.cfg().edges.metrics.idx_of(METRIC_ID);
+    let edge_idx = {
+        let nodes = graph.nodes();
+        let src_idx = nodes.idx_from(src_id).expect("Src-id should exist.");
+        let dst_idx = nodes.idx_from(dst_id).expect("Dst-id should exist.");
+        let fwd_edges = graph.fwd_edges();
+        let edge = fwd_edges
+            .between(src_idx, dst_idx)
+            .expect("Edge should exist.");
+        assert!(!edge.is_shortcut());
+        edge.idx()
+    };
+    graph.metrics_mut().column_mut(metric_idx)[*edge_idx] *= factor;
+}
+
+/// Multiplies the metric's values of all edges, which are no shortcuts, leaving the shortcuts
+/// stale.
+fn scale_edges(graph: &mut Graph, factor: f64) {
+    let metric_idx = graph.cfg().edges.metrics.idx_of(METRIC_ID);
+    let is_shortcut: Vec<bool> = {
+        let fwd_edges = graph.fwd_edges();
+        fwd_edges
+            .iter()
+            .map(|edge_idx| fwd_edges.is_shortcut(edge_idx))
+            .collect()
+    };
+    let mut metrics = graph.metrics_mut();
+    for (value, &is_shortcut) in metrics
+        .column_mut(metric_idx)
+        .iter_mut()
+        .zip(is_shortcut.iter())
+    {
+        if !is_shortcut {
+            *value *= factor;
+        }
+    }
+}
+
+fn non_shortcut_count(graph: &Graph) -> usize {
+    let fwd_edges = graph.fwd_edges();
+    fwd_edges
+        .iter()
+        .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+        .count()
+}
+
+fn best_cost(graph: &Graph, algorithm: &str, src_id: i64, dst_id: i64) -> Option<f64> {
+    let nodes = graph.nodes();
+    all_costs(graph, algorithm)[*nodes.idx_from(src_id).expect("Src-id should exist.")
+        * nodes.count()
+        + *nodes.idx_from(dst_id).expect("Dst-id should exist.")]
+}
+
+/// Returns the best path's cost for every pair of nodes, which are connected.
+fn all_costs(graph: &Graph, algorithm: &str) -> Vec<Option<f64>> {
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: '{}', metrics: [{{ id: '{}' }}] }}",
+            algorithm, METRIC_ID
+        ),
+        graph.cfg(),
+    );
+    let metric_idx = graph.cfg().edges.metrics.idx_of(METRIC_ID);
+    let nodes = graph.nodes();
+    let mut dijkstra = Dijkstra::new();
+    let mut costs = Vec::new();
+    for src_idx in nodes.iter() {
+        for dst_idx in nodes.iter() {
+            costs.push(
+                dijkstra
+                    .compute_best_path(dijkstra::Query {
+                        src_idx,
+                        dst_idx,
+                        graph,
+                        routing_cfg: &routing_cfg,
+                        edge_bias: None,
+                        path_hint: dijkstra::PathHint::Auto,
+                    })
+                    .map(|path| path.flatten(graph).costs()[*metric_idx]),
+            );
+        }
+    }
+    costs
+}
+
+fn contracted_trigger(graph: &Graph) -> Trigger {
+    let mut trigger = Trigger::new(SimpleId::from(METRIC_ID), thresholds());
+    assert_eq!(
+        trigger
+            .decide(0, true, graph)
+            .expect("Deciding should work."),
+        Decision::Rebuild
+    );
+    trigger
+        .on_contracted(0, true, graph)
+        .expect("Remembering the contraction should work.");
+    trigger
+}
+
+#[test]
+fn first_contraction() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let mut trigger = contracted_trigger(&graph);
+    let record = trigger.records()[0];
+    assert_eq!(record.reason, Reason::NoHierarchy);
+    assert_eq!(record.hierarchy_iter, None);
+    assert_eq!(record.relative_delta, None);
+
+    // the hierarchy ignoring the metric doesn't fit the metric
+    assert_eq!(
+        trigger
+            .decide(1, false, &graph)
+            .expect("Deciding should work."),
+        Decision::Rebuild
+    );
+    assert_eq!(trigger.records()[1].reason, Reason::ChangedMetrics);
+}
+
+#[test]
+fn below_threshold() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    assert!(graph.shortcut_count() > 0);
+    let mut trigger = contracted_trigger(&graph);
+
+    // h -> c is cheaper than h -> d -> b -> c after the change, which changes the shortest path
+    let (h, c) = (7, 2);
+    assert_eq!(
+        best_cost(&graph, "Dijkstra", h, c).map(Approx),
+        Some(Approx(0.003))
+    );
+    scale_edge(&mut graph, h, c, 0.6);
+    assert_eq!(
+        trigger
+            .decide(1, true, &graph)
+            .expect("Deciding should work."),
+        Decision::Skip
+    );
+    let record = trigger.records()[1];
+    assert_eq!(record.reason, Reason::BelowThreshold);
+    assert_eq!(record.hierarchy_iter, Some(0));
+    let relative_delta = record
+        .relative_delta
+        .expect("Skipping should be based on the relative delta.");
+    assert_eq!(Approx(relative_delta.max), Approx(0.4));
+    assert_eq!(
+        Approx(relative_delta.mean),
+        Approx(0.4 / non_shortcut_count(&graph) as f64)
+    );
+
+    // the hierarchy still fits the changed metric
+    let metric_idx = graph.cfg().edges.metrics.idx_of(METRIC_ID);
+    graph.recompute_shortcuts(metric_idx);
+    assert_eq!(
+        trigger
+            .verify(
+                &graph,
+                osmgraphing::defaults::balancing::ch_rebuild::VERIFIED_QUERY_COUNT
+            )
+            .expect("Verifying should work."),
+        Decision::Skip
+    );
+    assert_eq!(trigger.records()[1].reason, Reason::BelowThreshold);
+    assert_eq!(
+        best_cost(&graph, "Dijkstra", h, c).map(Approx),
+        Some(Approx(0.0024))
+    );
+    let ch_costs = all_costs(&graph, "CHDijkstra");
+    let costs = all_costs(&graph, "Dijkstra");
+    assert!(costs.iter().any(Option::is_some));
+    for (&ch_cost, &cost) in ch_costs.iter().zip(costs.iter()) {
+        assert!(
+            Approx(ch_cost) == Approx(cost),
+            "CH-Dijkstra's cost {:?} should match Dijkstra's cost {:?} after patching the \
+             shortcuts.",
+            ch_cost,
+            cost
+        );
+    }
+}
+
+#[test]
+fn stale_hierarchy() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let mut trigger = Trigger::new(
+        SimpleId::from(METRIC_ID),
+        Some(ChRebuild {
+            max_relative_delta: 10.0,
+            mean_relative_delta: 1.0,
+        }),
+    );
+    trigger
+        .decide(0, true, &graph)
+        .expect("Deciding should work.");
+    trigger
+        .on_contracted(0, true, &graph)
+        .expect("Remembering the contraction should work.");
+
+    // Contracting d hasn't added a shortcut e -> h, since e -> f -> h has been a witness.
+    // After the change, e -> d -> h is the shortest path, which CH-Dijkstra can't find.
+    let (e, f, h) = (4, 5, 7);
+    scale_edge(&mut graph, e, f, 5.0);
+    assert_eq!(
+        trigger
+            .decide(1, true, &graph)
+            .expect("Deciding should work."),
+        Decision::Skip
+    );
+    let metric_idx = graph.cfg().edges.metrics.idx_of(METRIC_ID);
+    graph.recompute_shortcuts(metric_idx);
+    assert_eq!(
+        best_cost(&graph, "Dijkstra", e, h).map(Approx),
+        Some(Approx(0.003))
+    );
+    assert_eq!(
+        best_cost(&graph, "CHDijkstra", e, h).map(Approx),
+        Some(Approx(0.006))
+    );
+
+    assert_eq!(
+        trigger
+            .verify(
+                &graph,
+                osmgraphing::defaults::balancing::ch_rebuild::VERIFIED_QUERY_COUNT
+            )
+            .expect("Verifying should work."),
+        Decision::Rebuild
+    );
+    let record = trigger.records()[1];
+    assert_eq!(record.decision, Decision::Rebuild);
+    assert_eq!(record.reason, Reason::StaleHierarchy);
+}
+
+#[test]
+fn above_threshold() {
+    let mut graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let metadata_file = temp_file("ch-rebuild.yaml");
+
+    let mut trigger = contracted_trigger(&graph);
+    trigger
+        .append_last_to(&metadata_file)
+        .expect("Appending the decision should work.");
+
+    scale_edges(&mut graph, 2.0);
+    assert_eq!(
+        trigger
+            .decide(1, true, &graph)
+            .expect("Deciding should work."),
+        Decision::Rebuild
+    );
+    trigger
+        .append_last_to(&metadata_file)
+        .expect("Appending the decision should work.");
+    assert_eq!(trigger.records()[1].reason, Reason::AboveThreshold);
+
+    // the decision-log is appended to the run's metadata
+    let metadata = fs::read_to_string(&metadata_file).expect("Metadata should be readable.");
+    assert_eq!(
+        metadata,
+        "ch-rebuilds:\n\
+         - { iteration: 0, decision: rebuild, reason: no-hierarchy }\n\
+         - { iteration: 1, decision: rebuild, reason: above-threshold, hierarchy-iteration: 0, \
+         max-relative-delta: 1, mean-relative-delta: 1 }\n"
+    );
+}
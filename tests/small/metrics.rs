@@ -0,0 +1,250 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, routing::RoutingAlgo},
+    network::{MetricIdx, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn columns_match_edges() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let fwd_edges = graph.fwd_edges();
+    let metrics = graph.metrics();
+
+    assert_eq!(
+        metrics.columns().count(),
+        metrics.dim(),
+        "Every metric should have its own column."
+    );
+    for (metric_idx, column) in metrics.columns() {
+        assert_eq!(
+            column.len(),
+            fwd_edges.count(),
+            "Column of metric {} should contain every edge.",
+            graph.cfg().edges.metrics.ids[*metric_idx]
+        );
+        assert_eq!(metrics.column(metric_idx), column);
+
+        for edge_idx in fwd_edges.iter() {
+            let edge_metrics = fwd_edges.half_edge(edge_idx).metrics();
+            assert_eq!(
+                column[*edge_idx],
+                edge_metrics[*metric_idx],
+                "Column of metric {} differs from edge {}.",
+                graph.cfg().edges.metrics.ids[*metric_idx],
+                *edge_idx
+            );
+            assert_eq!(edge_metrics, &metrics.get(edge_idx));
+            assert_eq!(edge_metrics, &metrics[edge_idx]);
+        }
+    }
+
+    // sorted by (src-id, dst-id)
+    let expected_kilometers = vec![
+        1.0, 1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 2.0, 1.0, 1.0, 1.0, 1.0, 1.0, 4.0, 1.0, 1.0,
+    ]
+    .into_iter()
+    .map(|meters| meters / 1_000.0)
+    .collect::<Vec<f64>>();
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    assert!(
+        Approx(metrics.column(km_idx)) == Approx(&expected_kilometers[..]),
+        "Column of kilometers is {:?}, but should be {:?}.",
+        metrics.column(km_idx),
+        expected_kilometers
+    );
+}
+
+#[test]
+fn edges_follow_mutated_columns() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let mut graph = parse(parsing_cfg);
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+
+    {
+        let mut metrics = graph.metrics_mut();
+        let mut column = metrics.column_mut(km_idx);
+        for (edge_idx, value) in column.iter_mut().enumerate() {
+            *value = edge_idx as f64;
+        }
+    }
+
+    let fwd_edges = graph.fwd_edges();
+    let metrics = graph.metrics();
+    for edge_idx in fwd_edges.iter() {
+        assert_eq!(metrics[edge_idx][*km_idx], *edge_idx as f64);
+        assert_eq!(
+            fwd_edges.half_edge(edge_idx).metrics()[*km_idx],
+            *edge_idx as f64,
+            "Edge {} should see the mutated column.",
+            *edge_idx
+        );
+    }
+}
+
+#[test]
+fn dijkstra_costs_match_columns() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let raw_cfg = format!(
+        "{}\n{}\n{}\n{}",
+        "routing:",
+        format!("  algorithm: {}", RoutingAlgo::Dijkstra.name()),
+        "  metrics:",
+        format!("  - id: '{}'", defaults::DISTANCE_ID),
+    );
+    let routing_cfg = configs::routing::Config::from_str(&raw_cfg, graph.cfg());
+
+    let nodes = graph.nodes();
+    let metrics = graph.metrics();
+    let mut dijkstra = Dijkstra::new();
+    for src_idx in (0..nodes.count()).map(NodeIdx) {
+        for dst_idx in (0..nodes.count()).map(NodeIdx) {
+            let path = match dijkstra.compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            }) {
+                Some(path) => path.flatten(&graph),
+                None => continue,
+            };
+
+            for (metric_idx, column) in metrics.columns() {
+                let expected: f64 = path.iter().map(|edge_idx| column[**edge_idx]).sum();
+                assert!(
+                    Approx(path.costs()[*metric_idx]) == Approx(expected),
+                    "Path (idx:{}->idx:{}) has costs {} of metric {}, but its edges sum up to {}.",
+                    *src_idx,
+                    *dst_idx,
+                    path.costs()[*metric_idx],
+                    graph.cfg().edges.metrics.ids[*metric_idx],
+                    expected
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn unknown_metric_accessors() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let metrics_cfg = &graph.cfg().edges.metrics;
+
+    let msg = match metrics_cfg.try_idx_of("not-a-metric") {
+        Ok(_) => panic!("Unknown metric-ids should be refused."),
+        Err(msg) => format!("{}", msg),
+    };
+    assert!(msg.contains("not-a-metric"));
+    for id in &metrics_cfg.ids {
+        assert!(
+            msg.contains(&id.0),
+            "The error should list the available id {}, but is: {}",
+            id,
+            msg
+        );
+    }
+
+    let metric_count = metrics_cfg.ids.len();
+    assert!(metrics_cfg.try_id_of(MetricIdx(metric_count)).is_err());
+    assert!(metrics_cfg.try_unit_of(MetricIdx(metric_count)).is_err());
+    let metric_idx = metrics_cfg.idx_of(&metrics_cfg.ids[0]);
+    assert_eq!(metrics_cfg.id_of(metric_idx), &metrics_cfg.ids[0]);
+    assert_eq!(metrics_cfg.unit_of(metric_idx), &metrics_cfg.units[0]);
+}
+
+#[test]
+fn metric_quantiles() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+
+    // 13 edges of 1 m, 2 edges of 2 m and 1 edge of 4 m
+    let mut sorted_column = graph.metrics().column(km_idx).to_vec();
+    sorted_column.sort_by(|a, b| a.partial_cmp(b).expect("Metric-values should be comparable."));
+    let qs = [0.0, 0.25, 0.5, 0.8, 0.85, 1.0];
+    let expected: Vec<f64> = qs
+        .iter()
+        .map(|&q| {
+            let h = ((sorted_column.len() - 1) as f64) * q;
+            let lo = h.floor() as usize;
+            let hi = h.ceil() as usize;
+            sorted_column[lo] + (h - lo as f64) * (sorted_column[hi] - sorted_column[lo])
+        })
+        .collect();
+    let quantiles = graph.metric_quantiles(km_idx, &qs);
+    assert!(
+        Approx(&quantiles[..]) == Approx(&expected[..]),
+        "Quantiles of kilometers are {:?}, but should be {:?}.",
+        quantiles,
+        expected
+    );
+    assert!(Approx(&quantiles[..]) == Approx(&[0.001, 0.001, 0.001, 0.001, 0.00175, 0.004][..]));
+
+    // cached
+    assert_eq!(graph.metric_quantiles(km_idx, &qs), quantiles);
+
+    assert!(graph.try_metric_quantiles(km_idx, &[1.5]).is_err());
+    assert!(graph
+        .try_metric_quantiles(MetricIdx(graph.metrics().dim()), &[0.5])
+        .is_err());
+}
+
+#[test]
+fn auto_tolerated_scales() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    let hours_idx = graph.cfg().edges.metrics.idx_of("hours");
+
+    let mut routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [\
+         { id: 'kilometers', tolerated-scale: 'auto(0.2)' }, \
+         { id: 'hours', tolerated-scale: '1.5' }] }",
+        graph.cfg(),
+    );
+    assert_eq!(routing_cfg.auto_tolerated_scales[*km_idx], Some(0.2));
+    assert_eq!(routing_cfg.auto_tolerated_scales[*hours_idx], None);
+    // not derived yet, but kept when embedding
+    assert!(routing_cfg
+        .to_embedded(graph.cfg())
+        .contains("\"tolerated-scale\": \"auto(0.2)\""));
+
+    if let Err(msg) = routing_cfg.validate(&graph) {
+        panic!("Auto-tolerated-scales should be derivable, but: {}", msg);
+    }
+    let quantiles = graph.metric_quantiles(km_idx, &[0.25, 0.5, 0.75]);
+    let cv = (quantiles[2] - quantiles[0]) / (1.349 * quantiles[1]);
+    let tolerated_scale = routing_cfg.tolerated_scales[*km_idx];
+    assert!(tolerated_scale.is_finite());
+    assert!(
+        Approx(tolerated_scale) == Approx(1.0 + 0.2 * (1.0 + cv)),
+        "Tolerated scale of kilometers is {}, but should be {}.",
+        tolerated_scale,
+        1.0 + 0.2 * (1.0 + cv)
+    );
+    assert_eq!(routing_cfg.tolerated_scales[*hours_idx], 1.5);
+    assert!(routing_cfg.auto_tolerated_scales.iter().all(Option::is_none));
+
+    // the effective config contains the derived scale
+    let recovered_cfg =
+        configs::routing::Config::from_str(&routing_cfg.to_embedded(graph.cfg()), graph.cfg());
+    assert!(Approx(recovered_cfg.tolerated_scales[*km_idx]) == Approx(tolerated_scale));
+    assert!(recovered_cfg.auto_tolerated_scales.iter().all(Option::is_none));
+
+    // configs built for a graph are derived right away
+    let graph_cfg = configs::routing::Config::from_str_for_graph(
+        "routing: { algorithm: 'Dijkstra', metrics: [\
+         { id: 'kilometers', tolerated-scale: 'auto(0.2)' }, \
+         { id: 'hours', tolerated-scale: '1.5' }] }",
+        &graph,
+    );
+    assert_eq!(graph_cfg.tolerated_scales, routing_cfg.tolerated_scales);
+    assert!(graph_cfg.auto_tolerated_scales.iter().all(Option::is_none));
+}
@@ -1,2 +1,12 @@
+#[cfg(feature = "gpl")]
+mod balancing;
+mod ch_rebuild;
+mod metrics;
 mod parsing;
 mod routing;
+#[cfg(feature = "gpl")]
+mod runner;
+mod subgraph;
+#[cfg(feature = "tracing")]
+mod tracing;
+mod writing;
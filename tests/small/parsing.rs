@@ -1,4 +1,6 @@
-use crate::helpers::{assert_graph, assert_graph_sloppy, defaults, parse, TestEdge, TestNode};
+use crate::helpers::{
+    assert_graph, assert_graph_sloppy, defaults, parse, temp_file, TestEdge, TestNode,
+};
 use defaults::paths::resources::small as resources;
 use kissunits::{
     distance::Kilometers,
@@ -6,7 +8,20 @@ use kissunits::{
     speed::KilometersPerHour,
     time::{Hours, Seconds},
 };
-use osmgraphing::{configs, network::EdgeIdx};
+use osmgraphing::{
+    approximating::Approx,
+    configs::{
+        self,
+        parsing::{
+            edges::{cycleway, roughness, EdgeCategory},
+            nodes::NodeCategory,
+        },
+    },
+    io,
+    network::{vehicles::Category as VehicleCategory, EdgeIdx, Graph, NodeIdx, StreetCategory},
+};
+use osmpbfreader::{Tags, Way, WayId};
+use std::{collections::BTreeMap, convert::TryFrom, fs, path::PathBuf};
 
 #[test]
 fn ch_fmi_yaml() {
@@ -37,14 +52,14 @@ fn fmi_graph() {
     // nodes sorted by id
     // name, id, decimicro_lat, decimicro_lon
     let test_nodes = vec![
-        TestNode::new("a", 0, Coordinate::zero(), 0, &graph),
-        TestNode::new("b", 1, Coordinate::zero(), 0, &graph),
-        TestNode::new("c", 2, Coordinate::zero(), 0, &graph),
-        TestNode::new("d", 3, Coordinate::zero(), 0, &graph),
-        TestNode::new("e", 4, Coordinate::zero(), 0, &graph),
-        TestNode::new("f", 5, Coordinate::zero(), 0, &graph),
-        TestNode::new("g", 6, Coordinate::zero(), 0, &graph),
-        TestNode::new("h", 7, Coordinate::zero(), 0, &graph),
+        TestNode::new("a", 0, Coordinate::zero(), None, &graph),
+        TestNode::new("b", 1, Coordinate::zero(), None, &graph),
+        TestNode::new("c", 2, Coordinate::zero(), None, &graph),
+        TestNode::new("d", 3, Coordinate::zero(), None, &graph),
+        TestNode::new("e", 4, Coordinate::zero(), None, &graph),
+        TestNode::new("f", 5, Coordinate::zero(), None, &graph),
+        TestNode::new("g", 6, Coordinate::zero(), None, &graph),
+        TestNode::new("h", 7, Coordinate::zero(), None, &graph),
     ];
     let node_a = &test_nodes[0];
     let node_b = &test_nodes[1];
@@ -139,4 +154,989 @@ fn ch_fmi_graph() {
     let expected_node_count = 8;
     let expected_edge_count = 18;
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
+    assert!(graph.are_leaving_edges_level_sorted());
+    assert!(graph.nodes().has_levels());
+}
+
+#[test]
+fn fmi_graph_without_levels() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let nodes = graph.nodes();
+    assert!(!nodes.has_levels());
+    assert_eq!(nodes.level(NodeIdx(0)), None);
+    assert_eq!(nodes.create(NodeIdx(0)).ch_level(), None);
+    assert!(!graph.are_leaving_edges_level_sorted());
+}
+
+#[test]
+fn unsorted_ch_fmi_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::UNSORTED_CH_FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let expected_node_count = 8;
+    let expected_edge_count = 18;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
+
+    // the file's edges are not sorted by level, hence finalizing has to sort them
+    let nodes = graph.nodes();
+    for xwd_edges in &[graph.fwd_edges(), graph.bwd_edges()] {
+        for node_idx in nodes.iter() {
+            let levels: Vec<_> = xwd_edges
+                .starting_from(node_idx)
+                .map(|leaving_edge| nodes.level(leaving_edge.dst_idx()))
+                .collect();
+            let mut sorted_levels = levels.clone();
+            sorted_levels.sort_by(|a, b| b.cmp(a));
+            assert_eq!(
+                levels,
+                sorted_levels,
+                "Leaving edges of node {} should be sorted descendingly by their dst's level.",
+                nodes.id(node_idx)
+            );
+        }
+    }
+    assert!(graph.are_leaving_edges_level_sorted());
+}
+
+#[test]
+fn fmi_with_invalid_utf8() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+
+    // prepend a comment-line with invalid UTF-8
+    let mut bytes = b"# \xff\n".to_vec();
+    bytes.extend(fs::read(&parsing_cfg.map_files[0]).expect("Small fmi-file should be readable."));
+    let map_file = temp_file("invalid.fmi");
+    fs::write(&map_file, bytes).expect("Temporary fmi-file should be writable.");
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+
+    match io::network::graph::Parser::parse_and_finalize(parsing_cfg) {
+        Ok(_) => panic!("Parsing invalid UTF-8 should fail."),
+        Err(msg) => {
+            let msg = format!("{}", msg);
+            assert!(
+                msg.contains("Line 1 "),
+                "Error-msg should name the line-number, but is: {}",
+                msg
+            );
+        }
+    }
+}
+
+/// Parses the small fmi-file's content from the given map-file.
+fn parse_small_fmi(map_file: &str, is_lossy_utf8: bool) -> Result<Graph, String> {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg.map_files = vec![PathBuf::from(map_file)];
+    parsing_cfg.is_lossy_utf8 = is_lossy_utf8;
+    io::network::graph::Parser::parse_and_finalize(parsing_cfg).map_err(|msg| format!("{}", msg))
+}
+
+fn assert_same_graph(graph: &Graph, expected: &Graph) {
+    assert_eq!(graph.nodes().count(), expected.nodes().count());
+    assert_eq!(graph.fwd_edges().count(), expected.fwd_edges().count());
+    for (metric_idx, column) in expected.metrics().columns() {
+        assert_eq!(graph.metrics().column(metric_idx), column);
+    }
+}
+
+#[test]
+fn fmi_with_bom() {
+    let expected = parse_small_fmi(resources::GRAPH_FMI, false).expect("Small fmi should parse.");
+    match parse_small_fmi(resources::GRAPH_BOM_FMI, false) {
+        Ok(graph) => assert_same_graph(&graph, &expected),
+        Err(msg) => panic!("The BOM should be skipped, but parsing failed: {}", msg),
+    }
+}
+
+#[test]
+fn fmi_in_latin1() {
+    match parse_small_fmi(resources::GRAPH_LATIN1_FMI, false) {
+        Ok(_) => panic!("Parsing latin-1 should fail without lossy-utf8."),
+        Err(msg) => {
+            // 'ß' of '# Straßen' is 0xdf in latin-1
+            for expected in &["Line 1 ", "0xdf", "byte-offset 6", "latin-1", "lossy-utf8"] {
+                assert!(
+                    msg.contains(expected),
+                    "Error-msg should contain '{}', but is: {}",
+                    expected,
+                    msg
+                );
+            }
+        }
+    }
+
+    let expected = parse_small_fmi(resources::GRAPH_FMI, false).expect("Small fmi should parse.");
+    match parse_small_fmi(resources::GRAPH_LATIN1_FMI, true) {
+        Ok(graph) => assert_same_graph(&graph, &expected),
+        Err(msg) => panic!(
+            "Lossy parsing of latin-1-comments should work, but: {}",
+            msg
+        ),
+    }
+}
+
+/// Parses the small fmi-file with a modified edge-count and returns the error-msg.
+fn parse_with_edge_count(edge_count: usize) -> String {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+
+    let content =
+        fs::read_to_string(&parsing_cfg.map_files[0]).expect("Small fmi-file should be readable.");
+    let declaration = "# edge-count\n19\n";
+    assert!(
+        content.contains(declaration),
+        "Small fmi-file should declare 19 edges."
+    );
+    let content = content.replace(declaration, &format!("# edge-count\n{}\n", edge_count));
+    let map_file = temp_file(&format!("{}_edges.fmi", edge_count));
+    fs::write(&map_file, content).expect("Temporary fmi-file should be writable.");
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+
+    match io::network::graph::Parser::parse_and_finalize(parsing_cfg) {
+        Ok(_) => panic!("Parsing a wrong edge-count ({}) should fail.", edge_count),
+        Err(msg) => format!("{}", msg),
+    }
+}
+
+#[test]
+fn fmi_with_over_declared_edge_count() {
+    let msg = parse_with_edge_count(20);
+    assert!(
+        msg.contains("declares 20 edges, but has only 19 edge-lines, ending with line 38"),
+        "Error-msg should name both counts and the last line, but is: {}",
+        msg
+    );
+}
+
+#[test]
+fn fmi_with_under_declared_edge_count() {
+    let msg = parse_with_edge_count(18);
+    assert!(
+        msg.contains("declares 8 nodes and 18 edges, but has 1 more functional lines, beginning with line 38"),
+        "Error-msg should name the counts and the first surplus line, but is: {}",
+        msg
+    );
+}
+
+#[test]
+fn fmi_with_over_declared_node_count() {
+    match parse_small_fmi(resources::GRAPH_OVER_DECLARED_NODES_FMI, false) {
+        Ok(_) => panic!("Parsing a too high node-count should fail."),
+        Err(msg) => assert!(
+            msg.contains(
+                "declares 19 edges, but has only 18 edge-lines, ending with line 39. \
+                 If all edges are there, the node-count 9 is too high."
+            ),
+            "Error-msg should name the counts and the last line, but is: {}",
+            msg
+        ),
+    }
+}
+
+#[test]
+fn fmi_with_under_declared_node_count() {
+    match parse_small_fmi(resources::GRAPH_UNDER_DECLARED_NODES_FMI, false) {
+        Ok(_) => panic!("Parsing a too low node-count should fail."),
+        Err(msg) => {
+            for expected in &[
+                "Line 17: ",
+                "This is the first edge-line after the 7 declared nodes",
+            ] {
+                assert!(
+                    msg.contains(expected),
+                    "Error-msg should contain '{}', but is: {}",
+                    expected,
+                    msg
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn split_fmi_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::SPLIT_FMI_YAML);
+    assert_eq!(parsing_cfg.map_files.len(), 2);
+    let merged_graph = parse(parsing_cfg);
+
+    assert_graph_sloppy(
+        graph.nodes().count(),
+        graph.fwd_edges().count(),
+        &merged_graph,
+    );
+
+    let (nodes, merged_nodes) = (graph.nodes(), merged_graph.nodes());
+    for node_idx in (0..nodes.count()).map(NodeIdx) {
+        let expected = TestNode::from(nodes.create(node_idx));
+        let merged = TestNode::from(merged_nodes.create(node_idx));
+        assert_eq!(
+            expected, merged,
+            "Expected node {} but merged node is {}.",
+            expected, merged
+        );
+    }
+
+    let (fwd_edges, merged_fwd_edges) = (graph.fwd_edges(), merged_graph.fwd_edges());
+    let (bwd_edges, merged_bwd_edges) = (graph.bwd_edges(), merged_graph.bwd_edges());
+    let (metrics, merged_metrics) = (graph.metrics(), merged_graph.metrics());
+    for edge_idx in (0..fwd_edges.count()).map(EdgeIdx) {
+        assert_eq!(
+            fwd_edges.dst_idx(edge_idx),
+            merged_fwd_edges.dst_idx(edge_idx),
+            "Merged fwd-edge {} has wrong dst-idx.",
+            edge_idx
+        );
+        assert_eq!(
+            bwd_edges.dst_idx(edge_idx),
+            merged_bwd_edges.dst_idx(edge_idx),
+            "Merged bwd-edge {} has wrong dst-idx.",
+            edge_idx
+        );
+        assert_eq!(
+            metrics.get(edge_idx),
+            merged_metrics.get(edge_idx),
+            "Merged edge {} has wrong metrics.",
+            edge_idx
+        );
+    }
+}
+
+#[test]
+fn map_files_with_mixed_extensions() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::SPLIT_FMI_YAML);
+    parsing_cfg.map_files[1].set_extension("pbf");
+    assert!(
+        io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err(),
+        "Map-files with fmi- and pbf-extension shouldn't be merged."
+    );
+}
+
+#[test]
+fn directional_durations_by_gradient() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::GRADIENT_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let hours_idx = graph.cfg().edges.metrics.idx_of("hours");
+    let directional_idx = graph.cfg().edges.metrics.idx_of("directional-hours");
+
+    let edge_metrics = |src_id: i64, dst_id: i64| {
+        let src_idx = nodes.idx_from(src_id).expect("Src-id should exist.");
+        let dst_idx = nodes.idx_from(dst_id).expect("Dst-id should exist.");
+        fwd_edges
+            .between(src_idx, dst_idx)
+            .expect("Edge should exist.")
+            .metrics()
+    };
+    let uphill = edge_metrics(0, 1);
+    let downhill = edge_metrics(1, 0);
+    let flat = edge_metrics(1, 2);
+
+    // 1 km with 20 km/h, independent of the direction
+    for metrics in &[&uphill, &downhill] {
+        assert!(
+            Approx(metrics[*hours_idx]) == Approx(0.05),
+            "Non-directional duration should be 0.05 h, but is {} h.",
+            metrics[*hours_idx]
+        );
+    }
+
+    // 5 % uphill scales the speed by 1 / (1 + 10 * 0.05)
+    assert!(
+        Approx(uphill[*directional_idx]) == Approx(0.075),
+        "Uphill duration should be 0.075 h, but is {} h.",
+        uphill[*directional_idx]
+    );
+    // 5 % downhill would double the speed, but is capped at 1.5
+    assert!(
+        Approx(downhill[*directional_idx]) == Approx(0.05 / 1.5),
+        "Downhill duration should be {} h, but is {} h.",
+        0.05 / 1.5,
+        downhill[*directional_idx]
+    );
+    assert!(
+        Approx(uphill[*directional_idx] / downhill[*directional_idx]) == Approx(2.25),
+        "Uphill should take 2.25 times as long as downhill."
+    );
+    assert!(
+        Approx(flat[*directional_idx]) == Approx(flat[*hours_idx]),
+        "Flat edges shouldn't be affected by the gradient."
+    );
+}
+
+#[test]
+fn directional_metric_without_gradient() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::GRADIENT_FMI_YAML);
+    let generating_cfg = parsing_cfg
+        .generating
+        .as_mut()
+        .expect("Gradient-config should generate metrics.");
+    for category in generating_cfg.edges.categories.iter_mut() {
+        if let configs::parsing::generating::edges::Category::Calc { gradient, .. } = category {
+            *gradient = None;
+        }
+    }
+    assert!(
+        io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err(),
+        "A directional metric without gradient shouldn't be generated."
+    );
+}
+
+#[test]
+fn durations_by_capped_speeds() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CAPPED_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let kmph_idx = graph.cfg().edges.metrics.idx_of("kmph");
+    let capped_idx = graph.cfg().edges.metrics.idx_of("capped-kmph");
+    let hours_idx = graph.cfg().edges.metrics.idx_of("hours");
+
+    let edge_metrics = |src_id: i64, dst_id: i64| {
+        let src_idx = nodes.idx_from(src_id).expect("Src-id should exist.");
+        let dst_idx = nodes.idx_from(dst_id).expect("Dst-id should exist.");
+        fwd_edges
+            .between(src_idx, dst_idx)
+            .expect("Edge should exist.")
+            .metrics()
+    };
+    let fast = edge_metrics(0, 1);
+    let slow = edge_metrics(1, 2);
+
+    // 1 km with 100 km/h, but capped at 80 km/h
+    assert!(
+        Approx(fast[*hours_idx]) == Approx(1.0 / 80.0),
+        "Duration should be {} h, but is {} h.",
+        1.0 / 80.0,
+        fast[*hours_idx]
+    );
+    assert!(Approx(fast[*capped_idx]) == Approx(80.0));
+    // parsed speeds stay untouched
+    assert!(Approx(fast[*kmph_idx]) == Approx(100.0));
+
+    // 1 km with 50 km/h
+    assert!(
+        Approx(slow[*hours_idx]) == Approx(1.0 / 50.0),
+        "Duration should be {} h, but is {} h.",
+        1.0 / 50.0,
+        slow[*hours_idx]
+    );
+    assert!(Approx(slow[*capped_idx]) == Approx(50.0));
+    assert!(Approx(slow[*kmph_idx]) == Approx(50.0));
+}
+
+#[test]
+fn non_positive_max_speed() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CAPPED_FMI_YAML);
+    let proto_cfg = configs::parsing::vehicles::ProtoConfig {
+        category: parsing_cfg.vehicles.category,
+        are_drivers_picky: parsing_cfg.vehicles.are_drivers_picky,
+        max_speed: Some(0.0),
+    };
+    assert!(
+        configs::parsing::vehicles::Config::try_from(proto_cfg).is_err(),
+        "A max-speed of 0 km/h shouldn't be accepted."
+    );
+    parsing_cfg.vehicles.max_speed = None;
+    let graph = parse(parsing_cfg);
+    let capped_idx = graph.cfg().edges.metrics.idx_of("capped-kmph");
+    // edges are sorted by src, so edge 0 is the faster street
+    assert!(
+        Approx(graph.metrics().get(EdgeIdx(0))[*capped_idx]) == Approx(100.0),
+        "Without max-speed, speeds shouldn't be capped."
+    );
+}
+
+#[test]
+fn roughness_by_tags() {
+    let roughness_cfg = roughness::Config::default();
+    let way = |tags: &[(&str, &str)]| {
+        let mut way_tags = Tags::new();
+        for (key, value) in tags {
+            way_tags.insert((*key).into(), (*value).into());
+        }
+        Way {
+            id: WayId(0),
+            tags: way_tags,
+            nodes: Vec::new(),
+        }
+    };
+
+    for (tags, expected) in &[
+        (
+            vec![("highway", "residential"), ("surface", "asphalt")],
+            1.0,
+        ),
+        (vec![("highway", "residential"), ("surface", "gravel")], 1.5),
+        (vec![("highway", "track"), ("tracktype", "grade4")], 1.8),
+        // surface is more specific than tracktype
+        (
+            vec![
+                ("highway", "track"),
+                ("surface", "asphalt"),
+                ("tracktype", "grade4"),
+            ],
+            1.0,
+        ),
+        // untagged or unknown values use the street-category's default
+        (vec![("highway", "residential")], 1.0),
+        (vec![("highway", "track")], 1.5),
+        (vec![("highway", "residential"), ("surface", "lava")], 1.0),
+    ] {
+        let way = way(tags);
+        let street_category = StreetCategory::from(&way).expect("Highway-tag should be known.");
+        let roughness = street_category.parse_roughness(&way, &roughness_cfg);
+        assert!(
+            Approx(roughness) == Approx(*expected),
+            "Way with tags {:?} has roughness {}, but expected {}.",
+            tags,
+            roughness,
+            expected
+        );
+    }
+}
+
+#[test]
+fn roughness_factors_are_positive() {
+    for factor in &[0.0, -1.0, std::f64::NAN] {
+        let mut surface = BTreeMap::new();
+        surface.insert(String::from("gravel"), *factor);
+        let proto_cfg = roughness::ProtoConfig {
+            is_applied_to_duration: None,
+            surface: Some(surface),
+            tracktype: None,
+        };
+        assert!(
+            roughness::Config::try_from(proto_cfg).is_err(),
+            "Roughness-factor {} should be rejected.",
+            factor
+        );
+    }
+}
+
+#[test]
+fn durations_by_roughness() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::ROUGHNESS_FMI_YAML);
+    assert!(parsing_cfg.edges.roughness.is_applied_to_duration);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let hours_idx = graph.cfg().edges.metrics.idx_of("hours");
+    let roughness_idx = graph.cfg().edges.metrics.idx_of("roughness");
+
+    // 1 km with 20 km/h on asphalt, gravel, grade4 and untagged
+    for (src_id, dst_id, expected_roughness) in
+        &[(0, 1, 1.0), (1, 2, 1.5), (2, 3, 1.8), (3, 4, 1.0)]
+    {
+        let src_idx = nodes.idx_from(*src_id).expect("Src-id should exist.");
+        let dst_idx = nodes.idx_from(*dst_id).expect("Dst-id should exist.");
+        let metrics = fwd_edges
+            .between(src_idx, dst_idx)
+            .expect("Edge should exist.")
+            .metrics();
+
+        assert!(
+            Approx(metrics[*roughness_idx]) == Approx(*expected_roughness),
+            "Edge (id:{}->id:{}) has roughness {}, but expected {}.",
+            src_id,
+            dst_id,
+            metrics[*roughness_idx],
+            expected_roughness
+        );
+        assert!(
+            Approx(metrics[*hours_idx]) == Approx(0.05 * expected_roughness),
+            "Edge (id:{}->id:{}) takes {} h, but should take {} h.",
+            src_id,
+            dst_id,
+            metrics[*hours_idx],
+            0.05 * expected_roughness
+        );
+    }
+}
+
+#[test]
+fn conditional_restriction_by_tags() {
+    let way = |tags: &[(&str, &str)]| {
+        let mut way_tags = Tags::new();
+        way_tags.insert("highway".into(), "residential".into());
+        for (key, value) in tags {
+            way_tags.insert((*key).into(), (*value).into());
+        }
+        Way {
+            id: WayId(0),
+            tags: way_tags,
+            nodes: Vec::new(),
+        }
+    };
+
+    for (tags, vehicle_category, is_restricted) in &[
+        // simple restrictions
+        (
+            vec![("motor_vehicle:conditional", "no @ (Mo-Fr 07:00-09:00)")],
+            VehicleCategory::Car,
+            true,
+        ),
+        (
+            vec![("motorcar:conditional", "delivery @ (Mo-Sa 08:00-11:00)")],
+            VehicleCategory::Car,
+            true,
+        ),
+        // multiple restrictions, also with separators in conditions
+        (
+            vec![(
+                "access:conditional",
+                "yes @ (Mo-Fr 06:00-20:00; Sa 08:00-12:00); no @ (22:00-06:00)",
+            )],
+            VehicleCategory::Car,
+            true,
+        ),
+        (
+            vec![("vehicle:conditional", "No @ (wet)")],
+            VehicleCategory::Bicycle,
+            true,
+        ),
+        (
+            vec![("bicycle:conditional", "no @ (Su)")],
+            VehicleCategory::Bicycle,
+            true,
+        ),
+        // permissions aren't restrictions
+        (
+            vec![("motor_vehicle:conditional", "yes @ (Mo-Fr 06:00-20:00)")],
+            VehicleCategory::Car,
+            false,
+        ),
+        (
+            vec![("access:conditional", "designated @ (Sa,Su)")],
+            VehicleCategory::Pedestrian,
+            false,
+        ),
+        // other vehicles
+        (
+            vec![("bicycle:conditional", "no @ (Su)")],
+            VehicleCategory::Car,
+            false,
+        ),
+        (
+            vec![("hgv:conditional", "no @ (weight > 7.5)")],
+            VehicleCategory::Car,
+            false,
+        ),
+        // malformed or missing conditions
+        (
+            vec![("motor_vehicle:conditional", "no")],
+            VehicleCategory::Car,
+            false,
+        ),
+        (
+            vec![("motor_vehicle:conditional", " @ (Mo-Fr 07:00-09:00)")],
+            VehicleCategory::Car,
+            false,
+        ),
+        (vec![], VehicleCategory::Car, false),
+    ] {
+        let restriction =
+            StreetCategory::Residential.parse_conditional_restriction(&way(tags), vehicle_category);
+        assert_eq!(
+            restriction.is_some(),
+            *is_restricted,
+            "Way with tags {:?} for {:?} has restriction {:?}.",
+            tags,
+            vehicle_category,
+            restriction
+        );
+    }
+
+    // raw conditions are kept
+    let restriction = StreetCategory::Residential.parse_conditional_restriction(
+        &way(&[
+            ("motor_vehicle:conditional", "no @ (Mo-Fr 07:00-09:00)"),
+            ("access:conditional", "destination @ (Sa)"),
+        ]),
+        &VehicleCategory::Car,
+    );
+    assert_eq!(
+        restriction.as_ref().map(|condition| condition.as_ref()),
+        Some(
+            "access:conditional=destination @ (Sa) \
+             | motor_vehicle:conditional=no @ (Mo-Fr 07:00-09:00)"
+        )
+    );
+}
+
+#[test]
+fn conditions_of_parallel_edges() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CONDITIONAL_OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let kmph_idx = graph.cfg().edges.metrics.idx_of("kmph");
+    let idx_of = |id: i64| nodes.idx_from(id).expect("Node-id should exist.");
+
+    // the parallel edges 1->2 are distinguished, although they share their src and dst
+    let parallel_edges: Vec<_> = fwd_edges
+        .starting_from(idx_of(1))
+        .filter(|edge| edge.dst_idx() == idx_of(2))
+        .collect();
+    assert_eq!(
+        parallel_edges.len(),
+        2,
+        "Both parallel edges should be kept."
+    );
+    for edge in parallel_edges {
+        let condition = graph.restriction_condition(edge.idx());
+        if Approx(edge.metrics()[*kmph_idx]) == Approx(30.0) {
+            assert_eq!(
+                condition,
+                Some("motor_vehicle:conditional=no @ (Mo-Fr 07:00-09:00)"),
+                "The restricted edge should keep its condition."
+            );
+        } else {
+            assert_eq!(
+                condition, None,
+                "The unrestricted parallel edge shouldn't have a condition."
+            );
+        }
+    }
+
+    let edge = fwd_edges
+        .between(idx_of(2), idx_of(3))
+        .expect("Edge 2->3 should exist.");
+    assert_eq!(graph.restriction_condition(edge.idx()), None);
+}
+
+#[test]
+fn cycleway_comfort_by_tags() {
+    let cycleway_cfg = cycleway::Config::default();
+    let way = |tags: &[(&str, &str)]| {
+        let mut way_tags = Tags::new();
+        way_tags.insert("highway".into(), "residential".into());
+        for (key, value) in tags {
+            way_tags.insert((*key).into(), (*value).into());
+        }
+        Way {
+            id: WayId(0),
+            tags: way_tags,
+            nodes: Vec::new(),
+        }
+    };
+
+    // (tags, comfort, is_separated_track)
+    for (tags, expected_comfort, is_track) in &[
+        (vec![("cycleway", "lane")], 0.3, false),
+        (vec![("cycleway", "track")], 0.0, true),
+        (vec![("cycleway", "opposite_lane")], 0.3, false),
+        (vec![("cycleway", "no")], 1.0, false),
+        (vec![], 1.0, false),
+        // side-specific tags
+        (vec![("cycleway:right", "Track")], 0.0, true),
+        (vec![("cycleway:both", "lane")], 0.3, false),
+        // a cycleway on one side is enough
+        (
+            vec![("cycleway:left", "no"), ("cycleway:right", "lane")],
+            0.3,
+            false,
+        ),
+        // unknown values are like missing cycleways
+        (vec![("cycleway", "rainbow")], 1.0, false),
+    ] {
+        let way = way(tags);
+        let comfort = StreetCategory::Residential.parse_cycleway_comfort(&way, &cycleway_cfg);
+        assert!(
+            Approx(comfort) == Approx(*expected_comfort),
+            "Way with tags {:?} has cycleway-comfort {}, but expected {}.",
+            tags,
+            comfort,
+            expected_comfort
+        );
+        assert_eq!(
+            StreetCategory::Residential.has_cycle_track(&way),
+            *is_track,
+            "Way with tags {:?} should{} have a separated cycle-track.",
+            tags,
+            if *is_track { "" } else { "n't" }
+        );
+    }
+}
+
+#[test]
+fn cycleway_comforts_are_non_negative() {
+    let mut comfort = BTreeMap::new();
+    comfort.insert(String::from("lane"), -0.5);
+    let proto_cfg = cycleway::ProtoConfig {
+        comfort: Some(comfort),
+        track_speed_factor: None,
+    };
+    assert!(cycleway::Config::try_from(proto_cfg).is_err());
+
+    for factor in &[0.0, -1.0, std::f64::NAN] {
+        let proto_cfg = cycleway::ProtoConfig {
+            comfort: None,
+            track_speed_factor: Some(*factor),
+        };
+        assert!(
+            cycleway::Config::try_from(proto_cfg).is_err(),
+            "Speed-factor {} of cycle-tracks should be rejected.",
+            factor
+        );
+    }
+}
+
+#[test]
+fn checked_index_construction() {
+    assert_eq!(EdgeIdx::try_new(0, 1).ok(), Some(EdgeIdx(0)));
+    assert_eq!(NodeIdx::try_new(41, 42).ok(), Some(NodeIdx(41)));
+
+    let compact_limit = osmgraphing::defaults::capacity::max_edge_count(true);
+    assert_eq!(compact_limit, u32::MAX as usize);
+    assert_eq!(
+        osmgraphing::defaults::capacity::max_edge_count(false),
+        usize::MAX
+    );
+    assert!(EdgeIdx::try_new(compact_limit - 1, compact_limit).is_ok());
+
+    // the limit itself isn't addressable anymore
+    let msg = match EdgeIdx::try_new(compact_limit, compact_limit) {
+        Ok(_) => panic!("Edge-indices beyond the limit should be refused."),
+        Err(msg) => format!("{}", msg),
+    };
+    assert!(msg.contains(&compact_limit.to_string()));
+    assert!(NodeIdx::try_new(42, 42).is_err());
+    assert!(NodeIdx::try_new(usize::MAX, usize::MAX).is_err());
+}
+
+/// Returns the category as entry of a parsing-config's `nodes` or `data`.
+fn yaml_entry(name: &str, is_structural: bool, is_ignored: bool) -> String {
+    if is_ignored {
+        String::from(name)
+    } else if is_structural {
+        format!("meta: {{ info: '{}', id: 'tested' }}", name)
+    } else {
+        format!("metric: {{ unit: '{}', id: 'tested' }}", name)
+    }
+}
+
+#[test]
+fn category_names() {
+    for category in EdgeCategory::all() {
+        let name = category.to_string();
+        assert_eq!(name.parse::<EdgeCategory>().ok(), Some(*category));
+        assert_eq!(
+            [
+                category.is_metric(),
+                category.is_ignored(),
+                category.is_structural()
+            ]
+            .iter()
+            .filter(|&&is_true| is_true)
+            .count(),
+            1,
+            "Edge-category {} should be in exactly one group.",
+            name
+        );
+
+        let parsing_cfg = configs::parsing::Config::try_from_str(&format!(
+            "parsing:\n  map-file: 'graph.fmi'\n  nodes: []\n  edges:\n    data:\n    - {}\n",
+            yaml_entry(&name, category.is_structural(), category.is_ignored())
+        ))
+        .expect(&format!(
+            "Edge-category {} should be accepted in yaml.",
+            name
+        ));
+        assert_eq!(
+            EdgeCategory::try_from(&parsing_cfg.edges.categories[0]).ok(),
+            Some(*category)
+        );
+    }
+
+    for category in NodeCategory::all() {
+        let name = category.to_string();
+        assert_eq!(name.parse::<NodeCategory>().ok(), Some(*category));
+        assert_eq!(
+            [
+                category.is_metric(),
+                category.is_ignored(),
+                category.is_structural()
+            ]
+            .iter()
+            .filter(|&&is_true| is_true)
+            .count(),
+            1,
+            "Node-category {} should be in exactly one group.",
+            name
+        );
+
+        let parsing_cfg = configs::parsing::Config::try_from_str(&format!(
+            "parsing:\n  map-file: 'graph.fmi'\n  nodes:\n  - {}\n  edges:\n    data: []\n",
+            yaml_entry(&name, category.is_structural(), category.is_ignored())
+        ))
+        .expect(&format!(
+            "Node-category {} should be accepted in yaml.",
+            name
+        ));
+        assert_eq!(
+            NodeCategory::try_from(&parsing_cfg.nodes.categories[0]).ok(),
+            Some(*category)
+        );
+    }
+
+    assert!("Height".parse::<NodeCategory>().is_err());
+    assert!("SrcIdx".parse::<EdgeCategory>().is_err());
+    assert!("srcid".parse::<EdgeCategory>().is_err());
+}
+
+/// The yaml should accept a name as meta-info or metric-unit, if and only if the name parses to a
+/// category of this group.
+#[test]
+fn category_names_from_yaml() {
+    let unknown_names = ["Height", "SrcIdx", "StableId", "srcid", "meters", "Ignored"];
+
+    let names = EdgeCategory::all()
+        .iter()
+        .map(|category| category.to_string())
+        .chain(unknown_names.iter().map(|&name| String::from(name)));
+    for name in names {
+        let category = name.parse::<EdgeCategory>().ok();
+        for &is_structural in &[true, false] {
+            let is_accepted = configs::parsing::Config::try_from_str(&format!(
+                "parsing:\n  map-file: 'graph.fmi'\n  nodes: []\n  edges:\n    data:\n    - {}\n",
+                yaml_entry(&name, is_structural, false)
+            ))
+            .is_ok();
+            let is_expected = match category {
+                Some(category) => {
+                    assert_eq!(category.to_string(), name);
+                    !category.is_ignored() && category.is_structural() == is_structural
+                }
+                None => false,
+            };
+            assert_eq!(
+                is_accepted,
+                is_expected,
+                "The yaml should accept the edge-category {} as {} only if it parses.",
+                name,
+                if is_structural {
+                    "meta-info"
+                } else {
+                    "metric-unit"
+                }
+            );
+        }
+    }
+
+    let names = NodeCategory::all()
+        .iter()
+        .map(|category| category.to_string())
+        .chain(unknown_names.iter().map(|&name| String::from(name)));
+    for name in names {
+        let category = name.parse::<NodeCategory>().ok();
+        for &is_structural in &[true, false] {
+            let is_accepted = configs::parsing::Config::try_from_str(&format!(
+                "parsing:\n  map-file: 'graph.fmi'\n  nodes:\n  - {}\n  edges:\n    data: []\n",
+                yaml_entry(&name, is_structural, false)
+            ))
+            .is_ok();
+            let is_expected = match category {
+                Some(category) => {
+                    assert_eq!(category.to_string(), name);
+                    !category.is_ignored() && category.is_structural() == is_structural
+                }
+                None => false,
+            };
+            assert_eq!(
+                is_accepted,
+                is_expected,
+                "The yaml should accept the node-category {} as {} only if it parses.",
+                name,
+                if is_structural {
+                    "meta-info"
+                } else {
+                    "metric-unit"
+                }
+            );
+        }
+    }
+}
+
+#[test]
+fn link_speeds_by_parent() {
+    let way = |tags: &[(&str, &str)]| {
+        let mut way_tags = Tags::new();
+        for (key, value) in tags {
+            way_tags.insert((*key).into(), (*value).into());
+        }
+        Way {
+            id: WayId(0),
+            tags: way_tags,
+            nodes: Vec::new(),
+        }
+    };
+
+    // (tags, link-speed-fraction, expected maxspeed)
+    for (tags, fraction, expected) in &[
+        (vec![("highway", "motorway_link")], Some(0.7), 91.0),
+        (vec![("highway", "motorway_link")], None, 50.0),
+        (vec![("highway", "primary_link")], Some(0.5), 50.0),
+        // given maxspeeds always win
+        (
+            vec![("highway", "motorway_link"), ("maxspeed", "60")],
+            Some(0.7),
+            60.0,
+        ),
+        (
+            vec![("highway", "motorway_link"), ("maxspeed", "60")],
+            None,
+            60.0,
+        ),
+        // only links have a parent
+        (vec![("highway", "motorway")], Some(0.7), 130.0),
+        (vec![("highway", "residential")], Some(0.7), 50.0),
+    ] {
+        let way = way(tags);
+        let street_category = StreetCategory::from(&way).expect("Highway-tag should be known.");
+        let maxspeed = street_category.parse_maxspeed(&way, *fraction);
+        assert!(
+            Approx(*maxspeed) == Approx(*expected),
+            "Way with tags {:?} and link-speed-fraction {:?} has maxspeed {}, but expected {}.",
+            tags,
+            fraction,
+            maxspeed,
+            expected
+        );
+    }
+}
+
+#[test]
+fn link_speeds_in_yaml() {
+    let yaml = |edges_entries: &str| {
+        format!(
+            "parsing:\n  map-file: 'graph.fmi'\n  nodes: []\n  edges:\n    data: []\n{}",
+            edges_entries
+        )
+    };
+
+    let parsing_cfg = configs::parsing::Config::from_str(&yaml(""));
+    assert_eq!(parsing_cfg.edges.link_speed_fraction(), None);
+
+    let parsing_cfg = configs::parsing::Config::from_str(&yaml("    infer-link-speeds: true\n"));
+    assert_eq!(parsing_cfg.edges.link_speed_fraction(), Some(0.7));
+
+    let parsing_cfg = configs::parsing::Config::from_str(&yaml(
+        "    infer-link-speeds: true\n    link-speed-fraction: 0.8\n",
+    ));
+    assert_eq!(parsing_cfg.edges.link_speed_fraction(), Some(0.8));
+
+    for fraction in &["0.0", "-0.5"] {
+        assert!(
+            configs::parsing::Config::try_from_str(&yaml(&format!(
+                "    infer-link-speeds: true\n    link-speed-fraction: {}\n",
+                fraction
+            )))
+            .is_err(),
+            "Link-speed-fraction {} should be rejected.",
+            fraction
+        );
+    }
 }
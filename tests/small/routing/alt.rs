@@ -0,0 +1,157 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    configs,
+    network::{Graph, NodeIdx},
+    routing::{
+        alt::{AltRouter, LandmarkPreprocessor},
+        dijkstra::{self, Dijkstra},
+    },
+};
+use std::fs;
+
+const ROUTING_CFG: &str = "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }";
+
+/// Returns the best path's cost from Dijkstra, or `None`, if no path exists.
+fn dijkstra_cost(
+    src_idx: NodeIdx,
+    dst_idx: NodeIdx,
+    dijkstra: &mut Dijkstra,
+    graph: &Graph,
+    routing_cfg: &configs::routing::Config,
+) -> Option<f64> {
+    dijkstra
+        .compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .map(|mut path| path.calc_costs(graph)[0])
+}
+
+#[test]
+fn same_costs_as_dijkstra() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_str(ROUTING_CFG, graph.cfg());
+    let nodes = graph.nodes();
+
+    let preprocessor = LandmarkPreprocessor::build(&graph, &routing_cfg, 3);
+    assert_eq!(preprocessor.landmarks().len(), 3);
+    let mut alt = AltRouter::new(preprocessor);
+    let mut dijkstra = Dijkstra::new();
+
+    for src_idx in &nodes {
+        for dst_idx in &nodes {
+            let expected = dijkstra_cost(src_idx, dst_idx, &mut dijkstra, &graph, &routing_cfg);
+            let actual = alt
+                .compute_best_path(src_idx, dst_idx, &graph, &routing_cfg)
+                .map(|mut path| path.calc_costs(&graph)[0]);
+
+            match (expected, actual) {
+                (Some(expected), Some(actual)) => {
+                    assert!(
+                        (expected - actual).abs() <= 1e-6,
+                        "ALT's cost {} from {} to {} should be Dijkstra's cost {}.",
+                        actual,
+                        nodes.id(src_idx),
+                        nodes.id(dst_idx),
+                        expected
+                    );
+                    // bounds never overestimate
+                    assert!(alt.preprocessor().lower_bound(src_idx, dst_idx) <= expected + 1e-6);
+                }
+                (None, None) => (),
+                _ => panic!(
+                    "ALT should find a path from {} to {}, iff Dijkstra finds one.",
+                    nodes.id(src_idx),
+                    nodes.id(dst_idx)
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn landmarks_roundtrip() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_str(ROUTING_CFG, graph.cfg());
+    let nodes = graph.nodes();
+    let preprocessor = LandmarkPreprocessor::build(&graph, &routing_cfg, 2);
+
+    let map_file = temp_file("landmarks.fmi");
+    let file = LandmarkPreprocessor::file_of(&map_file);
+    assert_eq!(
+        file.file_name().and_then(|name| name.to_str()),
+        map_file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| format!("{}.landmarks", name))
+            .as_deref()
+    );
+    let _ = fs::remove_file(&file);
+    if let Err(msg) = preprocessor.write(&graph, &file) {
+        panic!("Could not write {}. ERROR: {}", file.display(), msg);
+    }
+    let result = LandmarkPreprocessor::try_read(&graph, &file);
+    let _ = fs::remove_file(&file);
+    let recovered = match result {
+        Ok(recovered) => recovered,
+        Err(msg) => panic!("{}", msg),
+    };
+
+    assert_eq!(recovered.alphas(), preprocessor.alphas());
+    assert_eq!(recovered.landmarks(), preprocessor.landmarks());
+    for landmark in 0..preprocessor.landmarks().len() {
+        for node_idx in &nodes {
+            // infinite distances of unreachable nodes are recovered as well
+            assert_eq!(
+                recovered.fwd_dist(landmark, node_idx),
+                preprocessor.fwd_dist(landmark, node_idx)
+            );
+            assert_eq!(
+                recovered.bwd_dist(landmark, node_idx),
+                preprocessor.bwd_dist(landmark, node_idx)
+            );
+        }
+    }
+}
+
+#[test]
+fn other_alphas_are_rejected() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_str(ROUTING_CFG, graph.cfg());
+    let nodes = graph.nodes();
+    let mut alt = AltRouter::new(LandmarkPreprocessor::build(&graph, &routing_cfg, 2));
+
+    let other_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers', alpha: 2.0 }] }",
+        graph.cfg(),
+    );
+    let (src_idx, dst_idx) = (NodeIdx(0), NodeIdx(nodes.count() - 1));
+    assert!(alt
+        .try_compute_best_path(src_idx, dst_idx, &graph, &other_cfg)
+        .is_err());
+    assert!(alt
+        .try_compute_best_path(src_idx, dst_idx, &graph, &routing_cfg)
+        .is_ok());
+}
+
+#[test]
+fn other_graphs_are_rejected() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_str(ROUTING_CFG, graph.cfg());
+    let mut alt = AltRouter::new(LandmarkPreprocessor::build(&graph, &routing_cfg, 2));
+
+    // same metrics, but other nodes
+    let other_graph = parse(configs::parsing::Config::from_yaml(
+        defaults::paths::resources::stacked_nodes::MERGED_FMI_YAML,
+    ));
+    assert_ne!(other_graph.nodes().count(), graph.nodes().count());
+    let other_cfg = configs::routing::Config::from_str(ROUTING_CFG, other_graph.cfg());
+    assert!(alt
+        .try_compute_best_path(NodeIdx(0), NodeIdx(1), &other_graph, &other_cfg)
+        .is_err());
+}
@@ -0,0 +1,96 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    configs::{self, routing::RoutingAlgo},
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+const A_ID: i64 = 0;
+const H_ID: i64 = 7;
+
+fn ch_routing_cfg(graph: &Graph, is_allowing_ch_fallback: bool) -> configs::routing::Config {
+    configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'CHDijkstra', allow-ch-fallback: {}, \
+             metrics: [{{ id: 'kilometers' }}] }}",
+            is_allowing_ch_fallback
+        ),
+        graph.cfg(),
+    )
+}
+
+fn best_path_edges(graph: &Graph, routing_cfg: &configs::routing::Config) -> Vec<usize> {
+    let nodes = graph.nodes();
+    Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(A_ID).expect("Src-id should exist."),
+            dst_idx: nodes.idx_from(H_ID).expect("Dst-id should exist."),
+            graph,
+            routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .expect("Path from a to h should exist.")
+        .flatten(graph)
+        .iter()
+        .map(|&edge_idx| *edge_idx)
+        .collect()
+}
+
+#[test]
+fn levels_and_shortcuts() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    assert_eq!(graph.shortcut_count(), 0);
+    assert!(!graph.has_nonuniform_levels());
+
+    let ch_graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    assert!(ch_graph.shortcut_count() > 0);
+    assert!(ch_graph.has_nonuniform_levels());
+}
+
+#[test]
+fn ch_dijkstra_on_plain_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut routing_cfg = ch_routing_cfg(&graph, false);
+    assert!(!routing_cfg.is_allowing_ch_fallback);
+    assert!(
+        routing_cfg.validate(&graph).is_err(),
+        "CHDijkstra on a plain graph should be refused."
+    );
+    assert_eq!(routing_cfg.routing_algo, RoutingAlgo::CHDijkstra);
+}
+
+#[test]
+fn ch_fallback_on_plain_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut routing_cfg = ch_routing_cfg(&graph, true);
+    if let Err(msg) = routing_cfg.validate(&graph) {
+        panic!("CHDijkstra should fall back to Dijkstra, but: {}", msg);
+    }
+    assert_eq!(routing_cfg.routing_algo, RoutingAlgo::Dijkstra);
+
+    let expected_routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    assert_eq!(
+        best_path_edges(&graph, &routing_cfg),
+        best_path_edges(&graph, &expected_routing_cfg)
+    );
+}
+
+#[test]
+fn ch_dijkstra_on_ch_graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    for &is_allowing_ch_fallback in &[false, true] {
+        let mut routing_cfg = ch_routing_cfg(&graph, is_allowing_ch_fallback);
+        if let Err(msg) = routing_cfg.validate(&graph) {
+            panic!(
+                "CHDijkstra on a contracted graph should be valid, but: {}",
+                msg
+            );
+        }
+        assert_eq!(routing_cfg.routing_algo, RoutingAlgo::CHDijkstra);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, NodeIdx},
+    routing::paths::{self, Path},
+};
+
+fn path_from(node_ids: &[i64], graph: &Graph) -> Path {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node_indices: Vec<NodeIdx> = node_ids
+        .iter()
+        .map(|&id| nodes.idx_from(id).expect("Node-id should exist."))
+        .collect();
+    let edges = node_indices
+        .windows(2)
+        .map(|pair| {
+            fwd_edges
+                .between(pair[0], pair[1])
+                .expect("Edge should exist.")
+                .idx()
+        })
+        .collect();
+
+    let src_idx = node_indices[0];
+    let dst_idx = node_indices[node_indices.len() - 1];
+    Path::new(
+        src_idx,
+        nodes.id(src_idx),
+        dst_idx,
+        nodes.id(dst_idx),
+        edges,
+    )
+}
+
+#[test]
+fn equal_cost_alternatives() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // g -> e -> d and g -> f -> h -> d have 3 km each
+    let optimal = path_from(&[6, 4, 3], &graph);
+    let alternative = path_from(&[6, 5, 7, 3], &graph);
+
+    let comparison = paths::compare(&optimal, &alternative, &graph);
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    assert!(
+        Approx(comparison.cost_ratios[*km_idx]) == Approx(1.0),
+        "Equal-cost paths should have a distance-ratio of 1.0, but is {}.",
+        comparison.cost_ratios[*km_idx]
+    );
+    // no edge of g->f->h->d is part of g->e->d
+    assert!(Approx(comparison.shared_edge_fraction) == Approx(0.0));
+    // all nodes of the small graph have the same coordinate
+    assert!(Approx(comparison.max_divergence.0) == Approx(0.0));
+    assert!(Approx(comparison.mean_divergence.0) == Approx(0.0));
+}
+
+#[test]
+fn partially_shared_alternative() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // g -> e -> d -> b (4 km) and g -> e -> f -> h -> d -> b (5 km)
+    let optimal = path_from(&[6, 4, 3, 1], &graph);
+    let alternative = path_from(&[6, 4, 5, 7, 3, 1], &graph);
+
+    let comparison = paths::compare(&optimal, &alternative, &graph);
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    assert!(
+        Approx(comparison.cost_ratios[*km_idx]) == Approx(5.0 / 4.0),
+        "Distance-ratio should be 1.25, but is {}.",
+        comparison.cost_ratios[*km_idx]
+    );
+    // g->e and d->b are shared
+    assert!(
+        Approx(comparison.shared_edge_fraction) == Approx(2.0 / 5.0),
+        "Shared-edge-fraction should be 0.4, but is {}.",
+        comparison.shared_edge_fraction
+    );
+}
+
+#[test]
+fn degenerate_paths() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // identical paths
+    let path = path_from(&[6, 4, 3], &graph);
+    let comparison = paths::compare(&path, &path, &graph);
+    assert!(comparison
+        .cost_ratios
+        .iter()
+        .all(|&ratio| Approx(ratio) == Approx(1.0)));
+    assert!(Approx(comparison.shared_edge_fraction) == Approx(1.0));
+    assert!(Approx(comparison.max_divergence.0) == Approx(0.0));
+
+    // empty paths
+    let nodes = graph.nodes();
+    let idx = nodes.idx_from(6).expect("Node-id should exist.");
+    let empty_path = Path::new(idx, 6, idx, 6, vec![]);
+    let comparison = paths::compare(&empty_path, &empty_path, &graph);
+    assert!(comparison
+        .cost_ratios
+        .iter()
+        .all(|&ratio| Approx(ratio) == Approx(1.0)));
+    assert!(Approx(comparison.shared_edge_fraction) == Approx(1.0));
+    assert!(Approx(comparison.max_divergence.0) == Approx(0.0));
+    assert!(Approx(comparison.mean_divergence.0) == Approx(0.0));
+}
@@ -0,0 +1,105 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn comfortable_cyclists_prefer_cycle_tracks() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CYCLEWAY_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    assert_comfortable_cyclists_prefer_cycle_tracks(&graph, resources::CYCLEWAY_FMI_YAML, 0, 2, 3);
+}
+
+#[test]
+fn comfortable_cyclists_prefer_cycle_tracks_of_pbf() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CYCLEWAY_OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node = |id| nodes.idx_from(id).expect("Node should exist.");
+    let comfort_idx = graph.cfg().edges.metrics.idx_of("cycleway-comfort");
+    let kmph_idx = graph.cfg().edges.metrics.idx_of("kmph");
+
+    // comfort and speed are parsed from the tags
+    for &(src_id, dst_id, expected_comfort, expected_kmph) in &[
+        (1, 2, 1.0, 30.0),
+        (2, 4, 1.0, 30.0),
+        (1, 3, 0.0, 31.5),
+        (3, 4, 0.0, 31.5),
+    ] {
+        let metrics = fwd_edges
+            .between(node(src_id), node(dst_id))
+            .expect("Edge should exist.")
+            .metrics();
+        // zeros are stored as accuracy
+        assert!(
+            Approx(metrics[*comfort_idx]) == Approx(expected_comfort),
+            "Edge (id:{}->id:{}) has comfort {}, but expected {}.",
+            src_id,
+            dst_id,
+            metrics[*comfort_idx],
+            expected_comfort
+        );
+        assert!(
+            Approx(metrics[*kmph_idx]) == Approx(expected_kmph),
+            "Edge (id:{}->id:{}) has {} kmph, but expected {} kmph.",
+            src_id,
+            dst_id,
+            metrics[*kmph_idx],
+            expected_kmph
+        );
+    }
+
+    assert_comfortable_cyclists_prefer_cycle_tracks(
+        &graph,
+        resources::CYCLEWAY_OSM_PBF_YAML,
+        1,
+        3,
+        4,
+    );
+}
+
+/// The src `a` is connected to the dst `d` by a short street without cycleway and a slightly
+/// longer street with a cycle-track via `c`.
+fn assert_comfortable_cyclists_prefer_cycle_tracks(
+    graph: &Graph,
+    routing_yaml: &str,
+    a_id: i64,
+    c_id: i64,
+    d_id: i64,
+) {
+    let profiles = configs::routing::Profiles::from_yaml(routing_yaml, graph.cfg());
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node = |id| nodes.idx_from(id).expect("Node should exist.");
+    let track_edge_idx = fwd_edges
+        .between(node(a_id), node(c_id))
+        .expect("Edge of cycle-track should exist.")
+        .idx();
+
+    let mut dijkstra = Dijkstra::new();
+    for (profile, is_using_track) in &[("fast", false), ("comfortable", true)] {
+        let path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx: node(a_id),
+                dst_idx: node(d_id),
+                graph,
+                routing_cfg: profiles.get(profile),
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .expect("Path from a to d should exist.")
+            .flatten(graph);
+        assert_eq!(
+            path.iter().any(|&edge_idx| edge_idx == track_edge_idx),
+            *is_using_track,
+            "Path of profile {} should{} use the cycle-track.",
+            profile,
+            if *is_using_track { "" } else { "n't" }
+        );
+    }
+}
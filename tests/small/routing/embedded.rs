@@ -0,0 +1,121 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{configs, io};
+
+const ROUTING_CFG: &str = "routing: { algorithm: 'CHDijkstra', check-non-negative: true, \
+                           max-snap-distance: 0.25, metrics: [\
+                           { id: 'kilometers', alpha: 0.3, tolerated-scale: '1.1' }, \
+                           { id: 'hours', alpha: 0.7 }] }";
+
+#[test]
+fn route_pairs_with_embedded_cfg() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(ROUTING_CFG, graph.cfg());
+
+    // write route-pairs
+
+    let file = temp_file("embedded.route-pairs");
+    let writing_cfg = configs::writing::routing::Config {
+        file: file.to_path_buf(),
+        category: configs::writing::routing::Category::RandomOrAll {
+            seed: 42,
+            max_count: 10,
+        },
+    };
+    if let Err(msg) = io::routing::Writer::write(&graph, &routing_cfg, &writing_cfg) {
+        panic!("Could not write {}. ERROR: {}", file.display(), msg);
+    }
+
+    // recover config
+
+    let recovered_cfg = match configs::routing::Config::try_from_embedded(&file, graph.cfg()) {
+        Ok(cfg) => cfg,
+        Err(msg) => panic!("{}", msg),
+    };
+
+    assert_eq!(recovered_cfg.routing_algo, routing_cfg.routing_algo);
+    assert_eq!(recovered_cfg.alphas, routing_cfg.alphas);
+    assert_eq!(recovered_cfg.tolerated_scales, routing_cfg.tolerated_scales);
+    assert_eq!(
+        recovered_cfg.is_checking_non_negative,
+        routing_cfg.is_checking_non_negative
+    );
+    assert_eq!(
+        recovered_cfg.max_snap_distance.0,
+        routing_cfg.max_snap_distance.0
+    );
+}
+
+#[test]
+fn embedded_cfg_with_reordered_metrics() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(ROUTING_CFG, graph.cfg());
+    let embedded_cfg = routing_cfg.to_embedded(graph.cfg());
+
+    // metrics are referenced by ids, hence the config fits a re-parsed graph
+    let mut reordered_cfg = graph.cfg().clone();
+    reordered_cfg.edges.metrics.ids.reverse();
+    reordered_cfg.edges.metrics.units.reverse();
+    let recovered_cfg = configs::routing::Config::from_str(&embedded_cfg, &reordered_cfg);
+
+    for metric_id in &["meters", "kmph", "kilometers", "hours"] {
+        let metric_idx = graph.cfg().edges.metrics.idx_of(metric_id);
+        let reordered_idx = reordered_cfg.edges.metrics.idx_of(metric_id);
+        assert_eq!(
+            recovered_cfg.alphas[*reordered_idx], routing_cfg.alphas[*metric_idx],
+            "Alpha of {} should be recovered.",
+            metric_id
+        );
+        assert_eq!(
+            recovered_cfg.tolerated_scales[*reordered_idx],
+            routing_cfg.tolerated_scales[*metric_idx],
+            "Tolerated scale of {} should be recovered.",
+            metric_id
+        );
+    }
+}
+
+#[test]
+fn golden_file_with_embedded_cfg() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+
+    let recovered_cfg =
+        configs::routing::Config::from_embedded(resources::SHORTEST_GOLDEN, graph.cfg());
+    assert_eq!(recovered_cfg.routing_algo, routing_cfg.routing_algo);
+    assert_eq!(recovered_cfg.alphas, routing_cfg.alphas);
+    assert_eq!(recovered_cfg.tolerated_scales, routing_cfg.tolerated_scales);
+
+    // files without embedded config are rejected
+    assert!(
+        configs::routing::Config::try_from_embedded(resources::GRAPH_FMI, graph.cfg()).is_err()
+    );
+}
+
+#[test]
+fn embedded_cfg_with_escaped_ids() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(ROUTING_CFG, graph.cfg());
+
+    // ids are escaped, hence quotes, backslashes or combining characters survive
+    let mut escaped_cfg = graph.cfg().clone();
+    let metric_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+    escaped_cfg.edges.metrics.ids[*metric_idx] =
+        configs::SimpleId::from("kilo\"meters\\ \"e\u{301}\"");
+    let embedded_cfg = routing_cfg.to_embedded(&escaped_cfg);
+    assert!(
+        !embedded_cfg.contains('\n'),
+        "Embedded config should be one line."
+    );
+
+    let recovered_cfg = configs::routing::Config::from_str(&embedded_cfg, &escaped_cfg);
+    assert_eq!(recovered_cfg.alphas, routing_cfg.alphas);
+    assert_eq!(recovered_cfg.tolerated_scales, routing_cfg.tolerated_scales);
+}
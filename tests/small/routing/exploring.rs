@@ -0,0 +1,217 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::NodeIdx,
+    routing::{
+        dijkstra::{self, Dijkstra},
+        explorating::{ConvexHullExplorator, ExplorationSummary},
+    },
+};
+use std::collections::HashSet;
+
+#[test]
+fn distinct_paths_on_ch_fmi_map() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "{}{}",
+            "routing: { algorithm: { Explorator: { algo: 'CHDijkstra' } }, ",
+            format!(
+                "metrics: [{{ id: '{}' }}, {{ id: '{}' }}] }}",
+                defaults::DISTANCE_ID,
+                defaults::DURATION_ID
+            )
+        ),
+        graph.cfg(),
+    );
+
+    let mut dijkstra = Dijkstra::new();
+    let mut explorator = ConvexHullExplorator::new();
+    let nodes = graph.nodes();
+    for src_idx in &nodes {
+        for dst_idx in &nodes {
+            let query = dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            };
+            // Without tolerances, every path of the triangulation is returned.
+            let (found_paths, summary) =
+                explorator.fully_explorate_with_summary(query, &mut dijkstra);
+
+            for (i, path) in found_paths.iter().enumerate() {
+                for other_path in &found_paths[(i + 1)..] {
+                    assert_ne!(
+                        path.flattened_hash(),
+                        other_path.flattened_hash(),
+                        "Explorator should find route {} only once.",
+                        path
+                    );
+                    assert!(
+                        Approx(path.costs()) != Approx(other_path.costs()),
+                        "Explorator should find costs of path {} only once.",
+                        path
+                    );
+                }
+            }
+
+            // Every edge's seconds are 0.12 times its meters, hence all paths' costs are
+            // proportional and the only path of the triangulation is the shortest one.
+            let expected_count = if Dijkstra::new().compute_best_path(query).is_some() {
+                1
+            } else {
+                0
+            };
+            let distinct_count = found_paths
+                .iter()
+                .map(|path| path.flattened_hash())
+                .collect::<HashSet<_>>()
+                .len();
+            assert_eq!(
+                summary.found_paths, distinct_count,
+                "The triangulation's vertices should be the distinct paths: {}",
+                summary
+            );
+            assert_eq!(
+                summary.found_paths, expected_count,
+                "Explorator should find {} path(s) from {} to {}: {}",
+                expected_count, src_idx, dst_idx, summary
+            );
+            assert_eq!(
+                explorator.duplicate_count(),
+                0,
+                "Explorator shouldn't skip any path from {} to {}: {}",
+                src_idx,
+                dst_idx,
+                summary
+            );
+        }
+    }
+}
+
+#[test]
+fn consistent_summaries_on_fmi_map() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "{}{}",
+            "routing: { algorithm: { Explorator: { algo: 'Dijkstra' } }, ",
+            format!(
+                "metrics: [{{ id: '{}' }}, {{ id: '{}' }}] }}",
+                defaults::DISTANCE_ID,
+                defaults::DURATION_ID
+            )
+        ),
+        graph.cfg(),
+    );
+
+    let mut dijkstra = Dijkstra::new();
+    let mut explorator = ConvexHullExplorator::new();
+    let nodes = graph.nodes();
+    for src_idx in &nodes {
+        for dst_idx in &nodes {
+            let (found_paths, summary) = explorator.fully_explorate_with_summary(
+                dijkstra::Query {
+                    src_idx,
+                    dst_idx,
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                },
+                &mut dijkstra,
+            );
+
+            // all combinations of two metrics
+            assert_eq!(summary.initial_alphas, 3, "{}", summary);
+            assert_eq!(
+                summary.visited_cells + summary.skipped_cells(),
+                summary.seen_cells,
+                "Every seen cell should be visited or skipped: {}",
+                summary
+            );
+            assert_eq!(
+                summary.dijkstra_runs,
+                summary.initial_alphas + summary.visited_cells,
+                "{}",
+                summary
+            );
+            assert!(
+                summary.dijkstra_runs >= summary.found_paths,
+                "Every found path needs a Dijkstra-run: {}",
+                summary
+            );
+            assert_eq!(
+                summary.found_paths,
+                found_paths.len() + summary.rejected_paths,
+                "{}",
+                summary
+            );
+        }
+    }
+}
+
+#[test]
+fn non_negative_alphas_on_alphas_map() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::ALPHAS_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: { Explorator: { algo: 'Dijkstra' } }, \
+         metrics: [{ id: 'a' }, { id: 'b' }, { id: 'c' }] }",
+        graph.cfg(),
+    );
+    assert!(!routing_cfg.is_allowing_negative_alphas);
+
+    let mut dijkstra = Dijkstra::new();
+    let mut explorator = ConvexHullExplorator::new();
+    let (found_paths, summary) = explorator.fully_explorate_with_summary(
+        dijkstra::Query {
+            src_idx: NodeIdx(0),
+            dst_idx: NodeIdx(5),
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        },
+        &mut dijkstra,
+    );
+
+    // (1, 10, 10), (10, 1, 10), (10, 10, 1) and (4, 4, 4)
+    assert_eq!(found_paths.len(), 4, "{}", summary);
+
+    // All 7 initial alphas find these 4 paths, which span a tetrahedron of 4 facets.
+    // The facet without (4, 4, 4) has alphas (1/3, 1/3, 1/3), finding (4, 4, 4) again.
+    // The 3 facets with (4, 4, 4) have normals with one negative component, e.g. (2/3, 2/3, -1/3)
+    // for (1, 10, 10), (10, 1, 10) and (4, 4, 4), which are re-solved with a zero instead, but
+    // don't find any better path.
+    assert_eq!(
+        summary,
+        ExplorationSummary {
+            initial_alphas: 7,
+            seen_cells: 4,
+            visited_cells: 4,
+            already_visited_cells: 0,
+            tolerance_pruned_cells: 0,
+            singular_cells: 0,
+            negative_alpha_cells: 0,
+            resolved_alpha_cells: 3,
+            dijkstra_runs: 11,
+            found_paths: 4,
+            rejected_paths: 0,
+        },
+        "No Dijkstra should run with negative alphas: {}",
+        summary
+    );
+    assert_eq!(
+        explorator.duplicate_count(),
+        1,
+        "Only the facet without (4, 4, 4) should find a known path."
+    );
+}
@@ -18,6 +18,11 @@ fn compare_dijkstras_on_ch_fmi_map() {
     compare_dijkstras(resources::CH_FMI_YAML, METRIC_ID);
 }
 
+#[test]
+fn compare_dijkstras_on_unsorted_ch_fmi_map() {
+    compare_dijkstras(resources::UNSORTED_CH_FMI_YAML, METRIC_ID);
+}
+
 #[test]
 fn chdijkstra_on_chmap() {
     test_dijkstra(
@@ -38,16 +43,6 @@ fn dijkstra_on_chmap() {
     )
 }
 
-#[test]
-fn chdijkstra_on_map() {
-    test_dijkstra(
-        resources::FMI_YAML,
-        METRIC_ID,
-        RoutingAlgo::CHDijkstra,
-        Box::new(expected_paths),
-    )
-}
-
 #[test]
 fn dijkstra_on_map() {
     test_dijkstra(
@@ -58,7 +53,7 @@ fn dijkstra_on_map() {
     )
 }
 
-fn expected_paths(
+pub fn expected_paths(
     parsing_cfg: &configs::parsing::Config,
 ) -> Vec<(
     TestNode,
@@ -91,7 +86,7 @@ fn expected_paths(
         idx: NodeIdx(idx),
         id: idx as i64,
         coord: Coordinate::zero(),
-        ch_level: 0,
+        ch_level: None,
     })
     .collect();
 
@@ -0,0 +1,50 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{configs, routing::golden};
+
+const QUERIES: [(i64, i64); 8] = [
+    (1, 0),
+    (3, 5),
+    (4, 0),
+    (5, 2),
+    (7, 4),
+    (6, 7),
+    (3, 3),
+    // unreachable
+    (0, 1),
+];
+
+#[test]
+fn shortest() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+
+    let actual = golden::dump(&QUERIES, &graph, &routing_cfg);
+    golden::assert_matches(resources::SHORTEST_GOLDEN, &actual);
+}
+
+#[test]
+fn diff_of_changed_route() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+
+    let actual = golden::dump(&QUERIES, &graph, &routing_cfg);
+    let changed = actual.replacen("  nodes: 3 7 5", "  nodes: 3 4 5", 1);
+    let diff = golden::try_diff(resources::SHORTEST_GOLDEN, &changed)
+        .expect("Golden-file should be readable.")
+        .expect("Changed dump should differ from golden-file.");
+
+    let changed_lines: Vec<&str> = diff
+        .lines()
+        .filter(|line| !line.starts_with("  "))
+        .collect();
+    assert_eq!(changed_lines, vec!["-   nodes: 3 7 5", "+   nodes: 3 4 5"]);
+}
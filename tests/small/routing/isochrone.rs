@@ -0,0 +1,135 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+
+const H_ID: i64 = 7;
+
+fn routing_cfg(graph: &Graph, algorithm: &str) -> configs::routing::Config {
+    configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: '{}', metrics: [{{ id: 'kilometers' }}] }}",
+            algorithm
+        ),
+        graph.cfg(),
+    )
+}
+
+/// Replaces node-indices by node-ids for readable assertions.
+fn ids(graph: &Graph, isochrone: &[(NodeIdx, f64)]) -> Vec<(i64, Approx<f64>)> {
+    let nodes = graph.nodes();
+    isochrone
+        .iter()
+        .map(|&(idx, cost)| (nodes.id(idx), Approx(cost)))
+        .collect()
+}
+
+#[test]
+fn within_max_cost() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = routing_cfg(&graph, "Dijkstra");
+    let src_idx = graph.nodes().idx_from(H_ID).expect("Src-id should exist.");
+
+    let mut dijkstra = Dijkstra::new();
+    let isochrone = dijkstra.compute_isochrone(src_idx, &graph, &routing_cfg, 0.002);
+    assert_eq!(
+        ids(&graph, &isochrone),
+        vec![
+            (7, Approx(0.0)),
+            (3, Approx(0.001)),
+            (5, Approx(0.001)),
+            (1, Approx(0.002)),
+            (4, Approx(0.002)),
+        ]
+    );
+
+    // g is not reachable at all
+    let isochrone = dijkstra.compute_isochrone(src_idx, &graph, &routing_cfg, std::f64::INFINITY);
+    assert_eq!(
+        ids(&graph, &isochrone),
+        vec![
+            (7, Approx(0.0)),
+            (3, Approx(0.001)),
+            (5, Approx(0.001)),
+            (1, Approx(0.002)),
+            (4, Approx(0.002)),
+            (0, Approx(0.003)),
+            (2, Approx(0.003)),
+        ]
+    );
+
+    assert_eq!(
+        ids(
+            &graph,
+            &dijkstra.compute_isochrone(src_idx, &graph, &routing_cfg, 0.0)
+        ),
+        vec![(7, Approx(0.0))]
+    );
+    assert!(dijkstra
+        .compute_isochrone(src_idx, &graph, &routing_cfg, -1.0)
+        .is_empty());
+}
+
+#[test]
+fn matches_best_paths() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = routing_cfg(&graph, "Dijkstra");
+    let nodes = graph.nodes();
+
+    let mut dijkstra = Dijkstra::new();
+    for src_idx in &nodes {
+        let isochrone =
+            dijkstra.compute_isochrone(src_idx, &graph, &routing_cfg, std::f64::INFINITY);
+        // reusing the search for the same query is deterministic
+        assert_eq!(
+            ids(&graph, &isochrone),
+            ids(
+                &graph,
+                &dijkstra.compute_isochrone(src_idx, &graph, &routing_cfg, std::f64::INFINITY)
+            )
+        );
+
+        for dst_idx in &nodes {
+            let best_cost = dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx,
+                    dst_idx,
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                    edge_bias: None,
+                    path_hint: dijkstra::PathHint::Auto,
+                })
+                .map(|path| path.flatten(&graph).costs()[0]);
+            let cost = isochrone
+                .iter()
+                .find(|&&(idx, _)| idx == dst_idx)
+                .map(|&(_, cost)| cost);
+            assert_eq!(
+                cost.map(Approx),
+                best_cost.map(Approx),
+                "Isochrone of idx {} has wrong costs for idx {}.",
+                src_idx,
+                dst_idx
+            );
+        }
+    }
+}
+
+#[test]
+fn ch_dijkstra() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CH_FMI_YAML));
+    let routing_cfg = routing_cfg(&graph, "CHDijkstra");
+    let src_idx = graph.nodes().idx_from(H_ID).expect("Src-id should exist.");
+
+    let mut dijkstra = Dijkstra::new();
+    assert!(
+        dijkstra
+            .try_compute_isochrone(src_idx, &graph, &routing_cfg, 0.002)
+            .is_err(),
+        "Isochrones shouldn't be computed with CHDijkstra."
+    );
+}
@@ -1,2 +1,22 @@
+mod alt;
+mod ch_fallback;
+mod comparing;
+mod cycleway;
+mod embedded;
+#[cfg(feature = "gpl")]
+mod exploring;
 mod fastest;
+mod golden;
+mod isochrone;
+mod non_negative;
+mod profiles;
+mod restricted;
+mod reversed;
+mod route_pairs;
 mod shortest;
+mod slow_queries;
+mod sp_tree;
+mod stabilizing;
+mod streaming;
+mod time_window;
+mod yen;
@@ -0,0 +1,116 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::cell::RefCell;
+
+const A_ID: i64 = 0;
+const C_ID: i64 = 2;
+
+fn query<'a>(graph: &'a Graph, routing_cfg: &'a configs::routing::Config) -> dijkstra::Query<'a> {
+    let nodes = graph.nodes();
+    dijkstra::Query {
+        src_idx: nodes.idx_from(A_ID).expect("Src-id should exist."),
+        dst_idx: nodes.idx_from(C_ID).expect("Dst-id should exist."),
+        graph,
+        routing_cfg,
+        edge_bias: None,
+        path_hint: dijkstra::PathHint::Auto,
+    }
+}
+
+#[test]
+fn unused_negative_metric() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::NEGATIVE_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let profiles = configs::routing::Profiles::from_yaml(resources::NEGATIVE_FMI_YAML, graph.cfg());
+    let routing_cfg = profiles.get("short");
+    assert!(routing_cfg.is_checking_non_negative);
+
+    let mut dijkstra = Dijkstra::new();
+    let best_path = match dijkstra.try_compute_best_path(query(&graph, routing_cfg)) {
+        Ok(best_path) => best_path.expect("Path from a to c should exist."),
+        Err(msg) => panic!("Kilometers are non-negative, but routing failed: {}", msg),
+    };
+    let best_path = best_path.flatten(&graph);
+    assert!(Approx(best_path.costs()[0]) == Approx(2.0));
+}
+
+#[test]
+fn used_negative_metric() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::NEGATIVE_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let profiles = configs::routing::Profiles::from_yaml(resources::NEGATIVE_FMI_YAML, graph.cfg());
+    let routing_cfg = profiles.get("attractive");
+
+    let mut dijkstra = Dijkstra::new();
+    let msg = match dijkstra.try_compute_best_path(query(&graph, routing_cfg)) {
+        Ok(_) => panic!("Routing with negative attractiveness should fail."),
+        Err(msg) => format!("{}", msg),
+    };
+    assert!(
+        msg.contains("attractiveness"),
+        "Error should name the negative metric, but is: {}",
+        msg
+    );
+}
+
+/// Collects the warnings of the current thread, since tests run in parallel.
+struct WarningCollector;
+
+thread_local! {
+    static WARNINGS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+impl log::Log for WarningCollector {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            WARNINGS.with(|warnings| warnings.borrow_mut().push(format!("{}", record.args())));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static WARNING_COLLECTOR: WarningCollector = WarningCollector;
+
+#[test]
+fn negative_alphas_are_warned() {
+    // another test could have set the logger already
+    let _ = log::set_logger(&WARNING_COLLECTOR);
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::NEGATIVE_FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    WARNINGS.with(|warnings| warnings.borrow_mut().clear());
+    let _ = configs::routing::Profiles::from_str(
+        "routing: { algorithm: 'Dijkstra', default-profile: 'positive', profiles: {\
+         positive: { metrics: [{ id: 'kilometers', alpha: 1.0 }] }, \
+         negative: { metrics: [{ id: 'kilometers', alpha: -1.0 }] } } }",
+        graph.cfg(),
+    );
+    let warnings = WARNINGS.with(|warnings| warnings.borrow().clone());
+    assert!(
+        warnings
+            .iter()
+            .any(|warning| warning.contains("Routing-profile negative has negative alphas")),
+        "Profile negative should be warned, but warnings are {:?}.",
+        warnings
+    );
+    assert!(
+        !warnings
+            .iter()
+            .any(|warning| warning.contains("Routing-profile positive")),
+        "Profile positive shouldn't be warned, but warnings are {:?}.",
+        warnings
+    );
+}
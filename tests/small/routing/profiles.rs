@@ -0,0 +1,65 @@
+use super::{fastest, shortest};
+use crate::helpers::{assert_expected_paths, defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{configs, routing::dijkstra::Dijkstra};
+
+#[test]
+fn profiles_yaml() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::PROFILES_YAML);
+    let profiles = configs::routing::Profiles::from_yaml(resources::PROFILES_YAML, &parsing_cfg);
+
+    assert_eq!(profiles.names().collect::<Vec<_>>(), vec!["fast", "short"]);
+    assert_eq!(profiles.default_name(), "fast");
+    assert!(profiles.try_get("balanced").is_err());
+
+    // a single config is the default-profile
+    let routing_cfg = configs::routing::Config::from_yaml(resources::PROFILES_YAML, &parsing_cfg);
+    assert_eq!(routing_cfg.alphas, profiles.get("fast").alphas);
+}
+
+#[test]
+fn short_and_fast() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::PROFILES_YAML);
+    let graph = parse(parsing_cfg);
+    let profiles = configs::routing::Profiles::from_yaml(resources::PROFILES_YAML, graph.cfg());
+    let mut dijkstra = Dijkstra::new();
+
+    assert_expected_paths(
+        &mut dijkstra,
+        &graph,
+        profiles.get("short"),
+        shortest::expected_paths(graph.cfg()),
+    );
+    assert_expected_paths(
+        &mut dijkstra,
+        &graph,
+        profiles.get("fast"),
+        fastest::expected_paths(graph.cfg()),
+    );
+}
+
+#[test]
+fn invalid_profile() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::PROFILES_YAML);
+    let yaml_str = "
+routing:
+  algorithm: 'Dijkstra'
+  profiles:
+    short:
+      metrics:
+      - id: 'kilometers'
+    broken:
+      metrics:
+      - id: 'lightyears'
+  default-profile: 'short'
+";
+    let msg = match configs::routing::Profiles::try_from_str(yaml_str, &parsing_cfg) {
+        Ok(_) => panic!("Routing-profile with unknown metric-id should be invalid."),
+        Err(msg) => format!("{}", msg),
+    };
+    assert!(
+        msg.contains("broken"),
+        "Error-msg should name the invalid profile, but is: {}",
+        msg
+    );
+}
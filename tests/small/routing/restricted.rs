@@ -0,0 +1,52 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    configs,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn cautious_routing_avoids_restricted_edges() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::RESTRICTED_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let profiles =
+        configs::routing::Profiles::from_yaml(resources::RESTRICTED_FMI_YAML, graph.cfg());
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node = |id| nodes.idx_from(id).expect("Node should exist.");
+    let restricted_edge_idx = fwd_edges
+        .between(node(1), node(3))
+        .expect("Restricted edge should exist.")
+        .idx();
+    let restricted_idx = graph
+        .cfg()
+        .edges
+        .metrics
+        .idx_of("has-conditional-restriction");
+    assert_eq!(
+        graph.metrics().column(restricted_idx)[*restricted_edge_idx],
+        1.0
+    );
+
+    let mut dijkstra = Dijkstra::new();
+    for (profile, is_passing_restriction) in &[("short", true), ("cautious", false)] {
+        let path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx: node(0),
+                dst_idx: node(3),
+                graph: &graph,
+                routing_cfg: profiles.get(profile),
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .expect("Path from a to d should exist.")
+            .flatten(&graph);
+        assert_eq!(
+            path.iter().any(|&edge_idx| edge_idx == restricted_edge_idx),
+            *is_passing_restriction,
+            "Path of profile {} should{} pass the restricted edge.",
+            profile,
+            if *is_passing_restriction { "" } else { "n't" }
+        );
+    }
+}
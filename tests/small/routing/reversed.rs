@@ -0,0 +1,83 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    configs,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn oneway_is_named() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'Dijkstra', metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        graph.cfg(),
+    );
+
+    // path e -> d -> b, where d -> b is a oneway
+    let nodes = graph.nodes();
+    let path = Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(4).expect("Src-id should exist."),
+            dst_idx: nodes.idx_from(1).expect("Dst-id should exist."),
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .expect("Path from e to b should exist.");
+
+    match path.reversed(&graph) {
+        Ok(reversed_path) => panic!(
+            "Path {} shouldn't be reversible, but is reversed to {}.",
+            path, reversed_path
+        ),
+        Err(msg) => {
+            let msg = format!("{}", msg);
+            assert!(
+                msg.contains("from node-id 3 to node-id 1"),
+                "Error-msg should name the oneway d -> b, but is: {}",
+                msg
+            );
+        }
+    }
+}
+
+#[test]
+fn bidirectional_path() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'Dijkstra', metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        graph.cfg(),
+    );
+
+    // path h -> f -> e, where e -> f and f -> h exist
+    let nodes = graph.nodes();
+    let path = Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(7).expect("Src-id should exist."),
+            dst_idx: nodes.idx_from(4).expect("Dst-id should exist."),
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .expect("Path from h to e should exist.");
+
+    let reversed_path = path
+        .reversed(&graph)
+        .expect("Path from h to e should be reversible.");
+    let node_ids: Vec<i64> = reversed_path
+        .node_indices(&graph)
+        .into_iter()
+        .map(|idx| nodes.id(idx))
+        .collect();
+    assert_eq!(node_ids, vec![4, 5, 7]);
+}
@@ -0,0 +1,95 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{configs, io, network::Graph};
+use std::{fs, path::Path};
+
+fn routing_cfg(route_pairs_file: &Path, graph: &Graph) -> configs::routing::Config {
+    configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ route-pairs-file: '{}', algorithm: 'Dijkstra', \
+             metrics: [{{ id: 'kilometers' }}] }}",
+            route_pairs_file.display()
+        ),
+        graph.cfg(),
+    )
+}
+
+#[test]
+fn no_route_pairs_file() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    assert!(routing_cfg.route_pairs_file.is_none());
+
+    match io::routing::Parser::parse(&routing_cfg) {
+        Err(io::routing::Error::NoRoutePairsConfigured) => (),
+        Err(e) => panic!("Expected NoRoutePairsConfigured, but got: {}", e),
+        Ok(_) => panic!("Parsing without route-pairs-file should fail."),
+    }
+    assert!(io::routing::Parser::stream(&routing_cfg).is_err());
+    assert!(io::routing::Parser::parse_and_snap(&routing_cfg, &graph).is_err());
+}
+
+#[test]
+fn empty_route_pairs_files() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    for (name, content) in &[("empty", ""), ("whitespace", "  \n\t\n \n")] {
+        let route_pairs_file = temp_file(&format!("{}.route-pairs", name));
+        fs::write(&route_pairs_file, content).expect("Route-pairs-file should be writable.");
+        let routing_cfg = routing_cfg(&route_pairs_file, &graph);
+
+        let parsed = io::routing::Parser::parse(&routing_cfg);
+        let count = io::routing::Parser::count(&route_pairs_file);
+        match parsed {
+            Ok(route_pairs) => assert!(
+                route_pairs.is_empty(),
+                "The {} route-pairs-file should have no route-pairs.",
+                name
+            ),
+            Err(e) => panic!(
+                "The {} route-pairs-file should be accepted, but: {}",
+                name, e
+            ),
+        }
+        assert_eq!(
+            count.expect("Counting should work."),
+            0,
+            "The {} route-pairs-file should be counted as empty.",
+            name
+        );
+    }
+}
+
+#[test]
+fn counted_route_pairs() {
+    assert_eq!(
+        io::routing::Parser::count(resources::FRACTIONAL_ROUTE_PAIRS)
+            .expect("Counting should work."),
+        4
+    );
+    assert!(io::routing::Parser::count("resources/small/missing.route-pairs").is_err());
+}
+
+#[test]
+fn unreadable_route_lines() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    // the header is valid, but the second route-line isn't utf8
+    let route_pairs_file = temp_file("latin1.route-pairs");
+    fs::write(&route_pairs_file, b"2\n3 7 1\n6 \xE4 1\n".as_ref())
+        .expect("Route-pairs-file should be writable.");
+    let routing_cfg = routing_cfg(&route_pairs_file, &graph);
+
+    let parsed = io::routing::Parser::parse(&routing_cfg);
+    match parsed {
+        Ok(_) => panic!("Route-lines, which aren't utf8, should be rejected."),
+        Err(e) => assert!(
+            format!("{}", e).contains("Couldn't read"),
+            "Reading should fail, but the error is: {}",
+            e
+        ),
+    }
+}
@@ -1,10 +1,11 @@
-use crate::helpers::{compare_dijkstras, defaults, test_dijkstra, TestNode};
+use crate::helpers::{compare_dijkstras, defaults, parse, test_dijkstra, TestNode};
 use defaults::paths::resources::small as resources;
 use kissunits::{distance::Kilometers, geo::Coordinate};
 use osmgraphing::{
     configs::{self, routing::RoutingAlgo, SimpleId},
     defaults::capacity::DimVec,
     network::{MetricIdx, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
 };
 use smallvec::smallvec;
 
@@ -15,6 +16,11 @@ fn compare_dijkstras_on_ch_fmi_map() {
     compare_dijkstras(resources::CH_FMI_YAML, METRIC_ID);
 }
 
+#[test]
+fn compare_dijkstras_on_unsorted_ch_fmi_map() {
+    compare_dijkstras(resources::UNSORTED_CH_FMI_YAML, METRIC_ID);
+}
+
 #[test]
 fn chdijkstra_on_chmap() {
     test_dijkstra(
@@ -36,13 +42,31 @@ fn dijkstra_on_chmap() {
 }
 
 #[test]
-fn chdijkstra_on_map() {
-    test_dijkstra(
-        resources::FMI_YAML,
-        METRIC_ID,
-        RoutingAlgo::CHDijkstra,
-        Box::new(expected_paths),
-    )
+fn chdijkstra_on_map_without_levels() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    assert!(!graph.nodes().has_levels());
+
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'CHDijkstra', metrics: [{{ id: '{}' }}] }}",
+            METRIC_ID
+        ),
+        graph.cfg(),
+    );
+    let nodes = graph.nodes();
+    let result = Dijkstra::new().try_compute_best_path(dijkstra::Query {
+        src_idx: nodes.idx_from(3).expect("Src-id should exist."),
+        dst_idx: nodes.idx_from(5).expect("Dst-id should exist."),
+        graph: &graph,
+        routing_cfg: &routing_cfg,
+        edge_bias: None,
+        path_hint: dijkstra::PathHint::Auto,
+    });
+    assert!(
+        result.is_err(),
+        "CHDijkstra should refuse a graph without ch-levels."
+    );
 }
 
 #[test]
@@ -55,7 +79,7 @@ fn dijkstra_on_map() {
     )
 }
 
-fn expected_paths(
+pub fn expected_paths(
     parsing_cfg: &configs::parsing::Config,
 ) -> Vec<(
     TestNode,
@@ -88,7 +112,7 @@ fn expected_paths(
         idx: NodeIdx(idx),
         id: idx as i64,
         coord: Coordinate::zero(),
-        ch_level: 0,
+        ch_level: None,
     })
     .collect();
 
@@ -0,0 +1,47 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    configs,
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::time::Duration;
+
+#[test]
+fn zero_threshold() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    let nodes = graph.nodes();
+    let query = dijkstra::Query {
+        src_idx: nodes.idx_from(1).expect("Src-id should exist."),
+        dst_idx: nodes.idx_from(3).expect("Dst-id should exist."),
+        graph: &graph,
+        routing_cfg: &routing_cfg,
+        edge_bias: None,
+        path_hint: dijkstra::PathHint::Auto,
+    };
+
+    // no threshold, no record
+
+    let mut dijkstra = Dijkstra::new();
+    assert_eq!(dijkstra.slow_query_threshold(), None);
+    dijkstra.compute_best_path(query);
+    assert!(dijkstra.last_slow_query().is_none());
+
+    // every query takes at least zero seconds
+
+    dijkstra.set_slow_query_threshold(Some(Duration::from_secs(0)));
+    dijkstra.compute_best_path(query);
+    let slow_query = dijkstra
+        .last_slow_query()
+        .expect("Query should be recorded as slow.");
+    assert_eq!(slow_query.src_id, 1);
+    assert_eq!(slow_query.dst_id, 3);
+    assert_eq!(slow_query.settled_count, dijkstra.settled_count());
+    assert!(slow_query.settled_count > 0);
+    assert_eq!(slow_query.alphas, routing_cfg.alphas);
+    assert!(!slow_query.is_ch_dijkstra);
+}
@@ -0,0 +1,42 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{configs, routing::dijkstra::Dijkstra};
+
+const G_ID: i64 = 6;
+const H_ID: i64 = 7;
+
+#[test]
+fn unreached_nodes() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    let nodes = graph.nodes();
+    let (g_idx, h_idx) = (
+        nodes.idx_from(G_ID).expect("Node-id should exist."),
+        nodes.idx_from(H_ID).expect("Node-id should exist."),
+    );
+
+    // g has no incoming edges
+    let sp_tree = Dijkstra::new().shortest_path_tree(h_idx, &graph, &routing_cfg);
+    assert_eq!(sp_tree.reached_count(), nodes.count() - 1);
+    assert_eq!(sp_tree.edges().count(), nodes.count() - 2);
+    assert!(!sp_tree.is_reached(g_idx));
+    assert_eq!(sp_tree.cost(g_idx), None);
+    assert_eq!(sp_tree.parent_edge(g_idx), None);
+    assert!(sp_tree.path_to(g_idx, &graph).is_none());
+
+    // the src-node is reached without parent
+    assert_eq!(sp_tree.cost(h_idx), Some(0.0));
+    assert_eq!(sp_tree.parent_edge(h_idx), None);
+
+    // isochrones as well as trees need plain Dijkstra
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'CHDijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    assert!(Dijkstra::new()
+        .try_shortest_path_tree(h_idx, &graph, &routing_cfg)
+        .is_err());
+}
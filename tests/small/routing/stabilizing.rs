@@ -0,0 +1,126 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    configs,
+    network::{Graph, MetricIdx},
+    routing::{
+        dijkstra::{self, Dijkstra},
+        stabilizing::{EdgeBias, RouteMemory},
+    },
+};
+
+const SRC_ID: i64 = 0;
+const DST_ID: i64 = 3;
+/// Added to the workload of every used edge after every iteration
+const WORKLOAD_INCREMENT: f64 = 0.25;
+const BONUS: f64 = 0.75;
+
+/// Routes like the balancer does and returns the id of the via-node of every iteration.
+fn via_ids(num_iter: usize, bonus: Option<f64>) -> Vec<i64> {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::TIE_FMI_YAML);
+    let mut graph: Graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_yaml(resources::TIE_FMI_YAML, graph.cfg());
+    let workload_idx: MetricIdx = graph.cfg().edges.metrics.idx_of("workload");
+
+    let mut dijkstra = Dijkstra::new();
+    let mut memory = RouteMemory::new();
+    let mut via_ids = Vec::with_capacity(num_iter);
+    for _ in 0..num_iter {
+        let mut edge_bias = bonus.map(|bonus| EdgeBias::new(bonus, &graph));
+        let path = {
+            let nodes = graph.nodes();
+            let bias = match edge_bias.as_mut() {
+                Some(edge_bias) => edge_bias.of(SRC_ID, DST_ID, &memory, &graph),
+                None => None,
+            };
+            dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx: nodes.idx_from(SRC_ID).expect("Src-id should exist."),
+                    dst_idx: nodes.idx_from(DST_ID).expect("Dst-id should exist."),
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                    edge_bias: bias,
+                    path_hint: dijkstra::PathHint::Auto,
+                })
+                .expect("Path from src to dst should exist.")
+                .flatten(&graph)
+        };
+        let node_idxs = path.node_indices(&graph);
+        assert_eq!(node_idxs.len(), 3, "Path should pass one via-node.");
+        via_ids.push(graph.nodes().id(node_idxs[1]));
+
+        // update the workload and remember the path for the next iteration
+
+        memory = RouteMemory::new();
+        memory.remember(&path, &graph);
+        let mut metrics = graph.metrics_mut();
+        let mut workloads = metrics.column_mut(workload_idx);
+        for &edge_idx in &path {
+            workloads[*edge_idx] += WORKLOAD_INCREMENT;
+        }
+    }
+    via_ids
+}
+
+#[test]
+fn routes_flap_without_stabilization() {
+    let via_ids = via_ids(3, None);
+    assert_ne!(
+        via_ids[0], via_ids[1],
+        "Without stabilization, the route should switch to the unused tie."
+    );
+}
+
+#[test]
+fn routes_are_kept_with_stabilization() {
+    let via_ids = via_ids(3, Some(BONUS));
+    assert!(
+        via_ids.iter().all(|&via_id| via_id == via_ids[0]),
+        "With stabilization, the route should be kept, but the via-nodes are {:?}.",
+        via_ids
+    );
+}
+
+#[test]
+fn bias_is_reset_between_route_pairs() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::TIE_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let routing_cfg = configs::routing::Config::from_yaml(resources::TIE_FMI_YAML, graph.cfg());
+    let nodes = graph.nodes();
+
+    let path = Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(SRC_ID).expect("Src-id should exist."),
+            dst_idx: nodes.idx_from(DST_ID).expect("Dst-id should exist."),
+            graph: &graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .expect("Path from src to dst should exist.");
+    let mut memory = RouteMemory::new();
+    memory.remember(&path, &graph);
+    assert_eq!(memory.len(), 1);
+
+    let mut edge_bias = EdgeBias::new(BONUS, &graph);
+    let bias = edge_bias
+        .of(SRC_ID, DST_ID, &memory, &graph)
+        .expect("Route-pair should be remembered.")
+        .to_vec();
+    for edge_idx in graph.fwd_edges().iter() {
+        let expected = if path.iter().any(|&idx| idx == edge_idx) {
+            -BONUS
+        } else {
+            0.0
+        };
+        assert_eq!(bias[*edge_idx], expected);
+    }
+
+    // unknown route-pairs have no bias, and the previous one is reset
+
+    assert!(edge_bias.of(DST_ID, SRC_ID, &memory, &graph).is_none());
+    let bias = edge_bias
+        .of(SRC_ID, DST_ID, &memory, &graph)
+        .expect("Route-pair should be remembered.");
+    assert_eq!(bias.iter().filter(|&&value| value != 0.0).count(), 2);
+}
@@ -0,0 +1,110 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{configs, io, network::Graph};
+use std::{
+    fs,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+fn routing_cfg(route_pairs_file: &Path, graph: &Graph) -> configs::routing::Config {
+    configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ route-pairs-file: '{}', algorithm: 'Dijkstra', \
+             metrics: [{{ id: 'kilometers' }}] }}",
+            route_pairs_file.display()
+        ),
+        graph.cfg(),
+    )
+}
+
+#[test]
+fn streamed_like_parsed() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    for route_pairs_file in &[
+        resources::ALL_ROUTE_PAIRS,
+        resources::FRACTIONAL_ROUTE_PAIRS,
+    ] {
+        let routing_cfg = routing_cfg(Path::new(route_pairs_file), &graph);
+        let parsed: Vec<_> = io::routing::Parser::parse(&routing_cfg)
+            .expect("Parsing should work.")
+            .into_iter()
+            .map(|(route_pair, count)| (route_pair.src, route_pair.dst, count))
+            .collect();
+
+        let stream = io::routing::Parser::stream(&routing_cfg).expect("Streaming should work.");
+        assert_eq!(stream.total_count(), parsed.len());
+        let streamed = stream
+            .map(|route_pair| {
+                route_pair.map(|(route_pair, count)| (route_pair.src, route_pair.dst, count))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Streaming should work.");
+        assert_eq!(
+            streamed, parsed,
+            "Streamed route-pairs of {} should equal the parsed ones.",
+            route_pairs_file
+        );
+    }
+}
+
+#[test]
+fn streaming_is_lazy() {
+    const COUNT: usize = 100_000;
+
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let node_count = graph.nodes().count() as i64;
+
+    // The last route-line is broken, which can only be noticed by reading the whole file.
+
+    let route_pairs_file = temp_file("lazy.route-pairs");
+    {
+        let mut writer = BufWriter::new(
+            fs::File::create(&route_pairs_file).expect("Route-pairs-file should be writable."),
+        );
+        writeln!(writer, "# route-count\n{}\n", COUNT).expect("Writing should work.");
+        for i in 0..(COUNT as i64 - 1) {
+            writeln!(writer, "{} {} 1", i % node_count, (i / 7) % node_count)
+                .expect("Writing should work.");
+        }
+        writeln!(writer, "0 broken-dst-id 1").expect("Writing should work.");
+    }
+    let routing_cfg = routing_cfg(&route_pairs_file, &graph);
+
+    assert!(io::routing::Parser::parse(&routing_cfg).is_err());
+
+    let mut stream = io::routing::Parser::stream(&routing_cfg).expect("Streaming should work.");
+    assert_eq!(stream.total_count(), COUNT);
+    assert_eq!(stream.size_hint(), (0, Some(COUNT)));
+    let first_pairs: Vec<_> = stream
+        .by_ref()
+        .take(10)
+        .collect::<Result<_, _>>()
+        .expect("The first route-pairs should be streamed before the broken one is read.");
+    assert_eq!(first_pairs.len(), 10);
+    assert_eq!(stream.size_hint(), (0, Some(COUNT - 10)));
+
+    // only the broken line fails, counted without collecting
+    let (num_of_ok, num_of_err) = stream.fold(
+        (first_pairs.len(), 0),
+        |(num_of_ok, num_of_err), route_pair| match route_pair {
+            Ok(_) => (num_of_ok + 1, num_of_err),
+            Err(_) => (num_of_ok, num_of_err + 1),
+        },
+    );
+    assert_eq!((num_of_ok, num_of_err), (COUNT - 1, 1));
+
+    // coordinates need snapping, hence they can't be streamed
+
+    {
+        let mut file =
+            fs::File::create(&route_pairs_file).expect("Route-pairs-file should be writable.");
+        writeln!(
+            file,
+            "# route-pairs-format: coordinates\n# route-count\n1\n48.0 9.0 48.1 9.1 1"
+        )
+        .expect("Writing should work.");
+    }
+    assert!(io::routing::Parser::stream(&routing_cfg).is_err());
+}
@@ -0,0 +1,96 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::Graph,
+    routing::time_window::{self, Arrival},
+};
+
+const A_ID: i64 = 0;
+const B_ID: i64 = 1;
+const C_ID: i64 = 2;
+
+fn earliest_arrival(graph: &Graph, departure: f64, is_waiting_allowed: bool) -> Option<Arrival> {
+    let nodes = graph.nodes();
+    let metrics_cfg = &graph.cfg().edges.metrics;
+    time_window::earliest_arrival(time_window::Query {
+        src_idx: nodes.idx_from(A_ID).expect("Src-id should exist."),
+        dst_idx: nodes.idx_from(C_ID).expect("Dst-id should exist."),
+        graph,
+        duration_idx: metrics_cfg.idx_of(defaults::DURATION_ID),
+        open_from_idx: metrics_cfg.idx_of("open-from"),
+        open_until_idx: metrics_cfg.idx_of("open-until"),
+        departure,
+        is_waiting_allowed,
+    })
+}
+
+fn assert_arrival(
+    graph: &Graph,
+    departure: f64,
+    is_waiting_allowed: bool,
+    expected_time: f64,
+    expected_node_ids: &[i64],
+) {
+    let arrival = earliest_arrival(graph, departure, is_waiting_allowed)
+        .expect("A path from a to c should exist.");
+    assert!(
+        Approx(arrival.time) == Approx(expected_time),
+        "Departing at {} (waiting: {}) should arrive at {}, but arrives at {}.",
+        departure,
+        is_waiting_allowed,
+        expected_time,
+        arrival.time
+    );
+
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node_ids: Vec<i64> = std::iter::once(A_ID)
+        .chain(
+            arrival
+                .path
+                .iter()
+                .map(|edge_idx| nodes.id(fwd_edges.dst_idx(*edge_idx))),
+        )
+        .collect();
+    assert_eq!(node_ids, expected_node_ids);
+}
+
+#[test]
+fn direct_road_opening_later() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::TIME_WINDOWS_FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // a -> c is open in [2, 4] and takes 0.5 hours
+    // a -> b -> c is always open and takes 3 hours
+    assert_arrival(&graph, 0.0, true, 2.5, &[A_ID, C_ID]);
+    assert_arrival(&graph, 0.0, false, 3.0, &[A_ID, B_ID, C_ID]);
+    assert_arrival(&graph, 2.0, false, 2.5, &[A_ID, C_ID]);
+    assert_arrival(&graph, 5.0, true, 8.0, &[A_ID, B_ID, C_ID]);
+    // every road is closed
+    assert!(earliest_arrival(&graph, 25.0, true).is_none());
+}
+
+#[test]
+fn src_equals_dst() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::TIME_WINDOWS_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let metrics_cfg = &graph.cfg().edges.metrics;
+
+    let b_idx = nodes.idx_from(B_ID).expect("Node-id should exist.");
+    let arrival = time_window::earliest_arrival(time_window::Query {
+        src_idx: b_idx,
+        dst_idx: b_idx,
+        graph: &graph,
+        duration_idx: metrics_cfg.idx_of(defaults::DURATION_ID),
+        open_from_idx: metrics_cfg.idx_of("open-from"),
+        open_until_idx: metrics_cfg.idx_of("open-until"),
+        departure: 1.0,
+        is_waiting_allowed: false,
+    })
+    .expect("Empty path should exist.");
+    assert!(Approx(arrival.time) == Approx(1.0));
+    assert_eq!(arrival.path.iter().count(), 0);
+}
@@ -0,0 +1,116 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::Graph,
+    routing::{paths::Path, yen::KShortestPaths},
+};
+
+const C_ID: i64 = 2;
+const G_ID: i64 = 6;
+const H_ID: i64 = 7;
+
+fn routing_cfg(graph: &Graph, algorithm: &str) -> configs::routing::Config {
+    configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: '{}', metrics: [{{ id: 'kilometers' }}] }}",
+            algorithm
+        ),
+        graph.cfg(),
+    )
+}
+
+/// Returns the ids of the visited nodes, beginning with src.
+fn node_ids(path: &Path, graph: &Graph) -> Vec<i64> {
+    let nodes = graph.nodes();
+    path.node_indices(graph)
+        .into_iter()
+        .map(|idx| nodes.id(idx))
+        .collect()
+}
+
+fn compute(src_id: i64, dst_id: i64, k: usize, graph: &Graph) -> Vec<Path> {
+    let routing_cfg = routing_cfg(graph, "Dijkstra");
+    let nodes = graph.nodes();
+    KShortestPaths::new().compute(
+        nodes.idx_from(src_id).expect("Src-id should exist."),
+        nodes.idx_from(dst_id).expect("Dst-id should exist."),
+        k,
+        graph,
+        &routing_cfg,
+    )
+}
+
+#[test]
+fn best_paths_in_order() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+
+    let paths = compute(H_ID, C_ID, 3, &graph);
+    assert_eq!(
+        paths
+            .iter()
+            .map(|path| node_ids(path, &graph))
+            .collect::<Vec<_>>(),
+        vec![vec![7, 3, 1, 2], vec![7, 2], vec![7, 5, 4, 3, 1, 2]]
+    );
+    assert_eq!(
+        paths
+            .iter()
+            .map(|path| Approx(path.length(km_idx)))
+            .collect::<Vec<_>>(),
+        vec![Approx(0.003), Approx(0.004), Approx(0.006)]
+    );
+}
+
+#[test]
+fn fewer_paths_than_k() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let km_idx = graph.cfg().edges.metrics.idx_of("kilometers");
+
+    // duplicated edges lead to paths visiting the same nodes, but every path is loop-free
+    let paths = compute(H_ID, C_ID, 100, &graph);
+    assert!(paths.len() >= 3 && paths.len() < 100);
+    for (i, path) in paths.iter().enumerate() {
+        let mut ids = node_ids(path, &graph);
+        ids.sort();
+        ids.dedup();
+        assert_eq!(
+            ids.len(),
+            path.iter().count() + 1,
+            "Path {} has loops.",
+            path
+        );
+        assert!(paths[..i].iter().all(|prev_path| prev_path != path));
+        assert!(paths[..i]
+            .iter()
+            .all(|prev_path| prev_path.length(km_idx) <= path.length(km_idx)));
+    }
+
+    // g is not reachable at all
+    assert!(compute(H_ID, G_ID, 3, &graph).is_empty());
+    assert!(compute(H_ID, C_ID, 0, &graph).is_empty());
+
+    // a node reaches itself only by the empty path
+    let paths = compute(H_ID, H_ID, 3, &graph);
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].iter().count(), 0);
+    assert_eq!(Approx(paths[0].length(km_idx)), Approx(0.0));
+}
+
+#[test]
+fn ch_dijkstra_is_rejected() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let routing_cfg = routing_cfg(&graph, "CHDijkstra");
+    let nodes = graph.nodes();
+
+    let result = KShortestPaths::new().try_compute(
+        nodes.idx_from(H_ID).expect("Src-id should exist."),
+        nodes.idx_from(C_ID).expect("Dst-id should exist."),
+        3,
+        &graph,
+        &routing_cfg,
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,328 @@
+use crate::helpers::{defaults::paths::resources::small as resources, temp_file, TempFile};
+use fs2::FileExt;
+use osmgraphing::{
+    balancing::{timing::Phase, BalancingObserver, Runner},
+    defaults::balancing as balancing_defaults,
+    helpers::err,
+    network::{Graph, RoutePair},
+    routing::paths::Path,
+};
+use std::{
+    fs,
+    path::{self, PathBuf},
+    thread,
+    time::Duration,
+};
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    IterationStart(usize),
+    PairRouted {
+        src_id: i64,
+        dst_id: i64,
+        num_of_paths: usize,
+        chosen: Vec<(usize, f64)>,
+    },
+    WorkloadComputed(usize),
+    IterationEnd(usize),
+}
+
+#[derive(Default)]
+struct RecordingObserver {
+    calls: Vec<Call>,
+    total_workload: f64,
+}
+
+impl BalancingObserver for RecordingObserver {
+    fn on_iteration_start(&mut self, iter: usize) {
+        self.calls.push(Call::IterationStart(iter));
+    }
+
+    fn on_pair_routed(
+        &mut self,
+        route_pair: &RoutePair<i64>,
+        paths: &[Path],
+        chosen: &[(usize, f64)],
+    ) {
+        self.calls.push(Call::PairRouted {
+            src_id: route_pair.src,
+            dst_id: route_pair.dst,
+            num_of_paths: paths.len(),
+            chosen: chosen.to_vec(),
+        });
+    }
+
+    fn on_workload_computed(&mut self, abs_workloads: &mut [f64]) {
+        self.total_workload = abs_workloads.iter().sum();
+        self.calls.push(Call::WorkloadComputed(abs_workloads.len()));
+    }
+
+    fn on_iteration_end(&mut self, graph: &Graph) {
+        self.calls
+            .push(Call::IterationEnd(graph.fwd_edges().count()));
+    }
+}
+
+struct ZeroingObserver;
+
+impl BalancingObserver for ZeroingObserver {
+    fn on_workload_computed(&mut self, abs_workloads: &mut [f64]) {
+        for workload in abs_workloads.iter_mut() {
+            *workload = 0.0;
+        }
+    }
+}
+
+/// Fails the given iteration after writing its graph and routing-config, but before writing its
+/// stats, like a crash between writing two files of the iteration.
+struct FailingObserver {
+    iter: usize,
+}
+
+impl BalancingObserver for FailingObserver {
+    fn on_iteration_prepared(&mut self, iter: usize) -> err::Feedback {
+        if iter == self.iter {
+            Err(err::Msg::from(format!(
+                "Injected failure in iteration {}.",
+                iter
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Writes the runner's config with its own results-dir, so tests don't share their results.
+/// The returned tmp-dir is removed, when it is dropped.
+fn runner_cfg(name: &str) -> (PathBuf, TempFile) {
+    let tmp_dir = temp_file(&format!("runner-{}", name));
+    fs::create_dir_all(&tmp_dir).expect("Tmp-dir should be creatable.");
+
+    let raw_cfg = fs::read_to_string(resources::BALANCING_RUNNER_YAML)
+        .expect("Runner-config should be readable.")
+        .replace(
+            "results-dir: 'custom/results/small/runner'",
+            &format!("results-dir: '{}'", tmp_dir.join("results").display()),
+        );
+    let cfg_file = tmp_dir.join("runner.yaml");
+    fs::write(&cfg_file, raw_cfg).expect("Runner-config should be writable.");
+
+    (cfg_file, tmp_dir)
+}
+
+#[test]
+fn observed_calls() {
+    let (cfg_file, _tmp_dir) = runner_cfg("observed-calls");
+    let mut runner = Runner::new(&cfg_file)
+        .with_contraction(false)
+        .with_observer(RecordingObserver::default());
+    let graph = runner.run().expect("Balancing should work.");
+    let observer = runner.into_observer();
+
+    let num_of_edges = graph.fwd_edges().count();
+    // Dijkstra chooses its only path with the route-pair's count
+    let routed = |src_id, dst_id, count| Call::PairRouted {
+        src_id,
+        dst_id,
+        num_of_paths: 1,
+        chosen: vec![(0, count)],
+    };
+    assert_eq!(
+        observer.calls,
+        vec![
+            Call::IterationStart(0),
+            routed(3, 7, 2.7),
+            routed(6, 0, 0.5),
+            routed(5, 0, 1.0),
+            routed(2, 1, 0.25),
+            Call::WorkloadComputed(num_of_edges),
+            Call::IterationEnd(num_of_edges),
+        ]
+    );
+    assert!(
+        observer.total_workload > 0.0,
+        "Routing the route-pairs should lead to workload."
+    );
+}
+
+#[test]
+fn mutated_workloads() {
+    let (cfg_file, _tmp_dir) = runner_cfg("mutated-workloads");
+    let graph = Runner::new(&cfg_file)
+        .with_contraction(false)
+        .with_observer(ZeroingObserver)
+        .run()
+        .expect("Balancing should work.");
+
+    let workload_idx = graph.cfg().edges.metrics.idx_of("workload");
+    let metrics = graph.metrics();
+    let workloads = metrics.column(workload_idx);
+    assert!(workloads.len() > 0);
+    assert!(
+        workloads.iter().all(|&workload| workload == 0.0),
+        "The zeroed workloads should be the new workload-metric, but it is {:?}",
+        workloads
+    );
+}
+
+/// The runs' dirs in the results-dir, sorted by their creation (by name)
+fn list_run_dirs(tmp_dir: &path::Path) -> Vec<PathBuf> {
+    let mut run_dirs: Vec<PathBuf> = fs::read_dir(tmp_dir.join("results"))
+        .expect("Results-dir should be readable.")
+        .map(|entry| entry.expect("Results-dir should be readable.").path())
+        .filter(|path| path.is_dir())
+        .collect();
+    run_dirs.sort();
+    run_dirs
+}
+
+#[test]
+fn crash_safe_iterations() {
+    let (cfg_file, tmp_dir) = runner_cfg("crash-safe-iterations");
+
+    // crash between writing two files of iteration 0
+
+    let result = Runner::new(&cfg_file)
+        .with_contraction(false)
+        .with_observer(FailingObserver { iter: 0 })
+        .run();
+    assert!(result.is_err(), "The injected failure should fail the run.");
+
+    let run_dirs = list_run_dirs(&tmp_dir);
+    assert_eq!(run_dirs.len(), 1);
+    let crashed_dir = run_dirs[0].clone();
+    assert!(
+        !crashed_dir.join("0").exists(),
+        "The incomplete iteration shouldn't look like a completed one."
+    );
+    assert!(crashed_dir.join("0.tmp").is_dir());
+    assert!(crashed_dir.join("0.tmp").join("iteration.yaml").is_file());
+    assert!(
+        !crashed_dir.join("run.lock").exists(),
+        "The failed run shouldn't look like an active one."
+    );
+
+    // re-run removes the incomplete iteration
+    // (runs are stored by their starting second)
+
+    thread::sleep(Duration::from_millis(1_100));
+    Runner::new(&cfg_file)
+        .with_contraction(false)
+        .run()
+        .expect("Balancing should work.");
+
+    let run_dirs = list_run_dirs(&tmp_dir);
+    assert_eq!(run_dirs.len(), 2);
+    assert_eq!(run_dirs[0], crashed_dir);
+    assert!(!crashed_dir.join("0.tmp").exists());
+    assert!(!crashed_dir.join("0").exists());
+    assert!(run_dirs[1].join("0").join("iteration.yaml").is_file());
+    assert!(!run_dirs[1].join("0.tmp").exists());
+}
+
+#[test]
+fn active_runs_are_kept() {
+    let (cfg_file, tmp_dir) = runner_cfg("active-runs");
+
+    // a run, which is writing its iteration 0 and holds its lock
+    let active_dir = tmp_dir.join("results").join("utc_active");
+    fs::create_dir_all(active_dir.join("0.tmp")).expect("Active run-dir should be creatable.");
+    fs::write(active_dir.join("run.lock"), "").expect("Lock-file should be writable.");
+    let active_lock = fs::OpenOptions::new()
+        .write(true)
+        .open(active_dir.join("run.lock"))
+        .expect("Lock-file should be openable.");
+    active_lock
+        .try_lock_exclusive()
+        .expect("Lock-file should be lockable.");
+
+    // a crashed run, whose lock-file has been left behind with a (reused) process-id
+    let stale_dir = tmp_dir.join("results").join("utc_stale");
+    fs::create_dir_all(stale_dir.join("0.tmp")).expect("Stale run-dir should be creatable.");
+    fs::write(
+        stale_dir.join("run.lock"),
+        format!("{}\n", std::process::id()),
+    )
+    .expect("Lock-file should be writable.");
+
+    Runner::new(&cfg_file)
+        .with_contraction(false)
+        .run()
+        .expect("Balancing should work.");
+    drop(active_lock);
+
+    assert!(
+        active_dir.join("0.tmp").is_dir(),
+        "The iteration of an active run shouldn't be removed."
+    );
+    assert!(
+        !stale_dir.join("0.tmp").exists(),
+        "The iteration of a run with an unlocked lock-file should be removed."
+    );
+    let run_dirs = list_run_dirs(&tmp_dir);
+    assert_eq!(run_dirs.len(), 3);
+    let new_dir = run_dirs
+        .iter()
+        .find(|&run_dir| run_dir != &active_dir && run_dir != &stale_dir)
+        .expect("The new run should have its own dir.");
+    assert!(
+        !new_dir.join("run.lock").exists(),
+        "The finished run should have removed its lock-file."
+    );
+}
+
+#[test]
+fn timing_per_phase() {
+    let (cfg_file, tmp_dir) = runner_cfg("timing-per-phase");
+    Runner::new(&cfg_file)
+        .with_contraction(false)
+        .run()
+        .expect("Balancing should work.");
+
+    let run_dirs = list_run_dirs(&tmp_dir);
+    assert_eq!(run_dirs.len(), 1);
+    let content = fs::read_to_string(run_dirs[0].join(balancing_defaults::files::TIMING))
+        .expect("Timing should be readable.");
+
+    // one row per phase and iteration
+
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some("iteration phase phase_us iteration_us"));
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(' ').collect()).collect();
+    // the runner's config has 1 metric-update, hence 2 iterations
+    let num_iter = 2;
+    assert_eq!(rows.len(), num_iter * Phase::all().len());
+    for (iter, iter_rows) in rows.chunks(Phase::all().len()).enumerate() {
+        for (row, phase) in iter_rows.iter().zip(Phase::all().iter()) {
+            assert_eq!(row.len(), 4, "Row {:?} should have 4 columns.", row);
+            assert_eq!(row[0], iter.to_string());
+            assert_eq!(row[1], phase.name());
+        }
+
+        // phases are measured without overlap, so they can't take longer than their iteration
+
+        let phase_us: Vec<i128> = iter_rows
+            .iter()
+            .map(|row| {
+                row[2]
+                    .parse::<i128>()
+                    .expect("Phase-time should be a number.")
+            })
+            .collect();
+        assert!(
+            phase_us.iter().all(|&us| us >= 0),
+            "Phase-times {:?} shouldn't be negative.",
+            phase_us
+        );
+        let iteration_us: i128 = iter_rows[0][3]
+            .parse()
+            .expect("Iteration-time should be a number.");
+        assert!(iter_rows.iter().all(|row| row[3] == iter_rows[0][3]));
+        assert!(
+            phase_us.iter().sum::<i128>() <= iteration_us,
+            "Phases ({:?} µs) shouldn't take longer than their iteration ({} µs).",
+            phase_us,
+            iteration_us
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::NodeIdx,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+#[test]
+fn neighborhood_of_d() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+
+    // d and its neighbors b, e and h
+    let neighborhood: Vec<NodeIdx> = vec![3, 1, 4, 7]
+        .into_iter()
+        .map(|id| nodes.idx_from(id).expect("Node-id should exist."))
+        .collect();
+    let (subgraph, mapping) = graph.subgraph(&neighborhood);
+    let subnodes = subgraph.nodes();
+
+    // ids are sorted like in the original graph
+    assert_eq!(subnodes.count(), 4);
+    let subnode_ids: Vec<i64> = subnodes.iter().map(|idx| subnodes.id(idx)).collect();
+    assert_eq!(subnode_ids, vec![1, 3, 4, 7]);
+    for new_idx in subnodes.iter() {
+        let old_idx = mapping.to_old(new_idx);
+        assert_eq!(mapping.to_new(old_idx), Some(new_idx));
+        assert_eq!(subnodes.id(new_idx), nodes.id(old_idx));
+        assert!(Approx(subnodes.coord(new_idx)) == Approx(nodes.coord(old_idx)));
+    }
+
+    // d -> b, d -> e, d -> h, e -> d, h -> d
+    assert_eq!(subgraph.fwd_edges().count(), 5);
+    assert_eq!(subgraph.bwd_edges().count(), 5);
+    let sub_bwd_edges = subgraph.bwd_edges();
+    let d_idx = subnodes.idx_from(3).expect("Node-id should exist.");
+    let mut entering_d: Vec<i64> = sub_bwd_edges
+        .starting_from(d_idx)
+        .map(|half_edge| subnodes.id(half_edge.dst_idx()))
+        .collect();
+    entering_d.sort();
+    assert_eq!(entering_d, vec![4, 7]);
+
+    // routing inside the subgraph
+    // e -> h uses the detour via d, because f isn't in the subgraph
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'Dijkstra', metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        subgraph.cfg(),
+    );
+    let expected_paths: Vec<(i64, i64, f64, Vec<i64>)> = vec![
+        (3, 1, 1.0, vec![3, 1]),
+        (3, 4, 2.0, vec![3, 4]),
+        (3, 7, 1.0, vec![3, 7]),
+        (4, 1, 3.0, vec![4, 3, 1]),
+        (7, 1, 2.0, vec![7, 3, 1]),
+        (4, 7, 3.0, vec![4, 3, 7]),
+    ];
+    let metric_idx = subgraph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let mut dijkstra = Dijkstra::new();
+    for (src_id, dst_id, expected_cost, expected_ids) in expected_paths {
+        let path = dijkstra
+            .compute_best_path(dijkstra::Query {
+                src_idx: subnodes.idx_from(src_id).expect("Src-id should exist."),
+                dst_idx: subnodes.idx_from(dst_id).expect("Dst-id should exist."),
+                graph: &subgraph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            })
+            .expect(&format!("Path from {} to {} should exist.", src_id, dst_id));
+
+        // translate back to the original graph
+        let ids: Vec<i64> = path
+            .node_indices(&subgraph)
+            .into_iter()
+            .map(|new_idx| nodes.id(mapping.to_old(new_idx)))
+            .collect();
+        assert_eq!(ids, expected_ids);
+        assert!(
+            Approx(path.costs()[*metric_idx]) == Approx(expected_cost),
+            "Path from {} to {} should cost {}, but costs {}.",
+            src_id,
+            dst_id,
+            expected_cost,
+            path.costs()[*metric_idx]
+        );
+    }
+
+    // b has no leaving edges in the subgraph
+    let b_idx = subnodes.idx_from(1).expect("Node-id should exist.");
+    assert!(dijkstra
+        .compute_best_path(dijkstra::Query {
+            src_idx: b_idx,
+            dst_idx: d_idx,
+            graph: &subgraph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .is_none());
+}
+
+#[test]
+fn out_of_bounds() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    assert!(graph.try_subgraph(&[NodeIdx(0), NodeIdx(8)]).is_err());
+}
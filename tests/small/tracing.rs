@@ -0,0 +1,67 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::configs;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tracing_lib::{span, Event, Metadata, Subscriber};
+
+/// Remembers the names of all created spans in their order of creation.
+struct SpanRecorder {
+    names: Arc<Mutex<Vec<&'static str>>>,
+    next_id: AtomicU64,
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes) -> span::Id {
+        self.names
+            .lock()
+            .expect("Recording spans shouldn't panic.")
+            .push(span.metadata().name());
+        // ids have to be non-zero
+        span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, _event: &Event) {}
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[test]
+fn parsing_emits_spans_in_order() {
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let recorder = SpanRecorder {
+        names: Arc::clone(&names),
+        next_id: AtomicU64::new(1),
+    };
+
+    let graph = tracing_lib::subscriber::with_default(recorder, || {
+        parse(configs::parsing::Config::from_yaml(resources::FMI_YAML))
+    });
+    assert!(graph.nodes().count() > 0);
+
+    let names = names.lock().expect("Recording spans shouldn't panic.");
+    assert_eq!(
+        *names,
+        vec![
+            "parsing",
+            "fmi::ways",
+            "fmi::nodes",
+            "building",
+            "building::metrics",
+            "building::offsets",
+            "building::bwd_offsets",
+        ]
+    );
+}
@@ -0,0 +1,1001 @@
+use crate::helpers::{
+    assert_graph_sloppy, defaults, graph_writing_cfg, parse, temp_file, write_graph, TempFile,
+};
+use defaults::paths::resources::small as resources;
+use kissunits::{distance::Kilometers, geo};
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    helpers::err,
+    io,
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra},
+};
+use quick_xml::{events::Event, Reader};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[test]
+fn pbf_round_trip() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // write graph as pbf
+
+    let map_file = temp_file("round_trip.osm.pbf");
+    write_graph(&graph, &graph_writing_cfg(&map_file, &[], &[]));
+
+    // re-parse written pbf
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let new_graph = parse(parsing_cfg);
+
+    assert_graph_sloppy(graph.nodes().count(), graph.fwd_edges().count(), &new_graph);
+
+    // compare maxspeeds and derived durations
+
+    let nodes = graph.nodes();
+    let bwd_edges = graph.bwd_edges();
+    let kmph_idx = graph.cfg().edges.metrics.idx_of("kmph");
+    let new_nodes = new_graph.nodes();
+    let new_fwd_edges = new_graph.fwd_edges();
+    let new_kmph_idx = new_graph.cfg().edges.metrics.idx_of("kmph");
+    let new_hours_idx = new_graph.cfg().edges.metrics.idx_of("hours");
+
+    for edge_idx in graph.fwd_edges().iter() {
+        let src_idx = bwd_edges.dst_idx(edge_idx);
+        let dst_idx = graph.fwd_edges().dst_idx(edge_idx);
+        let new_src_idx = new_nodes
+            .idx_from(nodes.id(src_idx))
+            .expect("Written node should be re-parsed.");
+        let new_dst_idx = new_nodes
+            .idx_from(nodes.id(dst_idx))
+            .expect("Written node should be re-parsed.");
+        let new_edge = new_fwd_edges
+            .between(new_src_idx, new_dst_idx)
+            .expect("Written edge should be re-parsed.");
+
+        let kmph = graph.metrics().column(kmph_idx)[*edge_idx];
+        assert!(
+            Approx(new_edge.metrics()[*new_kmph_idx]) == Approx(kmph),
+            "Re-parsed maxspeed {} of edge (id:{}->id:{}) should be {}.",
+            new_edge.metrics()[*new_kmph_idx],
+            nodes.id(src_idx),
+            nodes.id(dst_idx),
+            kmph
+        );
+
+        let hours =
+            *geo::haversine_distance_km(&nodes.coord(src_idx), &nodes.coord(dst_idx)) / kmph;
+        assert!(
+            Approx(new_edge.metrics()[*new_hours_idx]) == Approx(hours),
+            "Re-parsed duration {} of edge (id:{}->id:{}) should be {}.",
+            new_edge.metrics()[*new_hours_idx],
+            nodes.id(src_idx),
+            nodes.id(dst_idx),
+            hours
+        );
+    }
+}
+
+#[test]
+fn pbf_way_ids_of_edges_without_id() {
+    // the reversed edge of the bidirectional edge has no id
+    let map_file = temp_file("mixed_ids.fmi");
+    fs::write(
+        &map_file,
+        "3\n2\n0 48.0 9.0\n1 48.0 9.01\n2 48.0 9.02\n0 0 1 1000 30 1\n1 1 2 1000 30 0\n",
+    )
+    .expect("Map-file should be writable.");
+    let parsing_cfg = configs::parsing::Config::from_str(&format!(
+        "parsing:
+  map-file: '{}'
+  vehicles: {{ category: 'Car', are_drivers_picky: false }}
+  nodes:
+  - meta: {{ info: 'NodeId', id: 'node-id' }}
+  - metric: {{ unit: 'Latitude', id: 'latitude' }}
+  - metric: {{ unit: 'Longitude', id: 'longitude' }}
+  edges:
+    data:
+    - meta: {{ info: 'EdgeId', id: 'edge-id' }}
+    - meta: {{ info: 'SrcId', id: 'src-id' }}
+    - meta: {{ info: 'DstId', id: 'dst-id' }}
+    - metric: {{ unit: 'Meters', id: 'meters' }}
+    - metric: {{ unit: 'KilometersPerHour', id: 'kmph' }}
+    - meta: {{ info: 'Bidirectional', id: 'bidirectional' }}",
+        map_file.display()
+    ));
+    let graph = parse(parsing_cfg);
+    assert_eq!(graph.fwd_edges().count(), 3);
+
+    let pbf_file = temp_file("mixed_ids.osm.pbf");
+    write_graph(&graph, &graph_writing_cfg(&pbf_file, &[], &[]));
+
+    let mut reader = osmpbfreader::OsmPbfReader::new(
+        fs::File::open(&pbf_file).expect("Written pbf-file should be readable."),
+    );
+    let mut way_ids: Vec<i64> = reader
+        .iter()
+        .filter_map(
+            |obj| match obj.expect("Written pbf-file should be decodable.") {
+                osmpbfreader::OsmObj::Way(way) => Some(way.id.0),
+                _ => None,
+            },
+        )
+        .collect();
+    way_ids.sort_unstable();
+    assert_eq!(
+        &way_ids[..2],
+        &[0, 1],
+        "Edges with id should be written with their id."
+    );
+    assert_eq!(way_ids.len(), 3);
+    assert!(
+        way_ids[2] > 1,
+        "The edge without id should get a way-id above all edge-ids, but got {}.",
+        way_ids[2]
+    );
+}
+
+#[test]
+fn fmi_with_embedded_cfg() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let map_file = write_fmi(
+        "embedded_cfg.fmi",
+        &graph,
+        &["node-id", "latitude", "longitude"],
+        configs::writing::network::Precision::default(),
+    );
+
+    // matching config
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::WRITTEN_FMI_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let new_graph = parse(parsing_cfg);
+    assert_graph_sloppy(graph.nodes().count(), graph.fwd_edges().count(), &new_graph);
+
+    // reordered config
+
+    let mut parsing_cfg =
+        configs::parsing::Config::from_yaml(resources::WRITTEN_REORDERED_FMI_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg.clone());
+
+    // ignoring embedded config
+
+    parsing_cfg.is_ignoring_embedded_cfg = true;
+    let ignoring_result = io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+
+    match result {
+        Ok(_) => panic!("Parsing with reordered edge-columns should fail."),
+        Err(msg) => {
+            let msg = format!("{}", msg);
+            assert!(
+                msg.contains("kilometers (Kilometers)\", \"kmph (KilometersPerHour)"),
+                "Error-msg should name the written order, but is: {}",
+                msg
+            );
+            assert!(
+                msg.contains("kmph (KilometersPerHour)\", \"kilometers (Kilometers)"),
+                "Error-msg should name the expected order, but is: {}",
+                msg
+            );
+        }
+    }
+    assert!(
+        ignoring_result.is_ok(),
+        "Parsing with ignored embedded config should work."
+    );
+}
+
+#[test]
+fn fmi_with_escaped_ids_in_embedded_cfg() {
+    // quotes and combining characters have to be escaped as json, not as rust-strings
+    let escaped_id = "latitude \"e\u{301}\"";
+    let renamed = |yaml_file: &str| {
+        fs::read_to_string(yaml_file)
+            .expect("Parsing-config should be readable.")
+            .replace("id: 'latitude'", &format!("id: '{}'", escaped_id))
+    };
+    let graph = parse(configs::parsing::Config::from_str(&renamed(
+        resources::FMI_YAML,
+    )));
+
+    let map_file = write_fmi(
+        "escaped_ids.fmi",
+        &graph,
+        &["node-id", escaped_id, "longitude"],
+        configs::writing::network::Precision::default(),
+    );
+
+    let mut parsing_cfg = configs::parsing::Config::from_str(&renamed(resources::WRITTEN_FMI_YAML));
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+    match result {
+        Ok(new_graph) => {
+            assert_graph_sloppy(graph.nodes().count(), graph.fwd_edges().count(), &new_graph)
+        }
+        Err(msg) => panic!("The embedded config should be readable, but: {}", msg),
+    }
+}
+
+#[test]
+fn fmi_with_custom_node_values() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::ZONES_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let zone_idx = graph.cfg().nodes.custom_idx_of("zone-id");
+    let expected_zones = vec![10.0, 10.0, 10.0, 20.0, 20.0, 20.0, 30.0, 30.0];
+
+    let nodes = graph.nodes();
+    for node_idx in &nodes {
+        assert_eq!(
+            nodes.custom(node_idx, zone_idx),
+            expected_zones[*node_idx],
+            "Node {} has wrong zone-id.",
+            nodes.id(node_idx)
+        );
+    }
+
+    // round-trip
+
+    let map_file = write_fmi(
+        "custom_node_values.fmi",
+        &graph,
+        &["node-id", "latitude", "longitude", "zone-id"],
+        configs::writing::network::Precision::default(),
+    );
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::WRITTEN_FMI_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    parsing_cfg
+        .nodes
+        .categories
+        .push(configs::parsing::nodes::Category::Metric {
+            unit: configs::parsing::nodes::metrics::UnitInfo::F64,
+            id: configs::SimpleId::from("zone-id"),
+        });
+    let new_graph = parse(parsing_cfg);
+
+    assert_graph_sloppy(graph.nodes().count(), graph.fwd_edges().count(), &new_graph);
+    let new_nodes = new_graph.nodes();
+    let new_zone_idx = new_graph.cfg().nodes.custom_idx_of("zone-id");
+    for node_idx in &nodes {
+        assert_eq!(
+            nodes.id(node_idx),
+            new_nodes.id(node_idx),
+            "Round-trip should keep node-order."
+        );
+        assert_eq!(
+            nodes.custom(node_idx, zone_idx),
+            new_nodes.custom(node_idx, new_zone_idx),
+            "Node {} has lost its zone-id.",
+            nodes.id(node_idx)
+        );
+    }
+}
+
+#[test]
+fn fmi_with_precision() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let precision = configs::writing::network::Precision {
+        coordinates: Some(7),
+        metrics: Some(3),
+    };
+    let map_file = write_fmi(
+        "precision.fmi",
+        &graph,
+        &["node-id", "latitude", "longitude"],
+        precision,
+    );
+
+    // re-parse written fmi
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::WRITTEN_FMI_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let new_graph = parse(parsing_cfg);
+
+    assert_graph_sloppy(graph.nodes().count(), graph.fwd_edges().count(), &new_graph);
+
+    // values should be rounded to the given digits, but not further
+
+    let nodes = graph.nodes();
+    let new_nodes = new_graph.nodes();
+    for node_idx in &nodes {
+        let coord = nodes.coord(node_idx);
+        let new_coord = new_nodes.coord(node_idx);
+        for &(value, new_value) in &[(coord.lat, new_coord.lat), (coord.lon, new_coord.lon)] {
+            assert!(
+                (value - new_value).abs() <= 0.5e-7 + 1e-12,
+                "Re-parsed coordinate {} of node {} should be {} with 7 digits.",
+                new_value,
+                nodes.id(node_idx),
+                value
+            );
+        }
+    }
+
+    let bwd_edges = graph.bwd_edges();
+    let new_fwd_edges = new_graph.fwd_edges();
+    for metric_id in &["kilometers", "kmph"] {
+        let metric_idx = graph.cfg().edges.metrics.idx_of(metric_id);
+        let new_metric_idx = new_graph.cfg().edges.metrics.idx_of(metric_id);
+
+        for edge_idx in graph.fwd_edges().iter() {
+            let src_idx = bwd_edges.dst_idx(edge_idx);
+            let dst_idx = graph.fwd_edges().dst_idx(edge_idx);
+            let new_edge = new_fwd_edges
+                .between(
+                    new_nodes.idx_from(nodes.id(src_idx)).unwrap(),
+                    new_nodes.idx_from(nodes.id(dst_idx)).unwrap(),
+                )
+                .expect("Written edge should be re-parsed.");
+
+            let value = graph.metrics().column(metric_idx)[*edge_idx];
+            let new_value = new_edge.metrics()[*new_metric_idx];
+            assert!(
+                (value - new_value).abs() <= 0.5e-3 + 1e-12,
+                "Re-parsed {} {} of edge (id:{}->id:{}) should be {} with 3 digits.",
+                metric_id,
+                new_value,
+                nodes.id(src_idx),
+                nodes.id(dst_idx),
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn stable_ids_of_fmi_round_trip() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    // rounded coordinates shouldn't change stable ids
+    let precision = configs::writing::network::Precision {
+        coordinates: Some(7),
+        metrics: Some(3),
+    };
+    let map_file = write_fmi(
+        "stable_ids.fmi",
+        &graph,
+        &["node-id", "latitude", "longitude"],
+        precision,
+    );
+
+    // re-parse written fmi
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::WRITTEN_FMI_YAML);
+    parsing_cfg.map_files = vec![map_file.to_path_buf()];
+    let new_graph = parse(parsing_cfg);
+
+    // distinct edges have distinct stable ids
+
+    let stable_id_index = graph.stable_id_index();
+    let new_stable_id_index = new_graph.stable_id_index();
+    assert_eq!(
+        stable_id_index.len(),
+        graph.fwd_edges().count(),
+        "Every edge should have its own stable id."
+    );
+    assert_eq!(
+        new_stable_id_index.len(),
+        new_graph.fwd_edges().count(),
+        "Every re-parsed edge should have its own stable id."
+    );
+
+    // stable ids refer to the same edges
+
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let new_nodes = new_graph.nodes();
+    let new_fwd_edges = new_graph.fwd_edges();
+    let new_bwd_edges = new_graph.bwd_edges();
+    let stable_ids = graph.stable_ids();
+    for edge_idx in fwd_edges.iter() {
+        let stable_id = stable_ids[*edge_idx];
+        assert_eq!(stable_id_index[&stable_id], edge_idx);
+
+        let new_edge_idx = *new_stable_id_index.get(&stable_id).expect(&format!(
+            "Stable id {} of edge (id:{}->id:{}) should be re-parsed.",
+            stable_id,
+            nodes.id(bwd_edges.dst_idx(edge_idx)),
+            nodes.id(fwd_edges.dst_idx(edge_idx))
+        ));
+        assert_eq!(
+            (
+                nodes.id(bwd_edges.dst_idx(edge_idx)),
+                nodes.id(fwd_edges.dst_idx(edge_idx))
+            ),
+            (
+                new_nodes.id(new_bwd_edges.dst_idx(new_edge_idx)),
+                new_nodes.id(new_fwd_edges.dst_idx(new_edge_idx))
+            ),
+            "Stable id {} should refer to the same edge after re-parsing.",
+            stable_id
+        );
+    }
+}
+
+#[test]
+fn edges_info_with_stable_ids() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    parsing_cfg
+        .generating
+        .as_mut()
+        .expect("Fmi-config should generate metrics.")
+        .edges
+        .categories
+        .push(configs::parsing::generating::edges::Category::Meta {
+            info: configs::parsing::generating::edges::MetaInfo::StableId,
+            id: configs::SimpleId::from("stable-id"),
+        });
+    let graph = parse(parsing_cfg);
+
+    // write edges-info
+
+    let file = temp_file("stable_ids.csv");
+    let writing_cfg = configs::writing::network::edges::Config {
+        file: file.to_path_buf(),
+        is_writing_shortcuts: false,
+        is_writing_header: true,
+        is_denormalizing: false,
+        is_writing_provenance: false,
+        ids: vec!["stable-id", "kmph"]
+            .into_iter()
+            .map(|id| Some(configs::SimpleId::from(id)))
+            .collect(),
+        precision: configs::writing::network::Precision::default(),
+    };
+    if let Err(msg) = io::network::edges::Writer::write(&graph, &writing_cfg) {
+        panic!("Could not write {}. ERROR: {}", file.display(), msg);
+    }
+    let content = fs::read_to_string(&file).expect("Written edges-info should be readable.");
+
+    // every row starts with the edge's stable id
+
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some("stable-id kmph"));
+    let stable_ids: Vec<u64> = lines
+        .map(|line| {
+            line.split_whitespace()
+                .next()
+                .expect("Row should have a stable id.")
+                .parse()
+                .expect("Stable id should be an u64.")
+        })
+        .collect();
+    assert_eq!(stable_ids, graph.stable_ids().to_vec());
+}
+
+#[test]
+fn stable_ids_independent_of_edge_order() {
+    // Both parallel edges collide, even with their metrics, since their speeds only differ
+    // below the accuracy.
+    let parallel_edges = ["0 1 1 30.0000001", "0 1 1 30.0000002"];
+
+    let mut stable_ids_by_kmph = Vec::new();
+    for (i, edges) in [parallel_edges, [parallel_edges[1], parallel_edges[0]]]
+        .iter()
+        .enumerate()
+    {
+        let map_file = temp_file(&format!("parallel_{}.fmi", i));
+        fs::write(
+            &map_file,
+            format!("2\n3\n0 0 0\n1 0 0\n{}\n{}\n1 0 1 30\n", edges[0], edges[1]),
+        )
+        .expect("Map-file should be writable.");
+        let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+        parsing_cfg.map_files = vec![map_file.to_path_buf()];
+        let graph = parse(parsing_cfg);
+
+        let kmph_idx = graph.cfg().edges.metrics.idx_of("kmph");
+        let kmphs = graph.metrics().column(kmph_idx);
+        let stable_ids = graph.stable_ids();
+        assert_eq!(
+            graph.stable_id_index().len(),
+            graph.fwd_edges().count(),
+            "Every edge should have its own stable id."
+        );
+        let mut by_kmph: Vec<(u64, u64)> = graph
+            .fwd_edges()
+            .iter()
+            .map(|edge_idx| (kmphs[*edge_idx].to_bits(), stable_ids[*edge_idx]))
+            .collect();
+        by_kmph.sort_unstable();
+        stable_ids_by_kmph.push(by_kmph);
+    }
+    assert_eq!(
+        stable_ids_by_kmph[0], stable_ids_by_kmph[1],
+        "Stable ids should be independent of the order of parallel edges."
+    );
+}
+
+#[test]
+fn osm_overlay_of_workloads() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::BALANCING_FREEFLOW_YAML);
+    let graph = parse(parsing_cfg);
+    let fwd_edges = graph.fwd_edges();
+
+    // synthetic workloads
+
+    let workloads: Vec<f64> = fwd_edges.iter().map(|idx| *idx as f64 + 0.5).collect();
+
+    let file = temp_file("overlay.osm");
+    let writing_cfg = configs::writing::network::edges::Config {
+        file: file.to_path_buf(),
+        is_writing_shortcuts: false,
+        is_writing_header: false,
+        is_denormalizing: true,
+        is_writing_provenance: false,
+        ids: vec![],
+        precision: configs::writing::network::Precision::default(),
+    };
+    if let Err(msg) = io::osm::Writer::write(&workloads, &graph, &writing_cfg) {
+        panic!("Could not write {}. ERROR: {}", file.display(), msg);
+    }
+    let content = fs::read_to_string(&file).expect("Written overlay should be readable.");
+
+    // parse xml back and collect every way's id and tags
+
+    let mut reader = Reader::from_str(&content);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut ways: Vec<(i64, Vec<(String, String)>)> = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                let attributes: Vec<(String, String)> = e
+                    .attributes()
+                    .map(|attribute| {
+                        let attribute = attribute.expect("Attribute should be valid xml.");
+                        (
+                            String::from_utf8_lossy(attribute.key).into_owned(),
+                            String::from_utf8_lossy(
+                                &attribute
+                                    .unescaped_value()
+                                    .expect("Attribute should be escaped properly."),
+                            )
+                            .into_owned(),
+                        )
+                    })
+                    .collect();
+                let value_of = |key: &str| {
+                    attributes
+                        .iter()
+                        .find(|(k, _)| k == key)
+                        .map(|(_, v)| v.clone())
+                        .expect("Attribute should exist.")
+                };
+                match e.name() {
+                    b"way" => ways.push((
+                        value_of("id").parse().expect("Way-id should be an i64."),
+                        Vec::new(),
+                    )),
+                    b"tag" => ways
+                        .last_mut()
+                        .expect("Tags should belong to a way.")
+                        .1
+                        .push((value_of("k"), value_of("v"))),
+                    _ => (),
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => (),
+            Err(e) => panic!("Overlay is no valid xml: {}", e),
+        }
+        buf.clear();
+    }
+
+    // one way per edge with synthetic, negative ids
+
+    assert_eq!(ways.len(), fwd_edges.count());
+    assert!(ways.iter().all(|(id, _)| *id < 0));
+
+    let (id, tags) = &ways[3];
+    assert_eq!(*id, -4);
+    let tag = |key: &str| tags.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    assert_eq!(tag("osmgraphing:workload"), Some("3.5"));
+    assert_eq!(tag("osmgraphing:lane_count"), Some("2"));
+    assert!(tag("osmgraphing:distance").is_some());
+}
+
+/// Writes the graph into a temporary fmi-file with the given (unique) name.
+fn write_fmi(
+    name: &str,
+    graph: &Graph,
+    node_ids: &[&str],
+    precision: configs::writing::network::Precision,
+) -> TempFile {
+    let map_file = temp_file(name);
+    let mut writing_cfg = graph_writing_cfg(
+        &map_file,
+        node_ids,
+        &["src-id", "dst-id", "kilometers", "kmph"],
+    );
+    writing_cfg.edges.precision = precision;
+    write_graph(graph, &writing_cfg);
+    map_file
+}
+
+#[test]
+fn validation_refuses_unknown_ids() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let map_file = temp_file("unknown_ids.fmi");
+
+    let writing_cfg = graph_writing_cfg(&map_file, &["node-id"], &["src-id", "dst-id"]);
+    assert!(writing_cfg.validate(&parsing_cfg).is_ok());
+
+    let writing_cfg = graph_writing_cfg(&map_file, &["not-a-node-id"], &["src-id", "dst-id"]);
+    assert!(writing_cfg.validate(&parsing_cfg).is_err());
+
+    let writing_cfg = graph_writing_cfg(&map_file, &["node-id"], &["src-id", "not-a-metric"]);
+    assert!(writing_cfg.validate(&parsing_cfg).is_err());
+    assert!(writing_cfg.edges.validate_ids(&parsing_cfg).is_err());
+
+    // provenances have to be tracked when parsing
+    let mut writing_cfg = graph_writing_cfg(&map_file, &[], &["src-id", "dst-id"]);
+    writing_cfg.edges.is_writing_provenance = true;
+    assert!(writing_cfg.edges.validate_ids(&parsing_cfg).is_err());
+
+    assert!(
+        !map_file.exists(),
+        "Validation shouldn't leave any file behind."
+    );
+}
+
+#[test]
+fn validation_refuses_unsupported_ext() {
+    let map_file = temp_file("unsupported.xyz");
+    let writing_cfg = graph_writing_cfg(&map_file, &[], &[]);
+    assert!(writing_cfg.validate_files().is_err());
+
+    let mut edges_cfg = writing_cfg.edges;
+    edges_cfg.file = map_file.with_extension("fmi");
+    assert!(
+        edges_cfg.validate_files().is_err(),
+        "Edges-info should only be written as csv."
+    );
+}
+
+#[test]
+fn validation_refuses_existing_file() {
+    let map_file = temp_file("existing.fmi");
+    fs::write(&map_file, "").expect("Temporary file should be writable.");
+
+    let writing_cfg = graph_writing_cfg(&map_file, &[], &[]);
+    assert!(writing_cfg.validate_files().is_err());
+
+    let routes_file = temp_file("existing.route-pairs");
+    fs::write(&routes_file, "").expect("Temporary file should be writable.");
+    let writing_cfg = configs::writing::routing::Config {
+        file: routes_file.to_path_buf(),
+        category: configs::writing::routing::Category::RandomOrAll {
+            seed: 42,
+            max_count: 10,
+        },
+    };
+    let result = writing_cfg.validate();
+    fs::remove_file(&routes_file).expect("Temporary file should be removable.");
+    assert!(result.is_err());
+    assert!(writing_cfg.validate().is_ok());
+}
+
+#[test]
+fn validation_refuses_unwritable_dir() {
+    let missing_dir = temp_file("missing_dir");
+    let writing_cfg = graph_writing_cfg(&missing_dir.join("graph.fmi"), &[], &[]);
+    assert!(writing_cfg.validate_files().is_err());
+
+    // a missing directory is fine, if it can be created
+    assert!(io::files::check_writable_dir(&missing_dir.join("results")).is_ok());
+
+    // a file can't be a directory
+    let file = temp_file("not_a_dir");
+    fs::write(&file, "").expect("Temporary file should be writable.");
+    assert!(io::files::check_writable_dir(&file.join("results")).is_err());
+}
+
+#[test]
+fn stratified_route_pairs_of_empty_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let (graph, _mapping) = parse(parsing_cfg).subgraph(&[]);
+    assert_eq!(graph.nodes().count(), 0);
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'Dijkstra', metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        graph.cfg(),
+    );
+
+    let file = temp_file("empty.route-pairs");
+    let writing_cfg = configs::writing::routing::Config {
+        file: file.to_path_buf(),
+        category: configs::writing::routing::Category::StratifiedByDistance {
+            seed: 42,
+            bands: vec![(Kilometers(5.0), 10)],
+        },
+    };
+    let result = io::routing::Writer::write(&graph, &routing_cfg, &writing_cfg);
+    assert!(
+        result.is_err(),
+        "Route-pairs shouldn't be sampled from a graph without nodes."
+    );
+}
+
+#[test]
+fn atomic_file_is_renamed_on_commit() {
+    let file = temp_file("atomic.csv");
+
+    let mut writer = io::files::AtomicFile::create(&file).expect("Tmp-file should be creatable.");
+    let tmp_file = writer.tmp_file().to_path_buf();
+    writeln!(writer, "a b").expect("Writing should work.");
+    assert!(tmp_file.exists());
+    assert!(!file.exists(), "File shouldn't exist before committing.");
+    writer.commit().expect("Committing should work.");
+
+    assert!(!tmp_file.exists());
+    let content = fs::read_to_string(&file).expect("Committed file should be readable.");
+    assert_eq!(content, "a b\n");
+}
+
+#[test]
+fn atomic_file_replaces_on_commit() {
+    let file = temp_file("atomic_replace.csv");
+    fs::write(&file, "old\n").expect("Old file should be writable.");
+    assert!(io::files::AtomicFile::create(&file).is_err());
+
+    let mut writer = io::files::AtomicFile::replace(&file).expect("Tmp-file should be creatable.");
+    writeln!(writer, "new").expect("Writing should work.");
+    let content = fs::read_to_string(&file).expect("Old file should be readable.");
+    assert_eq!(
+        content, "old\n",
+        "Old file should be kept before committing."
+    );
+    writer.commit().expect("Committing should work.");
+
+    let content = fs::read_to_string(&file).expect("Committed file should be readable.");
+    assert_eq!(content, "new\n");
+}
+
+#[test]
+fn atomic_file_is_removed_on_failure() {
+    let file = temp_file("atomic_failure.csv");
+
+    // fails mid-write like a writer hitting an error
+    let write = |file: &Path, is_failing: bool| -> err::Feedback {
+        let mut writer = io::files::AtomicFile::create(file)?;
+        for i in 0..1_000 {
+            writeln!(writer, "{} {}", i, i)?;
+            if is_failing && i == 500 {
+                return Err(err::Msg::from("Injected error"));
+            }
+        }
+        writer.commit()
+    };
+    assert!(write(&file, true).is_err());
+
+    assert!(!file.exists(), "Failing writers shouldn't leave a file.");
+    let tmp_file = PathBuf::from(format!("{}.tmp", file.display()));
+    assert!(
+        !tmp_file.exists(),
+        "Failing writers shouldn't leave a tmp-file."
+    );
+
+    // the same writer succeeds without failure
+    assert!(write(&file, false).is_ok());
+    let line_count = fs::read_to_string(&file)
+        .expect("Committed file should be readable.")
+        .lines()
+        .count();
+    assert_eq!(line_count, 1_000);
+}
+
+#[test]
+fn simple_fmi_round_trip() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    // write graph as simple fmi
+
+    let map_file = temp_file("simple.fmi");
+    let writing_cfg = configs::writing::network::simple_fmi::Config {
+        map_file: map_file.to_path_buf(),
+        metric_id: configs::SimpleId::from(defaults::DISTANCE_ID),
+        precision: osmgraphing::defaults::writing::simple_fmi::PRECISION,
+    };
+    writing_cfg
+        .validate(graph.cfg())
+        .expect("The graph has the weight's metric.");
+    io::network::simple_fmi::Writer::write(&graph, &writing_cfg)
+        .expect("Writing simple fmi should work.");
+
+    // only counts, nodes and edges
+
+    let content = fs::read_to_string(&map_file).expect("Written file should be readable.");
+    let lines: Vec<&str> = content.lines().collect();
+    let node_count = graph.nodes().count();
+    let edge_count = graph.fwd_edges().count();
+    assert_eq!(lines[0], format!("{}", node_count));
+    assert_eq!(lines[1], format!("{}", edge_count));
+    assert_eq!(lines.len(), 2 + node_count + edge_count);
+    assert!(lines[2..2 + node_count]
+        .iter()
+        .all(|line| line.split_whitespace().count() == 3));
+    assert!(lines[2 + node_count..]
+        .iter()
+        .all(|line| line.split_whitespace().count() == 3));
+
+    // re-parse with the matching profile
+
+    let parsing_cfg = configs::parsing::Config::from_str(&format!(
+        "parsing:\n  map-file: '{}'\n  profile: 'simple-fmi'",
+        map_file.display()
+    ));
+    let new_graph = parse(parsing_cfg);
+    assert_eq!(new_graph.nodes().count(), node_count);
+    assert_eq!(new_graph.fwd_edges().count(), edge_count);
+    assert_eq!(new_graph.cfg().edges.metrics.ids.len(), 1);
+
+    // node-ids are the old node-indices, and routing by weight equals routing by distance
+
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ algorithm: 'Dijkstra', metrics: [{{ id: '{}' }}] }}",
+            defaults::DISTANCE_ID
+        ),
+        graph.cfg(),
+    );
+    let new_routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'weight' }] }",
+        new_graph.cfg(),
+    );
+    let mut dijkstra = Dijkstra::new();
+    let new_nodes = new_graph.nodes();
+    for src_idx in &graph.nodes() {
+        for dst_idx in &graph.nodes() {
+            let path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx,
+                dst_idx,
+                graph: &graph,
+                routing_cfg: &routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            });
+            let new_path = dijkstra.compute_best_path(dijkstra::Query {
+                src_idx: new_nodes
+                    .idx_from(*src_idx as i64)
+                    .expect("Node-ids should be the old indices."),
+                dst_idx: new_nodes
+                    .idx_from(*dst_idx as i64)
+                    .expect("Node-ids should be the old indices."),
+                graph: &new_graph,
+                routing_cfg: &new_routing_cfg,
+                edge_bias: None,
+                path_hint: dijkstra::PathHint::Auto,
+            });
+            match (path, new_path) {
+                (Some(path), Some(new_path)) => {
+                    // weights are rounded per edge
+                    let tolerance = 1e-6 * (graph.fwd_edges().count() as f64);
+                    assert!(
+                        (path.costs()[0] - new_path.costs()[0]).abs() <= tolerance,
+                        "Path from {} to {} should cost {}, but costs {}.",
+                        src_idx,
+                        dst_idx,
+                        path.costs()[0],
+                        new_path.costs()[0]
+                    );
+                }
+                (None, None) => (),
+                _ => panic!(
+                    "Path from {} to {} should be found in both graphs or in none.",
+                    src_idx, dst_idx
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn simple_fmi_refuses_missing_metric() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let map_file = temp_file("simple_refused.fmi");
+    let writing_cfg = configs::writing::network::simple_fmi::Config {
+        map_file: map_file.to_path_buf(),
+        metric_id: configs::SimpleId::from("not-a-metric"),
+        precision: osmgraphing::defaults::writing::simple_fmi::PRECISION,
+    };
+    assert!(writing_cfg.validate(graph.cfg()).is_err());
+    assert!(io::network::simple_fmi::Writer::write(&graph, &writing_cfg).is_err());
+    assert!(!writing_cfg.map_file.exists());
+}
+
+#[test]
+fn geojson_features() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let file = temp_file("features.geojson");
+    let writing_cfg = configs::writing::network::geojson::Config {
+        file: file.to_path_buf(),
+        is_writing_shortcuts: false,
+        is_denormalizing: false,
+    };
+    writing_cfg
+        .validate_files()
+        .expect("The geojson-file should be new.");
+    io::network::geojson::Writer::write(&graph, &writing_cfg)
+        .expect("Writing geojson should work.");
+
+    let content = fs::read_to_string(&file).expect("Written file should be readable.");
+    let geojson: serde_json::Value =
+        serde_json::from_str(&content).expect("Written file should be valid json.");
+    assert_eq!(geojson["type"], "FeatureCollection");
+    let features = geojson["features"]
+        .as_array()
+        .expect("Features should be an array.");
+
+    // nodes first, then edges
+
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    assert_eq!(features.len(), nodes.count() + fwd_edges.count());
+    for (node_idx, feature) in nodes.iter().zip(features.iter()) {
+        let coord = nodes.coord(node_idx);
+        assert_eq!(feature["geometry"]["type"], "Point");
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            serde_json::json!([coord.lon, coord.lat])
+        );
+        assert_eq!(feature["properties"]["osm_id"], nodes.id(node_idx));
+        assert_eq!(feature["properties"]["node_idx"], *node_idx);
+    }
+
+    let metric_ids = &graph.cfg().edges.metrics.ids;
+    for (edge_idx, feature) in fwd_edges.iter().zip(features[nodes.count()..].iter()) {
+        let src_coord = nodes.coord(bwd_edges.dst_idx(edge_idx));
+        let dst_coord = nodes.coord(fwd_edges.dst_idx(edge_idx));
+        assert_eq!(feature["geometry"]["type"], "LineString");
+        assert_eq!(
+            feature["geometry"]["coordinates"],
+            serde_json::json!([
+                [src_coord.lon, src_coord.lat],
+                [dst_coord.lon, dst_coord.lat]
+            ])
+        );
+
+        let properties = feature["properties"]
+            .as_object()
+            .expect("Properties should be an object.");
+        assert_eq!(properties.len(), metric_ids.len());
+        for (metric_id, &value) in metric_ids.iter().zip(graph.metrics().get(edge_idx).iter()) {
+            assert!(
+                Approx(
+                    properties[&metric_id.0]
+                        .as_f64()
+                        .expect("Metrics should be numbers.")
+                ) == Approx(value),
+                "Edge {} should have {} of {}.",
+                edge_idx,
+                metric_id,
+                value
+            );
+        }
+    }
+
+    // wrong extension
+    let writing_cfg = configs::writing::network::geojson::Config {
+        file: temp_file("wrong_ext.fmi").to_path_buf(),
+        is_writing_shortcuts: false,
+        is_denormalizing: false,
+    };
+    assert!(writing_cfg.validate_files().is_err());
+}
@@ -0,0 +1,134 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::stacked_nodes as resources;
+use osmgraphing::{
+    configs,
+    network::Graph,
+    routing::dijkstra::{self, Dijkstra},
+};
+
+const WEST_ID: i64 = 0;
+const CENTER_ID: i64 = 1;
+const STACKED_CENTER_ID: i64 = 2;
+const EAST_ID: i64 = 3;
+const NORTH_ID: i64 = 4;
+
+fn has_route(graph: &Graph, src_id: i64, dst_id: i64) -> bool {
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+    let nodes = graph.nodes();
+    let src_idx = nodes.idx_from(src_id).expect("Src-id should exist.");
+    let dst_idx = nodes.idx_from(dst_id).expect("Dst-id should exist.");
+    Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .is_some()
+}
+
+#[test]
+fn fmi_yaml() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    assert!(parsing_cfg.merging_stacked_nodes.is_none());
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::MERGED_FMI_YAML);
+    match parsing_cfg.merging_stacked_nodes {
+        Some(stacked_nodes) => assert_eq!(*stacked_nodes.max_distance, 0.1),
+        None => panic!("Stacked nodes should be merged, if configured."),
+    }
+}
+
+#[test]
+fn split_intersection_without_merging() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    assert_eq!(graph.nodes().count(), 8);
+    assert!(has_route(&graph, WEST_ID, CENTER_ID));
+    assert!(
+        !has_route(&graph, WEST_ID, EAST_ID),
+        "The split intersection shouldn't be crossable without merging stacked nodes."
+    );
+}
+
+#[test]
+fn split_intersection_with_merging() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::MERGED_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+
+    // the stacked center is merged into the center with the smaller id
+    assert_eq!(nodes.count(), 7);
+    assert!(nodes.idx_from(CENTER_ID).is_ok());
+    assert!(nodes.idx_from(STACKED_CENTER_ID).is_err());
+    assert!(
+        has_route(&graph, WEST_ID, EAST_ID),
+        "The split intersection should be crossable after merging stacked nodes."
+    );
+    assert!(has_route(&graph, EAST_ID, WEST_ID));
+
+    // nearby nodes and connected stacked nodes are kept
+    assert!(!has_route(&graph, NORTH_ID, EAST_ID));
+    assert!(nodes.idx_from(6).is_ok());
+    assert!(nodes.idx_from(7).is_ok());
+}
+
+#[test]
+fn bridge_over_road_with_merging() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::UNMERGEABLE_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+
+    // stacked nodes inside streets are crossings of different levels
+    for id in 0..=5 {
+        assert!(nodes.idx_from(id).is_ok());
+    }
+    assert!(has_route(&graph, 0, 2));
+    assert!(has_route(&graph, 3, 5));
+    assert!(
+        !has_route(&graph, 0, 5),
+        "A bridge over a road shouldn't be merged into the road."
+    );
+}
+
+#[test]
+fn stacked_nodes_are_not_chained() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::UNMERGEABLE_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+
+    // 9 is within max-distance of 7, but 11 is only within max-distance of 9
+    assert!(nodes.idx_from(9).is_err());
+    assert!(nodes.idx_from(11).is_ok());
+    assert!(has_route(&graph, 6, 8));
+    assert!(
+        !has_route(&graph, 6, 10),
+        "Stacked nodes shouldn't be merged beyond max-distance via chaining."
+    );
+}
+
+#[test]
+fn stacked_nodes_dont_create_self_loops() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::UNMERGEABLE_FMI_YAML);
+    let graph = parse(parsing_cfg);
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+
+    // 14 is merged into 13, but its neighbour 15 isn't, although all of them are stacked
+    assert_eq!(nodes.count(), 14);
+    assert!(nodes.idx_from(14).is_err());
+    assert!(nodes.idx_from(15).is_ok());
+    assert!(has_route(&graph, 12, 15));
+    for node_idx in nodes.iter() {
+        assert!(
+            fwd_edges.between(node_idx, node_idx).is_none(),
+            "Merging stacked nodes shouldn't create self-loops."
+        );
+    }
+}
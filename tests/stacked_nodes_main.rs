@@ -0,0 +1,2 @@
+mod helpers;
+mod stacked_nodes;
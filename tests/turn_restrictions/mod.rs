@@ -0,0 +1,2 @@
+mod parsing;
+mod routing;
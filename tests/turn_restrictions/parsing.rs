@@ -0,0 +1,66 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::turn_restrictions as resources;
+use osmgraphing::{
+    configs,
+    network::{TurnRestriction, TurnRestrictionKind},
+};
+
+#[test]
+fn resolved_turns() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let edge = |src_id, dst_id| {
+        fwd_edges
+            .between(
+                nodes.idx_from(src_id).expect("Src-id should exist."),
+                nodes.idx_from(dst_id).expect("Dst-id should exist."),
+            )
+            .expect("Edge should exist.")
+            .idx()
+    };
+
+    // the exception for motorcars and the restriction via a way are skipped
+    assert!(graph.has_turn_restrictions());
+    let turn_restrictions: Vec<_> = graph
+        .turn_restrictions()
+        .map(|(node_ids, turn_restriction)| (node_ids, *turn_restriction))
+        .collect();
+    assert_eq!(
+        turn_restrictions,
+        vec![
+            (
+                (1, 2, 4),
+                TurnRestriction {
+                    from_way: 10,
+                    via_node: 2,
+                    to_way: 12,
+                    restriction: TurnRestrictionKind::NoLeftTurn,
+                }
+            ),
+            (
+                (4, 2, 5),
+                TurnRestriction {
+                    from_way: 12,
+                    via_node: 2,
+                    to_way: 13,
+                    restriction: TurnRestrictionKind::OnlyStraightOn,
+                }
+            ),
+        ]
+    );
+
+    assert!(graph.is_turn_forbidden(edge(1, 2), edge(2, 4)));
+    assert!(!graph.is_turn_forbidden(edge(1, 2), edge(2, 5)));
+    assert!(!graph.is_turn_forbidden(edge(1, 2), edge(2, 3)));
+    // only-restrictions are kept, but don't forbid their turn
+    assert_eq!(
+        graph
+            .turn_restriction(edge(4, 2), edge(2, 5))
+            .map(|turn_restriction| turn_restriction.restriction),
+        Some(TurnRestrictionKind::OnlyStraightOn)
+    );
+    assert!(!graph.is_turn_forbidden(edge(4, 2), edge(2, 5)));
+    // edges, which don't meet, can't be turned into
+    assert!(graph.turn_restriction(edge(1, 2), edge(3, 6)).is_none());
+}
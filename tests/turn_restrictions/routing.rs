@@ -0,0 +1,141 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::turn_restrictions as resources;
+use osmgraphing::{
+    configs,
+    network::{Graph, NodeIdx},
+    routing::{
+        dijkstra::{self, Dijkstra},
+        yen::KShortestPaths,
+    },
+};
+
+/// Returns the ids of the visited nodes (without the src-node) of the best paths of a
+/// bidirectional and a one-to-many query.
+fn visited_ids(src_id: i64, dst_id: i64, graph: &Graph) -> [Vec<i64>; 2] {
+    // both fixtures have the same metrics
+    let routing_cfg = configs::routing::Config::from_yaml(resources::OSM_PBF_YAML, graph.cfg());
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let src_idx = nodes.idx_from(src_id).expect("Src-id should exist.");
+    let dst_idx = nodes.idx_from(dst_id).expect("Dst-id should exist.");
+
+    let mut dijkstra = Dijkstra::new();
+    let best_path = dijkstra
+        .compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg: &routing_cfg,
+            edge_bias: None,
+            path_hint: dijkstra::PathHint::Auto,
+        })
+        .expect("Path should exist.");
+    let best_paths = dijkstra.compute_best_paths_from(src_idx, &[dst_idx], graph, &routing_cfg);
+
+    let ids = |path: &osmgraphing::routing::paths::Path| {
+        path.iter()
+            .map(|&edge_idx| nodes.id(fwd_edges.dst_idx(edge_idx)))
+            .collect()
+    };
+    [
+        ids(&best_path),
+        ids(best_paths[0].as_ref().expect("Path should exist.")),
+    ]
+}
+
+#[test]
+fn detour_around_forbidden_turn() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+
+    // turning left from 1 via 2 to 4 is forbidden
+    for ids in &visited_ids(1, 4, &graph) {
+        assert_eq!(ids, &vec![2, 3, 6, 4]);
+    }
+    // the opposite turn is allowed
+    for ids in &visited_ids(4, 1, &graph) {
+        assert_eq!(ids, &vec![2, 1]);
+    }
+    // turning right is allowed for motorcars
+    for ids in &visited_ids(1, 5, &graph) {
+        assert_eq!(ids, &vec![2, 5]);
+    }
+}
+
+/// Dijkstra labels nodes instead of edges, hence a via-node is settled only with its best
+/// predecessor, which is restricted here.
+/// The detours through the via-nodes are missed (see `Dijkstra::compute_best_path(...)`).
+#[test]
+#[ignore]
+fn detour_through_via_node() {
+    let graph = parse(configs::parsing::Config::from_yaml(
+        resources::DETOUR_OSM_PBF_YAML,
+    ));
+
+    // going straight on via 2 and via 8 is forbidden, but turning around behind them isn't
+    for ids in &visited_ids(1, 5, &graph) {
+        assert_eq!(ids, &vec![2, 3, 2, 4, 8, 9, 8, 5]);
+    }
+}
+
+#[test]
+fn k_shortest_paths_without_forbidden_turn() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    assert_k_shortest_paths_without_turn(1, 4, [1, 2, 4], &graph);
+}
+
+#[test]
+fn subgraph_with_forbidden_turn() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML));
+    let nodes = graph.nodes();
+
+    // without node 5, only the restriction of the turn (1, 2, 4) is kept
+    let node_idxs: Vec<NodeIdx> = [1, 2, 3, 4, 6]
+        .iter()
+        .map(|&id| nodes.idx_from(id).expect("Node-id should exist."))
+        .collect();
+    let (subgraph, _mapping) = graph.subgraph(&node_idxs);
+    let turns: Vec<_> = subgraph
+        .turn_restrictions()
+        .map(|(node_ids, _turn_restriction)| node_ids)
+        .collect();
+    assert_eq!(turns, vec![(1, 2, 4)]);
+
+    // The subgraph's edge-indices differ from the graph's ones, but the turn is still forbidden,
+    // even for Yen's spur-queries blocking edges by their indices.
+    assert_ne!(subgraph.fwd_edges().count(), graph.fwd_edges().count());
+    for ids in &visited_ids(1, 4, &subgraph) {
+        assert_eq!(ids, &vec![2, 3, 6, 4]);
+    }
+    assert_k_shortest_paths_without_turn(1, 4, [1, 2, 4], &subgraph);
+}
+
+/// Asserts that none of the k shortest paths from src to dst contains the turn given by its
+/// node-ids.
+fn assert_k_shortest_paths_without_turn(src_id: i64, dst_id: i64, turn: [i64; 3], graph: &Graph) {
+    let routing_cfg = configs::routing::Config::from_yaml(resources::OSM_PBF_YAML, graph.cfg());
+    let nodes = graph.nodes();
+
+    let paths = KShortestPaths::new().compute(
+        nodes.idx_from(src_id).expect("Src-id should exist."),
+        nodes.idx_from(dst_id).expect("Dst-id should exist."),
+        5,
+        graph,
+        &routing_cfg,
+    );
+    assert!(!paths.is_empty());
+    for path in &paths {
+        let ids: Vec<i64> = path
+            .node_indices(graph)
+            .into_iter()
+            .map(|idx| nodes.id(idx))
+            .collect();
+        assert!(
+            !ids.windows(3).any(|window| window == turn),
+            "Path {:?} turns from {} via {} to {}.",
+            ids,
+            turn[0],
+            turn[1],
+            turn[2]
+        );
+    }
+}
@@ -0,0 +1,2 @@
+mod helpers;
+mod turn_restrictions;
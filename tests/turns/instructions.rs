@@ -0,0 +1,130 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::turns as resources;
+use kissunits::geo;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, NodeIdx},
+    routing::{
+        instructions::{self, Maneuver},
+        paths::Path,
+    },
+};
+
+fn path_from(node_ids: &[i64], graph: &Graph) -> Path {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node_indices: Vec<NodeIdx> = node_ids
+        .iter()
+        .map(|&id| nodes.idx_from(id).expect("Node-id should exist."))
+        .collect();
+    let edges = node_indices
+        .windows(2)
+        .map(|pair| {
+            fwd_edges
+                .between(pair[0], pair[1])
+                .expect("Edge should exist.")
+                .idx()
+        })
+        .collect();
+
+    let src_idx = node_indices[0];
+    let dst_idx = node_indices[node_indices.len() - 1];
+    Path::new(
+        src_idx,
+        nodes.id(src_idx),
+        dst_idx,
+        nodes.id(dst_idx),
+        edges,
+    )
+}
+
+fn meters_between(src_id: i64, dst_id: i64, graph: &Graph) -> f64 {
+    let nodes = graph.nodes();
+    let src_coord = nodes.coord(nodes.idx_from(src_id).expect("Node-id should exist."));
+    let dst_coord = nodes.coord(nodes.idx_from(dst_id).expect("Node-id should exist."));
+    *geo::haversine_distance_km(&src_coord, &dst_coord) * 1_000.0
+}
+
+#[test]
+fn straight_route() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let path = path_from(&[0, 1, 2], &graph);
+    let instructions = instructions::generate(&path, &graph);
+
+    let maneuvers: Vec<Maneuver> = instructions.iter().map(|i| i.maneuver).collect();
+    assert_eq!(
+        maneuvers,
+        vec![Maneuver::Depart, Maneuver::Continue, Maneuver::Arrive]
+    );
+
+    let expected = meters_between(0, 1, &graph) + meters_between(1, 2, &graph);
+    assert!(
+        Approx(instructions[1].distance.0) == Approx(expected),
+        "Continuing should cover {} m, but covers {} m.",
+        expected,
+        instructions[1].distance.0
+    );
+}
+
+#[test]
+fn l_shaped_route() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let path = path_from(&[0, 1, 2, 3], &graph);
+    let instructions = instructions::generate(&path, &graph);
+
+    let maneuvers: Vec<Maneuver> = instructions.iter().map(|i| i.maneuver).collect();
+    assert_eq!(
+        maneuvers,
+        vec![
+            Maneuver::Depart,
+            Maneuver::Continue,
+            Maneuver::Left,
+            Maneuver::Arrive
+        ]
+    );
+
+    let expected = meters_between(0, 1, &graph) + meters_between(1, 2, &graph);
+    assert!(
+        Approx(instructions[1].distance.0) == Approx(expected),
+        "Continuing should cover {} m, but covers {} m.",
+        expected,
+        instructions[1].distance.0
+    );
+    let expected = meters_between(2, 3, &graph);
+    assert!(
+        Approx(instructions[2].distance.0) == Approx(expected),
+        "Turning left should cover {} m, but covers {} m.",
+        expected,
+        instructions[2].distance.0
+    );
+}
+
+#[test]
+fn bearings() {
+    let src = geo::Coordinate {
+        lat: 48.0,
+        lon: 9.0,
+    };
+    let north = geo::Coordinate {
+        lat: 48.1,
+        lon: 9.0,
+    };
+    let east = geo::Coordinate {
+        lat: 48.0,
+        lon: 9.1,
+    };
+
+    assert!(Approx(instructions::bearing(&src, &north)) == Approx(0.0));
+    assert!(Approx(instructions::bearing(&north, &src)) == Approx(180.0));
+    let bearing = instructions::bearing(&src, &east);
+    assert!(
+        89.9 < bearing && bearing < 90.0,
+        "Bearing eastwards should be slightly below 90°, but is {}.",
+        bearing
+    );
+}